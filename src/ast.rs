@@ -15,6 +15,52 @@ pub struct Program {
     pub top_level_functions: Vec<TopLevelFunction>,
     pub extern_declarations: Vec<ExternDecl>,  // FFI extern 声明
     pub type_aliases: Vec<TypeAliasDecl>,      // 类型别名声明 (type X = Y)
+    pub units: Vec<SourceUnit>,                // 各源文件的包和导入声明（多文件编译时有多个）
+}
+
+/// 包声明 - package a.b;
+#[derive(Debug, Clone)]
+pub struct PackageDecl {
+    pub name: String,
+    pub loc: SourceLocation,
+}
+
+/// 导入声明 - import a.b.Name; 或 import a.b.*;
+#[derive(Debug, Clone)]
+pub struct ImportDecl {
+    pub package: String,
+    /// 导入的类、接口或顶层函数名；`*` 导入整个包时为 None
+    pub name: Option<String>,
+    pub loc: SourceLocation,
+}
+
+impl ImportDecl {
+    /// 导入路径（`a.b.Name` 或 `a.b.*`）
+    pub fn path(&self) -> String {
+        format!("{}.{}", self.package, self.name.as_deref().unwrap_or("*"))
+    }
+}
+
+/// 一个源文件：包声明、导入和它声明的类、接口、顶层函数名
+///
+/// 多文件编译时每个输入文件对应一个单元，语义分析据此检查跨文件的可见性。
+#[derive(Debug, Clone, Default)]
+pub struct SourceUnit {
+    pub file: String,
+    /// 没有 package 声明的文件属于默认包，其中的声明对所有文件可见
+    pub package: Option<PackageDecl>,
+    pub imports: Vec<ImportDecl>,
+    /// 本文件声明的类、接口和顶层函数名，与它们在 [`Program`] 中的顺序一致
+    pub classes: Vec<String>,
+    pub interfaces: Vec<String>,
+    pub functions: Vec<String>,
+}
+
+impl SourceUnit {
+    /// 包名，默认包为 None
+    pub fn package_name(&self) -> Option<&str> {
+        self.package.as_ref().map(|p| p.name.as_str())
+    }
 }
 
 /// 类型别名声明 - type Name = Type;
@@ -68,7 +114,8 @@ pub enum CallingConvention {
 pub struct InterfaceDecl {
     pub name: String,
     pub modifiers: Vec<Modifier>,
-    pub methods: Vec<MethodDecl>,
+    pub parents: Vec<String>,  // 继承的父接口列表（interface A extends B, C）
+    pub methods: Vec<MethodDecl>,  // 带 Modifier::Default 的方法有默认实现
     pub loc: SourceLocation,
 }
 
@@ -147,6 +194,9 @@ pub enum Modifier {
     Native,
     Main,      // 标记主类，用于解决多main冲突
     Override,  // @Override 注解，标记方法重写
+    Test,      // @Test 注解，标记 `cavly test` 运行的测试方法
+    Default,   // 接口默认方法
+    Export,    // 库输出时以 C ABI 导出
 }
 
 #[derive(Debug, Clone)]
@@ -170,6 +220,7 @@ pub enum Stmt {
     Break(Option<String>),  // 可选的标签
     Continue(Option<String>),  // 可选的标签
     InlineIr(InlineIrStmt),  // 内联IR语句块
+    Error(SourceLocation),   // 语法错误恢复后留下的占位节点
 }
 
 /// 内联IR语句 - __ir { ... }
@@ -265,6 +316,7 @@ pub enum Expr {
     Lambda(LambdaExpr),        // Lambda 表达式: (params) -> { body }
     Ternary(TernaryExpr),      // 三元运算符: condition ? true_expr : false_expr
     InstanceOf(InstanceOfExpr), // instanceof 运算符: obj instanceof Type
+    SizeOf(SizeOfExpr),        // sizeof 运算符: sizeof(Type)
     Alloc(AllocExpr),          // 0.5.0.0: 内存分配表达式: __cay_alloc(size)
     Dealloc(DeallocExpr),      // 0.5.0.0: 内存释放表达式: __cay_free(ptr)
 }
@@ -292,6 +344,7 @@ impl HasLocation for Expr {
             Expr::Lambda(lambda) => &lambda.loc,
             Expr::Ternary(ternary) => &ternary.loc,
             Expr::InstanceOf(instance) => &instance.loc,
+            Expr::SizeOf(size_of) => &size_of.loc,
             Expr::Alloc(alloc) => &alloc.loc,
             Expr::Dealloc(dealloc) => &dealloc.loc,
         }
@@ -531,6 +584,13 @@ pub struct InstanceOfExpr {
     pub loc: SourceLocation,
 }
 
+/// sizeof 表达式: sizeof(Type)，值为类型在目标平台上的大小（long）
+#[derive(Debug, Clone)]
+pub struct SizeOfExpr {
+    pub target_type: crate::types::Type,
+    pub loc: SourceLocation,
+}
+
 impl Program {
     pub fn find_main_class(&self) -> Option<&ClassDecl> {
         self.classes.iter().find(|c| {
@@ -549,6 +609,218 @@ impl Program {
     }
 }
 
+impl Program {
+    /// 把另一个程序的全部声明追加到本程序之后（分离编译时合并依赖单元的接口）
+    pub fn append(&mut self, other: Program) {
+        self.classes.extend(other.classes);
+        self.interfaces.extend(other.interfaces);
+        self.top_level_functions.extend(other.top_level_functions);
+        self.extern_declarations.extend(other.extern_declarations);
+        self.type_aliases.extend(other.type_aliases);
+        self.units.extend(other.units);
+    }
+
+    /// 返回擦除了所有可空标记（T? -> T）的程序副本
+    ///
+    /// 可空性只在空安全检查中使用；之后的语义分析和代码生成阶段
+    /// 在擦除后的 AST 上工作，不需要区分 T 与 T?。
+    pub fn erase_nullability(&self) -> Program {
+        let mut program = self.clone();
+        for alias in &mut program.type_aliases {
+            erase_type(&mut alias.target_type);
+        }
+        for func in &mut program.top_level_functions {
+            erase_type(&mut func.return_type);
+            erase_params(&mut func.params);
+            erase_block(&mut func.body);
+        }
+        for ext in &mut program.extern_declarations {
+            for func in &mut ext.functions {
+                erase_type(&mut func.return_type);
+                erase_params(&mut func.params);
+            }
+        }
+        for interface in &mut program.interfaces {
+            for method in &mut interface.methods {
+                erase_method(method);
+            }
+        }
+        for class in &mut program.classes {
+            for member in &mut class.members {
+                match member {
+                    ClassMember::Method(method) => erase_method(method),
+                    ClassMember::Field(field) => {
+                        erase_type(&mut field.field_type);
+                        if let Some(init) = &mut field.initializer {
+                            erase_expr(init);
+                        }
+                    }
+                    ClassMember::Constructor(ctor) => {
+                        erase_params(&mut ctor.params);
+                        match &mut ctor.constructor_call {
+                            Some(ConstructorCall::This(args)) | Some(ConstructorCall::Super(args)) => {
+                                args.iter_mut().for_each(erase_expr);
+                            }
+                            None => {}
+                        }
+                        erase_block(&mut ctor.body);
+                    }
+                    ClassMember::Destructor(dtor) => erase_block(&mut dtor.body),
+                    ClassMember::InstanceInitializer(block) | ClassMember::StaticInitializer(block) => {
+                        erase_block(block)
+                    }
+                }
+            }
+        }
+        program
+    }
+}
+
+fn erase_type(ty: &mut Type) {
+    *ty = ty.erase_nullability();
+}
+
+fn erase_params(params: &mut [ParameterInfo]) {
+    for param in params {
+        erase_type(&mut param.param_type);
+    }
+}
+
+fn erase_method(method: &mut MethodDecl) {
+    erase_type(&mut method.return_type);
+    erase_params(&mut method.params);
+    if let Some(body) = &mut method.body {
+        erase_block(body);
+    }
+}
+
+fn erase_block(block: &mut Block) {
+    block.statements.iter_mut().for_each(erase_stmt);
+}
+
+fn erase_stmt(stmt: &mut Stmt) {
+    match stmt {
+        Stmt::Expr(expr) => erase_expr(expr),
+        Stmt::VarDecl(var) => {
+            erase_type(&mut var.var_type);
+            if let Some(init) = &mut var.initializer {
+                erase_expr(init);
+            }
+        }
+        Stmt::Return(value) => {
+            if let Some(value) = value {
+                erase_expr(value);
+            }
+        }
+        Stmt::If(if_stmt) => {
+            erase_expr(&mut if_stmt.condition);
+            erase_stmt(&mut if_stmt.then_branch);
+            if let Some(else_branch) = &mut if_stmt.else_branch {
+                erase_stmt(else_branch);
+            }
+        }
+        Stmt::While(while_stmt) => {
+            erase_expr(&mut while_stmt.condition);
+            erase_stmt(&mut while_stmt.body);
+        }
+        Stmt::For(for_stmt) => {
+            if let Some(init) = &mut for_stmt.init {
+                erase_stmt(init);
+            }
+            if let Some(cond) = &mut for_stmt.condition {
+                erase_expr(cond);
+            }
+            if let Some(update) = &mut for_stmt.update {
+                erase_expr(update);
+            }
+            erase_stmt(&mut for_stmt.body);
+        }
+        Stmt::DoWhile(do_while) => {
+            erase_stmt(&mut do_while.body);
+            erase_expr(&mut do_while.condition);
+        }
+        Stmt::Switch(switch) => {
+            erase_expr(&mut switch.expr);
+            for case in &mut switch.cases {
+                case.body.iter_mut().for_each(erase_stmt);
+            }
+            if let Some(default) = &mut switch.default {
+                default.iter_mut().for_each(erase_stmt);
+            }
+        }
+        Stmt::Block(block) => erase_block(block),
+        Stmt::Scope(scope) => erase_block(&mut scope.body),
+        Stmt::Break(_) | Stmt::Continue(_) | Stmt::InlineIr(_) | Stmt::Error(_) => {}
+    }
+}
+
+fn erase_expr(expr: &mut Expr) {
+    match expr {
+        Expr::Literal(_) | Expr::Identifier(_) => {}
+        Expr::Binary(bin) => {
+            erase_expr(&mut bin.left);
+            erase_expr(&mut bin.right);
+        }
+        Expr::Unary(unary) => erase_expr(&mut unary.operand),
+        Expr::Call(call) => {
+            erase_expr(&mut call.callee);
+            call.args.iter_mut().for_each(erase_expr);
+        }
+        Expr::MemberAccess(member) => erase_expr(&mut member.object),
+        Expr::New(new) => new.args.iter_mut().for_each(erase_expr),
+        Expr::Assignment(assign) => {
+            erase_expr(&mut assign.target);
+            erase_expr(&mut assign.value);
+        }
+        Expr::Cast(cast) => {
+            erase_type(&mut cast.target_type);
+            erase_expr(&mut cast.expr);
+        }
+        Expr::ArrayCreation(arr) => {
+            erase_type(&mut arr.element_type);
+            arr.sizes.iter_mut().for_each(erase_expr);
+        }
+        Expr::ArrayAccess(arr) => {
+            erase_expr(&mut arr.array);
+            erase_expr(&mut arr.index);
+        }
+        Expr::ArrayInit(arr) => arr.elements.iter_mut().for_each(erase_expr),
+        Expr::MethodRef(method_ref) => {
+            if let Some(object) = &mut method_ref.object {
+                erase_expr(object);
+            }
+        }
+        Expr::Lambda(lambda) => {
+            for param in &mut lambda.params {
+                if let Some(ty) = &mut param.param_type {
+                    erase_type(ty);
+                }
+            }
+            match &mut lambda.body {
+                LambdaBody::Expr(body) => erase_expr(body),
+                LambdaBody::Block(block) => erase_block(block),
+            }
+        }
+        Expr::Ternary(ternary) => {
+            erase_expr(&mut ternary.condition);
+            erase_expr(&mut ternary.true_branch);
+            erase_expr(&mut ternary.false_branch);
+        }
+        Expr::InstanceOf(instance) => {
+            erase_type(&mut instance.target_type);
+            erase_expr(&mut instance.expr);
+        }
+        Expr::SizeOf(size_of) => erase_type(&mut size_of.target_type),
+        Expr::Alloc(alloc) => {
+            erase_expr(&mut alloc.size);
+            if let Some(align) = &mut alloc.align {
+                erase_expr(align);
+            }
+        }
+        Expr::Dealloc(dealloc) => erase_expr(&mut dealloc.ptr),
+    }
+}
+
 impl Default for Program {
     fn default() -> Self {
         Self {
@@ -557,6 +829,7 @@ impl Default for Program {
             top_level_functions: Vec::new(),
             extern_declarations: Vec::new(),
             type_aliases: Vec::new(),
+            units: Vec::new(),
         }
     }
 }
@@ -17,26 +17,67 @@ fn print_usage() {
     println!("选项:");
     println!("  -v, --verbose     显示详细输出");
     println!("  -V, --version     显示版本号");
+    println!("  --locked          cavly.lock 需要更新时报错");
+    println!("  --offline         不访问网络，git 依赖只使用 ~/.cavly/git 中的缓存");
+    println!("  --registry <目录> 使用的包注册表（默认为 [registry] path 或 CAVLY_REGISTRY）");
+    println!("  --release         使用 release 配置档构建（build、run、test）");
+    println!("  --profile <名称>  使用 [profile.<名称>] 配置档构建（默认为 dev）");
+    println!("  --features <列表> 启用的特性，以逗号分隔");
+    println!("  --no-default-features  不启用 default 特性");
+    println!("  -p, --package <成员>   在工作区中构建指定成员及它依赖的成员（build、run、test、clean）");
+    println!("  --workspace       构建工作区的全部成员，互不依赖的成员并行构建（build、test、clean）");
     println!("  -h, --help        显示帮助信息");
     println!();
     println!("命令:");
     println!("  init [名称]       初始化新可执行项目");
     println!("  init --lib [名称] 初始化新库项目");
-    println!("  build             构建项目（自动处理依赖）");
+    println!("  new <名称>        用模板创建新项目（--template <模板>，默认为 cli）");
+    println!("  build             构建项目（自动处理依赖，更新 cavly.lock）");
     println!("  clean             清理构建产物");
     println!("  run               构建并运行项目");
-    println!("  info              显示项目信息");
-    println!("  add <库>          添加系统库依赖");
+    println!("  test              运行 src 和 tests/ 中的 @Test 方法（--filter <文本>、--junit <文件>）");
+    println!("  update [包名...]  重新解析依赖版本并更新 cavly.lock");
+    println!("  package           打包库项目到 target/package（--no-verify 跳过编译检查）");
+    println!("  publish           打包并发布库项目到注册表");
+    println!("  info              显示项目信息（工作区根目录显示成员依赖图）");
+    println!("  tree              显示解析后的依赖树（-i <包> 显示哪些包依赖它）");
+    println!("  why-link <库>     说明链接参数 -l<库> 来自哪个包的配置或编译器规则");
+    println!("  add <包>[@版本]   添加依赖到 [dependencies]（--path <目录>、--git <地址> [--branch|--tag|--rev <名称>]）");
+    println!("  add --system <库> 添加系统库依赖");
+    println!("  remove <包>...    从 [dependencies] 删除依赖");
     println!("  ffi <名称> <库>   添加 FFI 库配置");
     println!("  help              显示此帮助信息");
     println!();
+    println!("模板:");
+    for (name, description) in cavvy::cavly::template::BUILTIN_TEMPLATES {
+        println!("  {:<17} {}", name, description);
+    }
+    println!("  <目录>            用户模板目录，或 {} 中的同名目录", cavvy::cavly::template::user_template_dir().display());
+    println!();
     println!("示例:");
     println!("  cavly init my-project");
     println!("  cavly init --lib my-library");
+    println!("  cavly new my-tool --template cli");
+    println!("  cavly new zmath --template ffi-wrapper");
     println!("  cavly build");
     println!("  cavly build -v");
     println!("  cavly run");
-    println!("  cavly add m");
+    println!("  cavly test --filter Math --junit target/junit.xml");
+    println!("  cavly build --release");
+    println!("  cavly run --profile bench --features gui,logging");
+    println!("  cavly build --workspace");
+    println!("  cavly run -p app");
+    println!("  cavly build --locked");
+    println!("  cavly build --offline");
+    println!("  cavly update json");
+    println!("  cavly publish --registry ../registry");
+    println!("  cavly tree -i json");
+    println!("  cavly why-link SDL2");
+    println!("  cavly add json@1.2");
+    println!("  cavly add mylib --path ../mylib");
+    println!("  cavly add net --git https://github.com/user/net --tag v1.0.0");
+    println!("  cavly remove json");
+    println!("  cavly add --system m");
     println!("  cavly ffi sdl2 SDL2");
 }
 
@@ -58,11 +99,19 @@ fn main() {
     
     let result = match command.as_str() {
         "init" => cmd_init(&args),
-        "build" => cmd_build(verbose),
-        "clean" => cmd_clean(verbose),
-        "run" => cmd_run(verbose),
+        "new" => cmd_new(&args),
+        "build" => cmd_build(&args, verbose),
+        "clean" => cmd_clean(&args, verbose),
+        "run" => cmd_run(&args, verbose),
+        "test" => cmd_test(&args, verbose),
+        "update" => cmd_update(&args, verbose),
+        "package" => cmd_package(&args, verbose).map(|_| ()),
+        "publish" => cmd_publish(&args, verbose),
         "info" => cmd_info(),
+        "tree" => cmd_tree(&args),
+        "why-link" => cmd_why_link(&args),
         "add" => cmd_add(&args),
+        "remove" => cmd_remove(&args),
         "ffi" => cmd_ffi(&args),
         "help" | "-h" | "--help" => {
             print_usage();
@@ -85,6 +134,176 @@ fn main() {
     }
 }
 
+/// 取带值选项的值（`--registry <目录>`）
+fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .map(|value| value.as_str())
+}
+
+/// 命令后的位置参数（跳过选项和选项的值）
+fn positional_args(args: &[String]) -> Vec<String> {
+    let mut positional = Vec::new();
+    let mut iter = args.iter().skip(2);
+    while let Some(arg) = iter.next() {
+        if ["--registry", "--filter", "--junit", "--profile", "--features", "-p", "--package", "-i", "--invert",
+            "--template", "--path", "--git", "--branch", "--tag", "--rev", "--system"].contains(&arg.as_str()) {
+            iter.next();
+        } else if !arg.starts_with('-') {
+            positional.push(arg.clone());
+        }
+    }
+    positional
+}
+
+/// 命令行中的依赖解析选项（`--locked`、`--offline`、`--registry`）
+fn resolve_options(args: &[String]) -> Result<cavvy::cavly::workspace::ResolveOptions> {
+    let registry = match option_value(args, "--registry") {
+        Some(path) => Some(env::current_dir()?.join(path)),
+        None => None,
+    };
+    Ok(cavvy::cavly::workspace::ResolveOptions {
+        locked: args.iter().any(|arg| arg == "--locked"),
+        offline: args.iter().any(|arg| arg == "--offline"),
+        registry,
+        ..Default::default()
+    })
+}
+
+/// 命令行选择的配置档（`--release`、`--profile <名称>`），默认为 dev
+fn profile_name(args: &[String]) -> Result<String> {
+    let release = args.iter().any(|arg| arg == "--release");
+    match option_value(args, "--profile") {
+        Some(name) if release && name != "release" => {
+            anyhow::bail!("--release 与 --profile {} 不能同时使用", name)
+        }
+        Some(name) => Ok(name.to_string()),
+        None if release => Ok("release".to_string()),
+        None => Ok(cavvy::cavly::config::DEFAULT_PROFILE.to_string()),
+    }
+}
+
+/// 命令行中的特性选择（`--features a,b`、`--no-default-features`）
+fn feature_selection(args: &[String]) -> cavvy::cavly::config::FeatureSelection {
+    let features = option_value(args, "--features")
+        .map(|list| list.split([',', ' '])
+            .filter(|feature| !feature.is_empty())
+            .map(|feature| feature.to_string())
+            .collect())
+        .unwrap_or_default();
+    cavvy::cavly::config::FeatureSelection {
+        features,
+        no_default_features: args.iter().any(|arg| arg == "--no-default-features"),
+    }
+}
+
+/// 加载 cavly.toml 并应用命令行选择的配置档和特性（未启用的可选依赖被移除）
+fn load_build_config(project_root: &std::path::Path, args: &[String])
+    -> Result<(cavvy::cavly::config::CavlyConfig, cavvy::cavly::config::EnabledFeatures)>
+{
+    let mut config = cavvy::cavly::config::CavlyConfig::from_file(&project_root.join("cavly.toml"))?;
+    if config.is_virtual_workspace() {
+        anyhow::bail!("{} 是工作区根目录，请用 --workspace 构建全部成员或用 -p <成员> 选择成员", project_root.display());
+    }
+    config.apply_profile(&profile_name(args)?)?;
+    let features = config.apply_features(&feature_selection(args))?;
+    Ok((config, features))
+}
+
+/// 工作区命令选择的成员（`-p <成员>`、`--workspace`）
+enum MemberSelection {
+    /// 当前项目（没有 `-p` 和 `--workspace`）
+    Current,
+    /// 指定的成员及它依赖的成员
+    Package(String),
+    /// 全部成员
+    Workspace,
+}
+
+impl MemberSelection {
+    /// `-p` 选择的成员名，`--workspace` 时为 None
+    fn name(&self) -> Option<&str> {
+        match self {
+            MemberSelection::Package(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// 命令行中的成员选择
+fn member_selection(args: &[String]) -> Result<MemberSelection> {
+    let package = option_value(args, "-p").or_else(|| option_value(args, "--package"));
+    let workspace = args.iter().any(|arg| arg == "--workspace");
+    match (package, workspace) {
+        (Some(_), true) => anyhow::bail!("-p 与 --workspace 不能同时使用"),
+        (Some(name), false) => Ok(MemberSelection::Package(name.to_string())),
+        (None, true) => Ok(MemberSelection::Workspace),
+        (None, false) => Ok(MemberSelection::Current),
+    }
+}
+
+/// 从当前目录向上查找并加载工作区
+fn load_workspace() -> Result<cavvy::cavly::members::Workspace> {
+    let current_dir = env::current_dir()?;
+    let root = cavvy::cavly::members::find_workspace_root(&current_dir)
+        .ok_or_else(|| anyhow::anyhow!("当前目录不在 Cavly 工作区中（找不到带 [workspace] members 的 cavly.toml）"))?;
+    cavvy::cavly::members::Workspace::load(&root)
+}
+
+/// 工作区成员的构建配置：应用配置档和特性
+///
+/// 特性选择只作用于 `-p` 选择的成员（`--workspace` 时作用于全部成员），被依赖的成员使用默认特性
+fn member_build_config(member: &cavvy::cavly::members::Member, args: &[String], selected: Option<&str>)
+    -> Result<(cavvy::cavly::config::CavlyConfig, cavvy::cavly::config::EnabledFeatures)>
+{
+    let mut config = member.config.clone();
+    config.apply_profile(&profile_name(args)?)?;
+    let selection = if selected.is_none_or(|name| name == member.name) {
+        feature_selection(args)
+    } else {
+        cavvy::cavly::config::FeatureSelection::default()
+    };
+    let features = config.apply_features(&selection)?;
+    Ok((config, features))
+}
+
+/// 构建工作区成员：同一层的成员并行构建，产物写入工作区共享的目标目录
+/// 
+/// # 复杂度
+/// - 时间: 各层中最慢的成员的构建时间之和
+/// - 空间: O(n)，n 为成员数量
+fn build_members(args: &[String], verbose: bool, selected: Option<&str>)
+    -> Result<(cavvy::cavly::members::Workspace, Vec<(String, std::path::PathBuf)>)>
+{
+    let workspace = load_workspace()?;
+    if selected.is_none() && !feature_selection(args).features.is_empty() {
+        anyhow::bail!("--features 只能与 -p <成员> 一起使用");
+    }
+    let members = workspace.select(selected)?;
+    let member_paths: Vec<std::path::PathBuf> = members.iter().map(|member| member.path.clone()).collect();
+    let target_dir = workspace.target_dir();
+    let options = resolve_options(args)?;
+    let profile = profile_name(args)?;
+    
+    if verbose {
+        println!("Cavly: 工作区: {}", workspace.root.display());
+        println!("Cavly: 构建成员: {}",
+            members.iter().map(|member| member.name.as_str()).collect::<Vec<_>>().join(", "));
+    }
+    
+    let outputs = workspace.build_parallel(&members, |member| {
+        let (config, features) = member_build_config(member, args, selected)?;
+        cavvy::cavly::builder::Builder::with_resolve_options(member.path.clone(), config, options.clone())?
+            .features(features)
+            .profile(&profile)
+            .workspace(&target_dir, &member_paths)
+            .verbose(verbose)
+            .build()
+    })?;
+    Ok((workspace, outputs))
+}
+
 /// 初始化新项目
 /// 
 /// # 复杂度
@@ -114,16 +333,43 @@ fn cmd_init(args: &[String]) -> Result<()> {
     Ok(())
 }
 
+/// 用模板创建新项目：`cavly new <名称> [--template <模板>]`
+/// 
+/// # 复杂度
+/// - 时间: O(n)，n 为模板文件总大小
+/// - 空间: O(n)
+fn cmd_new(args: &[String]) -> Result<()> {
+    use cavvy::cavly::template::{Template, DEFAULT_TEMPLATE};
+    
+    let project_name = positional_args(args).into_iter().next()
+        .ok_or_else(|| anyhow::anyhow!("请指定项目名称，例如: cavly new my-tool --template cli"))?;
+    let current_dir = env::current_dir()?;
+    let template = Template::find(option_value(args, "--template").unwrap_or(DEFAULT_TEMPLATE), &current_dir)?;
+    
+    cavvy::cavly::project::Project::new_from_template(&current_dir.join(&project_name), &project_name, &template)?;
+    
+    Ok(())
+}
+
 /// 构建项目
 /// 
 /// # 复杂度
 /// - 时间: O(n + m)，n 为源码大小，m 为链接复杂度
 /// - 空间: O(n)
-fn cmd_build(verbose: bool) -> Result<()> {
+fn cmd_build(args: &[String], verbose: bool) -> Result<()> {
     println!("Cavvy 包管理器 {}", VERSION);
     println!("版权所有 (c) 2026, Ethernos Studio");
     println!("使用 GNU 通用公共许可证 版本三 协议开源");
 
+    let selection = member_selection(args)?;
+    if !matches!(selection, MemberSelection::Current) {
+        let (_, outputs) = build_members(args, verbose, selection.name())?;
+        for (name, output) in outputs {
+            println!("构建成功: {} ({})", output.display(), name);
+        }
+        return Ok(());
+    }
+
     let current_dir = env::current_dir()?;
     
     // 查找项目根目录
@@ -134,14 +380,18 @@ fn cmd_build(verbose: bool) -> Result<()> {
         println!("Cavly: 项目根目录: {}", project_root.display());
     }
     
-    // 加载配置
-    let config_path = project_root.join("cavly.toml");
-    let config = cavvy::cavly::config::CavlyConfig::from_file(&config_path)?;
+    // 加载配置，应用配置档和特性
+    let (config, features) = load_build_config(&project_root, args)?;
     
     if verbose {
         let type_str = if config.is_lib() { "库" } else { "可执行程序" };
         println!("Cavly: 项目: {} v{} ({})", 
             config.package.name, config.package.version, type_str);
+        println!("Cavly: 配置档: {} ({})", profile_name(args)?, config.opt_flag());
+        
+        if !features.names.is_empty() {
+            println!("Cavly: 特性: {}", features.names.join(", "));
+        }
         
         if !config.dependencies.is_empty() {
             println!("Cavly: 依赖: {}", 
@@ -154,9 +404,12 @@ fn cmd_build(verbose: bool) -> Result<()> {
         }
     }
     
-    // 构建（使用 with_dependencies 自动解析和构建依赖）
-    let mut builder = cavvy::cavly::builder::Builder::with_dependencies(
-            project_root.clone(), config)?
+    // 构建（自动解析和构建依赖，--locked 时 cavly.lock 必须是最新的）
+    let options = resolve_options(args)?;
+    let mut builder = cavvy::cavly::builder::Builder::with_resolve_options(
+            project_root.clone(), config, options)?
+        .features(features)
+        .profile(&profile_name(args)?)
         .verbose(verbose);
     
     let output_path = builder.build()?;
@@ -171,7 +424,29 @@ fn cmd_build(verbose: bool) -> Result<()> {
 /// # 复杂度
 /// - 时间: O(1)
 /// - 空间: O(1)
-fn cmd_clean(verbose: bool) -> Result<()> {
+fn cmd_clean(args: &[String], verbose: bool) -> Result<()> {
+    match member_selection(args)? {
+        MemberSelection::Current => {}
+        MemberSelection::Workspace => {
+            // 成员共享工作区的目标目录
+            let workspace = load_workspace()?;
+            cavvy::cavly::builder::Builder::new(workspace.root.clone(), workspace.config.clone())
+                .verbose(verbose)
+                .clean()?;
+            println!("清理完成");
+            return Ok(());
+        }
+        MemberSelection::Package(name) => {
+            let workspace = load_workspace()?;
+            let member = workspace.member(&name)?;
+            cavvy::cavly::builder::Builder::new(member.path.clone(), member.config.clone())
+                .verbose(verbose)
+                .clean_package()?;
+            println!("清理完成: {}", name);
+            return Ok(());
+        }
+    }
+    
     let current_dir = env::current_dir()?;
     
     let project_root = cavvy::cavly::find_project_root(&current_dir)
@@ -194,9 +469,26 @@ fn cmd_clean(verbose: bool) -> Result<()> {
 /// # 复杂度
 /// - 时间: O(n + m) + 运行时间
 /// - 空间: O(n)
-fn cmd_run(verbose: bool) -> Result<()> {
+fn cmd_run(args: &[String], verbose: bool) -> Result<()> {
+    match member_selection(args)? {
+        MemberSelection::Current => {}
+        MemberSelection::Workspace => anyhow::bail!("cavly run 不支持 --workspace，请用 -p <成员> 选择要运行的程序"),
+        MemberSelection::Package(name) => {
+            let workspace = load_workspace()?;
+            if workspace.member(&name)?.config.is_lib() {
+                anyhow::bail!("成员 {} 是库项目，不能运行", name);
+            }
+            let (_, outputs) = build_members(args, verbose, Some(&name))?;
+            let exe_path = outputs.into_iter()
+                .find(|(member, _)| *member == name)
+                .map(|(_, output)| output)
+                .expect("选择的成员总会被构建");
+            return run_executable(&exe_path, verbose);
+        }
+    }
+    
     // 先构建
-    cmd_build(verbose)?;
+    cmd_build(args, verbose)?;
     
     let current_dir = env::current_dir()?;
     let project_root = cavvy::cavly::find_project_root(&current_dir)
@@ -219,6 +511,11 @@ fn cmd_run(verbose: bool) -> Result<()> {
         target_dir.join(&output_name)
     };
     
+    run_executable(&exe_path, verbose)
+}
+
+/// 运行构建出的可执行文件，非零退出码作为错误返回
+fn run_executable(exe_path: &std::path::Path, verbose: bool) -> Result<()> {
     if !exe_path.exists() {
         anyhow::bail!("可执行文件不存在: {}", exe_path.display());
     }
@@ -228,7 +525,7 @@ fn cmd_run(verbose: bool) -> Result<()> {
     }
     
     // 运行
-    let status = std::process::Command::new(&exe_path)
+    let status = std::process::Command::new(exe_path)
         .status()
         .with_context(|| format!("运行失败: {}", exe_path.display()))?;
     
@@ -239,6 +536,253 @@ fn cmd_run(verbose: bool) -> Result<()> {
     Ok(())
 }
 
+/// 构建并运行测试：每个 @Test 方法在独立进程中运行，报告失败断言的位置
+/// 
+/// # 复杂度
+/// - 时间: O(n + m) + 各测试的运行时间，n 为源码大小，m 为链接复杂度
+/// - 空间: O(n + t)，t 为测试数量
+fn cmd_test(args: &[String], verbose: bool) -> Result<()> {
+    use cavvy::cavly::testing::TestOutcome;
+    
+    let current_dir = env::current_dir()?;
+    let filter = option_value(args, "--filter");
+    let mut results = Vec::new();
+    let mut filtered_out = 0;
+    let suite_name = match member_selection(args)? {
+        MemberSelection::Current => {
+            let project_root = cavvy::cavly::find_project_root(&current_dir)
+                .ok_or_else(|| anyhow::anyhow!("当前目录不是 Cavly 项目（找不到 cavly.toml）"))?;
+            let (config, features) = load_build_config(&project_root, args)?;
+            let builder = cavvy::cavly::builder::Builder::with_resolve_options(
+                    project_root.clone(), config.clone(), resolve_options(args)?)?
+                .features(features)
+                .profile(&profile_name(args)?)
+                .verbose(verbose);
+            filtered_out += run_test_suite(&project_root, &config, builder, filter, &mut results)?;
+            config.package.name
+        }
+        selection => {
+            // 先构建成员（库成员的产物供测试程序链接），再逐个运行成员的测试
+            let (workspace, _) = build_members(args, verbose, selection.name())?;
+            let member_paths: Vec<std::path::PathBuf> = workspace.select(selection.name())?.iter()
+                .map(|member| member.path.clone())
+                .collect();
+            let tested = match selection.name() {
+                Some(name) => vec![workspace.member(name)?],
+                None => workspace.members.iter().collect(),
+            };
+            for member in tested {
+                let (config, features) = member_build_config(member, args, selection.name())?;
+                let builder = cavvy::cavly::builder::Builder::with_resolve_options(
+                        member.path.clone(), config.clone(), resolve_options(args)?)?
+                    .features(features)
+                    .profile(&profile_name(args)?)
+                    .workspace(&workspace.target_dir(), &member_paths)
+                    .verbose(verbose);
+                println!();
+                println!("测试成员: {}", member.name);
+                filtered_out += run_test_suite(&member.path, &config, builder, filter, &mut results)?;
+            }
+            selection.name().map(str::to_string).unwrap_or_else(|| {
+                workspace.root.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "workspace".to_string())
+            })
+        }
+    };
+    
+    let failed: Vec<_> = results.iter().filter(|result| !result.passed()).collect();
+    if !failed.is_empty() {
+        println!();
+        println!("失败:");
+        for result in &failed {
+            println!();
+            println!("---- {} ----", result.test.name());
+            if let TestOutcome::Failed { message, location } = &result.outcome {
+                println!("{}", message);
+                match location {
+                    Some(location) => println!("  --> {}", location),
+                    None => println!("  --> {}:{}", result.test.file.display(), result.test.line),
+                }
+            }
+            if verbose && !result.output.is_empty() {
+                print!("{}", result.output);
+            }
+        }
+    }
+    
+    println!();
+    println!("测试结果: {}。{} 通过；{} 失败；{} 被过滤",
+        if failed.is_empty() { "ok" } else { "FAILED" },
+        results.len() - failed.len(),
+        failed.len(),
+        filtered_out
+    );
+    
+    if let Some(path) = option_value(args, "--junit") {
+        let path = current_dir.join(path);
+        std::fs::write(&path, cavvy::cavly::testing::junit_xml(&suite_name, &results))
+            .with_context(|| format!("写入 JUnit 报告失败: {}", path.display()))?;
+        println!("JUnit 报告: {}", path.display());
+    }
+    
+    if !failed.is_empty() {
+        anyhow::bail!("{} 个测试失败", failed.len());
+    }
+    Ok(())
+}
+
+/// 构建一个项目的测试程序并运行选中的测试，结果追加到 `results`，返回被过滤掉的测试数量
+fn run_test_suite(
+    project_root: &std::path::Path,
+    config: &cavvy::cavly::config::CavlyConfig,
+    mut builder: cavvy::cavly::builder::Builder,
+    filter: Option<&str>,
+    results: &mut Vec<cavvy::cavly::testing::TestResult>,
+) -> Result<usize> {
+    use cavvy::cavly::testing;
+    
+    let suite = testing::discover(project_root, config)?;
+    let selected: Vec<_> = suite.tests.iter()
+        .filter(|test| filter.is_none_or(|filter| test.name().contains(filter)))
+        .collect();
+    let filtered_out = suite.tests.len() - selected.len();
+    
+    let harness = testing::write_harness(project_root, config, &suite)?;
+    let executable = builder.build_test_harness(&harness)?;
+    
+    println!();
+    println!("运行 {} 个测试", selected.len());
+    for test in selected {
+        let result = testing::run_test(&executable, test)?;
+        println!("test {} ... {}", test.name(), if result.passed() { "ok" } else { "FAILED" });
+        results.push(result);
+    }
+    Ok(filtered_out)
+}
+
+/// 重新解析依赖版本并更新 cavly.lock
+/// 
+/// 不指定包名时重新选择所有包的版本，否则只更新指定的包；
+/// `--locked` 时 lock 需要改变则报错而不是写入
+/// 
+/// # 复杂度
+/// - 时间: O(n*m)，n 为依赖数量，m 为候选版本数量
+/// - 空间: O(n)
+fn cmd_update(args: &[String], verbose: bool) -> Result<()> {
+    use cavvy::cavly::lockfile::LockFile;
+    use cavvy::cavly::workspace::{LockUpdate, ResolveOptions, WorkspaceResolver};
+    
+    let current_dir = env::current_dir()?;
+    let project_root = cavvy::cavly::find_project_root(&current_dir)
+        .ok_or_else(|| anyhow::anyhow!("当前目录不是 Cavly 项目（找不到 cavly.toml）"))?;
+    
+    let config_path = project_root.join("cavly.toml");
+    let config = cavvy::cavly::config::CavlyConfig::from_file(&config_path)?;
+    
+    let packages = positional_args(args);
+    let lock_path = project_root.join(cavvy::cavly::LOCK_FILE);
+    let previous = LockFile::load(&lock_path)?;
+    if let Some(ref previous) = previous {
+        for name in &packages {
+            if previous.find(name).is_none() {
+                anyhow::bail!("{} 中没有包 '{}'", cavvy::cavly::LOCK_FILE, name);
+            }
+        }
+    }
+    
+    let update = if packages.is_empty() { LockUpdate::All } else { LockUpdate::Packages(packages) };
+    let mut resolver = WorkspaceResolver::new(project_root)
+        .with_options(ResolveOptions {
+            update,
+            ..resolve_options(args)?
+        });
+    resolver.resolve_all(&config)?;
+    
+    let lock = resolver.lock().expect("resolve_all produces a lock");
+    match previous {
+        Some(previous) => {
+            let changes = previous.diff(lock);
+            if changes.is_empty() {
+                println!("依赖版本没有变化");
+            }
+            for change in changes {
+                println!("  {}", change);
+            }
+        }
+        None => println!("已生成 {}（{} 个包）", cavvy::cavly::LOCK_FILE, lock.packages.len()),
+    }
+    if verbose {
+        println!("Cavly: 已写入 {}", lock_path.display());
+    }
+    
+    Ok(())
+}
+
+/// 打包库项目：生成 target/package/<包名>-<版本>.tar，并解压编译检查（`--no-verify` 时跳过）
+/// 
+/// # 复杂度
+/// - 时间: O(n)，n 为源码大小
+/// - 空间: O(n)
+fn cmd_package(args: &[String], verbose: bool) -> Result<cavvy::cavly::registry::PackageArchive> {
+    use cavvy::cavly::registry;
+    
+    let current_dir = env::current_dir()?;
+    let project_root = cavvy::cavly::find_project_root(&current_dir)
+        .ok_or_else(|| anyhow::anyhow!("当前目录不是 Cavly 项目（找不到 cavly.toml）"))?;
+    
+    let config_path = project_root.join("cavly.toml");
+    let config = cavvy::cavly::config::CavlyConfig::from_file(&config_path)?;
+    
+    println!("打包 {} v{}", config.package.name, config.package.version);
+    let archive = registry::package(&project_root, &config)?;
+    if !args.iter().any(|arg| arg == "--no-verify") {
+        println!("校验 {} v{}", archive.name, archive.version);
+        registry::verify(&archive, resolve_options(args)?, verbose)?;
+    }
+    println!("已打包: {}", archive.path.display());
+    if verbose {
+        println!("Cavly: SHA-256: {}", archive.checksum);
+    }
+    
+    Ok(archive)
+}
+
+/// 注册表目录：`--registry`（相对于当前目录），其次是 `[registry] path`（相对于项目根目录），最后是 `CAVLY_REGISTRY`
+fn registry_root(args: &[String], project_root: &std::path::Path, config: &cavvy::cavly::config::CavlyConfig)
+    -> Result<Option<std::path::PathBuf>>
+{
+    if let Some(path) = option_value(args, "--registry") {
+        return Ok(Some(env::current_dir()?.join(path)));
+    }
+    Ok(match config.registry.path {
+        Some(ref path) => Some(project_root.join(path)),
+        None => env::var_os("CAVLY_REGISTRY").map(std::path::PathBuf::from),
+    })
+}
+
+/// 打包并发布库项目到注册表
+/// 
+/// # 复杂度
+/// - 时间: O(n + v)，n 为源码大小，v 为已发布的版本数
+/// - 空间: O(n)
+fn cmd_publish(args: &[String], verbose: bool) -> Result<()> {
+    let current_dir = env::current_dir()?;
+    let project_root = cavvy::cavly::find_project_root(&current_dir)
+        .ok_or_else(|| anyhow::anyhow!("当前目录不是 Cavly 项目（找不到 cavly.toml）"))?;
+    let config = cavvy::cavly::config::CavlyConfig::from_file(&project_root.join("cavly.toml"))?;
+    
+    let registry_root = registry_root(args, &project_root, &config)?
+        .ok_or_else(|| anyhow::anyhow!("没有配置注册表：使用 --registry <目录>、[registry] path 或 CAVLY_REGISTRY"))?;
+    
+    let archive = cmd_package(args, verbose)?;
+    let registry = cavvy::cavly::registry::Registry::open(registry_root);
+    registry.publish(&archive)?;
+    println!("已发布 {} v{} 到 {}", archive.name, archive.version, registry.root().display());
+    
+    Ok(())
+}
+
 /// 显示项目信息
 /// 
 /// # 复杂度
@@ -250,26 +794,255 @@ fn cmd_info() -> Result<()> {
     let project_root = cavvy::cavly::find_project_root(&current_dir)
         .ok_or_else(|| anyhow::anyhow!("当前目录不是 Cavly 项目（找不到 cavly.toml）"))?;
     
-    let info = cavvy::cavly::project::Project::info(&project_root)?;
-    info.print();
+    let config = cavvy::cavly::config::CavlyConfig::from_file(&project_root.join("cavly.toml"))?;
+    if !config.is_virtual_workspace() {
+        let info = cavvy::cavly::project::Project::info(&project_root)?;
+        info.print();
+    }
+    
+    // 工作区根目录显示成员依赖图
+    if !config.workspace.members.is_empty() {
+        let workspace = cavvy::cavly::members::Workspace::load(&project_root)?;
+        if !config.is_virtual_workspace() {
+            println!();
+        }
+        for line in workspace.graph_lines() {
+            println!("{}", line);
+        }
+    }
     
     Ok(())
 }
 
-/// 添加系统库依赖
+/// 依赖检查命令（tree、why-link）要检查的项目：当前项目，或 `-p`/`--workspace` 选择的工作区成员
+fn inspected_projects(args: &[String]) -> Result<Vec<(std::path::PathBuf, cavvy::cavly::config::CavlyConfig)>> {
+    let selection = member_selection(args)?;
+    if matches!(selection, MemberSelection::Current) {
+        let current_dir = env::current_dir()?;
+        let project_root = cavvy::cavly::find_project_root(&current_dir)
+            .ok_or_else(|| anyhow::anyhow!("当前目录不是 Cavly 项目（找不到 cavly.toml）"))?;
+        let (config, _) = load_build_config(&project_root, args)?;
+        return Ok(vec![(project_root, config)]);
+    }
+    
+    let workspace = load_workspace()?;
+    let members = match selection.name() {
+        Some(name) => vec![workspace.member(name)?],
+        None => workspace.members.iter().collect(),
+    };
+    members.into_iter()
+        .map(|member| {
+            let (config, _) = member_build_config(member, args, selection.name())?;
+            Ok((member.path.clone(), config))
+        })
+        .collect()
+}
+
+/// 解析项目的依赖并构建依赖图
+fn dependency_tree(project_root: &std::path::Path, config: &cavvy::cavly::config::CavlyConfig, args: &[String])
+    -> Result<cavvy::cavly::tree::DependencyTree>
+{
+    let mut resolver = cavvy::cavly::workspace::WorkspaceResolver::new(project_root.to_path_buf())
+        .with_options(resolve_options(args)?);
+    let dependencies = resolver.resolve_all(config)?;
+    Ok(cavvy::cavly::tree::DependencyTree::new(project_root, config, &dependencies))
+}
+
+/// 显示依赖树；`-i <包>` 显示反向依赖树
 /// 
 /// # 复杂度
-/// - 时间: O(1)
-/// - 空间: O(1)
+/// - 时间: O(n + e)，n 为包数量，e 为依赖边数
+/// - 空间: O(n + e)
+fn cmd_tree(args: &[String]) -> Result<()> {
+    let invert = option_value(args, "-i").or_else(|| option_value(args, "--invert"));
+    
+    for (i, (project_root, config)) in inspected_projects(args)?.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let tree = dependency_tree(project_root, config, args)?;
+        let lines = match invert {
+            Some(name) => tree.inverted_lines(name)?,
+            None => tree.lines(),
+        };
+        for line in lines {
+            println!("{}", line);
+        }
+    }
+    
+    Ok(())
+}
+
+/// 说明链接参数 `-l<库>` 的来源
+/// 
+/// # 复杂度
+/// - 时间: O(n*m)，n 为包数量，m 为每个包的库配置数量
+/// - 空间: O(n + e)
+fn cmd_why_link(args: &[String]) -> Result<()> {
+    // 库名也可以写成链接参数的形式（-lm）
+    let lib = positional_args(args).into_iter().next()
+        .or_else(|| args.iter().skip(2)
+            .filter_map(|arg| arg.strip_prefix("-l"))
+            .find(|lib| !lib.is_empty())
+            .map(|lib| lib.to_string()))
+        .ok_or_else(|| anyhow::anyhow!("请指定库名，例如: cavly why-link m"))?;
+    
+    for (i, (project_root, config)) in inspected_projects(args)?.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        if config.is_lib() && config.lib.only_include {
+            println!("{} 是 only_include 库，不链接任何库", config.package.name);
+            continue;
+        }
+        let tree = dependency_tree(project_root, config, args)?;
+        let sources = tree.link_sources(&lib, config.build.target.as_deref());
+        if sources.is_empty() {
+            println!("{} 的链接命令中没有 -l{}", config.package.name, lib);
+            continue;
+        }
+        println!("{} 链接 -l{}，来源:", config.package.name, lib);
+        for source in sources {
+            println!("  {}", source.describe(&tree));
+        }
+    }
+    
+    Ok(())
+}
+
+/// 添加依赖：`cavly add <包>[@版本] [--path <目录> | --git <地址> [--branch|--tag|--rev <名称>]]`，
+/// `cavly add --system <库>` 添加系统库
+/// 
+/// 只修改 cavly.toml 的 [dependencies]，其余内容和注释保持不变
+/// 
+/// # 复杂度
+/// - 时间: O(n + v)，n 为 cavly.toml 大小，v 为注册表中的版本数
+/// - 空间: O(n)
 fn cmd_add(args: &[String]) -> Result<()> {
-    let lib_name = args.get(2)
-        .ok_or_else(|| anyhow::anyhow!("请指定库名，例如: cavly add m"))?;
+    use cavvy::cavly::manifest::{ConfigDocument, DependencySource, DependencySpec};
+    
+    let current_dir = env::current_dir()?;
+    let project_root = cavvy::cavly::find_project_root(&current_dir)
+        .ok_or_else(|| anyhow::anyhow!("当前目录不是 Cavly 项目（找不到 cavly.toml）"))?;
+    
+    if let Some(lib_name) = option_value(args, "--system") {
+        return cavvy::cavly::project::Project::add_system_lib(&project_root, lib_name);
+    }
+    
+    let package = positional_args(args).into_iter().next()
+        .ok_or_else(|| anyhow::anyhow!("请指定包名，例如: cavly add json@1.2（系统库使用 cavly add --system m）"))?;
+    let (name, version) = DependencySpec::split_name(&package)?;
+    let config = cavvy::cavly::config::CavlyConfig::from_file(&project_root.join("cavly.toml"))?;
+    if config.is_virtual_workspace() {
+        anyhow::bail!("工作区根目录没有 [package]，请在成员目录中添加依赖");
+    }
+    if name == config.package.name {
+        anyhow::bail!("包不能依赖自身: {}", name);
+    }
+    
+    let reference = ["--branch", "--tag", "--rev"].iter()
+        .filter_map(|kind| option_value(args, kind).map(|value| (kind.trim_start_matches('-').to_string(), value.to_string())))
+        .collect::<Vec<_>>();
+    if reference.len() > 1 {
+        anyhow::bail!("--branch、--tag 和 --rev 只能指定一个");
+    }
+    if !reference.is_empty() && option_value(args, "--git").is_none() {
+        anyhow::bail!("--branch、--tag 和 --rev 只能与 --git 一起使用");
+    }
+    
+    let source = match (option_value(args, "--path"), option_value(args, "--git")) {
+        (Some(_), Some(_)) => anyhow::bail!("--path 和 --git 不能同时使用"),
+        (Some(path), None) => {
+            let dep_root = current_dir.join(path);
+            let dep_config = cavvy::cavly::config::CavlyConfig::from_file(&dep_root.join("cavly.toml"))
+                .with_context(|| format!("{} 不是 Cavly 项目", dep_root.display()))?;
+            if dep_config.package.name != name {
+                anyhow::bail!("{} 中的包名是 '{}'，不是 '{}'", dep_root.display(), dep_config.package.name, name);
+            }
+            if !dep_config.is_lib() {
+                anyhow::bail!("只能依赖库项目: {} 不是库项目", name);
+            }
+            let relative = cavvy::cavly::workspace::relative_path(&dep_root, &project_root);
+            DependencySource::Path(relative.to_string_lossy().replace('\\', "/"))
+        }
+        (None, Some(url)) => DependencySource::Git {
+            url: url.to_string(),
+            reference: reference.into_iter().next(),
+        },
+        (None, None) => DependencySource::Registry,
+    };
+    
+    // 注册表依赖没有指定版本时使用注册表中的最新版本
+    let version = match (version, &source) {
+        (None, DependencySource::Registry) => Some(latest_registry_version(args, &project_root, &config, &name)?),
+        (version, _) => version,
+    };
+    
+    let spec = DependencySpec { name, version, source };
+    let mut document = ConfigDocument::load(&project_root)?;
+    let replaced = document.add_dependency(&spec)?;
+    document.save(&project_root)?;
+    
+    let version = spec.version.as_deref().map(|version| format!(" {}", version)).unwrap_or_default();
+    let source = match spec.source {
+        DependencySource::Registry => String::new(),
+        DependencySource::Path(ref path) => format!("（路径 {}）", path),
+        DependencySource::Git { ref url, .. } => format!("（git {}）", url),
+    };
+    println!("{}依赖: {}{}{}", if replaced { "已更新" } else { "已添加" }, spec.name, version, source);
+    
+    Ok(())
+}
+
+/// 注册表中包的最新版本
+fn latest_registry_version(
+    args: &[String],
+    project_root: &std::path::Path,
+    config: &cavvy::cavly::config::CavlyConfig,
+    name: &str,
+) -> Result<String> {
+    use cavvy::cavly::semver::Version;
+    
+    let registry_root = registry_root(args, project_root, config)?
+        .ok_or_else(|| anyhow::anyhow!("请指定版本（cavly add {}@<版本>），或用 --registry、[registry] path 或 CAVLY_REGISTRY 配置注册表", name))?;
+    let registry = cavvy::cavly::registry::Registry::open(registry_root);
+    registry.versions(name)?
+        .into_iter()
+        .filter_map(|entry| Version::parse(&entry.version).ok())
+        .max()
+        .map(|version| version.to_string())
+        .ok_or_else(|| anyhow::anyhow!("注册表 {} 中没有包 '{}'", registry.root().display(), name))
+}
+
+/// 删除依赖：`cavly remove <包>...`
+/// 
+/// # 复杂度
+/// - 时间: O(n)，n 为 cavly.toml 大小
+/// - 空间: O(n)
+fn cmd_remove(args: &[String]) -> Result<()> {
+    let packages = positional_args(args);
+    if packages.is_empty() {
+        anyhow::bail!("请指定要删除的包名，例如: cavly remove json");
+    }
     
     let current_dir = env::current_dir()?;
     let project_root = cavvy::cavly::find_project_root(&current_dir)
         .ok_or_else(|| anyhow::anyhow!("当前目录不是 Cavly 项目（找不到 cavly.toml）"))?;
     
-    cavvy::cavly::project::Project::add_system_lib(&project_root, lib_name)?;
+    let mut document = cavvy::cavly::manifest::ConfigDocument::load(&project_root)?;
+    let mut dangling = Vec::new();
+    for name in &packages {
+        for feature in document.remove_dependency(name)? {
+            dangling.push((feature, name));
+        }
+    }
+    document.save(&project_root)?;
+    for name in &packages {
+        println!("已删除依赖: {}", name);
+    }
+    for (feature, name) in &dangling {
+        eprintln!("警告: 特性 '{}' 仍启用 \"dep:{}\"，请同时修改 [features]", feature, name);
+    }
     
     Ok(())
 }
@@ -2,7 +2,7 @@ use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process;
-use cavvy::error::{print_error_with_context, cayError, get_error_location, get_error_message, get_error_help};
+use cavvy::error::{print_error_with_context, print_warning_with_location, cayError, get_error_location, get_error_message, get_error_help};
 use cavvy::lexer;
 use cavvy::parser;
 use cavvy::preprocessor;
@@ -121,6 +121,7 @@ fn print_usage() {
     println!("  --lex-only            只进行词法分析");
     println!("  --parse-only          进行词法和语法分析（不进行语义分析）");
     println!("  --no-preprocess       跳过预处理阶段");
+    println!("  -F<feature>, --feature=<feature>  启用语言特性（如 null_safety：空安全问题报告为错误）");
     println!("  --version, -v         显示版本号");
     println!("  --help, -h            显示帮助信息");
     println!("");
@@ -128,6 +129,7 @@ fn print_usage() {
     println!("  cay-check hello.cay");
     println!("  cay-check --lex-only hello.cay");
     println!("  cay-check --parse-only hello.cay");
+    println!("  cay-check -F null_safety hello.cay");
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -146,6 +148,7 @@ impl Default for CheckLevel {
 struct CheckOptions {
     level: CheckLevel,
     preprocess: bool,
+    features: Vec<String>,        // -F/--feature=<feature>
 }
 
 impl Default for CheckOptions {
@@ -153,6 +156,7 @@ impl Default for CheckOptions {
         CheckOptions {
             level: CheckLevel::default(),
             preprocess: true,
+            features: Vec::new(),
         }
     }
 }
@@ -183,6 +187,23 @@ fn parse_args(args: &[String]) -> Result<(CheckOptions, String), String> {
             "--no-preprocess" => {
                 options.preprocess = false;
             }
+            _ if arg.starts_with("-F") || arg.starts_with("--feature=") => {
+                // -F<feature>、-F=<feature>、-F <feature> 或 --feature=<feature> 格式
+                let value = arg.strip_prefix("--feature=")
+                    .or_else(|| arg.strip_prefix("-F="))
+                    .or_else(|| arg.strip_prefix("-F"))
+                    .unwrap_or_default();
+                let feature = if value.is_empty() {
+                    i += 1;
+                    if i >= args.len() {
+                        return Err("-F 需要特性名称参数".to_string());
+                    }
+                    args[i].clone()
+                } else {
+                    value.to_string()
+                };
+                options.features.push(feature);
+            }
             _ => {
                 if arg.starts_with('-') {
                     return Err(format!("未知选项: {}", arg));
@@ -321,19 +342,16 @@ fn main() {
 
             println!("");
             println!("[2] 语法分析...");
-            match parser::parse(tokens) {
-                Ok(ast) => {
-                    let elapsed = start_time.elapsed();
-                    println!("  [+] 语法分析通过");
-                    println!("      发现 {} 个类定义", ast.classes.len());
-                    println!("");
-                    println!("[+] 语法检查完成! (耗时: {:?})", elapsed);
-                }
-                Err(e) => {
-                    print_error_with_context(&e, &processed_source, &source_path);
-                    process::exit(1);
-                }
+            let output = parser::parse_recovering(tokens);
+            if output.has_errors() {
+                report_syntax_errors(&output.errors, &processed_source, &source_path);
+                process::exit(1);
             }
+            let elapsed = start_time.elapsed();
+            println!("  [+] 语法分析通过");
+            println!("      发现 {} 个类定义", output.program.classes.len());
+            println!("");
+            println!("[+] 语法检查完成! (耗时: {:?})", elapsed);
         }
         CheckLevel::Full => {
             if options.preprocess {
@@ -358,23 +376,28 @@ fn main() {
 
             println!("");
             println!("[2] 语法分析...");
-            let ast = match parser::parse(tokens) {
-                Ok(ast) => {
-                    println!("  [+] 语法分析通过");
-                    ast
-                }
-                Err(e) => {
-                    print_error_with_context(&e, &processed_source, &source_path);
-                    process::exit(1);
-                }
-            };
+            let output = parser::parse_recovering(tokens);
+            if output.has_errors() {
+                report_syntax_errors(&output.errors, &processed_source, &source_path);
+                process::exit(1);
+            }
+            println!("  [+] 语法分析通过");
+            let ast = output.program;
 
             println!("");
             println!("[3] 语义分析...");
-            let mut analyzer = semantic::SemanticAnalyzer::new();
+            let mut analyzer = semantic::SemanticAnalyzer::with_features(options.features.clone());
             match analyzer.analyze(&ast) {
                 Ok(_) => {
                     let elapsed = start_time.elapsed();
+                    for warning in analyzer.warnings() {
+                        // 警告的行号是预处理后的行号，经源映射换算为原始文件和行号
+                        let (file, line) = match source_map.as_ref().and_then(|map| map.get(&warning.line)) {
+                            Some((file, line)) => (file.as_str(), *line),
+                            None => (warning.file.as_deref().unwrap_or(&source_path), warning.line),
+                        };
+                        print_warning_with_location(&warning.message, file, line, warning.column);
+                    }
                     println!("  [+] 语义分析通过");
                     println!("");
                     println!("[+] 语法检查完成! (耗时: {:?})", elapsed);
@@ -392,3 +415,12 @@ fn main() {
         }
     }
 }
+
+/// 报告语法分析阶段收集到的全部错误
+fn report_syntax_errors(errors: &[cayError], source: &str, source_path: &str) {
+    for e in errors {
+        print_error_with_context(e, source, source_path);
+    }
+    eprintln!("");
+    eprintln!("[-] 语法分析失败: 共 {} 个错误", errors.len());
+}
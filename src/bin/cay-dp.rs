@@ -6,7 +6,7 @@ use std::fs;
 use std::path::Path;
 use std::process;
 use cavvy::lexer::lex_with_diagnostics;
-use cavvy::parser::parse_with_source;
+use cavvy::parser::parse_with_source_recovering;
 use cavvy::ast::{Program, ClassDecl, InterfaceDecl, TopLevelFunction, ClassMember, MethodDecl, FieldDecl};
 use cavvy::preprocessor::preprocess;
 
@@ -132,18 +132,21 @@ fn main() {
         process::exit(1);
     }
 
-    match parse_with_source(tokens, source_to_parse.clone()) {
-        Ok(ast) => {
-            if options.json_output {
-                print_ast_json(&ast);
-            } else {
-                print_ast_pretty(&ast, &options, &file_path);
-            }
-        }
-        Err(e) => {
-            eprintln!("语法分析错误: {}", e);
-            process::exit(1);
+    // 带错误恢复的解析：即使存在语法错误也输出部分 AST
+    let output = parse_with_source_recovering(tokens, source_to_parse.clone());
+
+    if options.json_output {
+        print_ast_json(&output.program);
+    } else {
+        print_ast_pretty(&output.program, &options, &file_path);
+    }
+
+    if output.has_errors() {
+        eprintln!("语法分析错误 ({} 个):", output.errors.len());
+        for e in &output.errors {
+            eprintln!("  {}", e);
         }
+        process::exit(1);
     }
 }
 
@@ -308,8 +311,14 @@ fn print_interface(iface: &InterfaceDecl, options: &Options, indent: usize) {
     let reset = if options.no_color { "" } else { "\x1b[0m" };
     let indent_str = " ".repeat(indent);
 
-    println!("{}{}interface {}{}", 
-        indent_str, item_color, iface.name, reset);
+    let extends = if !iface.parents.is_empty() {
+        format!(" extends {}", iface.parents.join(", "))
+    } else {
+        String::new()
+    };
+
+    println!("{}{}interface {}{}{}", 
+        indent_str, item_color, iface.name, extends, reset);
 
     if !options.compact {
         for method in &iface.methods {
@@ -333,6 +342,7 @@ fn print_method(method: &MethodDecl, options: &Options, indent: usize) {
             cavvy::ast::Modifier::Final => "final",
             cavvy::ast::Modifier::Abstract => "abstract",
             cavvy::ast::Modifier::Native => "native",
+            cavvy::ast::Modifier::Default => "default",
             _ => "",
         })
         .filter(|s| !s.is_empty())
@@ -3,6 +3,8 @@ use std::fs;
 use std::process;
 use std::path::{Path, PathBuf};
 use cavvy::Compiler;
+use cavvy::ir::{LlvmBackend, PassManager, IrVerifier};
+use cavvy::ir::passes::AVAILABLE_PASSES;
 use cavvy::error::{print_error_with_context, print_miette_error, print_tool_error, print_warning};
 
 /// 查找 clang 可执行文件
@@ -38,12 +40,21 @@ struct CompileOptions {
     optimize_ir: bool,       // --opt-ir: 使用 clang 优化 IR
     emit_optimized: bool,    // --emit-optimized: 输出发优化后的 IR
     target_os: String,       // --target: 目标操作系统
+    target_triple: Option<String>, // --target: 目标三元组（如 aarch64-unknown-linux-gnu）
     features: Vec<String>,   // -f:XX 或 --feature:XX 开启特性
     no_features: Vec<String>, // -No:XX 关闭特性
     defines: Vec<String>,    // -D:XX 定义宏
     undefines: Vec<String>,  // -U:XX 取消定义宏
     obfuscate: bool,         // --obfuscate 混淆 IR 代码
     include_paths: Vec<String>, // -I:XX 包含路径
+    check_overflow: bool,    // -fcheck-overflow 整数溢出检查
+    legacy_codegen: bool,    // --legacy-codegen 使用旧的字符串代码生成器
+    passes: Option<String>,  // --passes=XX 经结构化 IR 运行的优化 pass
+    print_after: Option<String>, // --print-after[=XX] 在 pass 之后输出 IR
+    emit_cayir: bool,        // --emit=cayir 输出 .cayir 文本而不是 LLVM IR
+    debug_info: bool,        // -g 生成 DWARF 调试信息
+    print_devirt: bool,      // --print-devirt 列出去虚拟化的调用点
+    escape_remarks: bool,    // -Rpass=escape 输出逃逸分析结果
 }
 
 impl Default for CompileOptions {
@@ -54,26 +65,44 @@ impl Default for CompileOptions {
             optimize_ir: false,
             emit_optimized: false,
             target_os: std::env::consts::OS.to_string(),
+            target_triple: None,
             features: Vec::new(),
             no_features: Vec::new(),
             defines: Vec::new(),
             undefines: Vec::new(),
             obfuscate: false,
+            check_overflow: false,
+            legacy_codegen: false,
+            passes: None,
+            print_after: None,
+            emit_cayir: false,
+            debug_info: false,
+            print_devirt: false,
+            escape_remarks: false,
         }
     }
 }
 
 fn print_usage() {
     println!("Cavvy IR Generator v{}", VERSION);
-    println!("Usage: cay-ir [options] <source_file.cay> [output_file.ll]");
+    println!("Usage: cay-ir [options] <source_file.cay|module.cayir> [output_file]");
     println!("");
     println!("Options:");
     println!("  -O0, -O1, -O2, -O3    编译器优化级别 (默认: -O2)");
     println!("  -Os, -Oz              优化代码大小");
     println!("  --opt-ir              使用 LLVM 优化 IR (增加编译时间，提高运行时性能)");
     println!("  --emit-optimized      输出优化后的 IR (与 --opt-ir 一起使用)");
-    println!("  --target <os>         目标操作系统 (windows, linux, macos)");
+    println!("  --target <os|triple>  目标操作系统 (windows, linux, macos) 或目标三元组");
+    println!("                        (如 aarch64-unknown-linux-gnu, riscv64gc-unknown-linux-gnu)");
     println!("  --obfuscate           混淆 IR 代码");
+    println!("  -fcheck-overflow      int/long 算术溢出时 panic");
+    println!("  -g                    生成 DWARF 调试信息");
+    println!("  --legacy-codegen      使用旧的字符串代码生成器（不经过结构化 IR）");
+    println!("  --print-devirt        列出被去虚拟化为直接调用的接口调用点");
+    println!("  -Rpass=escape         输出每个 new 是否分配在栈上及逃逸原因");
+    println!("  --passes=<list>       经结构化 IR 运行指定的优化 pass（逗号分隔）");
+    println!("  --print-after[=list]  在每个（或指定的）pass 之后输出 IR 到 stderr");
+    println!("  --emit=<llvm|cayir>   输出格式 (默认: llvm)；cayir 输出结构化 IR 文本");
     println!("  -f:XX, --feature:XX   启用特定功能");
     println!("  -No:XX                禁用特定功能");
     println!("  -D:XX                 定义宏");
@@ -82,11 +111,20 @@ fn print_usage() {
     println!("  --version, -v         显示版本号");
     println!("  --help, -h            显示帮助信息");
     println!("");
+    println!("IR Passes:");
+    for (name, description) in AVAILABLE_PASSES.iter() {
+        println!("  {:<22}{}", name, description);
+    }
+    println!("");
     println!("Examples:");
     println!("  cay-ir hello.cay");
     println!("  cay-ir -O3 hello.cay hello.ll");
+    println!("  cay-ir --target aarch64-unknown-linux-gnu hello.cay");
     println!("  cay-ir --opt-ir -O3 hello.cay         # 生成优化后的 IR");
     println!("  cay-ir --opt-ir --emit-optimized -O3 hello.cay  # 输出优化后的 IR");
+    println!("  cay-ir --passes=mem2reg,fold,dce --print-after hello.cay");
+    println!("  cay-ir --emit=cayir hello.cay         # 输出 hello.cayir");
+    println!("  cay-ir --passes=mem2reg hello.cayir hello.ll");
 }
 
 fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), String> {
@@ -118,15 +156,39 @@ fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), Strin
             }
             "--target" => {
                 if i + 1 < args.len() {
-                    options.target_os = args[i + 1].clone();
+                    let target = &args[i + 1];
+                    if target.contains('-') {
+                        // 目标三元组：操作系统由三元组决定
+                        let info = cavvy::target::TargetInfo::from_triple(target)?;
+                        options.target_os = info.os;
+                        options.target_triple = Some(target.clone());
+                    } else {
+                        options.target_os = target.clone();
+                        options.target_triple = None;
+                    }
                     i += 1;
                 } else {
-                    return Err("--target 需要一个参数，如 windows、linux、macos".to_string());
+                    return Err("--target 需要一个参数，如 windows、linux、macos 或目标三元组".to_string());
                 }
             }
             "--obfuscate" => {
                 options.obfuscate = true;
             }
+            "-fcheck-overflow" => {
+                options.check_overflow = true;
+            }
+            "-g" => {
+                options.debug_info = true;
+            }
+            "--legacy-codegen" => {
+                options.legacy_codegen = true;
+            }
+            "--print-devirt" => {
+                options.print_devirt = true;
+            }
+            "-Rpass=escape" => {
+                options.escape_remarks = true;
+            }
             "-o" => {
                 if i + 1 < args.len() {
                     output_file = Some(args[i + 1].clone());
@@ -135,6 +197,24 @@ fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), Strin
                     return Err("-o 需要一个输出文件参数".to_string());
                 }
             }
+            arg if arg.starts_with("--passes=") => {
+                options.passes = Some(arg["--passes=".len()..].to_string());
+            }
+            "--emit=llvm" => {
+                options.emit_cayir = false;
+            }
+            "--emit=cayir" => {
+                options.emit_cayir = true;
+            }
+            arg if arg.starts_with("--emit=") => {
+                return Err(format!("未知的输出格式: {}（可选 llvm、cayir）", &arg["--emit=".len()..]));
+            }
+            "--print-after" => {
+                options.print_after = Some(String::new());
+            }
+            arg if arg.starts_with("--print-after=") => {
+                options.print_after = Some(arg["--print-after=".len()..].to_string());
+            }
             arg if arg.starts_with("-f:") || arg.starts_with("--feature:") => {
                 let feature = if arg.starts_with("-f:") {
                     &arg[3..]
@@ -175,13 +255,26 @@ fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), Strin
         i += 1;
     }
 
+    if options.print_after.is_some() && options.passes.is_none() {
+        return Err("--print-after 需要与 --passes 一起使用".to_string());
+    }
+    if options.legacy_codegen && (options.passes.is_some() || options.emit_cayir) {
+        return Err("--legacy-codegen 不能与 --passes 或 --emit=cayir 一起使用".to_string());
+    }
+    if options.emit_cayir && options.optimize_ir {
+        return Err("--opt-ir 不能与 --emit=cayir 一起使用".to_string());
+    }
+
     let input_file = input_file.ok_or("需要指定输入文件")?;
+    if options.legacy_codegen && is_cayir_file(&input_file) {
+        return Err("--legacy-codegen 不能用于 .cayir 输入".to_string());
+    }
+    let extension = if options.emit_cayir { "cayir" } else { "ll" };
     let output_file = output_file.unwrap_or_else(|| {
-        if input_file.ends_with(".cay") {
-            input_file.replace(".cay", ".ll")
-        } else {
-            format!("{}.ll", input_file)
-        }
+        let stem = input_file.strip_suffix(".cayir")
+            .or_else(|| input_file.strip_suffix(".cay"))
+            .unwrap_or(&input_file);
+        format!("{}.{}", stem, extension)
     });
 
     Ok((options, input_file, output_file))
@@ -217,6 +310,68 @@ fn optimize_ir(ir_file: &str, opt_level: &str) -> Result<String, String> {
     Ok(optimized_file)
 }
 
+/// 根据 --passes / --print-after 构建 pass 管理器
+fn build_pass_manager(passes: &str, print_after: Option<&str>) -> Result<PassManager, String> {
+    let mut manager = PassManager::from_names(passes).map_err(|e| e.to_string())?;
+    match print_after {
+        Some("") => manager.print_after_all(),
+        Some(names) => {
+            let names: Vec<String> = names.split(',').map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).collect();
+            manager.print_after(&names).map_err(|e| e.to_string())?;
+        }
+        None => {}
+    }
+    Ok(manager)
+}
+
+fn is_cayir_file(path: &str) -> bool {
+    path.ends_with(".cayir")
+}
+
+/// 经结构化 IR 编译：IrBuilder（或 .cayir 文件）→ PassManager → LlvmBackend / .cayir
+///
+/// 未指定 pass 时不做优化，`--emit=cayir` 输出 IrBuilder 的原始 IR。
+fn compile_with_passes(
+    compiler: &Compiler,
+    source_path: &str,
+    output_path: &str,
+    manager: Option<PassManager>,
+    emit_cayir: bool,
+    debug_info: bool,
+) -> cavvy::error::cayResult<()> {
+    let mut module = if is_cayir_file(source_path) {
+        let text = fs::read_to_string(source_path).map_err(|e| cavvy::error::cayError::Io(e.to_string()))?;
+        let module = cavvy::ir::parse_cayir(&text)?;
+        let result = IrVerifier::new().verify(&module);
+        if !result.is_valid {
+            return Err(cavvy::error::codegen_error(format!("IR verification failed: {}", result.errors.join("; "))));
+        }
+        module
+    } else {
+        compiler.build_ir_module_file(source_path)?
+    };
+
+    if let Some(mut manager) = manager {
+        manager.run(&mut module)?;
+        for (pass, dump) in manager.dumps() {
+            eprintln!("*** IR Dump After {} ***", pass);
+            eprintln!("{}", dump);
+        }
+        for stats in manager.stats() {
+            println!("  [pass] {}", stats);
+        }
+    }
+
+    let output = if emit_cayir {
+        cavvy::ir::write_cayir(&module)
+    } else {
+        let mut backend = LlvmBackend::new();
+        backend.set_debug_info(debug_info);
+        backend.emit(&module)?
+    };
+    fs::write(output_path, output).map_err(|e| cavvy::error::cayError::Io(e.to_string()))
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
 
@@ -257,19 +412,44 @@ fn main() {
     // 创建多平台编译器配置
     let compiler_options = cavvy::CompilerOptions {
         target_os: options.target_os,
+        target_triple: options.target_triple,
         features: options.features,
         no_features: options.no_features,
         defines: options.defines,
         undefines: options.undefines,
         obfuscate: options.obfuscate,
         include_paths: Vec::new(),
+        runtime_checks: true,
+        check_overflow: options.check_overflow,
+        legacy_codegen: options.legacy_codegen,
+        debug_info: options.debug_info,
+        print_devirt: options.print_devirt,
+        escape_remarks: options.escape_remarks,
+        crate_type: cavvy::cabi::CrateType::Bin,
+        exports: Vec::new(),
     };
 
     // 编译 Cavvy → IR
     let compiler = Compiler::with_options(compiler_options);
     let temp_ir_file = format!("{}.tmp.ll", output_path.trim_end_matches(".ll"));
 
-    match compiler.compile_file(&source_path, &temp_ir_file) {
+    let manager = match &options.passes {
+        Some(passes) => match build_pass_manager(passes, options.print_after.as_deref()) {
+            Ok(manager) => Some(manager),
+            Err(e) => {
+                print_miette_error("cavvy::argument_error", &e, Some("使用 --help 查看可用的 IR pass"));
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let result = if manager.is_some() || options.emit_cayir || is_cayir_file(&source_path) {
+        compile_with_passes(&compiler, &source_path, &temp_ir_file, manager, options.emit_cayir, options.debug_info)
+    } else {
+        compiler.compile_file(&source_path, &temp_ir_file)
+    };
+
+    match result {
         Ok(_) => {
             println!("  [+] Cavvy → IR 编译成功");
         }
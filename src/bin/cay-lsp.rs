@@ -293,6 +293,8 @@ impl LanguageServer for CavvyLanguageServer {
                     ("let", "变量声明"),
                     ("auto", "自动类型推断"),
                     ("extern", "外部函数声明"),
+                    ("package", "包声明"),
+                    ("import", "导入其他包的声明"),
                     ("true", "真值"),
                     ("false", "假值"),
                     ("null", "空值"),
@@ -315,6 +317,9 @@ impl LanguageServer for CavvyLanguageServer {
                     ("readInt", "readInt()", "读取整数"),
                     ("readFloat", "readFloat()", "读取浮点数"),
                     ("readLine", "readLine()", "读取一行字符串"),
+                    ("assertEquals", "assertEquals(期望值, 实际值)", "测试断言：两值相等"),
+                    ("assertTrue", "assertTrue(条件)", "测试断言：条件为真"),
+                    ("fail", "fail(说明)", "测试断言：直接失败"),
                 ];
                 
                 for (name, insert, desc) in builtins {
@@ -326,6 +331,9 @@ impl LanguageServer for CavvyLanguageServer {
                         ..Default::default()
                     });
                 }
+
+                // 文档中声明的类、方法和字段（语法错误时使用部分语法树）
+                items.extend(self.collect_document_completions(content, &uri).await);
                 
                 return Ok(Some(CompletionResponse::Array(items)));
             }
@@ -395,16 +403,17 @@ impl CavvyLanguageServer {
             }
         };
 
-        // 3. 语法分析
-        let ast = match parser::parse(tokens) {
-            Ok(ast) => ast,
-            Err(e) => {
-                if let Some(diagnostic) = error_to_diagnostic(&e, &processed_content) {
+        // 3. 语法分析（带错误恢复，报告全部语法错误）
+        let output = parser::parse_recovering(tokens);
+        if output.has_errors() {
+            for e in &output.errors {
+                if let Some(diagnostic) = error_to_diagnostic(e, &processed_content) {
                     diagnostics.push(diagnostic);
                 }
-                return diagnostics;
             }
-        };
+            return diagnostics;
+        }
+        let ast = output.program;
 
         // 4. 语义分析
         let mut analyzer = semantic::SemanticAnalyzer::new();
@@ -427,6 +436,64 @@ impl CavvyLanguageServer {
         preprocessor::preprocess(content, file_path, base_dir.to_str().unwrap_or("."))
     }
 
+    /// 收集文档中声明的符号作为补全项
+    async fn collect_document_completions(&self, content: &str, uri: &str) -> Vec<CompletionItem> {
+        use cavvy::ast::ClassMember;
+
+        let file_path = uri.strip_prefix("file://").unwrap_or(uri);
+        let Ok(processed) = self.preprocess_content(content, file_path).await else {
+            return Vec::new();
+        };
+        let Ok(tokens) = lexer::lex(&processed) else {
+            return Vec::new();
+        };
+        let ast = parser::parse_recovering(tokens).program;
+
+        let mut items = Vec::new();
+        for class in &ast.classes {
+            items.push(CompletionItem {
+                label: class.name.clone(),
+                kind: Some(CompletionItemKind::CLASS),
+                detail: Some(format!("class {}", class.name)),
+                ..Default::default()
+            });
+            for member in &class.members {
+                match member {
+                    ClassMember::Method(method) => items.push(CompletionItem {
+                        label: method.name.clone(),
+                        kind: Some(CompletionItemKind::METHOD),
+                        detail: Some(format!("{}.{}", class.name, method.name)),
+                        ..Default::default()
+                    }),
+                    ClassMember::Field(field) => items.push(CompletionItem {
+                        label: field.name.clone(),
+                        kind: Some(CompletionItemKind::FIELD),
+                        detail: Some(format!("{}.{}", class.name, field.name)),
+                        ..Default::default()
+                    }),
+                    _ => {}
+                }
+            }
+        }
+        for interface in &ast.interfaces {
+            items.push(CompletionItem {
+                label: interface.name.clone(),
+                kind: Some(CompletionItemKind::INTERFACE),
+                detail: Some(format!("interface {}", interface.name)),
+                ..Default::default()
+            });
+        }
+        for func in &ast.top_level_functions {
+            items.push(CompletionItem {
+                label: func.name.clone(),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: Some(format!("{}()", func.name)),
+                ..Default::default()
+            });
+        }
+        items
+    }
+
     /// 解析文档符号
     async fn parse_symbols(&self, content: &str, uri: &str) -> std::result::Result<Vec<DocumentSymbol>, String> {
         let file_path = uri.strip_prefix("file://").unwrap_or(uri);
@@ -443,11 +510,8 @@ impl CavvyLanguageServer {
             Err(e) => return Err(format!("词法分析失败: {:?}", e)),
         };
         
-        // 语法分析
-        let ast = match parser::parse(tokens) {
-            Ok(a) => a,
-            Err(e) => return Err(format!("语法分析失败: {:?}", e)),
-        };
+        // 语法分析（有语法错误时仍使用部分语法树生成大纲）
+        let ast = parser::parse_recovering(tokens).program;
         
         let mut symbols = Vec::new();
         
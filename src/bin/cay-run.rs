@@ -22,6 +22,7 @@ struct RunOptions {
     lib_paths: Vec<String>,    // -L: 库搜索路径
     optimize: String,          // -O: 优化级别
     features: Vec<String>,     // -F/--feature: 启用的语言特性
+    legacy_codegen: bool,      // --legacy-codegen: 使用旧的字符串代码生成器
 }
 
 impl Default for RunOptions {
@@ -37,6 +38,7 @@ impl Default for RunOptions {
             lib_paths: Vec::new(),
             optimize: "-O2".to_string(),
             features: Vec::new(),
+            legacy_codegen: false,
         }
     }
 }
@@ -58,6 +60,7 @@ fn print_usage() {
     println!("  -l<lib>                链接指定库");
     println!("  -L<path>               添加库搜索路径");
     println!("  -O<level>              优化级别 (0, 1, 2, 3, s, z)");
+    println!("  --legacy-codegen       使用旧的字符串代码生成器");
     println!("  -F<feature>            启用语言特性 (如: -F=top_level_function)");
     println!("  --keep-temp            保留临时文件");
     println!("  --verbose, -v          显示详细编译信息");
@@ -123,6 +126,9 @@ fn parse_args(args: &[String]) -> Result<(RunOptions, String), String> {
                 "--no-run" => {
                     options.no_run = true;
                 }
+                "--legacy-codegen" => {
+                    options.legacy_codegen = true;
+                }
                 "--obfuscate" => {
                     options.obfuscate = true;
                 }
@@ -293,12 +299,21 @@ fn compile_cay_to_ir(source_path: &str, options: &RunOptions) -> Result<String,
     // 编译
     let compiler_options = cavvy::CompilerOptions {
         target_os: env::consts::OS.to_string(),
+        target_triple: None,
         features: options.features.clone(),
         no_features: Vec::new(),
         defines: Vec::new(),
         undefines: Vec::new(),
         obfuscate: options.obfuscate,
         include_paths: Vec::new(),
+        runtime_checks: true,
+        check_overflow: false,
+        legacy_codegen: options.legacy_codegen,
+        debug_info: false,
+        print_devirt: false,
+        escape_remarks: false,
+        crate_type: cavvy::cabi::CrateType::Bin,
+        exports: Vec::new(),
     };
 
     let compiler = Compiler::with_options(compiler_options);
@@ -3,6 +3,7 @@ use std::fs;
 use std::process;
 use std::path::{Path, PathBuf};
 use cavvy::Compiler;
+use cavvy::cabi::CrateType;
 use cavvy::error::{print_error_with_context, print_miette_error, print_tool_error, print_warning};
 
 /// 根据平台获取 llvm-minimal 下的 clang 路径
@@ -55,6 +56,7 @@ struct CompileOptions {
     opt_ir: bool,                 // --opt-ir: 优化 IR 阶段
     debug: bool,                  // -g
     keep_ir: bool,                // --keep-ir
+    compile_only: bool,           // -c
     extra_lib_paths: Vec<String>, // -L<path>
     extra_libs: Vec<String>,      // -l<lib>
     extra_ldflags: Vec<String>,   // --ldflags
@@ -84,10 +86,25 @@ struct CompileOptions {
     funroll_loops: bool,          // -funroll-loops
     fvectorize: bool,             // -fvectorize
     fslp_vectorize: bool,         // -fslp-vectorize
+    // 运行时检查
+    runtime_checks: bool,         // -fno-runtime-checks / --release-unchecked
+    check_overflow: bool,         // -fcheck-overflow
+    legacy_codegen: bool,         // --legacy-codegen
+    print_devirt: bool,           // --print-devirt
+    escape_remarks: bool,         // -Rpass=escape
     // 工具链选项
     use_llc_lld: bool,            // --use-llc-lld
     // 语言特性
     features: Vec<String>,        // -F/--feature=<feature>
+    // 预处理器宏
+    defines: Vec<String>,         // -D<name>[=<value>]
+    undefines: Vec<String>,       // -U<name>
+    // 库输出
+    crate_type: CrateType,        // --crate-type=<bin|staticlib|cdylib>
+    exports: Vec<String>,         // --export <name>
+    emit_header: Option<String>,  // --emit-header=<path>
+    // 多文件编译
+    extra_sources: Vec<String>,   // 第一个源文件之后的 .cay 文件和 --src-dir 中的文件
 }
 
 /// 根据当前操作系统自动选择默认目标平台
@@ -120,6 +137,7 @@ impl Default for CompileOptions {
             opt_ir: false,
             debug: false,
             keep_ir: false,
+            compile_only: false,
             extra_lib_paths: Vec::new(),
             extra_libs: Vec::new(),
             extra_ldflags: Vec::new(),
@@ -145,8 +163,19 @@ impl Default for CompileOptions {
             funroll_loops: false,
             fvectorize: false,
             fslp_vectorize: false,
+            runtime_checks: true,
+            check_overflow: false,
+            legacy_codegen: false,
+            print_devirt: false,
+            escape_remarks: false,
             use_llc_lld: false,
             features: Vec::new(),
+            defines: Vec::new(),
+            undefines: Vec::new(),
+            crate_type: CrateType::Bin,
+            exports: Vec::new(),
+            emit_header: None,
+            extra_sources: Vec::new(),
         }
     }
 }
@@ -154,6 +183,11 @@ impl Default for CompileOptions {
 fn print_usage() {
     println!("Cavvy Compiler v{}", VERSION);
     println!("Usage: cayc [options] <source_file.cay> [output_file.exe]");
+    println!("       cayc [options] <a.cay> <b.cay>... [output_file.exe]");
+    println!("       cayc [options] --src-dir <dir> [output_file.exe]");
+    println!("       cayc [options] <input_file.ll> [output_file.exe]");
+    println!("       cayc -c [options] <source_file.cay> [output_file.ll]");
+    println!("       cayc --crate-type=staticlib|cdylib [options] <source_file.cay> [output_lib]");
     println!("");
     println!("Optimization Options:");
     println!("  -O0, -O1, -O2, -O3    优化级别 (默认: -O2)");
@@ -177,8 +211,10 @@ fn print_usage() {
     println!("  -fcs-profile-generate  上下文敏感的性能分析");
     println!("");
     println!("Code Generation:");
-    println!("  -g                    生成调试信息");
+    println!("  -g                    生成调试信息（与 --legacy-codegen 同用时只有函数和行号，没有变量和类型）");
     println!("  --keep-ir             保留中间 IR 文件 (.ll)");
+    println!("  -c                    分离编译：只生成本文件的 IR 和接口文件 (.cai)，#include 的文件须先编译");
+    println!("  --src-dir <dir>       编译目录下的所有 .cay 文件（多文件编译，文件之间用 package/import 控制可见性）");
     println!("  -I<path>              添加包含搜索路径（供 #include 使用）");
     println!("  -L<path>              添加库搜索路径");
     println!("  -l<lib>               链接额外的库");
@@ -189,10 +225,27 @@ fn print_usage() {
     println!("  --use-llc-lld         使用 llc+lld 工具链（不使用 clang）");
     println!("  -fno-exceptions       禁用异常处理");
     println!("  -fno-rtti             禁用运行时类型信息");
+    println!("  -fno-runtime-checks   不生成空指针、类型转换和除零运行时检查");
+    println!("  --release-unchecked   等价于 -O3 -fno-runtime-checks");
+    println!("  -fcheck-overflow      int/long 算术溢出时 panic（默认按补码回绕）");
+    println!("  --legacy-codegen      使用旧的字符串代码生成器（不经过结构化 IR）");
+    println!("  --print-devirt        列出被去虚拟化为直接调用的接口调用点");
+    println!("  -Rpass=escape         输出每个 new 是否分配在栈上及逃逸原因");
+    println!("");
+    println!("Library Output:");
+    println!("  --crate-type=<type>   输出类型: bin (默认), staticlib (.a/.lib), cdylib (.so/.dylib/.dll)");
+    println!("                        与 -c 同时使用时，该单元作为库的根单元（分离编译的库）");
+    println!("  --export <name>       以 C ABI 导出函数 (类名.方法名 或 函数名)，可重复；标记 export 的函数总是导出");
+    println!("  --emit-header=<path>  为导出的函数生成 C 头文件");
     println!("");
     println!("Language Features:");
     println!("  -F<feature>, --feature=<feature>  启用语言特性");
     println!("                                     top_level_function - 允许顶层函数");
+    println!("                                     null_safety        - 空安全问题报告为错误（默认只警告）");
+    println!("");
+    println!("Preprocessor:");
+    println!("  -D<name>[=<value>]    预定义宏，等价于 #define name value");
+    println!("  -U<name>              取消宏定义（包括平台宏）");
     println!("");
     println!("Other Options:");
     println!("  --version, -v         显示版本号");
@@ -201,15 +254,18 @@ fn print_usage() {
     println!("Examples:");
     println!("  cayc hello.cay");
     println!("  cayc -O3 hello.cay hello.exe");
+    println!("  cayc main.cay util.cay app.exe");
     println!("  cayc --opt-ir -O3 --lto=full hello.cay");
     println!("  cayc -O3 -march=native -mtune=native -fvectorize hello.cay");
     println!("  cayc --static -O2 -L./libs -lmylib app.cay app.exe");
+    println!("  cayc --crate-type=staticlib --emit-header=mathlib.h mathlib.cay");
 }
 
 fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), String> {
     let mut options = CompileOptions::default();
     let mut input_file: Option<String> = None;
     let mut output_file: Option<String> = None;
+    let mut src_dir_files: Vec<String> = Vec::new();
     let mut i = 1;
 
     while i < args.len() {
@@ -236,6 +292,9 @@ fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), Strin
             "--keep-ir" => {
                 options.keep_ir = true;
             }
+            "-c" => {
+                options.compile_only = true;
+            }
             "--static" => {
                 options.static_link = true;
             }
@@ -248,6 +307,28 @@ fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), Strin
             "-fno-rtti" => {
                 options.fno_rtti = true;
             }
+            "-fno-runtime-checks" => {
+                options.runtime_checks = false;
+            }
+            "-fcheck-overflow" => {
+                options.check_overflow = true;
+            }
+            "-fno-check-overflow" => {
+                options.check_overflow = false;
+            }
+            "--legacy-codegen" => {
+                options.legacy_codegen = true;
+            }
+            "--print-devirt" => {
+                options.print_devirt = true;
+            }
+            "-Rpass=escape" => {
+                options.escape_remarks = true;
+            }
+            "--release-unchecked" => {
+                options.optimization = "-O3".to_string();
+                options.runtime_checks = false;
+            }
             "-fomit-frame-pointer" => {
                 options.fomit_frame_pointer = true;
             }
@@ -280,8 +361,16 @@ fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), Strin
                 if i >= args.len() {
                     return Err("--target 需要参数".to_string());
                 }
+                cavvy::target::TargetInfo::from_triple(&args[i])?;
                 options.target = args[i].clone();
             }
+            "--export" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--export 需要函数名参数".to_string());
+                }
+                options.exports.push(args[i].clone());
+            }
             "--ldflags" => {
                 i += 1;
                 if i >= args.len() {
@@ -291,6 +380,15 @@ fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), Strin
                     options.extra_ldflags.push(flag.to_string());
                 }
             }
+            "--src-dir" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--src-dir 需要目录参数".to_string());
+                }
+                let files = cavvy::source_files(Path::new(&args[i]))
+                    .map_err(|e| format!("无法读取源文件目录 '{}': {}", args[i], e))?;
+                src_dir_files.extend(files.into_iter().map(|f| f.to_string_lossy().to_string()));
+            }
             "--cflags" => {
                 i += 1;
                 if i >= args.len() {
@@ -314,6 +412,14 @@ fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), Strin
                     _ => return Err(format!("未知的 LTO 类型: {}", lto_type)),
                 }
             }
+            _ if arg.starts_with("--crate-type=") => {
+                let name = &arg["--crate-type=".len()..];
+                options.crate_type = CrateType::parse(name)
+                    .ok_or_else(|| format!("未知的输出类型: {}（可选 bin、staticlib、cdylib）", name))?;
+            }
+            _ if arg.starts_with("--emit-header=") => {
+                options.emit_header = Some(arg["--emit-header=".len()..].to_string());
+            }
             _ if arg.starts_with("-march=") => {
                 options.march = Some(arg[7..].to_string());
             }
@@ -368,6 +474,30 @@ fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), Strin
                 };
                 options.extra_libs.push(lib);
             }
+            _ if arg.starts_with("-D") => {
+                let define = if arg.len() > 2 {
+                    arg[2..].to_string()
+                } else {
+                    i += 1;
+                    if i >= args.len() {
+                        return Err("-D 需要宏名参数".to_string());
+                    }
+                    args[i].clone()
+                };
+                options.defines.push(define);
+            }
+            _ if arg.starts_with("-U") => {
+                let undefine = if arg.len() > 2 {
+                    arg[2..].to_string()
+                } else {
+                    i += 1;
+                    if i >= args.len() {
+                        return Err("-U 需要宏名参数".to_string());
+                    }
+                    args[i].clone()
+                };
+                options.undefines.push(undefine);
+            }
             _ if arg.starts_with("-F") => {
                 // -F<feature> 或 -F=<feature> 格式
                 let feature = if arg.len() > 2 {
@@ -393,8 +523,11 @@ fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), Strin
                 if arg.starts_with('-') {
                     return Err(format!("未知选项: {}", arg));
                 }
+                let is_source = |path: &str| path.ends_with(".cay");
                 if input_file.is_none() {
                     input_file = Some(arg.clone());
+                } else if is_source(arg) && input_file.as_deref().is_some_and(is_source) {
+                    options.extra_sources.push(arg.clone());
                 } else if output_file.is_none() {
                     output_file = Some(arg.clone());
                 } else {
@@ -405,19 +538,41 @@ fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), Strin
         i += 1;
     }
 
+    // --src-dir 中的文件排在命令行给出的源文件之后，已给出的文件不重复编译；
+    // 此时唯一的非 .cay 位置参数是输出文件
+    if !src_dir_files.is_empty() && output_file.is_none()
+        && input_file.as_deref().is_some_and(|f| !f.ends_with(".cay")) {
+        output_file = input_file.take();
+    }
+    for file in src_dir_files {
+        let canonical = fs::canonicalize(&file).ok();
+        let listed = input_file.iter().chain(&options.extra_sources)
+            .any(|given| fs::canonicalize(given).ok() == canonical);
+        if input_file.is_none() {
+            input_file = Some(file);
+        } else if !listed {
+            options.extra_sources.push(file);
+        }
+    }
+    if !options.extra_sources.is_empty() && options.compile_only {
+        return Err("-c 分离编译只接受一个源文件".to_string());
+    }
+
     let input_file = input_file.ok_or("需要指定输入文件")?;
+    if !options.extra_sources.is_empty() && !input_file.ends_with(".cay") {
+        return Err(format!("多文件编译只接受 .cay 源文件: {}", input_file));
+    }
     let output_file = output_file.unwrap_or_else(|| {
         let stem = Path::new(&input_file)
             .file_stem()
             .and_then(|stem| stem.to_str())
             .unwrap_or("output");
         
-        // 根据目标平台选择扩展名
-        if options.target.contains("windows") || options.target.contains("mingw") {
-            format!("{}.exe", stem)
+        // 根据目标平台和输出类型选择扩展名（Linux 等系统的可执行文件不使用 .exe 扩展名）
+        if options.compile_only {
+            format!("{}.ll", stem)
         } else {
-            // Linux和其他系统不使用.exe扩展名
-            stem.to_string()
+            options.crate_type.output_filename(stem, &options.target)
         }
     });
 
@@ -471,9 +626,17 @@ fn main() {
         .with_extension("ll")
         .to_string_lossy()
         .to_string();
+    // 输入已经是 LLVM IR（如 cavly 链接后的编译单元）时跳过 Cavvy → IR，且不删除输入文件
+    let ir_input = Path::new(&source_path).extension().is_some_and(|ext| ext == "ll");
+    let same_ir_file = ir_input && fs::canonicalize(&source_path).ok() == fs::canonicalize(&ir_file).ok();
+    let remove_ir = !options.keep_ir && !same_ir_file;
 
     println!("Cavvy 编译器 v{}", VERSION);
-    println!("源文件: {}", source_path);
+    if options.extra_sources.is_empty() {
+        println!("源文件: {}", source_path);
+    } else {
+        println!("源文件: {}, {}", source_path, options.extra_sources.join(", "));
+    }
     println!("输出: {}", exe_output);
     println!("优化级别: {}", options.optimization);
 
@@ -530,47 +693,38 @@ fn main() {
     if options.debug {
         println!("调试信息: 启用");
     }
+    if options.debug && options.legacy_codegen {
+        print_warning("--legacy-codegen 的调试信息只有函数和行号，没有局部变量和类类型；去掉 --legacy-codegen 以生成完整的调试信息");
+    }
     if options.keep_ir {
         println!("保留 IR: 是");
     }
     if options.static_link {
         println!("链接模式: 静态链接");
     }
+    if options.crate_type.is_library() {
+        println!("输出类型: {}", options.crate_type.name());
+    }
     println!("");
 
     // 1. Cavvy → IR
-    println!("[1] Cavvy → IR 编译...");
-    let source = match fs::read_to_string(&source_path) {
-        Ok(content) => content,
-        Err(e) => {
-            print_miette_error(
-                "cavvy::io_error",
-                &format!("无法读取源文件 '{}': {}", source_path, e),
-                Some("请检查文件路径是否正确，文件是否存在")
-            );
-            process::exit(1);
-        }
-    };
-
-    // 创建编译器选项
-    let compiler_options = cavvy::CompilerOptions {
-        target_os: std::env::consts::OS.to_string(),
-        features: options.features.clone(),
-        no_features: Vec::new(),
-        defines: Vec::new(),
-        undefines: Vec::new(),
-        obfuscate: false,
-        include_paths: options.include_paths.clone(),
-    };
-    let compiler = cavvy::Compiler::with_options(compiler_options);
-    match compiler.compile_file(&source_path, &ir_file) {
-        Ok(_) => {
-            println!("  [+] Cavvy 编译成功");
-        }
-        Err(e) => {
-            print_error_with_context(&e, &source, &source_path);
-            process::exit(1);
+    if ir_input {
+        println!("[1] 使用 LLVM IR: {}", source_path);
+        if !same_ir_file {
+            if let Err(e) = fs::copy(&source_path, &ir_file) {
+                print_miette_error(
+                    "cavvy::io_error",
+                    &format!("无法读取 IR 文件 '{}': {}", source_path, e),
+                    Some("请检查文件路径是否正确，文件是否存在")
+                );
+                process::exit(1);
+            }
         }
+    } else {
+        compile_source(&options, &source_path, &ir_file);
+    }
+    if options.compile_only {
+        return;
     }
 
     // 2. IR 优化 (如果启用)
@@ -588,10 +742,14 @@ fn main() {
         }
     }
 
-    // 3. IR → EXE (调用ir2exe)
+    // 3. IR → EXE/库 (调用ir2exe)
     println!("");
     let step_num = if options.opt_ir { "[3]" } else { "[2]" };
-    println!("{} IR → EXE 编译...", step_num);
+    if options.crate_type.is_library() {
+        println!("{} IR → {} 编译...", step_num, options.crate_type.name());
+    } else {
+        println!("{} IR → EXE 编译...", step_num);
+    }
 
     let current_exe = match env::current_exe() {
         Ok(path) => path,
@@ -632,7 +790,9 @@ fn main() {
                 &format!("找不到 ir2exe 或 ir2exe.exe\n搜索位置:\n{}", paths_str),
                 Some("请确保 ir2exe 与 cayc 在同一目录下")
             );
-            let _ = fs::remove_file(&ir_file);
+            if remove_ir {
+                let _ = fs::remove_file(&ir_file);
+            }
             process::exit(1);
         }
     };
@@ -648,6 +808,11 @@ fn main() {
     // 基础优化
     ir2exe_args.push(options.optimization.clone());
 
+    // 输出类型
+    if options.crate_type.is_library() {
+        ir2exe_args.push(format!("--crate-type={}", options.crate_type.name()));
+    }
+
     // LTO
     if options.lto {
         if options.lto_thin {
@@ -774,7 +939,7 @@ fn main() {
         .output()
         .unwrap_or_else(|e| {
             print_tool_error("ir2exe", &format!("执行失败: {}", e), Some("请检查 ir2exe 是否正确安装"));
-            if !options.keep_ir {
+            if remove_ir {
                 let _ = fs::remove_file(&ir_file);
             }
             process::exit(1);
@@ -783,14 +948,14 @@ fn main() {
     if !output.status.success() {
         let error_msg = String::from_utf8_lossy(&output.stderr);
         print_tool_error("ir2exe", "IR→EXE编译失败", Some(&error_msg));
-        if !options.keep_ir {
+        if remove_ir {
             let _ = fs::remove_file(&ir_file);
         }
         process::exit(1);
     }
 
     // 清理IR文件（如果不保留）
-    if !options.keep_ir {
+    if remove_ir {
         if let Err(e) = fs::remove_file(&ir_file) {
             print_warning(&format!("无法清理临时文件 {}: {}", ir_file, e));
         }
@@ -803,3 +968,78 @@ fn main() {
     println!("[+] 编译完成!");
     println!("生成: {}", exe_output);
 }
+
+/// 第 1 步：Cavvy → IR；`-c` 时分离编译，同时生成接口文件
+fn compile_source(options: &CompileOptions, source_path: &str, ir_file: &str) {
+    println!("[1] Cavvy → IR 编译...");
+    let source = match fs::read_to_string(source_path) {
+        Ok(content) => content,
+        Err(e) => {
+            print_miette_error(
+                "cavvy::io_error",
+                &format!("无法读取源文件 '{}': {}", source_path, e),
+                Some("请检查文件路径是否正确，文件是否存在")
+            );
+            process::exit(1);
+        }
+    };
+
+    // 创建编译器选项
+    let compiler_options = cavvy::CompilerOptions {
+        target_os: std::env::consts::OS.to_string(),
+        target_triple: Some(options.target.clone()),
+        features: options.features.clone(),
+        no_features: Vec::new(),
+        defines: options.defines.clone(),
+        undefines: options.undefines.clone(),
+        obfuscate: false,
+        include_paths: options.include_paths.clone(),
+        runtime_checks: options.runtime_checks,
+        check_overflow: options.check_overflow,
+        legacy_codegen: options.legacy_codegen,
+        debug_info: options.debug,
+        print_devirt: options.print_devirt,
+        escape_remarks: options.escape_remarks,
+        crate_type: options.crate_type,
+        exports: options.exports.clone(),
+    };
+    let compiler = cavvy::Compiler::with_options(compiler_options);
+    if options.compile_only {
+        match compiler.compile_unit_file(source_path, ir_file) {
+            Ok(interface_file) => {
+                println!("  [+] Cavvy 编译成功");
+                println!("");
+                println!("[+] 生成: {}", ir_file);
+                println!("接口文件: {}", interface_file.display());
+            }
+            Err(e) => {
+                print_error_with_context(&e, &source, source_path);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    let sources: Vec<String> = std::iter::once(source_path.to_string())
+        .chain(options.extra_sources.iter().cloned())
+        .collect();
+    match compiler.compile_files(&sources, ir_file) {
+        Ok(_) => {
+            println!("  [+] Cavvy 编译成功");
+        }
+        Err(e) => {
+            print_error_with_context(&e, &source, source_path);
+            process::exit(1);
+        }
+    }
+    if let Some(header) = &options.emit_header {
+        match compiler.write_c_header_files(&sources, header) {
+            Ok(_) => {
+                println!("  [+] 生成 C 头文件: {}", header);
+            }
+            Err(e) => {
+                print_error_with_context(&e, &source, source_path);
+                process::exit(1);
+            }
+        }
+    }
+}
@@ -2,7 +2,9 @@ use std::env;
 use std::process;
 use std::path::{Path, PathBuf, Component};
 use std::fs;
+use cavvy::cabi::CrateType;
 use cavvy::error::{print_miette_error, print_tool_error, print_warning};
+use cavvy::target::TargetInfo;
 
 /// 规范化路径，去除 . 和 ..
 
@@ -374,6 +376,23 @@ fn get_llvm_minimal_paths(exe_dir: &Path, sub_path: &str) -> Vec<PathBuf> {
     paths
 }
 
+/// clang 的目标参数（riscv64 等目标附带默认的 -march/-mabi）
+fn clang_target_args(target: &str) -> Vec<String> {
+    match TargetInfo::from_triple(target) {
+        Ok(info) => info.clang_args(),
+        Err(_) => vec!["-target".to_string(), target.to_string()],
+    }
+}
+
+/// llc 的目标参数：三元组、默认 CPU 特性和 ABI；`TargetInfo` 不支持的目标（如 wasm）只指定三元组
+fn llc_target_args(target: &str) -> Vec<String> {
+    match TargetInfo::from_triple(target) {
+        Ok(info) => info.llc_args(),
+        Err(_) if target.contains("wasm") => vec!["-mtriple=wasm32-unknown-unknown".to_string()],
+        Err(_) => vec![format!("-mtriple={}", target)],
+    }
+}
+
 /// 根据目标平台获取对应的 lld 链接器名称
 fn get_lld_linker_name(target: &str) -> &'static str {
     if target.contains("windows") || target.contains("mingw") {
@@ -487,6 +506,8 @@ struct CompileOptions {
     fslp_vectorize: bool,         // -fslp-vectorize
     // 工具链选择
     use_llc_lld: bool,            // --use-llc-lld
+    // 输出类型
+    crate_type: CrateType,        // --crate-type=<bin|staticlib|cdylib>
 }
 
 /// 根据当前操作系统自动选择默认目标平台
@@ -542,6 +563,7 @@ impl Default for CompileOptions {
             fvectorize: false,
             fslp_vectorize: false,
             use_llc_lld: false,
+            crate_type: CrateType::Bin,
         }
     }
 }
@@ -606,6 +628,7 @@ fn print_usage() {
     println!("  --target <target>     指定目标平台 (默认: {})", default_target);
     println!("  --fno-exceptions      禁用异常处理");
     println!("  --fno-rtti            禁用运行时类型信息");
+    println!("  --crate-type=<type>   输出类型: bin (默认), staticlib (目标文件打包为静态库), cdylib (动态库)");
     println!("");
     println!("Toolchain Options:");
     println!("  --use-llc-lld         强制使用 llc + lld-link 工具链（而不是 clang）");
@@ -776,6 +799,11 @@ fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), Strin
                     _ => return Err(format!("未知的 LTO 类型: {}", lto_type)),
                 }
             }
+            _ if arg.starts_with("--crate-type=") => {
+                let name = &arg["--crate-type=".len()..];
+                options.crate_type = CrateType::parse(name)
+                    .ok_or_else(|| format!("未知的输出类型: {}（可选 bin、staticlib、cdylib）", name))?;
+            }
             _ if arg.starts_with("--march=") => {
                 options.march = Some(arg[8..].to_string());
             }
@@ -847,16 +875,8 @@ fn parse_args(args: &[String]) -> Result<(CompileOptions, String, String), Strin
             .and_then(|stem| stem.to_str())
             .unwrap_or("output");
         
-        // 根据目标平台选择扩展名
-        if options.target.contains("windows") || options.target.contains("mingw") {
-            format!("{}.exe", stem)
-        } else if options.target.contains("darwin") {
-            // macOS 通常没有扩展名，或者使用 .app
-            stem.to_string()
-        } else {
-            // Linux 和其他 Unix-like 系统
-            stem.to_string()
-        }
+        // 根据目标平台和输出类型选择扩展名（macOS、Linux 等系统的可执行文件没有扩展名）
+        options.crate_type.output_filename(stem, &options.target)
     });
 
     Ok((options, input_file, output_file))
@@ -1017,6 +1037,9 @@ fn main() {
     if options.position_independent {
         println!("位置无关代码: 启用");
     }
+    if options.crate_type.is_library() {
+        println!("输出类型: {}", options.crate_type.name());
+    }
     if !options.extra_lib_paths.is_empty() {
         println!("额外库路径: {:?}", options.extra_lib_paths);
     }
@@ -1045,6 +1068,12 @@ fn main() {
         println!("  [I] 已加载源映射: {} 个映射点", source_map.mappings.len());
     }
 
+    // 静态库只需要目标文件和归档工具，不需要链接器
+    if options.crate_type == CrateType::StaticLib {
+        compile_static_library(&input_file, &output_file, &options, &source_map);
+        return;
+    }
+
 // 检测工具链
     if options.use_llc_lld {
         // 强制使用 llc + 对应平台的 lld
@@ -1124,7 +1153,7 @@ fn compile_with_clang(
     let mut cmd = process::Command::new(clang_exe);
     cmd.arg(&input_file)
         .arg("-o").arg(&output_file)
-        .arg("-target").arg(&options.target)
+        .args(clang_target_args(&options.target))
         .arg(&options.optimization)
         .arg("-Wno-override-module");
 
@@ -1180,8 +1209,10 @@ fn compile_with_clang(
         cmd.arg("-g");
     }
 
-    // 位置无关代码
-    if options.position_independent {
+    // 位置无关代码（动态库总是位置无关的）
+    if options.crate_type == CrateType::CDylib {
+        cmd.arg("-shared").arg("-fPIC");
+    } else if options.position_independent {
         cmd.arg("-fPIC");
     }
 
@@ -1365,19 +1396,16 @@ fn compile_with_llc_lld(
     let is_windows = options.target.contains("windows") || options.target.contains("mingw");
     let is_darwin = options.target.contains("darwin") || options.target.contains("macos");
     let is_wasm = options.target.contains("wasm");
-    
-    if is_windows {
-        llc_cmd.arg("-mtriple=x86_64-w64-mingw32");
-    } else if is_darwin {
-        llc_cmd.arg("-mtriple=x86_64-apple-darwin");
-    } else if is_wasm {
-        llc_cmd.arg("-mtriple=wasm32-unknown-unknown");
-    }
+    llc_cmd.args(llc_target_args(&options.target));
     
     // CPU 指令集
     if let Some(ref march) = options.march {
         llc_cmd.arg(format!("-mcpu={}", march));
     }
+
+    if options.crate_type == CrateType::CDylib || options.position_independent {
+        llc_cmd.arg("-relocation-model=pic");
+    }
     
     let llc_output = llc_cmd.output()
         .unwrap_or_else(|e| {
@@ -1400,6 +1428,9 @@ fn compile_with_llc_lld(
     let linker_name = get_lld_linker_name(&options.target);
     println!("  [2] OBJ → EXE ({})...", linker_name);
     let mut lld_cmd = process::Command::new(lld_exe);
+    if options.crate_type == CrateType::CDylib {
+        lld_cmd.arg(if is_darwin { "-dylib" } else { "-shared" });
+    }
     
     if is_windows {
         // Windows/MinGW: 使用 GNU ld 风格参数 (ld.lld)
@@ -1567,11 +1598,161 @@ fn compile_with_llc_lld(
     print_completion_message(output_file, options);
 }
 
+/// 查找归档工具：先查 PATH 和编译器所在目录的 llvm-ar，再回退到系统的 ar
+fn find_archiver() -> Result<PathBuf, String> {
+    if let Ok(output) = process::Command::new("llvm-ar").arg("--version").output() {
+        if output.status.success() {
+            return Ok(PathBuf::from("llvm-ar"));
+        }
+    }
+
+    if let Ok(exe_path) = env::current_exe() {
+        if let Some(exe_dir) = exe_path.parent() {
+            let sub_path = if cfg!(target_os = "windows") {
+                "llvm-minimal/bin/llvm-ar.exe"
+            } else {
+                "llvm-minimal/bin-linux/llvm-ar"
+            };
+            for path in get_llvm_minimal_paths(exe_dir, sub_path) {
+                if path.exists() {
+                    return Ok(path);
+                }
+            }
+        }
+    }
+
+    if let Ok(output) = process::Command::new("ar").arg("--version").output() {
+        if output.status.success() {
+            return Ok(PathBuf::from("ar"));
+        }
+    }
+
+    Err("找不到归档工具 (llvm-ar 或 ar)。请确保 LLVM 已安装并在 PATH 中，或将 llvm-minimal 放在编译器同目录下。".to_string())
+}
+
+/// 编译静态库：IR → 位置无关的目标文件（clang，不可用时用 llc），再用归档工具打包
+fn compile_static_library(
+    input_file: &str,
+    output_file: &str,
+    options: &CompileOptions,
+    source_map: &IRSourceMap,
+) {
+    let is_windows = options.target.contains("windows") || options.target.contains("mingw");
+    let obj_file = format!("{}.{}", input_file, if is_windows { "obj" } else { "o" });
+
+    // 步骤1: IR → OBJ
+    let clang = if options.use_llc_lld { None } else { find_clang().ok() };
+    let (tool, mut cmd) = match &clang {
+        Some(clang_exe) => {
+            println!("  [1] IR → OBJ (clang)...");
+            let mut cmd = process::Command::new(clang_exe);
+            cmd.arg("-c").arg(input_file)
+                .arg("-o").arg(&obj_file)
+                .args(clang_target_args(&options.target))
+                .arg(&options.optimization)
+                .arg("-fPIC")
+                .arg("-Wno-override-module");
+            if let Some(ref march) = options.march {
+                cmd.arg(format!("-march={}", march));
+            }
+            if options.debug {
+                cmd.arg("-g");
+            }
+            for flag in &options.extra_cflags {
+                cmd.arg(flag);
+            }
+            ("clang", cmd)
+        }
+        None => {
+            let llc_exe = find_llc().unwrap_or_else(|e| {
+                print_tool_error("llc", &e, Some("请确保 LLVM/Clang 已正确安装"));
+                process::exit(1);
+            });
+            println!("  [1] IR → OBJ (llc)...");
+            let opt_level = match options.optimization.as_str() {
+                "-O0" => "-O=0",
+                "-O1" => "-O=1",
+                "-O3" => "-O=3",
+                _ => "-O=2",
+            };
+            let mut cmd = process::Command::new(llc_exe);
+            cmd.arg("-filetype=obj")
+                .arg("-relocation-model=pic")
+                .args(llc_target_args(&options.target))
+                .arg(opt_level)
+                .arg("-o").arg(&obj_file)
+                .arg(input_file);
+            if let Some(ref march) = options.march {
+                cmd.arg(format!("-mcpu={}", march));
+            }
+            ("llc", cmd)
+        }
+    };
+    let output = cmd.output().unwrap_or_else(|e| {
+        print_tool_error(tool, &format!("执行失败: {}", e), Some("请检查 LLVM 是否正确安装"));
+        process::exit(1);
+    });
+    if !output.status.success() {
+        let error_msg = String::from_utf8_lossy(&output.stderr);
+        let error_msg = if tool == "clang" && !source_map.mappings.is_empty() {
+            add_clang_error_notice(&remap_clang_error(&error_msg, source_map, input_file))
+        } else {
+            error_msg.to_string()
+        };
+        print_tool_error(
+            tool,
+            &format!("编译失败 (exit code: {})", output.status.code().unwrap_or(-1)),
+            Some(&error_msg)
+        );
+        let _ = fs::remove_file(&obj_file);
+        process::exit(1);
+    }
+
+    // 步骤2: OBJ → 静态库（ar 会追加到已有的归档中，先删除旧文件）
+    let archiver = find_archiver().unwrap_or_else(|e| {
+        print_tool_error("llvm-ar", &e, Some("请确保 LLVM 已正确安装"));
+        let _ = fs::remove_file(&obj_file);
+        process::exit(1);
+    });
+    println!("  [2] OBJ → LIB ({})...", archiver.display());
+    let _ = fs::remove_file(output_file);
+    let output = process::Command::new(&archiver)
+        .arg("rcs")
+        .arg(output_file)
+        .arg(&obj_file)
+        .output();
+    let _ = fs::remove_file(&obj_file);
+    match output {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => {
+            print_tool_error(
+                "llvm-ar",
+                &format!("打包失败 (exit code: {})", output.status.code().unwrap_or(-1)),
+                Some(&String::from_utf8_lossy(&output.stderr))
+            );
+            process::exit(1);
+        }
+        Err(e) => {
+            print_tool_error("llvm-ar", &format!("执行失败: {}", e), Some("请检查 llvm-ar 是否正确安装"));
+            process::exit(1);
+        }
+    }
+
+    let lib_size = fs::metadata(output_file)
+        .map(|m| m.len() as f64 / 1024.0)
+        .unwrap_or(0.0);
+    println!("  [+] 生成: {} ({:.1} KB)", output_file, lib_size);
+
+    print_completion_message(output_file, options);
+}
+
 /// 打印完成消息
 fn print_completion_message(output_file: &str, options: &CompileOptions) {
     println!("");
-    println!("[I] 提示: 使用 './{}' 可直接运行并测速", output_file);
-    println!("");
+    if !options.crate_type.is_library() {
+        println!("[I] 提示: 使用 './{}' 可直接运行并测速", output_file);
+        println!("");
+    }
     
     // 根据目标平台显示完成消息
     let mode_str = if options.target.contains("windows") || options.target.contains("mingw") {
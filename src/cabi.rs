@@ -0,0 +1,295 @@
+//! C ABI 导出：库输出（staticlib/cdylib）
+//!
+//! 导出的函数是标记 `export` 的函数，以及 `exports` 列表中列出的函数（`类名.方法名` 或函数名）。
+//! 只能导出静态方法和顶层函数，参数和返回值必须能用 C 类型表示；导出名就是函数名，不做名称修饰，
+//! IR 中为每个导出函数生成一个同名的包装函数，转发到 Cavvy 函数。
+
+use crate::ast::{ClassMember, Modifier, Program};
+use crate::error::{cayResult, codegen_error, semantic_error, SourceLocation};
+use crate::types::{ParameterInfo, Type};
+use std::fmt::Write;
+
+/// 输出类型（`--crate-type`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CrateType {
+    /// 可执行文件
+    #[default]
+    Bin,
+    /// 静态库（.a/.lib）
+    StaticLib,
+    /// 动态库（.so/.dylib/.dll）
+    CDylib,
+}
+
+impl CrateType {
+    /// 解析 `--crate-type` 的值
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "bin" => Some(CrateType::Bin),
+            "staticlib" => Some(CrateType::StaticLib),
+            "cdylib" => Some(CrateType::CDylib),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CrateType::Bin => "bin",
+            CrateType::StaticLib => "staticlib",
+            CrateType::CDylib => "cdylib",
+        }
+    }
+
+    pub fn is_library(&self) -> bool {
+        *self != CrateType::Bin
+    }
+
+    /// 默认输出文件名，扩展名按目标三元组选择（与 cavly 的库文件命名一致）
+    pub fn output_filename(&self, stem: &str, target: &str) -> String {
+        let windows = target.contains("windows") || target.contains("mingw");
+        let apple = target.contains("apple") || target.contains("darwin");
+        match self {
+            CrateType::Bin if windows => format!("{}.exe", stem),
+            CrateType::Bin => stem.to_string(),
+            CrateType::StaticLib if windows => format!("{}.lib", stem),
+            CrateType::StaticLib => format!("lib{}.a", stem),
+            CrateType::CDylib if windows => format!("{}.dll", stem),
+            CrateType::CDylib if apple => format!("lib{}.dylib", stem),
+            CrateType::CDylib => format!("lib{}.so", stem),
+        }
+    }
+}
+
+/// 以 C ABI 导出的函数
+#[derive(Debug, Clone)]
+pub struct ExportedFunction {
+    /// 导出名（C 符号名）
+    pub name: String,
+    /// 所在的类；顶层函数为 None
+    pub class_name: Option<String>,
+    pub params: Vec<ParameterInfo>,
+    pub return_type: Type,
+    pub loc: SourceLocation,
+}
+
+/// 收集要导出的函数（按声明顺序），`names` 是 `exports` 配置中列出的函数
+pub fn collect_exports(program: &Program, names: &[String]) -> cayResult<Vec<ExportedFunction>> {
+    let mut exports: Vec<ExportedFunction> = Vec::new();
+    let mut found = vec![false; names.len()];
+    let mut listed = |qualified: &str, name: &str| -> bool {
+        let mut any = false;
+        for (i, n) in names.iter().enumerate() {
+            if n == qualified || n == name {
+                found[i] = true;
+                any = true;
+            }
+        }
+        any
+    };
+
+    for func in &program.top_level_functions {
+        if func.modifiers.contains(&Modifier::Export) | listed(&func.name, &func.name) {
+            exports.push(ExportedFunction {
+                name: func.name.clone(),
+                class_name: None,
+                params: func.params.clone(),
+                return_type: func.return_type.clone(),
+                loc: func.loc.clone(),
+            });
+        }
+    }
+    for class in &program.classes {
+        for member in &class.members {
+            let ClassMember::Method(method) = member else { continue };
+            let qualified = format!("{}.{}", class.name, method.name);
+            if !(method.modifiers.contains(&Modifier::Export) | listed(&qualified, &method.name)) {
+                continue;
+            }
+            if !method.modifiers.contains(&Modifier::Static) {
+                return Err(semantic_error(method.loc.line, method.loc.column,
+                    format!("只能导出静态方法，'{}' 不能以 C ABI 导出", qualified)));
+            }
+            exports.push(ExportedFunction {
+                name: method.name.clone(),
+                class_name: Some(class.name.clone()),
+                params: method.params.clone(),
+                return_type: method.return_type.clone(),
+                loc: method.loc.clone(),
+            });
+        }
+    }
+
+    if let Some(i) = found.iter().position(|f| !f) {
+        return Err(codegen_error(format!("找不到要导出的函数 '{}'", names[i])));
+    }
+    for (i, export) in exports.iter().enumerate() {
+        if exports[..i].iter().any(|e| e.name == export.name) {
+            return Err(semantic_error(export.loc.line, export.loc.column,
+                format!("导出名 '{}' 重复：C ABI 导出名不含类名和参数类型，重载或同名方法不能同时导出", export.name)));
+        }
+        for param in &export.params {
+            if param.is_varargs || c_type(&param.param_type).is_none() || param.param_type == Type::CVoid {
+                return Err(semantic_error(export.loc.line, export.loc.column,
+                    format!("导出函数 '{}' 的参数 '{}' 的类型 {} 不能用 C 类型表示", export.name, param.name, param.param_type)));
+            }
+        }
+        if c_type(&export.return_type).is_none() {
+            return Err(semantic_error(export.loc.line, export.loc.column,
+                format!("导出函数 '{}' 的返回类型 {} 不能用 C 类型表示", export.name, export.return_type)));
+        }
+    }
+    Ok(exports)
+}
+
+/// Cavvy 类型对应的 C 类型（`stdint.h`/`stdbool.h`/`stddef.h`）；对象、数组和函数类型没有对应的 C 类型
+pub fn c_type(ty: &Type) -> Option<String> {
+    let name = match ty.non_null() {
+        Type::Void | Type::CVoid => "void",
+        Type::Int32 => "int32_t",
+        Type::Int64 => "int64_t",
+        Type::Float32 | Type::CFloat => "float",
+        Type::Float64 | Type::CDouble => "double",
+        Type::Bool | Type::CBool => "bool",
+        Type::Char | Type::CChar => "char",
+        Type::String => "const char*",
+        Type::CInt => "int",
+        Type::CUInt => "unsigned int",
+        Type::CLong => "long",
+        Type::CShort => "short",
+        Type::CUShort => "unsigned short",
+        Type::CUChar => "unsigned char",
+        Type::SizeT => "size_t",
+        Type::SSizeT => "ptrdiff_t",
+        Type::UIntPtr => "uintptr_t",
+        Type::IntPtr => "intptr_t",
+        Type::Pointer(inner) => {
+            return match inner.as_ref() {
+                Type::Struct(name) => Some(format!("struct {}*", name)),
+                other => c_type(other).map(|t| format!("{}*", t)),
+            };
+        }
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// 生成导出函数的 C 头文件，`header_name` 用于生成头文件保护宏
+pub fn write_header(exports: &[ExportedFunction], header_name: &str) -> String {
+    let guard: String = header_name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    let mut out = String::new();
+    let _ = writeln!(out, "/* Cavvy library header, generated by cayc {} */", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(out, "#ifndef {}", guard);
+    let _ = writeln!(out, "#define {}\n", guard);
+    out.push_str("#include <stdbool.h>\n#include <stddef.h>\n#include <stdint.h>\n\n");
+
+    // 指针参数中出现的结构体只需前置声明
+    let mut structs: Vec<&str> = Vec::new();
+    let types = exports.iter()
+        .flat_map(|e| e.params.iter().map(|p| &p.param_type).chain(std::iter::once(&e.return_type)));
+    for ty in types {
+        let name = match ty.non_null() {
+            Type::Pointer(inner) => match inner.as_ref() {
+                Type::Struct(name) => name.as_str(),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        if !structs.contains(&name) {
+            structs.push(name);
+        }
+    }
+    for name in &structs {
+        let _ = writeln!(out, "struct {};", name);
+    }
+    if !structs.is_empty() {
+        out.push('\n');
+    }
+
+    out.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n");
+    for export in exports {
+        let params: Vec<String> = export.params.iter()
+            .map(|p| format!("{} {}", c_type(&p.param_type).unwrap_or_default(), p.name))
+            .collect();
+        let params = if params.is_empty() { "void".to_string() } else { params.join(", ") };
+        let _ = writeln!(out, "{} {}({});", c_type(&export.return_type).unwrap_or_default(), export.name, params);
+    }
+    out.push_str("\n#ifdef __cplusplus\n}\n#endif\n\n");
+    let _ = writeln!(out, "#endif /* {} */", guard);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIBRARY: &str = r#"public class MathLib {
+    public static export int add(int a, int b) {
+        return a + b;
+    }
+    public static double scale(double x, float factor) {
+        return x * factor;
+    }
+    public static bool isEven(long n) {
+        return n % 2 == 0;
+    }
+    public static String greet() {
+        return "hi";
+    }
+    public int instanceMethod() {
+        return 0;
+    }
+}
+"#;
+
+    fn parse(source: &str) -> Program {
+        crate::parser::parse(crate::lexer::lex(source).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_collect_marked_and_listed_exports() {
+        let program = parse(LIBRARY);
+        let names = vec!["MathLib.scale".to_string(), "isEven".to_string(), "greet".to_string()];
+        let exports = collect_exports(&program, &names).unwrap();
+        let exported: Vec<&str> = exports.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(exported, vec!["add", "scale", "isEven", "greet"]);
+        assert_eq!(exports[0].class_name.as_deref(), Some("MathLib"));
+
+        let header = write_header(&exports, "mathlib.h");
+        assert!(header.contains("#ifndef MATHLIB_H\n"));
+        assert!(header.contains("#include <stdint.h>\n"));
+        assert!(header.contains("int32_t add(int32_t a, int32_t b);\n"));
+        assert!(header.contains("double scale(double x, float factor);\n"));
+        assert!(header.contains("bool isEven(int64_t n);\n"));
+        assert!(header.contains("const char* greet(void);\n"));
+        assert!(header.contains("extern \"C\" {"));
+    }
+
+    #[test]
+    fn test_invalid_exports_are_rejected() {
+        let program = parse(LIBRARY);
+        let err = collect_exports(&program, &["instanceMethod".to_string()]).unwrap_err();
+        assert!(crate::error::get_error_message(&err).contains("静态方法"));
+        let err = collect_exports(&program, &["missing".to_string()]).unwrap_err();
+        assert!(crate::error::get_error_message(&err).contains("missing"));
+
+        let program = parse("public class A {\n    public static export int[] values() {\n        return new int[1];\n    }\n}\n");
+        let err = collect_exports(&program, &[]).unwrap_err();
+        assert!(crate::error::get_error_message(&err).contains("C 类型"));
+    }
+
+    #[test]
+    fn test_c_types_follow_ffi_types() {
+        assert_eq!(c_type(&Type::CUInt).as_deref(), Some("unsigned int"));
+        assert_eq!(c_type(&Type::SizeT).as_deref(), Some("size_t"));
+        assert_eq!(c_type(&Type::Pointer(Box::new(Type::CVoid))).as_deref(), Some("void*"));
+        assert_eq!(c_type(&Type::Pointer(Box::new(Type::Struct("Point".into())))).as_deref(), Some("struct Point*"));
+        assert_eq!(c_type(&Type::Object("Foo".into())), None);
+        assert_eq!(CrateType::parse("cdylib"), Some(CrateType::CDylib));
+        assert!(!CrateType::Bin.is_library());
+        assert_eq!(CrateType::StaticLib.output_filename("math", "x86_64-unknown-linux-gnu"), "libmath.a");
+        assert_eq!(CrateType::CDylib.output_filename("math", "x86_64-apple-darwin"), "libmath.dylib");
+        assert_eq!(CrateType::Bin.output_filename("math", "x86_64-w64-mingw32"), "math.exe");
+    }
+}
@@ -0,0 +1,258 @@
+// 包归档：ustar 格式的 .tar 读写和 SHA-256 校验和
+// 时间复杂度: O(n)，n 为归档大小
+// 空间复杂度: O(n)
+
+use std::path::{Component, Path};
+use anyhow::{Result, Context, bail};
+
+use super::ensure_dir;
+
+/// tar 块大小
+const BLOCK_SIZE: usize = 512;
+
+/// 构建 tar 归档
+///
+/// 所有条目的修改时间、属主和权限都固定，相同的文件内容总是得到相同的归档和校验和
+#[derive(Debug, Default)]
+pub struct TarBuilder {
+    data: Vec<u8>,
+}
+
+impl TarBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一个普通文件，`path` 使用 `/` 分隔
+    pub fn append_file(&mut self, path: &str, contents: &[u8]) -> Result<()> {
+        let mut header = [0u8; BLOCK_SIZE];
+        let (prefix, name) = split_name(path)?;
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        write_octal(&mut header[100..108], 0o644);
+        write_octal(&mut header[108..116], 0);
+        write_octal(&mut header[116..124], 0);
+        write_octal(&mut header[124..136], contents.len() as u64);
+        write_octal(&mut header[136..148], 0);
+        header[156] = b'0';
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+        header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+        // 校验和：计算时校验和字段视为空格
+        header[148..156].copy_from_slice(b"        ");
+        let checksum: u32 = header.iter().map(|b| *b as u32).sum();
+        let text = format!("{:06o}\0 ", checksum);
+        header[148..156].copy_from_slice(text.as_bytes());
+
+        self.data.extend_from_slice(&header);
+        self.data.extend_from_slice(contents);
+        let padding = (BLOCK_SIZE - contents.len() % BLOCK_SIZE) % BLOCK_SIZE;
+        self.data.extend(std::iter::repeat_n(0u8, padding));
+        Ok(())
+    }
+
+    /// 结束归档（两个全零块）
+    pub fn finish(mut self) -> Vec<u8> {
+        self.data.extend(std::iter::repeat_n(0u8, BLOCK_SIZE * 2));
+        self.data
+    }
+}
+
+/// 把路径拆成 ustar 的 prefix（最多 155 字节）和 name（最多 100 字节）
+fn split_name(path: &str) -> Result<(&str, &str)> {
+    if path.len() <= 100 {
+        return Ok(("", path));
+    }
+    for (index, _) in path.match_indices('/') {
+        let (prefix, name) = (&path[..index], &path[index + 1..]);
+        if prefix.len() <= 155 && name.len() <= 100 && !name.is_empty() {
+            return Ok((prefix, name));
+        }
+    }
+    bail!("归档中的路径过长: {}", path)
+}
+
+fn write_octal(field: &mut [u8], value: u64) {
+    let text = format!("{:0width$o}\0", value, width = field.len() - 1);
+    field.copy_from_slice(text.as_bytes());
+}
+
+fn read_octal(field: &[u8]) -> Result<u64> {
+    let text: String = field.iter()
+        .take_while(|b| **b != 0)
+        .map(|b| *b as char)
+        .collect();
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(text, 8).with_context(|| format!("tar 头中的数字无效: '{}'", text))
+}
+
+fn read_str(field: &[u8]) -> String {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).to_string()
+}
+
+/// 读取归档中的所有普通文件：(路径, 内容)
+pub fn read_tar(data: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut files = Vec::new();
+    let mut offset = 0;
+    while offset + BLOCK_SIZE <= data.len() {
+        let header = &data[offset..offset + BLOCK_SIZE];
+        if header.iter().all(|b| *b == 0) {
+            return Ok(files);
+        }
+        let expected = read_octal(&header[148..156])?;
+        let actual: u64 = header.iter().enumerate()
+            .map(|(i, b)| if (148..156).contains(&i) { b' ' as u64 } else { *b as u64 })
+            .sum();
+        if expected != actual {
+            bail!("tar 头校验和错误（偏移 {}）", offset);
+        }
+
+        let name = read_str(&header[0..100]);
+        let prefix = read_str(&header[345..500]);
+        let path = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+        let size = read_octal(&header[124..136])? as usize;
+        offset += BLOCK_SIZE;
+        if offset + size > data.len() {
+            bail!("tar 归档被截断: {}", path);
+        }
+        match header[156] {
+            b'0' | 0 => files.push((path, data[offset..offset + size].to_vec())),
+            // 目录和其他条目类型不需要
+            _ => {}
+        }
+        offset += size.div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+    }
+    bail!("tar 归档缺少结束块")
+}
+
+/// 解压归档到 `dest`，拒绝绝对路径和包含 `..` 的条目
+pub fn unpack(data: &[u8], dest: &Path) -> Result<()> {
+    for (path, contents) in read_tar(data)? {
+        let relative = Path::new(&path);
+        if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            bail!("归档中的路径不安全: {}", path);
+        }
+        let target = dest.join(relative);
+        if let Some(parent) = target.parent() {
+            ensure_dir(parent)?;
+        }
+        std::fs::write(&target, contents)
+            .with_context(|| format!("写入文件失败: {}", target.display()))?;
+    }
+    Ok(())
+}
+
+/// SHA-256 常量：前 64 个质数立方根的小数部分
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256 校验和（十六进制）
+pub fn sha256_hex(data: &[u8]) -> String {
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in message.chunks(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(SHA256_K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (slot, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *slot = slot.wrapping_add(value);
+        }
+    }
+    state.iter().map(|word| format!("{:08x}", word)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sha256_known_vectors() {
+        assert_eq!(sha256_hex(b""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(sha256_hex(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(
+            sha256_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_tar_roundtrip_and_unpack() {
+        let long_path = format!("pkg-1.0.0/src/{}/lib.cay", "nested".repeat(20));
+        let mut builder = TarBuilder::new();
+        builder.append_file("pkg-1.0.0/cavly.toml", b"[package]\n").unwrap();
+        builder.append_file(&long_path, &[7u8; 1000]).unwrap();
+        builder.append_file("pkg-1.0.0/empty.txt", b"").unwrap();
+        let data = builder.finish();
+        assert_eq!(data.len() % BLOCK_SIZE, 0);
+
+        let files = read_tar(&data).unwrap();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0], ("pkg-1.0.0/cavly.toml".to_string(), b"[package]\n".to_vec()));
+        assert_eq!(files[1].0, long_path);
+        assert_eq!(files[1].1.len(), 1000);
+
+        let temp = TempDir::new().unwrap();
+        unpack(&data, temp.path()).unwrap();
+        assert_eq!(std::fs::read(temp.path().join(&long_path)).unwrap(), vec![7u8; 1000]);
+    }
+
+    #[test]
+    fn test_tar_rejects_unsafe_and_corrupt_archives() {
+        let mut builder = TarBuilder::new();
+        builder.append_file("../escape.txt", b"x").unwrap();
+        let data = builder.finish();
+        let temp = TempDir::new().unwrap();
+        assert!(unpack(&data, temp.path()).is_err());
+
+        let mut builder = TarBuilder::new();
+        builder.append_file("a.txt", b"x").unwrap();
+        let mut data = builder.finish();
+        data[0] = b'b';
+        assert!(read_tar(&data).unwrap_err().to_string().contains("校验和"));
+    }
+}
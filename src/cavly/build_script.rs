@@ -0,0 +1,292 @@
+// 时间复杂度: O(n) 解析脚本输出, n 为输出行数
+// 空间复杂度: O(n) 存储指令
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use anyhow::{Result, Context, bail};
+
+use super::config::{feature_macro, CavlyConfig, EnabledFeatures};
+use super::ensure_dir;
+
+/// 构建脚本指令的前缀
+pub const DIRECTIVE_PREFIX: &str = "cavly:";
+
+/// 构建脚本目录（`target/build/<包名>/`）中保存脚本输出的文件
+pub const OUTPUT_FILE: &str = "output";
+
+/// 构建脚本输出的指令
+///
+/// 脚本在 stdout 上每行输出一条 `cavly:<指令>=<值>`，其他行被忽略：
+/// - `cavly:link-lib=<库>`：链接参数 `-l<库>`，依赖它的项目链接时同样使用
+/// - `cavly:link-search=<目录>`：库搜索路径 `-L<目录>`，同上
+/// - `cavly:define=<名称>[=<值>]`：预处理器宏 `-D`
+/// - `cavly:include=<目录>`：包含路径 `-I`
+/// - `cavly:warning=<消息>`：构建时显示的警告
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BuildScriptOutput {
+    pub link_libs: Vec<String>,
+    pub link_search: Vec<String>,
+    pub defines: Vec<String>,
+    pub includes: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl BuildScriptOutput {
+    /// 解析脚本的 stdout，相对路径相对于 `base`（项目根目录）
+    ///
+    /// # 复杂度
+    /// - 时间: O(n)，n 为输出长度
+    /// - 空间: O(n)
+    pub fn parse(stdout: &str, base: &Path) -> Result<Self> {
+        let mut output = Self::default();
+        for (index, line) in stdout.lines().enumerate() {
+            let Some(directive) = line.trim_end().strip_prefix(DIRECTIVE_PREFIX) else { continue };
+            let Some((key, value)) = directive.split_once('=') else {
+                bail!("构建脚本输出第 {} 行的指令缺少 '=': {}", index + 1, line);
+            };
+            if value.is_empty() {
+                bail!("构建脚本输出第 {} 行的指令没有值: {}", index + 1, line);
+            }
+            let path = || base.join(value).to_string_lossy().to_string();
+            match key {
+                "link-lib" => output.link_libs.push(value.to_string()),
+                "link-search" => output.link_search.push(path()),
+                "define" => output.defines.push(value.to_string()),
+                "include" => output.includes.push(path()),
+                "warning" => output.warnings.push(value.to_string()),
+                _ => bail!("构建脚本输出第 {} 行是未知指令 '{}{}'（支持 link-lib、link-search、define、include、warning）",
+                    index + 1, DIRECTIVE_PREFIX, key),
+            }
+        }
+        Ok(output)
+    }
+
+    /// 读取包上次运行构建脚本保存的输出；包没有构建脚本或还没有运行过时为 None
+    pub fn load(project_root: &Path, config: &CavlyConfig) -> Result<Option<Self>> {
+        if config.package.build.is_none() {
+            return Ok(None);
+        }
+        let path = script_dir(project_root, config).join(OUTPUT_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let stdout = std::fs::read_to_string(&path)
+            .with_context(|| format!("读取构建脚本输出失败: {}", path.display()))?;
+        Self::parse(&stdout, project_root).map(Some)
+    }
+
+    /// 编译参数：`-D` 宏和 `-I` 包含路径
+    pub fn compile_flags(&self) -> Vec<String> {
+        self.defines.iter().map(|define| format!("-D{}", define))
+            .chain(self.includes.iter().map(|dir| format!("-I{}", dir)))
+            .collect()
+    }
+
+    /// 链接参数：`-L` 搜索路径和 `-l` 库
+    pub fn link_flags(&self) -> Vec<String> {
+        self.link_search.iter().map(|dir| format!("-L{}", dir))
+            .chain(self.link_libs.iter().map(|lib| format!("-l{}", lib)))
+            .collect()
+    }
+}
+
+/// 包的构建脚本目录 `target/build/<包名>/`：编译出的脚本、`out/` 目录和保存的输出
+pub fn script_dir(project_root: &Path, config: &CavlyConfig) -> PathBuf {
+    config.target_path(project_root).join("build").join(&config.package.name)
+}
+
+/// 当前平台的目标三元组（构建脚本总是为当前平台编译）
+fn host_target() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "x86_64-w64-mingw32"
+    } else if cfg!(target_os = "macos") {
+        "x86_64-apple-darwin"
+    } else {
+        "x86_64-unknown-linux-gnu"
+    }
+}
+
+/// 一个包的构建脚本
+pub struct BuildScript<'a> {
+    /// 项目根目录（脚本的工作目录）
+    pub project_root: &'a Path,
+    /// 项目配置（已应用配置档和特性）
+    pub config: &'a CavlyConfig,
+    /// 配置档名
+    pub profile: &'a str,
+    /// 启用的特性
+    pub features: &'a EnabledFeatures,
+}
+
+impl<'a> BuildScript<'a> {
+    /// 传给脚本的环境变量
+    ///
+    /// `CAVLY_TARGET`、`CAVLY_HOST`、`CAVLY_PROFILE`、`CAVLY_OPT_LEVEL`、`CAVLY_DEBUG`、
+    /// `CAVLY_OUT_DIR`（脚本生成文件的目录）、`CAVLY_MANIFEST_DIR`、`CAVLY_PKG_NAME`、
+    /// `CAVLY_PKG_VERSION`，以及每个启用的特性的 `CAVLY_FEATURE_<名称>=1`
+    pub fn env(&self, out_dir: &Path) -> Vec<(String, String)> {
+        let build = &self.config.build;
+        let mut env = vec![
+            ("CAVLY_TARGET".to_string(), build.target.clone().unwrap_or_else(|| host_target().to_string())),
+            ("CAVLY_HOST".to_string(), host_target().to_string()),
+            ("CAVLY_PROFILE".to_string(), self.profile.to_string()),
+            ("CAVLY_OPT_LEVEL".to_string(), build.opt_level.clone()),
+            ("CAVLY_DEBUG".to_string(), build.debug.to_string()),
+            ("CAVLY_OUT_DIR".to_string(), out_dir.to_string_lossy().to_string()),
+            ("CAVLY_MANIFEST_DIR".to_string(), self.project_root.to_string_lossy().to_string()),
+            ("CAVLY_PKG_NAME".to_string(), self.config.package.name.clone()),
+            ("CAVLY_PKG_VERSION".to_string(), self.config.package.version.clone()),
+        ];
+        for name in &self.features.names {
+            env.push((format!("CAVLY_{}", feature_macro(name)), "1".to_string()));
+        }
+        env
+    }
+
+    /// 编译（脚本比上次编译的程序新时）并运行构建脚本，保存并解析它的输出
+    ///
+    /// 没有设置 `build` 时返回空输出
+    ///
+    /// # 复杂度
+    /// - 时间: O(n + s)，n 为脚本源码大小，s 为脚本运行时间
+    /// - 空间: O(n)
+    pub fn run(&self, cayc_path: &Path, verbose: bool) -> Result<BuildScriptOutput> {
+        let Some(script) = self.config.build_script_path(self.project_root) else {
+            return Ok(BuildScriptOutput::default());
+        };
+        if !script.exists() {
+            bail!("构建脚本不存在: {}", script.display());
+        }
+        let dir = script_dir(self.project_root, self.config);
+        let out_dir = dir.join("out");
+        ensure_dir(&out_dir)?;
+        let exe = dir.join(format!("build-script{}", std::env::consts::EXE_SUFFIX));
+
+        let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let up_to_date = modified(&exe).is_some_and(|exe_time| modified(&script).is_some_and(|t| t <= exe_time));
+        if !up_to_date {
+            if verbose {
+                println!("Cavly: 编译构建脚本: {}", script.display());
+            }
+            let output = Command::new(cayc_path)
+                .arg(&script)
+                .arg(&exe)
+                .current_dir(self.project_root)
+                .output()
+                .with_context(|| format!("执行 cayc 失败: {}", cayc_path.display()))?;
+            if !output.status.success() || !exe.exists() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                bail!("编译构建脚本 {} 失败:\nstdout:\n{}\nstderr:\n{}", script.display(), stdout, stderr);
+            }
+        }
+
+        if verbose {
+            println!("Cavly: 运行构建脚本: {}", exe.display());
+        }
+        let output = Command::new(&exe)
+            .envs(self.env(&out_dir))
+            .current_dir(self.project_root)
+            .output()
+            .with_context(|| format!("运行构建脚本失败: {}", exe.display()))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("构建脚本 {} 运行失败（{}）:\nstdout:\n{}\nstderr:\n{}", script.display(), output.status, stdout, stderr);
+        }
+
+        let parsed = BuildScriptOutput::parse(&stdout, self.project_root)
+            .with_context(|| format!("构建脚本 {} 的输出无效", script.display()))?;
+        let output_path = dir.join(OUTPUT_FILE);
+        std::fs::write(&output_path, stdout.as_bytes())
+            .with_context(|| format!("保存构建脚本输出失败: {}", output_path.display()))?;
+        for warning in &parsed.warnings {
+            eprintln!("警告: {}: {}", self.config.package.name, warning);
+        }
+        if verbose {
+            let flags: Vec<String> = parsed.compile_flags().into_iter().chain(parsed.link_flags()).collect();
+            println!("Cavly: 构建脚本参数: {}", flags.join(" "));
+        }
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_directives() {
+        let stdout = "检测 zlib...\n\
+            cavly:link-lib=z\n\
+            cavly:link-search=native/lib\n\
+            cavly:define=HAVE_ZLIB\n\
+            cavly:define=ZLIB_VERSION=13\n\
+            cavly:include=/usr/include/zlib\n\
+            cavly:warning=使用系统 zlib\n";
+        let output = BuildScriptOutput::parse(stdout, Path::new("/proj")).unwrap();
+        assert_eq!(output.link_libs, vec!["z"]);
+        assert_eq!(output.warnings, vec!["使用系统 zlib"]);
+        assert_eq!(output.compile_flags(), vec!["-DHAVE_ZLIB", "-DZLIB_VERSION=13", "-I/usr/include/zlib"]);
+        let search = Path::new("/proj").join("native/lib");
+        assert_eq!(output.link_flags(), vec![format!("-L{}", search.display()), "-lz".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_invalid_directives() {
+        let err = BuildScriptOutput::parse("ok\ncavly:rustc-link-lib=z\n", Path::new(".")).unwrap_err();
+        assert!(err.to_string().contains("第 2 行是未知指令 'cavly:rustc-link-lib'"), "{}", err);
+        let err = BuildScriptOutput::parse("cavly:link-lib\n", Path::new(".")).unwrap_err();
+        assert!(err.to_string().contains("缺少 '='"), "{}", err);
+        let err = BuildScriptOutput::parse("cavly:define=\n", Path::new(".")).unwrap_err();
+        assert!(err.to_string().contains("没有值"), "{}", err);
+    }
+
+    #[test]
+    fn test_env_and_saved_output() {
+        let temp = TempDir::new().unwrap();
+        let mut config: CavlyConfig = toml::from_str(r#"
+[package]
+name = "zwrap"
+version = "0.2.0"
+build = "build.cay"
+
+[build]
+opt_level = "3"
+target = "x86_64-w64-mingw32"
+"#).unwrap();
+        let features = EnabledFeatures { names: vec!["static-zlib".to_string()], ..Default::default() };
+        let script = BuildScript { project_root: temp.path(), config: &config, profile: "release", features: &features };
+        let env = script.env(Path::new("/out"));
+        let get = |key: &str| env.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        assert_eq!(get("CAVLY_TARGET"), Some("x86_64-w64-mingw32"));
+        assert_eq!(get("CAVLY_PROFILE"), Some("release"));
+        assert_eq!(get("CAVLY_OPT_LEVEL"), Some("3"));
+        assert_eq!(get("CAVLY_OUT_DIR"), Some("/out"));
+        assert_eq!(get("CAVLY_PKG_NAME"), Some("zwrap"));
+        assert_eq!(get("CAVLY_FEATURE_STATIC_ZLIB"), Some("1"));
+
+        // 还没有运行过脚本
+        assert_eq!(BuildScriptOutput::load(temp.path(), &config).unwrap(), None);
+        let dir = script_dir(temp.path(), &config);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(OUTPUT_FILE), "cavly:link-lib=z\n").unwrap();
+        let saved = BuildScriptOutput::load(temp.path(), &config).unwrap().unwrap();
+        assert_eq!(saved.link_libs, vec!["z"]);
+
+        // 没有构建脚本的包不读取输出
+        config.package.build = None;
+        assert_eq!(BuildScriptOutput::load(temp.path(), &config).unwrap(), None);
+    }
+
+    #[test]
+    fn test_missing_script() {
+        let temp = TempDir::new().unwrap();
+        let config: CavlyConfig = toml::from_str("[package]\nname = \"a\"\nversion = \"0.1.0\"\nbuild = \"build.cay\"\n").unwrap();
+        let features = EnabledFeatures::default();
+        let script = BuildScript { project_root: temp.path(), config: &config, profile: "dev", features: &features };
+        let err = script.run(Path::new("cayc"), false).unwrap_err();
+        assert!(err.to_string().contains("构建脚本不存在"), "{}", err);
+    }
+}
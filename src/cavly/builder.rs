@@ -1,10 +1,17 @@
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use anyhow::{Result, Context, bail};
-use crate::cavly::config::{CavlyConfig, ProjectType};
-use crate::cavly::workspace::{WorkspaceResolver, ResolvedDependency, topological_sort};
+use std::sync::Mutex;
+use anyhow::{Result, Context, anyhow, bail};
+use crate::cavly::build_script::{self, BuildScript, BuildScriptOutput};
+use crate::cavly::config::{CavlyConfig, EnabledFeatures, ProjectType, DEFAULT_PROFILE};
+use crate::cavly::units::{self, Manifest, MANIFEST_FILE, UNITS_DIR};
+use crate::cavly::workspace::{WorkspaceResolver, ResolvedDependency, ResolveOptions, topological_sort};
 use crate::cavly::{ensure_dir, TARGET_DIR};
 
+/// 依赖库逐个构建：工作区并行构建成员时，多个成员可能依赖同一个库
+static DEPENDENCY_BUILD: Mutex<()> = Mutex::new(());
+
 /// 构建器状态
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BuildState {
@@ -29,6 +36,14 @@ pub struct Builder {
     verbose: bool,
     /// 解析后的依赖列表
     dependencies: Vec<ResolvedDependency>,
+    /// 启用的特性（`-D` 宏和 `-F` 语言特性）
+    features: EnabledFeatures,
+    /// 已由工作区构建到共享目标目录的依赖，不再重复构建
+    prebuilt: HashSet<String>,
+    /// 配置档名（传给构建脚本）
+    profile: String,
+    /// 构建脚本输出的编译和链接参数
+    script: BuildScriptOutput,
 }
 
 impl Builder {
@@ -44,6 +59,10 @@ impl Builder {
             state: BuildState::Idle,
             verbose: false,
             dependencies: Vec::new(),
+            features: EnabledFeatures::default(),
+            prebuilt: HashSet::new(),
+            profile: DEFAULT_PROFILE.to_string(),
+            script: BuildScriptOutput::default(),
         }
     }
     
@@ -52,8 +71,17 @@ impl Builder {
     /// # 复杂度
     /// - 时间: O(n*m)，n 为依赖数量，m 为每个依赖的配置大小
     /// - 空间: O(n)
-    pub fn with_dependencies(project_root: PathBuf, mut config: CavlyConfig) -> Result<Self> {
-        let mut resolver = WorkspaceResolver::new(project_root.clone());
+    pub fn with_dependencies(project_root: PathBuf, config: CavlyConfig) -> Result<Self> {
+        Self::with_resolve_options(project_root, config, ResolveOptions::default())
+    }
+    
+    /// 按解析选项（`--locked` 等）解析依赖并创建构建器
+    /// 
+    /// # 复杂度
+    /// - 时间: O(n*m)，n 为依赖数量，m 为每个依赖的配置大小
+    /// - 空间: O(n)
+    pub fn with_resolve_options(project_root: PathBuf, mut config: CavlyConfig, options: ResolveOptions) -> Result<Self> {
+        let mut resolver = WorkspaceResolver::new(project_root.clone()).with_options(options);
         
         // 解析所有依赖
         let dependencies = resolver.resolve_all(&config)?;
@@ -70,6 +98,10 @@ impl Builder {
             state: BuildState::Idle,
             verbose: false,
             dependencies: sorted_deps,
+            features: EnabledFeatures::default(),
+            prebuilt: HashSet::new(),
+            profile: DEFAULT_PROFILE.to_string(),
+            script: BuildScriptOutput::default(),
         })
     }
     
@@ -79,6 +111,36 @@ impl Builder {
         self
     }
     
+    /// 设置启用的特性（由 `CavlyConfig::apply_features` 解析）
+    pub fn features(mut self, features: EnabledFeatures) -> Self {
+        self.features = features;
+        self
+    }
+    
+    /// 设置配置档名（`CavlyConfig::apply_profile` 已应用到配置，这里只传给构建脚本）
+    pub fn profile(mut self, profile: &str) -> Self {
+        self.profile = profile.to_string();
+        self
+    }
+    
+    /// 工作区构建：`members` 中的依赖已构建到共享目标目录 `target_dir`，从那里链接而不再单独构建
+    /// 
+    /// # 复杂度
+    /// - 时间: O(n*m)，n 为依赖数量，m 为成员数量
+    /// - 空间: O(m)
+    pub fn workspace(mut self, target_dir: &Path, members: &[PathBuf]) -> Self {
+        let members: Vec<PathBuf> = members.iter()
+            .filter_map(|member| std::fs::canonicalize(member).ok())
+            .collect();
+        for dep in &mut self.dependencies {
+            if std::fs::canonicalize(&dep.path).is_ok_and(|path| members.contains(&path)) {
+                dep.config.package.target_dir = target_dir.to_string_lossy().to_string();
+                self.prebuilt.insert(dep.name.clone());
+            }
+        }
+        self
+    }
+    
     /// 获取当前状态
     pub fn state(&self) -> BuildState {
         self.state
@@ -90,8 +152,9 @@ impl Builder {
     /// 1. 首先构建所有依赖库（如果是 lib 项目）
     /// 2. 验证源文件存在
     /// 3. 查找 cayc 编译器
-    /// 4. 构建 cayc 命令行参数
-    /// 5. 调用 cayc 执行编译
+    /// 4. 增量构建时分离编译各单元并合并 IR
+    /// 5. 构建 cayc 命令行参数
+    /// 6. 调用 cayc 执行编译
     /// 
     /// # 复杂度
     /// - 时间: O(n + m)，n 为源码大小，m 为链接复杂度
@@ -112,14 +175,28 @@ impl Builder {
         let target_dir = self.config.target_path(&self.project_root);
         ensure_dir(&target_dir)?;
         
-        // 4. 查找 cayc 编译器
+        // 4. 查找 cayc 编译器，运行构建脚本
         let cayc_path = find_cayc()?;
+        self.run_build_script(&cayc_path)?;
         
         // 5. 确定输出文件路径
         let output_path = self.determine_output_path(&target_dir)?;
         
-        // 6. 构建 cayc 命令行参数
-        let args = self.build_cayc_args(&source_path, &output_path)?;
+        // 6. 增量构建：先分离编译各单元，再把合并后的 IR 交给 cayc 生成最终产物；
+        //    否则把源代码目录下的全部源文件交给 cayc
+        let sources = self.source_files(&source_path)?;
+        let is_only_include = self.config.is_lib() && self.config.lib.only_include;
+        let inputs = if self.config.build.incremental && !is_only_include {
+            match self.compile_units(&cayc_path, &source_path, &sources, &target_dir)? {
+                Some(linked_path) => vec![linked_path],
+                None => sources,
+            }
+        } else {
+            sources
+        };
+        
+        // 7. 构建 cayc 命令行参数
+        let args = self.build_cayc_args(&inputs, &output_path)?;
         
         if self.verbose {
             if self.config.is_lib() && self.config.lib.only_include {
@@ -140,7 +217,7 @@ impl Builder {
             );
         }
 
-        // 7. 执行 cayc 编译
+        // 8. 执行 cayc 编译
         self.state = BuildState::Linking;
         
         let output = Command::new(&cayc_path)
@@ -155,12 +232,12 @@ impl Builder {
             bail!("编译失败:\nstdout:\n{}\nstderr:\n{}", stdout, stderr);
         }
         
-        // 8. 检查输出文件是否生成
+        // 9. 检查输出文件是否生成
         if !output_path.exists() {
             bail!("编译未生成输出文件: {}", output_path.display());
         }
         
-        // 9. 如果是库项目且不是 only_include，安装到 lib 目录
+        // 10. 如果是库项目且不是 only_include，安装到 lib 目录
         if self.config.is_lib() && !self.config.lib.only_include {
             self.install_library(&output_path)?;
         }
@@ -174,9 +251,131 @@ impl Builder {
         Ok(output_path)
     }
     
-    /// 构建所有依赖库
+    /// 只检查能否编译（Cavvy → IR），不构建依赖、不链接，IR 写入 `target/check/`
+    ///
+    /// 单文件项目用 `cayc -c` 检查；多文件项目由 cayc 无法只生成 IR，在进程内编译全部源文件
     /// 
     /// # 复杂度
+    /// - 时间: O(n)，n 为源码大小
+    /// - 空间: O(n) 临时文件
+    pub fn check(&mut self) -> Result<PathBuf> {
+        self.state = BuildState::Compiling;
+        
+        let source_path = self.config.main_source_path(&self.project_root);
+        if !source_path.exists() {
+            bail!("主源文件不存在: {}", source_path.display());
+        }
+        let check_dir = self.config.target_path(&self.project_root).join("check");
+        ensure_dir(&check_dir)?;
+        let cayc_path = find_cayc()?;
+        self.run_build_script(&cayc_path)?;
+        let ir_path = check_dir.join(format!("{}.ll", self.config.output_filename()));
+        
+        let sources = self.source_files(&source_path)?;
+        if sources.len() > 1 {
+            if self.verbose {
+                println!("Cavly: 检查 {} 个源文件", sources.len());
+            }
+            let inputs: Vec<String> = sources.iter().map(|s| s.to_string_lossy().to_string()).collect();
+            if let Err(e) = crate::Compiler::with_options(self.compiler_options())
+                .compile_files(&inputs, &ir_path.to_string_lossy()) {
+                self.state = BuildState::Failed;
+                bail!("编译失败: {}", e);
+            }
+            self.state = BuildState::Complete;
+            return Ok(ir_path);
+        }
+        
+        let flags = self.build_unit_flags();
+        if self.verbose {
+            println!("Cavly: 检查: {} {} {} {}",
+                cayc_path.display(),
+                flags.join(" "),
+                source_path.display(),
+                ir_path.display()
+            );
+        }
+        let output = Command::new(&cayc_path)
+            .args(&flags)
+            .arg(&source_path)
+            .arg(&ir_path)
+            .current_dir(&self.project_root)
+            .output()
+            .with_context(|| format!("执行 cayc 失败: {}", cayc_path.display()))?;
+        if !output.status.success() {
+            self.state = BuildState::Failed;
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            bail!("编译失败:\nstdout:\n{}\nstderr:\n{}", stdout, stderr);
+        }
+        
+        self.state = BuildState::Complete;
+        Ok(ir_path)
+    }
+    
+    /// 构建测试入口程序，输出到 `target/test/`
+    ///
+    /// 测试程序总是可执行文件：库项目也按可执行项目传参，但仍链接依赖库和 FFI 库
+    ///
+    /// # 复杂度
+    /// - 时间: O(n + m)，n 为源码大小，m 为链接复杂度
+    /// - 空间: O(n) 临时文件
+    pub fn build_test_harness(&mut self, harness_path: &Path) -> Result<PathBuf> {
+        self.state = BuildState::Compiling;
+        self.build_dependencies()?;
+
+        let test_dir = self.config.target_path(&self.project_root).join("test");
+        ensure_dir(&test_dir)?;
+        let cayc_path = find_cayc()?;
+        self.run_build_script(&cayc_path)?;
+        let stem = harness_path.file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("{}_tests", self.config.output_filename()));
+        let output_path = if self.is_windows_target() {
+            test_dir.join(format!("{}.exe", stem))
+        } else {
+            test_dir.join(stem)
+        };
+
+        let mut config = self.config.clone();
+        config.package.project_type = ProjectType::Bin;
+        config.lib.only_include = false;
+        let harness_builder = Builder {
+            project_root: self.project_root.clone(),
+            config,
+            state: BuildState::Idle,
+            verbose: self.verbose,
+            dependencies: self.dependencies.clone(),
+            features: self.features.clone(),
+            prebuilt: self.prebuilt.clone(),
+            profile: self.profile.clone(),
+            script: self.script.clone(),
+        };
+        let args = harness_builder.build_cayc_args(&[harness_path.to_path_buf()], &output_path)?;
+        if self.verbose {
+            println!("Cavly: 构建测试: {} {}", cayc_path.display(), args.join(" "));
+        }
+
+        self.state = BuildState::Linking;
+        let output = Command::new(&cayc_path)
+            .args(&args)
+            .current_dir(&self.project_root)
+            .output()
+            .with_context(|| format!("执行 cayc 失败: {}", cayc_path.display()))?;
+        if !output.status.success() || !output_path.exists() {
+            self.state = BuildState::Failed;
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            bail!("测试编译失败:\nstdout:\n{}\nstderr:\n{}", stdout, stderr);
+        }
+
+        self.state = BuildState::Complete;
+        Ok(output_path)
+    }
+
+    /// 构建所有依赖库
+    ///
+    /// # 复杂度
     /// - 时间: O(n*m)，n 为依赖数量，m 为每个依赖的构建时间
     /// - 空间: O(n)
     fn build_dependencies(&mut self) -> Result<()> {
@@ -188,7 +387,15 @@ impl Builder {
             println!("Cavly: 开始构建 {} 个依赖...", self.dependencies.len());
         }
 
+        let _guard = DEPENDENCY_BUILD.lock().unwrap_or_else(|e| e.into_inner());
         for dep in &self.dependencies {
+            if self.prebuilt.contains(&dep.name) {
+                if self.verbose {
+                    println!("Cavly: 依赖已由工作区构建: {}", dep.name);
+                }
+                continue;
+            }
+            
             // 跳过 only_include 依赖：它们只做接口检查，不产出 .lib
             if dep.config.lib.only_include {
                 if self.verbose {
@@ -203,6 +410,7 @@ impl Builder {
 
             // 为每个依赖创建构建器
             let mut dep_builder = Builder::new(dep.path.clone(), dep.config.clone())
+                .profile(&self.profile)
                 .verbose(self.verbose);
 
             dep_builder.build()?;
@@ -215,6 +423,166 @@ impl Builder {
         Ok(())
     }
 
+    /// 分离编译各单元（跳过未变化的单元），返回合并后的 IR 文件路径
+    ///
+    /// 单元从主源文件出发沿 `#include` 查找；`sources` 中有文件不在其中时无法分离编译，返回 None，
+    /// 由调用者改为完整构建
+    /// 
+    /// # 复杂度
+    /// - 时间: O(n)，n 为变化的单元的源码大小
+    /// - 空间: O(u)，u 为单元数量
+    fn compile_units(&self, cayc_path: &Path, source_path: &Path, sources: &[PathBuf], target_dir: &Path) -> Result<Option<PathBuf>> {
+        let all_units = units::discover_units(source_path, &self.unit_include_paths(cayc_path))?;
+        let unreachable = sources.iter()
+            .filter(|source| !std::fs::canonicalize(source)
+                .is_ok_and(|path| all_units.iter().any(|unit| Path::new(&unit.source) == path)))
+            .count();
+        if unreachable > 0 {
+            if self.verbose {
+                println!("Cavly: {} 个源文件没有被主源文件 #include，不能增量构建，改为完整构建", unreachable);
+            }
+            return Ok(None);
+        }
+        
+        let units_dir = self.units_dir(target_dir);
+        ensure_dir(&units_dir)?;
+        let flags = self.build_unit_flags();
+        // 主源文件是库的根单元，导出函数的包装函数和库初始化函数在这里生成
+        let mut root_flags = flags.clone();
+        root_flags.extend(self.library_flags());
+        let manifest_path = units_dir.join(MANIFEST_FILE);
+        let mut manifest = Manifest::load(&manifest_path);
+        manifest.retain_units(&all_units);
+        
+        let mut ir_files = Vec::new();
+        let mut compiled = 0;
+        for unit in &all_units {
+            let ir_path = units::unit_ir_path(&unit.source, &units_dir);
+            let is_root = std::ptr::eq(unit, all_units.last().expect("main source is a unit"));
+            let flags = if is_root { &root_flags } else { &flags };
+            let key = units::unit_key(unit, &all_units, flags, &units_dir)?;
+            if !manifest.is_up_to_date(unit, &key, &units_dir) {
+                if self.verbose {
+                    println!("Cavly: 编译单元: {}", unit.source);
+                }
+                let output = Command::new(cayc_path)
+                    .args(flags)
+                    .arg(&unit.source)
+                    .arg(&ir_path)
+                    .current_dir(&self.project_root)
+                    .output()
+                    .with_context(|| format!("执行 cayc 失败: {}", cayc_path.display()))?;
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    let stdout = String::from_utf8_lossy(&output.stdout);
+                    bail!("编译单元 {} 失败:\nstdout:\n{}\nstderr:\n{}", unit.source, stdout, stderr);
+                }
+                // 每个单元编译成功后立即保存，后续单元失败时不必重新编译已完成的单元
+                manifest.record(unit, key);
+                manifest.save(&manifest_path)?;
+                compiled += 1;
+            }
+            ir_files.push(ir_path);
+        }
+        
+        if self.verbose {
+            println!("Cavly: 增量构建: 编译 {} 个单元，{} 个未变化",
+                compiled,
+                all_units.len() - compiled
+            );
+        }
+        
+        let linked_path = units_dir.join(format!("{}.ll", self.config.output_filename()));
+        crate::unit::link_units(&ir_files, &linked_path)
+            .map_err(|e| anyhow!("{}", crate::error::get_error_message(&e)))?;
+        Ok(Some(linked_path))
+    }
+    
+    /// 运行构建脚本（`[package] build`），它输出的参数用于之后的编译和链接
+    fn run_build_script(&mut self, cayc_path: &Path) -> Result<()> {
+        let script = BuildScript {
+            project_root: &self.project_root,
+            config: &self.config,
+            profile: &self.profile,
+            features: &self.features,
+        };
+        self.script = script.run(cayc_path, self.verbose)?;
+        Ok(())
+    }
+    
+    /// 增量构建的单元目录，按包名区分，工作区成员共享目标目录时互不影响
+    fn units_dir(&self, target_dir: &Path) -> PathBuf {
+        target_dir.join(UNITS_DIR).join(&self.config.package.name)
+    }
+    
+    /// 项目的全部源文件：主源文件在前，其后是源代码目录下的其他 .cay 文件（按路径排序）
+    /// 
+    /// # 复杂度
+    /// - 时间: O(f log f)，f 为源代码目录下的文件数
+    /// - 空间: O(f)
+    fn source_files(&self, main_source: &Path) -> Result<Vec<PathBuf>> {
+        let src_dir = self.project_root.join(&self.config.package.src_dir);
+        let main = std::fs::canonicalize(main_source)
+            .with_context(|| format!("无法访问源文件: {}", main_source.display()))?;
+        let mut files = vec![main_source.to_path_buf()];
+        let others = crate::source_files(&src_dir)
+            .with_context(|| format!("读取源代码目录失败: {}", src_dir.display()))?;
+        files.extend(others.into_iter()
+            .filter(|file| std::fs::canonicalize(file).ok().as_ref() != Some(&main)));
+        Ok(files)
+    }
+    
+    /// 构建 `cayc -c` 编译单元的参数（不含输入和输出文件）
+    fn build_unit_flags(&self) -> Vec<String> {
+        let mut flags = vec!["-c".to_string(), self.config.opt_flag()];
+        if self.config.build.debug {
+            flags.push("-g".to_string());
+        }
+        if !self.config.build.runtime_checks {
+            flags.push("-fno-runtime-checks".to_string());
+        }
+        flags.extend(self.features.cayc_flags());
+        flags.extend(self.script.compile_flags());
+        for dir in self.dependency_source_dirs() {
+            flags.push(format!("-I{}", dir.display()));
+        }
+        flags
+    }
+    
+    /// 库项目的输出类型和导出函数参数；可执行项目和 only_include 库为空
+    fn library_flags(&self) -> Vec<String> {
+        if !self.config.is_lib() || self.config.lib.only_include {
+            return Vec::new();
+        }
+        let mut flags = vec![format!("--crate-type={}", self.config.lib_crate_type().name())];
+        for export in &self.config.lib.exports {
+            flags.push("--export".to_string());
+            flags.push(export.clone());
+        }
+        flags
+    }
+    
+    /// 查找单元依赖时的包含路径，与 cayc 预处理时使用的路径一致
+    fn unit_include_paths(&self, cayc_path: &Path) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        if let Some(cayc_dir) = cayc_path.parent() {
+            paths.push(cayc_dir.join("caylibs"));
+        }
+        paths.push(self.project_root.join("caylibs"));
+        paths.extend(self.script.includes.iter().map(PathBuf::from));
+        paths.extend(self.dependency_source_dirs());
+        paths.retain(|p| p.exists());
+        paths
+    }
+    
+    /// 依赖的源代码目录（供 #include 使用）
+    fn dependency_source_dirs(&self) -> Vec<PathBuf> {
+        self.dependencies.iter()
+            .map(|dep| dep.path.join(&dep.config.package.src_dir))
+            .filter(|dir| dir.exists())
+            .collect()
+    }
+    
     /// 确定输出文件路径
     /// 
     /// # 复杂度
@@ -273,7 +641,6 @@ impl Builder {
                     output_path.display(), install_path.display()))?;
         }
         
-        // TODO: 生成头文件（如果配置了）
         if self.config.lib.header.generate {
             self.generate_header(&lib_dir)?;
         }
@@ -285,34 +652,25 @@ impl Builder {
         Ok(())
     }
     
-    /// 生成 C 头文件
+    /// 生成 C 头文件，声明库以 C ABI 导出的函数（见 [`crate::cabi`]）
     /// 
     /// # 复杂度
-    /// - 时间: O(n)，n 为导出的符号数量
+    /// - 时间: O(n)，n 为源码大小
     /// - 空间: O(n)
     fn generate_header(&self, lib_dir: &Path) -> Result<()> {
         let header_name = self.config.lib.header.name.clone()
             .unwrap_or_else(|| format!("{}.h", self.config.package.name));
+        let header_dir = lib_dir.join(&self.config.lib.header.include_prefix);
+        ensure_dir(&header_dir)?;
+        let header_path = header_dir.join(&header_name);
         
-        let header_path = lib_dir.join(&header_name);
-        
-        // TODO: 解析源文件并生成头文件
-        // 目前生成一个占位头文件
-        let header_content = format!(r#"/* Cavvy Library Header - Auto Generated */
-#ifndef {}_H
-#define {}_H
-
-/* TODO: 解析并导出 Cavvy 公共接口 */
-
-#endif /* {}_H */
-"#, 
-            self.config.package.name.to_uppercase(),
-            self.config.package.name.to_uppercase(),
-            self.config.package.name.to_uppercase()
-        );
-        
-        std::fs::write(&header_path, header_content)
-            .with_context(|| format!("写入头文件失败: {}", header_path.display()))?;
+        let source_path = self.config.main_source_path(&self.project_root);
+        let sources: Vec<String> = self.source_files(&source_path)?.iter()
+            .map(|s| s.to_string_lossy().to_string())
+            .collect();
+        crate::Compiler::with_options(self.compiler_options())
+            .write_c_header_files(&sources, &header_path.to_string_lossy())
+            .map_err(|e| anyhow!("生成头文件失败: {}", crate::error::get_error_message(&e)))?;
         
         if self.verbose {
             println!("Cavly: 头文件已生成: {}", header_path.display());
@@ -321,12 +679,31 @@ impl Builder {
         Ok(())
     }
     
+    /// 进程内编译（生成头文件、检查多文件项目）使用的编译器选项，与传给 cayc 的参数一致
+    fn compiler_options(&self) -> crate::CompilerOptions {
+        let mut options = crate::CompilerOptions {
+            include_paths: self.script.includes.iter().cloned()
+                .chain(self.dependency_source_dirs().iter().map(|dir| dir.to_string_lossy().to_string()))
+                .collect(),
+            features: self.features.cayc_features.clone(),
+            defines: self.features.defines().into_iter().chain(self.script.defines.iter().cloned()).collect(),
+            runtime_checks: self.config.build.runtime_checks,
+            ..crate::CompilerOptions::default()
+        };
+        // 与 library_flags 一致：可执行项目和 only_include 库按可执行程序编译
+        if self.config.is_lib() && !self.config.lib.only_include {
+            options.crate_type = self.config.lib_crate_type();
+            options.exports = self.config.lib.exports.clone();
+        }
+        options
+    }
+    
     /// 构建 cayc 命令行参数
     /// 
     /// # 复杂度
     /// - 时间: O(n)，n 为配置参数数量
     /// - 空间: O(n)
-    fn build_cayc_args(&self, source_path: &Path, output_path: &Path) -> Result<Vec<String>> {
+    fn build_cayc_args(&self, sources: &[PathBuf], output_path: &Path) -> Result<Vec<String>> {
         let mut args = Vec::new();
 
         // only_include 模式：只编译检查，不链接任何库
@@ -340,6 +717,17 @@ impl Builder {
             args.push("-g".to_string());
         }
         
+        // 运行时检查
+        if !self.config.build.runtime_checks {
+            args.push("-fno-runtime-checks".to_string());
+        }
+        
+        // 特性对应的宏定义和语言特性
+        args.extend(self.features.cayc_flags());
+        
+        // 构建脚本输出的宏定义和包含路径
+        args.extend(self.script.compile_flags());
+        
         // 静态链接（only_include 模式不需要）
         if !is_only_include && self.config.build.static_link {
             args.push("--static".to_string());
@@ -366,6 +754,9 @@ impl Builder {
             args.push("--opt-ir".to_string());
         }
         
+        // 库的输出类型和导出函数
+        args.extend(self.library_flags());
+        
         // 保留 IR
         if self.config.build.keep_ir {
             args.push("--keep-ir".to_string());
@@ -402,14 +793,19 @@ impl Builder {
             for lib in self.config.all_libs() {
                 args.push(format!("-l{}", lib));
             }
+
+            // 构建脚本输出的库（包括依赖的构建脚本上次输出的库）
+            args.extend(self.script.link_flags());
+            for dep in &self.dependencies {
+                if let Some(output) = BuildScriptOutput::load(&dep.path, &dep.config)? {
+                    args.extend(output.link_flags());
+                }
+            }
         }
 
         // 添加依赖的源代码目录作为包含路径（供 #include 使用）
-        for dep in &self.dependencies {
-            let dep_src = dep.path.join(&dep.config.package.src_dir);
-            if dep_src.exists() {
-                args.push(format!("-I{}", dep_src.display()));
-            }
+        for dep_src in self.dependency_source_dirs() {
+            args.push(format!("-I{}", dep_src.display()));
         }
 
         // 额外的 cflags
@@ -424,8 +820,8 @@ impl Builder {
             args.push(self.config.build.ldflags.join(" "));
         }
         
-        // 输入文件（相对于项目根目录的路径）
-        args.push(source_path.to_string_lossy().to_string());
+        // 输入文件（主源文件在前）
+        args.extend(sources.iter().map(|source| source.to_string_lossy().to_string()));
         
         // 输出文件
         args.push(output_path.to_string_lossy().to_string());
@@ -461,6 +857,51 @@ impl Builder {
         
         Ok(())
     }
+    
+    /// 只清理本包的构建产物，保留共享目标目录中其他工作区成员的产物
+    /// 
+    /// # 复杂度
+    /// - 时间: O(u)，u 为本包的单元数量
+    /// - 空间: O(1)
+    pub fn clean_package(&self) -> Result<()> {
+        let target_dir = self.config.target_path(&self.project_root);
+        let output_name = self.config.output_filename();
+        let mut paths = vec![
+            self.units_dir(&target_dir),
+            build_script::script_dir(&self.project_root, &self.config),
+            target_dir.join("check").join(format!("{}.ll", output_name)),
+            target_dir.join("test").join(format!("{}_tests.cay", output_name)),
+            target_dir.join("test").join(format!("{}_tests", output_name)),
+            target_dir.join("test").join(format!("{}_tests.exe", output_name)),
+        ];
+        match self.config.package.project_type {
+            ProjectType::Bin => {
+                paths.push(target_dir.join(&output_name));
+                paths.push(target_dir.join(format!("{}.exe", output_name)));
+                paths.push(target_dir.join(format!("{}.ll", output_name)));
+            }
+            ProjectType::Lib => {
+                let lib_path = self.config.lib_install_path(&self.project_root).join(self.config.lib_output_filename());
+                paths.push(target_dir.join("ir").join(format!("{}.ll", output_name)));
+                paths.push(lib_path.with_extension("ll"));
+                paths.push(lib_path);
+            }
+        }
+        
+        for path in paths.iter().filter(|path| path.exists()) {
+            let result = if path.is_dir() {
+                std::fs::remove_dir_all(path)
+            } else {
+                std::fs::remove_file(path)
+            };
+            result.with_context(|| format!("清理失败: {}", path.display()))?;
+            if self.verbose {
+                println!("Cavly: 已清理: {}", path.display());
+            }
+        }
+        
+        Ok(())
+    }
 }
 
 /// 查找 cayc 编译器
@@ -596,10 +1037,10 @@ mod tests {
         
         let builder = Builder::new(temp.path().to_path_buf(), config);
         
-        let source = Path::new("src/main.cay");
+        let sources = [PathBuf::from("src/main.cay")];
         let output = Path::new("target/test.exe");
         
-        let args = builder.build_cayc_args(source, output).unwrap();
+        let args = builder.build_cayc_args(&sources, output).unwrap();
         
         // 验证参数包含预期内容
         assert!(args.contains(&"-O3".to_string()));
@@ -609,4 +1050,118 @@ mod tests {
         assert!(args.contains(&"src/main.cay".to_string()));
         assert!(args.contains(&"target/test.exe".to_string()));
     }
+
+    #[test]
+    fn test_source_files_main_first() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        std::fs::create_dir_all(src.join("util")).unwrap();
+        for file in ["main.cay", "app.cay", "util/math.cay", "notes.txt"] {
+            std::fs::write(src.join(file), "").unwrap();
+        }
+        
+        let builder = Builder::new(temp.path().to_path_buf(), create_test_config());
+        let main = builder.config.main_source_path(temp.path());
+        let sources = builder.source_files(&main).unwrap();
+        
+        // 主源文件在前，其余 .cay 文件按路径排序
+        assert_eq!(sources, vec![main, src.join("app.cay"), src.join("util/math.cay")]);
+    }
+
+    #[test]
+    fn test_build_unit_flags() {
+        let temp = TempDir::new().unwrap();
+        let mut config = create_test_config();
+        config.build.debug = true;
+        config.build.incremental = true;
+        config.build.libs = vec!["m".to_string()];
+        
+        let builder = Builder::new(temp.path().to_path_buf(), config);
+        let flags = builder.build_unit_flags();
+        
+        // 单元只编译到 IR，链接参数留给最终的 cayc 调用
+        assert_eq!(flags, vec!["-c".to_string(), "-O0".to_string(), "-g".to_string()]);
+    }
+
+    #[test]
+    fn test_profile_and_feature_flags() {
+        let temp = TempDir::new().unwrap();
+        let mut config = create_test_config();
+        config.build.runtime_checks = false;
+        let features = EnabledFeatures {
+            names: vec!["gui".to_string()],
+            dependencies: Vec::new(),
+            cayc_features: vec!["top_level_function".to_string()],
+        };
+        
+        let builder = Builder::new(temp.path().to_path_buf(), config).features(features);
+        let expected = ["-fno-runtime-checks", "-DFEATURE_GUI", "-Ftop_level_function"];
+        let args = builder.build_cayc_args(&[PathBuf::from("src/main.cay")], Path::new("target/test")).unwrap();
+        let flags = builder.build_unit_flags();
+        for flag in expected {
+            assert!(args.contains(&flag.to_string()), "cayc 参数缺少 {}: {:?}", flag, args);
+            assert!(flags.contains(&flag.to_string()), "单元参数缺少 {}: {:?}", flag, flags);
+        }
+        let options = builder.compiler_options();
+        assert!(!options.runtime_checks);
+        assert_eq!(options.defines, vec!["FEATURE_GUI".to_string()]);
+        assert_eq!(options.features, vec!["top_level_function".to_string()]);
+    }
+
+    #[test]
+    fn test_build_script_flags() {
+        let temp = TempDir::new().unwrap();
+        let mut builder = Builder::new(temp.path().to_path_buf(), create_test_config());
+        builder.script = BuildScriptOutput::parse("cavly:define=HAVE_Z\ncavly:include=/opt/z/include\ncavly:link-lib=z\n", temp.path()).unwrap();
+
+        // 依赖的构建脚本上次输出的链接参数同样传给 cayc
+        let dep_path = temp.path().join("dep");
+        let mut dep_config = create_test_config();
+        dep_config.package.name = "dep".to_string();
+        dep_config.package.build = Some("build.cay".to_string());
+        let script_dir = build_script::script_dir(&dep_path, &dep_config);
+        std::fs::create_dir_all(&script_dir).unwrap();
+        std::fs::write(script_dir.join(build_script::OUTPUT_FILE), "cavly:link-lib=png\n").unwrap();
+        builder.dependencies.push(ResolvedDependency {
+            name: "dep".to_string(),
+            path: dep_path,
+            config: dep_config,
+            is_local: true,
+            optional: false,
+        });
+
+        let args = builder.build_cayc_args(&[PathBuf::from("src/main.cay")], Path::new("target/test")).unwrap();
+        for flag in ["-DHAVE_Z", "-I/opt/z/include", "-lz", "-lpng"] {
+            assert!(args.contains(&flag.to_string()), "cayc 参数缺少 {}: {:?}", flag, args);
+        }
+        let flags = builder.build_unit_flags();
+        assert!(flags.contains(&"-DHAVE_Z".to_string()));
+        assert!(!flags.contains(&"-lz".to_string()));
+        let options = builder.compiler_options();
+        assert_eq!(options.defines, vec!["HAVE_Z".to_string()]);
+        assert_eq!(options.include_paths[0], "/opt/z/include");
+    }
+
+    #[test]
+    fn test_library_flags() {
+        let temp = TempDir::new().unwrap();
+        let mut config = create_test_config();
+        config.package.project_type = ProjectType::Lib;
+        config.lib.lib_type = "dynamic".to_string();
+        config.lib.exports = vec!["MathLib.add".to_string()];
+        
+        let builder = Builder::new(temp.path().to_path_buf(), config.clone());
+        assert_eq!(builder.library_flags(), vec![
+            "--crate-type=cdylib".to_string(),
+            "--export".to_string(),
+            "MathLib.add".to_string(),
+        ]);
+        let args = builder.build_cayc_args(&[PathBuf::from("src/main.cay")], Path::new("target/lib/libtest.so")).unwrap();
+        assert!(args.contains(&"--crate-type=cdylib".to_string()));
+        
+        // only_include 库不输出库文件
+        config.lib.only_include = true;
+        let builder = Builder::new(temp.path().to_path_buf(), config);
+        assert!(builder.library_flags().is_empty());
+    }
 }
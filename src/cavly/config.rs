@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use anyhow::{Result, Context};
+use crate::cabi::CrateType;
 
 /// 项目类型
 #[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
@@ -17,7 +18,8 @@ pub enum ProjectType {
 /// Cavly 项目配置（cavly.toml）
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct CavlyConfig {
-    /// 包信息
+    /// 包信息（只有 `[workspace] members` 的工作区根目录可以省略）
+    #[serde(default)]
     pub package: PackageConfig,
     
     /// 构建配置
@@ -44,6 +46,18 @@ pub struct CavlyConfig {
     #[serde(default)]
     pub lib: LibConfig,
     
+    /// 包注册表配置
+    #[serde(default)]
+    pub registry: RegistryConfig,
+    
+    /// 构建配置档（`[profile.dev]`、`[profile.release]` 和自定义配置档）
+    #[serde(default)]
+    pub profile: HashMap<String, ProfileConfig>,
+    
+    /// 特性：特性名 → 它启用的其他特性、可选依赖（`dep:名称`）和 cayc 语言特性（`cayc:名称`）
+    #[serde(default)]
+    pub features: HashMap<String, Vec<String>>,
+    
     /// 自定义配置段
     #[serde(flatten)]
     pub extra: HashMap<String, toml::Value>,
@@ -85,6 +99,10 @@ pub struct PackageConfig {
     /// 输出目录
     #[serde(default = "default_target_dir")]
     pub target_dir: String,
+    
+    /// 构建脚本（相对于项目根目录），编译前先编译并运行，输出的 `cavly:` 指令传给 cayc
+    #[serde(default)]
+    pub build: Option<String>,
 }
 
 impl Default for PackageConfig {
@@ -99,6 +117,7 @@ impl Default for PackageConfig {
             main: default_main(),
             src_dir: default_src_dir(),
             target_dir: default_target_dir(),
+            build: None,
         }
     }
 }
@@ -116,7 +135,7 @@ fn default_target_dir() -> String {
 }
 
 /// 构建配置
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BuildConfig {
     /// 优化级别: 0, 1, 2, 3, s, z
     #[serde(default = "default_opt_level")]
@@ -162,15 +181,166 @@ pub struct BuildConfig {
     #[serde(default)]
     pub keep_ir: bool,
     
+    /// 增量构建：每个源文件作为单独的编译单元，只重新编译变化的单元
+    #[serde(default)]
+    pub incremental: bool,
+    
+    /// 生成空指针、类型转换和除零运行时检查（关闭时传 -fno-runtime-checks）
+    #[serde(default = "default_true")]
+    pub runtime_checks: bool,
+    
     /// 输出文件名
     #[serde(default)]
     pub output_name: Option<String>,
 }
 
+impl Default for BuildConfig {
+    fn default() -> Self {
+        Self {
+            opt_level: default_opt_level(),
+            debug: false,
+            static_link: false,
+            target: None,
+            cflags: Vec::new(),
+            ldflags: Vec::new(),
+            lib_paths: Vec::new(),
+            libs: Vec::new(),
+            lto: false,
+            opt_ir: false,
+            keep_ir: false,
+            incremental: false,
+            runtime_checks: true,
+            output_name: None,
+        }
+    }
+}
+
 fn default_opt_level() -> String {
     "2".to_string()
 }
 
+fn default_true() -> bool {
+    true
+}
+
+/// 默认构建配置档
+pub const DEFAULT_PROFILE: &str = "dev";
+
+/// 构建配置档（`[profile.<name>]`）
+///
+/// 未设置的字段沿用继承的配置档，最终落到 `[build]`。内置的 `dev` 不做修改，
+/// 内置的 `release` 使用 `-O3` 且不生成调试信息；同名的 `[profile.dev]`、`[profile.release]` 在内置值之上覆盖。
+/// 自定义配置档用 `inherits` 指定基础配置档，未指定时基于 `[build]`。
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
+pub struct ProfileConfig {
+    /// 继承的配置档
+    #[serde(default)]
+    pub inherits: Option<String>,
+    
+    /// 优化级别: 0, 1, 2, 3, s, z
+    #[serde(default)]
+    pub opt_level: Option<String>,
+    
+    /// 是否启用调试信息
+    #[serde(default)]
+    pub debug: Option<bool>,
+    
+    /// 启用 LTO
+    #[serde(default)]
+    pub lto: Option<bool>,
+    
+    /// 启用 IR 优化
+    #[serde(default)]
+    pub opt_ir: Option<bool>,
+    
+    /// 是否静态链接
+    #[serde(default)]
+    pub static_link: Option<bool>,
+    
+    /// 是否生成运行时检查
+    #[serde(default)]
+    pub runtime_checks: Option<bool>,
+}
+
+impl ProfileConfig {
+    /// 内置配置档
+    fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dev" => Some(Self::default()),
+            "release" => Some(Self {
+                opt_level: Some("3".to_string()),
+                debug: Some(false),
+                ..Self::default()
+            }),
+            _ => None,
+        }
+    }
+    
+    /// 把设置了的字段写入构建配置
+    fn apply_to(&self, build: &mut BuildConfig) {
+        if let Some(ref opt_level) = self.opt_level {
+            build.opt_level = opt_level.clone();
+        }
+        if let Some(debug) = self.debug {
+            build.debug = debug;
+        }
+        if let Some(lto) = self.lto {
+            build.lto = lto;
+        }
+        if let Some(opt_ir) = self.opt_ir {
+            build.opt_ir = opt_ir;
+        }
+        if let Some(static_link) = self.static_link {
+            build.static_link = static_link;
+        }
+        if let Some(runtime_checks) = self.runtime_checks {
+            build.runtime_checks = runtime_checks;
+        }
+    }
+}
+
+/// 命令行选择的特性（`--features`、`--no-default-features`）
+#[derive(Debug, Clone, Default)]
+pub struct FeatureSelection {
+    /// 额外启用的特性
+    pub features: Vec<String>,
+    /// 不启用 `default` 特性
+    pub no_default_features: bool,
+}
+
+/// 解析后启用的特性
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnabledFeatures {
+    /// 启用的特性名（不含 `default`），每个特性定义预处理器宏 `FEATURE_<名称>`
+    pub names: Vec<String>,
+    /// 启用的可选依赖
+    pub dependencies: Vec<String>,
+    /// 传给 cayc `-F` 的语言特性
+    pub cayc_features: Vec<String>,
+}
+
+impl EnabledFeatures {
+    /// 启用的特性对应的预处理器宏
+    pub fn defines(&self) -> Vec<String> {
+        self.names.iter().map(|name| feature_macro(name)).collect()
+    }
+    
+    /// 传给 cayc 的 `-D` 和 `-F` 参数
+    pub fn cayc_flags(&self) -> Vec<String> {
+        self.defines().iter().map(|define| format!("-D{}", define))
+            .chain(self.cayc_features.iter().map(|feature| format!("-F{}", feature)))
+            .collect()
+    }
+}
+
+/// 特性对应的预处理器宏名：`FEATURE_` 加上大写的特性名，非字母数字字符换成下划线
+pub fn feature_macro(name: &str) -> String {
+    let suffix: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("FEATURE_{}", suffix)
+}
+
 /// FFI 配置
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct FfiConfig {
@@ -256,6 +426,14 @@ pub struct WorkspaceConfig {
     pub default_ffi: Option<FfiConfig>,
 }
 
+/// 包注册表配置
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct RegistryConfig {
+    /// 注册表目录（相对于项目根目录），未设置时使用环境变量 `CAVLY_REGISTRY`
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
 /// 库项目配置
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct LibConfig {
@@ -263,7 +441,7 @@ pub struct LibConfig {
     #[serde(default = "default_lib_type")]
     pub lib_type: String,
     
-    /// 导出的模块列表（为空则导出所有 public）
+    /// 以 C ABI 导出的函数（`类名.方法名` 或函数名），标记 `export` 的函数总是导出
     #[serde(default)]
     pub exports: Vec<String>,
     
@@ -330,6 +508,13 @@ pub enum Dependency {
     Detailed(DetailedDependency),
 }
 
+impl Dependency {
+    /// 是否为可选依赖（只在对应特性启用时使用）
+    pub fn is_optional(&self) -> bool {
+        matches!(self, Dependency::Detailed(detailed) if detailed.optional)
+    }
+}
+
 /// 详细依赖配置
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct DetailedDependency {
@@ -345,6 +530,10 @@ pub struct DetailedDependency {
     /// Git 标签
     pub tag: Option<String>,
     
+    /// Git 提交哈希
+    #[serde(default)]
+    pub rev: Option<String>,
+    
     /// 本地路径
     pub path: Option<PathBuf>,
     
@@ -391,6 +580,10 @@ impl CavlyConfig {
     
     /// 验证配置有效性
     fn validate(&self) -> Result<()> {
+        if self.is_virtual_workspace() {
+            return Ok(());
+        }
+        
         if self.package.name.is_empty() {
             anyhow::bail!("包名不能为空");
         }
@@ -412,6 +605,11 @@ impl CavlyConfig {
         project_root.join(&self.package.target_dir)
     }
     
+    /// 获取构建脚本路径（没有设置 `build` 时为 None）
+    pub fn build_script_path(&self, project_root: &Path) -> Option<PathBuf> {
+        self.package.build.as_ref().map(|script| project_root.join(script))
+    }
+    
     /// 获取输出文件名
     pub fn output_filename(&self) -> String {
         self.build.output_name.clone()
@@ -463,6 +661,111 @@ impl CavlyConfig {
         format!("-O{}", self.build.opt_level)
     }
     
+    /// 把配置档应用到 `[build]`：沿 `inherits` 从最基础的配置档开始依次覆盖
+    /// 
+    /// # 复杂度
+    /// - 时间: O(d)，d 为继承链长度
+    /// - 空间: O(d)
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let mut chain = Vec::new();
+        let mut visited: Vec<String> = Vec::new();
+        let mut current = name.to_string();
+        loop {
+            if visited.contains(&current) {
+                visited.push(current);
+                anyhow::bail!("配置档继承出现循环: {}", visited.join(" -> "));
+            }
+            let builtin = ProfileConfig::builtin(&current);
+            let profile = self.profile.get(&current);
+            if builtin.is_none() && profile.is_none() {
+                anyhow::bail!("未定义的配置档: {}", current);
+            }
+            let inherits = profile.and_then(|p| p.inherits.clone());
+            if builtin.is_some() && inherits.is_some() {
+                anyhow::bail!("内置配置档 '{}' 不能设置 inherits", current);
+            }
+            // 链中靠后的先应用：同名配置档覆盖内置值
+            chain.extend(profile.cloned());
+            chain.extend(builtin);
+            visited.push(current);
+            match inherits {
+                Some(parent) => current = parent,
+                None => break,
+            }
+        }
+        for profile in chain.iter().rev() {
+            profile.apply_to(&mut self.build);
+        }
+        Ok(())
+    }
+    
+    /// 检查依赖是否为可选依赖
+    fn is_optional_dependency(&self, name: &str) -> bool {
+        self.dependencies.get(name).is_some_and(Dependency::is_optional)
+    }
+    
+    /// 从命令行选择的特性出发，沿 `[features]` 求出启用的全部特性、可选依赖和语言特性
+    /// 
+    /// 与可选依赖同名的特性名直接启用该依赖。
+    /// 
+    /// # 复杂度
+    /// - 时间: O(f + e)，f 为特性数量，e 为特性表中的条目总数
+    /// - 空间: O(f)
+    pub fn resolve_features(&self, selection: &FeatureSelection) -> Result<EnabledFeatures> {
+        let mut pending: Vec<String> = selection.features.iter().rev().cloned().collect();
+        if !selection.no_default_features && self.features.contains_key("default") {
+            pending.push("default".to_string());
+        }
+        
+        let mut visited = HashSet::new();
+        let mut names = BTreeSet::new();
+        let mut dependencies = BTreeSet::new();
+        let mut cayc_features = BTreeSet::new();
+        while let Some(item) = pending.pop() {
+            if let Some(dep) = item.strip_prefix("dep:") {
+                if !self.is_optional_dependency(dep) {
+                    anyhow::bail!("'{}' 不是可选依赖，请在 [dependencies] 中设置 optional = true", item);
+                }
+                dependencies.insert(dep.to_string());
+                continue;
+            }
+            if let Some(feature) = item.strip_prefix("cayc:") {
+                cayc_features.insert(feature.to_string());
+                continue;
+            }
+            if !visited.insert(item.clone()) {
+                continue;
+            }
+            match self.features.get(&item) {
+                Some(entries) => pending.extend(entries.iter().rev().cloned()),
+                None if self.is_optional_dependency(&item) => {
+                    dependencies.insert(item.clone());
+                }
+                None => anyhow::bail!("未定义的特性: {}", item),
+            }
+            if item != "default" {
+                names.insert(item);
+            }
+        }
+        
+        Ok(EnabledFeatures {
+            names: names.into_iter().collect(),
+            dependencies: dependencies.into_iter().collect(),
+            cayc_features: cayc_features.into_iter().collect(),
+        })
+    }
+    
+    /// 解析特性并移除没有被启用的可选依赖
+    /// 
+    /// # 复杂度
+    /// - 时间: O(f + e + n)，n 为依赖数量
+    /// - 空间: O(f)
+    pub fn apply_features(&mut self, selection: &FeatureSelection) -> Result<EnabledFeatures> {
+        let enabled = self.resolve_features(selection)?;
+        self.dependencies.retain(|name, dep| !dep.is_optional() || enabled.dependencies.contains(name));
+        Ok(enabled)
+    }
+    
     /// 合并另一个配置（用于继承工作区或依赖库的配置）
     /// 
     /// # 说明
@@ -537,6 +840,14 @@ impl CavlyConfig {
         }
     }
     
+    /// 库项目对应的 cayc 输出类型（`--crate-type`）
+    pub fn lib_crate_type(&self) -> CrateType {
+        match self.lib.lib_type.as_str() {
+            "dynamic" => CrateType::CDylib,
+            _ => CrateType::StaticLib,
+        }
+    }
+
     /// 获取库安装路径（相对于 target）
     pub fn lib_install_path(&self, project_root: &Path) -> PathBuf {
         let target = self.target_path(project_root);
@@ -552,6 +863,11 @@ impl CavlyConfig {
     pub fn is_bin(&self) -> bool {
         self.package.project_type == ProjectType::Bin
     }
+    
+    /// 检查是否为没有 `[package]` 的工作区根目录（只能用 `--workspace` 或 `-p` 构建成员）
+    pub fn is_virtual_workspace(&self) -> bool {
+        self.package.name.is_empty() && !self.workspace.members.is_empty()
+    }
 }
 
 /// 创建默认可执行项目配置模板
@@ -570,6 +886,7 @@ license = "MIT"
 main = "main.cay"
 src_dir = "src"
 target_dir = "target"
+# build = "build.cay"  # 构建脚本：编译前运行，输出 cavly:link-lib=、cavly:define= 等指令
 
 [build]
 opt_level = "2"
@@ -583,6 +900,24 @@ libs = []
 lto = false
 opt_ir = false
 keep_ir = false
+incremental = false
+runtime_checks = true
+
+# 构建配置档：cavly build 使用 dev（即上面的 [build]），--release 使用 release，--profile <名称> 使用自定义配置档
+# [profile.release]
+# opt_level = "3"
+# lto = true
+#
+# [profile.bench]
+# inherits = "release"
+# runtime_checks = false
+
+[features]
+# 特性：启用其他特性、可选依赖（"dep:名称"）或 cayc 语言特性（"cayc:名称"）
+# 每个启用的特性定义预处理器宏 FEATURE_<名称>，源码中用 #ifdef FEATURE_LOGGING 判断
+# default = ["logging"]
+# logging = []
+# gui = ["dep:sdl", "cayc:top_level_function"]
 
 [ffi]
 # 系统库，如 "user32", "kernel32" (Windows) 或 "m", "pthread" (Linux)
@@ -612,12 +947,21 @@ link_options = []
 # members = ["../mylib", "./libs/helper"]
 # 额外的库搜索路径
 # lib_paths = ["./lib", "/usr/local/lib"]
+# 成员继承的默认构建 / FFI 配置
+# default_build = {{ opt_level = "2" }}
+# default_ffi = {{ system_libs = ["m"] }}
+
+[registry]
+# 包注册表目录（cavly publish 发布到这里，版本依赖从这里获取）
+# path = "../registry"
 
 [dependencies]
 # 依赖其他 Cavvy 包
 # example = "1.0.0"
 # mylib = {{ git = "https://github.com/user/mylib", branch = "main" }}
+# pinned = {{ git = "https://github.com/user/pinned", tag = "v1.0.0" }}  # 也可以用 rev = "<提交哈希>"
 # local = {{ path = "../local" }}
+# sdl = {{ path = "../sdl", optional = true }}  # 可选依赖，由特性启用
 
 [dev-dependencies]
 # 仅开发时使用的依赖
@@ -640,6 +984,7 @@ project_type = "lib"
 main = "lib.cay"
 src_dir = "src"
 target_dir = "target"
+# build = "build.cay"  # 构建脚本：编译前运行，输出 cavly:link-lib=、cavly:define= 等指令
 
 [build]
 opt_level = "2"
@@ -652,11 +997,20 @@ libs = []
 lto = false
 opt_ir = false
 keep_ir = false
+incremental = false
+runtime_checks = true
+
+# 构建配置档：cavly build --release 使用 release
+# [profile.release]
+# opt_level = "3"
+
+[features]
+# default = []
 
 [lib]
 # 库类型: static（静态库）或 dynamic（动态库）
 lib_type = "static"
-# 导出的模块列表（为空则导出所有 public）
+# 以 C ABI 导出的函数（"类名.方法名" 或函数名），标记 export 的函数总是导出
 exports = []
 # 安装路径（相对于 target）
 install_path = "lib"
@@ -738,6 +1092,18 @@ static_lib = true
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_virtual_workspace_root() {
+        let config = parse_config(r#"
+[workspace]
+members = ["app", "mylib"]
+"#);
+        assert!(config.is_virtual_workspace());
+        
+        let error = toml::from_str::<CavlyConfig>("[build]\nopt_level = \"1\"\n").unwrap().validate().unwrap_err();
+        assert!(error.to_string().contains("包名不能为空"));
+    }
+
     #[test]
     fn test_all_libs() {
         let mut config = CavlyConfig::default();
@@ -764,4 +1130,146 @@ static_lib = true
         config.build.opt_level = "3".to_string();
         assert_eq!(config.opt_flag(), "-O3");
     }
+
+    fn parse_config(toml_content: &str) -> CavlyConfig {
+        let config: CavlyConfig = toml::from_str(toml_content).unwrap();
+        config.validate().unwrap();
+        config
+    }
+
+    #[test]
+    fn test_default_build_without_section() {
+        let config = parse_config("[package]\nname = \"demo\"\nversion = \"0.1.0\"\n");
+        assert_eq!(config.opt_flag(), "-O2");
+        assert!(config.build.runtime_checks);
+    }
+
+    #[test]
+    fn test_apply_profile() {
+        let toml_content = r#"
+[package]
+name = "demo"
+version = "0.1.0"
+
+[build]
+opt_level = "1"
+debug = true
+
+[profile.release]
+lto = true
+
+[profile.bench]
+inherits = "release"
+runtime_checks = false
+"#;
+        let mut dev = parse_config(toml_content);
+        dev.apply_profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(dev.build.opt_level, "1");
+        assert!(dev.build.debug);
+
+        let mut release = parse_config(toml_content);
+        release.apply_profile("release").unwrap();
+        assert_eq!(release.build.opt_level, "3");
+        assert!(!release.build.debug);
+        assert!(release.build.lto);
+        assert!(release.build.runtime_checks);
+
+        let mut bench = parse_config(toml_content);
+        bench.apply_profile("bench").unwrap();
+        assert_eq!(bench.build.opt_level, "3");
+        assert!(bench.build.lto);
+        assert!(!bench.build.runtime_checks);
+
+        let mut missing = parse_config(toml_content);
+        assert!(missing.apply_profile("fast").unwrap_err().to_string().contains("未定义的配置档: fast"));
+    }
+
+    #[test]
+    fn test_profile_inherits_cycle() {
+        let mut config = parse_config(r#"
+[package]
+name = "demo"
+version = "0.1.0"
+
+[profile.a]
+inherits = "b"
+
+[profile.b]
+inherits = "a"
+"#);
+        let error = config.apply_profile("a").unwrap_err().to_string();
+        assert!(error.contains("a -> b -> a"), "{}", error);
+    }
+
+    #[test]
+    fn test_resolve_features() {
+        let toml_content = r#"
+[package]
+name = "demo"
+version = "0.1.0"
+
+[dependencies]
+sdl = { path = "../sdl", optional = true }
+json = { path = "../json", optional = true }
+base = { path = "../base" }
+
+[features]
+default = ["logging"]
+logging = []
+gui = ["dep:sdl", "cayc:top_level_function"]
+full = ["gui", "json"]
+"#;
+        let config = parse_config(toml_content);
+        let defaults = config.resolve_features(&FeatureSelection::default()).unwrap();
+        assert_eq!(defaults.names, vec!["logging"]);
+        assert!(defaults.dependencies.is_empty());
+        assert_eq!(defaults.cayc_flags(), vec!["-DFEATURE_LOGGING"]);
+
+        let selection = FeatureSelection {
+            features: vec!["full".to_string()],
+            no_default_features: true,
+        };
+        let full = config.resolve_features(&selection).unwrap();
+        assert_eq!(full.names, vec!["full", "gui", "json"]);
+        assert_eq!(full.dependencies, vec!["json", "sdl"]);
+        assert_eq!(full.cayc_features, vec!["top_level_function"]);
+        assert_eq!(full.defines(), vec!["FEATURE_FULL", "FEATURE_GUI", "FEATURE_JSON"]);
+
+        let unknown = FeatureSelection { features: vec!["fast-math".to_string()], ..Default::default() };
+        assert!(config.resolve_features(&unknown).unwrap_err().to_string().contains("未定义的特性: fast-math"));
+    }
+
+    #[test]
+    fn test_apply_features_removes_disabled_optional_dependencies() {
+        let mut config = parse_config(r#"
+[package]
+name = "demo"
+version = "0.1.0"
+
+[dependencies]
+sdl = { path = "../sdl", optional = true }
+json = { path = "../json", optional = true }
+base = { path = "../base" }
+
+[features]
+gui = ["dep:sdl"]
+"#);
+        let selection = FeatureSelection { features: vec!["gui".to_string()], ..Default::default() };
+        config.apply_features(&selection).unwrap();
+        let mut names: Vec<_> = config.dependencies.keys().cloned().collect();
+        names.sort();
+        assert_eq!(names, vec!["base", "sdl"]);
+    }
+
+    #[test]
+    fn test_feature_macro() {
+        assert_eq!(feature_macro("fast-math"), "FEATURE_FAST_MATH");
+        assert_eq!(feature_macro("gui"), "FEATURE_GUI");
+    }
+
+    #[test]
+    fn test_templates_parse() {
+        parse_config(&default_config_template("demo"));
+        parse_config(&default_lib_config_template("demo"));
+    }
 }
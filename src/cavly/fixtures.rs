@@ -0,0 +1,38 @@
+// 测试夹具：在临时目录中创建 cavly 项目（cavly.toml 和 src 下的源文件）
+// 时间复杂度: O(n) 写入文件，n 为文件大小
+// 空间复杂度: O(n)
+
+use std::path::{Path, PathBuf};
+use super::CONFIG_FILE;
+use super::config::CavlyConfig;
+use super::registry::{self, Registry};
+
+/// 在 `root/dir` 写入 cavly.toml 并创建 src 目录，返回项目目录
+pub fn write_project(root: &Path, dir: &str, toml_content: &str) -> PathBuf {
+    let path = root.join(dir);
+    std::fs::create_dir_all(path.join("src")).unwrap();
+    std::fs::write(path.join(CONFIG_FILE), toml_content).unwrap();
+    path
+}
+
+/// 在 `root/dir` 创建入口为 src/lib.cay 的库项目，`deps` 为 `[dependencies]` 表的内容
+pub fn write_lib(root: &Path, dir: &str, name: &str, version: &str, deps: &str) -> PathBuf {
+    let path = write_project(root, dir, &format!(
+        "[package]\nname = \"{}\"\nversion = \"{}\"\nproject_type = \"lib\"\nmain = \"lib.cay\"\n\n[dependencies]\n{}",
+        name, version, deps
+    ));
+    std::fs::write(path.join("src").join("lib.cay"), "public class Lib {}\n").unwrap();
+    path
+}
+
+/// 名为 app 的可执行项目配置，`deps` 为 `[dependencies]` 表的内容
+pub fn app_config(deps: &str) -> CavlyConfig {
+    toml::from_str(&format!("[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\n{}", deps)).unwrap()
+}
+
+/// 在 `root/<name>-<version>` 创建库项目，打包并发布到注册表
+pub fn publish_lib(root: &Path, registry: &Registry, name: &str, version: &str, deps: &str) {
+    let path = write_lib(root, &format!("{}-{}", name, version), name, version, deps);
+    let config = CavlyConfig::from_file(&path.join(CONFIG_FILE)).unwrap();
+    registry.publish(&registry::package(&path, &config).unwrap()).unwrap();
+}
@@ -0,0 +1,279 @@
+// Git 依赖：把仓库克隆到 ~/.cavly/git 缓存，检出指定的分支、标签或提交
+// 时间复杂度: O(r) 克隆/拉取，r 为仓库大小；缓存命中时 O(1)
+// 空间复杂度: O(r) 每个仓库一份裸仓库，每个提交一份检出
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use anyhow::{Result, Context, bail};
+
+use crate::unit::content_hash;
+use super::{ensure_dir, run_command};
+
+/// 检出完成的标记文件，目录中没有它说明上次检出被中断
+const CHECKOUT_MARKER: &str = ".cavly-ok";
+
+/// 要检出的 git 引用
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GitReference {
+    /// 远程仓库的默认分支（HEAD）
+    DefaultBranch,
+    Branch(String),
+    Tag(String),
+    /// 提交哈希（可以是缩写）
+    Rev(String),
+}
+
+impl GitReference {
+    /// 由依赖配置的 branch/tag/rev 创建，最多只能指定一个
+    pub fn from_fields(branch: Option<&str>, tag: Option<&str>, rev: Option<&str>) -> Result<Self> {
+        match (branch, tag, rev) {
+            (None, None, None) => Ok(GitReference::DefaultBranch),
+            (Some(branch), None, None) => Ok(GitReference::Branch(branch.to_string())),
+            (None, Some(tag), None) => Ok(GitReference::Tag(tag.to_string())),
+            (None, None, Some(rev)) => Ok(GitReference::Rev(rev.to_string())),
+            _ => bail!("git 依赖的 branch、tag 和 rev 只能指定一个"),
+        }
+    }
+
+    /// 在缓存的裸仓库中对应的修订表达式
+    fn revspec(&self) -> String {
+        match self {
+            GitReference::DefaultBranch => "HEAD".to_string(),
+            GitReference::Branch(branch) => format!("refs/heads/{}", branch),
+            GitReference::Tag(tag) => format!("refs/tags/{}", tag),
+            GitReference::Rev(rev) => rev.clone(),
+        }
+    }
+
+    /// lock 文件来源中的查询参数（`?branch=main`），默认分支为空
+    pub fn query(&self) -> String {
+        match self {
+            GitReference::DefaultBranch => String::new(),
+            GitReference::Branch(branch) => format!("?branch={}", branch),
+            GitReference::Tag(tag) => format!("?tag={}", tag),
+            GitReference::Rev(rev) => format!("?rev={}", rev),
+        }
+    }
+}
+
+impl fmt::Display for GitReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitReference::DefaultBranch => write!(f, "默认分支"),
+            GitReference::Branch(branch) => write!(f, "分支 {}", branch),
+            GitReference::Tag(tag) => write!(f, "标签 {}", tag),
+            GitReference::Rev(rev) => write!(f, "提交 {}", rev),
+        }
+    }
+}
+
+/// git 依赖缓存
+///
+/// 目录结构：
+/// - `db/<名称>-<哈希>`：每个仓库地址一个裸仓库
+/// - `checkouts/<名称>-<哈希>/<提交>`：每个提交一份检出
+pub struct GitCache {
+    root: PathBuf,
+    offline: bool,
+}
+
+impl GitCache {
+    /// 创建缓存；`offline` 时不访问远程仓库，只使用已缓存的提交
+    pub fn new(root: PathBuf, offline: bool) -> Self {
+        Self { root, offline }
+    }
+
+    /// 默认缓存目录：`$CAVLY_HOME/git`，未设置时为 `~/.cavly/git`
+    pub fn default_root() -> PathBuf {
+        super::cavly_home().join("git")
+    }
+
+    /// 获取依赖并检出，返回 (提交哈希, 检出目录)
+    ///
+    /// `locked` 是 lock 文件中记录的提交：缓存中已有时直接使用，不访问远程仓库
+    pub fn checkout(&self, url: &str, reference: &GitReference, locked: Option<&str>) -> Result<(String, PathBuf)> {
+        let ident = repo_ident(url);
+        let db = self.root.join("db").join(&ident);
+
+        let cached = db.join("HEAD").exists();
+        let locked_commit = match locked {
+            Some(commit) if cached => self.rev_parse(&db, commit).ok(),
+            _ => None,
+        };
+        let commit = match locked_commit {
+            Some(commit) => commit,
+            None => {
+                if self.offline {
+                    if !cached {
+                        bail!("离线模式下缓存中没有 git 仓库 {}（缓存目录 {}）", url, self.root.display());
+                    }
+                    if let Some(commit) = locked {
+                        bail!("离线模式下缓存中没有 {} 的提交 {}", url, commit);
+                    }
+                } else {
+                    self.fetch(url, &db, cached)?;
+                }
+                self.rev_parse(&db, &reference.revspec())
+                    .with_context(|| format!("git 仓库 {} 中找不到{}", url, reference))?
+            }
+        };
+
+        let checkout = self.root.join("checkouts").join(&ident).join(&commit[..commit.len().min(12)]);
+        if !checkout.join(CHECKOUT_MARKER).exists() {
+            self.create_checkout(&db, &checkout, &commit)
+                .with_context(|| format!("检出 {} 的提交 {} 失败", url, commit))?;
+        }
+        Ok((commit, checkout))
+    }
+
+    /// 克隆或更新裸仓库
+    fn fetch(&self, url: &str, db: &Path, cached: bool) -> Result<()> {
+        if !cached {
+            ensure_dir(db.parent().expect("db directory has a parent"))?;
+            let _ = std::fs::remove_dir_all(db);
+            return git(None, &["clone", "--quiet", "--bare", url, &db.to_string_lossy()])
+                .with_context(|| format!("克隆 git 仓库失败: {}", url));
+        }
+        git(Some(db), &[
+            "fetch", "--quiet", "--force", "--prune", url,
+            "+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*", "+HEAD:refs/remotes/origin/HEAD",
+        ]).with_context(|| format!("更新 git 仓库失败: {}", url))?;
+        // 远程默认分支可能变化，HEAD 指向刚拉取的远程 HEAD
+        git(Some(db), &["update-ref", "--no-deref", "HEAD", "refs/remotes/origin/HEAD"])
+    }
+
+    /// 解析为完整的提交哈希
+    fn rev_parse(&self, db: &Path, revspec: &str) -> Result<String> {
+        let output = run_command(Command::new("git")
+            .arg("-C").arg(db)
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("{}^{{commit}}", revspec)))?;
+        if !output.status.success() {
+            bail!("无法解析 {}", revspec);
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// 从裸仓库检出指定提交
+    fn create_checkout(&self, db: &Path, checkout: &Path, commit: &str) -> Result<()> {
+        let _ = std::fs::remove_dir_all(checkout);
+        ensure_dir(checkout.parent().expect("checkout directory has a parent"))?;
+        git(None, &["clone", "--quiet", "--no-checkout", &db.to_string_lossy(), &checkout.to_string_lossy()])?;
+        git(Some(checkout), &["checkout", "--quiet", "--detach", commit])?;
+        std::fs::write(checkout.join(CHECKOUT_MARKER), commit)
+            .with_context(|| format!("写入检出标记失败: {}", checkout.display()))
+    }
+}
+
+/// 缓存目录名：仓库名加地址哈希，不同地址的同名仓库不会冲突
+pub(crate) fn repo_ident(url: &str) -> String {
+    let trimmed = url.trim_end_matches('/').trim_end_matches(".git");
+    let name: String = trimmed.rsplit(['/', '\\', ':']).next().unwrap_or("repo")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let name = if name.is_empty() { "repo".to_string() } else { name };
+    format!("{}-{}", name, &content_hash(url.as_bytes())[..8])
+}
+
+/// 执行 git 命令，失败时带上 stderr
+fn git(dir: Option<&Path>, args: &[&str]) -> Result<()> {
+    let mut cmd = Command::new("git");
+    if let Some(dir) = dir {
+        cmd.arg("-C").arg(dir);
+    }
+    // 不提示输入凭据，私有仓库失败时直接报错
+    cmd.args(args).env("GIT_TERMINAL_PROMPT", "0");
+    let output = run_command(&mut cmd)?;
+    if !output.status.success() {
+        bail!("git {} 失败:\n{}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// 在 `dir` 创建带一个提交的仓库，返回 file:// 地址
+    fn init_repo(dir: &Path, content: &str) -> String {
+        std::fs::create_dir_all(dir).unwrap();
+        git(Some(dir), &["init", "--quiet", "--initial-branch=main"]).unwrap();
+        commit_file(dir, content);
+        format!("file://{}", dir.display())
+    }
+
+    fn commit_file(dir: &Path, content: &str) {
+        std::fs::write(dir.join("lib.txt"), content).unwrap();
+        git(Some(dir), &["add", "lib.txt"]).unwrap();
+        git(Some(dir), &["-c", "user.name=cavly", "-c", "user.email=cavly@example.com",
+            "commit", "--quiet", "-m", content]).unwrap();
+    }
+
+    fn read(checkout: &Path) -> String {
+        std::fs::read_to_string(checkout.join("lib.txt")).unwrap()
+    }
+
+    #[test]
+    fn test_checkout_branch_tag_and_rev() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        let url = init_repo(&repo, "v1");
+        git(Some(&repo), &["tag", "v1.0"]).unwrap();
+        commit_file(&repo, "v2");
+
+        let cache = GitCache::new(temp.path().join("cache"), false);
+        let (head, checkout) = cache.checkout(&url, &GitReference::Branch("main".into()), None).unwrap();
+        assert_eq!(head.len(), 40);
+        assert_eq!(read(&checkout), "v2");
+
+        let (tagged, checkout) = cache.checkout(&url, &GitReference::Tag("v1.0".into()), None).unwrap();
+        assert_ne!(tagged, head);
+        assert_eq!(read(&checkout), "v1");
+
+        let (rev, _) = cache.checkout(&url, &GitReference::Rev(tagged[..10].to_string()), None).unwrap();
+        assert_eq!(rev, tagged);
+
+        let err = cache.checkout(&url, &GitReference::Branch("missing".into()), None).unwrap_err();
+        assert!(format!("{:#}", err).contains("找不到分支 missing"), "{:#}", err);
+    }
+
+    #[test]
+    fn test_locked_commit_and_offline_mode() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        let url = init_repo(&repo, "v1");
+        let cache_dir = temp.path().join("cache");
+
+        let offline = GitCache::new(cache_dir.clone(), true);
+        let err = offline.checkout(&url, &GitReference::DefaultBranch, None).unwrap_err();
+        assert!(err.to_string().contains("离线模式下缓存中没有 git 仓库"), "{}", err);
+
+        let online = GitCache::new(cache_dir.clone(), false);
+        let (first, _) = online.checkout(&url, &GitReference::DefaultBranch, None).unwrap();
+        commit_file(&repo, "v2");
+
+        // 锁定的提交在缓存中：不拉取新提交
+        let (locked, checkout) = online.checkout(&url, &GitReference::DefaultBranch, Some(&first)).unwrap();
+        assert_eq!(locked, first);
+        assert_eq!(read(&checkout), "v1");
+
+        // 离线模式只能看到缓存中的提交
+        let (cached, _) = offline.checkout(&url, &GitReference::DefaultBranch, None).unwrap();
+        assert_eq!(cached, first);
+
+        let (latest, checkout) = online.checkout(&url, &GitReference::DefaultBranch, None).unwrap();
+        assert_ne!(latest, first);
+        assert_eq!(read(&checkout), "v2");
+    }
+
+    #[test]
+    fn test_repo_ident() {
+        let a = repo_ident("https://example.com/user/mathlib.git");
+        assert!(a.starts_with("mathlib-"), "{}", a);
+        assert_ne!(a, repo_ident("https://example.com/other/mathlib.git"));
+        assert!(repo_ident("file:///tmp/my lib/").starts_with("my_lib-"));
+    }
+}
@@ -0,0 +1,167 @@
+// cavly.lock：依赖解析结果，保证重复构建选中相同的版本
+// 时间复杂度: O(n log n) 序列化（按包名排序），n 为包数量
+// 空间复杂度: O(n)
+
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, Context};
+
+use super::semver::Version;
+
+/// lock 文件格式版本
+pub const LOCK_FORMAT_VERSION: u32 = 1;
+
+/// lock 文件头部注释
+const LOCK_HEADER: &str = "# 此文件由 cavly 自动生成，请勿手动编辑\n# 使用 `cavly update` 更新锁定的版本\n";
+
+/// lock 文件中的一个包
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LockedPackage {
+    /// 包名
+    pub name: String,
+    /// 选中的版本
+    pub version: String,
+    /// 来源：`path+<路径>`、`git+<地址>#<提交>` 或 `registry+<注册表>`；根项目没有来源
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// 注册表包归档的 SHA-256 校验和
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    /// 直接依赖的包名
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+}
+
+/// cavly.lock
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LockFile {
+    /// 格式版本
+    pub version: u32,
+    /// 所有包（包括根项目），按包名排序
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+impl Default for LockFile {
+    fn default() -> Self {
+        Self { version: LOCK_FORMAT_VERSION, packages: Vec::new() }
+    }
+}
+
+impl LockFile {
+    /// 从包列表创建，包和依赖按名称排序，保证输出稳定
+    pub fn new(mut packages: Vec<LockedPackage>) -> Self {
+        for package in &mut packages {
+            package.dependencies.sort();
+            package.dependencies.dedup();
+        }
+        packages.sort_by(|a, b| (&a.name, &a.version, &a.source).cmp(&(&b.name, &b.version, &b.source)));
+        Self { version: LOCK_FORMAT_VERSION, packages }
+    }
+
+    /// 读取 lock 文件，文件不存在时返回 None
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("读取 lock 文件失败: {}", path.display()))?;
+        let lock: LockFile = toml::from_str(&content)
+            .with_context(|| format!("解析 lock 文件失败: {}", path.display()))?;
+        if lock.version != LOCK_FORMAT_VERSION {
+            anyhow::bail!("不支持的 lock 文件格式版本 {}（{}），请删除后重新生成", lock.version, path.display());
+        }
+        Ok(Some(lock))
+    }
+
+    /// 写入 lock 文件
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_toml()?)
+            .with_context(|| format!("写入 lock 文件失败: {}", path.display()))
+    }
+
+    /// 序列化为 TOML 文本
+    pub fn to_toml(&self) -> Result<String> {
+        let body = toml::to_string(self).context("序列化 lock 文件失败")?;
+        Ok(format!("{}\n{}", LOCK_HEADER, body))
+    }
+
+    /// 查找锁定的包
+    pub fn find(&self, name: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|p| p.name == name)
+    }
+
+    /// 锁定的版本（版本号无效时视为未锁定）
+    pub fn locked_version(&self, name: &str) -> Option<Version> {
+        self.find(name).and_then(|p| Version::parse(&p.version).ok())
+    }
+
+    /// 与新的 lock 比较，返回变化描述（新增、移除、版本变化）
+    pub fn diff(&self, new: &LockFile) -> Vec<String> {
+        let mut changes = Vec::new();
+        for package in &new.packages {
+            match self.find(&package.name) {
+                None => changes.push(format!("新增 {} v{}", package.name, package.version)),
+                Some(old) if old.version != package.version => {
+                    changes.push(format!("更新 {} v{} -> v{}", package.name, old.version, package.version))
+                }
+                Some(old) if old.source != package.source || old.checksum != package.checksum => {
+                    changes.push(format!("更换来源 {} v{}", package.name, package.version))
+                }
+                Some(_) => {}
+            }
+        }
+        for package in &self.packages {
+            if new.find(&package.name).is_none() {
+                changes.push(format!("移除 {} v{}", package.name, package.version));
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn package(name: &str, version: &str, deps: &[&str]) -> LockedPackage {
+        LockedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            source: Some(format!("path+../{}", name)),
+            checksum: None,
+            dependencies: deps.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_lock_file_roundtrip_is_sorted() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("cavly.lock");
+        let lock = LockFile::new(vec![
+            package("zlib", "1.2.0", &[]),
+            package("app", "0.1.0", &["zlib", "base"]),
+            package("base", "2.0.1", &[]),
+        ]);
+        lock.save(&path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# 此文件由 cavly 自动生成"));
+        assert!(content.find("name = \"app\"").unwrap() < content.find("name = \"base\"").unwrap());
+
+        let loaded = LockFile::load(&path).unwrap().unwrap();
+        assert_eq!(loaded, lock);
+        assert_eq!(loaded.find("app").unwrap().dependencies, vec!["base", "zlib"]);
+        assert_eq!(loaded.locked_version("base"), Some(Version::new(2, 0, 1)));
+        assert!(LockFile::load(&temp.path().join("missing.lock")).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_lock_file_diff() {
+        let old = LockFile::new(vec![package("a", "1.0.0", &[]), package("b", "1.0.0", &[])]);
+        let new = LockFile::new(vec![package("a", "1.1.0", &[]), package("c", "0.1.0", &[])]);
+        assert_eq!(old.diff(&new), vec!["更新 a v1.0.0 -> v1.1.0", "新增 c v0.1.0", "移除 b v1.0.0"]);
+        assert!(old.diff(&old).is_empty());
+    }
+}
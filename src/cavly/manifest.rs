@@ -0,0 +1,257 @@
+// 时间复杂度: O(n) 解析和输出 cavly.toml, n 为文件大小
+// 空间复杂度: O(n) 文档树
+//
+// cavly add / cavly remove 修改 [dependencies]：用 toml_edit 编辑文档树，
+// 没有改动的部分（格式、注释、键的顺序）原样写回
+
+use std::path::Path;
+use anyhow::{Result, Context, bail};
+use toml_edit::{DocumentMut, InlineTable, Item, TableLike, Value};
+
+use super::config::CavlyConfig;
+use super::project::Project;
+use super::semver::VersionReq;
+use super::CONFIG_FILE;
+
+/// 依赖表的键
+const DEPENDENCIES: &str = "dependencies";
+
+/// 依赖的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencySource {
+    /// 注册表中的版本依赖
+    Registry,
+    /// 本地路径（相对于项目根目录）
+    Path(String),
+    /// git 仓库，可选的分支、标签或提交（`("branch", "main")`）
+    Git { url: String, reference: Option<(String, String)> },
+}
+
+/// `cavly add` 要写入的依赖
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencySpec {
+    pub name: String,
+    /// 版本要求（`json@1.2` 中的 `1.2`）
+    pub version: Option<String>,
+    pub source: DependencySource,
+}
+
+impl DependencySpec {
+    /// 拆分 `包名[@版本]`
+    pub fn split_name(arg: &str) -> Result<(String, Option<String>)> {
+        let (name, version) = match arg.split_once('@') {
+            Some((name, version)) => (name, Some(version)),
+            None => (arg, None),
+        };
+        if name.is_empty() {
+            bail!("缺少包名: {}", arg);
+        }
+        Project::validate_name(name).with_context(|| format!("无效的包名: {}", name))?;
+        if let Some(version) = version {
+            VersionReq::parse(version).with_context(|| format!("{} 的版本要求无效", name))?;
+        }
+        Ok((name.to_string(), version.map(String::from)))
+    }
+
+    /// 写入 cavly.toml 的值：只有版本时为字符串，否则为内联表
+    fn to_value(&self) -> Value {
+        if let (DependencySource::Registry, Some(version)) = (&self.source, &self.version) {
+            return Value::from(version.as_str());
+        }
+        let mut table = InlineTable::new();
+        if let Some(ref version) = self.version {
+            table.insert("version", Value::from(version.as_str()));
+        }
+        match self.source {
+            DependencySource::Registry => {}
+            DependencySource::Path(ref path) => {
+                table.insert("path", Value::from(path.as_str()));
+            }
+            DependencySource::Git { ref url, ref reference } => {
+                table.insert("git", Value::from(url.as_str()));
+                if let Some((kind, value)) = reference {
+                    table.insert(kind.as_str(), Value::from(value.as_str()));
+                }
+            }
+        }
+        Value::InlineTable(table)
+    }
+}
+
+/// 可编辑的 cavly.toml，保留原有的格式和注释
+pub struct ConfigDocument {
+    doc: DocumentMut,
+}
+
+impl ConfigDocument {
+    /// 解析 cavly.toml 的内容
+    pub fn parse(content: &str) -> Result<Self> {
+        let doc = content.parse::<DocumentMut>().context("解析 cavly.toml 失败")?;
+        Ok(Self { doc })
+    }
+
+    /// 读取项目的 cavly.toml
+    pub fn load(project_root: &Path) -> Result<Self> {
+        let path = project_root.join(CONFIG_FILE);
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("读取配置文件失败: {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("解析配置文件失败: {}", path.display()))
+    }
+
+    /// 写回项目的 cavly.toml；修改后的内容必须仍是有效的配置
+    pub fn save(&self, project_root: &Path) -> Result<()> {
+        let content = self.to_string();
+        toml::from_str::<CavlyConfig>(&content).context("修改后的 cavly.toml 无效")?;
+        let path = project_root.join(CONFIG_FILE);
+        std::fs::write(&path, content)
+            .with_context(|| format!("写入配置文件失败: {}", path.display()))
+    }
+
+    /// `[dependencies]` 表，不存在时在文件末尾创建
+    fn dependencies_mut(&mut self) -> Result<&mut dyn TableLike> {
+        self.doc.entry(DEPENDENCIES)
+            .or_insert_with(toml_edit::table)
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow::anyhow!("cavly.toml 中的 {} 不是表", DEPENDENCIES))
+    }
+
+    /// 添加或替换依赖；替换内联的依赖时保留它原来的位置和行尾注释，返回是否替换了已有的依赖
+    ///
+    /// # 复杂度
+    /// - 时间: O(d)，d 为依赖数量
+    /// - 空间: O(1)
+    pub fn add_dependency(&mut self, spec: &DependencySpec) -> Result<bool> {
+        let mut value = spec.to_value();
+        let dependencies = self.dependencies_mut()?;
+        if let Some(old) = dependencies.get_mut(&spec.name).and_then(|item| item.as_value_mut()) {
+            *value.decor_mut() = old.decor().clone();
+            *old = value;
+            return Ok(true);
+        }
+        // `[dependencies.<名称>]` 子表换成一行内联的依赖
+        let replaced = dependencies.remove(&spec.name).is_some();
+        dependencies.insert(&spec.name, Item::Value(value));
+        Ok(replaced)
+    }
+
+    /// 删除依赖（包括 `[dependencies.<名称>]` 形式的子表），返回仍以 `dep:<名称>` 引用它的特性
+    ///
+    /// # 复杂度
+    /// - 时间: O(d + f)，f 为特性数量
+    /// - 空间: O(f)
+    pub fn remove_dependency(&mut self, name: &str) -> Result<Vec<String>> {
+        let removed = self.doc.get_mut(DEPENDENCIES)
+            .and_then(|item| item.as_table_like_mut())
+            .and_then(|dependencies| dependencies.remove(name));
+        if removed.is_none() {
+            bail!("{} 的 [{}] 中没有依赖 '{}'", CONFIG_FILE, DEPENDENCIES, name);
+        }
+
+        let reference = format!("dep:{}", name);
+        let mut features = Vec::new();
+        if let Some(table) = self.doc.get("features").and_then(|item| item.as_table_like()) {
+            for (feature, enables) in table.iter() {
+                let uses = enables.as_array()
+                    .is_some_and(|array| array.iter().any(|value| value.as_str() == Some(reference.as_str())));
+                if uses {
+                    features.push(feature.to_string());
+                }
+            }
+        }
+        Ok(features)
+    }
+}
+
+impl std::fmt::Display for ConfigDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.doc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"[package]
+name = "app"   # 包名
+version = "0.1.0"
+
+[dependencies]
+# 解析 JSON
+json = "1.0"     # 固定主版本
+local = { path = "../local" }
+
+[dependencies.gui]
+path = "../gui"
+optional = true
+
+[features]
+gui = ["dep:gui"]
+"#;
+
+    fn spec(name: &str, version: Option<&str>, source: DependencySource) -> DependencySpec {
+        DependencySpec { name: name.to_string(), version: version.map(String::from), source }
+    }
+
+    #[test]
+    fn test_split_name() {
+        assert_eq!(DependencySpec::split_name("json").unwrap(), ("json".to_string(), None));
+        assert_eq!(DependencySpec::split_name("json@1.2").unwrap(), ("json".to_string(), Some("1.2".to_string())));
+        assert!(DependencySpec::split_name("json@abc").is_err());
+        assert!(DependencySpec::split_name("@1.0").is_err());
+        assert!(DependencySpec::split_name("../json").is_err());
+    }
+
+    #[test]
+    fn test_add_preserves_formatting() {
+        let mut doc = ConfigDocument::parse(CONFIG).unwrap();
+        assert!(!doc.add_dependency(&spec("text", Some("0.3"), DependencySource::Registry)).unwrap());
+        assert!(!doc.add_dependency(&spec("net", None, DependencySource::Git {
+            url: "https://example.com/net.git".to_string(),
+            reference: Some(("tag".to_string(), "v1.0.0".to_string())),
+        })).unwrap());
+        let content = doc.to_string();
+        assert!(content.starts_with("[package]\nname = \"app\"   # 包名\n"), "{}", content);
+        assert!(content.contains("# 解析 JSON\njson = \"1.0\"     # 固定主版本\n"), "{}", content);
+        assert!(content.contains("text = \"0.3\"\n"), "{}", content);
+        assert!(content.contains("net = { git = \"https://example.com/net.git\", tag = \"v1.0.0\" }\n"), "{}", content);
+
+        let config: CavlyConfig = toml::from_str(&content).unwrap();
+        assert_eq!(config.dependencies.len(), 5);
+    }
+
+    #[test]
+    fn test_replace_keeps_position_and_comment() {
+        let mut doc = ConfigDocument::parse(CONFIG).unwrap();
+        assert!(doc.add_dependency(&spec("json", Some("2.0"), DependencySource::Path("../json".to_string()))).unwrap());
+        let content = doc.to_string();
+        assert!(content.contains("# 解析 JSON\njson = { version = \"2.0\", path = \"../json\" }     # 固定主版本\nlocal"), "{}", content);
+
+        // 子表形式的依赖换成内联的写法
+        assert!(doc.add_dependency(&spec("gui", Some("0.4"), DependencySource::Registry)).unwrap());
+        let content = doc.to_string();
+        assert!(!content.contains("[dependencies.gui]"), "{}", content);
+        assert!(content.contains("local = { path = \"../local\" }\ngui = \"0.4\"\n"), "{}", content);
+    }
+
+    #[test]
+    fn test_remove_dependency() {
+        let mut doc = ConfigDocument::parse(CONFIG).unwrap();
+        assert!(doc.remove_dependency("local").unwrap().is_empty());
+        assert_eq!(doc.remove_dependency("gui").unwrap(), vec!["gui".to_string()]);
+        let content = doc.to_string();
+        assert!(!content.contains("local"), "{}", content);
+        assert!(!content.contains("[dependencies.gui]"), "{}", content);
+        assert!(content.contains("json = \"1.0\"     # 固定主版本\n"), "{}", content);
+
+        let err = doc.remove_dependency("nope").unwrap_err().to_string();
+        assert!(err.contains("没有依赖 'nope'"), "{}", err);
+    }
+
+    #[test]
+    fn test_add_creates_dependencies_table() {
+        let mut doc = ConfigDocument::parse("[package]\nname = \"app\"\nversion = \"0.1.0\"\n").unwrap();
+        doc.add_dependency(&spec("json", Some("1.0"), DependencySource::Registry)).unwrap();
+        assert_eq!(doc.to_string(), "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\njson = \"1.0\"\n");
+    }
+}
@@ -0,0 +1,414 @@
+// 时间复杂度: O(n + e) 成员排序与分层, n 为成员数, e 为成员之间的依赖数
+// 空间复杂度: O(n) 存储成员配置
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context, anyhow, bail};
+
+use super::config::CavlyConfig;
+use super::workspace::{topological_sort, ResolvedDependency};
+use super::CONFIG_FILE;
+
+/// 工作区成员
+#[derive(Debug, Clone)]
+pub struct Member {
+    /// 包名
+    pub name: String,
+    /// 成员目录
+    pub path: PathBuf,
+    /// 成员配置（已继承工作区的默认配置，目标目录指向工作区共享的目标目录）
+    pub config: CavlyConfig,
+    /// 依赖的其他成员
+    pub member_deps: Vec<String>,
+}
+
+/// 工作区：根目录的 `[workspace] members` 列出的项目，共享根目录的 `target/`
+///
+/// 根目录本身有 `[package]` 时也是一个成员，它链接所有库成员，在它们之后构建
+#[derive(Debug)]
+pub struct Workspace {
+    /// 工作区根目录
+    pub root: PathBuf,
+    /// 根目录配置
+    pub config: CavlyConfig,
+    /// 成员，按拓扑顺序排列（被依赖的在前）
+    pub members: Vec<Member>,
+}
+
+impl Workspace {
+    /// 加载工作区根目录及所有成员
+    ///
+    /// 成员继承根目录的 `default_build`、`default_ffi` 和 `[profile]`（成员自己的设置优先），
+    /// 并改用根目录的目标目录
+    ///
+    /// # 复杂度
+    /// - 时间: O(n*m + e)，m 为每个成员的配置大小
+    /// - 空间: O(n)
+    pub fn load(root: &Path) -> Result<Self> {
+        let config = CavlyConfig::from_file(&root.join(CONFIG_FILE))?;
+        if config.workspace.members.is_empty() {
+            bail!("{} 不是工作区根目录（[workspace] members 为空）", root.display());
+        }
+        let target_dir = config.target_path(root);
+        let defaults = CavlyConfig {
+            build: config.workspace.default_build.clone().unwrap_or_default(),
+            ffi: config.workspace.default_ffi.clone().unwrap_or_default(),
+            ..Default::default()
+        };
+
+        let mut loaded: Vec<ResolvedDependency> = Vec::new();
+        for member in &config.workspace.members {
+            let path = root.join(member);
+            let config_path = path.join(CONFIG_FILE);
+            if !config_path.exists() {
+                bail!("找不到工作区成员配置文件: {}", config_path.display());
+            }
+            let mut member_config = CavlyConfig::from_file(&config_path)
+                .with_context(|| format!("解析工作区成员配置失败: {}", config_path.display()))?;
+            if member_config.is_virtual_workspace() {
+                bail!("工作区成员 {} 没有 [package]", path.display());
+            }
+            if let Some(other) = loaded.iter().find(|dep| dep.name == member_config.package.name) {
+                bail!("工作区中有两个名为 '{}' 的成员: {} 和 {}",
+                    other.name, other.path.display(), path.display());
+            }
+
+            member_config.merge(&defaults);
+            for (name, profile) in &config.profile {
+                member_config.profile.entry(name.clone()).or_insert_with(|| profile.clone());
+            }
+            member_config.package.target_dir = target_dir.to_string_lossy().to_string();
+            loaded.push(ResolvedDependency {
+                name: member_config.package.name.clone(),
+                path,
+                config: member_config,
+                is_local: true,
+                optional: false,
+            });
+        }
+
+        // 被依赖的成员在前
+        let sorted = topological_sort(&loaded)?;
+        let names: Vec<String> = sorted.iter().map(|dep| dep.name.clone()).collect();
+        let mut members: Vec<Member> = sorted.into_iter()
+            .map(|dep| Member {
+                member_deps: names.iter()
+                    .filter(|name| dep.config.dependencies.contains_key(*name))
+                    .cloned()
+                    .collect(),
+                name: dep.name,
+                path: dep.path,
+                config: dep.config,
+            })
+            .collect();
+
+        if !config.is_virtual_workspace() {
+            if members.iter().any(|member| member.name == config.package.name) {
+                bail!("工作区根目录与成员同名: {}", config.package.name);
+            }
+            members.push(Member {
+                name: config.package.name.clone(),
+                path: root.to_path_buf(),
+                config: config.clone(),
+                member_deps: members.iter()
+                    .filter(|member| member.config.is_lib())
+                    .map(|member| member.name.clone())
+                    .collect(),
+            });
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            config,
+            members,
+        })
+    }
+
+    /// 工作区共享的目标目录
+    pub fn target_dir(&self) -> PathBuf {
+        self.config.target_path(&self.root)
+    }
+
+    /// 按名称查找成员
+    pub fn member(&self, name: &str) -> Result<&Member> {
+        self.members.iter().find(|member| member.name == name).ok_or_else(|| {
+            let names: Vec<&str> = self.members.iter().map(|member| member.name.as_str()).collect();
+            anyhow!("工作区中没有成员 '{}'（成员: {}）", name, names.join(", "))
+        })
+    }
+
+    /// 要构建的成员：指定名称时为该成员及它（间接）依赖的成员，否则为全部成员；按拓扑顺序
+    ///
+    /// # 复杂度
+    /// - 时间: O(n + e)
+    /// - 空间: O(n)
+    pub fn select(&self, name: Option<&str>) -> Result<Vec<&Member>> {
+        let Some(name) = name else {
+            return Ok(self.members.iter().collect());
+        };
+        let mut needed = vec![self.member(name)?.name.as_str()];
+        let mut index = 0;
+        while index < needed.len() {
+            for dep in &self.member(needed[index])?.member_deps {
+                if !needed.contains(&dep.as_str()) {
+                    needed.push(dep);
+                }
+            }
+            index += 1;
+        }
+        Ok(self.members.iter().filter(|member| needed.contains(&member.name.as_str())).collect())
+    }
+
+    /// 把成员分层：每层的成员只依赖前面各层的成员，同一层的成员可以并行构建
+    ///
+    /// # 复杂度
+    /// - 时间: O(n + e)
+    /// - 空间: O(n)
+    pub fn levels<'a>(&self, selected: &[&'a Member]) -> Vec<Vec<&'a Member>> {
+        let mut depth: HashMap<&str, usize> = HashMap::new();
+        let mut levels: Vec<Vec<&Member>> = Vec::new();
+        for member in selected {
+            let level = member.member_deps.iter()
+                .filter_map(|dep| depth.get(dep.as_str()))
+                .map(|level| level + 1)
+                .max()
+                .unwrap_or(0);
+            depth.insert(&member.name, level);
+            if levels.len() <= level {
+                levels.resize_with(level + 1, Vec::new);
+            }
+            levels[level].push(member);
+        }
+        levels
+    }
+
+    /// 逐层构建成员，同一层的成员在各自的线程中并行构建；某层有成员失败时不再构建后面的层
+    ///
+    /// 返回每个成员的构建产物，按拓扑顺序
+    ///
+    /// # 复杂度
+    /// - 时间: 各层中最慢的成员的构建时间之和
+    /// - 空间: O(n)
+    pub fn build_parallel<F>(&self, selected: &[&Member], build: F) -> Result<Vec<(String, PathBuf)>>
+    where
+        F: Fn(&Member) -> Result<PathBuf> + Sync,
+    {
+        let mut outputs = Vec::new();
+        for level in self.levels(selected) {
+            let results: Vec<(String, Result<PathBuf>)> = std::thread::scope(|scope| {
+                let handles: Vec<_> = level.iter()
+                    .map(|member| {
+                        let build = &build;
+                        (member.name.clone(), scope.spawn(move || build(member)))
+                    })
+                    .collect();
+                handles.into_iter()
+                    .map(|(name, handle)| {
+                        let result = handle.join().unwrap_or_else(|_| Err(anyhow!("构建线程崩溃")));
+                        (name, result)
+                    })
+                    .collect()
+            });
+
+            let mut errors = Vec::new();
+            for (name, result) in results {
+                match result {
+                    Ok(output) => outputs.push((name, output)),
+                    Err(e) => errors.push(format!("成员 {} 构建失败: {:#}", name, e)),
+                }
+            }
+            if !errors.is_empty() {
+                bail!("{}", errors.join("\n"));
+            }
+        }
+        Ok(outputs)
+    }
+
+    /// 成员依赖图的文本表示（`cavly info`）
+    ///
+    /// # 复杂度
+    /// - 时间: O(n + e)
+    /// - 空间: O(n + e)
+    pub fn graph_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("工作区: {} ({} 个成员)", self.root.display(), self.members.len())];
+        for member in &self.members {
+            let dir = member.path.strip_prefix(&self.root).unwrap_or(&member.path);
+            let dir = if dir.as_os_str().is_empty() { ".".to_string() } else { dir.display().to_string() };
+            let kind = if member.config.is_lib() { "库" } else { "可执行" };
+            let mut line = format!("  {} v{} ({}, {})", member.name, member.config.package.version, kind, dir);
+            if !member.member_deps.is_empty() {
+                line.push_str(&format!(" -> {}", member.member_deps.join(", ")));
+            }
+            lines.push(line);
+        }
+        let order: Vec<String> = self.levels(&self.members.iter().collect::<Vec<_>>()).iter()
+            .map(|level| {
+                let names: Vec<&str> = level.iter().map(|member| member.name.as_str()).collect();
+                format!("[{}]", names.join(", "))
+            })
+            .collect();
+        lines.push(format!("构建顺序: {}", order.join(" -> ")));
+        lines
+    }
+}
+
+/// 从 `start_dir` 向上查找工作区根目录（`[workspace] members` 不为空的 cavly.toml 所在目录）
+///
+/// # 复杂度
+/// - 时间: O(d)，d 为目录深度
+/// - 空间: O(1)
+pub fn find_workspace_root(start_dir: &Path) -> Option<PathBuf> {
+    start_dir.ancestors()
+        .filter(|dir| dir.join(CONFIG_FILE).exists())
+        .find(|dir| CavlyConfig::from_file(&dir.join(CONFIG_FILE))
+            .is_ok_and(|config| !config.workspace.members.is_empty()))
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use crate::cavly::fixtures::write_project;
+    use tempfile::TempDir;
+
+    /// app 依赖 mylib，tool 独立；根目录没有 [package]
+    fn sample_workspace() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join(CONFIG_FILE), r#"
+[workspace]
+members = ["app", "libs/mylib", "tool"]
+
+[workspace.default_build]
+opt_level = "1"
+libs = ["m"]
+
+[workspace.default_ffi]
+system_libs = ["pthread"]
+
+[profile.bench]
+inherits = "release"
+runtime_checks = false
+"#).unwrap();
+        write_project(temp.path(), "app", r#"
+[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+mylib = { path = "../libs/mylib" }
+"#);
+        write_project(temp.path(), "libs/mylib", r#"
+[package]
+name = "mylib"
+version = "0.2.0"
+project_type = "lib"
+
+[build]
+opt_level = "3"
+"#);
+        write_project(temp.path(), "tool", r#"
+[package]
+name = "tool"
+version = "0.1.0"
+"#);
+        temp
+    }
+
+    #[test]
+    fn test_load_orders_members_and_inherits_defaults() {
+        let temp = sample_workspace();
+        let workspace = Workspace::load(temp.path()).unwrap();
+
+        let position = |name: &str| workspace.members.iter().position(|m| m.name == name).unwrap();
+        assert_eq!(workspace.members.len(), 3);
+        assert!(position("mylib") < position("app"));
+
+        let app = workspace.member("app").unwrap();
+        assert_eq!(app.member_deps, vec!["mylib"]);
+        assert_eq!(app.config.build.opt_level, "1");
+        assert_eq!(app.config.build.libs, vec!["m"]);
+        assert_eq!(app.config.ffi.system_libs, vec!["pthread"]);
+        assert!(app.config.profile.contains_key("bench"));
+        assert_eq!(app.config.target_path(&app.path), workspace.target_dir());
+
+        // 成员自己的设置优先
+        assert_eq!(workspace.member("mylib").unwrap().config.build.opt_level, "3");
+    }
+
+    #[test]
+    fn test_select_and_levels() {
+        let temp = sample_workspace();
+        let workspace = Workspace::load(temp.path()).unwrap();
+
+        let names = |members: &[&Member]| members.iter().map(|m| m.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&workspace.select(Some("app")).unwrap()), vec!["mylib", "app"]);
+        assert_eq!(names(&workspace.select(Some("tool")).unwrap()), vec!["tool"]);
+        let error = workspace.select(Some("missing")).unwrap_err().to_string();
+        assert!(error.contains("工作区中没有成员 'missing'"), "{}", error);
+
+        let all = workspace.select(None).unwrap();
+        let levels = workspace.levels(&all);
+        assert_eq!(levels.len(), 2);
+        let mut first = names(&levels[0]);
+        first.sort();
+        assert_eq!(first, vec!["mylib", "tool"]);
+        assert_eq!(names(&levels[1]), vec!["app"]);
+    }
+
+    #[test]
+    fn test_build_parallel_respects_dependencies() {
+        let temp = sample_workspace();
+        let workspace = Workspace::load(temp.path()).unwrap();
+        let finished = Mutex::new(Vec::new());
+
+        let all = workspace.select(None).unwrap();
+        let outputs = workspace.build_parallel(&all, |member| {
+            let done = finished.lock().unwrap();
+            for dep in &member.member_deps {
+                assert!(done.contains(dep), "{} 在依赖 {} 之前构建", member.name, dep);
+            }
+            drop(done);
+            finished.lock().unwrap().push(member.name.clone());
+            Ok(member.path.clone())
+        }).unwrap();
+        assert_eq!(outputs.len(), 3);
+
+        let error = workspace.build_parallel(&all, |member| {
+            if member.name == "mylib" {
+                bail!("编译失败");
+            }
+            Ok(member.path.clone())
+        }).unwrap_err().to_string();
+        assert!(error.contains("成员 mylib 构建失败: 编译失败"), "{}", error);
+    }
+
+    #[test]
+    fn test_root_package_is_built_last() {
+        let temp = sample_workspace();
+        std::fs::write(temp.path().join(CONFIG_FILE), r#"
+[package]
+name = "suite"
+version = "1.0.0"
+
+[workspace]
+members = ["app", "libs/mylib", "tool"]
+"#).unwrap();
+        let workspace = Workspace::load(temp.path()).unwrap();
+        let root = workspace.members.last().unwrap();
+        assert_eq!(root.name, "suite");
+        assert_eq!(root.member_deps, vec!["mylib"]);
+    }
+
+    #[test]
+    fn test_find_workspace_root_and_graph() {
+        let temp = sample_workspace();
+        let app = temp.path().join("app").join("src");
+        assert_eq!(find_workspace_root(&app).as_deref(), Some(temp.path()));
+
+        let workspace = Workspace::load(temp.path()).unwrap();
+        let lines = workspace.graph_lines();
+        assert!(lines.contains(&"  app v0.1.0 (可执行, app) -> mylib".to_string()), "{:?}", lines);
+        assert!(lines.last().unwrap().ends_with("-> [app]"), "{:?}", lines);
+    }
+}
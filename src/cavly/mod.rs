@@ -3,6 +3,21 @@ pub mod builder;
 pub mod project;
 pub mod ffi;
 pub mod workspace;
+pub mod units;
+pub mod semver;
+pub mod lockfile;
+pub mod resolver;
+pub mod git;
+pub mod archive;
+pub mod registry;
+pub mod testing;
+pub mod members;
+pub mod tree;
+pub mod build_script;
+pub mod manifest;
+pub mod template;
+#[cfg(test)]
+mod fixtures;
 
 use std::path::{Path, PathBuf};
 use anyhow::{Result, Context};
@@ -13,6 +28,9 @@ pub const VERSION: &str = env!("CAVLY_VERSION");
 /// 默认配置文件名
 pub const CONFIG_FILE: &str = "cavly.toml";
 
+/// 依赖锁定文件名
+pub const LOCK_FILE: &str = "cavly.lock";
+
 /// 默认目标目录
 pub const TARGET_DIR: &str = "target";
 
@@ -33,6 +51,18 @@ pub fn find_project_root(start_dir: &Path) -> Option<PathBuf> {
     }
 }
 
+/// cavly 的全局目录：`$CAVLY_HOME`，未设置时为 `~/.cavly`（git 缓存、注册表包缓存）
+pub fn cavly_home() -> PathBuf {
+    if let Some(home) = std::env::var_os("CAVLY_HOME") {
+        return PathBuf::from(home);
+    }
+    let home = std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    home.join(".cavly")
+}
+
 /// 确保目录存在
 pub fn ensure_dir(path: &Path) -> Result<()> {
     if !path.exists() {
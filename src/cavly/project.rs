@@ -2,6 +2,7 @@ use std::path::{Path, PathBuf};
 use anyhow::{Result, Context, bail};
 use crate::cavly::config::{CavlyConfig, default_config_template, default_lib_config_template, ProjectType};
 use crate::cavly::{CONFIG_FILE, ensure_dir};
+use crate::cavly::template::Template;
 
 /// Cavly 项目管理器
 pub struct Project;
@@ -56,36 +57,14 @@ impl Project {
             ProjectType::Bin => {
                 let main_path = src_dir.join("main.cay");
                 if !main_path.exists() {
-                    let main_content = r#"// Cavvy 主程序入口
-
-public class main {
-    public static void main() {
-        println("Hello, Cavvy!");
-    }
-}
-"#;
-                    std::fs::write(&main_path, main_content)
+                    std::fs::write(&main_path, default_main_source())
                         .with_context(|| format!("写入主文件失败: {}", main_path.display()))?;
                 }
             }
             ProjectType::Lib => {
                 let lib_path = src_dir.join("lib.cay");
                 if !lib_path.exists() {
-                    let lib_content = format!(r#"// Cavvy 库项目: {}
-
-// 导出模块示例
-public class {} {{
-    // 公共函数会被导出到库中
-    public static int add(int a, int b) {{
-        return a + b;
-    }}
-    
-    public static void greet() {{
-        println("Hello from {} library!");
-    }}
-}}
-"#, project_name, Self::to_class_name(&project_name), project_name);
-                    std::fs::write(&lib_path, lib_content)
+                    std::fs::write(&lib_path, default_lib_source(&project_name))
                         .with_context(|| format!("写入库文件失败: {}", lib_path.display()))?;
                 }
             }
@@ -94,47 +73,7 @@ public class {} {{
         // 创建 .gitignore
         let gitignore_path = path.join(".gitignore");
         if !gitignore_path.exists() {
-            let gitignore_content = match project_type {
-                ProjectType::Bin => r#"# Cavvy 构建产物
-target/
-*.exe
-*.ll
-*.o
-
-# IDE
-.vscode/
-.idea/
-*.swp
-*.swo
-*~
-
-# 操作系统
-.DS_Store
-Thumbs.db
-"#,
-                ProjectType::Lib => r#"# Cavvy 构建产物
-target/
-*.exe
-*.ll
-*.o
-*.lib
-*.a
-*.dll
-*.so
-*.dylib
-
-# IDE
-.vscode/
-.idea/
-*.swp
-*.swo
-*~
-
-# 操作系统
-.DS_Store
-Thumbs.db
-"#,
-            };
+            let gitignore_content = gitignore_template(&project_type);
             std::fs::write(&gitignore_path, gitignore_content)
                 .with_context(|| format!("写入 .gitignore 失败: {}", gitignore_path.display()))?;
         }
@@ -156,8 +95,51 @@ Thumbs.db
         Ok(())
     }
     
+    /// 用模板创建新项目（`cavly new`）
+    /// 
+    /// 目标目录必须不存在或为空；写入后检查生成的 cavly.toml 是否有效
+    /// 
+    /// # 复杂度
+    /// - 时间: O(n)，n 为模板文件总大小
+    /// - 空间: O(n)
+    pub fn new_from_template(path: &Path, name: &str, template: &Template) -> Result<()> {
+        Self::validate_name(name)?;
+        
+        let non_empty = path.read_dir()
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(false);
+        if non_empty {
+            bail!("目录已存在且不为空: {}", path.display());
+        }
+        
+        let files = template.files(name)?;
+        for file in &files {
+            let file_path = path.join(&file.path);
+            if let Some(parent) = file_path.parent() {
+                ensure_dir(parent)?;
+            }
+            std::fs::write(&file_path, &file.contents)
+                .with_context(|| format!("写入文件失败: {}", file_path.display()))?;
+        }
+        
+        let config_path = path.join(CONFIG_FILE);
+        let config = CavlyConfig::from_file(&config_path)
+            .with_context(|| format!("模板 '{}' 生成的 {} 无效", template.name(), CONFIG_FILE))?;
+        
+        let type_str = match config.package.project_type {
+            ProjectType::Bin => "可执行项目",
+            ProjectType::Lib => "库项目",
+        };
+        println!("已在 {} 用模板 '{}' 创建{} '{}'", path.display(), template.name(), type_str, name);
+        for file in &files {
+            println!("  {}", file.path.display());
+        }
+        
+        Ok(())
+    }
+    
     /// 将项目名称转换为类名（首字母大写）
-    fn to_class_name(name: &str) -> String {
+    pub(crate) fn to_class_name(name: &str) -> String {
         let mut result = String::new();
         let mut capitalize = true;
         
@@ -185,7 +167,7 @@ Thumbs.db
     /// - 只能包含字母、数字、下划线和连字符
     /// - 不能以数字开头
     /// - 不能为空
-    fn validate_name(name: &str) -> Result<()> {
+    pub(crate) fn validate_name(name: &str) -> Result<()> {
         if name.is_empty() {
             bail!("项目名称不能为空");
         }
@@ -281,6 +263,81 @@ Thumbs.db
     }
 }
 
+/// 默认可执行项目的主文件
+pub(crate) fn default_main_source() -> &'static str {
+    r#"// Cavvy 主程序入口
+
+public class main {
+    public static void main() {
+        println("Hello, Cavvy!");
+    }
+}
+"#
+}
+
+/// 默认库项目的 lib.cay
+pub(crate) fn default_lib_source(name: &str) -> String {
+    format!(r#"// Cavvy 库项目: {}
+
+// 导出模块示例
+public class {} {{
+    // 公共函数会被导出到库中
+    public static int add(int a, int b) {{
+        return a + b;
+    }}
+    
+    public static void greet() {{
+        println("Hello from {} library!");
+    }}
+}}
+"#, name, Project::to_class_name(name), name)
+}
+
+/// 按项目类型生成的 .gitignore
+pub(crate) fn gitignore_template(project_type: &ProjectType) -> &'static str {
+    match project_type {
+        ProjectType::Bin => r#"# Cavvy 构建产物
+target/
+*.exe
+*.ll
+*.o
+
+# IDE
+.vscode/
+.idea/
+*.swp
+*.swo
+*~
+
+# 操作系统
+.DS_Store
+Thumbs.db
+"#,
+        ProjectType::Lib => r#"# Cavvy 构建产物
+target/
+*.exe
+*.ll
+*.o
+*.lib
+*.a
+*.dll
+*.so
+*.dylib
+
+# IDE
+.vscode/
+.idea/
+*.swp
+*.swo
+*~
+
+# 操作系统
+.DS_Store
+Thumbs.db
+"#,
+    }
+}
+
 /// 项目信息
 #[derive(Debug, Clone)]
 pub struct ProjectInfo {
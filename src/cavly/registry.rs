@@ -0,0 +1,379 @@
+// 文件系统注册表：普通目录（或共享盘上的静态文件树）中的包索引和 .tar 归档
+// 时间复杂度: O(v) 查询，v 为包的版本数；O(n) 打包，n 为源码大小
+// 空间复杂度: O(n)
+//
+// 目录结构：
+// - `index/<包名>.toml`：每个版本的版本号、校验和和依赖列表
+// - `packages/<包名>/<包名>-<版本>.tar`：包归档（`<包名>-<版本>/` 下的 cavly.toml 和源码目录）
+
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, Context, bail};
+
+use super::archive::{self, TarBuilder};
+use super::builder::Builder;
+use super::config::CavlyConfig;
+use super::semver::{Version, VersionReq};
+use super::workspace::ResolveOptions;
+use super::{ensure_dir, CONFIG_FILE};
+
+/// 解压完成的标记文件（内容为归档的校验和）
+const UNPACK_MARKER: &str = ".cavly-ok";
+
+/// 索引中的一个依赖
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IndexDependency {
+    pub name: String,
+    /// 版本要求
+    pub req: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+}
+
+/// 索引中的一个版本
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IndexEntry {
+    pub version: String,
+    /// 归档的 SHA-256 校验和
+    pub checksum: String,
+    #[serde(default)]
+    pub dependencies: Vec<IndexDependency>,
+}
+
+/// 一个包的索引文件
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct IndexFile {
+    #[serde(default, rename = "version")]
+    versions: Vec<IndexEntry>,
+}
+
+/// `cavly package` 生成的归档
+#[derive(Debug, Clone)]
+pub struct PackageArchive {
+    pub name: String,
+    pub version: String,
+    /// 归档文件路径
+    pub path: PathBuf,
+    /// 归档的 SHA-256 校验和
+    pub checksum: String,
+    /// 发布后的依赖列表（path 依赖已转换为版本依赖）
+    pub dependencies: Vec<IndexDependency>,
+}
+
+/// 文件系统注册表
+#[derive(Debug, Clone)]
+pub struct Registry {
+    root: PathBuf,
+}
+
+impl Registry {
+    pub fn open(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// 注册表根目录
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn index_path(&self, name: &str) -> PathBuf {
+        self.root.join("index").join(format!("{}.toml", name))
+    }
+
+    fn archive_path(&self, name: &str, version: &str) -> PathBuf {
+        self.root.join("packages").join(name).join(archive_name(name, version))
+    }
+
+    /// 包的所有已发布版本；未发布过的包返回空列表
+    pub fn versions(&self, name: &str) -> Result<Vec<IndexEntry>> {
+        let path = self.index_path(name);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("读取注册表索引失败: {}", path.display()))?;
+        let index: IndexFile = toml::from_str(&content)
+            .with_context(|| format!("解析注册表索引失败: {}", path.display()))?;
+        Ok(index.versions)
+    }
+
+    /// 发布归档：复制到 packages/ 并在索引中加入新版本
+    pub fn publish(&self, package: &PackageArchive) -> Result<()> {
+        let mut versions = self.versions(&package.name)?;
+        if versions.iter().any(|entry| entry.version == package.version) {
+            bail!("{} v{} 已发布到注册表 {}", package.name, package.version, self.root.display());
+        }
+
+        let archive_path = self.archive_path(&package.name, &package.version);
+        ensure_dir(archive_path.parent().expect("archive path has a parent"))?;
+        std::fs::copy(&package.path, &archive_path)
+            .with_context(|| format!("复制归档到注册表失败: {}", archive_path.display()))?;
+
+        versions.push(IndexEntry {
+            version: package.version.clone(),
+            checksum: package.checksum.clone(),
+            dependencies: package.dependencies.clone(),
+        });
+        versions.sort_by(|a, b| match (Version::parse(&a.version), Version::parse(&b.version)) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            _ => a.version.cmp(&b.version),
+        });
+
+        // 先写临时文件再改名，读取索引的构建不会看到写了一半的文件
+        let index_path = self.index_path(&package.name);
+        ensure_dir(index_path.parent().expect("index path has a parent"))?;
+        let content = toml::to_string(&IndexFile { versions }).context("序列化注册表索引失败")?;
+        let tmp_path = index_path.with_extension("toml.tmp");
+        std::fs::write(&tmp_path, content)
+            .with_context(|| format!("写入注册表索引失败: {}", tmp_path.display()))?;
+        std::fs::rename(&tmp_path, &index_path)
+            .with_context(|| format!("写入注册表索引失败: {}", index_path.display()))
+    }
+
+    /// 校验归档并解压到缓存目录，返回包目录
+    pub fn unpack(&self, name: &str, version: &str, checksum: &str, cache_root: &Path) -> Result<PathBuf> {
+        let dest = cache_root
+            .join(super::git::repo_ident(&self.root.to_string_lossy()))
+            .join(format!("{}-{}", name, version));
+        let marker = dest.join(UNPACK_MARKER);
+        if std::fs::read_to_string(&marker).is_ok_and(|recorded| recorded == checksum) {
+            return Ok(dest);
+        }
+
+        let archive_path = self.archive_path(name, version);
+        let data = std::fs::read(&archive_path)
+            .with_context(|| format!("读取注册表归档失败: {}", archive_path.display()))?;
+        let actual = archive::sha256_hex(&data);
+        if actual != checksum {
+            bail!("{} v{} 的归档校验和不匹配（期望 {}，实际 {}）: {}",
+                name, version, checksum, actual, archive_path.display());
+        }
+
+        let _ = std::fs::remove_dir_all(&dest);
+        let staging = dest.with_extension("unpack");
+        let _ = std::fs::remove_dir_all(&staging);
+        ensure_dir(&staging)?;
+        archive::unpack(&data, &staging)?;
+        let package_dir = staging.join(format!("{}-{}", name, version));
+        if !package_dir.join(CONFIG_FILE).exists() {
+            bail!("{} v{} 的归档中没有 {}", name, version, CONFIG_FILE);
+        }
+        std::fs::rename(&package_dir, &dest)
+            .with_context(|| format!("解压注册表归档失败: {}", dest.display()))?;
+        let _ = std::fs::remove_dir_all(&staging);
+        std::fs::write(&marker, checksum)
+            .with_context(|| format!("写入解压标记失败: {}", marker.display()))?;
+        Ok(dest)
+    }
+}
+
+/// 归档文件名
+pub fn archive_name(name: &str, version: &str) -> String {
+    format!("{}-{}.tar", name, version)
+}
+
+/// 打包项目：cavly.toml（path 依赖转换为版本依赖）、`src_dir` 下的所有文件和构建脚本
+///
+/// 归档写入 `target/package/<包名>-<版本>.tar`
+pub fn package(project_root: &Path, config: &CavlyConfig) -> Result<PackageArchive> {
+    if !config.is_lib() {
+        bail!("只有库项目可以打包发布（{} 的 project_type 不是 lib）", config.package.name);
+    }
+    let name = config.package.name.clone();
+    let version = config.package.version.clone();
+    Version::parse(&version).with_context(|| format!("{} 的版本号无效", name))?;
+
+    let (manifest, dependencies) = normalize_manifest(&project_root.join(CONFIG_FILE))?;
+    let prefix = format!("{}-{}", name, version);
+    let mut builder = TarBuilder::new();
+    builder.append_file(&format!("{}/{}", prefix, CONFIG_FILE), manifest.as_bytes())?;
+
+    let src_dir = project_root.join(&config.package.src_dir);
+    if !config.main_source_path(project_root).exists() {
+        bail!("主源文件不存在: {}", config.main_source_path(project_root).display());
+    }
+    let mut files = Vec::new();
+    collect_files(&src_dir, &mut files)?;
+    // 构建脚本可以在源代码目录之外
+    if let Some(script) = config.build_script_path(project_root) {
+        if !script.exists() {
+            bail!("构建脚本不存在: {}", script.display());
+        }
+        if !files.contains(&script) {
+            files.push(script);
+        }
+    }
+    files.sort();
+    for file in &files {
+        let relative = file.strip_prefix(project_root).unwrap_or(file);
+        let archive_path: Vec<String> = relative.components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        let contents = std::fs::read(file)
+            .with_context(|| format!("读取文件失败: {}", file.display()))?;
+        builder.append_file(&format!("{}/{}", prefix, archive_path.join("/")), &contents)?;
+    }
+
+    let data = builder.finish();
+    let package_dir = config.target_path(project_root).join("package");
+    ensure_dir(&package_dir)?;
+    let path = package_dir.join(archive_name(&name, &version));
+    std::fs::write(&path, &data)
+        .with_context(|| format!("写入归档失败: {}", path.display()))?;
+
+    Ok(PackageArchive {
+        name,
+        version,
+        path,
+        checksum: archive::sha256_hex(&data),
+        dependencies,
+    })
+}
+
+/// 校验归档：解压到 `target/package/<包名>-<版本>/`，解析依赖后编译检查（Cavvy → IR）
+pub fn verify(package: &PackageArchive, options: ResolveOptions, verbose: bool) -> Result<()> {
+    let data = std::fs::read(&package.path)
+        .with_context(|| format!("读取归档失败: {}", package.path.display()))?;
+    let package_dir = package.path.parent().expect("archive has a parent directory");
+    let unpacked = package_dir.join(format!("{}-{}", package.name, package.version));
+    let _ = std::fs::remove_dir_all(&unpacked);
+    archive::unpack(&data, package_dir)?;
+
+    let config = CavlyConfig::from_file(&unpacked.join(CONFIG_FILE))?;
+    let mut builder = Builder::with_resolve_options(unpacked.clone(), config, options)
+        .with_context(|| format!("校验 {} v{} 失败", package.name, package.version))?
+        .verbose(verbose);
+    builder.check()
+        .with_context(|| format!("校验 {} v{} 失败：归档中的源码无法编译", package.name, package.version))?;
+    Ok(())
+}
+
+/// 递归收集目录下的文件
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("读取目录失败: {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 发布用的 cavly.toml：path 依赖去掉 `path`（必须写明 `version`），不允许 git 依赖
+fn normalize_manifest(path: &Path) -> Result<(String, Vec<IndexDependency>)> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("读取配置文件失败: {}", path.display()))?;
+    let mut manifest: toml::Table = toml::from_str(&content)
+        .with_context(|| format!("解析配置文件失败: {}", path.display()))?;
+
+    let mut dependencies = Vec::new();
+    if let Some(toml::Value::Table(deps)) = manifest.get_mut("dependencies") {
+        for (name, dep) in deps.iter_mut() {
+            let (req, optional) = match dep {
+                toml::Value::String(req) => (req.clone(), false),
+                toml::Value::Table(table) => {
+                    if table.contains_key("git") {
+                        bail!("依赖 {} 是 git 依赖，发布到注册表的包只能依赖注册表中的包", name);
+                    }
+                    let version = table.get("version").and_then(|v| v.as_str()).map(String::from);
+                    if table.remove("path").is_some() && version.is_none() {
+                        bail!("path 依赖 {} 必须指定 version 才能发布", name);
+                    }
+                    let optional = table.get("optional").and_then(|v| v.as_bool()).unwrap_or(false);
+                    (version.unwrap_or_else(|| "*".to_string()), optional)
+                }
+                _ => bail!("依赖 {} 的配置无效", name),
+            };
+            VersionReq::parse(&req).with_context(|| format!("依赖 {} 的版本要求无效", name))?;
+            dependencies.push(IndexDependency { name: name.clone(), req, optional });
+        }
+    }
+    dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let normalized = toml::to_string(&manifest).context("序列化配置失败")?;
+    Ok((normalized, dependencies))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cavly::fixtures;
+    use tempfile::TempDir;
+
+    /// 在 `root/<name>-<version>` 创建带 src/util/math.cay 的库项目
+    fn write_lib(root: &Path, name: &str, version: &str, deps: &str) -> (PathBuf, CavlyConfig) {
+        let path = fixtures::write_lib(root, &format!("{}-{}", name, version), name, version, deps);
+        std::fs::create_dir_all(path.join("src").join("util")).unwrap();
+        std::fs::write(path.join("src").join("util").join("math.cay"), "// math\n").unwrap();
+        let config = CavlyConfig::from_file(&path.join(CONFIG_FILE)).unwrap();
+        (path, config)
+    }
+
+    #[test]
+    fn test_package_normalizes_dependencies() {
+        let temp = TempDir::new().unwrap();
+        let (path, config) = write_lib(temp.path(), "json", "1.2.0",
+            "base = { path = \"../base\", version = \"^1.0\" }\nlog = \"0.3\"\n");
+        let archive = package(&path, &config).unwrap();
+        assert_eq!(archive.path, path.join("target").join("package").join("json-1.2.0.tar"));
+        assert_eq!(archive.dependencies, vec![
+            IndexDependency { name: "base".into(), req: "^1.0".into(), optional: false },
+            IndexDependency { name: "log".into(), req: "0.3".into(), optional: false },
+        ]);
+
+        let files = archive::read_tar(&std::fs::read(&archive.path).unwrap()).unwrap();
+        let names: Vec<&str> = files.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["json-1.2.0/cavly.toml", "json-1.2.0/src/lib.cay", "json-1.2.0/src/util/math.cay"]);
+        let manifest = String::from_utf8(files[0].1.clone()).unwrap();
+        assert!(!manifest.contains("path"), "{}", manifest);
+
+        // 相同内容得到相同的校验和
+        assert_eq!(package(&path, &config).unwrap().checksum, archive.checksum);
+    }
+
+    #[test]
+    fn test_package_rejects_unpublishable_dependencies() {
+        let temp = TempDir::new().unwrap();
+        let (path, config) = write_lib(temp.path(), "a", "1.0.0", "base = { path = \"../base\" }\n");
+        assert!(package(&path, &config).unwrap_err().to_string().contains("必须指定 version"));
+
+        let (path, config) = write_lib(temp.path(), "b", "1.0.0", "base = { git = \"file:///tmp/base\" }\n");
+        assert!(package(&path, &config).unwrap_err().to_string().contains("git 依赖"));
+    }
+
+    #[test]
+    fn test_publish_and_unpack() {
+        let temp = TempDir::new().unwrap();
+        let registry = Registry::open(temp.path().join("registry"));
+        for version in ["1.0.0", "0.9.0"] {
+            let (path, config) = write_lib(temp.path(), "json", version, "");
+            registry.publish(&package(&path, &config).unwrap()).unwrap();
+        }
+        let versions = registry.versions("json").unwrap();
+        assert_eq!(versions.iter().map(|e| e.version.as_str()).collect::<Vec<_>>(), vec!["0.9.0", "1.0.0"]);
+        assert!(registry.versions("missing").unwrap().is_empty());
+
+        let (path, config) = write_lib(temp.path(), "json", "1.0.0", "");
+        let err = registry.publish(&package(&path, &config).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("json v1.0.0 已发布"), "{}", err);
+
+        let cache = temp.path().join("cache");
+        let entry = &versions[1];
+        let unpacked = registry.unpack("json", "1.0.0", &entry.checksum, &cache).unwrap();
+        assert!(unpacked.join("src").join("util").join("math.cay").exists());
+        assert_eq!(CavlyConfig::from_file(&unpacked.join(CONFIG_FILE)).unwrap().package.version, "1.0.0");
+
+        // 归档被改动后校验失败
+        let archive_path = registry.archive_path("json", "0.9.0");
+        let mut data = std::fs::read(&archive_path).unwrap();
+        let last = data.len() - 1030;
+        data[last] ^= 1;
+        std::fs::write(&archive_path, data).unwrap();
+        let err = registry.unpack("json", "0.9.0", &versions[0].checksum, &cache).unwrap_err();
+        assert!(err.to_string().contains("校验和不匹配"), "{}", err);
+    }
+}
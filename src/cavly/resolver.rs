@@ -0,0 +1,394 @@
+// 依赖版本解析：为依赖图中的每个包选择一个满足所有版本要求的版本
+// 时间复杂度: 最坏 O(v^n) 回溯，n 为包数量，v 为每个包的候选版本数；无冲突时 O(n*v)
+// 空间复杂度: O(n) 每层回溯保存一份已选版本
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+
+use super::git::GitReference;
+use super::semver::{Version, VersionReq};
+
+/// 包的来源
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SourceId {
+    /// 本地路径依赖
+    Path(PathBuf),
+    /// git 依赖：仓库地址、引用、解析出的提交和本地检出目录
+    Git {
+        url: String,
+        reference: GitReference,
+        commit: String,
+        checkout: PathBuf,
+    },
+    /// 注册表中的包：注册表目录和归档的校验和（选中后才解压）
+    Registry {
+        root: PathBuf,
+        checksum: String,
+    },
+}
+
+impl SourceId {
+    /// 包所在的本地目录；注册表包在解压之前没有本地目录
+    pub fn local_path(&self) -> Option<&Path> {
+        match self {
+            SourceId::Path(path) => Some(path),
+            SourceId::Git { checkout, .. } => Some(checkout),
+            SourceId::Registry { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for SourceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceId::Path(path) => write!(f, "{}", path.display()),
+            SourceId::Git { url, reference, commit, .. } => {
+                write!(f, "{}（{}，提交 {}）", url, reference, &commit[..commit.len().min(12)])
+            }
+            SourceId::Registry { root, .. } => write!(f, "注册表 {}", root.display()),
+        }
+    }
+}
+
+/// 对一个包的依赖要求
+#[derive(Debug, Clone)]
+pub struct DependencyReq {
+    /// 包名
+    pub name: String,
+    /// 版本要求
+    pub req: VersionReq,
+    /// 指定的来源（path 依赖）；None 表示任意来源
+    pub source: Option<SourceId>,
+    /// 可选依赖：没有任何来源提供这个包时跳过
+    pub optional: bool,
+}
+
+impl DependencyReq {
+    /// 候选版本是否满足这个要求
+    fn accepts(&self, summary: &Summary) -> bool {
+        self.req.matches(&summary.version)
+            && self.source.as_ref().is_none_or(|source| *source == summary.source)
+    }
+}
+
+/// 一个包的某个版本及其依赖
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub name: String,
+    pub version: Version,
+    pub source: SourceId,
+    pub dependencies: Vec<DependencyReq>,
+}
+
+/// 可用包版本的来源
+pub trait PackageSource {
+    /// 查询包的所有可用版本
+    fn query(&self, name: &str) -> Result<Vec<Summary>>;
+}
+
+/// 解析结果
+#[derive(Debug, Clone, Default)]
+pub struct Resolution {
+    /// 选中的包（包名 -> 版本）
+    pub packages: BTreeMap<String, Summary>,
+    /// 依赖边：包名（包括根项目）-> 直接依赖的包名
+    pub edges: BTreeMap<String, Vec<String>>,
+    /// 每个包是否只被可选依赖引用
+    pub optional: BTreeMap<String, bool>,
+}
+
+/// 解析失败：版本冲突可以回溯，查询出错直接终止
+enum Failure {
+    Conflict(String),
+    Fatal(anyhow::Error),
+}
+
+/// 提出依赖要求的包：包名和用于错误信息的 `名称 v版本`
+#[derive(Clone)]
+struct Requirer {
+    name: String,
+    display: String,
+}
+
+/// 回溯过程中的状态
+#[derive(Clone, Default)]
+struct State {
+    /// 已选中的版本
+    activated: BTreeMap<String, Summary>,
+    /// 每个包收到的要求：(要求者, 要求)
+    requirements: BTreeMap<String, Vec<(String, DependencyReq)>>,
+    /// 依赖边
+    edges: BTreeMap<String, Vec<String>>,
+}
+
+/// 版本解析器
+///
+/// 每个包名只选一个版本（同一个库的两个版本不能链接进同一个程序），
+/// 依次尝试最高的兼容版本，冲突时回溯到上一个选择点。lock 文件中锁定的版本仍满足要求时优先选择。
+pub struct Resolver<'a> {
+    source: &'a dyn PackageSource,
+    preferred: HashMap<String, Version>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(source: &'a dyn PackageSource) -> Self {
+        Self { source, preferred: HashMap::new() }
+    }
+
+    /// 优先选择的版本（来自 lock 文件）
+    pub fn prefer(&mut self, name: &str, version: Version) {
+        self.preferred.insert(name.to_string(), version);
+    }
+
+    /// 从根项目的依赖开始解析整个依赖图
+    pub fn resolve(&self, root: &str, dependencies: &[DependencyReq]) -> Result<Resolution> {
+        let pending = dependencies.iter()
+            .map(|dep| (Requirer { name: root.to_string(), display: root.to_string() }, dep.clone()))
+            .collect();
+        let mut state = State::default();
+        state.edges.insert(root.to_string(), Vec::new());
+
+        let state = match self.solve(state, pending) {
+            Ok(state) => state,
+            Err(Failure::Conflict(message)) => anyhow::bail!("{}", message),
+            Err(Failure::Fatal(err)) => return Err(err),
+        };
+
+        let mut optional = BTreeMap::new();
+        for (name, reqs) in &state.requirements {
+            if state.activated.contains_key(name) {
+                optional.insert(name.clone(), reqs.iter().all(|(_, req)| req.optional));
+            }
+        }
+        Ok(Resolution { packages: state.activated, edges: state.edges, optional })
+    }
+
+    /// 处理待解析的依赖，遇到需要选择版本的包时逐个尝试候选版本
+    fn solve(&self, mut state: State, mut pending: VecDeque<(Requirer, DependencyReq)>) -> Result<State, Failure> {
+        while let Some((requirer, dep)) = pending.pop_front() {
+            let name = dep.name.clone();
+            state.requirements.entry(name.clone()).or_default().push((requirer.display, dep.clone()));
+
+            if let Some(active) = state.activated.get(&name) {
+                if !dep.accepts(active) {
+                    return Err(Failure::Conflict(self.conflict_message(&state, &name)));
+                }
+                state.edges.entry(requirer.name).or_default().push(name);
+                continue;
+            }
+
+            let all = self.source.query(&name).map_err(Failure::Fatal)?;
+            if all.is_empty() && dep.optional {
+                state.requirements.remove(&name);
+                continue;
+            }
+            state.edges.entry(requirer.name).or_default().push(name.clone());
+            let candidates = self.candidates(&state, &name, all);
+            if candidates.is_empty() {
+                return Err(Failure::Conflict(self.conflict_message(&state, &name)));
+            }
+
+            let mut last_failure = None;
+            for candidate in candidates {
+                let mut next_state = state.clone();
+                let mut next_pending = pending.clone();
+                let requirer = Requirer {
+                    name: candidate.name.clone(),
+                    display: format!("{} v{}", candidate.name, candidate.version),
+                };
+                next_state.edges.entry(candidate.name.clone()).or_default();
+                for child in &candidate.dependencies {
+                    next_pending.push_back((requirer.clone(), child.clone()));
+                }
+                next_state.activated.insert(name.clone(), candidate);
+                match self.solve(next_state, next_pending) {
+                    Ok(solved) => return Ok(solved),
+                    Err(Failure::Fatal(err)) => return Err(Failure::Fatal(err)),
+                    Err(conflict) => last_failure = Some(conflict),
+                }
+            }
+            return Err(last_failure.expect("candidates is not empty"));
+        }
+        Ok(state)
+    }
+
+    /// 满足所有已知要求的候选版本：锁定的版本在前，其余从高到低
+    fn candidates(&self, state: &State, name: &str, all: Vec<Summary>) -> Vec<Summary> {
+        let reqs = &state.requirements[name];
+        let mut candidates: Vec<Summary> = all.into_iter()
+            .filter(|summary| reqs.iter().all(|(_, req)| req.accepts(summary)))
+            .collect();
+        let preferred = self.preferred.get(name);
+        candidates.sort_by(|a, b| {
+            let a_locked = Some(&a.version) == preferred;
+            let b_locked = Some(&b.version) == preferred;
+            b_locked.cmp(&a_locked).then_with(|| b.version.cmp(&a.version))
+        });
+        candidates
+    }
+
+    /// 冲突说明：列出所有要求者和可用版本
+    fn conflict_message(&self, state: &State, name: &str) -> String {
+        let reqs = &state.requirements[name];
+        let available: Vec<String> = match self.source.query(name) {
+            Ok(mut all) => {
+                all.sort_by(|a, b| b.version.cmp(&a.version));
+                all.iter().map(|s| s.version.to_string()).collect()
+            }
+            Err(_) => Vec::new(),
+        };
+
+        let describe = |req: &DependencyReq| match &req.source {
+            Some(source) => format!("{} {}（来自 {}）", name, req.req, source),
+            None => format!("{} {}", name, req.req),
+        };
+
+        let mut message = if reqs.len() == 1 {
+            let (requirer, req) = &reqs[0];
+            if available.is_empty() {
+                format!("找不到依赖 `{}`（{} 需要）：没有任何来源提供这个包", name, requirer)
+            } else {
+                format!("找不到满足 `{}` 的版本（{} 需要）", describe(req), requirer)
+            }
+        } else {
+            let mut message = format!("依赖版本冲突：无法为 `{}` 选择同时满足所有要求的版本", name);
+            for (requirer, req) in reqs {
+                message.push_str(&format!("\n  {} 要求 {}", requirer, describe(req)));
+            }
+            message
+        };
+        if let Some(active) = state.activated.get(name) {
+            message.push_str(&format!("\n  已选中 {} v{}", name, active.version));
+        }
+        if !available.is_empty() {
+            message.push_str(&format!("\n  可用版本: {}", available.join(", ")));
+        }
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 内存中的包来源
+    #[derive(Default)]
+    struct MemorySource {
+        packages: Vec<Summary>,
+    }
+
+    impl MemorySource {
+        fn add(&mut self, name: &str, version: &str, deps: &[(&str, &str)]) {
+            self.packages.push(Summary {
+                name: name.to_string(),
+                version: Version::parse(version).unwrap(),
+                source: SourceId::Path(PathBuf::from(format!("/pkgs/{}-{}", name, version))),
+                dependencies: deps.iter().map(|(n, r)| req(n, r)).collect(),
+            });
+        }
+    }
+
+    impl PackageSource for MemorySource {
+        fn query(&self, name: &str) -> Result<Vec<Summary>> {
+            Ok(self.packages.iter().filter(|s| s.name == name).cloned().collect())
+        }
+    }
+
+    fn req(name: &str, req: &str) -> DependencyReq {
+        DependencyReq {
+            name: name.to_string(),
+            req: VersionReq::parse(req).unwrap(),
+            source: None,
+            optional: false,
+        }
+    }
+
+    fn selected(resolution: &Resolution, name: &str) -> String {
+        resolution.packages[name].version.to_string()
+    }
+
+    #[test]
+    fn test_picks_highest_compatible_versions() {
+        let mut source = MemorySource::default();
+        source.add("json", "1.0.0", &[]);
+        source.add("json", "1.4.2", &[]);
+        source.add("json", "2.0.0", &[]);
+        source.add("http", "0.3.1", &[("json", "^1.2")]);
+
+        let resolution = Resolver::new(&source)
+            .resolve("app", &[req("http", "0.3"), req("json", ">=1.0")])
+            .unwrap();
+        assert_eq!(selected(&resolution, "http"), "0.3.1");
+        // app 允许 2.0.0，但 http 需要 ^1.2，回溯后选中 1.4.2
+        assert_eq!(selected(&resolution, "json"), "1.4.2");
+        assert_eq!(resolution.edges["app"], vec!["http", "json"]);
+        assert_eq!(resolution.edges["http"], vec!["json"]);
+    }
+
+    #[test]
+    fn test_backtracks_to_older_dependent_version() {
+        let mut source = MemorySource::default();
+        source.add("log", "1.0.0", &[]);
+        source.add("log", "2.0.0", &[]);
+        source.add("net", "1.1.0", &[("log", "^2")]);
+        source.add("net", "1.0.0", &[("log", "^1")]);
+
+        let resolution = Resolver::new(&source)
+            .resolve("app", &[req("log", "^1"), req("net", "^1")])
+            .unwrap();
+        assert_eq!(selected(&resolution, "net"), "1.0.0");
+        assert_eq!(selected(&resolution, "log"), "1.0.0");
+    }
+
+    #[test]
+    fn test_conflict_names_both_requirers() {
+        let mut source = MemorySource::default();
+        source.add("log", "1.5.0", &[]);
+        source.add("log", "2.1.0", &[]);
+        source.add("net", "1.0.0", &[("log", "^2")]);
+
+        let err = Resolver::new(&source)
+            .resolve("app", &[req("log", "^1.2"), req("net", "1")])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("依赖版本冲突"), "{}", err);
+        assert!(err.contains("app 要求 log ^1.2"), "{}", err);
+        assert!(err.contains("net v1.0.0 要求 log ^2"), "{}", err);
+        assert!(err.contains("可用版本: 2.1.0, 1.5.0"), "{}", err);
+    }
+
+    #[test]
+    fn test_missing_package_and_unsatisfiable_requirement() {
+        let mut source = MemorySource::default();
+        source.add("json", "1.0.0", &[]);
+
+        let err = Resolver::new(&source).resolve("app", &[req("yaml", "1")]).unwrap_err().to_string();
+        assert!(err.contains("找不到依赖 `yaml`（app 需要）"), "{}", err);
+
+        let err = Resolver::new(&source).resolve("app", &[req("json", "^2")]).unwrap_err().to_string();
+        assert!(err.contains("找不到满足 `json ^2` 的版本（app 需要）"), "{}", err);
+
+        let mut optional = req("yaml", "1");
+        optional.optional = true;
+        let resolution = Resolver::new(&source).resolve("app", &[optional]).unwrap();
+        assert!(resolution.packages.is_empty());
+    }
+
+    #[test]
+    fn test_prefers_locked_version() {
+        let mut source = MemorySource::default();
+        source.add("json", "1.0.0", &[]);
+        source.add("json", "1.3.0", &[]);
+
+        let mut resolver = Resolver::new(&source);
+        resolver.prefer("json", Version::new(1, 0, 0));
+        let resolution = resolver.resolve("app", &[req("json", "1")]).unwrap();
+        assert_eq!(selected(&resolution, "json"), "1.0.0");
+
+        // 锁定的版本不再满足要求时重新选择
+        let resolution = resolver.resolve("app", &[req("json", "^1.1")]).unwrap();
+        assert_eq!(selected(&resolution, "json"), "1.3.0");
+    }
+}
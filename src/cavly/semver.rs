@@ -0,0 +1,461 @@
+// 语义化版本（SemVer 2.0）与版本要求
+// 时间复杂度: O(n) 解析，n 为字符串长度；O(c) 匹配，c 为比较器数量
+// 空间复杂度: O(n)
+
+use std::cmp::Ordering;
+use std::fmt;
+use anyhow::{Result, bail};
+
+/// 语义化版本号：`主.次.修订[-预发布][+构建元数据]`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// 预发布标识（如 `alpha.1`），为空表示正式版本
+    pub pre: Vec<String>,
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self { major, minor, patch, pre: Vec::new() }
+    }
+
+    /// 解析版本号，构建元数据（`+` 之后）被忽略
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let without_build = text.split('+').next().unwrap_or_default();
+        let (core, pre) = match without_build.split_once('-') {
+            Some((core, pre)) => (core, parse_pre(pre, text)?),
+            None => (without_build, Vec::new()),
+        };
+        let parts: Vec<&str> = core.split('.').collect();
+        if parts.len() != 3 {
+            bail!("无效的版本号 '{}'：应为 主.次.修订 格式（如 1.2.3）", text);
+        }
+        Ok(Self {
+            major: parse_number(parts[0], text)?,
+            minor: parse_number(parts[1], text)?,
+            patch: parse_number(parts[2], text)?,
+            pre,
+        })
+    }
+
+    /// 是否为预发布版本
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch).cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| compare_pre(&self.pre, &other.pre))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 比较预发布标识：正式版本大于预发布版本，数字标识按数值比较且小于字母标识
+fn compare_pre(a: &[String], b: &[String]) -> Ordering {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        (false, false) => {}
+    }
+    for (x, y) in a.iter().zip(b) {
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+fn parse_number(part: &str, text: &str) -> Result<u64> {
+    if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+        bail!("无效的版本号 '{}'：'{}' 不是数字", text, part);
+    }
+    if part.len() > 1 && part.starts_with('0') {
+        bail!("无效的版本号 '{}'：数字 '{}' 不能有前导零", text, part);
+    }
+    part.parse().map_err(|_| anyhow::anyhow!("无效的版本号 '{}'：'{}' 超出范围", text, part))
+}
+
+fn parse_pre(pre: &str, text: &str) -> Result<Vec<String>> {
+    let identifiers: Vec<String> = pre.split('.').map(String::from).collect();
+    for id in &identifiers {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            bail!("无效的版本号 '{}'：预发布标识 '{}' 无效", text, id);
+        }
+    }
+    Ok(identifiers)
+}
+
+/// 比较运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// `=1.2.3`
+    Exact,
+    /// `>1.2.3`
+    Greater,
+    /// `>=1.2.3`
+    GreaterEq,
+    /// `<1.2.3`
+    Less,
+    /// `<=1.2.3`
+    LessEq,
+    /// `~1.2.3`：允许修订号变化
+    Tilde,
+    /// `^1.2.3` 或 `1.2.3`：允许不改变最左侧非零位的更新
+    Caret,
+    /// `1.*`、`1.2.x`：通配符
+    Wildcard,
+}
+
+/// 单个比较器，次版本号和修订号可以省略（如 `^1`、`~1.2`）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparator {
+    pub op: Op,
+    pub major: u64,
+    pub minor: Option<u64>,
+    pub patch: Option<u64>,
+    pub pre: Vec<String>,
+}
+
+impl Comparator {
+    fn parse(text: &str, full: &str) -> Result<Self> {
+        let text = text.trim();
+        let (op, rest) = if let Some(rest) = text.strip_prefix(">=") {
+            (Op::GreaterEq, rest)
+        } else if let Some(rest) = text.strip_prefix("<=") {
+            (Op::LessEq, rest)
+        } else if let Some(rest) = text.strip_prefix('>') {
+            (Op::Greater, rest)
+        } else if let Some(rest) = text.strip_prefix('<') {
+            (Op::Less, rest)
+        } else if let Some(rest) = text.strip_prefix('=') {
+            (Op::Exact, rest)
+        } else if let Some(rest) = text.strip_prefix('~') {
+            (Op::Tilde, rest)
+        } else if let Some(rest) = text.strip_prefix('^') {
+            (Op::Caret, rest)
+        } else {
+            (Op::Caret, text)
+        };
+        let rest = rest.trim();
+        let (core, pre) = match rest.split_once('-') {
+            Some((core, pre)) => (core, parse_pre(pre, full)?),
+            None => (rest, Vec::new()),
+        };
+
+        let parts: Vec<&str> = core.split('.').collect();
+        if parts.is_empty() || parts.len() > 3 || parts[0].is_empty() {
+            bail!("无效的版本要求 '{}'", full);
+        }
+        let is_wildcard = |part: &str| matches!(part, "*" | "x" | "X");
+
+        let mut numbers = Vec::new();
+        let mut wildcard = false;
+        for part in &parts {
+            if is_wildcard(part) {
+                wildcard = true;
+            } else if wildcard {
+                bail!("无效的版本要求 '{}'：通配符之后不能再有数字", full);
+            } else {
+                numbers.push(parse_number(part, full)?);
+            }
+        }
+        if wildcard {
+            if op != Op::Caret || text.starts_with('^') {
+                bail!("无效的版本要求 '{}'：通配符不能与运算符一起使用", full);
+            }
+            if numbers.is_empty() {
+                // `*` 匹配任意正式版本，用 `>=0.0.0` 表示
+                return Ok(Self { op: Op::GreaterEq, major: 0, minor: Some(0), patch: Some(0), pre: Vec::new() });
+            }
+        }
+        if !pre.is_empty() && numbers.len() != 3 {
+            bail!("无效的版本要求 '{}'：预发布版本必须写出完整的版本号", full);
+        }
+
+        Ok(Self {
+            op: if wildcard { Op::Wildcard } else { op },
+            major: numbers[0],
+            minor: numbers.get(1).copied(),
+            patch: numbers.get(2).copied(),
+            pre,
+        })
+    }
+
+    /// 版本是否满足此比较器（不考虑预发布版本的额外限制）
+    fn matches(&self, version: &Version) -> bool {
+        match self.op {
+            Op::Exact | Op::Wildcard => self.matches_exact(version),
+            Op::Greater => self.matches_greater(version),
+            Op::GreaterEq => self.matches_exact(version) || self.matches_greater(version),
+            Op::Less => !self.matches_exact(version) && !self.matches_greater(version),
+            Op::LessEq => !self.matches_greater(version),
+            Op::Tilde => self.matches_tilde(version),
+            Op::Caret => self.matches_caret(version),
+        }
+    }
+
+    /// 在比较器写出的各位上相等
+    fn matches_exact(&self, version: &Version) -> bool {
+        if version.major != self.major {
+            return false;
+        }
+        if self.minor.is_some_and(|minor| version.minor != minor) {
+            return false;
+        }
+        if self.patch.is_some_and(|patch| version.patch != patch) {
+            return false;
+        }
+        version.pre == self.pre || self.patch.is_none()
+    }
+
+    /// 在比较器写出的各位上大于比较器
+    fn matches_greater(&self, version: &Version) -> bool {
+        if version.major != self.major {
+            return version.major > self.major;
+        }
+        let minor = match self.minor {
+            Some(minor) => minor,
+            None => return false,
+        };
+        if version.minor != minor {
+            return version.minor > minor;
+        }
+        let patch = match self.patch {
+            Some(patch) => patch,
+            None => return false,
+        };
+        if version.patch != patch {
+            return version.patch > patch;
+        }
+        compare_pre(&version.pre, &self.pre) == Ordering::Greater
+    }
+
+    fn matches_tilde(&self, version: &Version) -> bool {
+        if version.major != self.major {
+            return false;
+        }
+        if self.minor.is_some_and(|minor| version.minor != minor) {
+            return false;
+        }
+        match self.patch {
+            Some(patch) if version.patch != patch => version.patch > patch,
+            Some(_) => compare_pre(&version.pre, &self.pre) != Ordering::Less,
+            None => true,
+        }
+    }
+
+    fn matches_caret(&self, version: &Version) -> bool {
+        if version.major != self.major {
+            return false;
+        }
+        let minor = match self.minor {
+            Some(minor) => minor,
+            None => return true,
+        };
+        let patch = match self.patch {
+            Some(patch) => patch,
+            None => {
+                return if self.major > 0 { version.minor >= minor } else { version.minor == minor };
+            }
+        };
+        if self.major > 0 {
+            if version.minor != minor {
+                return version.minor > minor;
+            }
+        } else if minor > 0 {
+            if version.minor != minor {
+                return false;
+            }
+        } else if version.minor != minor || version.patch != patch {
+            // ^0.0.x 只匹配这一个修订版本
+            return false;
+        }
+        if version.patch != patch {
+            return version.patch > patch;
+        }
+        compare_pre(&version.pre, &self.pre) != Ordering::Less
+    }
+
+    /// 比较器写出的预发布版本是否允许匹配这个预发布版本（相同的 主.次.修订）
+    fn allows_prerelease_of(&self, version: &Version) -> bool {
+        !self.pre.is_empty()
+            && self.major == version.major
+            && self.minor == Some(version.minor)
+            && self.patch == Some(version.patch)
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.op {
+            Op::Exact => "=",
+            Op::Greater => ">",
+            Op::GreaterEq => ">=",
+            Op::Less => "<",
+            Op::LessEq => "<=",
+            Op::Tilde => "~",
+            Op::Caret => "^",
+            Op::Wildcard => "",
+        };
+        write!(f, "{}{}", op, self.major)?;
+        match (self.minor, self.patch) {
+            (Some(minor), Some(patch)) => write!(f, ".{}.{}", minor, patch)?,
+            (Some(minor), None) => write!(f, ".{}", minor)?,
+            _ => {}
+        }
+        if self.op == Op::Wildcard {
+            write!(f, ".*")?;
+        }
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+/// 版本要求：逗号分隔的比较器，全部满足才匹配（如 `>=1.2, <1.5`）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    pub comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    /// 匹配任意正式版本的要求（`*`）
+    pub fn any() -> Self {
+        Self { comparators: Vec::new() }
+    }
+
+    /// 解析版本要求：`^1.2`、`~1.2.3`、`>=1.0, <2.0`、`=1.2.3`、`1.*`、`*`，不带运算符时等同 `^`
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            bail!("版本要求不能为空");
+        }
+        if text == "*" {
+            return Ok(Self::any());
+        }
+        let comparators = text.split(',')
+            .map(|part| Comparator::parse(part, text))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { comparators })
+    }
+
+    /// 版本是否满足要求
+    ///
+    /// 预发布版本只有在某个比较器写出了相同 主.次.修订 的预发布版本时才会匹配，
+    /// 避免 `^1.0` 意外选中 `1.1.0-alpha`
+    pub fn matches(&self, version: &Version) -> bool {
+        if !self.comparators.iter().all(|c| c.matches(version)) {
+            return false;
+        }
+        !version.is_prerelease() || self.comparators.iter().any(|c| c.allows_prerelease_of(version))
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.comparators.is_empty() {
+            return write!(f, "*");
+        }
+        let parts: Vec<String> = self.comparators.iter().map(|c| c.to_string()).collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(text: &str) -> Version {
+        Version::parse(text).unwrap()
+    }
+
+    fn matches(req: &str, version: &str) -> bool {
+        VersionReq::parse(req).unwrap().matches(&v(version))
+    }
+
+    #[test]
+    fn test_version_parse_and_order() {
+        assert_eq!(v("1.2.3"), Version::new(1, 2, 3));
+        assert_eq!(v("1.2.3+build.5").to_string(), "1.2.3");
+        assert!(v("1.0.0-alpha") < v("1.0.0-alpha.1"));
+        assert!(v("1.0.0-alpha.1") < v("1.0.0-beta"));
+        assert!(v("1.0.0-beta.2") < v("1.0.0-beta.11"));
+        assert!(v("1.0.0-rc.1") < v("1.0.0"));
+        assert!(v("1.9.0") < v("1.10.0"));
+        assert!(Version::parse("1.2").is_err());
+        assert!(Version::parse("01.2.3").is_err());
+        assert!(Version::parse("1.2.x").is_err());
+    }
+
+    #[test]
+    fn test_caret_and_default_requirements() {
+        assert!(matches("^1.2.3", "1.9.0"));
+        assert!(!matches("^1.2.3", "1.2.2"));
+        assert!(!matches("^1.2.3", "2.0.0"));
+        assert!(matches("1.2", "1.4.0"));
+        assert!(matches("^0.2.3", "0.2.9"));
+        assert!(!matches("^0.2.3", "0.3.0"));
+        assert!(matches("^0.0.3", "0.0.3"));
+        assert!(!matches("^0.0.3", "0.0.4"));
+        assert!(matches("^0", "0.9.9"));
+        assert!(!matches("^0", "1.0.0"));
+    }
+
+    #[test]
+    fn test_tilde_comparison_and_wildcards() {
+        assert!(matches("~1.2.3", "1.2.9"));
+        assert!(!matches("~1.2.3", "1.3.0"));
+        assert!(matches("~1", "1.9.0"));
+        assert!(matches(">=1.2, <1.5", "1.4.9"));
+        assert!(!matches(">=1.2, <1.5", "1.5.0"));
+        assert!(matches(">1.2", "1.3.0"));
+        assert!(!matches(">1.2", "1.2.9"));
+        assert!(matches("<=1.2", "1.2.9"));
+        assert!(matches("=1.2.3", "1.2.3"));
+        assert!(!matches("=1.2.3", "1.2.4"));
+        assert!(matches("1.*", "1.7.0"));
+        assert!(matches("1.2.x", "1.2.5"));
+        assert!(!matches("1.2.X", "1.3.0"));
+        assert!(matches("*", "3.0.0"));
+        assert!(VersionReq::parse(">=1.*").is_err());
+        assert!(VersionReq::parse("1.*.3").is_err());
+    }
+
+    #[test]
+    fn test_prerelease_requires_opt_in() {
+        assert!(!matches("^1.0", "1.1.0-alpha"));
+        assert!(!matches("*", "1.0.0-beta"));
+        assert!(matches("^1.1.0-alpha", "1.1.0-beta"));
+        assert!(matches("^1.1.0-alpha", "1.2.0"));
+        assert!(!matches("^1.1.0-alpha", "1.2.0-alpha"));
+        assert!(matches("=1.0.0-rc.1", "1.0.0-rc.1"));
+    }
+}
@@ -0,0 +1,373 @@
+// 时间复杂度: O(n) 生成或复制模板文件, n 为模板文件总大小
+// 空间复杂度: O(n) 生成的文件内容
+//
+// cavly new 的项目模板：内置模板在这里生成，用户模板是一个目录
+// （路径，或 ~/.cavly/templates/<名称>），复制时替换 {{name}} 和 {{class_name}}
+
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context, bail};
+
+use super::config::{default_config_template, default_lib_config_template, ProjectType};
+use super::project::{default_lib_source, gitignore_template, Project};
+use super::{cavly_home, CONFIG_FILE};
+
+/// 不指定 `--template` 时使用的模板
+pub const DEFAULT_TEMPLATE: &str = "cli";
+
+/// 内置模板：名称和说明
+pub const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("cli", "命令行程序：读取参数的 main、独立的逻辑类和测试"),
+    ("library", "库项目：导出类和测试"),
+    ("ffi-wrapper", "包装 C 库的库项目：extern 声明、按平台链接的构建脚本和测试"),
+    ("test-suite", "以测试为主的项目：被测代码和多个测试文件"),
+];
+
+/// 用户模板中替换为项目名的占位符
+const NAME_PLACEHOLDER: &str = "{{name}}";
+/// 用户模板中替换为类名（`my-app` → `MyApp`）的占位符
+const CLASS_NAME_PLACEHOLDER: &str = "{{class_name}}";
+
+/// 复制用户模板时跳过的目录
+const SKIPPED_DIRS: &[&str] = &[".git", "target"];
+
+/// 模板生成的一个文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateFile {
+    /// 相对于项目根目录的路径
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+impl TemplateFile {
+    fn new(path: impl Into<PathBuf>, contents: impl Into<String>) -> Self {
+        Self { path: path.into(), contents: contents.into() }
+    }
+}
+
+/// 项目模板
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Template {
+    Builtin(&'static str),
+    /// 用户模板目录
+    User { name: String, dir: PathBuf },
+}
+
+/// 用户模板的默认目录：`~/.cavly/templates`
+pub fn user_template_dir() -> PathBuf {
+    cavly_home().join("templates")
+}
+
+impl Template {
+    /// 查找模板：内置模板名，其次是目录路径（相对于 `base`），最后是用户模板目录中的同名子目录
+    pub fn find(name: &str, base: &Path) -> Result<Self> {
+        if let Some((builtin, _)) = BUILTIN_TEMPLATES.iter().find(|(builtin, _)| *builtin == name) {
+            return Ok(Template::Builtin(builtin));
+        }
+        let candidates = [base.join(name), user_template_dir().join(name)];
+        if let Some(dir) = candidates.into_iter().find(|dir| dir.is_dir()) {
+            if !dir.join(CONFIG_FILE).exists() {
+                bail!("模板目录 {} 中没有 {}", dir.display(), CONFIG_FILE);
+            }
+            return Ok(Template::User { name: name.to_string(), dir });
+        }
+        let builtin: Vec<&str> = BUILTIN_TEMPLATES.iter().map(|(name, _)| *name).collect();
+        bail!("找不到模板 '{}'（内置模板: {}；用户模板目录: {}）",
+            name, builtin.join(", "), user_template_dir().display())
+    }
+
+    /// 模板名
+    pub fn name(&self) -> &str {
+        match self {
+            Template::Builtin(name) => name,
+            Template::User { name, .. } => name,
+        }
+    }
+
+    /// 为项目 `project_name` 生成的文件
+    ///
+    /// # 复杂度
+    /// - 时间: O(n)，n 为模板文件总大小
+    /// - 空间: O(n)
+    pub fn files(&self, project_name: &str) -> Result<Vec<TemplateFile>> {
+        let class_name = Project::to_class_name(project_name);
+        match self {
+            Template::Builtin(name) => Ok(builtin_files(name, project_name, &class_name)),
+            Template::User { dir, .. } => {
+                let mut files = Vec::new();
+                collect_user_files(dir, dir, &mut files)?;
+                Ok(files.into_iter()
+                    .map(|file| TemplateFile {
+                        path: PathBuf::from(substitute(&file.path.to_string_lossy(), project_name, &class_name)),
+                        contents: substitute(&file.contents, project_name, &class_name),
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+/// 替换用户模板中的占位符
+fn substitute(text: &str, name: &str, class_name: &str) -> String {
+    text.replace(NAME_PLACEHOLDER, name).replace(CLASS_NAME_PLACEHOLDER, class_name)
+}
+
+/// 递归收集用户模板目录中的文件，按路径排序
+fn collect_user_files(root: &Path, dir: &Path, files: &mut Vec<TemplateFile>) -> Result<()> {
+    let mut entries = std::fs::read_dir(dir)
+        .with_context(|| format!("读取模板目录失败: {}", dir.display()))?
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if path.is_dir() {
+            let skipped = path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| SKIPPED_DIRS.contains(&name));
+            if !skipped {
+                collect_user_files(root, &path, files)?;
+            }
+            continue;
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("读取模板文件失败: {}", path.display()))?;
+        let relative = path.strip_prefix(root).expect("模板文件在模板目录中");
+        files.push(TemplateFile::new(relative, contents));
+    }
+    Ok(())
+}
+
+/// 测试文件名：`my-lib` → `tests/my_lib_test.cay`
+fn test_file_path(project_name: &str) -> String {
+    format!("tests/{}_test.cay", project_name.replace('-', "_"))
+}
+
+/// 内置模板的文件
+fn builtin_files(template: &str, name: &str, class_name: &str) -> Vec<TemplateFile> {
+    match template {
+        "cli" => vec![
+            TemplateFile::new(CONFIG_FILE, default_config_template(name)),
+            TemplateFile::new(".gitignore", gitignore_template(&ProjectType::Bin)),
+            TemplateFile::new("src/main.cay", format!(r#"// {name}: 命令行程序入口
+
+public int main(String[] args) {{
+    // 用法: {name} [名字]
+    String name = "World";
+    if (args.length > 1) {{
+        name = args[1];
+    }}
+    println(Greeter.greet(name));
+    return 0;
+}}
+"#)),
+            TemplateFile::new("src/greeter.cay", r#"// 程序逻辑放在 main 之外，cavly test 才能测试它
+
+public class Greeter {
+    public static String greet(String name) {
+        return "Hello, " + name + "!";
+    }
+}
+"#),
+            TemplateFile::new("tests/greeter_test.cay", r#"public class GreeterTest {
+    @Test
+    public static void greetsByName() {
+        assertEquals("Hello, Cavvy!", Greeter.greet("Cavvy"));
+    }
+}
+"#),
+        ],
+        "library" => vec![
+            TemplateFile::new(CONFIG_FILE, default_lib_config_template(name)),
+            TemplateFile::new(".gitignore", gitignore_template(&ProjectType::Lib)),
+            TemplateFile::new("src/lib.cay", default_lib_source(name)),
+            TemplateFile::new(test_file_path(name), format!(r#"public class {class_name}Test {{
+    @Test
+    public static void adds() {{
+        assertEquals(3, {class_name}.add(1, 2));
+    }}
+}}
+"#)),
+        ],
+        "ffi-wrapper" => vec![
+            TemplateFile::new(CONFIG_FILE, default_lib_config_template(name)
+                .replacen("# build = \"build.cay\"", "build = \"build.cay\"", 1)),
+            TemplateFile::new(".gitignore", gitignore_template(&ProjectType::Lib)),
+            TemplateFile::new("build.cay", r#"// 构建脚本：编译前运行，按目标平台选择要链接的 C 库
+#include <std/ffi.cay>
+
+public int main() {
+    c_string target = getenv("CAVLY_TARGET");
+    // Windows 的 C 运行时自带数学函数，其他平台需要链接 libm
+    if (strstr(target, "windows") == null) {
+        printf("cavly:link-lib=m\n");
+    }
+    return 0;
+}
+"#),
+            TemplateFile::new("src/lib.cay", format!(r#"// {name}: C 数学库的 Cavvy 包装
+
+extern {{
+    c_double cbrt(c_double x);
+    c_double hypot(c_double x, c_double y);
+}}
+
+public class {class_name} {{
+    // 立方根
+    public static double cubeRoot(double x) {{
+        return cbrt(x);
+    }}
+
+    // 点 (x, y) 到原点的距离
+    public static double distance(double x, double y) {{
+        return hypot(x, y);
+    }}
+}}
+"#)),
+            TemplateFile::new(test_file_path(name), format!(r#"public class {class_name}Test {{
+    @Test
+    public static void cubeRoot() {{
+        // 浮点结果只比较到足够的精度
+        double root = {class_name}.cubeRoot(27.0);
+        assertTrue(root > 2.999999 && root < 3.000001, "cbrt(27) 应接近 3");
+    }}
+
+    @Test
+    public static void distance() {{
+        assertEquals(5.0, {class_name}.distance(3.0, 4.0));
+    }}
+}}
+"#)),
+        ],
+        "test-suite" => vec![
+            TemplateFile::new(CONFIG_FILE, default_config_template(name)),
+            TemplateFile::new(".gitignore", gitignore_template(&ProjectType::Bin)),
+            TemplateFile::new("src/main.cay", r#"// 定义了顶层 main 的文件不参与测试构建，被测代码放在其他文件中
+
+public int main() {
+    println("5! = " + Calc.factorial(5));
+    return 0;
+}
+"#),
+            TemplateFile::new("src/calc.cay", r#"public class Calc {
+    public static int add(int a, int b) {
+        return a + b;
+    }
+
+    public static int divide(int a, int b) {
+        return a / b;
+    }
+
+    public static long factorial(int n) {
+        long result = 1;
+        for (int i = 2; i <= n; i++) {
+            result = result * i;
+        }
+        return result;
+    }
+}
+"#),
+            TemplateFile::new("tests/arithmetic_test.cay", r#"public class ArithmeticTest {
+    @Test
+    public static void adds() {
+        assertEquals(5, Calc.add(2, 3));
+    }
+
+    @Test
+    public static void dividesTowardZero() {
+        assertEquals(-2, Calc.divide(-7, 3), "整数除法向零取整");
+    }
+}
+"#),
+            TemplateFile::new("tests/factorial_test.cay", r#"public class FactorialTest {
+    @Test
+    public static void baseCases() {
+        assertEquals(1L, Calc.factorial(0));
+        assertEquals(1L, Calc.factorial(1));
+    }
+
+    @Test
+    public void grows() {
+        assertTrue(Calc.factorial(10) > Calc.factorial(9));
+        assertEquals(3628800L, Calc.factorial(10));
+    }
+}
+"#),
+        ],
+        _ => unreachable!("未知的内置模板 {}", template),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cavly::config::CavlyConfig;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_builtin_templates_produce_valid_configs() {
+        for (name, _) in BUILTIN_TEMPLATES {
+            let files = Template::find(name, Path::new(".")).unwrap().files("demo-app").unwrap();
+            let config = files.iter().find(|file| file.path == Path::new(CONFIG_FILE)).unwrap();
+            let config: CavlyConfig = toml::from_str(&config.contents).unwrap();
+            assert_eq!(config.package.name, "demo-app");
+            let main = Path::new(&config.package.src_dir).join(&config.package.main);
+            assert!(files.iter().any(|file| file.path == main), "{}: 缺少 {}", name, main.display());
+            assert!(files.iter().any(|file| file.path.starts_with("tests")), "{}: 没有测试", name);
+        }
+    }
+
+    #[test]
+    fn test_ffi_wrapper_enables_build_script() {
+        let files = Template::Builtin("ffi-wrapper").files("zmath").unwrap();
+        let config = files.iter().find(|file| file.path == Path::new(CONFIG_FILE)).unwrap();
+        let config: CavlyConfig = toml::from_str(&config.contents).unwrap();
+        assert!(config.is_lib());
+        assert_eq!(config.package.build.as_deref(), Some("build.cay"));
+        assert!(files.iter().any(|file| file.path == Path::new("build.cay")));
+        assert!(files.iter().any(|file| file.contents.contains("public class ZmathTest")));
+    }
+
+    #[test]
+    fn test_user_template_substitution() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("web");
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        std::fs::write(dir.join(CONFIG_FILE), "[package]\nname = \"{{name}}\"\nversion = \"0.1.0\"\n").unwrap();
+        std::fs::write(dir.join("src/{{name}}.cay"), "public class {{class_name}} {}\n").unwrap();
+        std::fs::write(dir.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+
+        let template = Template::find("web", temp.path()).unwrap();
+        assert_eq!(template, Template::User { name: "web".to_string(), dir: dir.clone() });
+        let files = template.files("my-site").unwrap();
+        assert_eq!(files, vec![
+            TemplateFile::new(CONFIG_FILE, "[package]\nname = \"my-site\"\nversion = \"0.1.0\"\n"),
+            TemplateFile::new("src/my-site.cay", "public class MySite {}\n"),
+        ]);
+
+        // 没有 cavly.toml 的目录不是模板
+        std::fs::remove_file(dir.join(CONFIG_FILE)).unwrap();
+        let err = Template::find("web", temp.path()).unwrap_err().to_string();
+        assert!(err.contains("中没有 cavly.toml"), "{}", err);
+    }
+
+    #[test]
+    fn test_unknown_template() {
+        let temp = TempDir::new().unwrap();
+        let err = Template::find("no-such-template", temp.path()).unwrap_err().to_string();
+        assert!(err.contains("找不到模板 'no-such-template'"), "{}", err);
+        assert!(err.contains("cli, library, ffi-wrapper, test-suite"), "{}", err);
+    }
+
+    #[test]
+    fn test_new_from_template() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("calc");
+        Project::new_from_template(&path, "calc", &Template::Builtin("test-suite")).unwrap();
+        assert!(path.join("src/calc.cay").exists());
+        assert!(path.join("tests/factorial_test.cay").exists());
+
+        // 不覆盖已有的项目
+        let err = Project::new_from_template(&path, "calc", &Template::Builtin("cli")).unwrap_err();
+        assert!(err.to_string().contains("不为空"), "{}", err);
+    }
+}
@@ -0,0 +1,455 @@
+// cavly test：发现 @Test 方法、生成测试入口程序、逐个进程运行测试并汇总结果
+// 时间复杂度: O(n) 发现，n 为源码大小；O(t) 运行，t 为测试数量（每个测试一个进程）
+// 空间复杂度: O(n)
+//
+// 测试入口程序 `target/test/<名称>_tests.cay` 包含 src_dir 和 tests/ 下的全部源文件，
+// 顶层 `main` 按 `args[1]`（`类名.方法名`）调用对应的测试方法：静态方法直接调用，
+// 实例方法先用无参构造函数创建实例。断言失败时进程以 panic 退出码 101 结束。
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use anyhow::{Result, Context, bail};
+
+use super::config::CavlyConfig;
+use super::ensure_dir;
+use crate::lexer::Token;
+
+/// 测试文件目录（相对于项目根目录）
+pub const TESTS_DIR: &str = "tests";
+
+/// 测试入口程序找不到测试名时的退出码
+const UNKNOWN_TEST_EXIT_CODE: i32 = 2;
+
+/// 一个 @Test 方法
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    pub class_name: String,
+    pub method: String,
+    pub is_static: bool,
+    /// 所在源文件
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+impl TestCase {
+    /// 测试名：`类名.方法名`
+    pub fn name(&self) -> String {
+        format!("{}.{}", self.class_name, self.method)
+    }
+}
+
+/// 参与测试构建的源文件和其中的测试
+#[derive(Debug, Default)]
+pub struct TestSuite {
+    pub files: Vec<PathBuf>,
+    pub tests: Vec<TestCase>,
+}
+
+/// 单个源文件的扫描结果
+#[derive(Debug, Default)]
+struct SourceScan {
+    tests: Vec<TestCase>,
+    /// 是否定义了顶层 `main` 函数（与测试入口程序冲突，不能包含进来）
+    has_top_level_main: bool,
+}
+
+/// 测试结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOutcome {
+    Passed,
+    Failed {
+        /// panic 信息（断言失败描述），进程异常退出时为退出状态
+        message: String,
+        /// 失败的源码位置 `文件:行:列`
+        location: Option<String>,
+    },
+}
+
+/// 一个测试的运行记录
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    pub test: TestCase,
+    pub outcome: TestOutcome,
+    pub duration: Duration,
+    /// 测试进程的标准输出和标准错误
+    pub output: String,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.outcome == TestOutcome::Passed
+    }
+}
+
+/// 发现项目中的测试：扫描 src_dir 和 tests/ 下的所有 .cay 文件
+///
+/// 定义了顶层 `main` 的文件不参与测试构建，其中不能有 @Test 方法
+///
+/// # 复杂度
+/// - 时间: O(n)，n 为源码大小
+/// - 空间: O(n)
+pub fn discover(project_root: &Path, config: &CavlyConfig) -> Result<TestSuite> {
+    let mut sources = Vec::new();
+    collect_sources(&project_root.join(&config.package.src_dir), &mut sources)?;
+    collect_sources(&project_root.join(TESTS_DIR), &mut sources)?;
+
+    let mut suite = TestSuite::default();
+    for file in sources {
+        let source = std::fs::read_to_string(&file)
+            .with_context(|| format!("读取源文件失败: {}", file.display()))?;
+        let scan = scan_source(&source, &file)?;
+        if scan.has_top_level_main {
+            if let Some(test) = scan.tests.first() {
+                bail!("{} 定义了顶层 main 函数，不能包含 @Test 方法（{}），请把测试移到其他文件",
+                    file.display(), test.name());
+            }
+            continue;
+        }
+        suite.tests.extend(scan.tests);
+        suite.files.push(file);
+    }
+    Ok(suite)
+}
+
+/// 递归收集目录下的 .cay 文件（按路径排序，保证入口程序内容稳定）
+fn collect_sources(dir: &Path, sources: &mut Vec<PathBuf>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    let mut entries = std::fs::read_dir(dir)
+        .with_context(|| format!("读取目录失败: {}", dir.display()))?
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.path());
+    for entry in entries {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_sources(&path, sources)?;
+        } else if path.extension().is_some_and(|ext| ext == "cay") {
+            sources.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 用词法分析器扫描源文件中的 @Test 方法和顶层 `main` 函数
+///
+/// 预处理指令行不参与词法分析（替换为空行以保留行号）
+fn scan_source(source: &str, file: &Path) -> Result<SourceScan> {
+    let stripped: String = source.lines()
+        .map(|line| if line.trim_start().starts_with('#') { "" } else { line })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let tokens = crate::lexer::tokenize(&stripped)
+        .map_err(|e| anyhow::anyhow!("{}: {}", file.display(), crate::error::get_error_message(&e)))?;
+
+    let mut scan = SourceScan::default();
+    let mut depth = 0usize;
+    // (类名, 类体的花括号深度)
+    let mut classes: Vec<(String, usize)> = Vec::new();
+    let mut pending_class: Option<String> = None;
+    // 遇到 @Test 后等待方法名：是否为 static
+    let mut pending_test: Option<bool> = None;
+
+    for (index, token) in tokens.iter().enumerate() {
+        let next = tokens.get(index + 1).map(|t| &t.token);
+        match &token.token {
+            Token::Class => {
+                if let Some(Token::Identifier(name)) = next {
+                    pending_class = Some(name.clone());
+                }
+            }
+            Token::LBrace => {
+                depth += 1;
+                if let Some(name) = pending_class.take() {
+                    classes.push((name, depth));
+                }
+            }
+            Token::RBrace => {
+                if classes.last().is_some_and(|(_, body)| *body == depth) {
+                    classes.pop();
+                }
+                depth = depth.saturating_sub(1);
+            }
+            Token::AtTest => pending_test = Some(false),
+            Token::Static => {
+                if let Some(is_static) = pending_test.as_mut() {
+                    *is_static = true;
+                }
+            }
+            Token::Identifier(name) if next == Some(&Token::LParen) => {
+                if depth == 0 && name == "main" {
+                    scan.has_top_level_main = true;
+                }
+                let class = classes.last().filter(|(_, body)| *body == depth);
+                if let (Some(is_static), Some((class_name, _))) = (pending_test, class) {
+                    scan.tests.push(TestCase {
+                        class_name: class_name.clone(),
+                        method: name.clone(),
+                        is_static,
+                        file: file.to_path_buf(),
+                        line: token.loc.line,
+                    });
+                    pending_test = None;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(scan)
+}
+
+/// 生成测试入口程序源码
+///
+/// # 复杂度
+/// - 时间: O(f + t)，f 为源文件数量，t 为测试数量
+/// - 空间: O(f + t)
+pub fn generate_harness(suite: &TestSuite) -> String {
+    let mut out = String::from("// 由 cavly test 生成，请勿手动修改\n");
+    for file in &suite.files {
+        out.push_str(&format!("#include \"{}\"\n", file.to_string_lossy().replace('\\', "/")));
+    }
+    out.push_str("\npublic int main(String[] args) {\n");
+    out.push_str(&format!("    if (args.length < 2) {{\n        return {};\n    }}\n", UNKNOWN_TEST_EXIT_CODE));
+    out.push_str("    String test = args[1];\n");
+    for test in &suite.tests {
+        let call = if test.is_static {
+            format!("{}.{}();", test.class_name, test.method)
+        } else {
+            format!("new {}().{}();", test.class_name, test.method)
+        };
+        out.push_str(&format!("    if (test.equals(\"{}\")) {{\n        {}\n        return 0;\n    }}\n",
+            test.name(), call));
+    }
+    out.push_str("    println(\"unknown test: \" + test);\n");
+    out.push_str(&format!("    return {};\n}}\n", UNKNOWN_TEST_EXIT_CODE));
+    out
+}
+
+/// 把测试入口程序写入 `target/test/<名称>_tests.cay`
+///
+/// # 复杂度
+/// - 时间: O(f + t)
+/// - 空间: O(f + t)
+pub fn write_harness(project_root: &Path, config: &CavlyConfig, suite: &TestSuite) -> Result<PathBuf> {
+    let test_dir = config.target_path(project_root).join("test");
+    ensure_dir(&test_dir)?;
+    let path = test_dir.join(format!("{}_tests.cay", config.output_filename()));
+    std::fs::write(&path, generate_harness(suite))
+        .with_context(|| format!("写入测试入口程序失败: {}", path.display()))?;
+    Ok(path)
+}
+
+/// 在独立进程中运行一个测试，崩溃不影响其他测试
+///
+/// # 复杂度
+/// - 时间: 测试本身的运行时间
+/// - 空间: O(o)，o 为测试输出大小
+pub fn run_test(executable: &Path, test: &TestCase) -> Result<TestResult> {
+    let start = Instant::now();
+    let output = Command::new(executable)
+        .arg(test.name())
+        .output()
+        .with_context(|| format!("运行测试失败: {}", executable.display()))?;
+    let duration = start.elapsed();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let outcome = if output.status.success() {
+        TestOutcome::Passed
+    } else {
+        parse_failure(&stderr, output.status.code())
+    };
+    Ok(TestResult {
+        test: test.clone(),
+        outcome,
+        duration,
+        output: format!("{}{}", stdout, stderr),
+    })
+}
+
+/// 从 panic 输出（`panic: <信息>` 和 `  --> 文件:行:列`）中取出失败信息和位置
+fn parse_failure(stderr: &str, code: Option<i32>) -> TestOutcome {
+    let mut lines = stderr.lines();
+    while let Some(line) = lines.next() {
+        if let Some(message) = line.strip_prefix("panic: ") {
+            let location = lines.next()
+                .and_then(|next| next.trim_start().strip_prefix("--> "))
+                .map(|loc| loc.to_string());
+            return TestOutcome::Failed { message: message.to_string(), location };
+        }
+    }
+    let message = match code {
+        Some(UNKNOWN_TEST_EXIT_CODE) => "测试入口程序中找不到该测试".to_string(),
+        Some(code) => format!("进程退出码 {}", code),
+        None => "进程被信号终止".to_string(),
+    };
+    TestOutcome::Failed { message, location: None }
+}
+
+/// 生成 JUnit XML 报告
+///
+/// # 复杂度
+/// - 时间: O(t + o)，t 为测试数量，o 为失败测试的输出大小
+/// - 空间: O(t + o)
+pub fn junit_xml(suite_name: &str, results: &[TestResult]) -> String {
+    let failures = results.iter().filter(|r| !r.passed()).count();
+    let total: Duration = results.iter().map(|r| r.duration).sum();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!("<testsuites tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+        results.len(), failures, total.as_secs_f64()));
+    out.push_str(&format!("  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+        xml_escape(suite_name), results.len(), failures, total.as_secs_f64()));
+    for result in results {
+        let attrs = format!("classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+            xml_escape(&result.test.class_name),
+            xml_escape(&result.test.method),
+            result.duration.as_secs_f64());
+        match &result.outcome {
+            TestOutcome::Passed => out.push_str(&format!("    <testcase {}/>\n", attrs)),
+            TestOutcome::Failed { message, location } => {
+                let summary = match location {
+                    Some(location) => format!("{} at {}", message, location),
+                    None => message.clone(),
+                };
+                out.push_str(&format!("    <testcase {}>\n", attrs));
+                out.push_str(&format!("      <failure message=\"{}\">{}</failure>\n",
+                    xml_escape(&summary), xml_escape(&result.output)));
+                out.push_str("    </testcase>\n");
+            }
+        }
+    }
+    out.push_str("  </testsuite>\n</testsuites>\n");
+    out
+}
+
+/// 转义 XML 特殊字符
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn test_config() -> CavlyConfig {
+        toml::from_str("[package]\nname = \"demo\"\nversion = \"0.1.0\"\n").unwrap()
+    }
+
+    #[test]
+    fn test_discover_tests_in_src_and_tests_dirs() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("tests")).unwrap();
+        fs::write(root.join("src/main.cay"), "\
+public class Calc {
+    public static int add(int a, int b) { return a + b; }
+}
+public int main() { return 0; }
+").unwrap();
+        fs::write(root.join("src/util.cay"), "\
+public class Util {
+    @Test
+    public static void trims() { assertEquals(\"a\", \" a \".trim()); }
+    public void helper() {}
+}
+").unwrap();
+        fs::write(root.join("tests/calc_test.cay"), "\
+#include \"../src/util.cay\"
+public class CalcTest {
+    @Test
+    public void adds() { assertEquals(3, 1 + 2); }
+    @Override
+    public String toString() { return \"CalcTest\"; }
+}
+").unwrap();
+
+        let suite = discover(root, &test_config()).unwrap();
+        let names: Vec<String> = suite.tests.iter().map(|t| t.name()).collect();
+        assert_eq!(names, vec!["Util.trims", "CalcTest.adds"]);
+        assert!(suite.tests[0].is_static);
+        assert!(!suite.tests[1].is_static);
+        assert_eq!(suite.tests[1].line, 4);
+        // 定义了顶层 main 的主源文件不参与测试构建
+        assert_eq!(suite.files, vec![root.join("src/util.cay"), root.join("tests/calc_test.cay")]);
+    }
+
+    #[test]
+    fn test_tests_next_to_top_level_main_are_rejected() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.cay"), "\
+public class T {
+    @Test
+    public static void t() {}
+}
+public int main() { return 0; }
+").unwrap();
+        let err = discover(root, &test_config()).unwrap_err().to_string();
+        assert!(err.contains("T.t"), "{}", err);
+    }
+
+    #[test]
+    fn test_generate_harness() {
+        let suite = TestSuite {
+            files: vec![PathBuf::from("/p/tests/a.cay")],
+            tests: vec![
+                TestCase { class_name: "A".into(), method: "s".into(), is_static: true, file: PathBuf::from("/p/tests/a.cay"), line: 2 },
+                TestCase { class_name: "A".into(), method: "i".into(), is_static: false, file: PathBuf::from("/p/tests/a.cay"), line: 4 },
+            ],
+        };
+        let harness = generate_harness(&suite);
+        assert!(harness.contains("#include \"/p/tests/a.cay\"\n"));
+        assert!(harness.contains("if (test.equals(\"A.s\")) {\n        A.s();"));
+        assert!(harness.contains("if (test.equals(\"A.i\")) {\n        new A().i();"));
+        assert!(harness.contains("return 2;\n}\n"));
+    }
+
+    #[test]
+    fn test_parse_failure() {
+        let stderr = "panic: assertion failed: expected 1, actual 2\n  --> tests/a.cay:3:20\nstack backtrace:\n    0: A.t\n";
+        assert_eq!(parse_failure(stderr, Some(101)), TestOutcome::Failed {
+            message: "assertion failed: expected 1, actual 2".to_string(),
+            location: Some("tests/a.cay:3:20".to_string()),
+        });
+        assert_eq!(parse_failure("", None), TestOutcome::Failed {
+            message: "进程被信号终止".to_string(),
+            location: None,
+        });
+    }
+
+    #[test]
+    fn test_junit_xml() {
+        let test = |method: &str| TestCase {
+            class_name: "A".into(), method: method.into(), is_static: true, file: PathBuf::from("a.cay"), line: 1,
+        };
+        let results = vec![
+            TestResult { test: test("ok"), outcome: TestOutcome::Passed, duration: Duration::from_millis(5), output: String::new() },
+            TestResult {
+                test: test("bad"),
+                outcome: TestOutcome::Failed { message: "expected \"<a>\"".into(), location: Some("a.cay:2:3".into()) },
+                duration: Duration::from_millis(7),
+                output: "panic & more".into(),
+            },
+        ];
+        let xml = junit_xml("demo", &results);
+        assert!(xml.contains("<testsuite name=\"demo\" tests=\"2\" failures=\"1\" time=\"0.012\">"));
+        assert!(xml.contains("<testcase classname=\"A\" name=\"ok\" time=\"0.005\"/>"));
+        assert!(xml.contains("<failure message=\"expected &quot;&lt;a&gt;&quot; at a.cay:2:3\">panic &amp; more</failure>"));
+    }
+}
@@ -0,0 +1,429 @@
+// 时间复杂度: O(n + e) 依赖树遍历, n 为包数, e 为依赖边数
+// 空间复杂度: O(n + e) 存储依赖图
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use anyhow::{Result, bail};
+
+use super::build_script::BuildScriptOutput;
+use super::config::CavlyConfig;
+use super::workspace::{build_dependency_graph, relative_path, ResolvedDependency};
+
+/// 链接参数 `-l<库>` 的一个来源
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkSource {
+    /// 添加该库的包（cayc/ir2exe 的规则为 None）
+    pub package: Option<String>,
+    /// 从根项目到该包的依赖路径
+    pub chain: Vec<String>,
+    /// 配置项或规则
+    pub reason: String,
+}
+
+impl LinkSource {
+    /// 一行说明，例如 `mylib v0.1.0 (../mylib): [ffi] system_libs（经由 app -> mylib）`
+    pub fn describe(&self, tree: &DependencyTree) -> String {
+        match self.package {
+            Some(ref name) if self.chain.len() > 1 => {
+                format!("{}: {}（经由 {}）", tree.label(name), self.reason, self.chain.join(" -> "))
+            }
+            Some(ref name) => format!("{}: {}", tree.label(name), self.reason),
+            None => format!("编译器: {}", self.reason),
+        }
+    }
+}
+
+/// 已解析的依赖图：根项目和 `resolve_all` 得到的全部依赖
+///
+/// 边来自 `build_dependency_graph` 的反向图；没有被任何包依赖的依赖（工作区库成员）
+/// 同样会链接进根项目，作为根项目的直接依赖显示
+pub struct DependencyTree {
+    /// 根项目名
+    root: String,
+    /// 根项目目录（本地依赖的路径相对于它显示）
+    project_root: PathBuf,
+    /// 包名 -> 解析结果（包括根项目）
+    packages: HashMap<String, ResolvedDependency>,
+    /// 包名 -> 它依赖的包（已排序）
+    dependencies: HashMap<String, Vec<String>>,
+    /// 包名 -> 依赖它的包（已排序）
+    dependents: HashMap<String, Vec<String>>,
+}
+
+impl DependencyTree {
+    /// 由根项目配置和解析出的依赖构建依赖图
+    ///
+    /// # 复杂度
+    /// - 时间: O(n + e log e)
+    /// - 空间: O(n + e)
+    pub fn new(project_root: &Path, config: &CavlyConfig, resolved: &[ResolvedDependency]) -> Self {
+        let root = ResolvedDependency {
+            name: config.package.name.clone(),
+            path: project_root.to_path_buf(),
+            config: config.clone(),
+            is_local: true,
+            optional: false,
+        };
+        let mut all = vec![root];
+        all.extend(resolved.iter().cloned());
+
+        let packages: HashMap<String, ResolvedDependency> = all.iter()
+            .map(|dep| (dep.name.clone(), dep.clone()))
+            .collect();
+
+        // 反向图只保留解析到的包（未启用的可选依赖不在图中）
+        let mut dependents: HashMap<String, Vec<String>> = build_dependency_graph(&all)
+            .into_iter()
+            .filter(|(name, _)| packages.contains_key(name))
+            .collect();
+        for dep in resolved {
+            let users = dependents.entry(dep.name.clone()).or_default();
+            if users.is_empty() {
+                users.push(config.package.name.clone());
+            }
+        }
+
+        let mut dependencies: HashMap<String, Vec<String>> = HashMap::new();
+        for (name, users) in &mut dependents {
+            users.sort();
+            users.dedup();
+            for user in users.iter() {
+                dependencies.entry(user.clone()).or_default().push(name.clone());
+            }
+        }
+        for deps in dependencies.values_mut() {
+            deps.sort();
+        }
+
+        Self { root: config.package.name.clone(), project_root: project_root.to_path_buf(), packages, dependencies, dependents }
+    }
+
+    /// 包的显示名：`名称 v版本 (路径) [标记]`，根项目不显示路径，本地依赖显示相对路径
+    pub fn label(&self, name: &str) -> String {
+        let Some(package) = self.packages.get(name) else {
+            return name.to_string();
+        };
+        let mut label = format!("{} v{}", name, package.config.package.version);
+        if name != self.root {
+            let path = if package.is_local {
+                relative_path(&package.path, &self.project_root)
+            } else {
+                package.path.clone()
+            };
+            label.push_str(&format!(" ({})", path.display()));
+            if !package.is_local {
+                label.push_str(" [远程]");
+            }
+        }
+        if package.config.is_lib() && package.config.lib.only_include {
+            label.push_str(" [only_include]");
+        }
+        if package.optional {
+            label.push_str(" [可选]");
+        }
+        label
+    }
+
+    /// 从根项目展开的依赖树，已展开过的包标记为 `(*)`
+    ///
+    /// # 复杂度
+    /// - 时间: O(n + e)
+    /// - 空间: O(n)
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut expanded = HashSet::new();
+        self.render(&self.root, &self.dependencies, "", "", &mut expanded, &mut lines);
+        lines
+    }
+
+    /// 反向依赖树：哪些包（直接或间接）依赖 `name`
+    ///
+    /// # 复杂度
+    /// - 时间: O(n + e)
+    /// - 空间: O(n)
+    pub fn inverted_lines(&self, name: &str) -> Result<Vec<String>> {
+        if !self.packages.contains_key(name) {
+            let mut names: Vec<&str> = self.packages.keys().map(|name| name.as_str()).collect();
+            names.sort();
+            bail!("依赖图中没有包 '{}'（包: {}）", name, names.join(", "));
+        }
+        let mut lines = Vec::new();
+        let mut expanded = HashSet::new();
+        self.render(name, &self.dependents, "", "", &mut expanded, &mut lines);
+        Ok(lines)
+    }
+
+    /// 按 `edges` 递归输出一个节点及其子树
+    fn render(
+        &self,
+        name: &str,
+        edges: &HashMap<String, Vec<String>>,
+        prefix: &str,
+        child_prefix: &str,
+        expanded: &mut HashSet<String>,
+        lines: &mut Vec<String>,
+    ) {
+        let children = edges.get(name).map(|children| children.as_slice()).unwrap_or(&[]);
+        if !expanded.insert(name.to_string()) && !children.is_empty() {
+            lines.push(format!("{}{} (*)", prefix, self.label(name)));
+            return;
+        }
+        lines.push(format!("{}{}", prefix, self.label(name)));
+        for (i, child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let (branch, indent) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+            self.render(
+                child,
+                edges,
+                &format!("{}{}", child_prefix, branch),
+                &format!("{}{}", child_prefix, indent),
+                expanded,
+                lines,
+            );
+        }
+    }
+
+    /// 从根项目到 `name` 的一条最短依赖路径
+    fn chain(&self, name: &str) -> Vec<String> {
+        let mut parent: HashMap<&str, &str> = HashMap::new();
+        let mut queue = std::collections::VecDeque::from([self.root.as_str()]);
+        let mut seen = HashSet::from([self.root.as_str()]);
+        while let Some(current) = queue.pop_front() {
+            if current == name {
+                break;
+            }
+            for child in self.dependencies.get(current).into_iter().flatten() {
+                if seen.insert(child.as_str()) {
+                    parent.insert(child.as_str(), current);
+                    queue.push_back(child.as_str());
+                }
+            }
+        }
+        let mut chain = vec![name.to_string()];
+        let mut current = name;
+        while let Some(&previous) = parent.get(current) {
+            chain.push(previous.to_string());
+            current = previous;
+        }
+        chain.reverse();
+        chain
+    }
+
+    /// 说明链接命令中的 `-l<lib>` 从哪里来
+    ///
+    /// 与 `Builder` 生成的参数一致：非 only_include 依赖的库产物、各包的 `[build] libs`、
+    /// `[ffi] system_libs`、`[ffi.libraries]` 的 `lib`/`deps`、构建脚本保存的输出，以及 cayc/ir2exe 按目标平台
+    /// `target`（None 为当前平台）自动添加的库
+    ///
+    /// # 复杂度
+    /// - 时间: O(n*m)，m 为每个包的库配置数量
+    /// - 空间: O(n)
+    pub fn link_sources(&self, lib: &str, target: Option<&str>) -> Vec<LinkSource> {
+        let root = &self.packages[&self.root];
+        if root.config.is_lib() && root.config.lib.only_include {
+            return Vec::new();
+        }
+
+        let mut names: Vec<&String> = self.packages.keys().collect();
+        names.sort_by_key(|name| (**name != self.root, name.as_str()));
+
+        let mut sources = Vec::new();
+        for name in names {
+            let package = &self.packages[name];
+            let config = &package.config;
+            let mut reasons = Vec::new();
+            if *name != self.root && !config.lib.only_include && config.output_filename() == lib {
+                reasons.push("库依赖的产物".to_string());
+            }
+            if config.build.libs.iter().any(|l| l == lib) {
+                reasons.push("[build] libs".to_string());
+            }
+            if config.ffi.system_libs.iter().any(|l| l == lib) {
+                reasons.push("[ffi] system_libs".to_string());
+            }
+            let mut libraries: Vec<(&String, _)> = config.ffi.libraries.iter().collect();
+            libraries.sort_by_key(|(key, _)| key.as_str());
+            for (key, library) in libraries {
+                if library.lib == lib {
+                    reasons.push(format!("[ffi.libraries.{}] lib", key));
+                }
+                if library.deps.iter().any(|l| l == lib) {
+                    reasons.push(format!("[ffi.libraries.{}] deps", key));
+                }
+            }
+            let script = BuildScriptOutput::load(&package.path, config).ok().flatten();
+            if script.is_some_and(|output| output.link_libs.iter().any(|l| l == lib)) {
+                reasons.push(format!("构建脚本 {} 上次运行时输出的 cavly:link-lib",
+                    config.package.build.as_deref().unwrap_or_default()));
+            }
+            for reason in reasons {
+                sources.push(LinkSource { package: Some(name.clone()), chain: self.chain(name), reason });
+            }
+        }
+
+        for (auto_lib, reason) in auto_link_rules(target) {
+            if auto_lib == lib {
+                sources.push(LinkSource { package: None, chain: Vec::new(), reason: reason.to_string() });
+            }
+        }
+        sources
+    }
+}
+
+/// cayc 和 ir2exe 按目标平台自动添加的链接库（`target` 为 None 时使用当前平台）
+pub fn auto_link_rules(target: Option<&str>) -> Vec<(&'static str, &'static str)> {
+    let os = match target {
+        Some(target) if target.contains("windows") || target.contains("mingw") => "windows",
+        Some(target) if target.contains("linux") => "linux",
+        Some(target) if target.contains("darwin") => "macos",
+        Some(_) => "other",
+        None if cfg!(target_os = "windows") => "windows",
+        None if cfg!(target_os = "linux") => "linux",
+        None if cfg!(target_os = "macos") => "macos",
+        None => "other",
+    };
+    match os {
+        "windows" => vec![
+            ("kernel32", "ir2exe 为 Windows 目标默认链接"),
+            ("msvcrt", "ir2exe 为 Windows 目标默认链接"),
+            ("advapi32", "ir2exe 为 Windows 目标默认链接"),
+            ("ws2_32", "IR 中使用套接字（WSAStartup、socket）时自动链接"),
+        ],
+        "linux" => vec![
+            ("c", "ir2exe 为 Linux 目标默认链接"),
+            ("m", "ir2exe 为 Linux 目标默认链接"),
+            ("pthread", "ir2exe 为 Linux 目标默认链接"),
+        ],
+        _ => vec![
+            ("c", "ir2exe 默认链接"),
+            ("m", "ir2exe 默认链接"),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn config(toml: &str) -> CavlyConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn dep(name: &str, toml: &str) -> ResolvedDependency {
+        ResolvedDependency {
+            name: name.to_string(),
+            path: PathBuf::from(format!("libs/{}", name)),
+            config: config(toml),
+            is_local: true,
+            optional: false,
+        }
+    }
+
+    /// app -> json -> text, app -> gfx -> text，gfx 为 only_include，helper 为未被依赖的工作区成员
+    fn sample() -> DependencyTree {
+        let root = config(r#"
+[package]
+name = "app"
+version = "1.0.0"
+
+[dependencies]
+json = { path = "libs/json" }
+gfx = { path = "libs/gfx" }
+
+[ffi]
+system_libs = ["m"]
+"#);
+        let deps = vec![
+            dep("json", r#"
+[package]
+name = "json"
+version = "0.2.0"
+project_type = "lib"
+
+[dependencies]
+text = { path = "../text" }
+"#),
+            dep("gfx", r#"
+[package]
+name = "gfx"
+version = "0.1.0"
+project_type = "lib"
+
+[lib]
+only_include = true
+
+[dependencies]
+text = { path = "../text" }
+
+[ffi.libraries.sdl2]
+name = "sdl2"
+lib = "SDL2"
+deps = ["m"]
+"#),
+            dep("text", r#"
+[package]
+name = "text"
+version = "0.3.0"
+project_type = "lib"
+"#),
+            dep("helper", r#"
+[package]
+name = "helper"
+version = "0.1.0"
+project_type = "lib"
+"#),
+        ];
+        DependencyTree::new(Path::new("."), &root, &deps)
+    }
+
+    #[test]
+    fn test_tree_lines() {
+        let lines = sample().lines();
+        assert_eq!(lines, vec![
+            "app v1.0.0",
+            "├── gfx v0.1.0 (libs/gfx) [only_include]",
+            "│   └── text v0.3.0 (libs/text)",
+            "├── helper v0.1.0 (libs/helper)",
+            "└── json v0.2.0 (libs/json)",
+            "    └── text v0.3.0 (libs/text)",
+        ]);
+    }
+
+    #[test]
+    fn test_inverted_lines() {
+        let tree = sample();
+        let lines = tree.inverted_lines("text").unwrap();
+        assert_eq!(lines, vec![
+            "text v0.3.0 (libs/text)",
+            "├── gfx v0.1.0 (libs/gfx) [only_include]",
+            "│   └── app v1.0.0",
+            "└── json v0.2.0 (libs/json)",
+            "    └── app v1.0.0",
+        ]);
+
+        let err = tree.inverted_lines("nope").unwrap_err().to_string();
+        assert!(err.contains("依赖图中没有包 'nope'"), "{}", err);
+    }
+
+    #[test]
+    fn test_link_sources() {
+        let tree = sample();
+        let sources = tree.link_sources("m", Some("x86_64-unknown-linux-gnu"));
+        let described: Vec<String> = sources.iter().map(|source| source.describe(&tree)).collect();
+        assert_eq!(described, vec![
+            "app v1.0.0: [ffi] system_libs",
+            "gfx v0.1.0 (libs/gfx) [only_include]: [ffi.libraries.sdl2] deps（经由 app -> gfx）",
+            "编译器: ir2exe 为 Linux 目标默认链接",
+        ]);
+
+        // 库依赖的产物；only_include 依赖不产出库
+        let sources = tree.link_sources("text", None);
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].chain, vec!["app", "gfx", "text"]);
+        assert!(tree.link_sources("gfx", None).is_empty());
+
+        let windows: Vec<&str> = auto_link_rules(Some("x86_64-pc-windows-msvc")).iter().map(|(lib, _)| *lib).collect();
+        assert!(windows.contains(&"ws2_32"));
+    }
+}
@@ -0,0 +1,224 @@
+// 增量构建：开启 `build.incremental` 后，每个源文件是一个编译单元，由 `cayc -c` 单独编译为 IR 和接口文件（.cai）
+// 单元的构建键由源码、编译参数和全部依赖单元（#include 的文件，含间接依赖）接口文件的内容哈希组成，
+// 键不变的单元不重新编译；依赖单元只修改了实现、接口文件不变时，依赖它的单元也不重新编译
+// 时间复杂度: O(n + e) 计算构建键，n 为单元数，e 为 #include 边数
+// 空间复杂度: O(n) 构建清单
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use crate::error::get_error_message;
+use crate::preprocessor::Preprocessor;
+use crate::unit::{artifact_stem, content_hash, interface_path};
+
+/// 单元产物所在目录（相对于目标目录）
+pub const UNITS_DIR: &str = "units";
+
+/// 构建清单文件名
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// 编译单元
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unit {
+    /// 源文件的规范化路径
+    pub source: String,
+    /// 直接依赖的单元（规范化路径）
+    pub imports: Vec<String>,
+}
+
+/// 从主源文件出发查找全部编译单元，按依赖顺序排列（被依赖的单元在前）
+pub fn discover_units(main_source: &Path, include_paths: &[PathBuf]) -> Result<Vec<Unit>> {
+    let main = std::fs::canonicalize(main_source)
+        .with_context(|| format!("无法访问源文件: {}", main_source.display()))?;
+    let mut units = Vec::new();
+    let mut visiting = Vec::new();
+    discover_recursive(&main.to_string_lossy(), include_paths, &mut units, &mut visiting)?;
+    Ok(units)
+}
+
+fn discover_recursive(
+    source: &str,
+    include_paths: &[PathBuf],
+    units: &mut Vec<Unit>,
+    visiting: &mut Vec<String>,
+) -> Result<()> {
+    if units.iter().any(|u| u.source == source) {
+        return Ok(());
+    }
+    if visiting.iter().any(|s| s == source) {
+        bail!("编译单元之间存在循环依赖: {} -> {}", visiting.join(" -> "), source);
+    }
+
+    let text = std::fs::read_to_string(source)
+        .with_context(|| format!("读取源文件失败: {}", source))?;
+    let base_dir = Path::new(source).parent().unwrap_or(Path::new("."));
+    let mut preprocessor = Preprocessor::with_include_paths(base_dir, include_paths.to_vec());
+    preprocessor.set_unit_mode();
+    preprocessor.process(&text, source)
+        .map_err(|e| anyhow!("预处理 '{}' 失败: {}", source, get_error_message(&e)))?;
+    let imports = preprocessor.unit_dependencies().to_vec();
+
+    visiting.push(source.to_string());
+    for import in &imports {
+        discover_recursive(import, include_paths, units, visiting)?;
+    }
+    visiting.pop();
+
+    units.push(Unit { source: source.to_string(), imports });
+    Ok(())
+}
+
+/// 单元的 IR 文件路径
+pub fn unit_ir_path(source: &str, units_dir: &Path) -> PathBuf {
+    units_dir.join(format!("{}.ll", artifact_stem(source)))
+}
+
+/// 单元的全部依赖（含间接依赖），按首次出现的顺序
+fn transitive_imports<'a>(unit: &'a Unit, units: &'a [Unit]) -> Vec<&'a str> {
+    let mut result: Vec<&str> = Vec::new();
+    let mut stack: Vec<&str> = unit.imports.iter().rev().map(|s| s.as_str()).collect();
+    while let Some(source) = stack.pop() {
+        if result.contains(&source) {
+            continue;
+        }
+        result.push(source);
+        if let Some(dep) = units.iter().find(|u| u.source == source) {
+            stack.extend(dep.imports.iter().rev().map(|s| s.as_str()));
+        }
+    }
+    result
+}
+
+/// 计算单元的构建键
+///
+/// 依赖单元的接口文件必须已经生成，因此要按 `discover_units` 返回的顺序计算和编译。
+pub fn unit_key(unit: &Unit, units: &[Unit], flags: &[String], units_dir: &Path) -> Result<String> {
+    let mut data = std::fs::read(&unit.source)
+        .with_context(|| format!("读取源文件失败: {}", unit.source))?;
+    data.extend_from_slice(env!("CARGO_PKG_VERSION").as_bytes());
+    for flag in flags {
+        data.push(0);
+        data.extend_from_slice(flag.as_bytes());
+    }
+    for import in transitive_imports(unit, units) {
+        let path = interface_path(import, units_dir);
+        let interface = std::fs::read(&path)
+            .with_context(|| format!("读取接口文件失败: {}", path.display()))?;
+        data.push(0);
+        data.extend_from_slice(&interface);
+    }
+    Ok(content_hash(&data))
+}
+
+/// 构建清单：记录每个单元上次成功编译时的构建键
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    units: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// 读取构建清单，文件不存在或格式不正确时返回空清单（全部单元重新编译）
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// 写入构建清单
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let text = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, text)
+            .with_context(|| format!("写入构建清单失败: {}", path.display()))
+    }
+
+    /// 单元是否无需重新编译：构建键不变且产物都在
+    pub fn is_up_to_date(&self, unit: &Unit, key: &str, units_dir: &Path) -> bool {
+        self.units.get(&unit.source).is_some_and(|k| k == key)
+            && unit_ir_path(&unit.source, units_dir).exists()
+            && interface_path(&unit.source, units_dir).exists()
+    }
+
+    /// 记录单元编译成功时的构建键
+    pub fn record(&mut self, unit: &Unit, key: String) {
+        self.units.insert(unit.source.clone(), key);
+    }
+
+    /// 移除已不属于项目的单元
+    pub fn retain_units(&mut self, units: &[Unit]) {
+        self.units.retain(|source, _| units.iter().any(|u| &u.source == source));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_sources(root: &Path) {
+        fs::write(root.join("main.cay"), "#include \"shapes.cay\"\n#include \"util.cay\"\n").unwrap();
+        fs::write(root.join("shapes.cay"), "#include \"util.cay\"\npublic class Shape {}\n").unwrap();
+        fs::write(root.join("util.cay"), "public class Util {}\n").unwrap();
+    }
+
+    #[test]
+    fn test_discover_units_in_dependency_order() {
+        let temp = TempDir::new().unwrap();
+        write_sources(temp.path());
+
+        let units = discover_units(&temp.path().join("main.cay"), &[]).unwrap();
+        let names: Vec<String> = units.iter()
+            .map(|u| Path::new(&u.source).file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["util.cay", "shapes.cay", "main.cay"]);
+        assert_eq!(units[2].imports, vec![units[1].source.clone(), units[0].source.clone()]);
+        assert_eq!(transitive_imports(&units[2], &units).len(), 2);
+    }
+
+    #[test]
+    fn test_discover_units_rejects_cycles() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("a.cay"), "#include \"b.cay\"\n").unwrap();
+        fs::write(temp.path().join("b.cay"), "#include \"a.cay\"\n").unwrap();
+
+        let err = discover_units(&temp.path().join("a.cay"), &[]).unwrap_err();
+        assert!(err.to_string().contains("循环依赖"));
+    }
+
+    #[test]
+    fn test_unit_key_tracks_interfaces_of_dependencies() {
+        let temp = TempDir::new().unwrap();
+        write_sources(temp.path());
+        let units_dir = temp.path().join(UNITS_DIR);
+        fs::create_dir_all(&units_dir).unwrap();
+        let units = discover_units(&temp.path().join("main.cay"), &[]).unwrap();
+        for unit in &units {
+            fs::write(interface_path(&unit.source, &units_dir), "public class Util {}\n").unwrap();
+            fs::write(unit_ir_path(&unit.source, &units_dir), "").unwrap();
+        }
+        let flags = vec!["-O0".to_string()];
+        let main = &units[2];
+        let key = unit_key(main, &units, &flags, &units_dir).unwrap();
+
+        let mut manifest = Manifest::default();
+        assert!(!manifest.is_up_to_date(main, &key, &units_dir));
+        manifest.record(main, key.clone());
+        assert!(manifest.is_up_to_date(main, &key, &units_dir));
+
+        // 编译参数变化
+        assert_ne!(unit_key(main, &units, &["-O2".to_string()], &units_dir).unwrap(), key);
+
+        // 间接依赖的接口变化
+        fs::write(interface_path(&units[0].source, &units_dir), "public class Util { int x; }\n").unwrap();
+        assert_ne!(unit_key(main, &units, &flags, &units_dir).unwrap(), key);
+
+        // 清单读写
+        let path = units_dir.join(MANIFEST_FILE);
+        manifest.save(&path).unwrap();
+        let loaded = Manifest::load(&path);
+        assert!(loaded.is_up_to_date(main, &key, &units_dir));
+        assert!(Manifest::load(&units_dir.join("missing.json")).units.is_empty());
+    }
+}
@@ -1,12 +1,17 @@
 // 时间复杂度: O(n*m) 依赖解析, O(n) 配置合并
 // 空间复杂度: O(n) 存储解析结果
 
-use std::collections::{HashMap, HashSet};
-use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
 use anyhow::{Result, Context, bail};
 
 use super::config::{CavlyConfig, Dependency, DetailedDependency, ProjectType};
-use super::CONFIG_FILE;
+use super::git::{GitCache, GitReference};
+use super::lockfile::{LockFile, LockedPackage};
+use super::registry::Registry;
+use super::resolver::{DependencyReq, PackageSource, Resolution, Resolver, SourceId, Summary};
+use super::semver::{Version, VersionReq};
+use super::{CONFIG_FILE, LOCK_FILE};
 
 /// 解析后的依赖信息
 #[derive(Debug, Clone)]
@@ -23,16 +28,121 @@ pub struct ResolvedDependency {
     pub optional: bool,
 }
 
+/// 依赖解析选项
+#[derive(Debug, Clone, Default)]
+pub struct ResolveOptions {
+    /// lock 文件需要改变时报错而不是写入（`--locked`）
+    pub locked: bool,
+    /// 忽略 lock 文件中锁定的版本，重新选择最新的兼容版本
+    pub update: LockUpdate,
+    /// 不访问网络，git 依赖只使用缓存（`--offline`）
+    pub offline: bool,
+    /// git 依赖缓存目录，默认为 `~/.cavly/git`
+    pub git_cache: Option<PathBuf>,
+    /// 注册表目录（`--registry`），覆盖 `[registry] path` 和 `CAVLY_REGISTRY`
+    pub registry: Option<PathBuf>,
+    /// 注册表包解压目录，默认为 `~/.cavly/registry`
+    pub registry_cache: Option<PathBuf>,
+}
+
+/// `cavly update` 要重新选择版本的包
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum LockUpdate {
+    /// 保留所有锁定的版本
+    #[default]
+    None,
+    /// 重新选择所有包的版本
+    All,
+    /// 只重新选择这些包的版本
+    Packages(Vec<String>),
+}
+
+impl LockUpdate {
+    /// 是否忽略这个包锁定的版本
+    fn covers(&self, name: &str) -> bool {
+        match self {
+            LockUpdate::None => false,
+            LockUpdate::All => true,
+            LockUpdate::Packages(names) => names.iter().any(|n| n == name),
+        }
+    }
+}
+
+/// 本地包索引：工作区成员、path 依赖和 git 依赖的检出（包括依赖的依赖）
+/// 
+/// 作为版本解析的候选来源，每个目录提供一个版本（它的 `package.version`）；
+/// 配置了注册表时，注册表中已发布的版本也是候选
+#[derive(Debug, Default)]
+struct LocalIndex {
+    /// 目录 -> 包
+    packages: BTreeMap<PathBuf, IndexedPackage>,
+    /// 包注册表
+    registry: Option<Registry>,
+}
+
+/// 索引中的一个本地库
+#[derive(Debug)]
+struct IndexedPackage {
+    config: CavlyConfig,
+    source: SourceId,
+    dependencies: Vec<DependencyReq>,
+}
+
+impl PackageSource for LocalIndex {
+    fn query(&self, name: &str) -> Result<Vec<Summary>> {
+        let mut summaries = Vec::new();
+        for (path, package) in &self.packages {
+            if package.config.package.name != name {
+                continue;
+            }
+            let version = Version::parse(&package.config.package.version)
+                .with_context(|| format!("库 {} 的版本号无效（{}）", name, path.display()))?;
+            summaries.push(Summary {
+                name: name.to_string(),
+                version,
+                source: package.source.clone(),
+                dependencies: package.dependencies.clone(),
+            });
+        }
+        if let Some(ref registry) = self.registry {
+            for entry in registry.versions(name)? {
+                let version = Version::parse(&entry.version)
+                    .with_context(|| format!("注册表中 {} 的版本号无效: {}", name, entry.version))?;
+                let mut dependencies = Vec::new();
+                for dep in &entry.dependencies {
+                    dependencies.push(DependencyReq {
+                        name: dep.name.clone(),
+                        req: parse_req(&dep.name, &dep.req)?,
+                        source: None,
+                        optional: dep.optional,
+                    });
+                }
+                summaries.push(Summary {
+                    name: name.to_string(),
+                    version,
+                    source: SourceId::Registry { root: registry.root().to_path_buf(), checksum: entry.checksum },
+                    dependencies,
+                });
+            }
+        }
+        Ok(summaries)
+    }
+}
+
 /// 工作区解析器
 /// 
-/// 处理工作区成员和依赖的解析
+/// 处理工作区成员和依赖的解析，版本依赖按 SemVer 解析并记录到 cavly.lock
 pub struct WorkspaceResolver {
     /// 项目根目录
     project_root: PathBuf,
-    /// 已解析的依赖缓存
-    resolved_cache: HashMap<String, ResolvedDependency>,
-    /// 解析栈（用于检测循环依赖）
-    resolution_stack: Vec<String>,
+    /// 解析选项
+    options: ResolveOptions,
+    /// 本地包索引
+    index: LocalIndex,
+    /// 解析前已有的 lock（git 依赖优先使用其中记录的提交）
+    previous_lock: Option<LockFile>,
+    /// 最近一次解析生成的 lock
+    lock: Option<LockFile>,
 }
 
 impl WorkspaceResolver {
@@ -44,79 +154,148 @@ impl WorkspaceResolver {
     pub fn new(project_root: PathBuf) -> Self {
         Self {
             project_root,
-            resolved_cache: HashMap::new(),
-            resolution_stack: Vec::new(),
+            options: ResolveOptions::default(),
+            index: LocalIndex::default(),
+            previous_lock: None,
+            lock: None,
         }
     }
     
+    /// 设置解析选项
+    pub fn with_options(mut self, options: ResolveOptions) -> Self {
+        self.options = options;
+        self
+    }
+    
+    /// 最近一次 `resolve_all` 生成的 lock
+    pub fn lock(&self) -> Option<&LockFile> {
+        self.lock.as_ref()
+    }
+    
     /// 解析所有依赖（包括传递依赖）
     /// 
     /// # 说明
-    /// 1. 首先解析 workspace.members 中的本地库
-    /// 2. 然后解析 dependencies 中的依赖
-    /// 3. 合并所有依赖的配置到主配置
+    /// 1. 首先解析 workspace.members 中的本地库（跳过可执行成员）
+    /// 2. 收集工作区成员、path 依赖、git 依赖（克隆到缓存并检出）和注册表中的包作为候选版本
+    /// 3. 按版本要求解析整个依赖图，优先使用 cavly.lock 中锁定的版本
+    /// 4. 写入（或在 `--locked` 时校验）cavly.lock，注册表包的校验和必须与 lock 中记录的一致
+    /// 5. 注册表包校验后解压到缓存目录
     /// 
     /// # 复杂度
     /// - 时间: O(n*m)，n 为依赖数量，m 为每个依赖的配置大小
     /// - 空间: O(n) 存储解析结果
     pub fn resolve_all(&mut self, config: &CavlyConfig) -> Result<Vec<ResolvedDependency>> {
         let mut resolved = Vec::new();
+        let lock_path = self.project_root.join(LOCK_FILE);
+        self.previous_lock = LockFile::load(&lock_path)?;
+        self.index.registry = self.registry_root(config).map(Registry::open);
         
         // 1. 解析工作区成员
+        let mut member_paths = HashSet::new();
         for member in &config.workspace.members {
-            let member_path = self.project_root.join(member);
+            let member_path = normalize_path(&self.project_root.join(member));
+            // 可执行成员只参与工作区命令（--workspace、-p），不作为库依赖
+            if CavlyConfig::from_file(&member_path.join(CONFIG_FILE)).is_ok_and(|member| member.is_bin()) {
+                continue;
+            }
             if let Some(dep) = self.resolve_local_lib(&member_path, false)? {
+                self.index_package(SourceId::Path(member_path.clone()), dep.config.clone())?;
+                member_paths.insert(member_path);
                 resolved.push(dep);
             }
         }
         
-        // 2. 解析依赖
-        for (name, dep) in &config.dependencies {
-            if let Some(resolved_dep) = self.resolve_dependency(name, dep)? {
-                resolved.push(resolved_dep);
-            }
-        }
+        // 2. 根项目的依赖要求（同时收集 path 依赖）
+        let root = self.project_root.clone();
+        let roots = self.dependency_reqs(&config.dependencies, &root)?;
         
-        Ok(resolved)
-    }
-    
-    /// 解析单个依赖
-    /// 
-    /// # 复杂度
-    /// - 时间: O(1) 缓存命中，O(n) 缓存未命中
-    /// - 空间: O(1)
-    fn resolve_dependency(&mut self, name: &str, dep: &Dependency) -> Result<Option<ResolvedDependency>> {
-        // 检查缓存
-        if let Some(cached) = self.resolved_cache.get(name) {
-            return Ok(Some(cached.clone()));
+        // 3. 解析版本
+        let previous = self.previous_lock.take();
+        let mut resolver = Resolver::new(&self.index);
+        if let Some(ref previous) = previous {
+            for package in &previous.packages {
+                if self.options.update.covers(&package.name) {
+                    continue;
+                }
+                if let Ok(version) = Version::parse(&package.version) {
+                    resolver.prefer(&package.name, version);
+                }
+            }
         }
+        let resolution = resolver.resolve(&config.package.name, &roots)?;
         
-        // 检查循环依赖
-        if self.resolution_stack.contains(&name.to_string()) {
-            bail!("检测到循环依赖: {} -> {:?}", name, self.resolution_stack);
+        // 4. 更新 lock 文件
+        let lock = self.lock_from_resolution(config, &resolution);
+        if let Some(ref previous) = previous {
+            verify_checksums(previous, &lock)?;
         }
+        self.sync_lock_file(&lock_path, previous.as_ref(), &lock)?;
+        self.lock = Some(lock);
         
-        self.resolution_stack.push(name.to_string());
-        
-        let result = match dep {
-            Dependency::Simple(_version) => {
-                // TODO: 从 registry 解析版本依赖
-                // 目前仅支持本地路径依赖
-                None
+        // 5. 选中的包作为本地库解析（工作区成员已在第 1 步加入）
+        for (name, summary) in &resolution.packages {
+            let path = match summary.source {
+                SourceId::Registry { ref checksum, .. } => self.unpack_registry_package(summary, checksum)?,
+                ref source => source.local_path().expect("本地来源总有目录").to_path_buf(),
+            };
+            if member_paths.contains(&path) {
+                continue;
             }
-            Dependency::Detailed(detailed) => {
-                self.resolve_detailed_dependency(name, detailed)?
+            let optional = resolution.optional.get(name).copied().unwrap_or(false);
+            if let Some(mut dep) = self.resolve_local_lib(&path, optional)? {
+                dep.is_local = matches!(summary.source, SourceId::Path(_));
+                resolved.push(dep);
             }
-        };
-        
-        self.resolution_stack.pop();
+        }
         
-        // 缓存结果
-        if let Some(ref resolved) = result {
-            self.resolved_cache.insert(name.to_string(), resolved.clone());
+        Ok(resolved)
+    }
+    
+    /// 注册表目录：`--registry`，其次是 `[registry] path`（相对于项目根目录），最后是 `CAVLY_REGISTRY`
+    fn registry_root(&self, config: &CavlyConfig) -> Option<PathBuf> {
+        if let Some(ref path) = self.options.registry {
+            return Some(path.clone());
+        }
+        if let Some(ref path) = config.registry.path {
+            return Some(normalize_path(&self.project_root.join(path)));
         }
+        std::env::var_os("CAVLY_REGISTRY").map(PathBuf::from)
+    }
+    
+    /// 校验并解压选中的注册表包，返回包目录
+    fn unpack_registry_package(&self, summary: &Summary, checksum: &str) -> Result<PathBuf> {
+        let registry = self.index.registry.as_ref().expect("注册表包来自已打开的注册表");
+        let cache = self.options.registry_cache.clone()
+            .unwrap_or_else(|| super::cavly_home().join("registry"));
+        registry.unpack(&summary.name, &summary.version.to_string(), checksum, &cache)
+            .with_context(|| format!("获取注册表依赖 {} v{} 失败", summary.name, summary.version))
+    }
+    
+    /// 把依赖表转换为版本要求，path 依赖会加入本地索引
+    /// 
+    /// # 复杂度
+    /// - 时间: O(n)，n 为依赖数量（path 依赖递归收集）
+    /// - 空间: O(n)
+    fn dependency_reqs(&mut self, dependencies: &HashMap<String, Dependency>, base_dir: &Path) -> Result<Vec<DependencyReq>> {
+        let mut names: Vec<&String> = dependencies.keys().collect();
+        names.sort();
         
-        Ok(result)
+        let mut reqs = Vec::new();
+        for name in names {
+            let req = match &dependencies[name] {
+                Dependency::Simple(version) => Some(DependencyReq {
+                    name: name.clone(),
+                    req: parse_req(name, version)?,
+                    source: None,
+                    optional: false,
+                }),
+                Dependency::Detailed(detailed) => {
+                    self.resolve_detailed_dependency(name, detailed, base_dir)?
+                }
+            };
+            reqs.extend(req);
+        }
+        Ok(reqs)
     }
     
     /// 解析详细依赖配置
@@ -128,21 +307,138 @@ impl WorkspaceResolver {
         &mut self,
         name: &str,
         detailed: &DetailedDependency,
-    ) -> Result<Option<ResolvedDependency>> {
-        // 优先处理本地路径依赖
+        base_dir: &Path,
+    ) -> Result<Option<DependencyReq>> {
+        let req = match detailed.version {
+            Some(ref version) => parse_req(name, version)?,
+            None => VersionReq::any(),
+        };
+        
+        // 本地路径依赖：只能选中该目录中的库
         if let Some(ref path) = detailed.path {
-            let full_path = self.project_root.join(path);
-            return self.resolve_local_lib(&full_path, detailed.optional);
+            let full_path = normalize_path(&base_dir.join(path));
+            // 以目录中库的包名为准；可选依赖不存在时不加入索引，解析时跳过
+            return self.local_dependency_req(name, req, SourceId::Path(full_path), detailed.optional);
         }
         
-        // TODO: 处理 Git 依赖
-        if detailed.git.is_some() {
-            // 克隆/更新仓库并解析
+        // Git 依赖：检出到缓存后按本地库处理
+        if let Some(ref url) = detailed.git {
+            let reference = GitReference::from_fields(
+                detailed.branch.as_deref(),
+                detailed.tag.as_deref(),
+                detailed.rev.as_deref(),
+            ).with_context(|| format!("依赖 {} 的 git 配置无效", name))?;
+            let locked = self.locked_git_commit(name, url, &reference);
+            let cache = GitCache::new(
+                self.options.git_cache.clone().unwrap_or_else(GitCache::default_root),
+                self.options.offline,
+            );
+            let (commit, checkout) = cache.checkout(url, &reference, locked.as_deref())
+                .with_context(|| format!("获取 git 依赖 {} 失败", name))?;
+            let source = SourceId::Git { url: url.clone(), reference, commit, checkout };
+            return self.local_dependency_req(name, req, source, detailed.optional);
         }
         
-        // TODO: 处理版本依赖（从 registry）
-        
-        Ok(None)
+        Ok(Some(DependencyReq {
+            name: name.to_string(),
+            req,
+            source: None,
+            optional: detailed.optional,
+        }))
+    }
+    
+    /// 指向本地目录（path 依赖或 git 检出）的依赖要求，目录中的库加入索引
+    /// 
+    /// 以目录中库的包名为准；可选依赖不存在时不加入索引，解析时跳过
+    fn local_dependency_req(&mut self, name: &str, req: VersionReq, source: SourceId, optional: bool) -> Result<Option<DependencyReq>> {
+        let path = source.local_path().expect("path 依赖和 git 依赖总有本地目录").to_path_buf();
+        let package_name = match self.resolve_local_lib(&path, optional)? {
+            Some(dep) => {
+                let package_name = dep.name.clone();
+                self.index_package(source.clone(), dep.config)?;
+                package_name
+            }
+            None => name.to_string(),
+        };
+        Ok(Some(DependencyReq {
+            name: package_name,
+            req,
+            source: Some(source),
+            optional,
+        }))
+    }
+    
+    /// lock 文件中记录的 git 依赖提交（地址和引用都相同时才使用，`cavly update` 时忽略）
+    fn locked_git_commit(&self, name: &str, url: &str, reference: &GitReference) -> Option<String> {
+        if self.options.update.covers(name) {
+            return None;
+        }
+        let prefix = format!("git+{}{}#", url, reference.query());
+        self.previous_lock.as_ref()?
+            .packages.iter()
+            .filter(|p| p.name == name)
+            .find_map(|p| p.source.as_deref()?.strip_prefix(&prefix).map(String::from))
+    }
+    
+    /// 把本地库加入索引，并递归收集它的依赖
+    fn index_package(&mut self, source: SourceId, config: CavlyConfig) -> Result<()> {
+        let path = source.local_path().expect("只有本地来源会加入索引").to_path_buf();
+        if self.index.packages.contains_key(&path) {
+            return Ok(());
+        }
+        let dependencies = config.dependencies.clone();
+        self.index.packages.insert(path.clone(), IndexedPackage { config, source, dependencies: Vec::new() });
+        let reqs = self.dependency_reqs(&dependencies, &path)?;
+        if let Some(package) = self.index.packages.get_mut(&path) {
+            package.dependencies = reqs;
+        }
+        Ok(())
+    }
+    
+    /// 由解析结果生成 lock
+    fn lock_from_resolution(&self, config: &CavlyConfig, resolution: &Resolution) -> LockFile {
+        let dependencies_of = |name: &str| resolution.edges.get(name).cloned().unwrap_or_default();
+        let mut packages = vec![LockedPackage {
+            name: config.package.name.clone(),
+            version: config.package.version.clone(),
+            source: None,
+            checksum: None,
+            dependencies: dependencies_of(&config.package.name),
+        }];
+        for (name, summary) in &resolution.packages {
+            let (source, checksum) = match summary.source {
+                SourceId::Path(ref path) => (format!("path+{}", relative_path(path, &self.project_root).display()), None),
+                SourceId::Git { ref url, ref reference, ref commit, .. } => {
+                    (format!("git+{}{}#{}", url, reference.query(), commit), None)
+                }
+                SourceId::Registry { ref root, ref checksum } => {
+                    (format!("registry+{}", relative_path(root, &self.project_root).display()), Some(checksum.clone()))
+                }
+            };
+            packages.push(LockedPackage {
+                name: name.clone(),
+                version: summary.version.to_string(),
+                source: Some(source),
+                checksum,
+                dependencies: dependencies_of(name),
+            });
+        }
+        LockFile::new(packages)
+    }
+    
+    /// lock 有变化时写入；`--locked` 时报错
+    fn sync_lock_file(&self, path: &Path, previous: Option<&LockFile>, lock: &LockFile) -> Result<()> {
+        if previous == Some(lock) {
+            return Ok(());
+        }
+        if self.options.locked {
+            let changes = match previous {
+                Some(previous) => previous.diff(lock),
+                None => vec![format!("{} 不存在", LOCK_FILE)],
+            };
+            bail!("{} 需要更新，但指定了 --locked：\n  {}", LOCK_FILE, changes.join("\n  "));
+        }
+        lock.save(path)
     }
     
     /// 解析本地库项目
@@ -316,6 +612,68 @@ impl WorkspaceResolver {
     }
 }
 
+/// 注册表包的校验和必须与 lock 中同一版本记录的一致（已发布的归档被改动或替换）
+fn verify_checksums(previous: &LockFile, lock: &LockFile) -> Result<()> {
+    for package in &lock.packages {
+        let Some(ref checksum) = package.checksum else { continue };
+        let recorded = previous.packages.iter()
+            .find(|p| p.name == package.name && p.version == package.version && p.source == package.source)
+            .and_then(|p| p.checksum.as_ref());
+        if recorded.is_some_and(|recorded| recorded != checksum) {
+            bail!("{} v{} 的校验和与 {} 中记录的不一致，注册表中的归档可能被替换过（{}）",
+                package.name, package.version, LOCK_FILE, package.source.as_deref().unwrap_or(""));
+        }
+    }
+    Ok(())
+}
+
+/// 解析依赖的版本要求
+fn parse_req(name: &str, req: &str) -> Result<VersionReq> {
+    VersionReq::parse(req).with_context(|| format!("依赖 {} 的版本要求无效", name))
+}
+
+/// 按字面规范化路径：去掉 `.`，`..` 与前一个目录抵消
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if matches!(result.components().next_back(), Some(Component::Normal(_))) {
+                    result.pop();
+                } else {
+                    result.push("..");
+                }
+            }
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+/// `path` 相对于 `base` 的路径（两者都按字面规范化），用于 lock 文件中与机器无关的来源
+pub fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path = normalize_path(path);
+    let base = normalize_path(base);
+    let path_parts: Vec<Component> = path.components().collect();
+    let base_parts: Vec<Component> = base.components().collect();
+    let common = path_parts.iter().zip(&base_parts).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return path;
+    }
+    let mut result = PathBuf::new();
+    for _ in common..base_parts.len() {
+        result.push("..");
+    }
+    for part in &path_parts[common..] {
+        result.push(part);
+    }
+    if result.as_os_str().is_empty() {
+        result.push(".");
+    }
+    result
+}
+
 /// 构建依赖图（反向图）
 /// 
 /// 返回的图中，graph[dep] 表示哪些节点依赖于 dep
@@ -415,6 +773,7 @@ pub fn topological_sort(dependencies: &[ResolvedDependency]) -> Result<Vec<Resol
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cavly::fixtures::{app_config, publish_lib, write_lib};
     use tempfile::TempDir;
     
     #[test]
@@ -432,6 +791,211 @@ mod tests {
         assert!(result.unwrap().is_none());
     }
     
+    #[test]
+    fn test_resolve_path_dependencies_writes_lock() {
+        let temp = TempDir::new().unwrap();
+        let app = temp.path().join("app");
+        std::fs::create_dir_all(&app).unwrap();
+        write_lib(temp.path(), "base", "base", "1.4.0", "");
+        write_lib(temp.path(), "json", "json", "0.3.2", "base = { path = \"../base\", version = \"^1.2\" }\n");
+        
+        let config = app_config("json = { path = \"../json\", version = \"0.3\" }\nbase = \"1\"\n");
+        let mut resolver = WorkspaceResolver::new(app.clone());
+        let deps = resolver.resolve_all(&config).unwrap();
+        let mut names: Vec<&str> = deps.iter().map(|d| d.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["base", "json"]);
+        
+        let lock = LockFile::load(&app.join(LOCK_FILE)).unwrap().unwrap();
+        assert_eq!(lock.find("app").unwrap().dependencies, vec!["base", "json"]);
+        assert_eq!(lock.find("json").unwrap().version, "0.3.2");
+        assert_eq!(lock.find("json").unwrap().dependencies, vec!["base"]);
+        assert_eq!(lock.find("base").unwrap().source.as_deref(), Some("path+../base"));
+    }
+    
+    #[test]
+    fn test_workspace_bin_members_are_not_dependencies() {
+        let temp = TempDir::new().unwrap();
+        write_lib(temp.path(), "mylib", "mylib", "0.1.0", "");
+        std::fs::create_dir_all(temp.path().join("tool")).unwrap();
+        std::fs::write(temp.path().join("tool").join(CONFIG_FILE),
+            "[package]\nname = \"tool\"\nversion = \"0.1.0\"\n").unwrap();
+
+        let config: CavlyConfig = toml::from_str(
+            "[package]\nname = \"suite\"\nversion = \"0.1.0\"\n\n[workspace]\nmembers = [\"mylib\", \"tool\"]\n"
+        ).unwrap();
+        let deps = WorkspaceResolver::new(temp.path().to_path_buf()).resolve_all(&config).unwrap();
+        let names: Vec<&str> = deps.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["mylib"]);
+    }
+
+    #[test]
+    fn test_path_dependency_version_mismatch() {
+        let temp = TempDir::new().unwrap();
+        let app = temp.path().join("app");
+        std::fs::create_dir_all(&app).unwrap();
+        write_lib(temp.path(), "base", "base", "1.4.0", "");
+        write_lib(temp.path(), "net", "net", "2.0.0", "base = { path = \"../base\", version = \"^2\" }\n");
+        
+        let config = app_config("base = { path = \"../base\", version = \"^1\" }\nnet = { path = \"../net\" }\n");
+        let err = WorkspaceResolver::new(app).resolve_all(&config).unwrap_err().to_string();
+        assert!(err.contains("app 要求 base ^1"), "{}", err);
+        assert!(err.contains("net v2.0.0 要求 base ^2"), "{}", err);
+    }
+    
+    #[test]
+    fn test_registry_dependencies_are_unpacked_and_locked() {
+        let temp = TempDir::new().unwrap();
+        let app = temp.path().join("app");
+        std::fs::create_dir_all(&app).unwrap();
+        let registry = Registry::open(temp.path().join("registry"));
+        publish_lib(temp.path(), &registry, "base", "1.0.0", "");
+        publish_lib(temp.path(), &registry, "base", "1.4.0", "");
+        publish_lib(temp.path(), &registry, "base", "2.0.0", "");
+        publish_lib(temp.path(), &registry, "json", "0.3.1", "base = \"^1.2\"\n");
+        
+        let mut config = app_config("json = \"0.3\"\n");
+        config.registry.path = Some("../registry".to_string());
+        let options = ResolveOptions { registry_cache: Some(temp.path().join("cache")), ..Default::default() };
+        let deps = WorkspaceResolver::new(app.clone()).with_options(options.clone())
+            .resolve_all(&config).unwrap();
+        let base = deps.iter().find(|d| d.name == "base").unwrap();
+        assert_eq!(base.config.package.version, "1.4.0");
+        assert!(!base.is_local);
+        assert!(base.path.starts_with(temp.path().join("cache")));
+        assert!(base.path.join("src").join("lib.cay").exists());
+        
+        let lock = LockFile::load(&app.join(LOCK_FILE)).unwrap().unwrap();
+        let locked = lock.find("base").unwrap();
+        assert_eq!(locked.source.as_deref(), Some("registry+../registry"));
+        let checksum = registry.versions("base").unwrap()[1].checksum.clone();
+        assert_eq!(locked.checksum.as_deref(), Some(checksum.as_str()));
+        
+        // lock 中记录的校验和与注册表不一致
+        let tampered = std::fs::read_to_string(app.join(LOCK_FILE)).unwrap().replace(&checksum, &"0".repeat(64));
+        std::fs::write(app.join(LOCK_FILE), tampered).unwrap();
+        let err = WorkspaceResolver::new(app).with_options(options)
+            .resolve_all(&config).unwrap_err().to_string();
+        assert!(err.contains("base v1.4.0 的校验和与 cavly.lock 中记录的不一致"), "{}", err);
+    }
+    
+    #[test]
+    fn test_locked_fails_when_lock_changes() {
+        let temp = TempDir::new().unwrap();
+        let app = temp.path().join("app");
+        std::fs::create_dir_all(&app).unwrap();
+        write_lib(temp.path(), "base", "base", "1.4.0", "");
+        let locked = ResolveOptions { locked: true, ..Default::default() };
+        
+        // 没有 lock 文件
+        let config = app_config("base = { path = \"../base\" }\n");
+        let err = WorkspaceResolver::new(app.clone()).with_options(locked.clone())
+            .resolve_all(&config).unwrap_err().to_string();
+        assert!(err.contains("--locked"), "{}", err);
+        assert!(!app.join(LOCK_FILE).exists());
+        
+        WorkspaceResolver::new(app.clone()).resolve_all(&config).unwrap();
+        WorkspaceResolver::new(app.clone()).with_options(locked.clone()).resolve_all(&config).unwrap();
+        
+        // 依赖版本变化后 lock 需要更新
+        write_lib(temp.path(), "base", "base", "1.5.0", "");
+        let err = WorkspaceResolver::new(app.clone()).with_options(locked)
+            .resolve_all(&config).unwrap_err().to_string();
+        assert!(err.contains("更新 base v1.4.0 -> v1.5.0"), "{}", err);
+    }
+    
+    #[test]
+    fn test_update_with_locked_fails_when_lock_changes() {
+        let temp = TempDir::new().unwrap();
+        let app = temp.path().join("app");
+        std::fs::create_dir_all(&app).unwrap();
+        let registry = Registry::open(temp.path().join("registry"));
+        publish_lib(temp.path(), &registry, "base", "1.0.0", "");
+        
+        let mut config = app_config("base = \"1\"\n");
+        config.registry.path = Some("../registry".to_string());
+        let options = ResolveOptions { registry_cache: Some(temp.path().join("cache")), ..Default::default() };
+        WorkspaceResolver::new(app.clone()).with_options(options.clone()).resolve_all(&config).unwrap();
+        let before = std::fs::read_to_string(app.join(LOCK_FILE)).unwrap();
+        
+        // `cavly update --locked`：重新选择版本后 lock 需要更新，报错且不写入
+        publish_lib(temp.path(), &registry, "base", "1.2.0", "");
+        let update = ResolveOptions { update: LockUpdate::All, locked: true, ..options };
+        let err = WorkspaceResolver::new(app.clone()).with_options(update)
+            .resolve_all(&config).unwrap_err().to_string();
+        assert!(err.contains("需要更新，但指定了 --locked"), "{}", err);
+        assert!(err.contains("更新 base v1.0.0 -> v1.2.0"), "{}", err);
+        assert_eq!(std::fs::read_to_string(app.join(LOCK_FILE)).unwrap(), before);
+    }
+    
+    /// 在 git 仓库中提交当前所有文件
+    fn git_commit(repo: &Path, message: &str) {
+        for args in [
+            vec!["init", "--quiet", "--initial-branch=main"],
+            vec!["add", "-A"],
+            vec!["-c", "user.name=cavly", "-c", "user.email=cavly@example.com", "commit", "--quiet", "-m", message],
+        ] {
+            let status = std::process::Command::new("git").arg("-C").arg(repo).args(&args).status().unwrap();
+            assert!(status.success(), "git {:?} 失败", args);
+        }
+    }
+    
+    #[test]
+    fn test_git_dependency_is_locked_to_commit() {
+        let temp = TempDir::new().unwrap();
+        let app = temp.path().join("app");
+        std::fs::create_dir_all(&app).unwrap();
+        let repo = write_lib(temp.path(), "mathlib", "mathlib", "1.0.0", "");
+        git_commit(&repo, "v1");
+        let url = format!("file://{}", repo.display());
+        
+        let config = app_config(&format!("mathlib = {{ git = \"{}\", branch = \"main\", version = \"1\" }}\n", url));
+        let options = ResolveOptions {
+            git_cache: Some(temp.path().join("git-cache")),
+            ..Default::default()
+        };
+        let resolve = |options: &ResolveOptions| {
+            let mut resolver = WorkspaceResolver::new(app.clone()).with_options(options.clone());
+            let deps = resolver.resolve_all(&config)?;
+            let source = resolver.lock().unwrap().find("mathlib").unwrap().source.clone().unwrap();
+            anyhow::Ok((deps, source))
+        };
+        
+        let (deps, first) = resolve(&options).unwrap();
+        assert_eq!(deps.len(), 1);
+        assert!(!deps[0].is_local);
+        assert!(deps[0].path.starts_with(temp.path().join("git-cache").join("checkouts")));
+        assert!(first.starts_with(&format!("git+{}?branch=main#", url)), "{}", first);
+        
+        // 新提交不影响已锁定的构建
+        write_lib(temp.path(), "mathlib", "mathlib", "1.1.0", "");
+        git_commit(&repo, "v1.1");
+        let (deps, locked) = resolve(&options).unwrap();
+        assert_eq!(locked, first);
+        assert_eq!(deps[0].config.package.version, "1.0.0");
+        
+        // 离线模式使用缓存中的提交
+        let offline = ResolveOptions { offline: true, ..options.clone() };
+        assert_eq!(resolve(&offline).unwrap().1, first);
+        
+        // cavly update 拉取新提交
+        let update = ResolveOptions { update: LockUpdate::All, ..options.clone() };
+        let (deps, updated) = resolve(&update).unwrap();
+        assert_ne!(updated, first);
+        assert_eq!(deps[0].config.package.version, "1.1.0");
+        
+        let empty_cache = ResolveOptions { offline: true, git_cache: Some(temp.path().join("empty")), ..Default::default() };
+        let err = format!("{:#}", resolve(&empty_cache).unwrap_err());
+        assert!(err.contains("离线模式下缓存中没有 git 仓库"), "{}", err);
+    }
+    
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(relative_path(Path::new("/w/app/../libs/a"), Path::new("/w/app")), PathBuf::from("../libs/a"));
+        assert_eq!(relative_path(Path::new("/w/app/vendor/a"), Path::new("/w/app/")), PathBuf::from("vendor/a"));
+        assert_eq!(normalize_path(Path::new("a/./b/../c")), PathBuf::from("a/c"));
+    }
+    
     #[test]
     fn test_topological_sort() {
         // 创建测试依赖
@@ -194,6 +194,7 @@ pub struct IRGenerator {
     pub var_class_map: HashMap<String, String>,
     pub loop_stack: Vec<LoopContext>,
     pub target_triple: String,
+    pub target: crate::target::TargetInfo,  // 目标平台（指针宽度、C 类型大小、数据布局）
     pub static_fields: Vec<StaticFieldInfo>,
     pub static_field_map: HashMap<String, StaticFieldInfo>,
     pub type_registry: Option<TypeRegistry>,
@@ -210,6 +211,7 @@ pub struct IRGenerator {
     pub emitted_externs: HashSet<String>,  // 已生成的extern声明（函数名 -> 签名）
     pub top_level_functions: Vec<crate::ast::TopLevelFunction>,  // 顶层函数列表
     pub current_param_order: Vec<String>,  // 当前函数参数顺序（用于内联IR）
+    pub frame_tracked: bool,  // 当前函数是否已压入运行时调用栈帧
     pub type_aliases: HashMap<String, crate::types::Type>,  // 类型别名映射
     // 源映射相关
     pub current_ir_line: usize,  // 当前IR行号
@@ -219,6 +221,8 @@ pub struct IRGenerator {
     pub enable_source_map: bool, // 是否启用源映射
     pub preprocessor_source_map: Option<std::collections::HashMap<usize, (String, usize)>>, // 预处理器源映射 (输出行 -> (文件, 源行))
     pub reverse_source_map: Option<std::collections::HashMap<(String, usize), usize>>, // 反向映射 ((文件, 源行) -> 输出行)
+    pub devirtualized_calls: Vec<crate::codegen::devirt::DevirtualizedCall>, // 改为直接调用的接口调用点
+    pub whole_program: bool, // 是否整体编译（分离编译的单元看不到其他单元中的子类）
 }
 
 impl IRGenerator {
@@ -242,6 +246,7 @@ impl IRGenerator {
             var_class_map: HashMap::new(),
             loop_stack: Vec::new(),
             target_triple,
+            target: crate::target::TargetInfo::default(),
             static_fields: Vec::new(),
             static_field_map: HashMap::new(),
             type_registry: None,
@@ -258,6 +263,7 @@ impl IRGenerator {
             emitted_externs: HashSet::new(),
             top_level_functions: Vec::new(),
             current_param_order: Vec::new(),
+            frame_tracked: false,
             type_aliases: HashMap::new(),
             // 源映射初始化
             current_ir_line: 1,
@@ -267,6 +273,8 @@ impl IRGenerator {
             enable_source_map: true, // 默认启用
             preprocessor_source_map: None,
             reverse_source_map: None,
+            devirtualized_calls: Vec::new(),
+            whole_program: true,
         }
     }
 
@@ -452,36 +460,31 @@ impl IRGenerator {
     /// 优先使用loc中的file字段，如果为None则使用传入的file参数
     /// 使用预处理器源映射将预处理后的行号映射回原始源文件行号
     pub fn set_source_from_loc(&mut self, loc: &crate::error::SourceLocation, file: &str) {
-        let file_path = loc.file.clone().unwrap_or_else(|| file.to_string());
-        
+        let (source_file, source_line, source_column) = self.resolve_source_position(loc, file);
+        self.source_file = source_file;
+        self.source_line = source_line;
+        self.source_column = source_column;
+    }
+
+    /// 将 AST 位置解析为原始源码位置 (文件, 行, 列)，不修改当前源位置
+    pub fn resolve_source_position(&self, loc: &crate::error::SourceLocation, file: &str) -> (String, usize, usize) {
         // 使用预处理器源映射查找原始源位置
         if let Some(ref source_map) = self.preprocessor_source_map {
             // source_map的键是预处理后的行号，值是(原始文件路径, 原始行号)
             if let Some((original_file, original_line)) = source_map.get(&loc.line) {
-                self.source_file = original_file.clone();
-                self.source_line = *original_line;
-                self.source_column = loc.column;
-                return;
+                return (original_file.clone(), *original_line, loc.column);
             }
         }
-        
+
         // 如果没有源映射或查找失败，使用原始位置
-        self.source_file = file_path;
-        self.source_line = loc.line;
-        self.source_column = loc.column;
+        let file_path = loc.file.clone().unwrap_or_else(|| file.to_string());
+        (file_path, loc.line, loc.column)
     }
 
 
     /// 获取类型的 LLVM 对齐字节数
     pub fn get_type_align(&self, llvm_type: &str) -> u32 {
-        match llvm_type {
-            "i1" | "i8" => 1,
-            "i16" => 2,
-            "i32" | "float" => 4,  // float 是 4 字节对齐！
-            "i64" | "double" => 8,
-            t if t.ends_with("*") => 8,  // 所有指针都是 8 字节（64位系统）
-            _ => 8, // 默认 8 字节
-        }
+        crate::ir::IrType::from_llvm_str(llvm_type).alignment(&self.target) as u32
     }
 
     /// 创建新标签
@@ -535,6 +538,7 @@ impl IRGenerator {
                 LiteralValue::Char(_) => Some(Type::Char),
                 LiteralValue::Null => Some(Type::Object("Object".to_string())),
             },
+            Expr::SizeOf(_) => Some(Type::Int64),
             Expr::Identifier(name) => {
                 // 首先从Cavvy类型映射中查找（更准确）
                 if let Some(cay_type) = self.var_cay_types.get(name.as_ref()) {
@@ -689,12 +693,17 @@ impl IRGenerator {
     /// 注意：函数名不包含 this 参数，this 在 IR 层面处理
     /// 注意：可变参数方法使用 ai/as 等签名表示数组类型
     pub fn generate_method_name(&self, class_name: &str, method: &crate::ast::MethodDecl) -> String {
-        if method.params.is_empty() {
+        self.generate_method_name_from_params(class_name, &method.name, &method.params)
+    }
+
+    /// 根据方法名和形参列表生成函数名（与方法定义时使用的名称一致）
+    pub fn generate_method_name_from_params(&self, class_name: &str, method_name: &str, params: &[crate::types::ParameterInfo]) -> String {
+        if params.is_empty() {
             // 无参数方法，使用简单名称
-            format!("{}.{}", class_name, method.name)
+            format!("{}.{}", class_name, method_name)
         } else {
             // 有参数方法，添加参数类型签名
-            let param_types: Vec<String> = method.params.iter()
+            let param_types: Vec<String> = params.iter()
                 .map(|p| {
                     if p.is_varargs {
                         // 可变参数使用特殊签名：ai（int数组）、as（string数组）等
@@ -704,7 +713,7 @@ impl IRGenerator {
                     }
                 })
                 .collect();
-            format!("{}.__{}_{}", class_name, method.name, param_types.join("_"))
+            format!("{}.__{}_{}", class_name, method_name, param_types.join("_"))
         }
     }
 
@@ -777,6 +786,7 @@ impl IRGenerator {
             // FFI 指针和结构体
             Type::Pointer(inner) => format!("p{}", self.type_to_signature(inner)),
             Type::Struct(name) => format!("st{}", name),
+            Type::Nullable(inner) => self.type_to_signature(inner),
         }
     }
 
@@ -821,6 +831,12 @@ impl IRGenerator {
         if class_name == target_name {
             return true;
         }
+        // 接口需要考虑父接口，交给类型注册表判断
+        if let Some(ref registry) = self.type_registry {
+            if registry.interface_exists(target_name) {
+                return registry.implements_interface(class_name, target_name);
+            }
+        }
         
         let mut current = class_name.to_string();
         while let Some(info) = self.type_id_map.get(&current) {
@@ -886,7 +902,7 @@ impl IRGenerator {
             }
 
             let llvm_type = self.type_to_llvm(&field.field_type);
-            let size = field.field_type.size_in_bytes();
+            let size = field.field_type.size_in_bytes(&self.target);
             
             // 对齐处理
             let align = self.get_type_align(&llvm_type) as usize;
@@ -939,13 +955,19 @@ impl IRGenerator {
 
     /// 设置平台配置
     pub fn set_platform_config(&mut self, config: &crate::CompilerOptions) {
+        // 目标三元组在语义分析时已校验
+        self.target = config.target_info()
+            .unwrap_or_else(|_| crate::target::TargetInfo::for_os(&config.target_os));
+        let target_os = if config.target_triple.is_some() { self.target.os.clone() } else { config.target_os.clone() };
         let platform_config = PlatformConfig {
-            target_os: config.target_os.clone(),
+            target_os,
             features: config.features.clone(),
             no_features: config.no_features.clone(),
             defines: config.defines.clone(),
             undefines: config.undefines.clone(),
             obfuscate: config.obfuscate,
+            runtime_checks: config.runtime_checks,
+            check_overflow: config.check_overflow,
         };
         self.platform_config = Some(platform_config);
     }
@@ -955,6 +977,32 @@ impl IRGenerator {
         self.platform_config.as_ref()
     }
 
+    /// 是否生成运行时检查（未设置平台配置时默认开启）
+    pub fn runtime_checks_enabled(&self) -> bool {
+        self.platform_config.as_ref().is_none_or(|c| c.runtime_checks)
+    }
+
+    /// 是否对整数算术生成溢出检查（-fcheck-overflow，默认关闭）
+    pub fn overflow_checks_enabled(&self) -> bool {
+        self.platform_config.as_ref().is_some_and(|c| c.check_overflow)
+    }
+
+    /// 在函数入口压入运行时调用栈帧（用于 panic 时打印调用栈）
+    pub fn emit_frame_push(&mut self, display_name: &str) {
+        self.frame_tracked = self.runtime_checks_enabled();
+        if self.frame_tracked {
+            let name_ptr = self.string_constant_ptr(display_name);
+            self.emit_line(&format!("  call void @__cay_frame_push(i8* {})", name_ptr));
+        }
+    }
+
+    /// 在函数返回前弹出运行时调用栈帧
+    pub fn emit_frame_pop(&mut self) {
+        if self.frame_tracked {
+            self.emit_line("  call void @__cay_frame_pop()");
+        }
+    }
+
     /// 生成平台特定的运行时声明
     pub fn generate_platform_declarations(&self) -> String {
         if let Some(config) = &self.platform_config {
@@ -0,0 +1,189 @@
+//! 接口调用的去虚拟化
+//!
+//! 通过接口类型发起的调用（以及类从接口继承的 default 方法）默认调用接口分派函数，
+//! 由分派函数按对象头部的类型 ID 跳转。能静态确定唯一实现时改为直接调用实现函数：
+//! - 接收者是 `new` 表达式：运行时类型就是被构造的类
+//! - 接收者的静态类型是 `final` 类
+//! - 类层次分析：静态类型的所有非抽象子类型都解析到同一个实现（没有重写）
+//!
+//! 类类型接收者上的普通方法调用（包括 final、private 和 static 方法）本来就是直接调用。
+//! 程序经预处理后整体编译时，`TypeRegistry` 包含全部类，因此类层次分析的结果是完整的；
+//! 分离编译的单元看不到依赖它的单元中的子类，不做类层次分析。
+
+use std::fmt;
+
+use crate::codegen::context::IRGenerator;
+use crate::error::SourceLocation;
+use crate::types::MethodInfo;
+
+/// 能够去虚拟化的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevirtReason {
+    /// 接收者是 `new` 表达式
+    ExactReceiver,
+    /// 接收者的静态类型是 final 类
+    FinalClass,
+    /// 类层次分析只找到一个实现
+    SingleImplementation,
+}
+
+impl fmt::Display for DevirtReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DevirtReason::ExactReceiver => write!(f, "接收者是 new 表达式"),
+            DevirtReason::FinalClass => write!(f, "final 类"),
+            DevirtReason::SingleImplementation => write!(f, "类层次分析：唯一实现"),
+        }
+    }
+}
+
+/// 一个被改为直接调用的调用点（`--print-devirt` 输出）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DevirtualizedCall {
+    /// 调用点所在的原始源文件、行、列
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    /// 原本调用的分派函数
+    pub dispatch: String,
+    /// 改为直接调用的实现函数
+    pub target: String,
+    pub reason: DevirtReason,
+}
+
+impl fmt::Display for DevirtualizedCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}: {} -> {} ({})", self.file, self.line, self.column, self.dispatch, self.target, self.reason)
+    }
+}
+
+impl IRGenerator {
+    /// 为接口方法调用查找唯一的实现函数
+    ///
+    /// `receiver_class` 是接收者的静态类型（类或接口），`exact_class` 是 `new` 表达式构造的类。
+    /// 找到时记录调用点并返回 (函数名, 是否为静态方法)，否则调用方仍应调用分派函数。
+    pub(crate) fn devirtualize_interface_call(
+        &mut self,
+        receiver_class: &str,
+        exact_class: Option<&str>,
+        method: &MethodInfo,
+        loc: &SourceLocation,
+    ) -> Option<(String, bool)> {
+        let registry = self.type_registry.as_ref()?;
+        let (classes, reason) = match exact_class {
+            Some(class_name) => (vec![class_name.to_string()], DevirtReason::ExactReceiver),
+            None if registry.get_class(receiver_class).is_some_and(|c| c.is_final) => {
+                (vec![receiver_class.to_string()], DevirtReason::FinalClass)
+            }
+            None if self.whole_program => (registry.concrete_subtypes(receiver_class), DevirtReason::SingleImplementation),
+            None => return None,
+        };
+
+        // 任一类找不到实现时分派函数会走到 unknown 分支，保持原样
+        let mut target: Option<(String, bool)> = None;
+        for class_name in &classes {
+            let found = self.resolve_interface_implementation(class_name, &method.name, &method.params)?;
+            match &target {
+                Some(existing) if *existing != found => return None,
+                Some(_) => {}
+                None => target = Some(found),
+            }
+        }
+        let target = target?;
+
+        let current_file = self.source_file.clone();
+        let (file, line, column) = self.resolve_source_position(loc, &current_file);
+        let dispatch = self.generate_method_name_from_params(&method.class_name, &method.name, &method.params);
+        self.devirtualized_calls.push(DevirtualizedCall {
+            file,
+            line,
+            column,
+            dispatch,
+            target: target.0.clone(),
+            reason,
+        });
+        Some(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DevirtReason;
+    use crate::codegen::IRGenerator;
+    use crate::{lexer, parser, semantic, CompilerOptions};
+
+    const SOURCE: &str = r#"interface Shape {
+    int area();
+    default int twice() {
+        return area() * 2;
+    }
+}
+public final class Square implements Shape {
+    public int area() {
+        return 4;
+    }
+}
+class Circle implements Shape {
+    public int area() {
+        return 3;
+    }
+}
+interface Named {
+    String name();
+}
+class Dog implements Named {
+    public String name() {
+        return "Dog";
+    }
+}
+public class Main {
+    public static void main() {
+        Shape s = new Circle();
+        println(s.area());
+        Square q = new Square();
+        println(q.twice());
+        println(new Circle().twice());
+        Named n = new Dog();
+        println(n.name());
+    }
+}"#;
+
+    fn generate() -> (String, IRGenerator) {
+        let tokens = lexer::lex(SOURCE).unwrap();
+        let ast = parser::parse_with_source(tokens, SOURCE.to_string()).unwrap();
+        let mut analyzer = semantic::SemanticAnalyzer::new();
+        analyzer.analyze(&ast).unwrap();
+        let mut ir_gen = IRGenerator::new();
+        ir_gen.set_platform_config(&CompilerOptions {
+            target_os: "linux".to_string(),
+            ..CompilerOptions::default()
+        });
+        ir_gen.set_type_registry(analyzer.get_type_registry().clone());
+        let ir = ir_gen.generate(&ast, "main.cay").unwrap();
+        (ir, ir_gen)
+    }
+
+    #[test]
+    fn test_devirtualized_call_sites() {
+        let (ir, ir_gen) = generate();
+        let main = ir.split("@Main.main() {").nth(1).unwrap();
+        let main = &main[..main.find("\n}").unwrap()];
+
+        // 两个实现：仍经分派函数
+        assert!(main.contains("@Shape.area(i8* "));
+        // final 类、new 表达式和唯一实现：直接调用
+        assert!(main.contains("@Shape.twice.default(i8* "));
+        assert!(!main.contains("@Shape.twice(i8* "));
+        assert!(main.contains("@Dog.name(i8* "));
+        assert!(!main.contains("@Named.name(i8* "));
+
+        let sites: Vec<(usize, &str, DevirtReason)> = ir_gen.devirtualized_calls.iter()
+            .map(|c| (c.line, c.target.as_str(), c.reason))
+            .collect();
+        assert_eq!(sites, vec![
+            (30, "Shape.twice.default", DevirtReason::FinalClass),
+            (31, "Shape.twice.default", DevirtReason::ExactReceiver),
+            (33, "Dog.name", DevirtReason::SingleImplementation),
+        ]);
+    }
+}
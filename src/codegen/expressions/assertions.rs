@@ -0,0 +1,165 @@
+//! 测试断言代码生成
+//!
+//! `assertEquals`/`assertTrue`/`fail` 失败时调用 `__cay_panic_message`，
+//! 错误描述在运行时拼接，包含期望值、实际值和可选的说明。
+
+use crate::codegen::context::IRGenerator;
+use crate::ast::*;
+use crate::error::{cayResult, codegen_error, SourceLocation};
+use crate::types::Type;
+
+impl IRGenerator {
+    /// 生成测试断言调用；不是断言函数时返回 `None`
+    pub fn try_generate_assertion(&mut self, name: &str, call: &CallExpr) -> cayResult<Option<String>> {
+        match name {
+            "assertEquals" => self.generate_assert_equals(&call.args, &call.loc).map(Some),
+            "assertTrue" => self.generate_assert_true(&call.args, &call.loc).map(Some),
+            "fail" => self.generate_fail(&call.args, &call.loc).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// `assertEquals(expected, actual[, message])`：数值按提升后的类型比较，字符串比较内容，其余比较引用
+    fn generate_assert_equals(&mut self, args: &[Expr], loc: &SourceLocation) -> cayResult<String> {
+        if args.len() < 2 || args.len() > 3 {
+            return Err(codegen_error("assertEquals() takes 2 or 3 arguments".to_string()));
+        }
+        // `i8*` 也可能是对象引用：静态类型已知且不是 String 时按引用比较
+        let not_string = |generator: &Self, arg: &Expr| {
+            generator.get_expression_type(arg).is_some_and(|ty| *ty.non_null() != Type::String)
+        };
+        let maybe_strings = !not_string(self, &args[0]) && !not_string(self, &args[1]);
+        let expected = self.generate_expression(&args[0])?;
+        let actual = self.generate_expression(&args[1])?;
+        let (expected_type, expected_val) = self.parse_typed_value(&expected);
+        let (actual_type, actual_val) = self.parse_typed_value(&actual);
+        let strings = maybe_strings && expected_type == "i8*" && actual_type == "i8*";
+
+        let equal = self.new_temp();
+        if strings {
+            self.emit_line(&format!("  {} = call i1 @__cay_string_equals(i8* {}, i8* {})",
+                equal, expected_val, actual_val));
+        } else if let Some((ty, l, r)) = self.promote_mixed_operands(&expected_type, &expected_val, &actual_type, &actual_val) {
+            self.emit_line(&format!("  {} = fcmp oeq {} {}, {}", equal, ty, l, r));
+        } else if self.is_float_type(&expected_type) && self.is_float_type(&actual_type) {
+            let (ty, l, r) = self.promote_float_operands(&expected_type, &expected_val, &actual_type, &actual_val);
+            self.emit_line(&format!("  {} = fcmp oeq {} {}, {}", equal, ty, l, r));
+        } else if self.is_integer_type(&expected_type) && self.is_integer_type(&actual_type) {
+            let (ty, l, r) = self.promote_integer_operands(&expected_type, &expected_val, &actual_type, &actual_val);
+            self.emit_line(&format!("  {} = icmp eq {} {}, {}", equal, ty, l, r));
+        } else {
+            let actual_val = if actual_type != expected_type {
+                let cast = self.new_temp();
+                self.emit_line(&format!("  {} = bitcast {} {} to {}", cast, actual_type, actual_val, expected_type));
+                cast
+            } else {
+                actual_val.clone()
+            };
+            self.emit_line(&format!("  {} = icmp eq {} {}, {}", equal, expected_type, expected_val, actual_val));
+        }
+
+        let fail_label = self.new_label("assert.fail");
+        let ok_label = self.new_label("assert.ok");
+        self.emit_line(&format!("  br i1 {}, label %{}, label %{}", equal, ok_label, fail_label));
+        self.emit_line(&format!("{}:", fail_label));
+        let quote = if strings { "\"" } else { "" };
+        let (_, expected_str) = self.convert_to_string(&expected_type, &expected_val);
+        let (_, actual_str) = self.convert_to_string(&actual_type, &actual_val);
+        let mut parts = vec![
+            self.string_constant_ptr(&format!("expected {}", quote)),
+            expected_str,
+            self.string_constant_ptr(&format!("{}, actual {}", quote, quote)),
+            actual_str,
+        ];
+        if strings {
+            parts.push(self.string_constant_ptr(quote));
+        }
+        let detail = self.concat_string_values(parts);
+        let message = self.assertion_message(args.get(2), detail)?;
+        self.emit_panic_message_call(&message, loc);
+        self.emit_line(&format!("{}:", ok_label));
+        Ok(String::new())
+    }
+
+    /// `assertTrue(condition[, message])`
+    fn generate_assert_true(&mut self, args: &[Expr], loc: &SourceLocation) -> cayResult<String> {
+        if args.is_empty() || args.len() > 2 {
+            return Err(codegen_error("assertTrue() takes 1 or 2 arguments".to_string()));
+        }
+        let condition = self.generate_expression(&args[0])?;
+        let (cond_type, cond_val) = self.parse_typed_value(&condition);
+        let cond_val = if cond_type != "i1" {
+            let temp = self.new_temp();
+            self.emit_line(&format!("  {} = icmp ne {} {}, 0", temp, cond_type, cond_val));
+            temp
+        } else {
+            cond_val
+        };
+
+        let fail_label = self.new_label("assert.fail");
+        let ok_label = self.new_label("assert.ok");
+        self.emit_line(&format!("  br i1 {}, label %{}, label %{}", cond_val, ok_label, fail_label));
+        self.emit_line(&format!("{}:", fail_label));
+        let detail = self.string_constant_ptr("expected true, actual false");
+        let message = self.assertion_message(args.get(1), detail)?;
+        self.emit_panic_message_call(&message, loc);
+        self.emit_line(&format!("{}:", ok_label));
+        Ok(String::new())
+    }
+
+    /// `fail([message])`：无条件失败
+    fn generate_fail(&mut self, args: &[Expr], loc: &SourceLocation) -> cayResult<String> {
+        if args.len() > 1 {
+            return Err(codegen_error("fail() takes at most 1 argument".to_string()));
+        }
+        let message = match args.first() {
+            Some(message) => {
+                let prefix = self.string_constant_ptr("assertion failed: ");
+                let message = self.generate_expression(message)?;
+                let (_, message) = self.parse_typed_value(&message);
+                self.concat_string_values(vec![prefix, message])
+            }
+            None => self.string_constant_ptr("assertion failed: fail() called"),
+        };
+        self.emit_panic_message_call(&message, loc);
+        // 后续语句放入新的（不可达）基本块
+        let after = self.new_label("fail.after");
+        self.emit_line(&format!("{}:", after));
+        Ok(String::new())
+    }
+
+    /// 断言失败描述：`assertion failed: [<message>: ]<detail>`
+    fn assertion_message(&mut self, message: Option<&Expr>, detail: String) -> cayResult<String> {
+        let mut parts = vec![self.string_constant_ptr("assertion failed: ")];
+        if let Some(message) = message {
+            let message = self.generate_expression(message)?;
+            let (_, message) = self.parse_typed_value(&message);
+            parts.push(message);
+            parts.push(self.string_constant_ptr(": "));
+        }
+        parts.push(detail);
+        Ok(self.concat_string_values(parts))
+    }
+
+    /// 依次拼接 `i8*` 字符串值
+    fn concat_string_values(&mut self, parts: Vec<String>) -> String {
+        let mut parts = parts.into_iter();
+        let mut result = parts.next().expect("at least one string");
+        for part in parts {
+            let temp = self.new_temp();
+            self.emit_line(&format!("  {} = call i8* @__cay_string_concat(i8* {}, i8* {})", temp, result, part));
+            result = temp;
+        }
+        result
+    }
+
+    /// 调用 `__cay_panic_message` 并结束当前基本块
+    fn emit_panic_message_call(&mut self, message: &str, loc: &SourceLocation) {
+        let fallback_file = self.source_file.clone();
+        let (file, line, column) = self.resolve_source_position(loc, &fallback_file);
+        let file_ptr = self.string_constant_ptr(&file);
+        self.emit_line(&format!("  call void @__cay_panic_message(i8* {}, i8* {}, i32 {}, i32 {})",
+            message, file_ptr, line, column));
+        self.emit_line("  unreachable");
+    }
+}
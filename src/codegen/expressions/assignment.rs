@@ -151,11 +151,13 @@ impl IRGenerator {
                         // 其他变量：生成表达式并提取值
                         let obj = self.generate_expression(&member.object)?;
                         let (_, obj_val) = self.parse_typed_value(&obj);
+                        self.generate_null_check("i8*", &obj_val, &member.loc);
                         obj_val
                     }
                 } else {
                     let obj = self.generate_expression(&member.object)?;
                     let (_, obj_val) = self.parse_typed_value(&obj);
+                    self.generate_null_check("i8*", &obj_val, &member.loc);
                     obj_val
                 };
                 
@@ -4,7 +4,8 @@
 
 use crate::codegen::context::IRGenerator;
 use crate::ast::*;
-use crate::error::{cayResult, codegen_error};
+use crate::error::{cayResult, codegen_error, SourceLocation};
+use super::overflow::OverflowOp;
 
 /// 检查类型是否为整数类型（不包括指针）
 fn is_integer_type(ty: &str) -> bool {
@@ -60,11 +61,11 @@ impl IRGenerator {
         let temp = self.new_temp();
         
         match bin.op {
-            BinaryOp::Add => self.generate_add(&left_type, &left_val, &right_type, &right_val, &temp),
-            BinaryOp::Sub => self.generate_sub(&left_type, &left_val, &right_type, &right_val, &temp),
-            BinaryOp::Mul => self.generate_mul(&left_type, &left_val, &right_type, &right_val, &temp),
-            BinaryOp::Div => self.generate_div(&left_type, &left_val, &right_type, &right_val, &temp),
-            BinaryOp::Mod => self.generate_mod(&left_type, &left_val, &right_type, &right_val, &temp),
+            BinaryOp::Add => self.generate_add(&left_type, &left_val, &right_type, &right_val, &temp, &bin.loc),
+            BinaryOp::Sub => self.generate_sub(&left_type, &left_val, &right_type, &right_val, &temp, &bin.loc),
+            BinaryOp::Mul => self.generate_mul(&left_type, &left_val, &right_type, &right_val, &temp, &bin.loc),
+            BinaryOp::Div => self.generate_div(&left_type, &left_val, &right_type, &right_val, &temp, &bin.loc),
+            BinaryOp::Mod => self.generate_mod(&left_type, &left_val, &right_type, &right_val, &temp, &bin.loc),
             BinaryOp::Eq => self.generate_eq(&left_type, &left_val, &right_type, &right_val, &temp),
             BinaryOp::Ne => self.generate_ne(&left_type, &left_val, &right_type, &right_val, &temp),
             BinaryOp::Lt => self.generate_lt(&left_type, &left_val, &right_type, &right_val, &temp),
@@ -83,7 +84,7 @@ impl IRGenerator {
     }
 
     /// 生成加法表达式
-    fn generate_add(&mut self, left_type: &str, left_val: &str, right_type: &str, right_val: &str, temp: &str) -> cayResult<String> {
+    fn generate_add(&mut self, left_type: &str, left_val: &str, right_type: &str, right_val: &str, temp: &str, loc: &SourceLocation) -> cayResult<String> {
         // 字符串拼接处理
         if left_type == "i8*" && right_type == "i8*" {
             // 调用内建的字符串拼接函数
@@ -173,8 +174,7 @@ impl IRGenerator {
         } else if is_integer_type(left_type) && is_integer_type(right_type) {
             // 整数加法，需要类型提升
             let (promoted_type, promoted_left, promoted_right) = self.promote_integer_operands(left_type, left_val, right_type, right_val);
-            self.emit_line(&format!("  {} = add {} {}, {}",
-                temp, promoted_type, promoted_left, promoted_right));
+            self.generate_int_arith(OverflowOp::Add, &promoted_type, &promoted_left, &promoted_right, temp, loc);
             return Ok(format!("{} {}", promoted_type, temp));
         } else if (left_type == "float" || left_type == "double") && (right_type == "float" || right_type == "double") {
             // 浮点数加法，需要类型提升
@@ -212,12 +212,11 @@ impl IRGenerator {
     }
 
     /// 生成减法表达式
-    fn generate_sub(&mut self, left_type: &str, left_val: &str, right_type: &str, right_val: &str, temp: &str) -> cayResult<String> {
+    fn generate_sub(&mut self, left_type: &str, left_val: &str, right_type: &str, right_val: &str, temp: &str, loc: &SourceLocation) -> cayResult<String> {
         if is_integer_type(left_type) && is_integer_type(right_type) {
             // 整数减法，需要类型提升
             let (promoted_type, promoted_left, promoted_right) = self.promote_integer_operands(left_type, left_val, right_type, right_val);
-            self.emit_line(&format!("  {} = sub {} {}, {}",
-                temp, promoted_type, promoted_left, promoted_right));
+            self.generate_int_arith(OverflowOp::Sub, &promoted_type, &promoted_left, &promoted_right, temp, loc);
             return Ok(format!("{} {}", promoted_type, temp));
         } else if (left_type == "float" || left_type == "double") && (right_type == "float" || right_type == "double") {
             // 浮点数减法，需要类型提升
@@ -236,12 +235,11 @@ impl IRGenerator {
     }
 
     /// 生成乘法表达式
-    fn generate_mul(&mut self, left_type: &str, left_val: &str, right_type: &str, right_val: &str, temp: &str) -> cayResult<String> {
+    fn generate_mul(&mut self, left_type: &str, left_val: &str, right_type: &str, right_val: &str, temp: &str, loc: &SourceLocation) -> cayResult<String> {
         if is_integer_type(left_type) && is_integer_type(right_type) {
             // 整数乘法，需要类型提升
             let (promoted_type, promoted_left, promoted_right) = self.promote_integer_operands(left_type, left_val, right_type, right_val);
-            self.emit_line(&format!("  {} = mul {} {}, {}",
-                temp, promoted_type, promoted_left, promoted_right));
+            self.generate_int_arith(OverflowOp::Mul, &promoted_type, &promoted_left, &promoted_right, temp, loc);
             return Ok(format!("{} {}", promoted_type, temp));
         } else if (left_type == "float" || left_type == "double") && (right_type == "float" || right_type == "double") {
             // 浮点数乘法，需要类型提升
@@ -260,12 +258,14 @@ impl IRGenerator {
     }
 
     /// 生成除法表达式
-    fn generate_div(&mut self, left_type: &str, left_val: &str, right_type: &str, right_val: &str, temp: &str) -> cayResult<String> {
+    fn generate_div(&mut self, left_type: &str, left_val: &str, right_type: &str, right_val: &str, temp: &str, loc: &SourceLocation) -> cayResult<String> {
         if is_integer_type(left_type) && is_integer_type(right_type) {
             // 整数除法，需要类型提升
             let (promoted_type, promoted_left, promoted_right) = self.promote_integer_operands(left_type, left_val, right_type, right_val);
             // 运行时除零检查
-            self.generate_division_by_zero_check(&promoted_type, &promoted_right)?;
+            self.generate_division_by_zero_check(&promoted_type, &promoted_right, loc)?;
+            // -fcheck-overflow: MIN / -1 溢出检查
+            self.generate_division_overflow_check(&promoted_type, &promoted_left, &promoted_right, loc);
             self.emit_line(&format!("  {} = sdiv {} {}, {}",
                 temp, promoted_type, promoted_left, promoted_right));
             return Ok(format!("{} {}", promoted_type, temp));
@@ -286,12 +286,12 @@ impl IRGenerator {
     }
 
     /// 生成取模表达式
-    fn generate_mod(&mut self, left_type: &str, left_val: &str, right_type: &str, right_val: &str, temp: &str) -> cayResult<String> {
+    fn generate_mod(&mut self, left_type: &str, left_val: &str, right_type: &str, right_val: &str, temp: &str, loc: &SourceLocation) -> cayResult<String> {
         if is_integer_type(left_type) && is_integer_type(right_type) {
             // 整数取模，需要类型提升
             let (promoted_type, promoted_left, promoted_right) = self.promote_integer_operands(left_type, left_val, right_type, right_val);
             // 运行时除零检查（取模也需要检查）
-            self.generate_division_by_zero_check(&promoted_type, &promoted_right)?;
+            self.generate_division_by_zero_check(&promoted_type, &promoted_right, loc)?;
             self.emit_line(&format!("  {} = srem {} {}, {}",
                 temp, promoted_type, promoted_left, promoted_right));
             return Ok(format!("{} {}", promoted_type, temp));
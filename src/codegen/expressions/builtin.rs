@@ -513,7 +513,7 @@ impl IRGenerator {
 
     /// 将值转换为字符串类型
     /// 根据值的类型调用相应的运行时转换函数
-    fn convert_to_string(&mut self, type_str: &str, val: &str) -> (String, String) {
+    pub(super) fn convert_to_string(&mut self, type_str: &str, val: &str) -> (String, String) {
         match type_str {
             "i8" => {
                 // 字符类型
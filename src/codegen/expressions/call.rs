@@ -31,6 +31,9 @@ impl IRGenerator {
                 "__cay_read_int" => return self.generate_cay_read_int_call(&call.args),
                 _ => {}
             }
+            if let Some(result) = self.try_generate_assertion(name.as_str(), call)? {
+                return Ok(result);
+            }
         }
 
         // 处理 String 方法调用: str.method(args)
@@ -64,6 +67,15 @@ impl IRGenerator {
                     return self.generate_integer_parseint_call(&call.args);
                 }
             }
+
+            // 处理 Math.addExact() 等溢出检查内建函数
+            if let Expr::Identifier(class_name) = member.object.as_ref() {
+                let exact_op = self.type_registry.as_ref()
+                    .and_then(|r| r.math_exact_intrinsic(class_name.as_ref(), &member.member));
+                if let Some(op) = exact_op.filter(|_| self.get_variable_type(class_name.as_ref()).is_none()) {
+                    return self.generate_math_exact_call(op, call);
+                }
+            }
         }
 
         // 处理 extern 函数调用
@@ -179,6 +191,13 @@ impl IRGenerator {
             _ => return Err(codegen_error("Invalid function call".to_string())),
         };
 
+        // 通过接口类型调用，或调用类从接口继承的 default 方法：调用接口分派函数
+        if !is_static_call {
+            if let Some(method) = self.find_interface_dispatch(&class_name, &method_name, call.args.len()) {
+                return self.generate_interface_call(&class_name, &method, obj_expr.as_deref(), call);
+            }
+        }
+
         // 检查是否是可变参数方法（根据方法名推断）
         let is_varargs_method = self.is_varargs_method(&class_name, &method_name);
 
@@ -229,12 +248,17 @@ impl IRGenerator {
                         // 通过对象表达式获取 this 指针（如 obj1.getId()）
                         let obj_result = self.generate_expression(obj)?;
                         let (_, obj_val) = self.parse_typed_value(&obj_result);
+                        // this 不可能为 null，无需检查
+                        if name.as_ref() != "this" {
+                            self.generate_null_check("i8*", &obj_val, &call.loc);
+                        }
                         final_args.push(format!("i8* {}", obj_val));
                     }
                 } else {
                     // 通过对象表达式获取 this 指针（如 obj1.getId()）
                     let obj_result = self.generate_expression(obj)?;
                     let (_, obj_val) = self.parse_typed_value(&obj_result);
+                    self.generate_null_check("i8*", &obj_val, &call.loc);
                     final_args.push(format!("i8* {}", obj_val));
                 }
             } else if let Some(this_llvm_name) = self.scope_manager.get_llvm_name("this") {
@@ -287,6 +311,66 @@ impl IRGenerator {
         }
     }
 
+    /// 生成对接口方法分派函数的调用；能确定唯一实现时直接调用实现函数
+    ///
+    /// `class_name` 是接收者的静态类型
+    fn generate_interface_call(&mut self, class_name: &str, method: &crate::types::MethodInfo, receiver: Option<&Expr>, call: &CallExpr) -> cayResult<String> {
+        let exact_class = match receiver {
+            Some(Expr::New(new)) => Some(new.class_name.as_str()),
+            _ => None,
+        };
+        let direct = self.devirtualize_interface_call(class_name, exact_class, method, &call.loc);
+
+        // 先求值接收者，再求值参数
+        let this_val = match receiver {
+            Some(Expr::Identifier(name)) if name.as_ref() == "this" || name.as_ref() == "super" => None,
+            Some(obj) => {
+                let obj_result = self.generate_expression(obj)?;
+                let (_, obj_val) = self.parse_typed_value(&obj_result);
+                self.generate_null_check("i8*", &obj_val, &call.loc);
+                Some(obj_val)
+            }
+            None => None,
+        };
+        let this_val = match this_val {
+            Some(val) => val,
+            None => match self.scope_manager.get_llvm_name("this") {
+                Some(this_llvm_name) => {
+                    let this_temp = self.new_temp();
+                    self.emit_line(&format!("  {} = load i8*, i8** %{}, align 8", this_temp, this_llvm_name));
+                    this_temp
+                }
+                None => "null".to_string(),
+            },
+        };
+
+        // 静态方法实现不接收 this（与分派函数一致）
+        let mut final_args = Vec::new();
+        if !direct.as_ref().is_some_and(|(_, is_static)| *is_static) {
+            final_args.push(format!("i8* {}", this_val));
+        }
+        for (arg, param) in call.args.iter().zip(&method.params) {
+            let arg_result = self.generate_expression(arg)?;
+            let (arg_type, arg_val) = self.parse_typed_value(&arg_result);
+            let param_llvm_type = if param.is_varargs { "i8*".to_string() } else { self.type_to_llvm(&param.param_type) };
+            final_args.push(self.convert_arg_type(&arg_type, &arg_val, &param_llvm_type));
+        }
+
+        let fn_name = match direct {
+            Some((function, _)) => function,
+            None => self.generate_method_name_from_params(&method.class_name, &method.name, &method.params),
+        };
+        let llvm_ret_type = self.type_to_llvm(&method.return_type);
+        if method.return_type == crate::types::Type::Void {
+            self.emit_line(&format!("  call void @{}({})", fn_name, final_args.join(", ")));
+            Ok("void %dummy".to_string())
+        } else {
+            let temp = self.new_temp();
+            self.emit_line(&format!("  {} = call {} @{}({})", temp, llvm_ret_type, fn_name, final_args.join(", ")));
+            Ok(format!("{} {}", llvm_ret_type, temp))
+        }
+    }
+
     /// 生成函数名 - 优先使用类型注册表中方法定义的参数类型，支持继承
     fn generate_function_name(&self, class_name: &str, method_name: &str, processed_args: &[String], has_varargs_array: bool) -> String {
         // 特殊处理运行时 native 方法：直接返回运行时函数名
@@ -4,6 +4,7 @@
 
 use crate::codegen::context::IRGenerator;
 use crate::ast::*;
+use crate::codegen::runtime::PanicKind;
 use crate::error::{cayResult, codegen_error};
 
 impl IRGenerator {
@@ -15,6 +16,11 @@ impl IRGenerator {
         let expr_value = self.generate_expression(&cast.expr)?;
         let (from_type, val) = self.parse_typed_value(&expr_value);
         let to_type = self.type_to_llvm(&cast.target_type);
+
+        // 对象向下转型需要运行时类型检查
+        if let crate::types::Type::Object(target_class) = &cast.target_type {
+            self.generate_downcast_check(&cast.expr, &from_type, &val, target_class, &cast.loc)?;
+        }
         
         let temp = self.new_temp();
         
@@ -109,11 +115,7 @@ impl IRGenerator {
             
             // 判断源类型是否是无符号类型
             let is_from_unsigned = if let Some(src_type) = self.get_expression_type(&cast.expr) {
-                matches!(src_type, crate::types::Type::CUChar | 
-                                  crate::types::Type::CUShort | 
-                                  crate::types::Type::CUInt |
-                                  crate::types::Type::SizeT |
-                                  crate::types::Type::UIntPtr)
+                self.target.is_unsigned(&src_type)
             } else {
                 false
             };
@@ -235,4 +237,56 @@ impl IRGenerator {
 
         Err(codegen_error(format!("Unsupported cast from {} to {}", from_type, to_type)))
     }
+
+    /// 生成对象向下转型的运行时检查
+    ///
+    /// null 可以转换为任意类型；静态可知的向上转型不需要检查；
+    /// 其余情况读取对象头部的类型 ID，与目标类（及其子类）或接口实现类比较。
+    fn generate_downcast_check(&mut self, expr: &Expr, from_type: &str, val: &str, target_class: &str, loc: &crate::error::SourceLocation) -> cayResult<()> {
+        if !self.runtime_checks_enabled() || from_type != "i8*" {
+            return Ok(());
+        }
+
+        let is_interface = self.type_registry.as_ref()
+            .map(|r| r.get_interface(target_class).is_some())
+            .unwrap_or(false);
+        if !is_interface && self.get_type_id_value(target_class).is_none() {
+            // 不是已注册的类或接口（如 FFI 结构体），无法检查
+            return Ok(());
+        }
+
+        match self.get_expression_type(expr) {
+            Some(crate::types::Type::Object(source_class)) => {
+                if self.is_subtype(&source_class, target_class) {
+                    return Ok(());
+                }
+            }
+            _ => return Ok(()),
+        }
+
+        let check_label = self.new_label("cast.check");
+        let ok_label = self.new_label("cast.ok");
+        let error_label = self.new_label("cast.error");
+
+        let is_null = self.new_temp();
+        self.emit_line(&format!("  {} = icmp eq i8* {}, null", is_null, val));
+        self.emit_line(&format!("  br i1 {}, label %{}, label %{}", is_null, ok_label, check_label));
+
+        self.emit_line(&format!("\n{}:", check_label));
+        let type_id_ptr = self.new_temp();
+        self.emit_line(&format!("  {} = bitcast i8* {} to i32*", type_id_ptr, val));
+        let actual_type_id = self.new_temp();
+        self.emit_line(&format!("  {} = load i32, i32* {}", actual_type_id, type_id_ptr));
+        if is_interface {
+            self.generate_interface_check(&actual_type_id, target_class, &ok_label, &error_label)?;
+        } else {
+            self.generate_type_check(&actual_type_id, target_class, &ok_label, &error_label)?;
+        }
+
+        self.emit_line(&format!("\n{}:", error_label));
+        self.emit_panic_call(PanicKind::ClassCast, loc);
+
+        self.emit_line(&format!("\n{}:", ok_label));
+        Ok(())
+    }
 }
@@ -72,7 +72,7 @@ impl IRGenerator {
     }
 
     /// 生成类型检查代码（用于类继承）
-    fn generate_type_check(&mut self, actual_type_id: &str, target_class: &str, true_label: &str, false_label: &str) -> cayResult<()> {
+    pub(super) fn generate_type_check(&mut self, actual_type_id: &str, target_class: &str, true_label: &str, false_label: &str) -> cayResult<()> {
         let target_type_id_value = self.get_type_id_value(target_class).unwrap_or(-1);
 
         let all_matching_type_ids: Vec<i32> = if let Some(ref registry) = self.type_registry {
@@ -120,28 +120,16 @@ impl IRGenerator {
         Ok(())
     }
 
-    /// 生成接口检查代码
-    fn generate_interface_check(&mut self, actual_type_id: &str, interface_name: &str, true_label: &str, false_label: &str) -> cayResult<()> {
+    /// 生成接口检查代码（包括通过父类或子接口间接实现的情况）
+    pub(super) fn generate_interface_check(&mut self, actual_type_id: &str, interface_name: &str, true_label: &str, false_label: &str) -> cayResult<()> {
         let implementing_type_ids: Vec<i32> = if let Some(ref registry) = self.type_registry {
-            registry.classes.values()
-                .filter(|c| {
-                    if c.interfaces.contains(&interface_name.to_string()) {
-                        return true;
-                    }
-                    let mut current = c.parent.as_ref().map(|p| p.as_str());
-                    while let Some(parent_name) = current {
-                        if let Some(parent_class) = registry.get_class(parent_name) {
-                            if parent_class.interfaces.contains(&interface_name.to_string()) {
-                                return true;
-                            }
-                            current = parent_class.parent.as_ref().map(|p| p.as_str());
-                        } else {
-                            break;
-                        }
-                    }
-                    false
-                })
-                .filter_map(|c| self.get_type_id_value(&c.name))
+            let mut classes: Vec<&str> = registry.classes.values()
+                .filter(|c| registry.implements_interface(&c.name, interface_name))
+                .map(|c| c.name.as_str())
+                .collect();
+            classes.sort_unstable();
+            classes.into_iter()
+                .filter_map(|c| self.get_type_id_value(c))
                 .collect()
         } else {
             Vec::new()
@@ -26,6 +26,8 @@ impl IRGenerator {
         // 保存当前代码缓冲区
         let saved_code = std::mem::take(&mut self.code);
         let saved_temp_counter = self.temp_counter;
+        // Lambda 不压入调用栈帧，其中的 return 不能弹出外层函数的帧
+        let saved_frame_tracked = std::mem::replace(&mut self.frame_tracked, false);
 
         // 重置临时变量计数器
         self.temp_counter = 0;
@@ -71,6 +73,7 @@ impl IRGenerator {
         // 恢复之前的代码缓冲区
         self.code = saved_code;
         self.temp_counter = saved_temp_counter;
+        self.frame_tracked = saved_frame_tracked;
 
         // 将 Lambda 函数代码存储到全局函数列表
         self.lambda_functions.push(lambda_code);
@@ -65,6 +65,11 @@ impl IRGenerator {
             
             // instanceof
             Expr::InstanceOf(instanceof) => self.generate_instanceof_expression(instanceof),
+
+            // sizeof: 按目标平台求值的常量
+            Expr::SizeOf(size_of) => {
+                Ok(format!("i64 {}", self.resolve_type(&size_of.target_type).size_in_bytes(&self.target)))
+            }
             
             // 0.5.0.0: 内存分配表达式
             Expr::Alloc(alloc) => self.generate_alloc_expression(alloc),
@@ -10,8 +10,10 @@
 //! - `identifier`: 标识符/变量访问
 //! - `binary`: 二元表达式
 //! - `unary`: 一元表达式
+//! - `overflow`: 整数溢出检查和 `Math.*Exact` 内建函数
 //! - `call`: 函数/方法调用
 //! - `builtin`: 内置函数（print/read 等）
+//! - `assertions`: 测试断言（assertEquals/assertTrue/fail）
 //! - `string_methods`: String 方法调用
 //! - `array`: 数组创建、访问、初始化
 //! - `cast`: 类型转换
@@ -33,10 +35,12 @@ mod identifier;
 // 运算符
 mod binary;
 mod unary;
+mod overflow;
 
 // 调用相关
 mod call;
 mod builtin;
+mod assertions;
 mod string_methods;
 
 // 数组
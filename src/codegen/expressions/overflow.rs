@@ -0,0 +1,196 @@
+//! 整数溢出检查代码生成
+//!
+//! `Math.addExact` 等内建函数总是检查溢出；开启 `-fcheck-overflow` 时，
+//! int/long 的 `+ - *`、取负、自增/自减和除法也会检查。
+//! 检查通过 `llvm.s{add,sub,mul}.with.overflow` 实现，溢出时调用 `__cay_panic`。
+
+use crate::codegen::context::IRGenerator;
+use crate::codegen::runtime::PanicKind;
+use crate::ast::*;
+use crate::error::{cayResult, codegen_error, SourceLocation};
+use crate::types::ExactOp;
+
+/// 整数运算对应的带溢出标志的 LLVM 内建函数前缀
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowOp {
+    Add,
+    Sub,
+    Mul,
+}
+
+impl OverflowOp {
+    fn intrinsic(self) -> &'static str {
+        match self {
+            OverflowOp::Add => "sadd",
+            OverflowOp::Sub => "ssub",
+            OverflowOp::Mul => "smul",
+        }
+    }
+
+    fn plain(self) -> &'static str {
+        match self {
+            OverflowOp::Add => "add",
+            OverflowOp::Sub => "sub",
+            OverflowOp::Mul => "mul",
+        }
+    }
+}
+
+/// 是否为需要溢出检查的整数类型（int/long）
+fn is_checked_type(ty: &str) -> bool {
+    ty == "i32" || ty == "i64"
+}
+
+impl IRGenerator {
+    /// 生成整数运算，结果写入 `temp`
+    ///
+    /// 开启 `-fcheck-overflow` 且类型为 int/long 时生成溢出检查，否则生成普通的回绕运算。
+    pub(super) fn generate_int_arith(&mut self, op: OverflowOp, ty: &str, left: &str, right: &str, temp: &str, loc: &SourceLocation) {
+        if self.overflow_checks_enabled() && is_checked_type(ty) {
+            self.generate_checked_int_op(op, ty, left, right, temp, loc);
+        } else {
+            self.emit_line(&format!("  {} = {} {} {}, {}", temp, op.plain(), ty, left, right));
+        }
+    }
+
+    /// 生成带溢出检查的整数运算，结果写入 `temp`，溢出时 panic
+    pub(super) fn generate_checked_int_op(&mut self, op: OverflowOp, ty: &str, left: &str, right: &str, temp: &str, loc: &SourceLocation) {
+        let pair = self.new_temp();
+        let overflowed = self.new_temp();
+        self.emit_line(&format!("  {} = call {{{}, i1}} @llvm.{}.with.overflow.{}({} {}, {} {})",
+            pair, ty, op.intrinsic(), ty, ty, left, ty, right));
+        self.emit_line(&format!("  {} = extractvalue {{{}, i1}} {}, 0", temp, ty, pair));
+        self.emit_line(&format!("  {} = extractvalue {{{}, i1}} {}, 1", overflowed, ty, pair));
+        self.emit_overflow_branch(&overflowed, loc);
+    }
+
+    /// `-fcheck-overflow` 下检查 MIN / -1（结果无法表示）
+    pub(super) fn generate_division_overflow_check(&mut self, ty: &str, left: &str, right: &str, loc: &SourceLocation) {
+        if !self.overflow_checks_enabled() || !is_checked_type(ty) {
+            return;
+        }
+        let min = if ty == "i32" { i32::MIN as i64 } else { i64::MIN };
+        let is_min = self.new_temp();
+        let is_neg_one = self.new_temp();
+        let overflowed = self.new_temp();
+        self.emit_line(&format!("  {} = icmp eq {} {}, {}", is_min, ty, left, min));
+        self.emit_line(&format!("  {} = icmp eq {} {}, -1", is_neg_one, ty, right));
+        self.emit_line(&format!("  {} = and i1 {}, {}", overflowed, is_min, is_neg_one));
+        self.emit_overflow_branch(&overflowed, loc);
+    }
+
+    /// 溢出标志为真时跳转到 panic 块，否则继续
+    fn emit_overflow_branch(&mut self, overflowed: &str, loc: &SourceLocation) {
+        let error_label = self.new_label("overflow.error");
+        let continue_label = self.new_label("overflow.cont");
+        self.emit_line(&format!("  br i1 {}, label %{}, label %{}", overflowed, error_label, continue_label));
+        self.emit_line(&format!("{}:", error_label));
+        self.emit_panic_call(PanicKind::IntegerOverflow, loc);
+        self.emit_line(&format!("{}:", continue_label));
+    }
+
+    /// 生成 `Math.*Exact` 内建函数调用
+    ///
+    /// 参数中有 long 时按 long 计算，否则按 int 计算；无论是否开启 `-fcheck-overflow` 都检查溢出。
+    pub(super) fn generate_math_exact_call(&mut self, op: ExactOp, call: &CallExpr) -> cayResult<String> {
+        if call.args.len() != op.arity() {
+            return Err(codegen_error(format!("Math.{}() takes exactly {} argument(s)", op.method_name(), op.arity())));
+        }
+
+        let mut args = Vec::new();
+        for arg in &call.args {
+            let result = self.generate_expression(arg)?;
+            let (ty, val) = self.parse_typed_value(&result);
+            if !ty.starts_with('i') || ty.ends_with('*') {
+                return Err(codegen_error(format!("Math.{}() expects int or long, got {}", op.method_name(), ty)));
+            }
+            args.push((ty, val));
+        }
+
+        let ty = if args.iter().any(|(t, _)| t == "i64") { "i64" } else { "i32" };
+        let values: Vec<String> = args.iter()
+            .map(|(arg_ty, val)| self.widen_int_value(arg_ty, val, ty))
+            .collect();
+
+        let (overflow_op, left, right) = match op {
+            ExactOp::Add => (OverflowOp::Add, values[0].clone(), values[1].clone()),
+            ExactOp::Subtract => (OverflowOp::Sub, values[0].clone(), values[1].clone()),
+            ExactOp::Multiply => (OverflowOp::Mul, values[0].clone(), values[1].clone()),
+            ExactOp::Negate => (OverflowOp::Sub, "0".to_string(), values[0].clone()),
+            ExactOp::Increment => (OverflowOp::Add, values[0].clone(), "1".to_string()),
+            ExactOp::Decrement => (OverflowOp::Sub, values[0].clone(), "1".to_string()),
+        };
+        let temp = self.new_temp();
+        self.generate_checked_int_op(overflow_op, ty, &left, &right, &temp, &call.loc);
+        Ok(format!("{} {}", ty, temp))
+    }
+
+    /// 将整数值符号扩展到目标类型
+    fn widen_int_value(&mut self, from: &str, val: &str, to: &str) -> String {
+        if from == to {
+            return val.to_string();
+        }
+        let temp = self.new_temp();
+        self.emit_line(&format!("  {} = sext {} {} to {}", temp, from, val, to));
+        temp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::IRGenerator;
+    use crate::{lexer, parser, semantic, CompilerOptions};
+
+    const SOURCE: &str = r#"public class Main {
+    static int scale(int a, int b) {
+        return a * b;
+    }
+    public static void main() {
+        long big = Math.addExact(2147483647L, 1L);
+        int n = scale(3, 4);
+        n++;
+        println(-n / 2 + big);
+    }
+}"#;
+
+    fn generate(check_overflow: bool) -> String {
+        let tokens = lexer::lex(SOURCE).unwrap();
+        let ast = parser::parse_with_source(tokens, SOURCE.to_string()).unwrap();
+        let mut analyzer = semantic::SemanticAnalyzer::new();
+        analyzer.analyze(&ast).unwrap();
+        let mut ir_gen = IRGenerator::new();
+        ir_gen.set_platform_config(&CompilerOptions {
+            target_os: "linux".to_string(),
+            check_overflow,
+            ..CompilerOptions::default()
+        });
+        ir_gen.set_type_registry(analyzer.get_type_registry().clone());
+        ir_gen.generate(&ast, "main.cay").unwrap()
+    }
+
+    fn overflow_panics(ir: &str) -> usize {
+        ir.matches("call void @__cay_panic(i32 4,").count()
+    }
+
+    #[test]
+    fn test_math_exact_always_checked() {
+        let ir = generate(false);
+        assert!(ir.contains("call {i64, i1} @llvm.sadd.with.overflow.i64(i64 2147483647, i64 1)"));
+        assert_eq!(overflow_panics(&ir), 1);
+        let scale = ir.split("define i32 @Main.__scale_i_i").nth(1).unwrap();
+        let scale = &scale[..scale.find("\n}").unwrap()];
+        assert!(scale.contains(" = mul i32 "));
+    }
+
+    #[test]
+    fn test_check_overflow_mode() {
+        let ir = generate(true);
+        let scale = ir.split("define i32 @Main.__scale_i_i").nth(1).unwrap();
+        let scale = &scale[..scale.find("\n}").unwrap()];
+        assert!(scale.contains("@llvm.smul.with.overflow.i32"));
+        assert!(scale.lines().any(|l| l.contains("@__cay_panic(i32 4,") && l.contains("i32 3,")));
+        // addExact、乘法、自增、取负、除法 MIN / -1、long 加法
+        assert!(ir.contains("@llvm.ssub.with.overflow.i32(i32 0,"));
+        assert_eq!(overflow_panics(&ir), 6);
+    }
+}
@@ -5,6 +5,7 @@
 use crate::codegen::context::IRGenerator;
 use crate::ast::*;
 use crate::error::{cayResult, codegen_error};
+use super::overflow::OverflowOp;
 
 impl IRGenerator {
     /// 生成一元表达式代码
@@ -19,8 +20,7 @@ impl IRGenerator {
         match unary.op {
             UnaryOp::Neg => {
                 if op_type.starts_with("i") {
-                    self.emit_line(&format!("  {} = sub {} 0, {}",
-                        temp, op_type, op_val));
+                    self.generate_int_arith(OverflowOp::Sub, &op_type, "0", &op_val, &temp, &unary.loc);
                 } else {
                     self.emit_line(&format!("  {} = fneg {} {}",
                         temp, op_type, op_val));
@@ -95,13 +95,8 @@ impl IRGenerator {
                     new_temp, llvm_type, load_temp, one));
             }
         } else {
-            if is_inc {
-                self.emit_line(&format!("  {} = add {} {}, {}",
-                    new_temp, llvm_type, load_temp, one));
-            } else {
-                self.emit_line(&format!("  {} = sub {} {}, {}",
-                    new_temp, llvm_type, load_temp, one));
-            }
+            let op = if is_inc { OverflowOp::Add } else { OverflowOp::Sub };
+            self.generate_int_arith(op, &llvm_type, &load_temp, one, &new_temp, &unary.loc);
         }
         
         // 存储新值
@@ -4,7 +4,8 @@
 
 use crate::codegen::context::IRGenerator;
 use crate::ast::*;
-use crate::error::{cayResult, codegen_error};
+use crate::codegen::runtime::PanicKind;
+use crate::error::{cayResult, codegen_error, SourceLocation};
 
 impl IRGenerator {
     /// 提升整数操作数到相同类型
@@ -202,7 +203,12 @@ impl IRGenerator {
     /// # Arguments
     /// * `val_type` - 除数类型
     /// * `val` - 除数值
-    pub fn generate_division_by_zero_check(&mut self, val_type: &str, val: &str) -> cayResult<()> {
+    /// * `loc` - 除法表达式的源码位置
+    pub fn generate_division_by_zero_check(&mut self, val_type: &str, val: &str, loc: &SourceLocation) -> cayResult<()> {
+        if !self.runtime_checks_enabled() {
+            return Ok(());
+        }
+
         // 创建标签
         let error_label = self.new_label("div.error");
         let continue_label = self.new_label("div.cont");
@@ -214,12 +220,7 @@ impl IRGenerator {
 
         // 错误处理块
         self.emit_line(&format!("{}:", error_label));
-        // 输出错误信息到 stderr
-        let error_msg = self.get_or_create_string_constant("Error: Division by zero\n");
-        self.emit_line(&format!("  call i32 (i8*, ...) @printf(i8* {})", error_msg));
-        // 调用 exit 退出程序
-        self.emit_line("  call void @exit(i32 1)");
-        self.emit_line("  unreachable");
+        self.emit_panic_call(PanicKind::DivisionByZero, loc);
 
         // 正常继续块
         self.emit_line(&format!("{}:", continue_label));
@@ -227,6 +228,47 @@ impl IRGenerator {
         Ok(())
     }
 
+    /// 生成运行时空指针检查代码（关闭运行时检查时不生成）
+    ///
+    /// # Arguments
+    /// * `ptr_type` - 指针的 LLVM 类型
+    /// * `ptr` - 被解引用的指针值
+    /// * `loc` - 解引用处的源码位置
+    pub fn generate_null_check(&mut self, ptr_type: &str, ptr: &str, loc: &SourceLocation) {
+        if !self.runtime_checks_enabled() || !ptr_type.ends_with('*') {
+            return;
+        }
+
+        let error_label = self.new_label("null.error");
+        let continue_label = self.new_label("null.cont");
+
+        let is_null = self.new_temp();
+        self.emit_line(&format!("  {} = icmp eq {} {}, null", is_null, ptr_type, ptr));
+        self.emit_line(&format!("  br i1 {}, label %{}, label %{}", is_null, error_label, continue_label));
+
+        self.emit_line(&format!("{}:", error_label));
+        self.emit_panic_call(PanicKind::NullPointer, loc);
+
+        self.emit_line(&format!("{}:", continue_label));
+    }
+
+    /// 在当前基本块中调用 `__cay_panic` 并结束该基本块
+    pub fn emit_panic_call(&mut self, kind: PanicKind, loc: &SourceLocation) {
+        let fallback_file = self.source_file.clone();
+        let (file, line, column) = self.resolve_source_position(loc, &fallback_file);
+        let file_ptr = self.string_constant_ptr(&file);
+        self.emit_line(&format!("  call void @__cay_panic(i32 {}, i8* {}, i32 {}, i32 {})",
+            kind.code(), file_ptr, line, column));
+        self.emit_line("  unreachable");
+    }
+
+    /// 获取字符串常量首字节的 i8* 常量表达式
+    pub fn string_constant_ptr(&mut self, s: &str) -> String {
+        let name = self.get_or_create_string_constant(s);
+        let len = s.len() + 1;
+        format!("getelementptr inbounds ([{} x i8], [{} x i8]* {}, i64 0, i64 0)", len, len, name)
+    }
+
     /// 将 LLVM 类型转换为方法签名
     pub fn llvm_type_to_signature(&self, llvm_type: &str) -> String {
         match llvm_type {
@@ -282,11 +324,13 @@ impl IRGenerator {
                     } else {
                         let obj = self.generate_expression(member.object.as_ref())?;
                         let (_, obj_val) = self.parse_typed_value(&obj);
+                        self.generate_null_check("i8*", &obj_val, &member.loc);
                         obj_val
                     }
                 } else {
                     let obj = self.generate_expression(member.object.as_ref())?;
                     let (_, obj_val) = self.parse_typed_value(&obj);
+                    self.generate_null_check("i8*", &obj_val, &member.loc);
                     obj_val
                 };
 
@@ -85,6 +85,10 @@ impl IRGenerator {
     /// * `program` - AST程序
     /// * `source_file` - 源文件路径（用于源映射）
     pub fn generate(&mut self, program: &Program, source_file: &str) -> cayResult<String> {
+        // 可空标记只在语义检查中使用
+        let erased = program.erase_nullability();
+        let program = &erased;
+
         // 设置源文件路径
         self.source_file = source_file.to_string();
         
@@ -199,6 +203,9 @@ impl IRGenerator {
             self.generate_class(class)?;
         }
 
+        // 生成接口 default 方法体和接口方法分派函数
+        self.generate_interfaces(program)?;
+
         self.output.push_str(&self.code);
 
         // 生成跨平台 C entry point
@@ -379,7 +386,7 @@ impl IRGenerator {
         Ok(self.output.clone())
     }
 
-    fn collect_static_fields(&mut self, class: &ClassDecl) -> cayResult<()> {
+    pub(crate) fn collect_static_fields(&mut self, class: &ClassDecl) -> cayResult<()> {
         for member in &class.members {
             if let ClassMember::Field(field) = member {
                 if field.modifiers.contains(&Modifier::Static) {
@@ -403,7 +410,7 @@ impl IRGenerator {
         } else {
             base_llvm_type
         };
-        let size = field.field_type.size_in_bytes();
+        let size = field.field_type.size_in_bytes(&self.target);
 
         let field_info = crate::codegen::context::StaticFieldInfo {
             name: full_name.clone(),
@@ -453,7 +460,7 @@ impl IRGenerator {
         self.emit_raw("");
     }
 
-    fn register_type_identifiers(&mut self, program: &Program) {
+    pub(crate) fn register_type_identifiers(&mut self, program: &Program) {
         for interface in &program.interfaces {
             self.register_type_id(&interface.name, None, Vec::new());
         }
@@ -464,7 +471,7 @@ impl IRGenerator {
         }
     }
 
-    fn evaluate_const_initializer(&self, expr: &Expr, llvm_type: &str) -> Option<String> {
+    pub(crate) fn evaluate_const_initializer(&self, expr: &Expr, llvm_type: &str) -> Option<String> {
         match expr {
             Expr::Literal(crate::ast::LiteralValue::Int32(n)) => Some(n.to_string()),
             Expr::Literal(crate::ast::LiteralValue::Int64(n)) => Some(n.to_string()),
@@ -495,17 +502,23 @@ impl IRGenerator {
                 }
             }
             Expr::Literal(crate::ast::LiteralValue::Bool(b)) => Some(if *b { "1".to_string() } else { "0".to_string() }),
+            Expr::SizeOf(_) => self.evaluate_const_int(expr).map(|n| n.to_string()),
             Expr::Binary(binary) => {
                 let left = self.evaluate_const_int(&binary.left)?;
                 let right = self.evaluate_const_int(&binary.right)?;
                 let result = match binary.op {
-                    crate::ast::BinaryOp::Add => left + right,
-                    crate::ast::BinaryOp::Sub => left - right,
-                    crate::ast::BinaryOp::Mul => left * right,
-                    crate::ast::BinaryOp::Div => if right != 0 { left / right } else { return None },
+                    crate::ast::BinaryOp::Add => left.wrapping_add(right),
+                    crate::ast::BinaryOp::Sub => left.wrapping_sub(right),
+                    crate::ast::BinaryOp::Mul => left.wrapping_mul(right),
+                    crate::ast::BinaryOp::Div => if right != 0 { left.wrapping_div(right) } else { return None },
                     _ => return None,
                 };
-                Some(result.to_string())
+                // 非 final 静态字段的常量初始化按字段类型回绕（static final 溢出已在语义分析中报错）
+                if llvm_type == "i32" {
+                    Some((result as i32).to_string())
+                } else {
+                    Some(result.to_string())
+                }
             }
             _ => None,
         }
@@ -570,18 +583,19 @@ impl IRGenerator {
         }
     }
 
-    fn evaluate_const_int(&self, expr: &Expr) -> Option<i64> {
+    pub(crate) fn evaluate_const_int(&self, expr: &Expr) -> Option<i64> {
         match expr {
             Expr::Literal(crate::ast::LiteralValue::Int32(n)) => Some(*n as i64),
             Expr::Literal(crate::ast::LiteralValue::Int64(n)) => Some(*n),
+            Expr::SizeOf(size_of) => Some(self.resolve_type(&size_of.target_type).size_in_bytes(&self.target) as i64),
             Expr::Binary(binary) => {
                 let left = self.evaluate_const_int(&binary.left)?;
                 let right = self.evaluate_const_int(&binary.right)?;
                 match binary.op {
-                    crate::ast::BinaryOp::Add => Some(left + right),
-                    crate::ast::BinaryOp::Sub => Some(left - right),
-                    crate::ast::BinaryOp::Mul => Some(left * right),
-                    crate::ast::BinaryOp::Div => if right != 0 { Some(left / right) } else { None },
+                    crate::ast::BinaryOp::Add => Some(left.wrapping_add(right)),
+                    crate::ast::BinaryOp::Sub => Some(left.wrapping_sub(right)),
+                    crate::ast::BinaryOp::Mul => Some(left.wrapping_mul(right)),
+                    crate::ast::BinaryOp::Div => if right != 0 { Some(left.wrapping_div(right)) } else { None },
                     _ => None,
                 }
             }
@@ -589,7 +603,7 @@ impl IRGenerator {
         }
     }
 
-    fn get_type_size(&self, llvm_type: &str) -> i64 {
+    pub(crate) fn get_type_size(&self, llvm_type: &str) -> i64 {
         match llvm_type {
             "i1" => 1,
             "i8" => 1,
@@ -689,6 +703,11 @@ impl IRGenerator {
         }
 
         let fn_name = self.generate_method_name(class_name, method);
+        self.generate_method_with_name(class_name, method, fn_name)
+    }
+
+    /// 以指定的函数名生成方法定义（接口 default 方法体使用独立的函数名）
+    pub(super) fn generate_method_with_name(&mut self, class_name: &str, method: &MethodDecl, fn_name: String) -> cayResult<()> {
         self.current_function = fn_name.clone();
         self.current_class = class_name.to_string();
         self.current_return_type = self.type_to_llvm(&method.return_type);
@@ -726,6 +745,7 @@ impl IRGenerator {
         self.indent += 1;
 
         self.emit_line("entry:");
+        self.emit_frame_push(&format!("{}.{}", class_name, method.name));
         
         // 进入函数作用域，确保变量名有正确的作用域后缀
         self.scope_manager.enter_scope();
@@ -790,8 +810,10 @@ impl IRGenerator {
         }
 
         if method.return_type == Type::Void {
+            self.emit_frame_pop();
             self.emit_line("  ret void");
         }
+        self.frame_tracked = false;
         
         // 退出函数作用域
         self.scope_manager.exit_scope();
@@ -826,6 +848,7 @@ impl IRGenerator {
         self.indent += 1;
 
         self.emit_line("entry:");
+        self.emit_frame_push(&format!("{}.<init>", class_name));
         
         // 进入函数作用域，确保变量名有正确的作用域后缀
         self.scope_manager.enter_scope();
@@ -890,7 +913,9 @@ impl IRGenerator {
 
         self.generate_block(&ctor.body)?;
 
+        self.emit_frame_pop();
         self.emit_line("  ret void");
+        self.frame_tracked = false;
         
         // 退出函数作用域
         self.scope_manager.exit_scope();
@@ -963,6 +988,7 @@ impl IRGenerator {
         self.indent += 1;
 
         self.emit_line("entry:");
+        self.emit_frame_push(&format!("{}.<dtor>", class_name));
 
         let this_llvm_name = self.scope_manager.declare_var("this", "i8*");
         self.emit_line(&format!("  %{} = alloca i8*", this_llvm_name));
@@ -971,7 +997,9 @@ impl IRGenerator {
 
         self.generate_block(&dtor.body)?;
 
+        self.emit_frame_pop();
         self.emit_line("  ret void");
+        self.frame_tracked = false;
 
         self.indent -= 1;
         self.emit_line("}");
@@ -1039,7 +1067,7 @@ impl IRGenerator {
     }
 
     /// 推断表达式类型（用于构造函数调用）
-    fn infer_expr_type_for_ctor(&self, expr: &crate::ast::Expr) -> String {
+    pub(crate) fn infer_expr_type_for_ctor(&self, expr: &crate::ast::Expr) -> String {
         use crate::ast::*;
         
         match expr {
@@ -1161,6 +1189,7 @@ impl IRGenerator {
         self.indent += 1;
 
         self.emit_line("entry:");
+        self.emit_frame_push(&func.name);
 
         for param in &func.params {
             let param_type = self.type_to_llvm(&param.param_type);
@@ -1176,8 +1205,10 @@ impl IRGenerator {
         self.generate_block(&func.body)?;
 
         if func.return_type == Type::Void {
+            self.emit_frame_pop();
             self.emit_line("  ret void");
         }
+        self.frame_tracked = false;
 
         self.indent -= 1;
         self.emit_line("}");
@@ -1260,7 +1291,7 @@ impl IRGenerator {
     }
 
     /// 将调用约定转换为 LLVM 属性
-    fn calling_convention_to_llvm_attr(&self, cc: crate::ast::CallingConvention) -> String {
+    pub(crate) fn calling_convention_to_llvm_attr(&self, cc: crate::ast::CallingConvention) -> String {
         match cc {
             // Windows x64 平台使用 win64 调用约定
             crate::ast::CallingConvention::Cdecl => {
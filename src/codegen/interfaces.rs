@@ -0,0 +1,230 @@
+//! 接口方法分派
+//!
+//! 每个接口方法生成一个分派函数，函数名与普通方法的修饰名相同（如 `Shape.__scale_i`），
+//! 通过接口类型发起的调用直接调用该函数。分派函数读取对象头部的类型 ID，
+//! 为每个实现类跳转到具体实现：类（或其父类）中定义的方法，或最具体的 default 方法。
+//! default 方法体以 `<修饰名>.default` 为函数名单独生成。
+
+use crate::codegen::context::IRGenerator;
+use crate::codegen::runtime::PanicKind;
+use crate::ast::*;
+use crate::types::{MethodInfo, ParameterInfo};
+use crate::error::cayResult;
+
+/// 分派函数中一个实现类对应的跳转目标
+struct DispatchTarget {
+    type_id: i32,
+    function: String,
+    is_static: bool,
+}
+
+impl IRGenerator {
+    /// 生成所有接口的 default 方法体和分派函数
+    pub(super) fn generate_interfaces(&mut self, program: &Program) -> cayResult<()> {
+        for interface in &program.interfaces {
+            for method in &interface.methods {
+                if method.body.is_some() {
+                    let fn_name = format!("{}.default", self.generate_method_name(&interface.name, method));
+                    self.generate_method_with_name(&interface.name, method, fn_name)?;
+                }
+            }
+        }
+
+        // 只有非抽象类的对象会出现在运行时
+        let classes: Vec<&str> = program.classes.iter()
+            .filter(|c| !c.modifiers.contains(&Modifier::Abstract))
+            .map(|c| c.name.as_str())
+            .collect();
+        for interface in &program.interfaces {
+            for method in &interface.methods {
+                if !method.modifiers.contains(&Modifier::Static) {
+                    self.generate_interface_dispatch(&interface.name, method, &classes);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 生成单个接口方法的分派函数
+    fn generate_interface_dispatch(&mut self, interface_name: &str, method: &MethodDecl, classes: &[&str]) {
+        let targets: Vec<DispatchTarget> = classes.iter()
+            .filter_map(|class_name| {
+                let implements = self.type_registry.as_ref()
+                    .is_some_and(|r| r.implements_interface(class_name, interface_name));
+                if !implements {
+                    return None;
+                }
+                let type_id = self.get_type_id_value(class_name)?;
+                let (function, is_static) = self.resolve_interface_implementation(class_name, &method.name, &method.params)?;
+                Some(DispatchTarget { type_id, function, is_static })
+            })
+            .collect();
+
+        let fn_name = self.generate_method_name(interface_name, method);
+        let ret_type = self.type_to_llvm(&method.return_type);
+        let param_types: Vec<String> = method.params.iter()
+            .map(|p| if p.is_varargs { "i8*".to_string() } else { self.type_to_llvm(&p.param_type) })
+            .collect();
+        let params: Vec<String> = std::iter::once("i8* %this".to_string())
+            .chain(param_types.iter().enumerate().map(|(i, ty)| format!("{} %p{}", ty, i)))
+            .collect();
+        let args: Vec<String> = param_types.iter().enumerate()
+            .map(|(i, ty)| format!("{} %p{}", ty, i))
+            .collect();
+
+        self.emit_line(&format!("define {} @{}({}) {{", ret_type, fn_name, params.join(", ")));
+        self.emit_line("entry:");
+        self.emit_line("  %type_id.ptr = bitcast i8* %this to i32*");
+        self.emit_line("  %type_id = load i32, i32* %type_id.ptr, align 4");
+        self.emit_line("  switch i32 %type_id, label %unknown [");
+        for (i, target) in targets.iter().enumerate() {
+            self.emit_line(&format!("    i32 {}, label %impl.{}", target.type_id, i));
+        }
+        self.emit_line("  ]");
+
+        for (i, target) in targets.iter().enumerate() {
+            let call_args = if target.is_static {
+                args.clone()
+            } else {
+                std::iter::once("i8* %this".to_string()).chain(args.iter().cloned()).collect()
+            };
+            self.emit_line("");
+            self.emit_line(&format!("impl.{}:", i));
+            if ret_type == "void" {
+                self.emit_line(&format!("  call void @{}({})", target.function, call_args.join(", ")));
+                self.emit_line("  ret void");
+            } else {
+                self.emit_line(&format!("  %result.{} = call {} @{}({})", i, ret_type, target.function, call_args.join(", ")));
+                self.emit_line(&format!("  ret {} %result.{}", ret_type, i));
+            }
+        }
+
+        // 语义分析保证接口类型的对象都来自实现类，这里只防御非法的类型 ID（位置为接口方法的声明）
+        self.emit_line("");
+        self.emit_line("unknown:");
+        self.emit_panic_call(PanicKind::InvalidDispatch, &method.loc);
+        self.emit_line("}");
+        self.emit_line("");
+    }
+
+    /// 查找类对接口方法的实现：先沿类继承链查找同签名的非抽象方法，
+    /// 找不到时使用类实现的接口中最具体的 default 方法
+    ///
+    /// 返回 (函数名, 是否为静态方法)
+    pub(crate) fn resolve_interface_implementation(&self, class_name: &str, method_name: &str, params: &[ParameterInfo]) -> Option<(String, bool)> {
+        let registry = self.type_registry.as_ref()?;
+        let same_params = |m: &MethodInfo| {
+            m.params.len() == params.len()
+                && m.params.iter().zip(params).all(|(a, b)| a.param_type == b.param_type)
+        };
+
+        let mut current = registry.get_class(class_name);
+        while let Some(class_info) = current {
+            let found = class_info.methods.get(method_name)
+                .and_then(|methods| methods.iter().find(|m| same_params(m) && !m.is_abstract));
+            if let Some(method) = found {
+                let function = self.generate_method_name_from_params(&class_info.name, method_name, &method.params);
+                return Some((function, method.is_static));
+            }
+            current = class_info.parent.as_ref().and_then(|p| registry.get_class(p));
+        }
+
+        let defaults: Vec<&MethodInfo> = registry.implemented_interfaces(class_name).iter()
+            .filter_map(|i| registry.get_interface(i)?.methods.get(method_name))
+            .filter(|m| same_params(m) && !m.is_abstract)
+            .collect();
+        // 子接口中的 default 覆盖父接口中的 default
+        let most_specific = defaults.iter().find(|m| {
+            !defaults.iter().any(|o| o.class_name != m.class_name
+                && registry.implements_interface(&o.class_name, &m.class_name))
+        })?;
+        let function = self.generate_method_name_from_params(&most_specific.class_name, method_name, &most_specific.params);
+        Some((format!("{}.default", function), false))
+    }
+
+    /// 查找需要通过接口分派的方法调用
+    ///
+    /// 接收者是接口类型，或者类继承链中没有该方法（从接口继承的 default 方法）时，
+    /// 返回接口中声明的方法，调用方应调用该接口方法的分派函数。
+    pub(crate) fn find_interface_dispatch(&self, class_name: &str, method_name: &str, arg_count: usize) -> Option<MethodInfo> {
+        let registry = self.type_registry.as_ref()?;
+        if !registry.interface_exists(class_name) {
+            let mut current = registry.get_class(class_name);
+            while let Some(class_info) = current {
+                if class_info.methods.contains_key(method_name) {
+                    return None;
+                }
+                current = class_info.parent.as_ref().and_then(|p| registry.get_class(p));
+            }
+        }
+        registry.implemented_interfaces(class_name).iter()
+            .filter_map(|i| registry.get_interface(i)?.methods.get(method_name))
+            .find(|m| m.params.len() == arg_count && !m.is_static)
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::IRGenerator;
+    use crate::{lexer, parser, semantic, CompilerOptions};
+
+    const SOURCE: &str = r#"interface Named {
+    String name();
+    default String greet() {
+        return "Hello, " + name();
+    }
+}
+class Cat implements Named {
+    public String name() {
+        return "Cat";
+    }
+}
+class Bell implements Named {
+    public String name() {
+        return "Bell";
+    }
+    public String greet() {
+        return "Ding";
+    }
+}
+public class Main {
+    public static void main() {
+        Named n = new Cat();
+        println(n.greet());
+    }
+}"#;
+
+    fn generate() -> String {
+        let tokens = lexer::lex(SOURCE).unwrap();
+        let ast = parser::parse_with_source(tokens, SOURCE.to_string()).unwrap();
+        let mut analyzer = semantic::SemanticAnalyzer::new();
+        analyzer.analyze(&ast).unwrap();
+        let mut ir_gen = IRGenerator::new();
+        ir_gen.set_platform_config(&CompilerOptions {
+            target_os: "linux".to_string(),
+            ..CompilerOptions::default()
+        });
+        ir_gen.set_type_registry(analyzer.get_type_registry().clone());
+        ir_gen.generate(&ast, "main.cay").unwrap()
+    }
+
+    #[test]
+    fn test_dispatch_selects_default_or_override() {
+        let ir = generate();
+        assert!(ir.contains("@Named.greet.default(i8* %this)"));
+        let dispatch = ir.split("@Named.greet(i8* %this) {").nth(1).unwrap();
+        let dispatch = &dispatch[..dispatch.find("\n}").unwrap()];
+        assert!(dispatch.contains("switch i32 %type_id"));
+        assert!(dispatch.contains("@Named.greet.default(i8* %this)"));
+        assert!(dispatch.contains("@Bell.greet(i8* %this)"));
+    }
+
+    #[test]
+    fn test_interface_receiver_calls_dispatch() {
+        let ir = generate();
+        let main = ir.split("@Main.main() {").nth(1).unwrap();
+        let main = &main[..main.find("\n}").unwrap()];
+        assert!(main.contains("@Named.greet(i8* "));
+    }
+}
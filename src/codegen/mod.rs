@@ -18,6 +18,8 @@ mod expressions;
 mod statements;
 pub mod runtime;
 mod generator;
+mod interfaces;
+pub mod devirt;
 mod platform;
 pub mod obfuscator;
 pub mod source_map;
@@ -28,6 +30,9 @@ pub mod bridge;
 // 公开 IRGenerator 作为代码生成器的入口
 pub use context::IRGenerator;
 
+// 公开去虚拟化报告类型
+pub use devirt::{DevirtReason, DevirtualizedCall};
+
 // 公开源映射相关类型
 pub use source_map::{IRSourceMap, SourcePosition, SourceMapEmitter, remap_clang_error, parse_clang_error_line};
 
@@ -7,6 +7,10 @@ pub struct PlatformConfig {
     pub defines: Vec<String>,
     pub undefines: Vec<String>,
     pub obfuscate: bool,
+    /// 是否生成运行时检查（空指针、类型转换、除零）
+    pub runtime_checks: bool,
+    /// 是否对 int/long 算术生成溢出检查
+    pub check_overflow: bool,
 }
 
 impl PlatformConfig {
@@ -18,6 +22,8 @@ impl PlatformConfig {
             defines: Vec::new(),
             undefines: Vec::new(),
             obfuscate: false,
+            runtime_checks: true,
+            check_overflow: false,
         }
     }
     
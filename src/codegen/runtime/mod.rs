@@ -24,32 +24,30 @@ mod string_equals;
 mod buffer_to_string;
 mod ptr_operations;
 mod args_support;
+mod panic;
+
+pub use panic::{PanicKind, PANIC_EXIT_CODE};
 
 impl IRGenerator {
     /// 发射IR头部（外部声明和运行时函数）
     pub fn emit_header(&mut self) {
         self.emit_raw("; cay (Ethernos Object Language) Generated LLVM IR");
-        
-        // 根据目标平台设置目标三元组
-        let target_triple = if let Some(config) = &self.platform_config {
-            match config.target_os.as_str() {
-                "windows" => "x86_64-w64-mingw32",
-                "linux" => "x86_64-unknown-linux-gnu",
-                "macos" => "x86_64-apple-darwin",
-                _ => "x86_64-unknown-linux-gnu"
-            }
-        } else if cfg!(target_os = "windows") {
-            "x86_64-w64-mingw32"
-        } else if cfg!(target_os = "linux") {
-            "x86_64-unknown-linux-gnu"
-        } else if cfg!(target_os = "macos") {
-            "x86_64-apple-darwin"
-        } else {
-            "x86_64-unknown-linux-gnu"
-        };
+        let target_triple = self.platform_target_triple();
+        self.emit_raw(&format!("target datalayout = \"{}\"", self.target.data_layout));
         self.emit_raw(&format!("target triple = \"{}\"", target_triple));
         self.emit_raw("");
+        self.emit_runtime_prelude(&target_triple);
+    }
 
+    /// 目标三元组（由 `set_platform_config` 按 `--target` 确定，默认为当前操作系统的 x86_64 目标）
+    pub fn platform_target_triple(&self) -> String {
+        self.target.triple.clone()
+    }
+
+    /// 发射运行时前导代码：C 库声明、平台声明、格式字符串和所有运行时函数
+    ///
+    /// IrBuilder 把这部分文本整体放入 `IrModule::runtime_prelude`。
+    pub fn emit_runtime_prelude(&mut self, target_triple: &str) {
         // 声明外部函数 (printf 和标准C库函数)
         // 注意：这些声明会被标记为已发射，以避免与用户代码中的重复声明冲突
         // 签名格式: 函数名@返回类型@参数1@参数2@...@...
@@ -145,6 +143,13 @@ impl IRGenerator {
         
         // llvm.memcpy 是内部函数，不需要检查重复
         self.emit_raw("declare void @llvm.memcpy.p0i8.p0i8.i64(i8* noalias nocapture writeonly, i8* noalias nocapture readonly, i64, i1 immarg)");
+
+        // 带溢出标志的整数运算（Math.*Exact 和 -fcheck-overflow 使用）
+        for op in ["sadd", "ssub", "smul"] {
+            for ty in ["i32", "i64"] {
+                self.emit_raw(&format!("declare {{{0}, i1}} @llvm.{1}.with.overflow.{0}({0}, {0})", ty, op));
+            }
+        }
         
         // Windows平台使用 __acrt_iob_func 获取stdin, Linux/macOS使用外部全局变量
         if target_triple.contains("windows") || target_triple.contains("mingw") {
@@ -199,6 +204,9 @@ impl IRGenerator {
 
         // 生成内存操作函数
         self.emit_memory_runtime();
+
+        // 生成运行时检查失败时的 panic 支持
+        self.emit_panic_runtime(target_triple);
     }
     
     /// 生成内存操作运行时函数
@@ -0,0 +1,278 @@
+//! 运行时 panic 与调用栈跟踪
+//!
+//! 代码生成器在空指针解引用、对象向下转型失败、整数除零、整数溢出、接口分派等位置插入检查，
+//! 检查失败时调用 `__cay_panic(kind, file, line, col)`。
+//! panic 会打印错误类型、源码位置以及 Cavvy 函数调用栈，然后以
+//! `PANIC_EXIT_CODE` 退出。调用栈由每个函数入口/出口维护的影子栈提供。
+//! 测试断言失败时调用 `__cay_panic_message(msg, file, line, col)`，错误描述包含期望值和实际值。
+
+use crate::codegen::context::IRGenerator;
+
+/// 运行时 panic 的进程退出码（与普通错误退出码 1 区分）
+pub const PANIC_EXIT_CODE: i32 = 101;
+
+/// 影子调用栈最多记录的帧数，超出部分只计数不记录
+const MAX_FRAMES: usize = 256;
+
+/// 运行时检查失败的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanicKind {
+    /// 空引用上的成员访问或方法调用
+    NullPointer = 1,
+    /// 对象向下转型失败
+    ClassCast = 2,
+    /// 整数除零或取模零
+    DivisionByZero = 3,
+    /// 整数运算溢出（`Math.*Exact` 或 `-fcheck-overflow`）
+    IntegerOverflow = 4,
+    /// 测试断言失败（`assertEquals`/`assertTrue`/`fail`）
+    AssertionFailed = 5,
+    /// 接口方法分派时对象的类型 ID 不属于任何实现类
+    InvalidDispatch = 6,
+}
+
+impl PanicKind {
+    /// 所有 panic 类型
+    pub const ALL: [PanicKind; 6] = [
+        PanicKind::NullPointer,
+        PanicKind::ClassCast,
+        PanicKind::DivisionByZero,
+        PanicKind::IntegerOverflow,
+        PanicKind::AssertionFailed,
+        PanicKind::InvalidDispatch,
+    ];
+
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// 运行时打印的错误描述
+    pub fn message(self) -> &'static str {
+        match self {
+            PanicKind::NullPointer => "null pointer dereference",
+            PanicKind::ClassCast => "invalid object cast",
+            PanicKind::DivisionByZero => "integer division by zero",
+            PanicKind::IntegerOverflow => "integer overflow",
+            PanicKind::AssertionFailed => "assertion failed",
+            PanicKind::InvalidDispatch => "invalid interface dispatch",
+        }
+    }
+}
+
+impl IRGenerator {
+    /// 生成 panic 运行时：影子调用栈、`__cay_frame_push/pop` 和 `__cay_panic`
+    pub(super) fn emit_panic_runtime(&mut self, target_triple: &str) {
+        let decls = [
+            ("fprintf@i32@i8*@i8*@...", "declare i32 @fprintf(i8*, i8*, ...)"),
+            ("fflush@i32@i8*", "declare i32 @fflush(i8*)"),
+        ];
+        for (sig, decl) in decls {
+            if !self.is_extern_emitted(sig) {
+                self.emit_raw(decl);
+                self.mark_extern_emitted(sig.to_string());
+            }
+        }
+        let is_windows = target_triple.contains("windows") || target_triple.contains("mingw");
+        let is_apple = target_triple.contains("apple");
+        if !is_windows {
+            // stderr 是全局变量（macOS 上名为 __stderrp）
+            self.emit_raw(if is_apple { "@__stderrp = external global i8*" } else { "@stderr = external global i8*" });
+        }
+
+        self.emit_raw(&format!("@__cay_frames = internal global [{} x i8*] zeroinitializer", MAX_FRAMES));
+        self.emit_raw("@__cay_frame_depth = internal global i32 0");
+        for kind in PanicKind::ALL {
+            self.emit_panic_string(&format!("kind{}", kind.code()), kind.message());
+        }
+        let unknown = self.emit_panic_string("unknown", "runtime error");
+        let header = self.emit_panic_string("header", "panic: %s\n  --> %s:%d:%d\nstack backtrace:\n");
+        let frame = self.emit_panic_string("frame", "  %3d: %s\n");
+        self.emit_raw("");
+
+        // __cay_frame_push: 函数入口记录函数名
+        self.emit_raw("define void @__cay_frame_push(i8* %name) {");
+        self.emit_raw("entry:");
+        self.emit_raw("  %depth = load i32, i32* @__cay_frame_depth, align 4");
+        self.emit_raw(&format!("  %fits = icmp ult i32 %depth, {}", MAX_FRAMES));
+        self.emit_raw("  br i1 %fits, label %record, label %done");
+        self.emit_raw("");
+        self.emit_raw("record:");
+        self.emit_raw("  %idx = zext i32 %depth to i64");
+        self.emit_raw(&format!("  %slot = getelementptr [{0} x i8*], [{0} x i8*]* @__cay_frames, i64 0, i64 %idx", MAX_FRAMES));
+        self.emit_raw("  store i8* %name, i8** %slot, align 8");
+        self.emit_raw("  br label %done");
+        self.emit_raw("");
+        self.emit_raw("done:");
+        self.emit_raw("  %next = add i32 %depth, 1");
+        self.emit_raw("  store i32 %next, i32* @__cay_frame_depth, align 4");
+        self.emit_raw("  ret void");
+        self.emit_raw("}");
+        self.emit_raw("");
+
+        // __cay_frame_pop: 函数返回前弹出
+        self.emit_raw("define void @__cay_frame_pop() {");
+        self.emit_raw("entry:");
+        self.emit_raw("  %depth = load i32, i32* @__cay_frame_depth, align 4");
+        self.emit_raw("  %next = sub i32 %depth, 1");
+        self.emit_raw("  store i32 %next, i32* @__cay_frame_depth, align 4");
+        self.emit_raw("  ret void");
+        self.emit_raw("}");
+        self.emit_raw("");
+
+        // __cay_panic: 按类型选择错误描述
+        self.emit_raw("define void @__cay_panic(i32 %kind, i8* %file, i32 %line, i32 %col) noreturn {");
+        self.emit_raw("entry:");
+        let mut msg = unknown;
+        for kind in PanicKind::ALL {
+            let kind_msg = panic_string_ptr(&format!("kind{}", kind.code()), kind.message());
+            self.emit_raw(&format!("  %is{} = icmp eq i32 %kind, {}", kind.code(), kind.code()));
+            self.emit_raw(&format!("  %msg{} = select i1 %is{}, i8* {}, i8* {}",
+                kind.code(), kind.code(), kind_msg, msg));
+            msg = format!("%msg{}", kind.code());
+        }
+        self.emit_raw(&format!("  call void @__cay_panic_message(i8* {}, i8* %file, i32 %line, i32 %col)", msg));
+        self.emit_raw("  unreachable");
+        self.emit_raw("}");
+        self.emit_raw("");
+
+        // __cay_panic_message: 打印错误描述、位置和调用栈后退出（断言失败直接调用，描述在运行时拼接）
+        self.emit_raw("define void @__cay_panic_message(i8* %msg, i8* %file, i32 %line, i32 %col) noreturn {");
+        self.emit_raw("entry:");
+        self.emit_raw("  ; 先刷新 stdout，保证已输出内容出现在 panic 信息之前");
+        self.emit_raw("  %flushed = call i32 @fflush(i8* null)");
+        if is_windows {
+            self.emit_raw("  %err = call i8* @__acrt_iob_func(i32 2)");
+        } else if is_apple {
+            self.emit_raw("  %err = load i8*, i8** @__stderrp, align 8");
+        } else {
+            self.emit_raw("  %err = load i8*, i8** @stderr, align 8");
+        }
+        self.emit_raw(&format!("  %h = call i32 (i8*, i8*, ...) @fprintf(i8* %err, i8* {}, i8* %msg, i8* %file, i32 %line, i32 %col)", header));
+        self.emit_raw("  %depth = load i32, i32* @__cay_frame_depth, align 4");
+        self.emit_raw(&format!("  %over = icmp sgt i32 %depth, {}", MAX_FRAMES));
+        self.emit_raw(&format!("  %recorded = select i1 %over, i32 {}, i32 %depth", MAX_FRAMES));
+        self.emit_raw("  br label %loop");
+        self.emit_raw("");
+        self.emit_raw("loop:");
+        self.emit_raw("  %i = phi i32 [ %recorded, %entry ], [ %i.next, %frame ]");
+        self.emit_raw("  %more = icmp sgt i32 %i, 0");
+        self.emit_raw("  br i1 %more, label %frame, label %exit");
+        self.emit_raw("");
+        self.emit_raw("frame:");
+        self.emit_raw("  %i.next = sub i32 %i, 1");
+        self.emit_raw("  %idx = zext i32 %i.next to i64");
+        self.emit_raw(&format!("  %slot = getelementptr [{0} x i8*], [{0} x i8*]* @__cay_frames, i64 0, i64 %idx", MAX_FRAMES));
+        self.emit_raw("  %name = load i8*, i8** %slot, align 8");
+        self.emit_raw("  %n = sub i32 %recorded, %i");
+        self.emit_raw(&format!("  %f = call i32 (i8*, i8*, ...) @fprintf(i8* %err, i8* {}, i32 %n, i8* %name)", frame));
+        self.emit_raw("  br label %loop");
+        self.emit_raw("");
+        self.emit_raw("exit:");
+        self.emit_raw(&format!("  call void @exit(i32 {})", PANIC_EXIT_CODE));
+        self.emit_raw("  unreachable");
+        self.emit_raw("}");
+        self.emit_raw("");
+    }
+
+    /// 发射 panic 运行时使用的字符串常量，返回指向首字节的常量表达式
+    fn emit_panic_string(&mut self, name: &str, text: &str) -> String {
+        let escaped = text.replace('\n', "\\0A");
+        self.emit_raw(&format!("@.cay_panic.{} = private unnamed_addr constant [{} x i8] c\"{}\\00\", align 1",
+            name, text.len() + 1, escaped));
+        panic_string_ptr(name, text)
+    }
+}
+
+/// panic 字符串常量 `@.cay_panic.<name>` 的 i8* 常量表达式
+fn panic_string_ptr(name: &str, text: &str) -> String {
+    let len = text.len() + 1;
+    format!("getelementptr inbounds ([{} x i8], [{} x i8]* @.cay_panic.{}, i64 0, i64 0)", len, len, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::IRGenerator;
+    use crate::{lexer, parser, semantic, CompilerOptions};
+
+    const SOURCE: &str = r#"class Animal {
+    public int legs;
+}
+class Dog extends Animal {
+}
+public class Main {
+    static int legsOf(Animal a) {
+        return a.legs;
+    }
+    static int divide(int a, int b) {
+        return a / b;
+    }
+    public static void main() {
+        Animal a = new Dog();
+        Dog d = (Dog) a;
+        println(legsOf(d) + divide(4, 2));
+    }
+}"#;
+
+    fn generate(runtime_checks: bool) -> String {
+        let tokens = lexer::lex(SOURCE).unwrap();
+        let ast = parser::parse_with_source(tokens, SOURCE.to_string()).unwrap();
+        let mut analyzer = semantic::SemanticAnalyzer::new();
+        analyzer.analyze(&ast).unwrap();
+        let mut ir_gen = IRGenerator::new();
+        ir_gen.set_platform_config(&CompilerOptions {
+            target_os: "linux".to_string(),
+            runtime_checks,
+            ..CompilerOptions::default()
+        });
+        ir_gen.set_type_registry(analyzer.get_type_registry().clone());
+        ir_gen.generate(&ast, "main.cay").unwrap()
+    }
+
+    #[test]
+    fn test_runtime_checks_call_panic_with_location() {
+        let ir = generate(true);
+        assert!(ir.contains("define void @__cay_panic(i32 %kind, i8* %file, i32 %line, i32 %col) noreturn"));
+        // 空指针: a.legs 位于第 8 行
+        assert!(ir.contains("call void @__cay_panic(i32 1, i8* getelementptr inbounds ([9 x i8], [9 x i8]* @.str."));
+        assert!(ir.lines().any(|l| l.contains("@__cay_panic(i32 1,") && l.contains("i32 8,")));
+        // 除零: a / b 位于第 11 行
+        assert!(ir.lines().any(|l| l.contains("@__cay_panic(i32 3,") && l.contains("i32 11,")));
+        // 向下转型: (Dog) a 位于第 15 行
+        assert!(ir.lines().any(|l| l.contains("@__cay_panic(i32 2,") && l.contains("i32 15,")));
+    }
+
+    #[test]
+    fn test_functions_maintain_frame_stack() {
+        let ir = generate(true);
+        let divide = ir.split("define i32 @Main.__divide_i_i").nth(1).unwrap();
+        let divide = &divide[..divide.find("\n}").unwrap()];
+        assert!(divide.contains("call void @__cay_frame_push"));
+        let pop = divide.find("call void @__cay_frame_pop()").unwrap();
+        assert!(pop < divide.find("ret i32").unwrap());
+    }
+
+    #[test]
+    fn test_invalid_interface_dispatch_panics() {
+        let source = "public interface Shape {\n    int area();\n}\npublic class Square implements Shape {\n    public int area() { return 4; }\n}\npublic class Main {\n    public static void main() {\n        Shape s = new Square();\n        println(s.area());\n    }\n}";
+        let ast = parser::parse_with_source(lexer::lex(source).unwrap(), source.to_string()).unwrap();
+        let mut analyzer = semantic::SemanticAnalyzer::new();
+        analyzer.analyze(&ast).unwrap();
+        let mut ir_gen = IRGenerator::new();
+        ir_gen.set_type_registry(analyzer.get_type_registry().clone());
+        let ir = ir_gen.generate(&ast, "main.cay").unwrap();
+
+        // 未知类型 ID 报告接口方法 area 的声明位置（第 2 行）
+        let unknown = ir.split("\nunknown:\n").nth(1).unwrap();
+        let unknown = &unknown[..unknown.find("\n}").unwrap()];
+        assert!(unknown.contains("call void @__cay_panic(i32 6,") && unknown.contains("i32 2,"), "{}", unknown);
+        assert!(!unknown.contains("@exit("), "{}", unknown);
+    }
+
+    #[test]
+    fn test_runtime_checks_can_be_disabled() {
+        let ir = generate(false);
+        assert!(!ir.contains("call void @__cay_panic("));
+        assert!(!ir.contains("call void @__cay_frame_push"));
+        assert!(!ir.contains("call void @__cay_frame_pop"));
+    }
+}
@@ -12,6 +12,7 @@ impl IRGenerator {
         if let Some(e) = expr.as_ref() {
             let value = self.generate_expression(e)?;
             let (value_type, val) = self.parse_typed_value(&value);
+            self.emit_frame_pop();
             let ret_type = self.current_return_type.clone();
 
             // 如果返回类型是 void，但表达式非空，这是错误（但由语义分析处理）
@@ -76,6 +77,7 @@ impl IRGenerator {
                 self.emit_line(&format!("  ret {}", value));
             }
         } else {
+            self.emit_frame_pop();
             self.emit_line("  ret void");
         }
 
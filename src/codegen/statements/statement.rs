@@ -5,7 +5,7 @@
 use crate::codegen::context::IRGenerator;
 use crate::codegen::bridge::{InlineIrBridge, InlineIrResult};
 use crate::ast::*;
-use crate::error::cayResult;
+use crate::error::{cayResult, codegen_error};
 
 impl IRGenerator {
     /// 生成单个语句代码
@@ -79,6 +79,11 @@ impl IRGenerator {
                 // 0.5.0.0: 使用CodeGen-IR Builder协作桥处理内联IR
                 self.generate_inline_ir(inline_ir)?;
             }
+            Stmt::Error(loc) => {
+                return Err(codegen_error(format!(
+                    "语法错误节点不能生成代码 ({})", loc
+                )));
+            }
         }
         Ok(())
     }
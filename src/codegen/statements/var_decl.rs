@@ -9,7 +9,7 @@ use crate::error::cayResult;
 
 impl IRGenerator {
     /// 从表达式推断类型
-    fn infer_type_from_expr(&self, expr: &Expr) -> Option<Type> {
+    pub(crate) fn infer_type_from_expr(&self, expr: &Expr) -> Option<Type> {
         match expr {
             Expr::Literal(lit) => match lit {
                 LiteralValue::Int32(_) => Some(Type::Int32),
@@ -61,7 +61,7 @@ impl IRGenerator {
         // 处理内置函数
         if let Expr::Identifier(name) = call.callee.as_ref() {
             match name.as_str() {
-                "print" | "println" => return Some(Type::Void),
+                "print" | "println" | "assertEquals" | "assertTrue" | "fail" => return Some(Type::Void),
                 "readInt" => return Some(Type::Int32),
                 "readLong" => return Some(Type::Int64),
                 "readFloat" => return Some(Type::Float32),
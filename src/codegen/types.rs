@@ -53,10 +53,8 @@ impl IRGenerator {
             Type::CUChar => "i8".to_string(),     // C unsigned char 为 8 位
             Type::CFloat => "float".to_string(),  // C float 为 32 位
             Type::CDouble => "double".to_string(), // C double 为 64 位
-            Type::SizeT => "i64".to_string(),     // size_t 在 64 位系统为 64 位
-            Type::SSizeT => "i64".to_string(),    // ssize_t 在 64 位系统为 64 位
-            Type::UIntPtr => "i64".to_string(),   // uintptr_t 在 64 位系统为 64 位
-            Type::IntPtr => "i64".to_string(),    // intptr_t 在 64 位系统为 64 位
+            // size_t/ssize_t/uintptr_t/intptr_t 为指针宽度
+            Type::SizeT | Type::SSizeT | Type::UIntPtr | Type::IntPtr => self.target.pointer_int_llvm(),
             Type::CVoid => "void".to_string(),    // C void
             Type::CBool => "i8".to_string(),      // C bool 通常为 8 位
             // FFI 指针和结构体
@@ -69,6 +67,7 @@ impl IRGenerator {
                 }
             },
             Type::Struct(name) => format!("%struct.{}", name),                // 命名结构体
+            Type::Nullable(inner) => self.type_to_llvm(inner),                // 可空性不影响存储布局
         }
     }
 
@@ -76,11 +75,7 @@ impl IRGenerator {
     fn c_long_llvm(&self) -> String {
         // Windows: long 是 32 位
         // Linux/macOS: long 是 64 位
-        if self.is_windows_target() {
-            "i32".to_string()
-        } else {
-            "i64".to_string()
-        }
+        self.target.c_long_llvm()
     }
 
     /// 解析类型化的值，返回 (类型, 值)
@@ -86,10 +86,10 @@ impl SyntaxChecker {
             }
         };
         
-        // 2. 语法分析
-        let ast = match parser::parse(tokens) {
-            Ok(a) => a,
-            Err(e) => {
+        // 2. 语法分析（带错误恢复，一次报告全部语法错误）
+        let output = parser::parse_recovering(tokens);
+        if output.has_errors() {
+            for e in &output.errors {
                 let (line, col, msg) = Self::parse_error_message(&e.to_string());
                 diagnostics.push(Diagnostic {
                     line,
@@ -97,9 +97,10 @@ impl SyntaxChecker {
                     message: msg,
                     is_error: true,
                 });
-                return Ok(diagnostics);
             }
-        };
+            return Ok(diagnostics);
+        }
+        let ast = output.program;
         
         // 3. 语义分析
         let mut analyzer = semantic::SemanticAnalyzer::new();
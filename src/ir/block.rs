@@ -11,7 +11,7 @@ use std::fmt;
 /// - 唯一的标签名
 /// - 一系列非终止指令
 /// - 恰好一个终止指令（return / br / switch / unreachable）
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct IrBasicBlock {
     /// 基本块标签（如 "entry", "while.cond.0"）
     pub label: String,
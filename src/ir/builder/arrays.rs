@@ -0,0 +1,202 @@
+//! 数组构建：数组创建（含多维和不规则数组）、元素访问、长度和初始化器
+//!
+//! 内存布局: [长度:i32][填充:i32][元素0][元素1]...[元素N-1]，
+//! 数组值指向元素0，长度存储在指针前8字节。
+
+use super::{i8_ptr, IrBuilder};
+use crate::ast::*;
+use crate::error::{cayResult, codegen_error};
+use crate::ir::types::IrType;
+use crate::ir::value::{IrBinaryOp, IrCmpOp, IrInstruction, IrTerminator, IrValue};
+use crate::types::Type;
+
+impl IrBuilder {
+    /// `new T[n]` 或 `new T[n][m]...`
+    pub(super) fn build_array_creation(&mut self, arr: &ArrayCreationExpr) -> cayResult<IrValue> {
+        if arr.sizes.len() == 1 {
+            let size = self.build_array_size(&arr.sizes[0])?;
+            let elem_ty = self.ir_type(&arr.element_type);
+            Ok(self.allocate_array(&elem_ty, size))
+        } else {
+            self.build_md_array_creation(&arr.element_type, &arr.sizes)
+        }
+    }
+
+    /// 数组大小表达式（转换为 i64）
+    fn build_array_size(&mut self, size: &Expr) -> cayResult<IrValue> {
+        let value = self.build_expression(size)?;
+        if !value.ir_type().is_integer() {
+            return Err(codegen_error(format!("Array size must be integer, got {}", value.ir_type())));
+        }
+        Ok(self.convert(value, &IrType::I64))
+    }
+
+    /// 分配带长度头的一维数组（calloc 零初始化），返回元素类型指针
+    fn allocate_array(&mut self, elem_ty: &IrType, size: IrValue) -> IrValue {
+        let elem_size = elem_ty.size_bytes(&self.ctx.target).max(1) as i64;
+        let total = match &size {
+            IrValue::IntConst(n, _) => IrValue::IntConst(n * elem_size + 8, IrType::I64),
+            _ => {
+                let data = self.binop(IrBinaryOp::Mul, size.clone(), IrValue::IntConst(elem_size, IrType::I64));
+                self.binop(IrBinaryOp::Add, data, IrValue::IntConst(8, IrType::I64))
+            }
+        };
+        let raw = self.call("calloc", vec![IrValue::IntConst(1, IrType::I64), total], i8_ptr())
+            .expect("calloc returns a pointer");
+        let len_ptr = self.bitcast(raw.clone(), IrType::Pointer(Box::new(IrType::I32)));
+        let len = self.convert(size, &IrType::I32);
+        self.store(len, len_ptr);
+        let data = self.byte_offset(raw, 8);
+        self.bitcast(data, IrType::Pointer(Box::new(elem_ty.clone())))
+    }
+
+    /// 多维数组：分配子数组指针数组，循环创建每个子数组
+    ///
+    /// 含空维度（`new int[3][]`）时只分配指针数组，子数组保持 null。
+    fn build_md_array_creation(&mut self, element_type: &Type, sizes: &[Expr]) -> cayResult<IrValue> {
+        if sizes.len() < 2 {
+            return Err(codegen_error("Multidimensional array needs at least 2 dimensions".to_string()));
+        }
+        let is_jagged = sizes.iter().any(|s| matches!(s, Expr::Literal(LiteralValue::Null)));
+        let sub_sizes = &sizes[1..];
+
+        let mut sub_ty = self.ir_type(element_type);
+        for _ in 0..sub_sizes.len() {
+            sub_ty = IrType::Pointer(Box::new(sub_ty));
+        }
+
+        let count = self.build_array_size(&sizes[0])?;
+        let bytes = self.binop(IrBinaryOp::Mul, count.clone(), IrValue::IntConst(8, IrType::I64));
+        let raw = self.call("calloc", vec![IrValue::IntConst(1, IrType::I64), bytes], i8_ptr())
+            .expect("calloc returns a pointer");
+        let outer = self.bitcast(raw, IrType::Pointer(Box::new(sub_ty.clone())));
+        if is_jagged {
+            return Ok(outer);
+        }
+
+        let loop_label = self.ctx.new_label("md_loop");
+        let body_label = self.ctx.new_label("md_body");
+        let end_label = self.ctx.new_label("md_end");
+        let index_slot = self.alloca(&format!("{}.idx", loop_label), IrType::I64);
+        self.store(IrValue::IntConst(0, IrType::I64), index_slot.clone());
+
+        self.start_block(&loop_label);
+        let index = self.load(index_slot.clone(), IrType::I64);
+        let cond = self.compare(IrCmpOp::Slt, index.clone(), count);
+        self.terminate(IrTerminator::ConditionalBranch {
+            condition: cond,
+            true_target: body_label.clone(),
+            false_target: end_label.clone(),
+        });
+
+        self.start_block(&body_label);
+        let sub_array = if sub_sizes.len() == 1 {
+            let size = self.build_array_size(&sub_sizes[0])?;
+            let elem_ty = self.ir_type(element_type);
+            self.allocate_array(&elem_ty, size)
+        } else {
+            self.build_md_array_creation(element_type, sub_sizes)?
+        };
+        let slot = self.element_pointer(outer.clone(), sub_ty, index.clone());
+        self.store(sub_array, slot);
+        let next = self.binop(IrBinaryOp::Add, index, IrValue::IntConst(1, IrType::I64));
+        self.store(next, index_slot);
+        self.terminate(IrTerminator::Branch { target: loop_label });
+
+        self.start_block(&end_label);
+        Ok(outer)
+    }
+
+    /// `getelementptr elem, elem* array, i64 index`
+    fn element_pointer(&mut self, array: IrValue, elem_ty: IrType, index: IrValue) -> IrValue {
+        let result = self.temp(IrType::Pointer(Box::new(elem_ty.clone())));
+        self.emit(IrInstruction::GetElementPtr {
+            result: result.clone(),
+            ptr: array,
+            indices: vec![index],
+            base_ty: elem_ty,
+        });
+        result
+    }
+
+    // ============================================================
+    // 元素访问与长度
+    // ============================================================
+
+    pub(super) fn build_array_access(&mut self, arr: &ArrayAccessExpr) -> cayResult<IrValue> {
+        let (ptr, ty) = self.array_element_pointer(arr)?;
+        Ok(self.load(ptr, ty))
+    }
+
+    /// 数组元素的地址和元素类型（也用作赋值左值）
+    pub(super) fn array_element_pointer(&mut self, arr: &ArrayAccessExpr) -> cayResult<(IrValue, IrType)> {
+        let array = self.build_expression(&arr.array)?;
+        let index = self.build_expression(&arr.index)?;
+        if !index.ir_type().is_integer() {
+            return Err(codegen_error(format!("Array index must be integer, got {}", index.ir_type())));
+        }
+        let index = self.convert(index, &IrType::I64);
+        // 非指针数组值按 i64* 处理（与旧代码生成器一致）
+        let elem_ty = match array.ir_type() {
+            IrType::Pointer(inner) => *inner,
+            _ => IrType::I64,
+        };
+        let array = self.convert(array, &IrType::Pointer(Box::new(elem_ty.clone())));
+        let ptr = self.element_pointer(array, elem_ty.clone(), index);
+        Ok((ptr, elem_ty))
+    }
+
+    /// 读取数组指针前 8 字节中的长度（i32）
+    pub(super) fn array_length(&mut self, array: IrValue) -> IrValue {
+        let raw = self.convert(array, &i8_ptr());
+        let len_addr = self.byte_offset(raw, -8);
+        let len_ptr = self.bitcast(len_addr, IrType::Pointer(Box::new(IrType::I32)));
+        self.load(len_ptr, IrType::I32)
+    }
+
+    // ============================================================
+    // 数组初始化器
+    // ============================================================
+
+    /// `{a, b, c}`：元素类型取第一个元素的类型
+    pub(super) fn build_array_init(&mut self, init: &ArrayInitExpr) -> cayResult<IrValue> {
+        if init.elements.is_empty() {
+            return Err(codegen_error("Cannot generate code for empty array initializer".to_string()));
+        }
+        let mut values = Vec::with_capacity(init.elements.len());
+        for elem in &init.elements {
+            values.push(self.build_expression(elem)?);
+        }
+        let elem_ty = values[0].ir_type();
+        Ok(self.store_array_elements(&elem_ty, values))
+    }
+
+    /// 按目标数组类型构建初始化器，元素转换为目标元素类型
+    pub(super) fn build_array_init_with_type(&mut self, init: &ArrayInitExpr, target: &Type) -> cayResult<IrValue> {
+        let Type::Array(elem_type) = target else {
+            return self.build_array_init(init);
+        };
+        if init.elements.is_empty() {
+            return Err(codegen_error("Cannot generate code for empty array initializer".to_string()));
+        }
+        let elem_ty = self.ir_type(elem_type);
+        let mut values = Vec::with_capacity(init.elements.len());
+        for elem in &init.elements {
+            let value = self.build_expression_with_type(elem, elem_type)?;
+            values.push(self.convert(value, &elem_ty));
+        }
+        Ok(self.store_array_elements(&elem_ty, values))
+    }
+
+    /// 分配数组并依次存入元素
+    fn store_array_elements(&mut self, elem_ty: &IrType, values: Vec<IrValue>) -> IrValue {
+        let size = IrValue::IntConst(values.len() as i64, IrType::I64);
+        let array = self.allocate_array(elem_ty, size);
+        for (i, value) in values.into_iter().enumerate() {
+            let slot = self.element_pointer(array.clone(), elem_ty.clone(), IrValue::IntConst(i as i64, IrType::I64));
+            let value = self.convert(value, elem_ty);
+            self.store(value, slot);
+        }
+        array
+    }
+}
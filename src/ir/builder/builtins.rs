@@ -0,0 +1,540 @@
+//! 内置函数：print/println、read*、测试断言、运行时辅助函数、String 方法和 `Math.*Exact`
+
+use super::{i8_ptr, void_value, IrBuilder};
+use crate::ast::*;
+use crate::error::{cayResult, codegen_error, SourceLocation};
+use crate::ir::types::IrType;
+use crate::ir::value::{IrBinaryOp, IrCmpOp, IrTerminator, IrValue};
+use crate::types::{ExactOp, Type};
+
+/// 格式化字符串占位符类型
+#[derive(Debug, Clone)]
+enum Placeholder {
+    CStyle(String),      // %d, %s, %f 等
+    Sequential,          // {}
+    Named,               // {name}
+}
+
+/// 运行时库中定义的辅助函数的参数类型和返回类型
+fn runtime_helper_signature(name: &str) -> Option<(Vec<IrType>, IrType)> {
+    let signature = match name {
+        "__cay_read_ptr" => (vec![IrType::I64], IrType::I64),
+        "__cay_ptr_to_string" => (vec![IrType::I64], i8_ptr()),
+        "__cay_write_ptr" => (vec![IrType::I64, IrType::I64], IrType::Void),
+        "__cay_write_int" => (vec![IrType::I64, IrType::I32], IrType::Void),
+        "__cay_read_int" => (vec![IrType::I64], IrType::I32),
+        "__cay_buffer_to_string" => (vec![IrType::I64, IrType::I32], i8_ptr()),
+        _ => return None,
+    };
+    Some(signature)
+}
+
+impl IrBuilder {
+    /// 按名称调用的内置函数；不是内置函数时返回 `None`
+    pub(super) fn try_build_builtin(&mut self, name: &str, args: &[Expr], loc: &SourceLocation) -> cayResult<Option<IrValue>> {
+        let value = match name {
+            "print" => self.build_print(args, false)?,
+            "println" => self.build_print(args, true)?,
+            "assertEquals" => self.build_assert_equals(args, loc)?,
+            "assertTrue" => self.build_assert_true(args, loc)?,
+            "fail" => self.build_fail(args, loc)?,
+            "readInt" | "readLong" => {
+                let fmt = self.ctx.get_i64_format_specifier();
+                self.build_scanf(name, args, fmt, IrType::I64)?
+            }
+            "readFloat" => self.build_scanf(name, args, "%f", IrType::F32)?,
+            "readDouble" => self.build_scanf(name, args, "%lf", IrType::F64)?,
+            "readChar" => self.build_scanf(name, args, " %c", IrType::I8)?,
+            "readLine" => self.build_read_line(args)?,
+            _ => match runtime_helper_signature(name) {
+                Some((params, ret)) => self.build_runtime_helper(name, args, &params, ret)?,
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(value))
+    }
+
+    /// 运行时库中定义的辅助函数（参数按声明类型转换）
+    fn build_runtime_helper(&mut self, name: &str, args: &[Expr], params: &[IrType], ret: IrType) -> cayResult<IrValue> {
+        if args.len() != params.len() {
+            let noun = if params.len() == 1 { "argument" } else { "arguments" };
+            return Err(codegen_error(format!("{} requires {} {}", name, params.len(), noun)));
+        }
+        let mut values = Vec::new();
+        for (arg, param) in args.iter().zip(params) {
+            let value = self.build_expression(arg)?;
+            values.push(self.convert(value, param));
+        }
+        Ok(self.call(name, values, ret).unwrap_or_else(void_value))
+    }
+
+    // ============================================================
+    // print / println
+    // ============================================================
+
+    /// 单参数直接打印；多参数时第一个参数是格式字符串
+    fn build_print(&mut self, args: &[Expr], newline: bool) -> cayResult<IrValue> {
+        let end = if newline { "\n" } else { "" };
+        match args {
+            [] => {
+                if newline {
+                    self.printf("\n", Vec::new());
+                }
+            }
+            [Expr::Literal(LiteralValue::String(s))] => {
+                let value = self.string_ptr(s);
+                self.printf(&format!("%s{}", end), vec![value]);
+            }
+            [arg] => self.print_value(arg, end)?,
+            [Expr::Literal(LiteralValue::String(fmt)), rest @ ..] => self.print_format(fmt, rest, end)?,
+            [first, ..] => self.print_value(first, end)?,
+        }
+        Ok(IrValue::IntConst(0, IrType::I64))
+    }
+
+    fn print_value(&mut self, arg: &Expr, end: &str) -> cayResult<()> {
+        let value = self.build_expression(arg)?;
+        let ty = value.ir_type();
+        match &ty {
+            IrType::I1 => {
+                let s = self.value_to_string(value);
+                self.printf(&format!("%s{}", end), vec![s]);
+            }
+            t if t.is_integer() => {
+                let fmt = self.ctx.get_i64_format_specifier();
+                let wide = self.convert(value, &IrType::I64);
+                self.printf(&format!("{}{}", fmt, end), vec![wide]);
+            }
+            t if t.is_float() => {
+                let wide = self.convert(value, &IrType::F64);
+                self.printf(&format!("%f{}", end), vec![wide]);
+            }
+            _ => self.printf(&format!("%s{}", end), vec![value]),
+        }
+        Ok(())
+    }
+
+    /// 格式化打印：支持 C 风格 `%d`、顺序占位符 `{}` 和命名占位符 `{name}`
+    ///
+    /// `{}`/`{name}` 的参数先转换为字符串再以 `%s` 打印。
+    fn print_format(&mut self, fmt: &str, args: &[Expr], end: &str) -> cayResult<()> {
+        let placeholders = parse_format_string(fmt);
+        if placeholders.len() != args.len() {
+            return Err(codegen_error(format!(
+                "Format string expects {} arguments, but {} provided",
+                placeholders.len(), args.len()
+            )));
+        }
+
+        let mut values = Vec::new();
+        for (arg, placeholder) in args.iter().zip(&placeholders) {
+            let value = self.build_expression(arg)?;
+            let value = match placeholder {
+                Placeholder::CStyle(spec) => self.convert_for_spec(value, spec),
+                Placeholder::Sequential | Placeholder::Named => self.value_to_string(value),
+            };
+            values.push(value);
+        }
+
+        let c_format = format!("{}{}", to_c_format(fmt), end);
+        self.printf(&c_format, values);
+        Ok(())
+    }
+
+    /// 按 C 格式说明符的转换字符转换参数
+    fn convert_for_spec(&mut self, value: IrValue, spec: &str) -> IrValue {
+        let ty = value.ir_type();
+        match spec.chars().last().unwrap_or('s') {
+            'd' | 'i' | 'x' | 'X' | 'o' | 'u' if ty.is_integer() => self.convert(value, &IrType::I64),
+            'f' | 'e' | 'g' | 'E' | 'G' if ty.is_float() || ty.is_integer() => self.convert(value, &IrType::F64),
+            's' => self.value_to_string(value),
+            'c' if ty.is_integer() => self.convert(value, &IrType::I32),
+            _ => value,
+        }
+    }
+
+    fn printf(&mut self, fmt: &str, mut args: Vec<IrValue>) {
+        let fmt_ptr = self.string_ptr(fmt);
+        args.insert(0, fmt_ptr);
+        self.call_varargs("printf", vec![i8_ptr()], args, IrType::I32);
+    }
+
+    // ============================================================
+    // 测试断言
+    // ============================================================
+
+    /// `assertEquals(expected, actual[, message])`：数值按提升后的类型比较，字符串比较内容，其余比较引用
+    fn build_assert_equals(&mut self, args: &[Expr], loc: &SourceLocation) -> cayResult<IrValue> {
+        if args.len() < 2 || args.len() > 3 {
+            return Err(codegen_error("assertEquals() takes 2 or 3 arguments".to_string()));
+        }
+        // `i8*` 也可能是对象引用：静态类型已知且不是 String 时按引用比较
+        let not_string = |builder: &Self, arg: &Expr| {
+            builder.expr_type(arg).is_some_and(|ty| *ty.non_null() != Type::String)
+        };
+        let maybe_strings = !not_string(self, &args[0]) && !not_string(self, &args[1]);
+        let expected = self.build_expression(&args[0])?;
+        let actual = self.build_expression(&args[1])?;
+        let strings = maybe_strings && expected.ir_type() == i8_ptr() && actual.ir_type() == i8_ptr();
+
+        let equal = if strings {
+            self.call("__cay_string_equals", vec![expected.clone(), actual.clone()], IrType::I1).expect("non-void")
+        } else if let Some((l, r, ty)) = self.promote(expected.clone(), actual.clone()) {
+            let op = if ty.is_float() { IrCmpOp::FEq } else { IrCmpOp::Eq };
+            self.compare(op, l, r)
+        } else {
+            let actual = self.convert(actual.clone(), &expected.ir_type());
+            self.compare(IrCmpOp::Eq, expected.clone(), actual)
+        };
+
+        let fail_label = self.ctx.new_label("assert.fail");
+        let ok_label = self.ctx.new_label("assert.ok");
+        self.terminate(IrTerminator::ConditionalBranch {
+            condition: equal,
+            true_target: ok_label.clone(),
+            false_target: fail_label.clone(),
+        });
+        self.start_block(&fail_label);
+        let quote = if strings { "\"" } else { "" };
+        let expected = self.value_to_string(expected);
+        let actual = self.value_to_string(actual);
+        let mut parts = vec![
+            self.string_ptr(&format!("expected {}", quote)),
+            expected,
+            self.string_ptr(&format!("{}, actual {}", quote, quote)),
+            actual,
+        ];
+        if strings {
+            parts.push(self.string_ptr(quote));
+        }
+        let detail = self.concat_strings(parts);
+        let message = self.assertion_message(args.get(2), detail)?;
+        self.panic_with_message(message, loc);
+        self.start_block(&ok_label);
+        Ok(void_value())
+    }
+
+    /// `assertTrue(condition[, message])`
+    fn build_assert_true(&mut self, args: &[Expr], loc: &SourceLocation) -> cayResult<IrValue> {
+        if args.is_empty() || args.len() > 2 {
+            return Err(codegen_error("assertTrue() takes 1 or 2 arguments".to_string()));
+        }
+        let condition = self.build_expression(&args[0])?;
+        let condition = self.as_condition(condition);
+        let fail_label = self.ctx.new_label("assert.fail");
+        let ok_label = self.ctx.new_label("assert.ok");
+        self.terminate(IrTerminator::ConditionalBranch {
+            condition,
+            true_target: ok_label.clone(),
+            false_target: fail_label.clone(),
+        });
+        self.start_block(&fail_label);
+        let detail = self.string_ptr("expected true, actual false");
+        let message = self.assertion_message(args.get(1), detail)?;
+        self.panic_with_message(message, loc);
+        self.start_block(&ok_label);
+        Ok(void_value())
+    }
+
+    /// `fail([message])`：无条件失败
+    fn build_fail(&mut self, args: &[Expr], loc: &SourceLocation) -> cayResult<IrValue> {
+        if args.len() > 1 {
+            return Err(codegen_error("fail() takes at most 1 argument".to_string()));
+        }
+        let message = match args.first() {
+            Some(message) => {
+                let prefix = self.string_ptr("assertion failed: ");
+                let message = self.build_expression(message)?;
+                self.concat_strings(vec![prefix, message])
+            }
+            None => self.string_ptr("assertion failed: fail() called"),
+        };
+        self.panic_with_message(message, loc);
+        Ok(void_value())
+    }
+
+    /// 断言失败描述：`assertion failed: [<message>: ]<detail>`
+    fn assertion_message(&mut self, message: Option<&Expr>, detail: IrValue) -> cayResult<IrValue> {
+        let mut parts = vec![self.string_ptr("assertion failed: ")];
+        if let Some(message) = message {
+            parts.push(self.build_expression(message)?);
+            parts.push(self.string_ptr(": "));
+        }
+        parts.push(detail);
+        Ok(self.concat_strings(parts))
+    }
+
+    /// 依次拼接字符串
+    fn concat_strings(&mut self, parts: Vec<IrValue>) -> IrValue {
+        let mut parts = parts.into_iter();
+        let first = parts.next().expect("at least one string");
+        parts.fold(first, |acc, part| {
+            self.call("__cay_string_concat", vec![acc, part], i8_ptr()).expect("non-void")
+        })
+    }
+
+    // ============================================================
+    // read*
+    // ============================================================
+
+    /// 通过 scanf 读取一个值
+    fn build_scanf(&mut self, name: &str, args: &[Expr], fmt: &str, ty: IrType) -> cayResult<IrValue> {
+        if !args.is_empty() {
+            return Err(codegen_error(format!("{}() takes no arguments", name)));
+        }
+        let slot_name = format!("{}.buf{}", name, self.ctx.temp_counter);
+        let slot = self.alloca(&slot_name, ty.clone());
+        let fmt_ptr = self.string_ptr(fmt);
+        self.call_varargs("scanf", vec![i8_ptr()], vec![fmt_ptr, slot.clone()], IrType::I32);
+        Ok(self.load(slot, ty))
+    }
+
+    /// 通过 fgets 从 stdin 读取一行（缓冲区在堆上分配，返回后仍然有效）
+    fn build_read_line(&mut self, args: &[Expr]) -> cayResult<IrValue> {
+        if !args.is_empty() {
+            return Err(codegen_error("readLine() takes no arguments".to_string()));
+        }
+        const BUFFER_SIZE: i64 = 1024;
+        let buffer = self.call("calloc", vec![
+            IrValue::IntConst(1, IrType::I64),
+            IrValue::IntConst(BUFFER_SIZE, IrType::I64),
+        ], i8_ptr()).expect("non-void");
+        let stdin = if self.ctx.is_windows_target() {
+            self.call("__acrt_iob_func", vec![IrValue::IntConst(0, IrType::I32)], i8_ptr()).expect("non-void")
+        } else {
+            self.load(IrValue::GlobalRef("@stdin".to_string(), IrType::Pointer(Box::new(i8_ptr()))), i8_ptr())
+        };
+        self.call("fgets", vec![buffer.clone(), IrValue::IntConst(BUFFER_SIZE, IrType::I32), stdin], i8_ptr());
+        Ok(buffer)
+    }
+
+    // ============================================================
+    // String 方法与静态方法
+    // ============================================================
+
+    /// `str.method(args)`：对象不是 String 或方法未知时返回 `None`
+    pub(super) fn try_build_string_method(&mut self, member: &MemberAccessExpr, args: &[Expr]) -> cayResult<Option<IrValue>> {
+        const METHODS: &[&str] = &[
+            "length", "substring", "indexOf", "lastIndexOf", "charAt", "replace",
+            "isEmpty", "equals", "c_str", "startsWith", "endsWith",
+        ];
+        if !matches!(self.expr_type(&member.object), Some(Type::String)) || !METHODS.contains(&member.member.as_str()) {
+            return Ok(None);
+        }
+        let object = self.build_expression(&member.object)?;
+        let method = member.member.as_str();
+
+        let value = match method {
+            "length" | "isEmpty" | "c_str" => {
+                if !args.is_empty() {
+                    return Err(codegen_error(format!("String.{}() takes no arguments", method)));
+                }
+                match method {
+                    "length" => self.call("__cay_string_length", vec![object], IrType::I32),
+                    "isEmpty" => self.call("__cay_string_isempty", vec![object], IrType::I1),
+                    _ => Some(object),
+                }
+            }
+            "substring" => {
+                if args.is_empty() || args.len() > 2 {
+                    return Err(codegen_error("String.substring() takes 1 or 2 arguments".to_string()));
+                }
+                let begin = self.build_expression(&args[0])?;
+                let begin = self.convert(begin, &IrType::I32);
+                let end = match args.get(1) {
+                    Some(end) => {
+                        let end = self.build_expression(end)?;
+                        self.convert(end, &IrType::I32)
+                    }
+                    None => self.call("__cay_string_length", vec![object.clone()], IrType::I32).expect("non-void"),
+                };
+                self.call("__cay_string_substring", vec![object, begin, end], i8_ptr())
+            }
+            "charAt" => {
+                if args.len() != 1 {
+                    return Err(codegen_error("String.charAt() takes 1 argument".to_string()));
+                }
+                let index = self.build_expression(&args[0])?;
+                let index = self.convert(index, &IrType::I32);
+                self.call("__cay_string_charat", vec![object, index], IrType::I8)
+            }
+            "replace" => {
+                if args.len() != 2 {
+                    return Err(codegen_error("String.replace() takes 2 arguments".to_string()));
+                }
+                let old = self.build_expression(&args[0])?;
+                let new = self.build_expression(&args[1])?;
+                if old.ir_type() != i8_ptr() || new.ir_type() != i8_ptr() {
+                    return Err(codegen_error("String.replace() arguments must be strings".to_string()));
+                }
+                self.call("__cay_string_replace", vec![object, old, new], i8_ptr())
+            }
+            _ => {
+                // indexOf / lastIndexOf / equals / startsWith / endsWith：一个字符串参数
+                if args.len() != 1 {
+                    return Err(codegen_error(format!("String.{}() takes 1 argument", method)));
+                }
+                let other = self.build_expression(&args[0])?;
+                if other.ir_type() != i8_ptr() {
+                    return Err(codegen_error(format!("String.{}() argument must be a string", method)));
+                }
+                let (func, ret) = match method {
+                    "indexOf" => ("__cay_string_indexof", IrType::I32),
+                    "lastIndexOf" => ("__cay_string_lastindexof", IrType::I32),
+                    "equals" => ("__cay_string_equals", IrType::I1),
+                    "startsWith" => ("__cay_string_startswith", IrType::I1),
+                    _ => ("__cay_string_endswith", IrType::I1),
+                };
+                self.call(func, vec![object, other], ret)
+            }
+        };
+        Ok(value)
+    }
+
+    /// `String.valueOf(x)`
+    pub(super) fn build_string_value_of(&mut self, args: &[Expr]) -> cayResult<IrValue> {
+        if args.len() != 1 {
+            return Err(codegen_error("String.valueOf() takes exactly 1 argument".to_string()));
+        }
+        let value = self.build_expression(&args[0])?;
+        let ty = value.ir_type();
+        if !(ty.is_integer() || ty.is_float() || ty == i8_ptr()) {
+            return Err(codegen_error(format!("String.valueOf() does not support type: {}", ty)));
+        }
+        Ok(self.value_to_string(value))
+    }
+
+    /// `Integer.parseInt(s)`
+    pub(super) fn build_parse_int(&mut self, args: &[Expr]) -> cayResult<IrValue> {
+        if args.len() != 1 {
+            return Err(codegen_error("Integer.parseInt() takes exactly 1 argument".to_string()));
+        }
+        let value = self.build_expression(&args[0])?;
+        if value.ir_type() != i8_ptr() {
+            return Err(codegen_error(format!("Integer.parseInt() expects String, got {}", value.ir_type())));
+        }
+        Ok(self.call("atoi", vec![value], IrType::I32).expect("non-void"))
+    }
+
+    /// `Math.*Exact`：参数中有 long 时按 long 计算，总是检查溢出
+    pub(super) fn build_math_exact(&mut self, op: ExactOp, call: &CallExpr) -> cayResult<IrValue> {
+        if call.args.len() != op.arity() {
+            return Err(codegen_error(format!("Math.{}() takes exactly {} argument(s)", op.method_name(), op.arity())));
+        }
+        let mut values = Vec::new();
+        for arg in &call.args {
+            let value = self.build_expression(arg)?;
+            if !value.ir_type().is_integer() {
+                return Err(codegen_error(format!("Math.{}() expects int or long, got {}", op.method_name(), value.ir_type())));
+            }
+            values.push(value);
+        }
+        let ty = if values.iter().any(|v| v.ir_type() == IrType::I64) { IrType::I64 } else { IrType::I32 };
+        let values: Vec<IrValue> = values.into_iter().map(|v| self.convert(v, &ty)).collect();
+        let zero = IrValue::IntConst(0, ty.clone());
+        let one = IrValue::IntConst(1, ty);
+        let (bin_op, left, right) = match op {
+            ExactOp::Add => (IrBinaryOp::Add, values[0].clone(), values[1].clone()),
+            ExactOp::Subtract => (IrBinaryOp::Sub, values[0].clone(), values[1].clone()),
+            ExactOp::Multiply => (IrBinaryOp::Mul, values[0].clone(), values[1].clone()),
+            ExactOp::Negate => (IrBinaryOp::Sub, zero, values[0].clone()),
+            ExactOp::Increment => (IrBinaryOp::Add, values[0].clone(), one),
+            ExactOp::Decrement => (IrBinaryOp::Sub, values[0].clone(), one),
+        };
+        Ok(self.checked_int_op(bin_op, left, right, &call.loc))
+    }
+}
+
+/// 解析格式字符串中的占位符
+fn parse_format_string(fmt: &str) -> Vec<Placeholder> {
+    let mut placeholders = Vec::new();
+    let mut chars = fmt.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek().copied()) {
+            ('%', Some('%')) => {
+                chars.next();
+            }
+            ('%', Some(_)) => {
+                let mut spec = String::from("%");
+                for ch in chars.by_ref() {
+                    spec.push(ch);
+                    if ch.is_ascii_alphabetic() || ch == '*' {
+                        break;
+                    }
+                }
+                placeholders.push(Placeholder::CStyle(spec));
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                placeholders.push(Placeholder::Sequential);
+            }
+            ('{', Some('{')) => {
+                chars.next();
+            }
+            ('{', Some(next)) if next.is_ascii_alphabetic() || next == '_' => {
+                let mut lookahead = chars.clone();
+                let mut name_len = 0;
+                while lookahead.peek().is_some_and(|ch| ch.is_ascii_alphanumeric() || *ch == '_') {
+                    lookahead.next();
+                    name_len += 1;
+                }
+                if lookahead.peek() == Some(&'}') {
+                    for _ in 0..=name_len {
+                        chars.next();
+                    }
+                    placeholders.push(Placeholder::Named);
+                }
+            }
+            _ => {}
+        }
+    }
+    placeholders
+}
+
+/// 把 `{}`/`{name}` 改写为 `%s`，`{{` 改写为 `{`，C 风格说明符保持原样
+fn to_c_format(fmt: &str) -> String {
+    let mut result = String::new();
+    let mut chars = fmt.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek().copied()) {
+            ('%', Some(_)) => {
+                result.push(c);
+                if let Some(&'%') = chars.peek() {
+                    result.push(chars.next().unwrap_or('%'));
+                    continue;
+                }
+                for ch in chars.by_ref() {
+                    result.push(ch);
+                    if ch.is_ascii_alphabetic() || ch == '*' {
+                        break;
+                    }
+                }
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                result.push_str("%s");
+            }
+            ('{', Some('{')) => {
+                chars.next();
+                result.push('{');
+            }
+            ('{', Some(next)) if next.is_ascii_alphabetic() || next == '_' => {
+                let mut lookahead = chars.clone();
+                let mut name = String::new();
+                while let Some(&ch) = lookahead.peek().filter(|ch| ch.is_ascii_alphanumeric() || **ch == '_') {
+                    name.push(ch);
+                    lookahead.next();
+                }
+                if lookahead.peek() == Some(&'}') {
+                    lookahead.next();
+                    chars = lookahead;
+                    result.push_str("%s");
+                } else {
+                    result.push(c);
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
@@ -0,0 +1,390 @@
+//! 调用构建：方法调用（重载与可变参数）、接口分派、extern 函数和函数指针调用
+
+use super::{i8_ptr, void_value, IrBuilder};
+use crate::ast::*;
+use crate::error::{cayResult, codegen_error};
+use crate::ir::types::IrType;
+use crate::ir::value::{IrInstruction, IrValue};
+use crate::types::{FunctionType, MethodInfo, ParameterInfo, Type};
+
+/// 方法调用的接收者
+enum Receiver<'a> {
+    /// 无显式接收者（当前类的方法或顶层函数）
+    Implicit,
+    /// `super.m()`：调用父类方法，传递当前 this
+    Super,
+    /// `Class.m()`：静态调用，不传递 this
+    Static,
+    /// `obj.m()`
+    Object(&'a Expr),
+}
+
+impl IrBuilder {
+    pub(super) fn build_call(&mut self, call: &CallExpr) -> cayResult<IrValue> {
+        match call.callee.as_ref() {
+            Expr::Identifier(ident) => self.build_named_call(ident.as_str(), call),
+            Expr::MemberAccess(member) => self.build_member_call(member, call),
+            _ => Err(codegen_error("Invalid function call".to_string())),
+        }
+    }
+
+    /// `name(args)`：内置函数、extern 函数、函数指针变量、顶层函数或当前类的方法
+    fn build_named_call(&mut self, name: &str, call: &CallExpr) -> cayResult<IrValue> {
+        if let Some(value) = self.try_build_builtin(name, &call.args, &call.loc)? {
+            return Ok(value);
+        }
+        if self.ctx.is_extern_function(name) {
+            return self.build_extern_call(name, &call.args);
+        }
+        let local_type = self.ctx.var_cay_types.get(name).cloned().filter(|_| self.is_local(name));
+        if let Some(Type::Function(ft)) = local_type {
+            let (slot, ty) = self.build_lvalue(&Expr::Identifier(IdentifierExpr {
+                name: name.to_string(),
+                loc: call.loc.clone(),
+            }))?;
+            let callee = self.load(slot, ty);
+            return self.build_function_pointer_call(callee, &ft, &call.args);
+        }
+        if self.ctx.is_top_level_function(name) {
+            return self.build_top_level_call(name, &call.args);
+        }
+        let class_name = self.ctx.current_class.clone();
+        if let Some(Type::Function(ft)) = self.field_type(&class_name, name) {
+            let (ptr, ty) = self.this_field_pointer(&class_name, name);
+            let callee = self.load(ptr, ty);
+            return self.build_function_pointer_call(callee, &ft, &call.args);
+        }
+        self.build_method_call(&class_name, name, Receiver::Implicit, call)
+    }
+
+    /// `obj.m(args)`：String 方法、数组 length()、静态内建函数、函数指针字段或方法调用
+    fn build_member_call(&mut self, member: &MemberAccessExpr, call: &CallExpr) -> cayResult<IrValue> {
+        if let Some(value) = self.try_build_string_method(member, &call.args)? {
+            return Ok(value);
+        }
+        if member.member == "length" && call.args.is_empty() && matches!(self.expr_type(&member.object), Some(Type::Array(_))) {
+            let array = self.build_expression(&member.object)?;
+            return Ok(self.array_length(array));
+        }
+        if let Expr::Identifier(ident) = member.object.as_ref() {
+            let object_name = ident.as_str();
+            if !self.is_local(object_name) {
+                match (object_name, member.member.as_str()) {
+                    ("String", "valueOf") => return self.build_string_value_of(&call.args),
+                    ("Integer", "parseInt") => return self.build_parse_int(&call.args),
+                    _ => {}
+                }
+                let exact_op = self.ctx.type_registry.as_ref()
+                    .and_then(|r| r.math_exact_intrinsic(object_name, &member.member));
+                if let Some(op) = exact_op {
+                    return self.build_math_exact(op, call);
+                }
+            }
+            match object_name {
+                "super" => {
+                    let current = self.ctx.current_class.clone();
+                    let parent = self.ctx.get_parent_class(&current).unwrap_or(current);
+                    return self.build_method_call(&parent, &member.member, Receiver::Super, call);
+                }
+                "this" => {}
+                _ => {
+                    if let Some(class_name) = self.names_class(&member.object) {
+                        return self.build_method_call(&class_name, &member.member, Receiver::Static, call);
+                    }
+                }
+            }
+        }
+
+        let class_name = match (self.object_class(&member.object), member.object.as_ref()) {
+            (Some(class_name), _) => class_name,
+            (None, Expr::Identifier(ident)) => ident.name.clone(),
+            (None, _) => {
+                return Err(codegen_error(match self.expr_type(&member.object) {
+                    Some(_) => format!("Cannot call method '{}' on non-class type", member.member),
+                    None => format!("Cannot determine type for method call '{}'", member.member),
+                }));
+            }
+        };
+
+        if let Some(Type::Function(ft)) = self.field_type(&class_name, &member.member) {
+            let object = self.build_receiver(&member.object, &member.loc)?;
+            let field = self.ctx.get_instance_field(&class_name, &member.member).cloned().ok_or_else(|| {
+                codegen_error(format!("Field '{}' not found in class '{}'", member.member, class_name))
+            })?;
+            let (ptr, ty) = self.field_address(object, field.offset, &field.llvm_type);
+            let callee = self.load(ptr, ty);
+            return self.build_function_pointer_call(callee, &ft, &call.args);
+        }
+
+        self.build_method_call(&class_name, &member.member, Receiver::Object(&member.object), call)
+    }
+
+    /// 类（不含父类）中声明的字段类型
+    fn field_type(&self, class_name: &str, field: &str) -> Option<Type> {
+        self.ctx.type_registry.as_ref()?
+            .get_class(class_name)?
+            .fields.get(field)
+            .map(|f| f.field_type.clone())
+    }
+
+    // ============================================================
+    // 方法调用
+    // ============================================================
+
+    /// 方法调用：接口分派或按实参选择重载后直接调用
+    fn build_method_call(&mut self, class_name: &str, method_name: &str, receiver: Receiver, call: &CallExpr) -> cayResult<IrValue> {
+        let dispatch = match receiver {
+            Receiver::Static => None,
+            _ => self.ctx.find_interface_dispatch(class_name, method_name, call.args.len()),
+        };
+        if let Some(method) = dispatch {
+            return self.build_interface_call(class_name, &method, receiver, call);
+        }
+
+        // 先求值接收者，再求值参数
+        let this = match &receiver {
+            Receiver::Static => None,
+            Receiver::Implicit | Receiver::Super => Some(self.load_this()),
+            Receiver::Object(object) => Some(self.build_receiver(object, &call.loc)?),
+        };
+        let mut args = Vec::with_capacity(call.args.len());
+        for arg in &call.args {
+            args.push(self.build_expression(arg)?);
+        }
+
+        let selected = self.select_method(class_name, method_name, &args);
+        let passes_this = match &selected {
+            Some((_, method)) => !method.is_static,
+            None => self.has_instance_overload(class_name, method_name),
+        };
+        let mut final_args = Vec::new();
+        if passes_this && !matches!(receiver, Receiver::Static) {
+            final_args.push(this.unwrap_or_else(|| IrValue::NullConst(i8_ptr())));
+        }
+
+        let (fn_name, return_ty) = match selected {
+            Some((found_class, method)) => {
+                final_args.extend(self.pack_method_args(&method.params, args));
+                let fn_name = self.ctx.generate_method_name_from_params(&found_class, method_name, &method.params);
+                (fn_name, self.ir_type(&method.return_type))
+            }
+            None => {
+                let sigs: Vec<String> = args.iter()
+                    .map(|a| self.ctx.llvm_type_to_signature(&a.ir_type().to_llvm_str()))
+                    .collect();
+                let fn_name = if class_name.is_empty() {
+                    format!("__toplevel_{}", method_name)
+                } else if sigs.is_empty() {
+                    format!("{}.{}", class_name, method_name)
+                } else {
+                    format!("{}.__{}_{}", class_name, method_name, sigs.join("_"))
+                };
+                final_args.extend(args);
+                (fn_name, IrType::I64)
+            }
+        };
+        Ok(self.call(&fn_name, final_args, return_ty).unwrap_or_else(void_value))
+    }
+
+    /// 在类及其父类中选择重载：优先固定参数类型完全匹配，其次参数数量匹配
+    fn select_method(&self, class_name: &str, method_name: &str, args: &[IrValue]) -> Option<(String, MethodInfo)> {
+        let registry = self.ctx.type_registry.as_ref()?;
+        let mut current = Some(class_name.to_string());
+        while let Some(class) = current {
+            let class_info = registry.get_class(&class)?;
+            if let Some(methods) = class_info.methods.get(method_name) {
+                let candidates: Vec<&MethodInfo> = methods.iter()
+                    .filter(|m| arity_matches(&m.params, args.len()))
+                    .collect();
+                let exact = candidates.iter().find(|m| {
+                    m.params.iter()
+                        .filter(|p| !p.is_varargs)
+                        .zip(args)
+                        .all(|(p, a)| self.ir_type(&p.param_type) == a.ir_type())
+                });
+                if let Some(method) = exact.or(candidates.first()) {
+                    return Some((class, (*method).clone()));
+                }
+            }
+            current = class_info.parent.clone();
+        }
+        None
+    }
+
+    /// 类或其父类是否有同名实例方法
+    fn has_instance_overload(&self, class_name: &str, method_name: &str) -> bool {
+        let Some(registry) = self.ctx.type_registry.as_ref() else { return false };
+        let mut current = registry.get_class(class_name);
+        while let Some(class_info) = current {
+            if class_info.methods.get(method_name).is_some_and(|ms| ms.iter().any(|m| !m.is_static)) {
+                return true;
+            }
+            current = class_info.parent.as_deref().and_then(|p| registry.get_class(p));
+        }
+        false
+    }
+
+    /// 按形参转换实参；可变参数方法把多余的实参打包成数组
+    fn pack_method_args(&mut self, params: &[ParameterInfo], args: Vec<IrValue>) -> Vec<IrValue> {
+        let is_varargs = params.last().is_some_and(|p| p.is_varargs);
+        let fixed_count = if is_varargs { params.len() - 1 } else { params.len() };
+        let mut args = args;
+        let varargs = if is_varargs { args.split_off(fixed_count.min(args.len())) } else { Vec::new() };
+
+        let mut result = Vec::with_capacity(params.len());
+        for (param, arg) in params.iter().zip(args.iter().cloned()) {
+            let ty = self.ir_type(&param.param_type);
+            result.push(self.convert(arg, &ty));
+        }
+        result.extend(args.into_iter().skip(params.len()));
+
+        if is_varargs {
+            let elem_type = match &params[fixed_count].param_type {
+                Type::Array(elem) => elem.as_ref().clone(),
+                other => other.clone(),
+            };
+            let packed = self.pack_varargs(&elem_type, varargs);
+            result.push(packed);
+        }
+        result
+    }
+
+    /// 把可变参数打包为带长度头的数组；单个数组实参直接传递
+    fn pack_varargs(&mut self, elem_type: &Type, varargs: Vec<IrValue>) -> IrValue {
+        if let [single] = varargs.as_slice() {
+            let ty = single.ir_type();
+            if ty.is_pointer() && ty != i8_ptr() {
+                return self.bitcast(single.clone(), i8_ptr());
+            }
+        }
+
+        let elem_ty = self.ir_type(elem_type);
+        let elem_size = elem_ty.size_bytes(&self.ctx.target).max(1);
+        let total = 8 + varargs.len() * elem_size;
+        let raw = self.call("calloc", vec![
+            IrValue::IntConst(1, IrType::I64),
+            IrValue::IntConst(total as i64, IrType::I64),
+        ], i8_ptr()).expect("calloc returns a pointer");
+        let len_ptr = self.bitcast(raw.clone(), IrType::Pointer(Box::new(IrType::I32)));
+        self.store(IrValue::IntConst(varargs.len() as i64, IrType::I32), len_ptr);
+        let data = self.byte_offset(raw, 8);
+        for (i, value) in varargs.into_iter().enumerate() {
+            let slot = self.byte_offset(data.clone(), (i * elem_size) as i64);
+            let slot = self.bitcast(slot, IrType::Pointer(Box::new(elem_ty.clone())));
+            let value = self.convert(value, &elem_ty);
+            self.store(value, slot);
+        }
+        data
+    }
+
+    /// 通过接口分派函数调用（接收者作为第一个参数）；能确定唯一实现时直接调用实现函数
+    fn build_interface_call(&mut self, class_name: &str, method: &MethodInfo, receiver: Receiver, call: &CallExpr) -> cayResult<IrValue> {
+        let exact_class = match &receiver {
+            Receiver::Object(Expr::New(new)) => Some(new.class_name.as_str()),
+            _ => None,
+        };
+        let direct = self.ctx.devirtualize_interface_call(class_name, exact_class, method, &call.loc);
+
+        let this = match receiver {
+            Receiver::Object(object) => self.build_receiver(object, &call.loc)?,
+            _ => self.load_this(),
+        };
+        // 静态方法实现不接收 this（与分派函数一致）
+        let mut final_args = Vec::new();
+        if !direct.as_ref().is_some_and(|(_, is_static)| *is_static) {
+            final_args.push(this);
+        }
+        for (arg, param) in call.args.iter().zip(&method.params) {
+            let value = self.build_expression(arg)?;
+            let ty = if param.is_varargs { i8_ptr() } else { self.ir_type(&param.param_type) };
+            final_args.push(self.convert(value, &ty));
+        }
+        let fn_name = match direct {
+            Some((function, _)) => function,
+            None => self.ctx.generate_method_name_from_params(&method.class_name, &method.name, &method.params),
+        };
+        let return_ty = self.ir_type(&method.return_type);
+        Ok(self.call(&fn_name, final_args, return_ty).unwrap_or_else(void_value))
+    }
+
+    // ============================================================
+    // 顶层函数、extern 函数和函数指针
+    // ============================================================
+
+    fn build_top_level_call(&mut self, name: &str, args: &[Expr]) -> cayResult<IrValue> {
+        let Type::Function(ft) = self.ctx.get_top_level_function_type(name) else {
+            return Err(codegen_error(format!("Function '{}' not found", name)));
+        };
+        let values = self.build_args(args, &ft.params)?;
+        let fn_name = self.ctx.generate_top_level_function_name(name);
+        let return_ty = self.ir_type(&ft.return_type);
+        Ok(self.call(&fn_name, values, return_ty).unwrap_or_else(void_value))
+    }
+
+    /// 求值实参并转换为形参类型（多出的实参原样传递）
+    fn build_args(&mut self, args: &[Expr], params: &[Type]) -> cayResult<Vec<IrValue>> {
+        let mut values = Vec::with_capacity(args.len());
+        for (i, arg) in args.iter().enumerate() {
+            let value = match params.get(i) {
+                Some(param) => {
+                    let value = self.build_expression_with_type(arg, param)?;
+                    let ty = self.ir_type(param);
+                    self.convert(value, &ty)
+                }
+                None => self.build_expression(arg)?,
+            };
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    /// extern 函数调用，使用实际的 C 函数名（而非别名）
+    fn build_extern_call(&mut self, name: &str, args: &[Expr]) -> cayResult<IrValue> {
+        let extern_func = self.ctx.get_extern_function(name).cloned()
+            .ok_or_else(|| codegen_error(format!("Extern function '{}' not found", name)))?;
+        let fixed: Vec<Type> = extern_func.params.iter()
+            .filter(|p| !p.is_varargs)
+            .map(|p| p.param_type.clone())
+            .collect();
+        let values = self.build_args(args, &fixed)?;
+        let return_ty = self.ir_type(&extern_func.return_type);
+        let result = if extern_func.params.iter().any(|p| p.is_varargs) {
+            let fixed_tys = fixed.iter().map(|t| self.ir_type(t)).collect();
+            self.call_varargs(&extern_func.name, fixed_tys, values, return_ty)
+        } else {
+            self.call(&extern_func.name, values, return_ty)
+        };
+        Ok(result.unwrap_or_else(void_value))
+    }
+
+    /// 通过 `i8*` 形式保存的函数指针调用
+    fn build_function_pointer_call(&mut self, callee: IrValue, ft: &FunctionType, args: &[Expr]) -> cayResult<IrValue> {
+        if args.len() != ft.params.len() {
+            return Err(codegen_error(format!(
+                "Function pointer call requires {} arguments, but got {}",
+                ft.params.len(), args.len()
+            )));
+        }
+        let values = self.build_args(args, &ft.params)?;
+        let callee = self.bitcast(callee, self.ir_signature(ft));
+        let return_ty = self.ir_type(&ft.return_type);
+        let result = (return_ty != IrType::Void).then(|| self.temp(return_ty.clone()));
+        self.emit(IrInstruction::CallIndirect {
+            result: result.clone(),
+            callee,
+            return_ty,
+            param_tys: ft.params.iter().map(|p| self.ir_type(p)).collect(),
+            is_varargs: false,
+            args: values,
+        });
+        Ok(result.unwrap_or_else(void_value))
+    }
+}
+
+/// 实参数量是否满足方法形参（可变参数方法只要求固定参数）
+fn arity_matches(params: &[ParameterInfo], arg_count: usize) -> bool {
+    match params.last() {
+        Some(last) if last.is_varargs => arg_count >= params.len() - 1,
+        _ => params.len() == arg_count,
+    }
+}
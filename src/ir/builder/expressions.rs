@@ -0,0 +1,447 @@
+//! 表达式构建：字面量、标识符、成员访问、赋值和三元运算符
+//!
+//! 运算符、调用、对象、数组和 lambda 分别在同级模块中构建。
+
+use super::{i8_ptr, IrBuilder};
+use crate::ast::*;
+use crate::error::{cayResult, codegen_error, SourceLocation};
+use crate::ir::types::IrType;
+use crate::ir::value::{IrInstruction, IrTerminator, IrValue};
+use crate::types::{FunctionType, Type};
+
+impl IrBuilder {
+    /// 构建表达式，返回其值（void 表达式返回 `void_value()`）
+    pub(super) fn build_expression(&mut self, expr: &Expr) -> cayResult<IrValue> {
+        match expr {
+            Expr::Literal(lit) => Ok(self.build_literal(lit)),
+            Expr::Identifier(ident) => self.build_identifier(&ident.name),
+            Expr::Binary(bin) => self.build_binary(bin),
+            Expr::Unary(unary) => self.build_unary(unary),
+            Expr::Call(call) => self.build_call(call),
+            Expr::MemberAccess(member) => self.build_member_access(member),
+            Expr::New(new_expr) => self.build_new(new_expr),
+            Expr::Assignment(assign) => self.build_assignment(assign),
+            Expr::Cast(cast) => self.build_cast(cast),
+            Expr::ArrayCreation(arr) => self.build_array_creation(arr),
+            Expr::ArrayAccess(arr) => self.build_array_access(arr),
+            Expr::ArrayInit(init) => self.build_array_init(init),
+            Expr::MethodRef(method_ref) => Ok(self.build_method_ref(method_ref)),
+            Expr::Lambda(lambda) => self.build_lambda(lambda),
+            Expr::Ternary(ternary) => self.build_ternary(ternary),
+            Expr::InstanceOf(instance_of) => self.build_instanceof(instance_of),
+            Expr::SizeOf(size_of) => {
+                let size = self.ctx.resolve_type(&size_of.target_type).size_in_bytes(&self.ctx.target);
+                Ok(IrValue::IntConst(size as i64, IrType::I64))
+            }
+            Expr::Alloc(alloc) => self.build_alloc(alloc),
+            Expr::Dealloc(dealloc) => self.build_dealloc(dealloc),
+        }
+    }
+
+    /// 按目标类型构建表达式：数组初始化器使用目标元素类型，lambda 使用目标函数类型的签名
+    pub(super) fn build_expression_with_type(&mut self, expr: &Expr, target: &Type) -> cayResult<IrValue> {
+        match (expr, target) {
+            (Expr::ArrayInit(init), _) => self.build_array_init_with_type(init, target),
+            (Expr::Lambda(lambda), Type::Function(ft)) => self.build_lambda_with_signature(lambda, Some(ft.as_ref())),
+            _ => self.build_expression(expr),
+        }
+    }
+
+    fn build_literal(&mut self, lit: &LiteralValue) -> IrValue {
+        match lit {
+            LiteralValue::Int32(v) => IrValue::IntConst(*v as i64, IrType::I32),
+            LiteralValue::Int64(v) => IrValue::IntConst(*v, IrType::I64),
+            // float 字面量按 double 参与运算，赋值或传参时再截断（与旧代码生成器一致）
+            LiteralValue::Float32(v) => IrValue::FloatConst(v.to_string().parse().unwrap_or(*v as f64), IrType::F64),
+            LiteralValue::Float64(v) => IrValue::FloatConst(*v, IrType::F64),
+            LiteralValue::Bool(v) => IrValue::IntConst(*v as i64, IrType::I1),
+            LiteralValue::String(s) => self.string_ptr(s),
+            LiteralValue::Char(c) => IrValue::IntConst(*c as u8 as i8 as i64, IrType::I8),
+            LiteralValue::Null => IrValue::NullConst(i8_ptr()),
+        }
+    }
+
+    // ============================================================
+    // 标识符与左值
+    // ============================================================
+
+    /// 名称是否是当前作用域中的局部变量或参数
+    pub(super) fn is_local(&self, name: &str) -> bool {
+        self.ctx.scope_manager.get_var_type(name).is_some()
+    }
+
+    /// 局部变量的槽位和值类型
+    fn local_slot(&self, name: &str) -> Option<(IrValue, IrType)> {
+        let var_type = self.ctx.scope_manager.get_var_type(name)?;
+        let llvm_name = self.ctx.scope_manager.get_llvm_name(name)?;
+        let ty = IrType::from_llvm_str(&var_type);
+        Some((IrValue::Register(format!("%{}", llvm_name), IrType::Pointer(Box::new(ty.clone()))), ty))
+    }
+
+    /// 查找类（含父类）的静态字段
+    pub(super) fn static_field(&self, class_name: &str, field: &str) -> Option<(IrValue, IrType, Type)> {
+        let mut current = Some(class_name.to_string());
+        while let Some(class) = current {
+            if let Some(info) = self.ctx.static_field_map.get(&format!("{}.{}", class, field)) {
+                let ty = IrType::from_llvm_str(&info.llvm_type);
+                let global = IrValue::GlobalRef(info.name.clone(), IrType::Pointer(Box::new(ty.clone())));
+                return Some((global, ty, info.field_type.clone()));
+            }
+            current = self.ctx.get_parent_class(&class);
+        }
+        None
+    }
+
+    /// 构建标识符：局部变量优先，其次是顶层函数、当前类的静态字段和隐式 this 字段
+    fn build_identifier(&mut self, name: &str) -> cayResult<IrValue> {
+        if name == "super" {
+            return Ok(self.load_this());
+        }
+        if let Some((slot, ty)) = self.local_slot(name) {
+            return Ok(self.load(slot, ty));
+        }
+        if self.ctx.is_top_level_function(name) {
+            // 顶层函数作为函数指针使用
+            let fn_ty = self.ctx.get_top_level_function_type(name);
+            let fn_name = self.ctx.generate_top_level_function_name(name);
+            let callee = IrValue::GlobalRef(format!("@{}", fn_name), self.ir_function_type(&fn_ty));
+            return Ok(self.bitcast(callee, i8_ptr()));
+        }
+        if !self.ctx.current_class.is_empty() {
+            let current_class = self.ctx.current_class.clone();
+            if let Some((global, ty, _)) = self.static_field(&current_class, name) {
+                return Ok(self.load(global, ty));
+            }
+            if self.ctx.get_instance_field(&current_class, name).is_some() {
+                let (ptr, ty) = self.this_field_pointer(&current_class, name);
+                return Ok(self.load(ptr, ty));
+            }
+        }
+        if self.ctx.type_registry.as_ref().is_some_and(|r| r.class_exists(name)) {
+            // 类名只在成员访问中有意义
+            return Ok(IrValue::NullConst(i8_ptr()));
+        }
+        Err(codegen_error(format!("Variable '{}' not found", name)))
+    }
+
+    /// 函数类型对应的 IR 函数指针类型（非函数类型返回 `i8*`）
+    pub(super) fn ir_function_type(&self, ty: &Type) -> IrType {
+        match ty {
+            Type::Function(ft) => self.ir_signature(ft),
+            _ => i8_ptr(),
+        }
+    }
+
+    /// `ret (params)*` 形式的函数指针类型
+    pub(super) fn ir_signature(&self, ft: &FunctionType) -> IrType {
+        IrType::Function {
+            params: ft.params.iter().map(|p| self.ir_type(p)).collect(),
+            return_type: Box::new(self.ir_type(&ft.return_type)),
+        }
+    }
+
+    /// 当前对象字段的地址（this + 偏移）
+    pub(super) fn this_field_pointer(&mut self, class_name: &str, field: &str) -> (IrValue, IrType) {
+        let info = self.ctx.get_instance_field(class_name, field).cloned().expect("field exists");
+        let this = self.load_this();
+        self.field_address(this, info.offset, &info.llvm_type)
+    }
+
+    /// 对象中偏移处字段的类型化地址
+    pub(super) fn field_address(&mut self, object: IrValue, offset: usize, llvm_type: &str) -> (IrValue, IrType) {
+        let ty = IrType::from_llvm_str(llvm_type);
+        let addr = self.byte_offset(object, offset as i64);
+        let ptr = self.bitcast(addr, IrType::Pointer(Box::new(ty.clone())));
+        (ptr, ty)
+    }
+
+    /// 表达式的静态 Cavvy 类型（比 `get_expression_type` 多识别隐式 this 字段和静态字段）
+    pub(super) fn expr_type(&self, expr: &Expr) -> Option<Type> {
+        if let Expr::Identifier(ident) = expr {
+            let name = ident.as_str();
+            if let Some(ty) = self.ctx.var_cay_types.get(name).filter(|_| self.is_local(name)) {
+                return Some(ty.clone());
+            }
+            if name == "this" || name == "super" {
+                return Some(Type::Object(self.ctx.current_class.clone()));
+            }
+            if !self.is_local(name) && !self.ctx.current_class.is_empty() {
+                let class = &self.ctx.current_class;
+                if let Some((_, _, ty)) = self.static_field(class, name) {
+                    return Some(ty);
+                }
+                if let Some(field) = self.ctx.get_instance_field(class, name) {
+                    return Some(field.field_type.clone());
+                }
+            }
+        }
+        if let Expr::Cast(cast) = expr {
+            return Some(cast.target_type.clone());
+        }
+        self.ctx.get_expression_type(expr)
+    }
+
+    /// 对象表达式的类名（类名标识符不算对象）
+    pub(super) fn object_class(&self, expr: &Expr) -> Option<String> {
+        if let Expr::Identifier(ident) = expr {
+            let name = ident.as_str();
+            if name == "this" || name == "super" {
+                return (!self.ctx.current_class.is_empty()).then(|| self.ctx.current_class.clone());
+            }
+            if self.is_local(name) {
+                return match self.ctx.var_cay_types.get(name) {
+                    Some(Type::Object(class)) => Some(class.clone()),
+                    Some(_) => None,
+                    None => self.ctx.var_class_map.get(name).cloned(),
+                };
+            }
+        }
+        match self.expr_type(expr) {
+            Some(Type::Object(class)) => Some(class),
+            Some(Type::Nullable(inner)) => match *inner {
+                Type::Object(class) => Some(class),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// 标识符是否指向类名（而不是同名变量）
+    pub(super) fn names_class(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Identifier(ident)
+                if !self.is_local(ident.as_str())
+                    && ident.as_str() != "this"
+                    && ident.as_str() != "super"
+                    && self.ctx.type_registry.as_ref().is_some_and(|r| r.class_exists(ident.as_str())) =>
+            {
+                Some(ident.name.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// 实例字段的地址；对象不是类实例或没有该字段时返回 `None`
+    fn instance_field_pointer(&mut self, member: &MemberAccessExpr) -> cayResult<Option<(IrValue, IrType)>> {
+        let Some(class_name) = self.object_class(&member.object) else { return Ok(None) };
+        let Some(field) = self.ctx.get_instance_field(&class_name, &member.member).cloned() else { return Ok(None) };
+        let object = self.build_receiver(&member.object, &member.loc)?;
+        Ok(Some(self.field_address(object, field.offset, &field.llvm_type)))
+    }
+
+    /// 求值方法调用或字段访问的接收者；this/super 之外的接收者做空指针检查
+    pub(super) fn build_receiver(&mut self, object: &Expr, loc: &SourceLocation) -> cayResult<IrValue> {
+        if matches!(object, Expr::Identifier(ident) if ident.as_str() == "this" || ident.as_str() == "super") {
+            return Ok(self.load_this());
+        }
+        let value = self.build_expression(object)?;
+        let value = self.convert(value, &i8_ptr());
+        self.null_check(&value, loc);
+        Ok(value)
+    }
+
+    /// 构建左值，返回存储位置和值类型
+    pub(super) fn build_lvalue(&mut self, expr: &Expr) -> cayResult<(IrValue, IrType)> {
+        match expr {
+            Expr::Identifier(ident) => {
+                let name = ident.as_str();
+                if let Some(slot) = self.local_slot(name) {
+                    return Ok(slot);
+                }
+                let current_class = self.ctx.current_class.clone();
+                if !current_class.is_empty() {
+                    if let Some((global, ty, _)) = self.static_field(&current_class, name) {
+                        return Ok((global, ty));
+                    }
+                    if self.ctx.get_instance_field(&current_class, name).is_some() {
+                        return Ok(self.this_field_pointer(&current_class, name));
+                    }
+                }
+                Err(codegen_error(format!("Variable '{}' not found", name)))
+            }
+            Expr::MemberAccess(member) => {
+                if let Some(class_name) = self.names_class(&member.object) {
+                    return self.static_field(&class_name, &member.member)
+                        .map(|(global, ty, _)| (global, ty))
+                        .ok_or_else(|| codegen_error(format!(
+                            "Static field '{}' not found in class '{}'", member.member, class_name
+                        )));
+                }
+                self.instance_field_pointer(member)?
+                    .ok_or_else(|| codegen_error("Invalid member access assignment target".to_string()))
+            }
+            Expr::ArrayAccess(arr) => self.array_element_pointer(arr),
+            _ => Err(codegen_error("Invalid lvalue expression".to_string())),
+        }
+    }
+
+    // ============================================================
+    // 成员访问
+    // ============================================================
+
+    fn build_member_access(&mut self, member: &MemberAccessExpr) -> cayResult<IrValue> {
+        if let Some(class_name) = self.names_class(&member.object) {
+            if let Some((global, ty, _)) = self.static_field(&class_name, &member.member) {
+                return Ok(self.load(global, ty));
+            }
+            // Class.staticMethod 作为函数指针
+            let method = self.ctx.type_registry.as_ref()
+                .and_then(|r| r.get_method(&class_name, &member.member))
+                .cloned();
+            if let Some(method) = method {
+                let fn_name = self.ctx.generate_method_name_from_params(&method.class_name, &method.name, &method.params);
+                let fn_ty = IrType::Function {
+                    params: method.params.iter().map(|p| self.ir_type(&p.param_type)).collect(),
+                    return_type: Box::new(self.ir_type(&method.return_type)),
+                };
+                return Ok(self.bitcast(IrValue::GlobalRef(format!("@{}", fn_name), fn_ty), i8_ptr()));
+            }
+            return Err(codegen_error(format!(
+                "Static field '{}' not found in class '{}'", member.member, class_name
+            )));
+        }
+
+        if let Some((ptr, ty)) = self.instance_field_pointer(member)? {
+            return Ok(self.load(ptr, ty));
+        }
+
+        if member.member == "length" && matches!(self.expr_type(&member.object), Some(Type::Array(_))) {
+            let array = self.build_expression(&member.object)?;
+            return Ok(self.array_length(array));
+        }
+
+        // 无法解析的成员访问返回对象本身（与旧代码生成器一致）
+        let object = self.build_expression(&member.object)?;
+        Ok(self.convert(object, &i8_ptr()))
+    }
+
+    // ============================================================
+    // 赋值
+    // ============================================================
+
+    fn build_assignment(&mut self, assign: &AssignmentExpr) -> cayResult<IrValue> {
+        if !matches!(assign.target.as_ref(), Expr::Identifier(_) | Expr::MemberAccess(_) | Expr::ArrayAccess(_)) {
+            return Err(codegen_error("Invalid assignment target".to_string()));
+        }
+
+        let op = match assign.op {
+            AssignOp::Assign => None,
+            AssignOp::AddAssign => Some(BinaryOp::Add),
+            AssignOp::SubAssign => Some(BinaryOp::Sub),
+            AssignOp::MulAssign => Some(BinaryOp::Mul),
+            AssignOp::DivAssign => Some(BinaryOp::Div),
+            AssignOp::ModAssign => Some(BinaryOp::Mod),
+        };
+
+        let (ptr, ty, value) = match op {
+            None => {
+                let target_type = self.expr_type(&assign.target);
+                let value = match &target_type {
+                    Some(t) => self.build_expression_with_type(&assign.value, t)?,
+                    None => self.build_expression(&assign.value)?,
+                };
+                let (ptr, ty) = self.build_lvalue(&assign.target)?;
+                (ptr, ty, value)
+            }
+            Some(op) => {
+                let (ptr, ty) = self.build_lvalue(&assign.target)?;
+                let current = self.load(ptr.clone(), ty.clone());
+                let rhs = self.build_expression(&assign.value)?;
+                let value = self.binary_values(op, current, rhs, &assign.loc)?;
+                (ptr, ty, value)
+            }
+        };
+
+        let value = self.try_convert(value.clone(), &ty).ok_or_else(|| codegen_error(format!(
+            "Cannot assign value of type {} to {} at line {}", value.ir_type(), ty, assign.loc.line
+        )))?;
+        self.store(value.clone(), ptr);
+        Ok(value)
+    }
+
+    // ============================================================
+    // 三元运算符
+    // ============================================================
+
+    fn build_ternary(&mut self, ternary: &TernaryExpr) -> cayResult<IrValue> {
+        let true_label = self.ctx.new_label("ternary.true");
+        let false_label = self.ctx.new_label("ternary.false");
+        let end_label = self.ctx.new_label("ternary.end");
+
+        let condition = self.build_expression(&ternary.condition)?;
+        let condition = self.as_condition(condition);
+        self.terminate(IrTerminator::ConditionalBranch {
+            condition,
+            true_target: true_label.clone(),
+            false_target: false_label.clone(),
+        });
+
+        // 真分支先不终止：两个分支都求值后才知道公共类型
+        self.start_block(&true_label);
+        let true_value = self.build_expression(&ternary.true_branch)?;
+        let true_exit = self.open_block_label();
+
+        self.add_block(&false_label);
+        let false_value = self.build_expression(&ternary.false_branch)?;
+
+        let ty = common_type(&true_value.ir_type(), &false_value.ir_type());
+        let mut incoming = Vec::new();
+        if let Some(label) = self.open_block_label() {
+            let value = self.convert(false_value, &ty);
+            incoming.push((value, label));
+            self.terminate(IrTerminator::Branch { target: end_label.clone() });
+        }
+        if let Some(label) = true_exit {
+            self.resume_block(&label);
+            let value = self.convert(true_value, &ty);
+            incoming.insert(0, (value, label));
+            self.terminate(IrTerminator::Branch { target: end_label.clone() });
+        }
+
+        self.add_block(&end_label);
+        if ty == IrType::Void || incoming.is_empty() {
+            return Ok(super::void_value());
+        }
+        let result = self.temp(ty.clone());
+        self.emit(IrInstruction::Phi { result: result.clone(), ty, incoming });
+        Ok(result)
+    }
+
+    /// 当前未终止块的标签（已终止时返回 `None`）
+    pub(super) fn open_block_label(&self) -> Option<String> {
+        if self.current_block_complete() {
+            return None;
+        }
+        self.state.func.as_ref()?.current_block().map(|b| b.label.clone())
+    }
+
+    /// 添加新块但不从当前块跳转过去
+    pub(super) fn add_block(&mut self, label: &str) {
+        self.func_mut().add_block(crate::ir::block::IrBasicBlock::new(label.to_string()));
+    }
+
+    /// 把一个未终止的块移到末尾，使其重新成为当前块
+    fn resume_block(&mut self, label: &str) {
+        let func = self.func_mut();
+        if let Some(index) = func.blocks.iter().position(|b| b.label == label) {
+            let block = func.blocks.remove(index);
+            func.blocks.push(block);
+        }
+    }
+}
+
+/// 两个分支值的公共类型：整数取较宽者，含浮点时取浮点，其余取第一个
+pub(super) fn common_type(a: &IrType, b: &IrType) -> IrType {
+    if a == b {
+        return a.clone();
+    }
+    match (a, b) {
+        (x, y) if x.is_integer() && y.is_integer() => {
+            if x.bit_width() >= y.bit_width() { x.clone() } else { y.clone() }
+        }
+        (IrType::F64, _) | (_, IrType::F64) => IrType::F64,
+        (IrType::F32, _) | (_, IrType::F32) => IrType::F32,
+        (IrType::Void, other) | (other, IrType::Void) => other.clone(),
+        _ => a.clone(),
+    }
+}
@@ -0,0 +1,150 @@
+//! Lambda 与方法引用构建
+//!
+//! lambda 体构建为独立的 `__lambda_*` 函数：换出当前函数的构建状态和名称解析状态，
+//! 构建完成后恢复；表达式的值是该函数的 `i8*` 函数指针。
+
+use super::{i8_ptr, IrBuilder};
+use crate::ast::*;
+use crate::codegen::context::ScopeManager;
+use crate::error::cayResult;
+use crate::ir::function::{IrFunction, IrLinkage, IrParam};
+use crate::ir::types::IrType;
+use crate::ir::value::{IrTerminator, IrValue};
+use crate::types::{FunctionType, ParameterInfo, Type};
+
+impl IrBuilder {
+    pub(super) fn build_lambda(&mut self, lambda: &LambdaExpr) -> cayResult<IrValue> {
+        self.build_lambda_with_signature(lambda, None)
+    }
+
+    /// 构建 lambda；已知目标函数类型时，未标注类型的参数和返回值取自该签名
+    pub(super) fn build_lambda_with_signature(&mut self, lambda: &LambdaExpr, signature: Option<&FunctionType>) -> cayResult<IrValue> {
+        let class_name = self.ctx.current_class.clone();
+        let lambda_name = self.ctx.new_label(&format!("__lambda_{}", class_name));
+        let param_types: Vec<Type> = lambda.params.iter().enumerate()
+            .map(|(i, p)| p.param_type.clone()
+                .or_else(|| signature.and_then(|s| s.params.get(i).cloned()))
+                .unwrap_or(Type::Int64))
+            .collect();
+
+        // 换出外层函数的状态
+        let saved_state = std::mem::take(&mut self.state);
+        let saved_scope = std::mem::replace(&mut self.ctx.scope_manager, ScopeManager::new());
+        let saved_var_types = std::mem::take(&mut self.ctx.var_types);
+        let saved_var_cay_types = std::mem::take(&mut self.ctx.var_cay_types);
+        let saved_var_class_map = std::mem::take(&mut self.ctx.var_class_map);
+        let saved_loops = std::mem::take(&mut self.ctx.loop_stack);
+        let saved_param_order = std::mem::take(&mut self.ctx.current_param_order);
+        let saved_function = self.ctx.current_function.clone();
+        let saved_return_type = self.ctx.current_return_type.clone();
+        let saved_temp_counter = self.ctx.temp_counter;
+        // lambda 不压入调用栈帧，其中的 return 不能弹出外层函数的帧
+        let saved_frame_tracked = std::mem::replace(&mut self.ctx.frame_tracked, false);
+
+        let result = self.build_lambda_function(lambda, &lambda_name, &param_types, signature);
+
+        self.state = saved_state;
+        self.ctx.scope_manager = saved_scope;
+        self.ctx.var_types = saved_var_types;
+        self.ctx.var_cay_types = saved_var_cay_types;
+        self.ctx.var_class_map = saved_var_class_map;
+        self.ctx.loop_stack = saved_loops;
+        self.ctx.current_param_order = saved_param_order;
+        self.ctx.current_function = saved_function;
+        self.ctx.current_return_type = saved_return_type;
+        self.ctx.temp_counter = saved_temp_counter;
+        self.ctx.frame_tracked = saved_frame_tracked;
+        self.ctx.current_class = class_name;
+
+        let fn_ty = result?;
+        Ok(self.bitcast(IrValue::GlobalRef(format!("@{}", lambda_name), fn_ty), i8_ptr()))
+    }
+
+    /// 在已换出的状态中构建 lambda 函数，返回其函数指针类型
+    fn build_lambda_function(
+        &mut self,
+        lambda: &LambdaExpr,
+        lambda_name: &str,
+        param_types: &[Type],
+        signature: Option<&FunctionType>,
+    ) -> cayResult<IrType> {
+        let class_name = self.ctx.current_class.clone();
+        let params: Vec<IrParam> = param_types.iter().enumerate()
+            .map(|(i, ty)| IrParam { name: format!("param{}", i), ty: self.ir_type(ty) })
+            .collect();
+        let param_irs: Vec<IrType> = params.iter().map(|p| p.ty.clone()).collect();
+        let mut func = IrFunction::new(lambda_name.to_string(), IrType::Void, params);
+        func.is_static = true;
+        if !self.ctx.whole_program {
+            // lambda 名称只在单元内唯一
+            func.linkage = IrLinkage::Internal;
+        }
+        self.begin_function(func, &class_name);
+        self.ctx.scope_manager.enter_scope();
+        for (i, (param, ty)) in lambda.params.iter().zip(param_types).enumerate() {
+            let info = ParameterInfo { name: param.name.clone(), param_type: ty.clone(), is_varargs: false };
+            self.bind_param(&info, &format!("param{}", i));
+        }
+
+        // 参数绑定后才能推断返回类型
+        let return_type = match signature {
+            Some(sig) => sig.return_type.as_ref().clone(),
+            None => self.infer_lambda_return_type(lambda),
+        };
+        let return_ty = self.ir_type(&return_type);
+        self.func_mut().return_type = return_ty.clone();
+        self.ctx.current_return_type = return_ty.to_llvm_str();
+
+        match &lambda.body {
+            LambdaBody::Expr(expr) => {
+                let value = self.build_expression(expr)?;
+                let value = (return_ty != IrType::Void).then(|| self.convert(value, &return_ty));
+                self.terminate(IrTerminator::Return { value });
+            }
+            LambdaBody::Block(block) => self.build_block(block)?,
+        }
+        self.ctx.scope_manager.exit_scope();
+        self.finish_function();
+
+        Ok(IrType::Function { params: param_irs, return_type: Box::new(return_ty) })
+    }
+
+    /// 表达式体取表达式类型；块体取第一个顶层 return 的类型，没有 return 时为 void
+    fn infer_lambda_return_type(&self, lambda: &LambdaExpr) -> Type {
+        let expr = match &lambda.body {
+            LambdaBody::Expr(expr) => expr.as_ref(),
+            LambdaBody::Block(block) => match block.statements.iter().find_map(|s| match s {
+                Stmt::Return(ret) => Some(ret.as_ref()),
+                _ => None,
+            }) {
+                Some(Some(expr)) => expr,
+                _ => return Type::Void,
+            },
+        };
+        self.expr_type(expr).unwrap_or(Type::Int32)
+    }
+
+    /// `Class::method` 返回静态方法的函数指针；对象方法引用暂不支持，返回 null
+    pub(super) fn build_method_ref(&mut self, method_ref: &MethodRefExpr) -> IrValue {
+        let Some(class_name) = &method_ref.class_name else {
+            return IrValue::NullConst(i8_ptr());
+        };
+        let method = self.ctx.type_registry.as_ref()
+            .and_then(|r| r.get_method(class_name, &method_ref.method_name))
+            .cloned();
+        let (fn_name, fn_ty) = match method {
+            Some(method) => (
+                self.ctx.generate_method_name_from_params(&method.class_name, &method.name, &method.params),
+                IrType::Function {
+                    params: method.params.iter().map(|p| self.ir_type(&p.param_type)).collect(),
+                    return_type: Box::new(self.ir_type(&method.return_type)),
+                },
+            ),
+            None => (
+                format!("{}.{}", class_name, method_ref.method_name),
+                IrType::Function { params: vec![IrType::I64], return_type: Box::new(IrType::I64) },
+            ),
+        };
+        self.bitcast(IrValue::GlobalRef(format!("@{}", fn_name), fn_ty), i8_ptr())
+    }
+}
@@ -0,0 +1,641 @@
+//! IR Builder
+//!
+//! 将 AST 降级为结构化的 `IrModule`，覆盖完整语言：类与构造/析构函数、
+//! 接口分派、字符串、数组、lambda、`__cay_alloc`/`__cay_free`、extern 声明和内联 IR。
+//!
+//! 名称解析、类布局、方法重载和类型推断复用旧代码生成器 `IRGenerator` 的上下文，
+//! 保证两条管线生成的函数名、字段偏移和运行时调用一致；指令则直接构建为 `IrInstruction`，
+//! 之后可经过 `IrVerifier`、IR pass 和 `LlvmBackend::emit` 输出。
+//!
+//! 与旧代码生成器的差异（旧管线生成无效 IR 或行为错误的地方）：
+//! - `&&`/`||` 短路求值，复合赋值按运算符计算
+//! - 所有 alloca 提升到入口块，未初始化的局部变量置零
+//! - switch 内的无标签 break 跳出 switch
+//! - 类的静态初始化块在 `main` 开始时调用
+//! - 逃逸分析判定不逃逸的 `new` 对象分配在栈上，离开块时调用析构函数
+
+mod program;
+mod statements;
+mod expressions;
+mod operators;
+mod calls;
+mod builtins;
+mod objects;
+mod arrays;
+mod lambdas;
+
+use super::block::IrBasicBlock;
+use super::function::IrFunction;
+use super::module::{IrExternDecl, IrModule};
+use super::types::IrType;
+use super::value::{IrCastKind, IrCmpOp, IrInstruction, IrTerminator, IrValue};
+use crate::ast::Program;
+use crate::cabi::ExportedFunction;
+use crate::codegen::runtime::PanicKind;
+use crate::codegen::IRGenerator;
+use crate::error::{cayResult, SourceLocation};
+use crate::semantic::EscapeInfo;
+use crate::types::{Type, TypeRegistry};
+use std::collections::{HashMap, HashSet};
+
+/// break/continue 跳转目标
+#[derive(Debug, Clone)]
+struct JumpTarget {
+    /// break 跳转的目标
+    break_label: String,
+    /// continue 跳转的目标（switch 没有）
+    continue_label: Option<String>,
+    /// 循环标签（用于带标签的 break/continue）
+    label: Option<String>,
+    /// 进入循环或 switch 时的清理作用域层数，跳出时调用更内层作用域的析构函数
+    cleanup_depth: usize,
+}
+
+/// 离开作用域时调用的析构函数（栈上分配的对象）
+#[derive(Debug, Clone)]
+struct Cleanup {
+    object: IrValue,
+    destructor: String,
+}
+
+/// 正在构建的函数的状态（lambda 体构建时整体换出）
+#[derive(Default)]
+struct FunctionState {
+    /// 当前函数
+    func: Option<IrFunction>,
+    /// 已提升到入口块的 alloca 名称
+    allocas: HashSet<String>,
+    /// 入口块开头的 alloca 数量（新的 alloca 插在它们之后）
+    alloca_count: usize,
+    /// break/continue 目标栈
+    jumps: Vec<JumpTarget>,
+    /// 每个块作用域中栈上对象的析构函数（按声明顺序）
+    cleanups: Vec<Vec<Cleanup>>,
+    /// 上一条发射的源位置
+    last_location: Option<(String, usize, usize)>,
+}
+
+/// IR Builder
+///
+/// 负责将 AST 转换为 IR 模块。
+pub struct IrBuilder {
+    /// 名称解析、类型推断和布局信息（与旧代码生成器共享）
+    ctx: IRGenerator,
+    /// 正在构建的模块
+    module: IrModule,
+    /// 当前函数的构建状态
+    state: FunctionState,
+    /// 是否记录调试信息所需的变量声明和类布局（`-g`）
+    debug_info: bool,
+    /// 逃逸分析结果：不逃逸的 `new` 分配在栈上
+    escape: EscapeInfo,
+    /// 分离编译时依赖单元的接口声明；为 None 时整体编译
+    imports: Option<Program>,
+    /// 库输出时以 C ABI 导出的函数；为 None 时生成 C `main`
+    library_exports: Option<Vec<ExportedFunction>>,
+}
+
+impl IrBuilder {
+    /// 创建新的 IR Builder
+    pub fn new() -> Self {
+        Self {
+            ctx: IRGenerator::new(),
+            module: IrModule::new("module".to_string(), "x86_64-w64-mingw32".to_string()),
+            state: FunctionState::default(),
+            debug_info: false,
+            escape: EscapeInfo::default(),
+            imports: None,
+            library_exports: None,
+        }
+    }
+
+    /// 设置是否记录局部变量声明（`VarDecl`）和类布局类型声明，供 `LlvmBackend` 生成 DWARF 调试信息
+    pub fn set_debug_info(&mut self, enabled: bool) {
+        self.debug_info = enabled;
+    }
+
+    /// 构建过程中改为直接调用的接口调用点（`--print-devirt`）
+    pub fn devirtualized_calls(&self) -> &[crate::codegen::DevirtualizedCall] {
+        &self.ctx.devirtualized_calls
+    }
+
+    /// `-Rpass=escape` 输出：每个 `new` 表达式是否分配在栈上，逃逸时给出原因
+    pub fn escape_remarks(&self) -> Vec<String> {
+        let current_file = self.ctx.source_file.clone();
+        self.escape.remarks().iter().map(|remark| {
+            let (file, line, column) = self.ctx.resolve_source_position(&remark.loc, &current_file);
+            format!("{}:{}:{}: remark: {} [-Rpass=escape]", file, line, column, remark.message())
+        }).collect()
+    }
+
+    /// 分离编译：设置依赖单元的接口声明（见 `crate::unit`）
+    ///
+    /// 依赖单元中的函数只生成声明，静态字段是外部全局变量；类型 ID 由类型名哈希得到，
+    /// 运行时函数在每个单元中以 `linkonce_odr` 输出，链接时只保留一份。
+    pub fn set_imports(&mut self, imports: Program) {
+        self.imports = Some(imports);
+        self.ctx.whole_program = false;
+    }
+
+    /// 库输出（staticlib/cdylib，见 `crate::cabi`）：不生成 C `main`，为每个导出函数生成同名的 C ABI 包装函数
+    ///
+    /// 静态初始化在 `__cay_lib_init` 中进行，由 `llvm.global_ctors` 在库加载时调用；
+    /// 整体编译时其余函数和运行时函数都是模块私有的，库只导出包装函数。
+    pub fn set_library_exports(&mut self, exports: Vec<ExportedFunction>) {
+        self.library_exports = Some(exports);
+    }
+
+    /// 设置类型注册表
+    pub fn set_type_registry(&mut self, registry: TypeRegistry) {
+        self.ctx.set_type_registry(registry);
+    }
+
+    /// 设置目标平台、运行时检查和溢出检查选项
+    pub fn set_platform_config(&mut self, options: &crate::CompilerOptions) {
+        self.ctx.set_platform_config(options);
+    }
+
+    /// 设置预处理器源映射（预处理后行号 -> 原始文件和行号）
+    pub fn set_preprocessor_source_map(&mut self, source_map: HashMap<usize, (String, usize)>) {
+        self.ctx.set_preprocessor_source_map(source_map);
+    }
+
+    /// 从 AST 构建 IR 模块
+    pub fn build_from_ast(&mut self, program: &Program) -> cayResult<IrModule> {
+        self.build_program(program, "")?;
+        Ok(self.module.clone())
+    }
+
+    /// 从 AST 构建 IR 模块，`source_file` 用于源映射注释和 panic 位置
+    pub fn build_from_ast_with_source(&mut self, program: &Program, source_file: &str) -> cayResult<IrModule> {
+        self.build_program(program, source_file)?;
+        Ok(self.module.clone())
+    }
+
+    /// 消耗 builder，返回构建的模块
+    pub fn finish(self) -> IrModule {
+        self.module
+    }
+
+    // ============================================================
+    // 函数与基本块
+    // ============================================================
+
+    /// 开始构建新函数（重置每个函数的名称解析状态）
+    fn begin_function(&mut self, func: IrFunction, class_name: &str) {
+        self.ctx.current_function = func.name.clone();
+        self.ctx.current_class = class_name.to_string();
+        self.ctx.current_return_type = func.return_type.to_llvm_str();
+        self.ctx.temp_counter = 0;
+        self.ctx.var_types.clear();
+        self.ctx.var_cay_types.clear();
+        self.ctx.var_class_map.clear();
+        self.ctx.scope_manager.reset();
+        self.ctx.loop_stack.clear();
+        self.state = FunctionState {
+            func: Some(func),
+            ..FunctionState::default()
+        };
+    }
+
+    /// 结束当前函数：补齐缺失的终止指令并加入模块
+    fn finish_function(&mut self) {
+        self.ctx.frame_tracked = false;
+        if let Some(func) = self.take_function() {
+            self.module.add_function(func);
+        }
+    }
+
+    /// 取出当前函数，给所有未终止的块补上终止指令
+    fn take_function(&mut self) -> Option<IrFunction> {
+        let mut func = self.state.func.take()?;
+        let default_term = match &func.return_type {
+            IrType::Void => IrTerminator::Return { value: None },
+            ty => IrTerminator::Return { value: Some(zero_value(ty)) },
+        };
+        for block in &mut func.blocks {
+            if !block.is_complete() {
+                // 流出末尾的块：void 函数隐式返回；非 void 函数只可能是不可达的死代码块
+                let term = if block.instructions.is_empty() && !block.is_entry && func.return_type != IrType::Void {
+                    IrTerminator::Unreachable
+                } else {
+                    default_term.clone()
+                };
+                block.set_terminator(term);
+            }
+        }
+        Some(func)
+    }
+
+    fn func_mut(&mut self) -> &mut IrFunction {
+        self.state.func.as_mut().expect("IrBuilder: no function under construction")
+    }
+
+    fn current_block_complete(&self) -> bool {
+        self.state.func.as_ref()
+            .and_then(|f| f.current_block())
+            .is_none_or(|b| b.is_complete())
+    }
+
+    /// 开始新基本块；当前块未终止时先跳转到新块（顺序贯穿）
+    fn start_block(&mut self, label: &str) {
+        if !self.current_block_complete() {
+            self.terminate(IrTerminator::Branch { target: label.to_string() });
+        }
+        self.func_mut().add_block(IrBasicBlock::new(label.to_string()));
+    }
+
+    /// 发射一条指令；当前块已终止时放入新的不可达块
+    fn emit(&mut self, inst: IrInstruction) {
+        if self.current_block_complete() {
+            let label = self.ctx.new_label("dead");
+            self.func_mut().add_block(IrBasicBlock::new(label));
+        }
+        if !matches!(inst, IrInstruction::Comment { .. } | IrInstruction::SourceLocation { .. }) {
+            self.emit_source_location();
+        }
+        self.func_mut().current_block_mut().expect("function has a block").push(inst);
+    }
+
+    /// 设置当前块的终止指令；当前块已终止（如 return 之后）时丢弃
+    fn terminate(&mut self, term: IrTerminator) {
+        if self.current_block_complete() {
+            return;
+        }
+        self.func_mut().current_block_mut().expect("function has a block").set_terminator(term);
+    }
+
+    /// 源位置变化时发射源映射注释
+    fn emit_source_location(&mut self) {
+        if !self.ctx.enable_source_map || self.ctx.source_file.is_empty() {
+            return;
+        }
+        let position = (self.ctx.source_file.clone(), self.ctx.source_line, self.ctx.source_column);
+        if self.state.last_location.as_ref() == Some(&position) {
+            return;
+        }
+        let (file, line, column) = position.clone();
+        self.state.last_location = Some(position);
+        self.func_mut().current_block_mut().expect("function has a block").push(IrInstruction::SourceLocation {
+            file,
+            line: line as u32,
+            column: column as u32,
+        });
+    }
+
+    /// 把当前源位置设为语句位置
+    fn set_location(&mut self, loc: &SourceLocation) {
+        let file = self.ctx.source_file.clone();
+        self.ctx.set_source_from_loc(loc, &file);
+    }
+
+    // ============================================================
+    // 值与类型
+    // ============================================================
+
+    /// 新的临时寄存器
+    fn temp(&mut self, ty: IrType) -> IrValue {
+        IrValue::Register(self.ctx.new_temp(), ty)
+    }
+
+    /// Cavvy 类型对应的 IR 类型
+    fn ir_type(&self, ty: &Type) -> IrType {
+        IrType::from_llvm_str(&self.ctx.type_to_llvm(ty))
+    }
+
+    /// 在入口块分配局部变量槽位（按名称去重，循环体内的声明复用同一个槽位）
+    fn alloca(&mut self, name: &str, ty: IrType) -> IrValue {
+        let reg = IrValue::Register(format!("%{}", name), IrType::Pointer(Box::new(ty.clone())));
+        if self.state.allocas.insert(name.to_string()) {
+            let index = self.state.alloca_count;
+            self.state.alloca_count += 1;
+            let align = ty.alignment(&self.ctx.target) as u32;
+            let entry = self.func_mut().entry_block_mut().expect("function has an entry block");
+            entry.instructions.insert(index, IrInstruction::Alloca { result: reg.clone(), ty, align });
+        }
+        reg
+    }
+
+    /// 启用调试信息时记录变量声明（局部变量、参数和 `this`）
+    fn declare_debug_variable(&mut self, name: &str, slot: IrValue, ty: &Type) {
+        if self.debug_info {
+            let ty = self.debug_type(ty);
+            self.emit(IrInstruction::VarDecl { name: name.to_string(), alloca_reg: slot, ty });
+        }
+    }
+
+    /// 变量的调试类型：有实例布局的类是指向 `class.X` 的指针，其余按存储类型
+    fn debug_type(&self, ty: &Type) -> IrType {
+        match ty {
+            Type::Object(class) if self.ctx.get_class_layout(class).is_some() => {
+                IrType::Pointer(Box::new(IrType::Struct { name: format!("class.{}", class), fields: Vec::new() }))
+            }
+            other => IrType::from_llvm_str(&self.ctx.type_to_llvm(other)),
+        }
+    }
+
+    fn load(&mut self, ptr: IrValue, ty: IrType) -> IrValue {
+        let result = self.temp(ty.clone());
+        self.emit(IrInstruction::Load { result: result.clone(), ptr, ty });
+        result
+    }
+
+    fn store(&mut self, value: IrValue, ptr: IrValue) {
+        let ty = value.ir_type();
+        self.emit(IrInstruction::Store { value, ptr, ty });
+    }
+
+    fn cast(&mut self, kind: IrCastKind, value: IrValue, to_ty: IrType) -> IrValue {
+        let result = self.temp(to_ty.clone());
+        self.emit(IrInstruction::Cast { result: result.clone(), kind, value, to_ty });
+        result
+    }
+
+    fn bitcast(&mut self, value: IrValue, to_ty: IrType) -> IrValue {
+        let result = self.temp(to_ty.clone());
+        self.emit(IrInstruction::BitCast { result: result.clone(), value, to_ty });
+        result
+    }
+
+    fn compare(&mut self, op: IrCmpOp, left: IrValue, right: IrValue) -> IrValue {
+        let result = self.temp(IrType::I1);
+        self.emit(IrInstruction::Compare { result: result.clone(), op, left, right });
+        result
+    }
+
+    /// `getelementptr i8, i8* ptr, i64 offset`
+    fn byte_offset(&mut self, ptr: IrValue, offset: i64) -> IrValue {
+        let result = self.temp(i8_ptr());
+        self.emit(IrInstruction::GetElementPtr {
+            result: result.clone(),
+            ptr,
+            indices: vec![IrValue::IntConst(offset, IrType::I64)],
+            base_ty: IrType::I8,
+        });
+        result
+    }
+
+    /// 调用函数，返回值为 void 时返回 `None`
+    fn call(&mut self, func_name: &str, args: Vec<IrValue>, return_ty: IrType) -> Option<IrValue> {
+        let result = (return_ty != IrType::Void).then(|| self.temp(return_ty.clone()));
+        self.emit(IrInstruction::Call {
+            result: result.clone(),
+            func_name: func_name.to_string(),
+            args,
+            return_ty,
+        });
+        result
+    }
+
+    /// 调用 C 可变参数函数（需要带函数类型的调用形式）
+    fn call_varargs(&mut self, func_name: &str, fixed: Vec<IrType>, args: Vec<IrValue>, return_ty: IrType) -> Option<IrValue> {
+        let result = (return_ty != IrType::Void).then(|| self.temp(return_ty.clone()));
+        self.emit(IrInstruction::CallIndirect {
+            result: result.clone(),
+            callee: IrValue::GlobalRef(format!("@{}", func_name), i8_ptr()),
+            return_ty,
+            param_tys: fixed,
+            is_varargs: true,
+            args,
+        });
+        result
+    }
+
+    /// 字符串常量的 `i8*` 指针
+    fn string_ptr(&mut self, s: &str) -> IrValue {
+        let name = self.ctx.get_or_create_string_constant(s);
+        let array_ty = IrType::Array(Box::new(IrType::I8), s.len() + 1);
+        let result = self.temp(i8_ptr());
+        self.emit(IrInstruction::GetElementPtr {
+            result: result.clone(),
+            ptr: IrValue::GlobalRef(name, IrType::Pointer(Box::new(array_ty.clone()))),
+            indices: vec![IrValue::IntConst(0, IrType::I64), IrValue::IntConst(0, IrType::I64)],
+            base_ty: array_ty,
+        });
+        result
+    }
+
+    /// 声明运行时库之外的 C 函数（如 `malloc`/`free`），已由运行时或用户声明时跳过
+    fn declare_c_function(&mut self, name: &str, return_type: IrType, params: Vec<IrType>) {
+        let ret = return_type.to_llvm_str();
+        let param_strs: Vec<String> = params.iter().map(|p| p.to_llvm_str()).collect();
+        let signature = if param_strs.is_empty() {
+            format!("{}@{}@void", name, ret)
+        } else {
+            format!("{}@{}@{}", name, ret, param_strs.join("@"))
+        };
+        if self.ctx.is_extern_emitted(&signature) || self.module.find_extern(name).is_some() {
+            return;
+        }
+        self.ctx.mark_extern_emitted(signature);
+        self.module.add_extern(IrExternDecl {
+            name: name.to_string(),
+            return_type,
+            params: params.into_iter().enumerate().map(|(i, ty)| (format!("p{}", i), ty)).collect(),
+            calling_convention: None,
+            is_varargs: false,
+        });
+    }
+
+    // ============================================================
+    // 运行时检查
+    // ============================================================
+
+    /// 在函数入口压入运行时调用栈帧
+    fn frame_push(&mut self, display_name: &str) {
+        self.ctx.frame_tracked = self.ctx.runtime_checks_enabled();
+        if self.ctx.frame_tracked {
+            let name = self.string_ptr(display_name);
+            self.call("__cay_frame_push", vec![name], IrType::Void);
+        }
+    }
+
+    /// 在函数返回前弹出运行时调用栈帧
+    fn frame_pop(&mut self) {
+        if self.ctx.frame_tracked {
+            self.call("__cay_frame_pop", Vec::new(), IrType::Void);
+        }
+    }
+
+    /// 发射 `__cay_panic` 调用并终止当前块
+    fn panic(&mut self, kind: PanicKind, loc: &SourceLocation) {
+        let fallback_file = self.ctx.source_file.clone();
+        let (file, line, column) = self.ctx.resolve_source_position(loc, &fallback_file);
+        let file_ptr = self.string_ptr(&file);
+        self.call("__cay_panic", vec![
+            IrValue::IntConst(kind.code() as i64, IrType::I32),
+            file_ptr,
+            IrValue::IntConst(line as i64, IrType::I32),
+            IrValue::IntConst(column as i64, IrType::I32),
+        ], IrType::Void);
+        self.terminate(IrTerminator::Unreachable);
+    }
+
+    /// 发射带运行时错误描述的 `__cay_panic_message` 调用并终止当前块（测试断言失败）
+    fn panic_with_message(&mut self, message: IrValue, loc: &SourceLocation) {
+        let fallback_file = self.ctx.source_file.clone();
+        let (file, line, column) = self.ctx.resolve_source_position(loc, &fallback_file);
+        let file_ptr = self.string_ptr(&file);
+        self.call("__cay_panic_message", vec![
+            message,
+            file_ptr,
+            IrValue::IntConst(line as i64, IrType::I32),
+            IrValue::IntConst(column as i64, IrType::I32),
+        ], IrType::Void);
+        self.terminate(IrTerminator::Unreachable);
+    }
+
+    /// 条件为真时 panic，否则继续
+    fn panic_if(&mut self, condition: IrValue, kind: PanicKind, loc: &SourceLocation, prefix: &str) {
+        let error_label = self.ctx.new_label(&format!("{}.error", prefix));
+        let continue_label = self.ctx.new_label(&format!("{}.cont", prefix));
+        self.terminate(IrTerminator::ConditionalBranch {
+            condition,
+            true_target: error_label.clone(),
+            false_target: continue_label.clone(),
+        });
+        self.start_block(&error_label);
+        self.panic(kind, loc);
+        self.start_block(&continue_label);
+    }
+
+    /// 空指针检查（仅在启用运行时检查且值为指针时生成）
+    fn null_check(&mut self, value: &IrValue, loc: &SourceLocation) {
+        if !self.ctx.runtime_checks_enabled() || !value.ir_type().is_pointer() {
+            return;
+        }
+        let null = IrValue::NullConst(value.ir_type());
+        let is_null = self.compare(IrCmpOp::Eq, value.clone(), null);
+        self.panic_if(is_null, PanicKind::NullPointer, loc, "null");
+    }
+
+    /// 整数除零检查
+    fn div_zero_check(&mut self, divisor: &IrValue, loc: &SourceLocation) {
+        if !self.ctx.runtime_checks_enabled() {
+            return;
+        }
+        let zero = IrValue::IntConst(0, divisor.ir_type());
+        let is_zero = self.compare(IrCmpOp::Eq, divisor.clone(), zero);
+        self.panic_if(is_zero, PanicKind::DivisionByZero, loc, "div");
+    }
+
+    // ============================================================
+    // 类型转换
+    // ============================================================
+
+    /// 把值转换为目标类型（整数扩展/截断、整数与浮点互转、指针转换）
+    ///
+    /// 无法转换时返回 `None`。
+    fn try_convert(&mut self, value: IrValue, to: &IrType) -> Option<IrValue> {
+        let from = value.ir_type();
+        if from == *to {
+            return Some(value);
+        }
+        // null 字面量可以赋给任意指针，或作为整数 0
+        if let IrValue::NullConst(_) = value {
+            return match to {
+                IrType::Pointer(_) | IrType::Raw(_) => Some(IrValue::NullConst(to.clone())),
+                t if t.is_integer() => Some(IrValue::IntConst(0, t.clone())),
+                _ => None,
+            };
+        }
+        if matches!(value, IrValue::IntConst(0, _)) && to.is_pointer() {
+            return Some(IrValue::NullConst(to.clone()));
+        }
+        let is_ptr = |t: &IrType| t.is_pointer() || matches!(t, IrType::Raw(_) | IrType::Function { .. });
+        match (&from, to) {
+            (f, t) if f.is_integer() && t.is_integer() => {
+                let (fb, tb) = (f.bit_width().unwrap_or(64), t.bit_width().unwrap_or(64));
+                if let IrValue::IntConst(v, _) = value {
+                    let v = if fb == 1 { v & 1 } else { v };
+                    let v = match tb {
+                        1 => v & 1,
+                        64 => v,
+                        _ => (v << (64 - tb)) >> (64 - tb),
+                    };
+                    return Some(IrValue::IntConst(v, t.clone()));
+                }
+                let kind = if tb < fb {
+                    IrCastKind::Trunc
+                } else if fb == 1 {
+                    IrCastKind::ZeroExt
+                } else {
+                    IrCastKind::SignExt
+                };
+                Some(self.cast(kind, value, t.clone()))
+            }
+            (f, t) if f.is_integer() && t.is_float() => Some(self.cast(IrCastKind::IntToFloat, value, t.clone())),
+            (f, t) if f.is_float() && t.is_integer() => Some(self.cast(IrCastKind::FloatToInt, value, t.clone())),
+            (IrType::F32, IrType::F64) => Some(self.cast(IrCastKind::FloatExt, value, to.clone())),
+            (IrType::F64, IrType::F32) => Some(self.cast(IrCastKind::FloatTrunc, value, to.clone())),
+            (f, t) if is_ptr(f) && is_ptr(t) => Some(self.bitcast(value, t.clone())),
+            (f, t) if is_ptr(f) && t.is_integer() => Some(self.cast(IrCastKind::PtrToInt, value, t.clone())),
+            (f, t) if f.is_integer() && is_ptr(t) => {
+                let wide = self.try_convert(value, &IrType::I64)?;
+                Some(self.cast(IrCastKind::IntToPtr, wide, t.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// 与 `try_convert` 相同，无法转换时原样返回
+    fn convert(&mut self, value: IrValue, to: &IrType) -> IrValue {
+        match self.try_convert(value.clone(), to) {
+            Some(v) => v,
+            None => value,
+        }
+    }
+
+    /// 把条件值转换为 i1（非 i1 值与 0 比较）
+    fn as_condition(&mut self, value: IrValue) -> IrValue {
+        let ty = value.ir_type();
+        if ty == IrType::I1 {
+            return value;
+        }
+        let zero = if ty.is_pointer() {
+            IrValue::NullConst(ty)
+        } else if ty.is_float() {
+            IrValue::FloatConst(0.0, ty)
+        } else {
+            IrValue::IntConst(0, ty)
+        };
+        let op = if zero.ir_type().is_float() { IrCmpOp::FNe } else { IrCmpOp::Ne };
+        self.compare(op, value, zero)
+    }
+
+    /// 当前函数的 this 指针
+    fn load_this(&mut self) -> IrValue {
+        match self.ctx.scope_manager.get_llvm_name("this") {
+            Some(this_name) => {
+                let ptr = IrValue::Register(format!("%{}", this_name), IrType::Pointer(Box::new(i8_ptr())));
+                self.load(ptr, i8_ptr())
+            }
+            None => IrValue::NullConst(i8_ptr()),
+        }
+    }
+}
+
+impl Default for IrBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `i8*`
+fn i8_ptr() -> IrType {
+    IrType::Pointer(Box::new(IrType::I8))
+}
+
+/// 类型的零值
+fn zero_value(ty: &IrType) -> IrValue {
+    match ty {
+        t if t.is_float() => IrValue::FloatConst(0.0, t.clone()),
+        IrType::Pointer(_) | IrType::Raw(_) | IrType::Function { .. } => IrValue::NullConst(ty.clone()),
+        t => IrValue::IntConst(0, t.clone()),
+    }
+}
+
+/// void 表达式的占位值
+fn void_value() -> IrValue {
+    IrValue::Undef(IrType::Void)
+}
@@ -0,0 +1,319 @@
+//! 对象构建：new 表达式、构造函数调用、类型转换、instanceof 和 `__cay_alloc`/`__cay_free`
+
+use super::{i8_ptr, void_value, Cleanup, IrBuilder};
+use crate::ast::*;
+use crate::codegen::runtime::PanicKind;
+use crate::error::{cayResult, codegen_error, SourceLocation};
+use crate::ir::block::IrBasicBlock;
+use crate::ir::function::{IrFunction, IrParam};
+use crate::ir::types::IrType;
+use crate::ir::value::{IrBinaryOp, IrCastKind, IrCmpOp, IrInstruction, IrTerminator, IrValue};
+use crate::types::{ParameterInfo, Type};
+
+/// 分离编译时判断对象类型的函数（见 `build_instance_of_function`）
+const INSTANCE_OF_FUNCTION: &str = "__cay_instance_of";
+
+impl IrBuilder {
+    // ============================================================
+    // new 与构造函数
+    // ============================================================
+
+    /// `new C(args)`：分配对象、写入类型 ID 并调用构造函数
+    ///
+    /// 逃逸分析判定不逃逸的对象分配在栈上，并登记到当前块，离开块时调用析构函数。
+    pub(super) fn build_new(&mut self, new_expr: &NewExpr) -> cayResult<IrValue> {
+        let class_name = &new_expr.class_name;
+        let size = self.ctx.get_class_layout(class_name)
+            .map(|layout| layout.total_size as i64)
+            .unwrap_or(8);
+        let stack = self.escape.stack_allocation(&new_expr.loc).cloned();
+        let object = match &stack {
+            Some(stack) => {
+                // 以 i64 数组保证 8 字节对齐；循环中复用同一槽位，每次构造前清零
+                let slot_ty = IrType::Array(Box::new(IrType::I64), (size as usize).div_ceil(8).max(1));
+                let slot = self.alloca(&format!("{}.stack{}", class_name, stack.index), slot_ty);
+                let object = self.bitcast(slot, i8_ptr());
+                self.call("llvm.memset.p0i8.i64", vec![
+                    object.clone(),
+                    IrValue::IntConst(0, IrType::I8),
+                    IrValue::IntConst(size, IrType::I64),
+                    IrValue::IntConst(0, IrType::I1),
+                ], IrType::Void);
+                object
+            }
+            None => self.call("calloc", vec![
+                IrValue::IntConst(1, IrType::I64),
+                IrValue::IntConst(size, IrType::I64),
+            ], i8_ptr()).expect("calloc returns a pointer"),
+        };
+
+        let type_id = self.ctx.get_type_id_value(class_name).unwrap_or(0);
+        let type_id_ptr = self.bitcast(object.clone(), IrType::Pointer(Box::new(IrType::I32)));
+        self.store(IrValue::IntConst(type_id as i64, IrType::I32), type_id_ptr);
+
+        self.build_constructor_call(class_name, &new_expr.args, object.clone())?;
+        if let (Some(destructor), Some(scope)) = (stack.and_then(|s| s.destructor), self.state.cleanups.last_mut()) {
+            scope.push(Cleanup { object: object.clone(), destructor });
+        }
+        Ok(object)
+    }
+
+    /// 以 `this` 调用类的构造函数（按实参类型选择重载）
+    pub(super) fn build_constructor_call(&mut self, class_name: &str, args: &[Expr], this: IrValue) -> cayResult<()> {
+        // 旧代码生成器在求值实参前推断构造函数签名，找不到匹配的重载时沿用该签名
+        let inferred: Vec<String> = args.iter().map(|a| self.ctx.infer_expr_type_for_ctor(a)).collect();
+        let mut values = Vec::with_capacity(args.len());
+        for arg in args {
+            values.push(self.build_expression(arg)?);
+        }
+
+        let mut final_args = vec![this];
+        let ctor_name = match self.select_constructor(class_name, &values) {
+            Some(params) => {
+                for (param, value) in params.iter().zip(values) {
+                    let ty = self.ir_type(&param.param_type);
+                    final_args.push(self.convert(value, &ty));
+                }
+                let sigs: Vec<String> = params.iter().map(|p| self.ctx.type_to_signature(&p.param_type)).collect();
+                self.ctx.generate_constructor_call_name_with_types(class_name, &sigs)
+            }
+            None => {
+                final_args.extend(values);
+                self.ctx.generate_constructor_call_name_with_types(class_name, &inferred)
+            }
+        };
+        self.call(&ctor_name, final_args, IrType::Void);
+        Ok(())
+    }
+
+    /// 选择构造函数重载：优先参数类型完全匹配，其次参数数量匹配
+    fn select_constructor(&self, class_name: &str, args: &[IrValue]) -> Option<Vec<ParameterInfo>> {
+        let class_info = self.ctx.type_registry.as_ref()?.get_class(class_name)?;
+        let candidates: Vec<&Vec<ParameterInfo>> = class_info.constructors.iter()
+            .map(|c| &c.params)
+            .filter(|params| params.len() == args.len())
+            .collect();
+        let exact = candidates.iter().find(|params| {
+            params.iter().zip(args).all(|(p, a)| self.ir_type(&p.param_type) == a.ir_type())
+        });
+        exact.or(candidates.first()).map(|params| (*params).clone())
+    }
+
+    // ============================================================
+    // 类型转换
+    // ============================================================
+
+    pub(super) fn build_cast(&mut self, cast: &CastExpr) -> cayResult<IrValue> {
+        let value = self.build_expression(&cast.expr)?;
+        let from = value.ir_type();
+        let to = self.ir_type(&cast.target_type);
+
+        if let Type::Object(target_class) = &cast.target_type {
+            self.downcast_check(&cast.expr, &value, target_class, &cast.loc);
+        }
+        if from == to {
+            return Ok(value);
+        }
+
+        // 基本类型到字符串
+        if cast.target_type == Type::String && !from.is_pointer() {
+            return Ok(self.value_to_string(value));
+        }
+        // 字符串到整数使用 atoi
+        if from == i8_ptr() && to.is_integer() && matches!(self.expr_type(&cast.expr), Some(Type::String)) {
+            let parsed = self.call("atoi", vec![value], IrType::I32).expect("atoi returns i32");
+            return Ok(self.convert(parsed, &to));
+        }
+        // 无符号源类型零扩展
+        if from.is_integer() && to.is_integer() && from.bit_width() < to.bit_width() {
+            let unsigned = self.expr_type(&cast.expr).is_some_and(|ty| self.ctx.target.is_unsigned(&ty));
+            if unsigned {
+                return Ok(self.cast(IrCastKind::ZeroExt, value, to));
+            }
+        }
+
+        self.try_convert(value, &to)
+            .ok_or_else(|| codegen_error(format!("Unsupported cast from {} to {}", from, to)))
+    }
+
+    /// 对象向下转型的运行时检查
+    ///
+    /// null 可以转换为任意类型；静态可知的向上转型和未注册的类型（如 FFI 结构体）不检查。
+    fn downcast_check(&mut self, expr: &Expr, value: &IrValue, target_class: &str, loc: &SourceLocation) {
+        if !self.ctx.runtime_checks_enabled() || value.ir_type() != i8_ptr() {
+            return;
+        }
+        let is_interface = self.ctx.type_registry.as_ref().is_some_and(|r| r.get_interface(target_class).is_some());
+        if !is_interface && self.ctx.get_type_id_value(target_class).is_none() {
+            return;
+        }
+        match self.ctx.get_expression_type(expr) {
+            Some(Type::Object(source_class)) if !self.ctx.is_subtype(&source_class, target_class) => {}
+            _ => return,
+        }
+
+        let check_label = self.ctx.new_label("cast.check");
+        let ok_label = self.ctx.new_label("cast.ok");
+        let error_label = self.ctx.new_label("cast.error");
+
+        let is_null = self.compare(IrCmpOp::Eq, value.clone(), IrValue::NullConst(i8_ptr()));
+        self.terminate(IrTerminator::ConditionalBranch {
+            condition: is_null,
+            true_target: ok_label.clone(),
+            false_target: check_label.clone(),
+        });
+        self.start_block(&check_label);
+        let matches = self.type_id_matches(value.clone(), target_class);
+        self.terminate(IrTerminator::ConditionalBranch {
+            condition: matches,
+            true_target: ok_label.clone(),
+            false_target: error_label.clone(),
+        });
+        self.start_block(&error_label);
+        self.panic(PanicKind::ClassCast, loc);
+        self.start_block(&ok_label);
+    }
+
+    /// 读取对象头部的类型 ID，判断是否是目标类（含子类）或接口的实现类
+    fn type_id_matches(&mut self, object: IrValue, target: &str) -> IrValue {
+        let type_id_ptr = self.bitcast(object, IrType::Pointer(Box::new(IrType::I32)));
+        let actual = self.load(type_id_ptr, IrType::I32);
+        if !self.ctx.whole_program {
+            // 分离编译时子类可能在依赖本单元的其他单元中，交给入口单元定义的判断函数
+            let target_id = self.ctx.get_type_id_value(target).unwrap_or(0);
+            return self.call(INSTANCE_OF_FUNCTION, vec![actual, IrValue::IntConst(target_id as i64, IrType::I32)], IrType::I1)
+                .expect("non-void call has a result");
+        }
+        let ids = self.subtype_ids(target);
+        self.any_id_matches(actual, ids)
+    }
+
+    /// 目标类（含子类）或接口的实现类的类型 ID
+    fn subtype_ids(&self, target: &str) -> Vec<i32> {
+        let Some(registry) = self.ctx.type_registry.as_ref() else { return Vec::new() };
+        let is_interface = registry.interface_exists(target);
+        let mut classes: Vec<&str> = registry.classes.values()
+            .filter(|c| if is_interface {
+                registry.implements_interface(&c.name, target)
+            } else {
+                self.ctx.is_subtype(&c.name, target)
+            })
+            .map(|c| c.name.as_str())
+            .collect();
+        classes.sort_unstable();
+        classes.into_iter().filter_map(|c| self.ctx.get_type_id_value(c)).collect()
+    }
+
+    fn any_id_matches(&mut self, actual: IrValue, ids: Vec<i32>) -> IrValue {
+        let mut result = IrValue::IntConst(0, IrType::I1);
+        for id in ids {
+            let is_match = self.compare(IrCmpOp::Eq, actual.clone(), IrValue::IntConst(id as i64, IrType::I32));
+            result = match result {
+                IrValue::IntConst(0, _) => is_match,
+                prev => self.binop(IrBinaryOp::Or, prev, is_match),
+            };
+        }
+        result
+    }
+
+    /// 分离编译的类型判断函数 `i1 __cay_instance_of(i32 actual, i32 target)`
+    ///
+    /// 入口单元能看到程序中的全部类型，在这里定义；其他单元只声明。
+    pub(super) fn build_instance_of_function(&mut self, define: bool) {
+        let params = vec![
+            IrParam { name: "actual".to_string(), ty: IrType::I32 },
+            IrParam { name: "target".to_string(), ty: IrType::I32 },
+        ];
+        let mut func = IrFunction::new(INSTANCE_OF_FUNCTION.to_string(), IrType::I1, params);
+        func.is_static = true;
+        if !define {
+            self.module.add_function(func.into_declaration());
+            return;
+        }
+
+        let mut targets: Vec<(String, i32)> = self.ctx.type_id_map.iter()
+            .map(|(name, info)| (name.clone(), info.type_id_value))
+            .collect();
+        targets.sort();
+        self.begin_function(func, "");
+        self.terminate(IrTerminator::Switch {
+            value: IrValue::Param("target".to_string(), IrType::I32),
+            default_target: "none".to_string(),
+            cases: targets.iter().enumerate()
+                .map(|(i, (_, id))| (IrValue::IntConst(*id as i64, IrType::I32), format!("target.{}", i)))
+                .collect(),
+            ty: IrType::I32,
+        });
+        for (i, (name, _)) in targets.iter().enumerate() {
+            self.func_mut().add_block(IrBasicBlock::new(format!("target.{}", i)));
+            let ids = self.subtype_ids(name);
+            let result = self.any_id_matches(IrValue::Param("actual".to_string(), IrType::I32), ids);
+            self.terminate(IrTerminator::Return { value: Some(result) });
+        }
+        self.func_mut().add_block(IrBasicBlock::new("none".to_string()));
+        self.terminate(IrTerminator::Return { value: Some(IrValue::IntConst(0, IrType::I1)) });
+        self.finish_function();
+    }
+
+    // ============================================================
+    // instanceof
+    // ============================================================
+
+    pub(super) fn build_instanceof(&mut self, instance_of: &InstanceOfExpr) -> cayResult<IrValue> {
+        let Type::Object(target_class) = &instance_of.target_type else {
+            return Err(codegen_error("instanceof target must be an object type".to_string()));
+        };
+        let value = self.build_expression(&instance_of.expr)?;
+        if !value.ir_type().is_pointer() {
+            return Ok(IrValue::IntConst(0, IrType::I1));
+        }
+        let object = self.convert(value, &i8_ptr());
+
+        let check_label = self.ctx.new_label("instanceof.check");
+        let end_label = self.ctx.new_label("instanceof.end");
+        let entry_label = self.open_block_label().unwrap_or_default();
+        let is_null = self.compare(IrCmpOp::Eq, object.clone(), IrValue::NullConst(i8_ptr()));
+        self.terminate(IrTerminator::ConditionalBranch {
+            condition: is_null,
+            true_target: end_label.clone(),
+            false_target: check_label.clone(),
+        });
+        self.start_block(&check_label);
+        let matches = self.type_id_matches(object, target_class);
+        let check_exit = self.open_block_label().unwrap_or_default();
+        self.start_block(&end_label);
+
+        let result = self.temp(IrType::I1);
+        self.emit(IrInstruction::Phi {
+            result: result.clone(),
+            ty: IrType::I1,
+            incoming: vec![
+                (IrValue::IntConst(0, IrType::I1), entry_label),
+                (matches, check_exit),
+            ],
+        });
+        Ok(result)
+    }
+
+    // ============================================================
+    // __cay_alloc / __cay_free
+    // ============================================================
+
+    /// `__cay_alloc(size)`：malloc 分配，指针作为 long 返回
+    pub(super) fn build_alloc(&mut self, alloc: &AllocExpr) -> cayResult<IrValue> {
+        let size = self.build_expression(&alloc.size)?;
+        let size = self.convert(size, &IrType::I64);
+        self.declare_c_function("malloc", i8_ptr(), vec![IrType::I64]);
+        let ptr = self.call("malloc", vec![size], i8_ptr()).expect("malloc returns a pointer");
+        Ok(self.cast(IrCastKind::PtrToInt, ptr, IrType::I64))
+    }
+
+    /// `__cay_free(ptr)`：把 long 转回指针后 free
+    pub(super) fn build_dealloc(&mut self, dealloc: &DeallocExpr) -> cayResult<IrValue> {
+        let ptr = self.build_expression(&dealloc.ptr)?;
+        let ptr = self.convert(ptr, &i8_ptr());
+        self.declare_c_function("free", IrType::Void, vec![i8_ptr()]);
+        self.call("free", vec![ptr], IrType::Void);
+        Ok(void_value())
+    }
+}
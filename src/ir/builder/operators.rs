@@ -0,0 +1,353 @@
+//! 二元和一元运算符
+//!
+//! 整数运算按较宽的操作数类型计算，浮点运算提升到 double；
+//! 开启 `-fcheck-overflow` 时 int/long 的 `+ - *`、取负、自增/自减和除法检查溢出。
+
+use super::IrBuilder;
+use crate::ast::*;
+use crate::codegen::runtime::PanicKind;
+use crate::error::{cayResult, codegen_error, SourceLocation};
+use crate::ir::types::IrType;
+use crate::ir::value::{IrBinaryOp, IrCmpOp, IrInstruction, IrTerminator, IrValue};
+
+impl IrBuilder {
+    pub(super) fn build_binary(&mut self, bin: &BinaryExpr) -> cayResult<IrValue> {
+        if matches!(bin.op, BinaryOp::And | BinaryOp::Or) {
+            return self.build_logical(bin);
+        }
+        let left = self.build_expression(&bin.left)?;
+        let right = self.build_expression(&bin.right)?;
+        self.binary_values(bin.op, left, right, &bin.loc)
+    }
+
+    /// `&&` / `||`：短路求值，结果由 phi 合并
+    fn build_logical(&mut self, bin: &BinaryExpr) -> cayResult<IrValue> {
+        let is_and = bin.op == BinaryOp::And;
+        let rhs_label = self.ctx.new_label(if is_and { "and.rhs" } else { "or.rhs" });
+        let end_label = self.ctx.new_label(if is_and { "and.end" } else { "or.end" });
+
+        let left = self.build_expression(&bin.left)?;
+        let left = self.as_condition(left);
+        let left_exit = self.open_block_label();
+        let (true_target, false_target) = if is_and {
+            (rhs_label.clone(), end_label.clone())
+        } else {
+            (end_label.clone(), rhs_label.clone())
+        };
+        self.terminate(IrTerminator::ConditionalBranch { condition: left, true_target, false_target });
+
+        self.start_block(&rhs_label);
+        let right = self.build_expression(&bin.right)?;
+        let right = self.as_condition(right);
+        let right_exit = self.open_block_label();
+        self.start_block(&end_label);
+
+        let mut incoming = Vec::new();
+        if let Some(label) = left_exit {
+            incoming.push((IrValue::IntConst(!is_and as i64, IrType::I1), label));
+        }
+        if let Some(label) = right_exit {
+            incoming.push((right, label));
+        }
+        let result = self.temp(IrType::I1);
+        self.emit(IrInstruction::Phi { result: result.clone(), ty: IrType::I1, incoming });
+        Ok(result)
+    }
+
+    /// 对两个已求值的操作数应用二元运算符（复合赋值也使用）
+    pub(super) fn binary_values(&mut self, op: BinaryOp, left: IrValue, right: IrValue, loc: &SourceLocation) -> cayResult<IrValue> {
+        let (lt, rt) = (left.ir_type(), right.ir_type());
+        match op {
+            BinaryOp::Add if is_string(&lt) || is_string(&rt) => {
+                if !is_string(&lt) && !is_stringifiable(&lt) || !is_string(&rt) && !is_stringifiable(&rt) {
+                    return Err(codegen_error(format!("Unsupported addition types: {} and {}", lt, rt)));
+                }
+                let left = self.value_to_string(left);
+                let right = self.value_to_string(right);
+                Ok(self.call("__cay_string_concat", vec![left, right], super::i8_ptr()).expect("non-void"))
+            }
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => {
+                let (l, r, ty) = self.promote(left, right).ok_or_else(|| codegen_error(format!(
+                    "Unsupported {} types: {} and {}", arith_name(op), lt, rt
+                )))?;
+                if ty.is_float() {
+                    let fop = match op {
+                        BinaryOp::Add => IrBinaryOp::FAdd,
+                        BinaryOp::Sub => IrBinaryOp::FSub,
+                        BinaryOp::Mul => IrBinaryOp::FMul,
+                        _ => IrBinaryOp::FDiv,
+                    };
+                    return Ok(self.binop(fop, l, r));
+                }
+                match op {
+                    BinaryOp::Add => Ok(self.int_arith(IrBinaryOp::Add, l, r, loc)),
+                    BinaryOp::Sub => Ok(self.int_arith(IrBinaryOp::Sub, l, r, loc)),
+                    BinaryOp::Mul => Ok(self.int_arith(IrBinaryOp::Mul, l, r, loc)),
+                    _ => {
+                        self.div_zero_check(&r, loc);
+                        self.division_overflow_check(&l, &r, loc);
+                        Ok(self.binop(IrBinaryOp::Div, l, r))
+                    }
+                }
+            }
+            BinaryOp::Mod => {
+                if !lt.is_integer() || !rt.is_integer() {
+                    return Err(codegen_error(format!("Unsupported modulo types: {} and {}", lt, rt)));
+                }
+                let (l, r, _) = self.promote(left, right).expect("integer operands");
+                self.div_zero_check(&r, loc);
+                Ok(self.binop(IrBinaryOp::Mod, l, r))
+            }
+            BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+                self.comparison(op, left, right)
+            }
+            BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor | BinaryOp::Shl | BinaryOp::Shr | BinaryOp::UnsignedShr => {
+                if !lt.is_integer() || !rt.is_integer() {
+                    return Err(codegen_error(format!("{} requires integer operands, got {} and {}", bitwise_name(op), lt, rt)));
+                }
+                let (l, r, _) = self.promote(left, right).expect("integer operands");
+                let iop = match op {
+                    BinaryOp::BitAnd => IrBinaryOp::And,
+                    BinaryOp::BitOr => IrBinaryOp::Or,
+                    BinaryOp::BitXor => IrBinaryOp::Xor,
+                    BinaryOp::Shl => IrBinaryOp::Shl,
+                    BinaryOp::Shr => IrBinaryOp::Shr,
+                    _ => IrBinaryOp::LShr,
+                };
+                Ok(self.binop(iop, l, r))
+            }
+            BinaryOp::And | BinaryOp::Or => {
+                let l = self.as_condition(left);
+                let r = self.as_condition(right);
+                let iop = if op == BinaryOp::And { IrBinaryOp::And } else { IrBinaryOp::Or };
+                Ok(self.binop(iop, l, r))
+            }
+        }
+    }
+
+    fn comparison(&mut self, op: BinaryOp, left: IrValue, right: IrValue) -> cayResult<IrValue> {
+        let (lt, rt) = (left.ir_type(), right.ir_type());
+        if matches!(op, BinaryOp::Eq | BinaryOp::Ne) && (lt.is_pointer() || rt.is_pointer()) {
+            // 指针与指针或与 null 比较
+            let ptr_ty = if lt.is_pointer() { lt.clone() } else { rt.clone() };
+            let (l, r) = match (self.try_convert(left, &ptr_ty), self.try_convert(right, &ptr_ty)) {
+                (Some(l), Some(r)) => (l, r),
+                _ => return Err(codegen_error(format!("Unsupported {} comparison types: {} and {}", compare_name(op), lt, rt))),
+            };
+            let cmp = if op == BinaryOp::Eq { IrCmpOp::Eq } else { IrCmpOp::Ne };
+            return Ok(self.compare(cmp, l, r));
+        }
+        let (l, r, ty) = self.promote(left, right).ok_or_else(|| codegen_error(format!(
+            "Unsupported {} comparison types: {} and {}", compare_name(op), lt, rt
+        )))?;
+        let cmp = match (op, ty.is_float()) {
+            (BinaryOp::Eq, false) => IrCmpOp::Eq,
+            (BinaryOp::Ne, false) => IrCmpOp::Ne,
+            (BinaryOp::Lt, false) => IrCmpOp::Slt,
+            (BinaryOp::Le, false) => IrCmpOp::Sle,
+            (BinaryOp::Gt, false) => IrCmpOp::Sgt,
+            (BinaryOp::Ge, false) => IrCmpOp::Sge,
+            (BinaryOp::Eq, true) => IrCmpOp::FEq,
+            (BinaryOp::Ne, true) => IrCmpOp::FNe,
+            (BinaryOp::Lt, true) => IrCmpOp::FLt,
+            (BinaryOp::Le, true) => IrCmpOp::FLe,
+            (BinaryOp::Gt, true) => IrCmpOp::FGt,
+            _ => IrCmpOp::FGe,
+        };
+        Ok(self.compare(cmp, l, r))
+    }
+
+    /// 算术类型提升：整数取较宽者，浮点取 double（两个 float 保持 float），整数与浮点混合时转为浮点
+    pub(super) fn promote(&mut self, left: IrValue, right: IrValue) -> Option<(IrValue, IrValue, IrType)> {
+        let (lt, rt) = (left.ir_type(), right.ir_type());
+        let ty = match (&lt, &rt) {
+            (l, r) if l.is_integer() && r.is_integer() => {
+                if l.bit_width() >= r.bit_width() { l.clone() } else { r.clone() }
+            }
+            (l, r) if l.is_float() && r.is_float() => {
+                if *l == IrType::F64 || *r == IrType::F64 { IrType::F64 } else { IrType::F32 }
+            }
+            (l, r) if l.is_float() && r.is_integer() => l.clone(),
+            (l, r) if l.is_integer() && r.is_float() => r.clone(),
+            _ => return None,
+        };
+        let l = self.try_convert(left, &ty)?;
+        let r = self.try_convert(right, &ty)?;
+        Some((l, r, ty))
+    }
+
+    pub(super) fn binop(&mut self, op: IrBinaryOp, left: IrValue, right: IrValue) -> IrValue {
+        let result = self.temp(left.ir_type());
+        self.emit(IrInstruction::BinaryOp { result: result.clone(), op, left, right });
+        result
+    }
+
+    /// 整数 `+ - *`：开启 `-fcheck-overflow` 且为 int/long 时检查溢出
+    pub(super) fn int_arith(&mut self, op: IrBinaryOp, left: IrValue, right: IrValue, loc: &SourceLocation) -> IrValue {
+        if self.ctx.overflow_checks_enabled() && is_checked(&left.ir_type()) {
+            self.checked_int_op(op, left, right, loc)
+        } else {
+            self.binop(op, left, right)
+        }
+    }
+
+    /// 通过 `llvm.s{add,sub,mul}.with.overflow` 计算，溢出时 panic
+    pub(super) fn checked_int_op(&mut self, op: IrBinaryOp, left: IrValue, right: IrValue, loc: &SourceLocation) -> IrValue {
+        let ty = left.ir_type();
+        let intrinsic = match op {
+            IrBinaryOp::Add => "sadd",
+            IrBinaryOp::Sub => "ssub",
+            _ => "smul",
+        };
+        let pair_ty = IrType::Raw(format!("{{{}, i1}}", ty));
+        let name = format!("llvm.{}.with.overflow.{}", intrinsic, ty);
+        let pair = self.call(&name, vec![left, right], pair_ty).expect("non-void");
+        let value = self.temp(ty);
+        self.emit(IrInstruction::ExtractValue { result: value.clone(), aggregate: pair.clone(), index: 0 });
+        let overflowed = self.temp(IrType::I1);
+        self.emit(IrInstruction::ExtractValue { result: overflowed.clone(), aggregate: pair, index: 1 });
+        self.panic_if(overflowed, PanicKind::IntegerOverflow, loc, "overflow");
+        value
+    }
+
+    /// `-fcheck-overflow` 下检查 MIN / -1（结果无法表示）
+    fn division_overflow_check(&mut self, left: &IrValue, right: &IrValue, loc: &SourceLocation) {
+        let ty = left.ir_type();
+        if !self.ctx.overflow_checks_enabled() || !is_checked(&ty) {
+            return;
+        }
+        let min = if ty == IrType::I32 { i32::MIN as i64 } else { i64::MIN };
+        let is_min = self.compare(IrCmpOp::Eq, left.clone(), IrValue::IntConst(min, ty.clone()));
+        let is_neg_one = self.compare(IrCmpOp::Eq, right.clone(), IrValue::IntConst(-1, ty));
+        let overflowed = self.binop(IrBinaryOp::And, is_min, is_neg_one);
+        self.panic_if(overflowed, PanicKind::IntegerOverflow, loc, "overflow");
+    }
+
+    /// 把值转换为字符串（字符串拼接和格式化输出使用）
+    pub(super) fn value_to_string(&mut self, value: IrValue) -> IrValue {
+        let ty = value.ir_type();
+        let (func, arg_ty) = match &ty {
+            t if is_string(t) => return value,
+            IrType::I8 => ("__cay_char_to_string", IrType::I8),
+            IrType::I1 => ("__cay_bool_to_string", IrType::I1),
+            IrType::I64 => ("__cay_long_to_string", IrType::I64),
+            t if t.is_integer() => ("__cay_int_to_string", IrType::I32),
+            IrType::F32 => ("__cay_float_to_string", IrType::F32),
+            IrType::F64 => ("__cay_double_to_string", IrType::F64),
+            _ => return self.convert(value, &super::i8_ptr()),
+        };
+        let arg = self.convert(value, &arg_ty);
+        self.call(func, vec![arg], super::i8_ptr()).expect("non-void")
+    }
+
+    // ============================================================
+    // 一元运算符
+    // ============================================================
+
+    pub(super) fn build_unary(&mut self, unary: &UnaryExpr) -> cayResult<IrValue> {
+        match unary.op {
+            UnaryOp::Neg => {
+                let operand = self.build_expression(&unary.operand)?;
+                let ty = operand.ir_type();
+                if ty.is_float() {
+                    let zero = IrValue::FloatConst(-0.0, ty);
+                    return Ok(self.binop(IrBinaryOp::FSub, zero, operand));
+                }
+                if !ty.is_integer() {
+                    return Err(codegen_error(format!("Cannot negate value of type {}", ty)));
+                }
+                Ok(self.int_arith(IrBinaryOp::Sub, IrValue::IntConst(0, ty), operand, &unary.loc))
+            }
+            UnaryOp::Not => {
+                let operand = self.build_expression(&unary.operand)?;
+                let value = self.as_condition(operand);
+                Ok(self.binop(IrBinaryOp::Xor, value, IrValue::IntConst(1, IrType::I1)))
+            }
+            UnaryOp::BitNot => {
+                let operand = self.build_expression(&unary.operand)?;
+                let ty = operand.ir_type();
+                if !ty.is_integer() {
+                    return Err(codegen_error("Bitwise NOT not supported for floating point".to_string()));
+                }
+                Ok(self.binop(IrBinaryOp::Xor, operand, IrValue::IntConst(-1, ty)))
+            }
+            UnaryOp::PreInc | UnaryOp::PreDec | UnaryOp::PostInc | UnaryOp::PostDec => {
+                let is_inc = matches!(unary.op, UnaryOp::PreInc | UnaryOp::PostInc);
+                let is_pre = matches!(unary.op, UnaryOp::PreInc | UnaryOp::PreDec);
+                let (ptr, ty) = self.build_lvalue(&unary.operand)?;
+                let old = self.load(ptr.clone(), ty.clone());
+                let new = if ty.is_float() {
+                    let op = if is_inc { IrBinaryOp::FAdd } else { IrBinaryOp::FSub };
+                    self.binop(op, old.clone(), IrValue::FloatConst(1.0, ty.clone()))
+                } else {
+                    let op = if is_inc { IrBinaryOp::Add } else { IrBinaryOp::Sub };
+                    self.int_arith(op, old.clone(), IrValue::IntConst(1, ty.clone()), &unary.loc)
+                };
+                self.store(new.clone(), ptr);
+                Ok(if is_pre { new } else { old })
+            }
+            UnaryOp::AddressOf => {
+                // 对象头 8 字节（type_id + 填充），传给 FFI 时指向数据部分
+                let (ptr, ty) = self.build_lvalue(&unary.operand)?;
+                let value = self.load(ptr, ty.clone());
+                if ty == super::i8_ptr() {
+                    return Ok(self.byte_offset(value, 8));
+                }
+                Ok(value)
+            }
+            UnaryOp::Deref => {
+                let operand = self.build_expression(&unary.operand)?;
+                match operand.ir_type().pointee().cloned() {
+                    Some(elem) => Ok(self.load(operand, elem)),
+                    None => Err(codegen_error(format!("Cannot dereference non-pointer type: {}", operand.ir_type()))),
+                }
+            }
+        }
+    }
+}
+
+/// 字符串（`i8*`）
+fn is_string(ty: &IrType) -> bool {
+    *ty == super::i8_ptr()
+}
+
+/// 可以通过运行时函数转换为字符串的类型
+fn is_stringifiable(ty: &IrType) -> bool {
+    ty.is_integer() || ty.is_float()
+}
+
+/// 需要溢出检查的整数类型（int/long）
+fn is_checked(ty: &IrType) -> bool {
+    matches!(ty, IrType::I32 | IrType::I64)
+}
+
+fn arith_name(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "addition",
+        BinaryOp::Sub => "subtraction",
+        BinaryOp::Mul => "multiplication",
+        _ => "division",
+    }
+}
+
+fn compare_name(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Eq => "equality",
+        BinaryOp::Ne => "inequality",
+        BinaryOp::Lt => "less-than",
+        BinaryOp::Le => "less-or-equal",
+        BinaryOp::Gt => "greater-than",
+        _ => "greater-than-or-equal",
+    }
+}
+
+fn bitwise_name(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::BitAnd => "Bitwise AND",
+        BinaryOp::BitOr => "Bitwise OR",
+        BinaryOp::BitXor => "Bitwise XOR",
+        BinaryOp::Shl => "Shift left",
+        BinaryOp::Shr => "Arithmetic shift right",
+        _ => "Unsigned shift right",
+    }
+}
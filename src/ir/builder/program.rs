@@ -0,0 +1,1030 @@
+//! 程序级构建：运行时前导、全局变量、extern 声明、类成员函数、接口分派和 C 入口
+//!
+//! 分离编译（`set_imports`）时只为本单元的代码生成函数定义，依赖单元中的函数只声明；
+//! 接口分派函数和类型判断函数在入口单元中定义，C 入口按依赖顺序调用各类的 `__class_init`。
+//! 库输出（`set_library_exports`）时没有 C 入口，由库初始化函数完成静态初始化，导出函数经 C ABI 包装函数调用。
+
+use super::{IrBuilder, i8_ptr};
+use crate::cabi::ExportedFunction;
+use crate::codegen::runtime::PanicKind;
+use crate::ast::*;
+use crate::error::{cayResult, codegen_error};
+use crate::ir::block::IrBasicBlock;
+use crate::ir::function::{IrFunction, IrLinkage, IrParam};
+use crate::ir::module::{IrExternDecl, IrGlobal, IrGlobalLinkage, IrTypeDecl};
+use crate::ir::types::IrType;
+use crate::ir::value::{IrCmpOp, IrInstruction, IrTerminator, IrValue};
+use crate::semantic::EscapeAnalysis;
+use crate::types::{ParameterInfo, Type};
+use crate::unit::needs_class_init;
+use std::collections::{HashMap, HashSet};
+
+/// 运行时模块已经定义的函数，同名 extern 声明不再输出
+const RUNTIME_FUNCTIONS: &[&str] = &[
+    "__cay_memcpy_byte",
+    "__cay_memset_byte",
+    "__cay_write_int",
+    "__cay_read_int",
+    "__cay_string_concat",
+    "__cay_int_to_string",
+    "__cay_float_to_string",
+    "__cay_double_to_string",
+    "__cay_char_to_string",
+];
+
+/// 库初始化函数（静态初始化），由 `llvm.global_ctors` 调用
+const LIBRARY_INIT: &str = "__cay_lib_init";
+
+/// 程序入口
+enum EntryPoint {
+    /// 顶层 `main` 函数
+    TopLevel(TopLevelFunction),
+    /// 类中的 `public static main` 方法
+    Method(String, MethodDecl),
+}
+
+impl IrBuilder {
+    /// 构建整个程序（顺序与旧代码生成器一致）
+    pub(super) fn build_program(&mut self, program: &Program, source_file: &str) -> cayResult<()> {
+        // 可空标记只在语义检查中使用
+        let erased = program.erase_nullability();
+        let program = &erased;
+        // 分离编译时布局、静态字段、类型 ID 和分派还需要依赖单元中声明的类型
+        let imported = self.imports.as_ref().map(Program::erase_nullability);
+        let merged = imported.clone().map(|mut all| {
+            all.append(program.clone());
+            all
+        });
+        let all = merged.as_ref().unwrap_or(program);
+
+        self.ctx.source_file = source_file.to_string();
+        self.escape = EscapeAnalysis::new(program).analyze();
+        self.build_runtime_prelude();
+
+        self.ctx.set_extern_declarations(all.extern_declarations.clone());
+        self.ctx.top_level_functions = all.top_level_functions.clone();
+        for type_alias in &all.type_aliases {
+            self.ctx.type_aliases.insert(type_alias.name.clone(), type_alias.target_type.clone());
+        }
+
+        self.compute_class_layouts(all);
+        if self.debug_info {
+            self.build_class_debug_types();
+        }
+        for class in &all.classes {
+            self.ctx.collect_static_fields(class)?;
+        }
+        let entry = Self::find_entry_point(program);
+
+        self.build_static_field_globals(program);
+        self.ctx.register_type_identifiers(all);
+        if !self.ctx.whole_program {
+            self.assign_stable_type_ids()?;
+        }
+        self.build_type_id_globals();
+
+        for extern_decl in &all.extern_declarations {
+            for func in &extern_decl.functions {
+                self.build_extern_function(extern_decl.calling_convention, func);
+            }
+        }
+        if let Some(imported) = &imported {
+            self.declare_imported_functions(imported);
+        }
+        for func in &program.top_level_functions {
+            self.build_top_level_function(func)?;
+        }
+        for class in &program.classes {
+            self.build_class(class)?;
+        }
+        // 库和可执行文件一样是最终链接的模块，分派函数在这里定义
+        let library = self.library_exports.take();
+        let is_root = entry.is_some() || library.is_some();
+        self.build_interfaces(program, all, is_root)?;
+        if !self.ctx.whole_program {
+            self.build_instance_of_function(is_root);
+        }
+        match (library, entry) {
+            (Some(exports), _) => self.build_library(all, &exports)?,
+            (None, Some(entry)) => self.build_c_main(all, &entry)?,
+            (None, None) => {}
+        }
+
+        // 字符串常量：名称 -> 内容
+        self.module.string_constants = self.ctx.global_strings.iter()
+            .map(|(content, name)| (name.clone(), content.clone()))
+            .collect();
+        Ok(())
+    }
+
+    /// 运行时前导（C 库声明和运行时函数）作为文本整体放入模块
+    fn build_runtime_prelude(&mut self) {
+        let triple = self.ctx.platform_target_triple();
+        self.module.target_triple = triple.clone();
+        // 前导代码不对应任何源位置
+        let source_file = std::mem::take(&mut self.ctx.source_file);
+        self.ctx.output.clear();
+        self.ctx.emit_runtime_prelude(&triple);
+        let prelude = std::mem::take(&mut self.ctx.output);
+        self.module.runtime_prelude = if self.ctx.whole_program { prelude } else { link_once_prelude(&prelude) };
+        self.ctx.source_file = source_file;
+    }
+
+    /// 计算所有类的实例布局（父类先于子类）
+    fn compute_class_layouts(&mut self, program: &Program) {
+        let classes: HashMap<&str, &ClassDecl> = program.classes.iter()
+            .map(|c| (c.name.as_str(), c))
+            .collect();
+        let mut computed = HashSet::new();
+        for class in &program.classes {
+            // 沿继承链收集尚未计算的类，从最顶层的父类开始计算
+            let mut chain = Vec::new();
+            let mut current = Some(class);
+            while let Some(c) = current {
+                if computed.contains(&c.name) || chain.iter().any(|p: &&ClassDecl| p.name == c.name) {
+                    break;
+                }
+                chain.push(c);
+                current = c.parent.as_deref().and_then(|p| classes.get(p).copied());
+            }
+            for c in chain.into_iter().rev() {
+                let fields: Vec<FieldDecl> = c.members.iter()
+                    .filter_map(|m| match m {
+                        ClassMember::Field(f) => Some(f.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                self.ctx.compute_class_layout(&c.name, &fields, c.parent.as_deref());
+                computed.insert(c.name.clone());
+            }
+        }
+    }
+
+    /// 类实例布局作为 `class.X` 类型声明（调试信息的 `DICompositeType`）
+    ///
+    /// 字段按偏移排列，对象头和对齐空隙写成名称为空的 `[N x i8]`，使自然对齐下的偏移与布局一致。
+    fn build_class_debug_types(&mut self) {
+        let mut classes: Vec<String> = self.ctx.class_layouts.keys().cloned().collect();
+        classes.sort();
+        for class in classes {
+            let layout = self.ctx.class_layouts[&class].clone();
+            let mut fields: Vec<_> = layout.fields.values().collect();
+            fields.sort_by_key(|f| f.offset);
+            let mut members = Vec::new();
+            let mut offset = 0;
+            for field in fields {
+                if field.offset > offset {
+                    members.push((String::new(), IrType::Array(Box::new(IrType::I8), field.offset - offset)));
+                }
+                let ty = self.debug_type(&field.field_type);
+                offset = field.offset + ty.size_bytes(&self.ctx.target);
+                members.push((field.name.clone(), ty));
+            }
+            if layout.total_size > offset {
+                members.push((String::new(), IrType::Array(Box::new(IrType::I8), layout.total_size - offset)));
+            }
+            self.module.add_type_decl(IrTypeDecl { name: format!("class.{}", class), fields: members });
+        }
+    }
+
+    /// 选择程序入口：顶层 main 优先，其次是 `main` 修饰的类，最后是第一个有 main 方法的类
+    fn find_entry_point(program: &Program) -> Option<EntryPoint> {
+        if let Some(func) = program.top_level_functions.iter().find(|f| f.name == "main") {
+            return Some(EntryPoint::TopLevel(func.clone()));
+        }
+        let mut fallback = None;
+        for class in &program.classes {
+            for member in &class.members {
+                match member {
+                    ClassMember::Method(method) if method.name == "main"
+                        && method.modifiers.contains(&Modifier::Public)
+                        && method.modifiers.contains(&Modifier::Static) => {
+                        if class.modifiers.contains(&Modifier::Main) {
+                            return Some(EntryPoint::Method(class.name.clone(), method.clone()));
+                        }
+                        if fallback.is_none() {
+                            fallback = Some(EntryPoint::Method(class.name.clone(), method.clone()));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        fallback
+    }
+
+    // ============================================================
+    // 全局变量与 extern 声明
+    // ============================================================
+
+    /// 静态字段全局变量；常量初始化器直接作为全局初始值
+    ///
+    /// 分离编译时本单元的类的静态字段对其他单元可见，依赖单元的类的静态字段只声明。
+    fn build_static_field_globals(&mut self, program: &Program) {
+        let own_classes: HashSet<&str> = program.classes.iter().map(|c| c.name.as_str()).collect();
+        for field in self.ctx.static_fields.clone() {
+            let ty = IrType::from_llvm_str(&field.llvm_type);
+            let linkage = if self.ctx.whole_program {
+                IrGlobalLinkage::Private
+            } else if own_classes.contains(field.class_name.as_str()) {
+                IrGlobalLinkage::External
+            } else {
+                IrGlobalLinkage::Declare
+            };
+            let initializer = field.initializer.as_ref()
+                .filter(|_| linkage != IrGlobalLinkage::Declare)
+                .and_then(|init| self.const_initializer(init, &ty));
+            self.module.add_global(IrGlobal {
+                name: field.name.clone(),
+                ty,
+                initializer,
+                is_constant: false,
+                linkage,
+            });
+        }
+    }
+
+    /// 求值静态字段的常量初始化器
+    fn const_initializer(&self, expr: &Expr, ty: &IrType) -> Option<IrValue> {
+        let int_value = match expr {
+            Expr::Literal(LiteralValue::Float32(f)) if ty.is_float() => return Some(IrValue::FloatConst(*f as f64, ty.clone())),
+            Expr::Literal(LiteralValue::Float64(f)) if ty.is_float() => return Some(IrValue::FloatConst(*f, ty.clone())),
+            Expr::Literal(LiteralValue::Bool(b)) => *b as i64,
+            Expr::Literal(LiteralValue::Char(c)) => *c as i64,
+            _ => self.ctx.evaluate_const_int(expr)?,
+        };
+        if ty.is_float() {
+            return Some(IrValue::FloatConst(int_value as f64, ty.clone()));
+        }
+        // 非 final 静态字段的常量初始化按字段类型回绕（static final 溢出已在语义分析中报错）
+        let value = match ty.bit_width()? {
+            1 => int_value & 1,
+            8 => int_value as i8 as i64,
+            16 => int_value as i16 as i64,
+            32 => int_value as i32 as i64,
+            _ => int_value,
+        };
+        Some(IrValue::IntConst(value, ty.clone()))
+    }
+
+    /// 类型 ID 常量（按类型名排序，保证输出稳定）
+    fn build_type_id_globals(&mut self) {
+        let mut type_ids: Vec<(String, i32)> = self.ctx.type_id_map.iter()
+            .map(|(name, info)| (name.clone(), info.type_id_value))
+            .collect();
+        type_ids.sort();
+        for (name, value) in type_ids {
+            self.module.add_global(IrGlobal {
+                name: format!("@__type_id_{}", name),
+                ty: IrType::I32,
+                initializer: Some(IrValue::IntConst(value as i64, IrType::I32)),
+                is_constant: true,
+                linkage: if self.ctx.whole_program { IrGlobalLinkage::Private } else { IrGlobalLinkage::LinkOnceOdr },
+            });
+        }
+    }
+
+    /// 分离编译时类型 ID 由类型名哈希得到，各单元独立编译也一致
+    fn assign_stable_type_ids(&mut self) -> cayResult<()> {
+        let mut names: Vec<String> = self.ctx.type_id_map.keys().cloned().collect();
+        names.sort();
+        let mut assigned: HashMap<i32, String> = HashMap::new();
+        for name in names {
+            let id = crate::unit::stable_type_id(&name);
+            if let Some(other) = assigned.insert(id, name.clone()) {
+                return Err(codegen_error(format!(
+                    "Type id collision between '{}' and '{}' in separate compilation; rename one of the types",
+                    other, name
+                )));
+            }
+            if let Some(info) = self.ctx.type_id_map.get_mut(&name) {
+                info.type_id_value = id;
+            }
+        }
+        Ok(())
+    }
+
+    fn build_extern_function(&mut self, calling_conv: CallingConvention, func: &ExternFunction) {
+        if RUNTIME_FUNCTIONS.contains(&func.name.as_str()) {
+            return;
+        }
+        let ret = self.ctx.type_to_llvm(&func.return_type);
+        let param_strs: Vec<String> = func.params.iter()
+            .map(|p| if p.is_varargs { "...".to_string() } else { self.ctx.type_to_llvm(&p.param_type) })
+            .collect();
+        let signature = if param_strs.is_empty() {
+            format!("{}@{}@void", func.name, ret)
+        } else {
+            format!("{}@{}@{}", func.name, ret, param_strs.join("@"))
+        };
+        if self.ctx.is_extern_emitted(&signature) {
+            return;
+        }
+        self.ctx.mark_extern_emitted(signature);
+
+        self.module.add_extern(IrExternDecl {
+            name: func.name.clone(),
+            return_type: IrType::from_llvm_str(&ret),
+            params: func.params.iter()
+                .filter(|p| !p.is_varargs)
+                .map(|p| (p.name.clone(), self.ir_type(&p.param_type)))
+                .collect(),
+            calling_convention: Some(self.ctx.calling_convention_to_llvm_attr(calling_conv)),
+            is_varargs: func.params.iter().any(|p| p.is_varargs),
+        });
+    }
+
+    // ============================================================
+    // 函数
+    // ============================================================
+
+    /// 在当前函数中为参数分配槽位并保存参数值
+    pub(super) fn bind_param(&mut self, param: &ParameterInfo, incoming: &str) {
+        let slot = if param.is_varargs {
+            // 可变参数以 i8* 传入，局部变量使用数组类型
+            let elem = match &param.param_type {
+                Type::Array(elem) => self.ctx.type_to_llvm(elem),
+                other => self.ctx.type_to_llvm(other),
+            };
+            let array_ty = format!("{}*", elem);
+            let llvm_name = self.ctx.scope_manager.declare_var_with_flag(&param.name, &array_ty, true);
+            let ty = IrType::from_llvm_str(&array_ty);
+            let slot = self.alloca(&llvm_name, ty.clone());
+            let value = self.bitcast(IrValue::Param(incoming.to_string(), i8_ptr()), ty);
+            self.store(value, slot.clone());
+            self.ctx.var_types.insert(param.name.clone(), array_ty);
+            slot
+        } else {
+            let llvm_type = self.ctx.type_to_llvm(&param.param_type);
+            let llvm_name = self.ctx.scope_manager.declare_var_with_flag(&param.name, &llvm_type, true);
+            let ty = IrType::from_llvm_str(&llvm_type);
+            let slot = self.alloca(&llvm_name, ty.clone());
+            self.store(IrValue::Param(incoming.to_string(), ty), slot.clone());
+            self.ctx.var_types.insert(param.name.clone(), llvm_type);
+            slot
+        };
+        self.declare_debug_variable(&param.name, slot, &param.param_type);
+        self.ctx.var_cay_types.insert(param.name.clone(), param.param_type.clone());
+        if let Type::Object(class_name) = &param.param_type {
+            self.ctx.var_class_map.insert(param.name.clone(), class_name.clone());
+        }
+    }
+
+    /// 声明 `this` 局部变量并保存 `%this` 参数
+    fn bind_this(&mut self) {
+        let llvm_name = self.ctx.scope_manager.declare_var("this", "i8*");
+        let slot = self.alloca(&llvm_name, i8_ptr());
+        self.store(IrValue::Param("this".to_string(), i8_ptr()), slot.clone());
+        self.ctx.var_types.insert("this".to_string(), "i8*".to_string());
+        let class = Type::Object(self.ctx.current_class.clone());
+        self.declare_debug_variable("this", slot, &class);
+    }
+
+    fn param_ir_type(&self, param: &ParameterInfo) -> IrType {
+        if param.is_varargs { i8_ptr() } else { self.ir_type(&param.param_type) }
+    }
+
+    /// 顶层函数的函数头（声明和定义共用）
+    fn top_level_function_header(&self, func: &TopLevelFunction) -> IrFunction {
+        let fn_name = self.ctx.generate_top_level_function_name(&func.name);
+        let params = func.params.iter()
+            .map(|p| IrParam { name: format!("{}.param", p.name), ty: self.ir_type(&p.param_type) })
+            .collect();
+        let mut ir_func = IrFunction::new(fn_name, self.ir_type(&func.return_type), params);
+        ir_func.is_static = true;
+        ir_func
+    }
+
+    fn build_top_level_function(&mut self, func: &TopLevelFunction) -> cayResult<()> {
+        let ir_func = self.top_level_function_header(func);
+        self.begin_function(ir_func, "");
+        self.ctx.current_param_order = func.params.iter().map(|p| p.name.clone()).collect();
+        self.set_location(&func.loc);
+
+        self.frame_push(&func.name);
+        for param in &func.params {
+            let incoming = format!("{}.param", param.name);
+            self.bind_param(&ParameterInfo { is_varargs: false, ..param.clone() }, &incoming);
+        }
+        self.build_block(&func.body)?;
+        if func.return_type == Type::Void {
+            self.frame_pop();
+            self.terminate(IrTerminator::Return { value: None });
+        }
+        self.finish_function();
+        Ok(())
+    }
+
+    fn build_class(&mut self, class: &ClassDecl) -> cayResult<()> {
+        let has_explicit_ctor = class.members.iter().any(|m| matches!(m, ClassMember::Constructor(_)));
+        for member in &class.members {
+            match member {
+                ClassMember::Method(method) => {
+                    // native 和 abstract 方法没有方法体
+                    if !method.modifiers.contains(&Modifier::Native)
+                        && !method.modifiers.contains(&Modifier::Abstract) {
+                        let fn_name = self.ctx.generate_method_name(&class.name, method);
+                        self.build_method(&class.name, method, fn_name)?;
+                    }
+                }
+                ClassMember::Constructor(ctor) => self.build_constructor(&class.name, ctor)?,
+                ClassMember::Destructor(dtor) => self.build_destructor(&class.name, dtor)?,
+                ClassMember::StaticInitializer(block) => self.build_static_initializer(&class.name, block)?,
+                ClassMember::Field(_) | ClassMember::InstanceInitializer(_) => {}
+            }
+        }
+        if !has_explicit_ctor {
+            self.build_default_constructor(&class.name);
+        }
+        if !self.ctx.whole_program && needs_class_init(class) {
+            self.build_class_init(class)?;
+        }
+        Ok(())
+    }
+
+    /// 方法的函数头（声明和定义共用）
+    fn method_header(&self, class_name: &str, method: &MethodDecl, fn_name: String) -> IrFunction {
+        let is_static = method.modifiers.contains(&Modifier::Static);
+        let mut params = Vec::new();
+        if !is_static {
+            params.push(IrParam { name: "this".to_string(), ty: i8_ptr() });
+        }
+        for param in &method.params {
+            params.push(IrParam { name: format!("{}.{}", class_name, param.name), ty: self.param_ir_type(param) });
+        }
+        let mut ir_func = IrFunction::new(fn_name, self.ir_type(&method.return_type), params);
+        ir_func.is_static = is_static;
+        ir_func
+    }
+
+    /// 以指定的函数名构建方法（接口 default 方法体使用独立的函数名）
+    fn build_method(&mut self, class_name: &str, method: &MethodDecl, fn_name: String) -> cayResult<()> {
+        let is_static = method.modifiers.contains(&Modifier::Static);
+        let ir_func = self.method_header(class_name, method, fn_name);
+        self.begin_function(ir_func, class_name);
+        self.ctx.current_param_order = method.params.iter().map(|p| p.name.clone()).collect();
+        self.set_location(&method.loc);
+
+        self.frame_push(&format!("{}.{}", class_name, method.name));
+        self.ctx.scope_manager.enter_scope();
+        if !is_static {
+            self.bind_this();
+        }
+        for param in &method.params {
+            self.bind_param(param, &format!("{}.{}", class_name, param.name));
+        }
+        if let Some(body) = &method.body {
+            self.set_location(&body.loc);
+            self.build_block(body)?;
+        }
+        if method.return_type == Type::Void {
+            self.frame_pop();
+            self.terminate(IrTerminator::Return { value: None });
+        }
+        self.ctx.scope_manager.exit_scope();
+        self.finish_function();
+        Ok(())
+    }
+
+    /// 构造函数的函数头（声明和定义共用）
+    fn constructor_header(&self, class_name: &str, ctor: &ConstructorDecl) -> IrFunction {
+        let fn_name = if ctor.params.is_empty() {
+            format!("{}.__ctor", class_name)
+        } else {
+            let sigs: Vec<String> = ctor.params.iter().map(|p| self.ctx.type_to_signature(&p.param_type)).collect();
+            format!("{}.__ctor_{}", class_name, sigs.join("_"))
+        };
+        let mut params = vec![IrParam { name: "this".to_string(), ty: i8_ptr() }];
+        for param in &ctor.params {
+            params.push(IrParam { name: format!("{}.{}_param", class_name, param.name), ty: self.ir_type(&param.param_type) });
+        }
+        IrFunction::new(fn_name, IrType::Void, params)
+    }
+
+    fn build_constructor(&mut self, class_name: &str, ctor: &ConstructorDecl) -> cayResult<()> {
+        let ir_func = self.constructor_header(class_name, ctor);
+        self.begin_function(ir_func, class_name);
+        self.ctx.current_param_order = ctor.params.iter().map(|p| p.name.clone()).collect();
+        self.set_location(&ctor.loc);
+
+        self.frame_push(&format!("{}.<init>", class_name));
+        self.ctx.scope_manager.enter_scope();
+        self.bind_this();
+        for param in &ctor.params {
+            let incoming = format!("{}.{}_param", class_name, param.name);
+            self.bind_param(&ParameterInfo { is_varargs: false, ..param.clone() }, &incoming);
+            // 构造函数参数不参与对象方法解析（与旧代码生成器一致）
+            self.ctx.var_class_map.remove(&param.name);
+        }
+
+        let this = IrValue::Param("this".to_string(), i8_ptr());
+        match &ctor.constructor_call {
+            Some(ConstructorCall::This(args)) => {
+                self.build_constructor_call(class_name, args, this)?;
+            }
+            Some(ConstructorCall::Super(args)) => {
+                if let Some(parent) = self.ctx.get_parent_class(class_name) {
+                    self.build_constructor_call(&parent, args, this)?;
+                }
+            }
+            None => {}
+        }
+
+        self.build_block(&ctor.body)?;
+        self.frame_pop();
+        self.terminate(IrTerminator::Return { value: None });
+        self.ctx.scope_manager.exit_scope();
+        self.finish_function();
+        Ok(())
+    }
+
+    /// 没有显式构造函数的类生成无参构造函数，只调用父类的无参构造函数
+    fn build_default_constructor(&mut self, class_name: &str) {
+        let fn_name = format!("{}.__ctor", class_name);
+        let params = vec![IrParam { name: "this".to_string(), ty: i8_ptr() }];
+        self.begin_function(IrFunction::new(fn_name, IrType::Void, params), class_name);
+        self.ctx.scope_manager.enter_scope();
+        self.bind_this();
+        if let Some(parent) = self.ctx.get_parent_class(class_name) {
+            self.call(&format!("{}.__ctor", parent), vec![IrValue::Param("this".to_string(), i8_ptr())], IrType::Void);
+        }
+        self.terminate(IrTerminator::Return { value: None });
+        self.ctx.scope_manager.exit_scope();
+        self.finish_function();
+    }
+
+    fn build_destructor(&mut self, class_name: &str, dtor: &DestructorDecl) -> cayResult<()> {
+        let fn_name = format!("{}.__dtor", class_name);
+        let params = vec![IrParam { name: "this".to_string(), ty: i8_ptr() }];
+        self.begin_function(IrFunction::new(fn_name, IrType::Void, params), class_name);
+        self.set_location(&dtor.loc);
+        self.frame_push(&format!("{}.<dtor>", class_name));
+        self.ctx.scope_manager.enter_scope();
+        self.bind_this();
+        self.build_block(&dtor.body)?;
+        self.frame_pop();
+        self.terminate(IrTerminator::Return { value: None });
+        self.ctx.scope_manager.exit_scope();
+        self.finish_function();
+        Ok(())
+    }
+
+    fn build_static_initializer(&mut self, class_name: &str, block: &Block) -> cayResult<()> {
+        let fn_name = format!("{}.__static_init", class_name);
+        let mut ir_func = IrFunction::new(fn_name, IrType::Void, Vec::new());
+        ir_func.is_static = true;
+        self.begin_function(ir_func, class_name);
+        self.build_block(block)?;
+        self.terminate(IrTerminator::Return { value: None });
+        self.finish_function();
+        Ok(())
+    }
+
+    // ============================================================
+    // 分离编译
+    // ============================================================
+
+    /// 分离编译时类的静态初始化：非常量的静态字段初始化器，然后是静态初始化块
+    fn build_class_init(&mut self, class: &ClassDecl) -> cayResult<()> {
+        self.begin_function(class_init_header(&class.name), &class.name);
+        self.build_static_field_initialization(Some(&class.name))?;
+        if class.members.iter().any(|m| matches!(m, ClassMember::StaticInitializer(_))) {
+            self.call(&format!("{}.__static_init", class.name), Vec::new(), IrType::Void);
+        }
+        self.terminate(IrTerminator::Return { value: None });
+        self.finish_function();
+        Ok(())
+    }
+
+    /// 声明依赖单元中定义的函数：顶层函数、方法、构造函数、析构函数、类初始化和接口 default 方法
+    fn declare_imported_functions(&mut self, imported: &Program) {
+        let mut declarations = Vec::new();
+        for func in &imported.top_level_functions {
+            declarations.push(self.top_level_function_header(func));
+        }
+        for class in &imported.classes {
+            let this_only = || vec![IrParam { name: "this".to_string(), ty: i8_ptr() }];
+            let mut has_explicit_ctor = false;
+            for member in &class.members {
+                match member {
+                    ClassMember::Method(method) if !method.modifiers.contains(&Modifier::Native)
+                        && !method.modifiers.contains(&Modifier::Abstract) => {
+                        let fn_name = self.ctx.generate_method_name(&class.name, method);
+                        declarations.push(self.method_header(&class.name, method, fn_name));
+                    }
+                    ClassMember::Constructor(ctor) => {
+                        has_explicit_ctor = true;
+                        declarations.push(self.constructor_header(&class.name, ctor));
+                    }
+                    ClassMember::Destructor(_) => {
+                        declarations.push(IrFunction::new(format!("{}.__dtor", class.name), IrType::Void, this_only()));
+                    }
+                    _ => {}
+                }
+            }
+            if !has_explicit_ctor {
+                declarations.push(IrFunction::new(format!("{}.__ctor", class.name), IrType::Void, this_only()));
+            }
+            if needs_class_init(class) {
+                declarations.push(class_init_header(&class.name));
+            }
+        }
+        for interface in &imported.interfaces {
+            // 接口文件中的方法都没有方法体，default 和 static 方法的实现在依赖单元中
+            for method in &interface.methods {
+                if method.modifiers.contains(&Modifier::Default) || method.modifiers.contains(&Modifier::Static) {
+                    let fn_name = format!("{}.default", self.ctx.generate_method_name(&interface.name, method));
+                    declarations.push(self.method_header(&interface.name, method, fn_name));
+                }
+            }
+        }
+        for func in declarations {
+            self.module.add_function(func.into_declaration());
+        }
+    }
+
+    // ============================================================
+    // 接口
+    // ============================================================
+
+    /// 构建接口 default 方法体和每个接口方法的分派函数
+    ///
+    /// 分派函数需要看到全部实现类：整体编译时总是定义；分离编译时只在入口单元（`has_entry`）中
+    /// 按 `all`（依赖单元和本单元）定义，其他单元只声明。
+    fn build_interfaces(&mut self, program: &Program, all: &Program, has_entry: bool) -> cayResult<()> {
+        for interface in &program.interfaces {
+            for method in &interface.methods {
+                if method.body.is_some() {
+                    let fn_name = format!("{}.default", self.ctx.generate_method_name(&interface.name, method));
+                    self.build_method(&interface.name, method, fn_name)?;
+                }
+            }
+        }
+
+        let define = self.ctx.whole_program || has_entry;
+        // 只有非抽象类的对象会出现在运行时
+        let classes: Vec<&str> = all.classes.iter()
+            .filter(|c| !c.modifiers.contains(&Modifier::Abstract))
+            .map(|c| c.name.as_str())
+            .collect();
+        for interface in &all.interfaces {
+            for method in &interface.methods {
+                if method.modifiers.contains(&Modifier::Static) {
+                    continue;
+                }
+                if define {
+                    self.build_interface_dispatch(&interface.name, method, &classes);
+                } else {
+                    let header = self.interface_dispatch_header(&interface.name, method);
+                    self.module.add_function(header.into_declaration());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 分派函数的函数头：`this` 之后依次是接口方法的参数 `p0`、`p1`…
+    fn interface_dispatch_header(&self, interface_name: &str, method: &MethodDecl) -> IrFunction {
+        let fn_name = self.ctx.generate_method_name(interface_name, method);
+        let params = std::iter::once(IrParam { name: "this".to_string(), ty: i8_ptr() })
+            .chain(method.params.iter().enumerate()
+                .map(|(i, p)| IrParam { name: format!("p{}", i), ty: self.param_ir_type(p) }))
+            .collect();
+        IrFunction::new(fn_name, self.ir_type(&method.return_type), params)
+    }
+
+    /// 分派函数：按对象头部的类型 ID 跳转到实现类的方法
+    fn build_interface_dispatch(&mut self, interface_name: &str, method: &MethodDecl, classes: &[&str]) {
+        let targets: Vec<(i32, String, bool)> = classes.iter()
+            .filter_map(|class_name| {
+                let implements = self.ctx.type_registry.as_ref()
+                    .is_some_and(|r| r.implements_interface(class_name, interface_name));
+                if !implements {
+                    return None;
+                }
+                let type_id = self.ctx.get_type_id_value(class_name)?;
+                let (function, is_static) = self.ctx.resolve_interface_implementation(class_name, &method.name, &method.params)?;
+                Some((type_id, function, is_static))
+            })
+            .collect();
+
+        let ir_func = self.interface_dispatch_header(interface_name, method);
+        let ret_ty = ir_func.return_type.clone();
+        let args: Vec<IrValue> = ir_func.params[1..].iter()
+            .map(|p| IrValue::Param(p.name.clone(), p.ty.clone()))
+            .collect();
+        let this = IrValue::Param("this".to_string(), i8_ptr());
+        self.begin_function(ir_func, interface_name);
+
+        let type_id_ptr = self.bitcast(this.clone(), IrType::Pointer(Box::new(IrType::I32)));
+        let type_id = self.load(type_id_ptr, IrType::I32);
+        self.terminate(IrTerminator::Switch {
+            value: type_id,
+            default_target: "unknown".to_string(),
+            cases: targets.iter().enumerate()
+                .map(|(i, (id, _, _))| (IrValue::IntConst(*id as i64, IrType::I32), format!("impl.{}", i)))
+                .collect(),
+            ty: IrType::I32,
+        });
+
+        for (i, (_, function, is_static)) in targets.iter().enumerate() {
+            self.func_mut().add_block(IrBasicBlock::new(format!("impl.{}", i)));
+            let call_args = if *is_static {
+                args.clone()
+            } else {
+                std::iter::once(this.clone()).chain(args.iter().cloned()).collect()
+            };
+            let value = self.call(function, call_args, ret_ty.clone());
+            self.terminate(IrTerminator::Return { value });
+        }
+
+        // 语义分析保证接口类型的对象都来自实现类，这里只防御非法的类型 ID（位置为接口方法的声明）
+        self.func_mut().add_block(IrBasicBlock::new("unknown".to_string()));
+        self.panic(PanicKind::InvalidDispatch, &method.loc);
+        self.finish_function();
+    }
+
+    // ============================================================
+    // C 入口
+    // ============================================================
+
+    /// 生成 C `main`：平台初始化、静态字段初始化、命令行参数转换，然后调用程序入口
+    fn build_c_main(&mut self, program: &Program, entry: &EntryPoint) -> cayResult<()> {
+        let (main_fn_name, has_args, return_ty) = match entry {
+            EntryPoint::TopLevel(func) => (
+                self.ctx.generate_top_level_function_name(&func.name),
+                !func.params.is_empty(),
+                self.ir_type(&func.return_type),
+            ),
+            EntryPoint::Method(class_name, method) => (
+                self.ctx.generate_method_name(class_name, method),
+                !method.params.is_empty(),
+                self.ir_type(&method.return_type),
+            ),
+        };
+        let params = if has_args {
+            vec![
+                IrParam { name: "argc".to_string(), ty: IrType::I32 },
+                IrParam { name: "argv".to_string(), ty: IrType::Pointer(Box::new(i8_ptr())) },
+            ]
+        } else {
+            Vec::new()
+        };
+        let mut ir_func = IrFunction::new("main".to_string(), IrType::I32, params);
+        ir_func.is_static = true;
+        self.begin_function(ir_func, "");
+        // C main 的源位置取入口函数的声明位置
+        match entry {
+            EntryPoint::TopLevel(func) => self.set_location(&func.loc),
+            EntryPoint::Method(_, method) => self.set_location(&method.loc),
+        }
+
+        match entry {
+            EntryPoint::TopLevel(_) => {
+                let platform_init = self.ctx.generate_platform_init();
+                let lines: Vec<String> = platform_init.lines()
+                    .map(|l| l.trim().to_string())
+                    .filter(|l| !l.is_empty())
+                    .collect();
+                if !lines.is_empty() {
+                    self.emit(IrInstruction::InlineIr { lines, outputs: Vec::new(), inputs: Vec::new() });
+                }
+            }
+            EntryPoint::Method(..) => {
+                if self.ctx.is_windows_target() {
+                    self.call("SetConsoleOutputCP", vec![IrValue::IntConst(65001, IrType::I32)], IrType::Void);
+                }
+            }
+        }
+
+        self.build_program_initialization(program)?;
+
+        let args = if has_args { vec![self.build_args_array()] } else { Vec::new() };
+        let call_ty = match entry {
+            // 类的 main 方法只有声明为 int 时才使用返回值
+            EntryPoint::Method(..) if return_ty != IrType::I32 => IrType::Void,
+            _ => return_ty,
+        };
+        let result = self.call(&main_fn_name, args, call_ty);
+        let exit_code = match result {
+            Some(value) => self.convert(value, &IrType::I32),
+            None => IrValue::IntConst(0, IrType::I32),
+        };
+        self.terminate(IrTerminator::Return { value: Some(exit_code) });
+        self.finish_function();
+        Ok(())
+    }
+
+    /// 程序的静态初始化：静态字段的非常量初始化器和静态初始化块
+    fn build_program_initialization(&mut self, program: &Program) -> cayResult<()> {
+        if self.ctx.whole_program {
+            self.build_static_field_initialization(None)?;
+            for class in &program.classes {
+                if class.members.iter().any(|m| matches!(m, ClassMember::StaticInitializer(_))) {
+                    self.call(&format!("{}.__static_init", class.name), Vec::new(), IrType::Void);
+                }
+            }
+        } else {
+            // 各类在定义它的单元中初始化，依赖单元的类先初始化
+            for class in program.classes.iter().filter(|c| needs_class_init(c)) {
+                self.call(&format!("{}.__class_init", class.name), Vec::new(), IrType::Void);
+            }
+        }
+        Ok(())
+    }
+
+    // ============================================================
+    // 库输出
+    // ============================================================
+
+    /// 库输出：库初始化函数和导出函数的 C ABI 包装函数
+    ///
+    /// 整体编译时其余函数和运行时函数改为模块私有，库中只有包装函数是外部可见的；
+    /// 分离编译的单元之间还要互相调用，保持原有链接类型。
+    fn build_library(&mut self, program: &Program, exports: &[ExportedFunction]) -> cayResult<()> {
+        let mut init = IrFunction::new(LIBRARY_INIT.to_string(), IrType::Void, Vec::new());
+        init.is_static = true;
+        init.linkage = IrLinkage::Internal;
+        self.begin_function(init, "");
+        if self.ctx.is_windows_target() {
+            self.call("SetConsoleOutputCP", vec![IrValue::IntConst(65001, IrType::I32)], IrType::Void);
+        }
+        self.build_program_initialization(program)?;
+        self.terminate(IrTerminator::Return { value: None });
+        self.finish_function();
+
+        if self.ctx.whole_program {
+            for func in self.module.functions.iter_mut().filter(|f| f.linkage == IrLinkage::External) {
+                func.linkage = IrLinkage::Internal;
+            }
+            self.module.runtime_prelude = internal_prelude(&self.module.runtime_prelude);
+        }
+        for export in exports {
+            self.build_export_wrapper(export)?;
+        }
+        // 库加载时（动态库）或程序启动时（静态库）调用初始化函数
+        self.module.runtime_prelude.push_str(&format!(
+            "@llvm.global_ctors = appending global [1 x {{ i32, void ()*, i8* }}] \
+             [{{ i32, void ()*, i8* }} {{ i32 65535, void ()* @{}, i8* null }}]\n",
+            LIBRARY_INIT
+        ));
+        Ok(())
+    }
+
+    /// 导出函数的包装函数：以导出名和 C 类型定义，参数和返回值在 C 类型与 Cavvy 类型之间转换
+    fn build_export_wrapper(&mut self, export: &ExportedFunction) -> cayResult<()> {
+        let clashes = self.module.functions.iter().any(|f| f.name == export.name)
+            || self.module.extern_declarations.iter().any(|d| d.name == export.name)
+            || self.module.runtime_prelude.contains(&format!("@{}(", export.name));
+        if clashes {
+            return Err(codegen_error(format!(
+                "导出名 '{}' 与运行时或 extern 函数同名，不能以 C ABI 导出", export.name
+            )));
+        }
+        let callee = match &export.class_name {
+            Some(class_name) => self.ctx.generate_method_name_from_params(class_name, &export.name, &export.params),
+            None => self.ctx.generate_top_level_function_name(&export.name),
+        };
+        let params: Vec<IrParam> = export.params.iter()
+            .map(|p| IrParam { name: p.name.clone(), ty: c_abi_type(self.ir_type(&p.param_type)) })
+            .collect();
+        let return_ty = self.ir_type(&export.return_type);
+        let mut wrapper = IrFunction::new(export.name.clone(), c_abi_type(return_ty.clone()), params.clone());
+        wrapper.is_static = true;
+        self.begin_function(wrapper, export.class_name.as_deref().unwrap_or_default());
+        self.set_location(&export.loc);
+
+        let mut args = Vec::new();
+        for (param, info) in params.iter().zip(&export.params) {
+            let value = IrValue::Param(param.name.clone(), param.ty.clone());
+            let arg = match info.param_type.non_null() {
+                // C 字符串复制为 Cavvy 字符串（带长度头）
+                Type::String => self.call("__cay_cstr_to_string", vec![value], i8_ptr())
+                    .expect("non-void call has a result"),
+                Type::Bool => self.as_condition(value),
+                _ => value,
+            };
+            args.push(arg);
+        }
+        let result = self.call(&callee, args, return_ty.clone());
+        let value = result.map(|value| self.convert(value, &c_abi_type(return_ty)));
+        self.terminate(IrTerminator::Return { value });
+        self.finish_function();
+        Ok(())
+    }
+
+    /// 求值静态字段的非常量初始化器（常量初始化器已作为全局初始值）；`class_name` 限定只初始化该类的字段
+    fn build_static_field_initialization(&mut self, class_name: Option<&str>) -> cayResult<()> {
+        for field in self.ctx.static_fields.clone() {
+            if class_name.is_some_and(|name| name != field.class_name) {
+                continue;
+            }
+            let Some(init) = &field.initializer else { continue };
+            let ty = IrType::from_llvm_str(&field.llvm_type);
+            if self.const_initializer(init, &ty).is_some() {
+                continue;
+            }
+            self.ctx.current_class = field.class_name.clone();
+            let value = match init {
+                Expr::ArrayInit(array_init) => self.build_array_init_with_type(array_init, &field.field_type)?,
+                other => self.build_expression(other)?,
+            };
+            let value = self.convert(value, &ty);
+            self.store(value, IrValue::GlobalRef(field.name.clone(), IrType::Pointer(Box::new(ty))));
+        }
+        self.ctx.current_class = class_name.unwrap_or_default().to_string();
+        Ok(())
+    }
+
+    /// 把 argc/argv 转换为 String[]
+    fn build_args_array(&mut self) -> IrValue {
+        let argc = IrValue::Param("argc".to_string(), IrType::I32);
+        let argv = IrValue::Param("argv".to_string(), IrType::Pointer(Box::new(i8_ptr())));
+        let array_ty = IrType::Pointer(Box::new(i8_ptr()));
+        let array = self.call("__cay_create_string_array", vec![argc.clone()], array_ty.clone())
+            .expect("non-void call has a result");
+        let counter = self.alloca("args.i", IrType::I32);
+        self.store(IrValue::IntConst(0, IrType::I32), counter.clone());
+
+        self.start_block("args_loop_cond");
+        let index = self.load(counter.clone(), IrType::I32);
+        let more = self.compare(IrCmpOp::Slt, index.clone(), argc);
+        self.terminate(IrTerminator::ConditionalBranch {
+            condition: more,
+            true_target: "args_loop_body".to_string(),
+            false_target: "args_loop_end".to_string(),
+        });
+
+        self.start_block("args_loop_body");
+        let arg_ptr = self.temp(array_ty.clone());
+        self.emit(IrInstruction::GetElementPtr {
+            result: arg_ptr.clone(),
+            ptr: argv,
+            indices: vec![index.clone()],
+            base_ty: i8_ptr(),
+        });
+        let cstr = self.load(arg_ptr, i8_ptr());
+        let string = self.call("__cay_cstr_to_string", vec![cstr], i8_ptr()).expect("non-void call has a result");
+        self.call("__cay_array_set_ref", vec![array.clone(), index.clone(), string], IrType::Void);
+        let next = self.temp(IrType::I32);
+        self.emit(IrInstruction::BinaryOp {
+            result: next.clone(),
+            op: crate::ir::value::IrBinaryOp::Add,
+            left: index,
+            right: IrValue::IntConst(1, IrType::I32),
+        });
+        self.store(next, counter);
+        self.terminate(IrTerminator::Branch { target: "args_loop_cond".to_string() });
+
+        self.start_block("args_loop_end");
+        array
+    }
+}
+
+/// 类静态初始化函数 `{Class}.__class_init` 的函数头
+fn class_init_header(class_name: &str) -> IrFunction {
+    let mut ir_func = IrFunction::new(format!("{}.__class_init", class_name), IrType::Void, Vec::new());
+    ir_func.is_static = true;
+    ir_func
+}
+
+/// C `bool` 以字节传递，Cavvy 的 `bool`（`i1`）在包装函数中转换
+fn c_abi_type(ty: IrType) -> IrType {
+    match ty {
+        IrType::I1 => IrType::I8,
+        other => other,
+    }
+}
+
+/// 整体编译的库中运行时函数和运行时全局变量都是模块私有的，多个 Cavvy 库可以链接到同一个程序
+fn internal_prelude(prelude: &str) -> String {
+    let mut out = String::with_capacity(prelude.len());
+    for line in prelude.lines() {
+        match line.strip_prefix("define ") {
+            Some(rest) if !rest.starts_with("internal ") && !rest.starts_with("private ") => {
+                out.push_str("define internal ");
+                out.push_str(rest);
+            }
+            _ => out.push_str(&line.replacen(" = global ", " = internal global ", 1)),
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// 分离编译时每个单元都带有运行时前导：函数改为 `linkonce_odr`，链接时只保留一份；
+/// 运行时全局变量（调用栈帧）由各单元共享
+fn link_once_prelude(prelude: &str) -> String {
+    let mut out = String::with_capacity(prelude.len());
+    for line in prelude.lines() {
+        if let Some(rest) = line.strip_prefix("define ") {
+            out.push_str("define linkonce_odr ");
+            out.push_str(rest);
+        } else {
+            out.push_str(&line.replacen(" = internal global ", " = linkonce_odr global ", 1)
+                .replacen(" = global ", " = linkonce_odr global ", 1));
+        }
+        out.push('\n');
+    }
+    out
+}
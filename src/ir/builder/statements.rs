@@ -0,0 +1,418 @@
+//! 语句构建：块、变量声明、控制流、跳转和内联 IR
+
+use super::{Cleanup, IrBuilder, JumpTarget, zero_value};
+use crate::ast::*;
+use crate::codegen::bridge::InlineIrBridge;
+use crate::error::{cayResult, codegen_error};
+use crate::ir::types::IrType;
+use crate::ir::value::{IrInstruction, IrTerminator, IrValue};
+use crate::types::Type;
+
+impl IrBuilder {
+    /// 构建块（进入新作用域）
+    pub(super) fn build_block(&mut self, block: &Block) -> cayResult<()> {
+        self.ctx.scope_manager.enter_scope();
+        self.state.cleanups.push(Vec::new());
+        for stmt in &block.statements {
+            self.build_statement(stmt)?;
+        }
+        self.exit_cleanup_scope();
+        self.ctx.scope_manager.exit_scope();
+        Ok(())
+    }
+
+    /// 块正常结束：调用本块中栈上对象的析构函数
+    fn exit_cleanup_scope(&mut self) {
+        self.emit_cleanups(self.state.cleanups.len().saturating_sub(1));
+        self.state.cleanups.pop();
+    }
+
+    /// 调用第 `depth` 层及更内层作用域中栈上对象的析构函数（后声明的先析构）
+    fn emit_cleanups(&mut self, depth: usize) {
+        if self.current_block_complete() {
+            return;
+        }
+        let cleanups: Vec<Cleanup> = self.state.cleanups.iter().skip(depth).flatten().rev().cloned().collect();
+        for cleanup in cleanups {
+            self.call(&cleanup.destructor, vec![cleanup.object], IrType::Void);
+        }
+    }
+
+    pub(super) fn build_statement(&mut self, stmt: &Stmt) -> cayResult<()> {
+        match stmt {
+            Stmt::Expr(expr) => {
+                self.set_location(expr.location());
+                self.build_expression(expr)?;
+            }
+            Stmt::VarDecl(var) => {
+                self.set_location(&var.loc);
+                self.build_var_decl(var)?;
+            }
+            Stmt::Return(expr) => {
+                if let Some(e) = expr {
+                    self.set_location(e.location());
+                }
+                self.build_return(expr.as_ref())?;
+            }
+            Stmt::Block(block) => {
+                self.set_location(&block.loc);
+                // `int a, b;` 解析为只含声明的块，变量属于外层作用域
+                if block.statements.iter().all(|s| matches!(s, Stmt::VarDecl(_))) {
+                    for stmt in &block.statements {
+                        if let Stmt::VarDecl(var) = stmt {
+                            self.build_var_decl(var)?;
+                        }
+                    }
+                } else {
+                    self.build_block(block)?;
+                }
+            }
+            Stmt::If(if_stmt) => {
+                self.set_location(&if_stmt.loc);
+                self.build_if(if_stmt)?;
+            }
+            Stmt::While(while_stmt) => {
+                self.set_location(&while_stmt.loc);
+                self.build_while(while_stmt)?;
+            }
+            Stmt::For(for_stmt) => {
+                self.set_location(&for_stmt.loc);
+                self.build_for(for_stmt)?;
+            }
+            Stmt::DoWhile(do_while) => {
+                self.set_location(&do_while.loc);
+                self.build_do_while(do_while)?;
+            }
+            Stmt::Switch(switch) => {
+                self.set_location(&switch.loc);
+                self.build_switch(switch)?;
+            }
+            Stmt::Scope(scope) => {
+                self.set_location(&scope.loc);
+                self.build_scope(scope)?;
+            }
+            Stmt::Break(label) => self.build_break(label.as_deref())?,
+            Stmt::Continue(label) => self.build_continue(label.as_deref())?,
+            Stmt::InlineIr(inline_ir) => {
+                self.set_location(&inline_ir.loc);
+                self.build_inline_ir(inline_ir)?;
+            }
+            Stmt::Error(loc) => {
+                return Err(codegen_error(format!(
+                    "语法错误节点不能生成代码 ({})", loc
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // ============================================================
+    // 变量声明
+    // ============================================================
+
+    fn build_var_decl(&mut self, var: &VarDecl) -> cayResult<()> {
+        let actual_type = if var.var_type == Type::Auto {
+            match &var.initializer {
+                Some(init) => self.ctx.infer_type_from_expr(init).unwrap_or(Type::Int32),
+                None => {
+                    return Err(crate::error::semantic_error(
+                        var.loc.line, var.loc.column,
+                        "'auto' variable declaration requires an initializer".to_string()
+                    ));
+                }
+            }
+        } else {
+            var.var_type.clone()
+        };
+
+        let llvm_type = self.ctx.type_to_llvm(&actual_type);
+        let var_ty = IrType::from_llvm_str(&llvm_type);
+        let llvm_name = self.ctx.scope_manager.declare_var(&var.name, &llvm_type);
+        let slot = self.alloca(&llvm_name, var_ty.clone());
+        self.ctx.var_types.insert(var.name.clone(), llvm_type.clone());
+        self.ctx.var_cay_types.insert(var.name.clone(), actual_type.clone());
+        if let Type::Object(class_name) = &actual_type {
+            self.ctx.var_class_map.insert(var.name.clone(), class_name.clone());
+        }
+
+        let value = match &var.initializer {
+            Some(Expr::ArrayInit(init)) => self.build_array_init_with_type(init, &actual_type)?,
+            Some(init) => {
+                let value = self.build_expression(init)?;
+                self.try_convert(value.clone(), &var_ty).ok_or_else(|| codegen_error(format!(
+                    "Cannot convert {} to {} in variable initialization '{}' at line {}",
+                    value.ir_type(), llvm_type, var.name, var.loc.line
+                )))?
+            }
+            // 槽位可能在循环中复用，未初始化的变量每次声明都置零
+            None => zero_value(&var_ty),
+        };
+        let value = self.convert(value, &var_ty);
+        self.store(value, slot.clone());
+        self.declare_debug_variable(&var.name, slot, &actual_type);
+        Ok(())
+    }
+
+    // ============================================================
+    // 控制流
+    // ============================================================
+
+    /// 构建条件跳转
+    fn branch_on(&mut self, condition: &Expr, true_target: &str, false_target: &str) -> cayResult<()> {
+        let value = self.build_expression(condition)?;
+        let condition = self.as_condition(value);
+        self.terminate(IrTerminator::ConditionalBranch {
+            condition,
+            true_target: true_target.to_string(),
+            false_target: false_target.to_string(),
+        });
+        Ok(())
+    }
+
+    fn build_if(&mut self, if_stmt: &IfStmt) -> cayResult<()> {
+        let then_label = self.ctx.new_label("then");
+        let else_label = self.ctx.new_label("else");
+        let merge_label = self.ctx.new_label("ifmerge");
+
+        let false_target = if if_stmt.else_branch.is_some() { &else_label } else { &merge_label };
+        self.branch_on(&if_stmt.condition, &then_label, false_target)?;
+
+        self.start_block(&then_label);
+        self.build_statement(&if_stmt.then_branch)?;
+        self.terminate(IrTerminator::Branch { target: merge_label.clone() });
+
+        if let Some(else_branch) = &if_stmt.else_branch {
+            self.start_block(&else_label);
+            self.build_statement(else_branch)?;
+            self.terminate(IrTerminator::Branch { target: merge_label.clone() });
+        }
+
+        self.start_block(&merge_label);
+        Ok(())
+    }
+
+    fn build_while(&mut self, while_stmt: &WhileStmt) -> cayResult<()> {
+        let cond_label = self.ctx.new_label("while.cond");
+        let body_label = self.ctx.new_label("while.body");
+        let end_label = self.ctx.new_label("while.end");
+
+        self.start_block(&cond_label);
+        self.branch_on(&while_stmt.condition, &body_label, &end_label)?;
+
+        self.start_block(&body_label);
+        self.with_loop(&end_label, &cond_label, while_stmt.label.clone(), |b| {
+            b.build_statement(&while_stmt.body)
+        })?;
+        self.terminate(IrTerminator::Branch { target: cond_label });
+
+        self.start_block(&end_label);
+        Ok(())
+    }
+
+    fn build_for(&mut self, for_stmt: &ForStmt) -> cayResult<()> {
+        let cond_label = self.ctx.new_label("for.cond");
+        let body_label = self.ctx.new_label("for.body");
+        let update_label = self.ctx.new_label("for.update");
+        let end_label = self.ctx.new_label("for.end");
+
+        if let Some(init) = &for_stmt.init {
+            self.build_statement(init)?;
+        }
+
+        self.start_block(&cond_label);
+        match &for_stmt.condition {
+            Some(condition) => self.branch_on(condition, &body_label, &end_label)?,
+            None => self.terminate(IrTerminator::Branch { target: body_label.clone() }),
+        }
+
+        self.start_block(&body_label);
+        self.with_loop(&end_label, &update_label, for_stmt.label.clone(), |b| {
+            b.build_statement(&for_stmt.body)
+        })?;
+
+        self.start_block(&update_label);
+        if let Some(update) = &for_stmt.update {
+            self.build_expression(update)?;
+        }
+        self.terminate(IrTerminator::Branch { target: cond_label });
+
+        self.start_block(&end_label);
+        Ok(())
+    }
+
+    fn build_do_while(&mut self, do_while: &DoWhileStmt) -> cayResult<()> {
+        let body_label = self.ctx.new_label("dowhile.body");
+        let cond_label = self.ctx.new_label("dowhile.cond");
+        let end_label = self.ctx.new_label("dowhile.end");
+
+        self.start_block(&body_label);
+        self.with_loop(&end_label, &cond_label, do_while.label.clone(), |b| {
+            b.build_statement(&do_while.body)
+        })?;
+
+        self.start_block(&cond_label);
+        self.branch_on(&do_while.condition, &body_label, &end_label)?;
+
+        self.start_block(&end_label);
+        Ok(())
+    }
+
+    /// 在循环上下文中构建循环体
+    fn with_loop(
+        &mut self,
+        break_label: &str,
+        continue_label: &str,
+        label: Option<String>,
+        body: impl FnOnce(&mut Self) -> cayResult<()>,
+    ) -> cayResult<()> {
+        self.state.jumps.push(JumpTarget {
+            break_label: break_label.to_string(),
+            continue_label: Some(continue_label.to_string()),
+            label,
+            cleanup_depth: self.state.cleanups.len(),
+        });
+        let result = body(self);
+        self.state.jumps.pop();
+        result
+    }
+
+    fn build_switch(&mut self, switch: &SwitchStmt) -> cayResult<()> {
+        let end_label = self.ctx.new_label("switch.end");
+        let default_label = if switch.default.is_some() {
+            self.ctx.new_label("switch.default")
+        } else {
+            end_label.clone()
+        };
+
+        let value = self.build_expression(&switch.expr)?;
+        let value = self.convert(value, &IrType::I64);
+
+        let case_labels: Vec<String> = switch.cases.iter()
+            .map(|case| self.ctx.new_label(&format!("switch.case.{}", case.value)))
+            .collect();
+        self.terminate(IrTerminator::Switch {
+            value,
+            default_target: default_label.clone(),
+            cases: switch.cases.iter().zip(&case_labels)
+                .map(|(case, label)| (IrValue::IntConst(case.value, IrType::I64), label.clone()))
+                .collect(),
+            ty: IrType::I64,
+        });
+
+        // case 按源码顺序排列，未 break 的 case 贯穿到下一个 case；最后一个贯穿到 default
+        self.state.jumps.push(JumpTarget {
+            break_label: end_label.clone(),
+            continue_label: None,
+            label: None,
+            cleanup_depth: self.state.cleanups.len(),
+        });
+        let result = (|| {
+            for (case, label) in switch.cases.iter().zip(&case_labels) {
+                self.start_block(label);
+                for stmt in &case.body {
+                    self.build_statement(stmt)?;
+                }
+            }
+            if let Some(default_body) = &switch.default {
+                self.start_block(&default_label);
+                for stmt in default_body {
+                    self.build_statement(stmt)?;
+                }
+            }
+            Ok(())
+        })();
+        self.state.jumps.pop();
+        result?;
+
+        self.start_block(&end_label);
+        Ok(())
+    }
+
+    fn build_break(&mut self, label: Option<&str>) -> cayResult<()> {
+        let (target, depth) = match label {
+            Some(label) => self.state.jumps.iter().rev()
+                .find(|j| j.label.as_deref() == Some(label))
+                .map(|j| (j.break_label.clone(), j.cleanup_depth))
+                .ok_or_else(|| codegen_error(format!("break label '{}' not found", label)))?,
+            None => self.state.jumps.last()
+                .map(|j| (j.break_label.clone(), j.cleanup_depth))
+                .ok_or_else(|| codegen_error("break statement outside of loop".to_string()))?,
+        };
+        self.emit_cleanups(depth);
+        self.terminate(IrTerminator::Branch { target });
+        Ok(())
+    }
+
+    fn build_continue(&mut self, label: Option<&str>) -> cayResult<()> {
+        let (target, depth) = match label {
+            Some(label) => self.state.jumps.iter().rev()
+                .find(|j| j.label.as_deref() == Some(label))
+                .and_then(|j| Some((j.continue_label.clone()?, j.cleanup_depth)))
+                .ok_or_else(|| codegen_error(format!("continue label '{}' not found", label)))?,
+            // switch 不是 continue 的目标，跳到外层循环
+            None => self.state.jumps.iter().rev()
+                .find_map(|j| Some((j.continue_label.clone()?, j.cleanup_depth)))
+                .ok_or_else(|| codegen_error("continue statement outside of loop".to_string()))?,
+        };
+        self.emit_cleanups(depth);
+        self.terminate(IrTerminator::Branch { target });
+        Ok(())
+    }
+
+    fn build_return(&mut self, expr: Option<&Expr>) -> cayResult<()> {
+        let ret_ty = self.func_mut().return_type.clone();
+        let value = match expr {
+            Some(e) => {
+                let value = self.build_expression(e)?;
+                (ret_ty != IrType::Void).then(|| self.convert(value, &ret_ty))
+            }
+            None => None,
+        };
+        self.emit_cleanups(0);
+        self.frame_pop();
+        self.terminate(IrTerminator::Return { value });
+        Ok(())
+    }
+
+    fn build_scope(&mut self, scope: &ScopeStmt) -> cayResult<()> {
+        let scope_id = self.ctx.new_temp().replace('%', "");
+        self.emit(IrInstruction::Comment { text: format!("====== scope {} start ======", scope_id) });
+        self.ctx.scope_manager.enter_scope();
+        self.state.cleanups.push(Vec::new());
+        for stmt in &scope.body.statements {
+            self.build_statement(stmt)?;
+        }
+        self.exit_cleanup_scope();
+        self.emit(IrInstruction::Comment { text: format!("====== scope {} end ======", scope_id) });
+        self.ctx.scope_manager.exit_scope();
+        Ok(())
+    }
+
+    // ============================================================
+    // 内联 IR
+    // ============================================================
+
+    /// `__ir { ... }`：经内联 IR 桥映射变量名后原样嵌入
+    ///
+    /// 引用的参数和变量槽位记为指令输入，mem2reg 不会提升这些槽位。
+    fn build_inline_ir(&mut self, inline_ir: &InlineIrStmt) -> cayResult<()> {
+        let result = InlineIrBridge::new().process_inline_ir(&self.ctx, inline_ir)?;
+
+        let mut referenced: Vec<&String> = result.input_mappings.values().collect();
+        referenced.sort();
+        referenced.dedup();
+        let inputs = referenced.into_iter()
+            .filter(|llvm_name| result.llvm_ir_lines.iter().any(|l| l.contains(&format!("%{}", llvm_name))))
+            .map(|llvm_name| IrValue::Register(format!("%{}", llvm_name), IrType::Raw("ptr".to_string())))
+            .collect();
+
+        self.emit(IrInstruction::Comment { text: "Inline IR block start".to_string() });
+        self.emit(IrInstruction::InlineIr {
+            lines: result.llvm_ir_lines,
+            outputs: Vec::new(),
+            inputs,
+        });
+        self.emit(IrInstruction::Comment { text: "Inline IR block end".to_string() });
+        Ok(())
+    }
+}
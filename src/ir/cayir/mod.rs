@@ -0,0 +1,202 @@
+//! `.cayir` 文本格式
+//!
+//! `IrModule` 的稳定、可读的文本表示，写出后再解析得到的模块与原模块完全一致，
+//! 用于 `cay-ir --emit=cayir`、从 `.cayir` 文件生成 LLVM IR，以及 IrBuilder 和各优化 pass 的 golden 测试。
+//!
+//! # 格式
+//!
+//! ```text
+//! module "main"
+//! target "x86_64-unknown-linux-gnu"
+//!
+//! type %Point = { x: i32, y: i32 }
+//! string @.str.0 = "hello\n"
+//! global internal @Counter.count: i32 = i32 0
+//! global external @Shared.total_s: i32
+//! extern i32 @printf(i8* $fmt, ...)
+//!
+//! prelude {
+//! | declare i8* @calloc(i64, i64)
+//! }
+//!
+//! define static i32 @Main.add(i32 $a, i32 $b) temps 1 {
+//! entry:
+//!   %t0: i32 = add i32 $a, i32 $b
+//!   ret i32 %t0
+//! }
+//! ```
+//!
+//! - 值总是带类型书写：`i32 5`、`double 0x3FF0000000000000`（位模式）、`i1 true`、`i8* c"..."`、
+//!   `T null`、`T undef`；寄存器以 `%` 开头，全局引用以 `@` 开头，函数参数以 `$` 开头
+//! - 指令结果写作 `%t0: i32 = ...`；名称含特殊字符时加引号（`%"a b"`、`@"x y"`）
+//! - 函数指针类型写作 `(fn(i32) -> i32)*`，原始 LLVM 类型写作 `raw"..."`
+//! - 运行时支持代码和内联 IR 的原始 LLVM 文本逐行以 `| ` 开头
+//! - `;` 开头的行是注释；第一个基本块是入口块
+
+mod parser;
+mod writer;
+
+pub use parser::parse_module;
+pub use writer::write_module;
+
+/// 不需要加引号的名称字符
+pub(super) fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-' | '$')
+}
+
+/// 名称是否可以不加引号书写
+pub(super) fn is_simple_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(is_name_char)
+}
+
+/// 带引号和转义的字符串字面量
+pub(super) fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::test_support::*;
+    use crate::ir::*;
+
+    fn round_trip(module: &IrModule) -> IrModule {
+        let text = write_module(module);
+        let parsed = parse_module(&text).unwrap_or_else(|e| panic!("{:?}\n{}", e, text));
+        assert_eq!(write_module(&parsed), text);
+        parsed
+    }
+
+    #[test]
+    fn test_round_trip_functions_and_declarations() {
+        let mut module = module(vec![function("add", &["a", "b"], IrType::I32, vec![
+            block("entry", vec![
+                binary("sum", IrBinaryOp::Add, param("a"), param("b")),
+                compare("pos", IrCmpOp::Sgt, reg("sum"), int(0)),
+            ], cond_br(flag("pos"), "yes", "no")),
+            block("yes", vec![print(reg("sum"))], ret(reg("sum"))),
+            block("no", vec![], IrTerminator::Unreachable),
+        ])]);
+        module.add_string("line\n\"quoted\"\t\u{1}");
+        module.add_type_decl(IrTypeDecl {
+            name: "struct.Point".to_string(),
+            fields: vec![("x".to_string(), IrType::I32), ("y".to_string(), IrType::F64)],
+        });
+        module.add_global(IrGlobal {
+            name: "@Counter.count".to_string(),
+            ty: IrType::I64,
+            initializer: Some(IrValue::IntConst(-3, IrType::I64)),
+            is_constant: false,
+            linkage: IrGlobalLinkage::Internal,
+        });
+        module.add_global(IrGlobal {
+            name: "@__type_id_Counter".to_string(),
+            ty: IrType::I32,
+            initializer: Some(IrValue::IntConst(7, IrType::I32)),
+            is_constant: true,
+            linkage: IrGlobalLinkage::LinkOnceOdr,
+        });
+        module.add_global(IrGlobal {
+            name: "@Shared.total_s".to_string(),
+            ty: IrType::I32,
+            initializer: None,
+            is_constant: false,
+            linkage: IrGlobalLinkage::Declare,
+        });
+        module.add_extern(IrExternDecl {
+            name: "printf".to_string(),
+            return_type: IrType::I32,
+            params: vec![("fmt".to_string(), IrType::Pointer(Box::new(IrType::I8)))],
+            calling_convention: Some("#0".to_string()),
+            is_varargs: true,
+        });
+        module.runtime_prelude = "declare i8* @calloc(i64, i64)\n  ; | not a delimiter }\n".to_string();
+
+        let parsed = round_trip(&module);
+        assert_eq!(parsed.functions, module.functions);
+        assert_eq!(parsed.string_constants, module.string_constants);
+        assert_eq!(parsed.globals, module.globals);
+        assert_eq!(parsed.extern_declarations, module.extern_declarations);
+        assert_eq!(parsed.runtime_prelude, module.runtime_prelude);
+        // 解析后的模块继续分配不冲突的字符串常量名
+        let mut parsed = parsed;
+        assert_eq!(parsed.add_string("fresh"), "@.str.1");
+    }
+
+    #[test]
+    fn test_round_trip_every_instruction_kind() {
+        let ptr = |t: IrType| IrType::Pointer(Box::new(t));
+        let fn_ty = IrType::Function { params: vec![IrType::I32], return_type: Box::new(IrType::I32) };
+        let r = |name: &str, t: IrType| IrValue::Register(format!("%{}", name), t);
+        let instructions = vec![
+            IrInstruction::Alloca { result: r("slot", ptr(IrType::I32)), ty: IrType::I32, align: 4 },
+            IrInstruction::Store { value: IrValue::BoolConst(true), ptr: r("flag", ptr(IrType::I1)), ty: IrType::I1 },
+            IrInstruction::Load { result: r("v", IrType::I32), ptr: r("slot", ptr(IrType::I32)), ty: IrType::I32 },
+            IrInstruction::Cast { result: r("d", IrType::F64), kind: IrCastKind::IntToFloat, value: r("v", IrType::I32), to_ty: IrType::F64 },
+            IrInstruction::BinaryOp { result: r("f", IrType::F64), op: IrBinaryOp::FMul, left: r("d", IrType::F64), right: IrValue::FloatConst(0.1, IrType::F64) },
+            IrInstruction::Compare { result: r("c", IrType::I1), op: IrCmpOp::FLt, left: r("f", IrType::F64), right: IrValue::FloatConst(f64::NAN, IrType::F64) },
+            IrInstruction::Call { result: None, func_name: "llvm.trap".to_string(), args: vec![], return_ty: IrType::Void },
+            IrInstruction::Call { result: Some(r("s", ptr(IrType::I8))), func_name: "weird name".to_string(), args: vec![IrValue::StringConst("hi".to_string()), IrValue::NullConst(ptr(IrType::I8))], return_ty: ptr(IrType::I8) },
+            IrInstruction::CallIndirect { result: Some(r("n", IrType::I32)), callee: r("fp", ptr(fn_ty.clone())), return_ty: IrType::I32, param_tys: vec![IrType::I32], is_varargs: true, args: vec![IrValue::Undef(IrType::I32)] },
+            IrInstruction::ExtractValue { result: r("ov", IrType::I1), aggregate: r("pair", IrType::Raw("{ i32, i1 }".to_string())), index: 1 },
+            IrInstruction::GetElementPtr { result: r("e", ptr(IrType::I64)), ptr: r("arr", ptr(IrType::I64)), indices: vec![IrValue::IntConst(2, IrType::I64)], base_ty: IrType::I64 },
+            IrInstruction::BitCast { result: r("g", IrType::I8), value: IrValue::GlobalRef("@lambda".to_string(), fn_ty), to_ty: ptr(IrType::I8) },
+            IrInstruction::Phi { result: r("p", IrType::I32), ty: IrType::I32, incoming: vec![(IrValue::IntConst(1, IrType::I32), "entry".to_string()), (r("v", IrType::I32), "odd label!".to_string())] },
+            IrInstruction::Select { result: r("sel", IrType::I32), condition: r("c", IrType::I1), true_val: r("v", IrType::I32), false_val: IrValue::IntConst(0, IrType::I32) },
+            IrInstruction::InlineIr { lines: vec!["%x = add i32 1, 2".to_string(), "}".to_string()], outputs: vec![r("x", IrType::I32)], inputs: vec![] },
+            IrInstruction::Comment { text: "comment; with \"quotes\"".to_string() },
+            IrInstruction::SourceLocation { file: "dir/main file.cay".to_string(), line: 12, column: 3 },
+            IrInstruction::VarDecl { name: "count".to_string(), alloca_reg: r("slot", ptr(IrType::I32)), ty: IrType::Array(Box::new(IrType::I8), 4) },
+        ];
+        let mut func = IrFunction::new("all".to_string(), IrType::Void, vec![]);
+        func.blocks[0].instructions = instructions;
+        func.blocks[0].set_terminator(IrTerminator::Switch {
+            value: r("v", IrType::I32),
+            default_target: "entry".to_string(),
+            cases: vec![(IrValue::IntConst(7, IrType::I32), "entry".to_string())],
+            ty: IrType::I32,
+        });
+        func.linkage = IrLinkage::Private;
+        func.is_static = true;
+        func.calling_convention = Some("fastcc".to_string());
+        func.temp_counter = 9;
+        let mut declared = IrFunction::declare("ext".to_string(), IrType::Struct { name: "class.A".to_string(), fields: vec![] }, vec![]);
+        declared.local_count = 2;
+
+        let module = module(vec![func, declared]);
+        let parsed = round_trip(&module);
+        assert_eq!(format!("{:?}", parsed.functions), format!("{:?}", module.functions));
+    }
+
+    #[test]
+    fn test_parse_errors_report_line() {
+        let err = parse_module("module \"m\"\ntarget \"t\"\ndefine void @f() {\nentry:\n  %x: i32 = frobnicate i32 1\n}\n")
+            .unwrap_err();
+        let message = format!("{:?}", err);
+        assert!(message.contains("line 5"), "{}", message);
+        assert!(message.contains("frobnicate"), "{}", message);
+    }
+
+    #[test]
+    fn test_parsed_module_emits_llvm_ir() {
+        let module = module(vec![function("main", &[], IrType::I32, vec![
+            block("entry", vec![print(int(42))], ret(int(0))),
+        ])]);
+        let parsed = round_trip(&module);
+        assert_eq!(LlvmBackend::emit_module(&parsed).unwrap(), LlvmBackend::emit_module(&module).unwrap());
+    }
+}
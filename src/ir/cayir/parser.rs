@@ -0,0 +1,835 @@
+//! `.cayir` 解析
+
+use super::is_name_char;
+use super::writer::{binary_op_name, cast_kind_name, cmp_op_name};
+use crate::error::{cayResult, codegen_error};
+use crate::ir::block::IrBasicBlock;
+use crate::ir::function::{IrFunction, IrLinkage, IrParam};
+use crate::ir::module::{IrExternDecl, IrGlobal, IrGlobalLinkage, IrModule, IrTypeDecl};
+use crate::ir::types::IrType;
+use crate::ir::value::{IrBinaryOp, IrCastKind, IrCmpOp, IrInstruction, IrTerminator, IrValue};
+
+const BINARY_OPS: [IrBinaryOp; 16] = [
+    IrBinaryOp::Add, IrBinaryOp::Sub, IrBinaryOp::Mul, IrBinaryOp::Div, IrBinaryOp::Mod,
+    IrBinaryOp::And, IrBinaryOp::Or, IrBinaryOp::Xor,
+    IrBinaryOp::Shl, IrBinaryOp::Shr, IrBinaryOp::LShr,
+    IrBinaryOp::FAdd, IrBinaryOp::FSub, IrBinaryOp::FMul, IrBinaryOp::FDiv, IrBinaryOp::FRem,
+];
+
+const CMP_OPS: [IrCmpOp; 16] = [
+    IrCmpOp::Eq, IrCmpOp::Ne,
+    IrCmpOp::Slt, IrCmpOp::Sle, IrCmpOp::Sgt, IrCmpOp::Sge,
+    IrCmpOp::Ult, IrCmpOp::Ule, IrCmpOp::Ugt, IrCmpOp::Uge,
+    IrCmpOp::FEq, IrCmpOp::FNe, IrCmpOp::FLt, IrCmpOp::FLe, IrCmpOp::FGt, IrCmpOp::FGe,
+];
+
+const CAST_KINDS: [IrCastKind; 10] = [
+    IrCastKind::SignExt, IrCastKind::ZeroExt, IrCastKind::Trunc,
+    IrCastKind::IntToFloat, IrCastKind::FloatToInt,
+    IrCastKind::FloatExt, IrCastKind::FloatTrunc,
+    IrCastKind::BitCast, IrCastKind::PtrToInt, IrCastKind::IntToPtr,
+];
+
+/// 解析 `.cayir` 文本
+pub fn parse_module(text: &str) -> cayResult<IrModule> {
+    let mut parser = Parser { lines: text.split('\n').collect(), index: 0 };
+    parser.parse_module()
+}
+
+fn error_at(line: usize, message: impl std::fmt::Display) -> crate::error::cayError {
+    codegen_error(format!("cayir line {}: {}", line, message))
+}
+
+// ============================================================
+// 模块与函数（按行）
+// ============================================================
+
+struct Parser<'a> {
+    lines: Vec<&'a str>,
+    /// 下一行的下标
+    index: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// 下一个非空、非注释行，返回 (行号, 去掉缩进的内容)
+    fn next_line(&mut self) -> Option<(usize, &'a str)> {
+        while self.index < self.lines.len() {
+            let line = self.lines[self.index].trim();
+            self.index += 1;
+            if !line.is_empty() && !line.starts_with(';') {
+                return Some((self.index, line));
+            }
+        }
+        None
+    }
+
+    fn parse_module(&mut self) -> cayResult<IrModule> {
+        let mut name = None;
+        let mut target = None;
+        let mut module_parts = ModuleParts::default();
+
+        while let Some((line_no, line)) = self.next_line() {
+            let mut c = Cursor::new(line, line_no);
+            let keyword = c.word();
+            match keyword.as_str() {
+                "module" => name = Some(c.string()?),
+                "target" => target = Some(c.string()?),
+                "type" => {
+                    let decl_name = c.prefixed_name('%')?;
+                    c.expect("=")?;
+                    let fields = c.fields()?;
+                    module_parts.types.push(IrTypeDecl { name: decl_name, fields });
+                }
+                "string" => {
+                    let string_name = c.sigil_name('@')?;
+                    c.expect("=")?;
+                    module_parts.strings.push((string_name, c.string()?));
+                }
+                "global" => module_parts.globals.push(c.global()?),
+                "extern" => module_parts.externs.push(c.extern_decl()?),
+                "prelude" => {
+                    c.expect("{")?;
+                    c.end()?;
+                    let lines = self.raw_lines(line_no)?;
+                    module_parts.prelude = lines.join("\n");
+                }
+                "define" | "declare" => {
+                    let func = self.function(&mut c, keyword == "declare")?;
+                    module_parts.functions.push(func);
+                }
+                _ => return Err(error_at(line_no, format!("unexpected '{}'", line))),
+            }
+            if !matches!(keyword.as_str(), "prelude" | "define" | "declare") {
+                c.end()?;
+            }
+        }
+
+        let name = name.ok_or_else(|| error_at(1, "missing 'module' line"))?;
+        let target = target.ok_or_else(|| error_at(1, "missing 'target' line"))?;
+        let mut module = IrModule::new(name, target);
+        module.type_declarations = module_parts.types;
+        module.string_constants = module_parts.strings.into_iter().collect();
+        module.globals = module_parts.globals;
+        module.extern_declarations = module_parts.externs;
+        module.runtime_prelude = module_parts.prelude;
+        module.functions = module_parts.functions;
+        module.sync_name_counters();
+        Ok(module)
+    }
+
+    /// `| ` 开头的原始文本行，直到单独的 `}`
+    fn raw_lines(&mut self, start_line: usize) -> cayResult<Vec<String>> {
+        let mut lines = Vec::new();
+        while self.index < self.lines.len() {
+            let line = self.lines[self.index].trim_start();
+            self.index += 1;
+            if let Some(rest) = line.strip_prefix('|') {
+                lines.push(rest.strip_prefix(' ').unwrap_or(rest).to_string());
+            } else if line.trim_end() == "}" {
+                return Ok(lines);
+            } else if !line.trim().is_empty() {
+                return Err(error_at(self.index, "expected '| ' raw line or '}'"));
+            }
+        }
+        Err(error_at(start_line, "unterminated raw block"))
+    }
+
+    fn function(&mut self, c: &mut Cursor, is_declare: bool) -> cayResult<IrFunction> {
+        let mut linkage = if is_declare { IrLinkage::Declare } else { IrLinkage::External };
+        if c.eat_word("internal") {
+            linkage = IrLinkage::Internal;
+        } else if c.eat_word("private") {
+            linkage = IrLinkage::Private;
+        }
+        let is_static = c.eat_word("static");
+        let return_type = c.ty()?;
+        let name = c.prefixed_name('@')?;
+        c.expect("(")?;
+        let mut params = Vec::new();
+        if !c.eat(")") {
+            loop {
+                let ty = c.ty()?;
+                let name = c.prefixed_name('$')?;
+                params.push(IrParam { name, ty });
+                if c.eat(")") {
+                    break;
+                }
+                c.expect(",")?;
+            }
+        }
+
+        let mut func = if is_declare {
+            IrFunction::declare(name, return_type, params)
+        } else {
+            IrFunction::new(name, return_type, params)
+        };
+        func.linkage = linkage;
+        func.is_static = is_static;
+        if c.eat_word("cc") {
+            func.calling_convention = Some(c.string()?);
+        }
+        if c.eat_word("locals") {
+            func.local_count = c.number()? as u32;
+        }
+        if c.eat_word("temps") {
+            func.temp_counter = c.number()? as u32;
+        }
+        if is_declare {
+            c.end()?;
+            return Ok(func);
+        }
+        c.expect("{")?;
+        c.end()?;
+
+        func.blocks.clear();
+        let start_line = c.line_no;
+        loop {
+            let (line_no, line) = self.next_line().ok_or_else(|| error_at(start_line, "unterminated function body"))?;
+            if line == "}" {
+                break;
+            }
+            if let Some(header) = line.strip_suffix(':') {
+                let mut hc = Cursor::new(header, line_no);
+                let label = hc.label()?;
+                hc.end()?;
+                let mut block = IrBasicBlock::new(label);
+                block.is_entry = func.blocks.is_empty();
+                func.blocks.push(block);
+                continue;
+            }
+            let block = func.blocks.last_mut().ok_or_else(|| error_at(line_no, "instruction outside of a basic block"))?;
+            if block.terminator.is_some() {
+                return Err(error_at(line_no, format!("instruction after terminator in block '{}'", block.label)));
+            }
+            let mut c = Cursor::new(line, line_no);
+            match c.statement()? {
+                Statement::Instruction(inst) => block.push(inst),
+                Statement::Terminator(term) => block.set_terminator(term),
+                Statement::InlineIr { outputs, inputs } => {
+                    let lines = self.raw_lines(line_no)?;
+                    block.push(IrInstruction::InlineIr { lines, outputs, inputs });
+                }
+            }
+        }
+        Ok(func)
+    }
+}
+
+#[derive(Default)]
+struct ModuleParts {
+    types: Vec<IrTypeDecl>,
+    strings: Vec<(String, String)>,
+    globals: Vec<IrGlobal>,
+    externs: Vec<IrExternDecl>,
+    prelude: String,
+    functions: Vec<IrFunction>,
+}
+
+enum Statement {
+    Instruction(IrInstruction),
+    Terminator(IrTerminator),
+    /// `inline_ir outputs(...) inputs(...) {`，原始文本行随后读取
+    InlineIr { outputs: Vec<IrValue>, inputs: Vec<IrValue> },
+}
+
+/// 类型之后的操作数
+enum Operand {
+    Int(i64),
+    Float(u64),
+    Bool(bool),
+    Str(String),
+    Null,
+    Undef,
+    Register(String),
+    Global(String),
+    Param(String),
+}
+
+impl Operand {
+    fn with_type(self, ty: IrType) -> IrValue {
+        match self {
+            Operand::Int(n) => IrValue::IntConst(n, ty),
+            Operand::Float(bits) => IrValue::FloatConst(f64::from_bits(bits), ty),
+            Operand::Bool(b) => IrValue::BoolConst(b),
+            Operand::Str(s) => IrValue::StringConst(s),
+            Operand::Null => IrValue::NullConst(ty),
+            Operand::Undef => IrValue::Undef(ty),
+            Operand::Register(name) => IrValue::Register(name, ty),
+            Operand::Global(name) => IrValue::GlobalRef(name, ty),
+            Operand::Param(name) => IrValue::Param(name, ty),
+        }
+    }
+}
+
+// ============================================================
+// 行内解析
+// ============================================================
+
+struct Cursor<'a> {
+    rest: &'a str,
+    line_no: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(line: &'a str, line_no: usize) -> Self {
+        Self { rest: line, line_no }
+    }
+
+    fn error(&self, message: impl std::fmt::Display) -> crate::error::cayError {
+        error_at(self.line_no, message)
+    }
+
+    fn skip_ws(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_ws();
+        self.rest.chars().next()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_ws();
+        match self.rest.strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> cayResult<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{}' at '{}'", token, self.rest)))
+        }
+    }
+
+    fn end(&mut self) -> cayResult<()> {
+        self.skip_ws();
+        if self.rest.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error(format!("unexpected '{}'", self.rest)))
+        }
+    }
+
+    /// 连续的名称字符（可能为空）
+    fn word(&mut self) -> String {
+        self.skip_ws();
+        let end = self.rest.find(|c: char| !is_name_char(c)).unwrap_or(self.rest.len());
+        let (word, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        word.to_string()
+    }
+
+    /// 下一个名称等于 `word` 时消费它
+    fn eat_word(&mut self, word: &str) -> bool {
+        let saved = self.rest;
+        if self.word() == word {
+            true
+        } else {
+            self.rest = saved;
+            false
+        }
+    }
+
+    fn number(&mut self) -> cayResult<i64> {
+        self.skip_ws();
+        let end = self.rest.char_indices()
+            .find(|&(i, c)| !(c.is_ascii_digit() || (i == 0 && c == '-')))
+            .map_or(self.rest.len(), |(i, _)| i);
+        let (digits, rest) = self.rest.split_at(end);
+        let n = digits.parse::<i64>().map_err(|_| self.error(format!("expected number at '{}'", self.rest)))?;
+        self.rest = rest;
+        Ok(n)
+    }
+
+    /// 带引号的字符串
+    fn string(&mut self) -> cayResult<String> {
+        self.skip_ws();
+        let mut chars = self.rest.char_indices();
+        if chars.next().map(|(_, c)| c) != Some('"') {
+            return Err(self.error(format!("expected string at '{}'", self.rest)));
+        }
+        let mut out = String::new();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.rest = &self.rest[i + 1..];
+                    return Ok(out);
+                }
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => out.push('\n'),
+                    Some('r') => out.push('\r'),
+                    Some('t') => out.push('\t'),
+                    Some('\\') => out.push('\\'),
+                    Some('"') => out.push('"'),
+                    Some('u') => {
+                        let body: String = chars.by_ref().map(|(_, c)| c).take_while(|&c| c != '}').collect();
+                        let code = body.strip_prefix('{')
+                            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error(format!("invalid escape '\\u{}}}'", body)))?;
+                        out.push(code);
+                    }
+                    other => return Err(self.error(format!("invalid escape '\\{}'", other.unwrap_or(' ')))),
+                },
+                c => out.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    /// 名称或带引号的名称
+    fn name_or_string(&mut self) -> cayResult<String> {
+        if self.peek() == Some('"') {
+            return self.string();
+        }
+        let name = self.word();
+        if name.is_empty() {
+            return Err(self.error(format!("expected name at '{}'", self.rest)));
+        }
+        Ok(name)
+    }
+
+    fn label(&mut self) -> cayResult<String> {
+        self.name_or_string()
+    }
+
+    /// `@name` → `name`，`@"..."` → 引号内的名称
+    fn prefixed_name(&mut self, sigil: char) -> cayResult<String> {
+        self.skip_ws();
+        if !self.rest.starts_with(sigil) {
+            return Err(self.error(format!("expected '{}' name at '{}'", sigil, self.rest)));
+        }
+        self.rest = &self.rest[sigil.len_utf8()..];
+        if self.rest.starts_with('"') {
+            return self.string();
+        }
+        let name = self.word();
+        if name.is_empty() {
+            return Err(self.error(format!("expected name after '{}'", sigil)));
+        }
+        Ok(name)
+    }
+
+    /// `%t0` → `%t0`（名称带前缀存储），`%"..."` → 引号内的名称
+    fn sigil_name(&mut self, sigil: char) -> cayResult<String> {
+        self.skip_ws();
+        let quoted = self.rest.strip_prefix(sigil).is_some_and(|rest| rest.starts_with('"'));
+        let name = self.prefixed_name(sigil)?;
+        Ok(if quoted { name } else { format!("{}{}", sigil, name) })
+    }
+
+    // ---------------- 类型 ----------------
+
+    fn ty(&mut self) -> cayResult<IrType> {
+        let mut ty = self.primary_ty()?;
+        while self.rest.starts_with('*') {
+            self.rest = &self.rest[1..];
+            ty = IrType::Pointer(Box::new(ty));
+        }
+        Ok(ty)
+    }
+
+    fn primary_ty(&mut self) -> cayResult<IrType> {
+        match self.peek() {
+            Some('(') => {
+                self.rest = &self.rest[1..];
+                let ty = self.ty()?;
+                self.expect(")")?;
+                Ok(ty)
+            }
+            Some('[') => {
+                self.rest = &self.rest[1..];
+                let count = self.number()? as usize;
+                self.expect("x")?;
+                let elem = self.ty()?;
+                self.expect("]")?;
+                Ok(IrType::Array(Box::new(elem), count))
+            }
+            Some('%') => {
+                let name = self.prefixed_name('%')?;
+                let fields = if self.peek() == Some('{') { self.fields()? } else { Vec::new() };
+                Ok(IrType::Struct { name, fields })
+            }
+            _ => {
+                if self.rest.starts_with("raw\"") {
+                    self.rest = &self.rest[3..];
+                    return Ok(IrType::Raw(self.string()?));
+                }
+                if self.rest.starts_with("fn(") {
+                    self.rest = &self.rest[2..];
+                    let (params, is_varargs) = self.type_list()?;
+                    if is_varargs {
+                        return Err(self.error("function types cannot be variadic"));
+                    }
+                    self.expect("->")?;
+                    let return_type = self.ty()?;
+                    return Ok(IrType::Function { params, return_type: Box::new(return_type) });
+                }
+                let word = self.word();
+                match word.as_str() {
+                    "void" => Ok(IrType::Void),
+                    "i1" => Ok(IrType::I1),
+                    "i8" => Ok(IrType::I8),
+                    "i16" => Ok(IrType::I16),
+                    "i32" => Ok(IrType::I32),
+                    "i64" => Ok(IrType::I64),
+                    "float" => Ok(IrType::F32),
+                    "double" => Ok(IrType::F64),
+                    "label" => Ok(IrType::Label),
+                    _ => Err(self.error(format!("expected type at '{}{}'", word, self.rest))),
+                }
+            }
+        }
+    }
+
+    /// `(T, T, ...)`，返回 (类型列表, 是否可变参数)
+    fn type_list(&mut self) -> cayResult<(Vec<IrType>, bool)> {
+        self.expect("(")?;
+        let mut types = Vec::new();
+        if self.eat(")") {
+            return Ok((types, false));
+        }
+        loop {
+            if self.eat("...") {
+                self.expect(")")?;
+                return Ok((types, true));
+            }
+            types.push(self.ty()?);
+            if self.eat(")") {
+                return Ok((types, false));
+            }
+            self.expect(",")?;
+        }
+    }
+
+    /// `{ name: T, ... }`
+    fn fields(&mut self) -> cayResult<Vec<(String, IrType)>> {
+        self.expect("{")?;
+        let mut fields = Vec::new();
+        if self.eat("}") {
+            return Ok(fields);
+        }
+        loop {
+            let name = self.label()?;
+            self.expect(":")?;
+            fields.push((name, self.ty()?));
+            if self.eat("}") {
+                return Ok(fields);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    // ---------------- 值 ----------------
+
+    fn operand(&mut self) -> cayResult<Operand> {
+        match self.peek() {
+            Some('%') => Ok(Operand::Register(self.sigil_name('%')?)),
+            Some('@') => Ok(Operand::Global(self.sigil_name('@')?)),
+            Some('$') => Ok(Operand::Param(self.prefixed_name('$')?)),
+            Some('0') if self.rest.starts_with("0x") => {
+                self.rest = &self.rest[2..];
+                let hex = self.word();
+                u64::from_str_radix(&hex, 16)
+                    .map(Operand::Float)
+                    .map_err(|_| self.error(format!("invalid float bits '0x{}'", hex)))
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' => Ok(Operand::Int(self.number()?)),
+            Some('c') if self.rest.starts_with("c\"") => {
+                self.rest = &self.rest[1..];
+                Ok(Operand::Str(self.string()?))
+            }
+            _ => {
+                let word = self.word();
+                match word.as_str() {
+                    "true" => Ok(Operand::Bool(true)),
+                    "false" => Ok(Operand::Bool(false)),
+                    "null" => Ok(Operand::Null),
+                    "undef" => Ok(Operand::Undef),
+                    _ => Err(self.error(format!("expected value at '{}{}'", word, self.rest))),
+                }
+            }
+        }
+    }
+
+    /// 带类型的值 `T operand`
+    fn value(&mut self) -> cayResult<IrValue> {
+        let ty = self.ty()?;
+        Ok(self.operand()?.with_type(ty))
+    }
+
+    /// 逗号分隔的值列表直到 `close`
+    fn values_until(&mut self, close: &str) -> cayResult<Vec<IrValue>> {
+        let mut values = Vec::new();
+        if self.eat(close) {
+            return Ok(values);
+        }
+        loop {
+            values.push(self.value()?);
+            if self.eat(close) {
+                return Ok(values);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    // ---------------- 模块级声明 ----------------
+
+    fn global(&mut self) -> cayResult<IrGlobal> {
+        let linkage = if self.eat_word("internal") {
+            IrGlobalLinkage::Internal
+        } else if self.eat_word("private") {
+            IrGlobalLinkage::Private
+        } else if self.eat_word("linkonce_odr") {
+            IrGlobalLinkage::LinkOnceOdr
+        } else if self.eat_word("external") {
+            IrGlobalLinkage::Declare
+        } else {
+            IrGlobalLinkage::External
+        };
+        let is_constant = self.eat_word("constant");
+        let name = self.sigil_name('@')?;
+        self.expect(":")?;
+        let ty = self.ty()?;
+        let initializer = if self.eat("=") { Some(self.value()?) } else { None };
+        Ok(IrGlobal { name, ty, initializer, is_constant, linkage })
+    }
+
+    fn extern_decl(&mut self) -> cayResult<IrExternDecl> {
+        let return_type = self.ty()?;
+        let name = self.prefixed_name('@')?;
+        self.expect("(")?;
+        let mut params = Vec::new();
+        let mut is_varargs = false;
+        if !self.eat(")") {
+            loop {
+                if self.eat("...") {
+                    is_varargs = true;
+                    self.expect(")")?;
+                    break;
+                }
+                let ty = self.ty()?;
+                let name = if self.peek() == Some('$') { self.prefixed_name('$')? } else { String::new() };
+                params.push((name, ty));
+                if self.eat(")") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+        let calling_convention = if self.eat_word("cc") { Some(self.string()?) } else { None };
+        Ok(IrExternDecl { name, return_type, params, calling_convention, is_varargs })
+    }
+
+    // ---------------- 指令 ----------------
+
+    fn statement(&mut self) -> cayResult<Statement> {
+        let result = match self.peek() {
+            Some('%' | '@' | '$') => {
+                let operand = self.operand()?;
+                self.expect(":")?;
+                let ty = self.ty()?;
+                self.expect("=")?;
+                Some(operand.with_type(ty))
+            }
+            _ => None,
+        };
+        let keyword = self.word();
+        let needs_result = |c: &Self| -> cayResult<IrValue> {
+            result.clone().ok_or_else(|| c.error(format!("'{}' needs a result", keyword)))
+        };
+
+        let statement = match keyword.as_str() {
+            "alloca" => {
+                let ty = self.ty()?;
+                self.expect(",")?;
+                self.expect("align")?;
+                let align = self.number()? as u32;
+                Statement::Instruction(IrInstruction::Alloca { result: needs_result(self)?, ty, align })
+            }
+            "load" => {
+                let ty = self.ty()?;
+                self.expect(",")?;
+                let ptr = self.value()?;
+                Statement::Instruction(IrInstruction::Load { result: needs_result(self)?, ptr, ty })
+            }
+            "store" => {
+                let ty = self.ty()?;
+                self.expect(",")?;
+                let value = self.value()?;
+                self.expect(",")?;
+                let ptr = self.value()?;
+                Statement::Instruction(IrInstruction::Store { value, ptr, ty })
+            }
+            "cmp" => {
+                let name = self.word();
+                let op = CMP_OPS.into_iter().find(|op| cmp_op_name(*op) == name)
+                    .ok_or_else(|| self.error(format!("unknown comparison '{}'", name)))?;
+                let left = self.value()?;
+                self.expect(",")?;
+                let right = self.value()?;
+                Statement::Instruction(IrInstruction::Compare { result: needs_result(self)?, op, left, right })
+            }
+            "cast" => {
+                let name = self.word();
+                let kind = CAST_KINDS.into_iter().find(|k| cast_kind_name(*k) == name)
+                    .ok_or_else(|| self.error(format!("unknown cast '{}'", name)))?;
+                let value = self.value()?;
+                self.expect("to")?;
+                let to_ty = self.ty()?;
+                Statement::Instruction(IrInstruction::Cast { result: needs_result(self)?, kind, value, to_ty })
+            }
+            "call" => {
+                let return_ty = self.ty()?;
+                let func_name = self.prefixed_name('@')?;
+                self.expect("(")?;
+                let args = self.values_until(")")?;
+                Statement::Instruction(IrInstruction::Call { result: result.clone(), func_name, args, return_ty })
+            }
+            "call_indirect" => {
+                let return_ty = self.ty()?;
+                let (param_tys, is_varargs) = self.type_list()?;
+                let callee = self.value()?;
+                self.expect("(")?;
+                let args = self.values_until(")")?;
+                Statement::Instruction(IrInstruction::CallIndirect {
+                    result: result.clone(), callee, return_ty, param_tys, is_varargs, args,
+                })
+            }
+            "extractvalue" => {
+                let aggregate = self.value()?;
+                self.expect(",")?;
+                let index = self.number()? as u32;
+                Statement::Instruction(IrInstruction::ExtractValue { result: needs_result(self)?, aggregate, index })
+            }
+            "getelementptr" => {
+                let base_ty = self.ty()?;
+                self.expect(",")?;
+                let ptr = self.value()?;
+                let mut indices = Vec::new();
+                while self.eat(",") {
+                    indices.push(self.value()?);
+                }
+                Statement::Instruction(IrInstruction::GetElementPtr { result: needs_result(self)?, ptr, indices, base_ty })
+            }
+            "bitcast" => {
+                let value = self.value()?;
+                self.expect("to")?;
+                let to_ty = self.ty()?;
+                Statement::Instruction(IrInstruction::BitCast { result: needs_result(self)?, value, to_ty })
+            }
+            "phi" => {
+                let ty = self.ty()?;
+                let mut incoming = Vec::new();
+                if self.peek() == Some('[') {
+                    loop {
+                        self.expect("[")?;
+                        let value = self.value()?;
+                        self.expect(",")?;
+                        let label = self.label()?;
+                        self.expect("]")?;
+                        incoming.push((value, label));
+                        if !self.eat(",") {
+                            break;
+                        }
+                    }
+                }
+                Statement::Instruction(IrInstruction::Phi { result: needs_result(self)?, ty, incoming })
+            }
+            "select" => {
+                let condition = self.value()?;
+                self.expect(",")?;
+                let true_val = self.value()?;
+                self.expect(",")?;
+                let false_val = self.value()?;
+                Statement::Instruction(IrInstruction::Select { result: needs_result(self)?, condition, true_val, false_val })
+            }
+            "inline_ir" => {
+                self.expect("outputs")?;
+                self.expect("(")?;
+                let outputs = self.values_until(")")?;
+                self.expect("inputs")?;
+                self.expect("(")?;
+                let inputs = self.values_until(")")?;
+                self.expect("{")?;
+                Statement::InlineIr { outputs, inputs }
+            }
+            "comment" => Statement::Instruction(IrInstruction::Comment { text: self.string()? }),
+            "loc" => {
+                let file = self.string()?;
+                let line = self.number()? as u32;
+                self.expect(":")?;
+                let column = self.number()? as u32;
+                Statement::Instruction(IrInstruction::SourceLocation { file, line, column })
+            }
+            "vardecl" => {
+                let name = self.string()?;
+                let alloca_reg = self.value()?;
+                self.expect(",")?;
+                let ty = self.ty()?;
+                Statement::Instruction(IrInstruction::VarDecl { name, alloca_reg, ty })
+            }
+            "ret" => {
+                let value = if self.peek().is_some() { Some(self.value()?) } else { None };
+                Statement::Terminator(IrTerminator::Return { value })
+            }
+            "br" => Statement::Terminator(IrTerminator::Branch { target: self.label()? }),
+            "condbr" => {
+                let condition = self.value()?;
+                self.expect(",")?;
+                let true_target = self.label()?;
+                self.expect(",")?;
+                let false_target = self.label()?;
+                Statement::Terminator(IrTerminator::ConditionalBranch { condition, true_target, false_target })
+            }
+            "switch" => {
+                let ty = self.ty()?;
+                self.expect(",")?;
+                let value = self.value()?;
+                self.expect(",")?;
+                self.expect("default")?;
+                let default_target = self.label()?;
+                self.expect(",")?;
+                self.expect("[")?;
+                let mut cases = Vec::new();
+                if !self.eat("]") {
+                    loop {
+                        let case = self.value()?;
+                        self.expect("=>")?;
+                        cases.push((case, self.label()?));
+                        if self.eat("]") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                Statement::Terminator(IrTerminator::Switch { value, default_target, cases, ty })
+            }
+            "unreachable" => Statement::Terminator(IrTerminator::Unreachable),
+            other => match BINARY_OPS.into_iter().find(|op| binary_op_name(*op) == other) {
+                Some(op) => {
+                    let left = self.value()?;
+                    self.expect(",")?;
+                    let right = self.value()?;
+                    Statement::Instruction(IrInstruction::BinaryOp { result: needs_result(self)?, op, left, right })
+                }
+                None => return Err(self.error(format!("unknown instruction '{}'", other))),
+            },
+        };
+        let has_result = match &statement {
+            Statement::Instruction(inst) => inst.result().is_some(),
+            Statement::Terminator(_) | Statement::InlineIr { .. } => false,
+        };
+        if result.is_some() && !has_result {
+            return Err(self.error(format!("'{}' cannot have a result", keyword)));
+        }
+        self.end()?;
+        Ok(statement)
+    }
+}
@@ -0,0 +1,348 @@
+//! `.cayir` 写出
+
+use super::{is_simple_name, quote};
+use crate::ir::block::IrBasicBlock;
+use crate::ir::function::{IrFunction, IrLinkage};
+use crate::ir::module::{IrGlobalLinkage, IrModule};
+use crate::ir::types::IrType;
+use crate::ir::value::{IrBinaryOp, IrCastKind, IrCmpOp, IrInstruction, IrTerminator, IrValue};
+use std::fmt::Write;
+
+/// 把模块写成 `.cayir` 文本
+pub fn write_module(module: &IrModule) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "module {}", quote(&module.name));
+    let _ = writeln!(out, "target {}", quote(&module.target_triple));
+
+    if !module.type_declarations.is_empty() {
+        out.push('\n');
+        for decl in &module.type_declarations {
+            let _ = writeln!(out, "type {} = {}", struct_name(&decl.name), fields(&decl.fields));
+        }
+    }
+
+    if !module.string_constants.is_empty() {
+        out.push('\n');
+        // 按编号排序，保证输出稳定（与 LlvmBackend 一致）
+        let mut constants: Vec<(&String, &String)> = module.string_constants.iter().collect();
+        constants.sort_by_key(|(name, _)| {
+            let index = name.rsplit('.').next().and_then(|n| n.parse::<u64>().ok());
+            (index.is_none(), index, name.to_string())
+        });
+        for (name, value) in constants {
+            let _ = writeln!(out, "string {} = {}", sigil_name('@', name), quote(value));
+        }
+    }
+
+    if !module.globals.is_empty() {
+        out.push('\n');
+        for global in &module.globals {
+            out.push_str("global ");
+            match global.linkage {
+                IrGlobalLinkage::External => {}
+                IrGlobalLinkage::Internal => out.push_str("internal "),
+                IrGlobalLinkage::Private => out.push_str("private "),
+                IrGlobalLinkage::LinkOnceOdr => out.push_str("linkonce_odr "),
+                IrGlobalLinkage::Declare => out.push_str("external "),
+            }
+            if global.is_constant {
+                out.push_str("constant ");
+            }
+            let _ = write!(out, "{}: {}", sigil_name('@', &global.name), ty(&global.ty));
+            if let Some(init) = &global.initializer {
+                let _ = write!(out, " = {}", value(init));
+            }
+            out.push('\n');
+        }
+    }
+
+    if !module.extern_declarations.is_empty() {
+        out.push('\n');
+        for decl in &module.extern_declarations {
+            let mut params: Vec<String> = decl.params.iter()
+                .map(|(name, t)| if name.is_empty() { ty(t) } else { format!("{} {}", ty(t), param_name(name)) })
+                .collect();
+            if decl.is_varargs {
+                params.push("...".to_string());
+            }
+            let _ = write!(out, "extern {} {}({})", ty(&decl.return_type), function_name(&decl.name), params.join(", "));
+            if let Some(cc) = &decl.calling_convention {
+                let _ = write!(out, " cc {}", quote(cc));
+            }
+            out.push('\n');
+        }
+    }
+
+    if !module.runtime_prelude.is_empty() {
+        out.push_str("\nprelude {\n");
+        write_raw_lines(&mut out, "", module.runtime_prelude.split('\n'));
+        out.push_str("}\n");
+    }
+
+    for func in &module.functions {
+        out.push('\n');
+        write_function(&mut out, func);
+    }
+    out
+}
+
+fn write_function(out: &mut String, func: &IrFunction) {
+    let keyword = if func.linkage == IrLinkage::Declare { "declare" } else { "define" };
+    out.push_str(keyword);
+    match func.linkage {
+        IrLinkage::Internal => out.push_str(" internal"),
+        IrLinkage::Private => out.push_str(" private"),
+        IrLinkage::External | IrLinkage::Declare => {}
+    }
+    if func.is_static {
+        out.push_str(" static");
+    }
+    let params: Vec<String> = func.params.iter()
+        .map(|p| format!("{} {}", ty(&p.ty), param_name(&p.name)))
+        .collect();
+    let _ = write!(out, " {} {}({})", ty(&func.return_type), function_name(&func.name), params.join(", "));
+    if let Some(cc) = &func.calling_convention {
+        let _ = write!(out, " cc {}", quote(cc));
+    }
+    if func.local_count != 0 {
+        let _ = write!(out, " locals {}", func.local_count);
+    }
+    if func.temp_counter != 0 {
+        let _ = write!(out, " temps {}", func.temp_counter);
+    }
+    if func.linkage == IrLinkage::Declare {
+        out.push('\n');
+        return;
+    }
+    out.push_str(" {\n");
+    for block in &func.blocks {
+        write_block(out, block);
+    }
+    out.push_str("}\n");
+}
+
+fn write_block(out: &mut String, block: &IrBasicBlock) {
+    let _ = writeln!(out, "{}:", label(&block.label));
+    for inst in &block.instructions {
+        write_instruction(out, inst);
+    }
+    if let Some(term) = &block.terminator {
+        let _ = writeln!(out, "  {}", terminator(term));
+    }
+}
+
+fn write_instruction(out: &mut String, inst: &IrInstruction) {
+    let body = match inst {
+        IrInstruction::Alloca { ty: t, align, .. } => format!("alloca {}, align {}", ty(t), align),
+        IrInstruction::Load { ptr, ty: t, .. } => format!("load {}, {}", ty(t), value(ptr)),
+        IrInstruction::Store { value: v, ptr, ty: t } => format!("store {}, {}, {}", ty(t), value(v), value(ptr)),
+        IrInstruction::BinaryOp { op, left, right, .. } => format!("{} {}, {}", binary_op_name(*op), value(left), value(right)),
+        IrInstruction::Compare { op, left, right, .. } => format!("cmp {} {}, {}", cmp_op_name(*op), value(left), value(right)),
+        IrInstruction::Cast { kind, value: v, to_ty, .. } => format!("cast {} {} to {}", kind.to_llvm_str(), value(v), ty(to_ty)),
+        IrInstruction::Call { func_name, args, return_ty, .. } => {
+            format!("call {} {}({})", ty(return_ty), function_name(func_name), values(args))
+        }
+        IrInstruction::CallIndirect { callee, return_ty, param_tys, is_varargs, args, .. } => {
+            let mut params: Vec<String> = param_tys.iter().map(ty).collect();
+            if *is_varargs {
+                params.push("...".to_string());
+            }
+            format!("call_indirect {} ({}) {}({})", ty(return_ty), params.join(", "), value(callee), values(args))
+        }
+        IrInstruction::ExtractValue { aggregate, index, .. } => format!("extractvalue {}, {}", value(aggregate), index),
+        IrInstruction::GetElementPtr { ptr, indices, base_ty, .. } => {
+            let mut s = format!("getelementptr {}, {}", ty(base_ty), value(ptr));
+            for index in indices {
+                let _ = write!(s, ", {}", value(index));
+            }
+            s
+        }
+        IrInstruction::BitCast { value: v, to_ty, .. } => format!("bitcast {} to {}", value(v), ty(to_ty)),
+        IrInstruction::Phi { ty: t, incoming, .. } => {
+            let incoming: Vec<String> = incoming.iter()
+                .map(|(v, l)| format!("[{}, {}]", value(v), label(l)))
+                .collect();
+            if incoming.is_empty() {
+                format!("phi {}", ty(t))
+            } else {
+                format!("phi {} {}", ty(t), incoming.join(", "))
+            }
+        }
+        IrInstruction::Select { condition, true_val, false_val, .. } => {
+            format!("select {}, {}, {}", value(condition), value(true_val), value(false_val))
+        }
+        IrInstruction::InlineIr { lines, outputs, inputs } => {
+            let _ = writeln!(out, "  inline_ir outputs({}) inputs({}) {{", values(outputs), values(inputs));
+            write_raw_lines(out, "  ", lines.iter().map(String::as_str));
+            out.push_str("  }\n");
+            return;
+        }
+        IrInstruction::Comment { text } => format!("comment {}", quote(text)),
+        IrInstruction::SourceLocation { file, line, column } => format!("loc {} {}:{}", quote(file), line, column),
+        IrInstruction::VarDecl { name, alloca_reg, ty: t } => format!("vardecl {} {}, {}", quote(name), value(alloca_reg), ty(t)),
+    };
+    let result = match inst {
+        IrInstruction::InlineIr { .. } => None,
+        _ => inst.result(),
+    };
+    match result {
+        Some(result) => {
+            let _ = writeln!(out, "  {}: {} = {}", operand(result), ty(&result.ir_type()), body);
+        }
+        None => {
+            let _ = writeln!(out, "  {}", body);
+        }
+    }
+}
+
+fn terminator(term: &IrTerminator) -> String {
+    match term {
+        IrTerminator::Return { value: None } => "ret".to_string(),
+        IrTerminator::Return { value: Some(v) } => format!("ret {}", value(v)),
+        IrTerminator::Branch { target } => format!("br {}", label(target)),
+        IrTerminator::ConditionalBranch { condition, true_target, false_target } => {
+            format!("condbr {}, {}, {}", value(condition), label(true_target), label(false_target))
+        }
+        IrTerminator::Switch { value: v, default_target, cases, ty: t } => {
+            let cases: Vec<String> = cases.iter()
+                .map(|(case, target)| format!("{} => {}", value(case), label(target)))
+                .collect();
+            format!("switch {}, {}, default {}, [{}]", ty(t), value(v), label(default_target), cases.join(", "))
+        }
+        IrTerminator::Unreachable => "unreachable".to_string(),
+    }
+}
+
+fn write_raw_lines<'a>(out: &mut String, indent: &str, lines: impl Iterator<Item = &'a str>) {
+    for line in lines {
+        if line.is_empty() {
+            let _ = writeln!(out, "{}|", indent);
+        } else {
+            let _ = writeln!(out, "{}| {}", indent, line);
+        }
+    }
+}
+
+// ============================================================
+// 类型、值和名称
+// ============================================================
+
+pub(super) fn ty(t: &IrType) -> String {
+    match t {
+        IrType::Void => "void".to_string(),
+        IrType::I1 => "i1".to_string(),
+        IrType::I8 => "i8".to_string(),
+        IrType::I16 => "i16".to_string(),
+        IrType::I32 => "i32".to_string(),
+        IrType::I64 => "i64".to_string(),
+        IrType::F32 => "float".to_string(),
+        IrType::F64 => "double".to_string(),
+        IrType::Label => "label".to_string(),
+        IrType::Pointer(inner) => match inner.as_ref() {
+            IrType::Function { .. } => format!("({})*", ty(inner)),
+            _ => format!("{}*", ty(inner)),
+        },
+        IrType::Array(elem, count) => format!("[{} x {}]", count, ty(elem)),
+        IrType::Struct { name, fields: f } if f.is_empty() => struct_name(name),
+        IrType::Struct { name, fields: f } => format!("{} {}", struct_name(name), fields(f)),
+        IrType::Function { params, return_type } => {
+            let params: Vec<String> = params.iter().map(ty).collect();
+            format!("fn({}) -> {}", params.join(", "), ty(return_type))
+        }
+        IrType::Raw(s) => format!("raw{}", quote(s)),
+    }
+}
+
+fn fields(fields: &[(String, IrType)]) -> String {
+    if fields.is_empty() {
+        return "{}".to_string();
+    }
+    let fields: Vec<String> = fields.iter().map(|(name, t)| format!("{}: {}", label(name), ty(t))).collect();
+    format!("{{ {} }}", fields.join(", "))
+}
+
+/// 带类型的值
+fn value(v: &IrValue) -> String {
+    format!("{} {}", ty(&v.ir_type()), operand(v))
+}
+
+fn values(vs: &[IrValue]) -> String {
+    vs.iter().map(value).collect::<Vec<_>>().join(", ")
+}
+
+/// 不带类型的操作数
+fn operand(v: &IrValue) -> String {
+    match v {
+        IrValue::IntConst(n, _) => n.to_string(),
+        IrValue::FloatConst(f, _) => format!("0x{:016X}", f.to_bits()),
+        IrValue::BoolConst(b) => b.to_string(),
+        IrValue::StringConst(s) => format!("c{}", quote(s)),
+        IrValue::NullConst(_) => "null".to_string(),
+        IrValue::Undef(_) => "undef".to_string(),
+        IrValue::Register(name, _) => sigil_name('%', name),
+        IrValue::GlobalRef(name, _) => sigil_name('@', name),
+        IrValue::Param(name, _) => param_name(name),
+    }
+}
+
+/// 名称本身带前缀的寄存器和全局引用：`%t0`、`@.str.0`，否则加引号保留原名
+fn sigil_name(sigil: char, name: &str) -> String {
+    match name.strip_prefix(sigil) {
+        Some(rest) if is_simple_name(rest) => name.to_string(),
+        _ => format!("{}{}", sigil, quote(name)),
+    }
+}
+
+/// 不带前缀存储的名称：函数名写作 `@name`，参数写作 `$name`，结构体写作 `%name`
+fn prefixed_name(sigil: char, name: &str) -> String {
+    if is_simple_name(name) {
+        format!("{}{}", sigil, name)
+    } else {
+        format!("{}{}", sigil, quote(name))
+    }
+}
+
+fn function_name(name: &str) -> String {
+    prefixed_name('@', name)
+}
+
+fn param_name(name: &str) -> String {
+    prefixed_name('$', name)
+}
+
+fn struct_name(name: &str) -> String {
+    prefixed_name('%', name)
+}
+
+fn label(name: &str) -> String {
+    if is_simple_name(name) { name.to_string() } else { quote(name) }
+}
+
+pub(super) fn binary_op_name(op: IrBinaryOp) -> &'static str {
+    op.to_llvm_str()
+}
+
+pub(super) fn cmp_op_name(op: IrCmpOp) -> &'static str {
+    match op {
+        IrCmpOp::Eq => "eq",
+        IrCmpOp::Ne => "ne",
+        IrCmpOp::Slt => "slt",
+        IrCmpOp::Sle => "sle",
+        IrCmpOp::Sgt => "sgt",
+        IrCmpOp::Sge => "sge",
+        IrCmpOp::Ult => "ult",
+        IrCmpOp::Ule => "ule",
+        IrCmpOp::Ugt => "ugt",
+        IrCmpOp::Uge => "uge",
+        IrCmpOp::FEq => "feq",
+        IrCmpOp::FNe => "fne",
+        IrCmpOp::FLt => "flt",
+        IrCmpOp::FLe => "fle",
+        IrCmpOp::FGt => "fgt",
+        IrCmpOp::FGe => "fge",
+    }
+}
+
+pub(super) fn cast_kind_name(kind: IrCastKind) -> &'static str {
+    kind.to_llvm_str()
+}
@@ -0,0 +1,477 @@
+//! DWARF 调试信息
+//!
+//! 生成 LLVM 调试元数据：`DICompileUnit`、每个函数的 `DISubprogram`、指令的 `DILocation`、
+//! 局部变量和参数的 `DILocalVariable`，以及类实例布局的 `DICompositeType`。
+//!
+//! - `LlvmBackend` 根据 `SourceLocation`/`VarDecl` 指令和类型声明生成完整的调试信息
+//! - 旧代码生成器的输出由 [`add_line_info`] 按 `; !source` 注释补上函数和行号信息（不含变量）
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::module::IrTypeDecl;
+use super::types::IrType;
+use crate::target::TargetInfo;
+
+/// 调试元数据节点表，节点编号即 `!N`
+pub struct DebugInfoBuilder {
+    nodes: Vec<String>,
+    compile_unit: usize,
+    files: HashMap<String, usize>,
+    /// 子程序 -> 所在文件
+    subprogram_files: HashMap<usize, usize>,
+    /// (子程序, 文件) -> 子程序中来自其他文件（#include）代码的作用域
+    file_scopes: HashMap<(usize, usize), usize>,
+    locations: HashMap<(u32, u32, usize), usize>,
+    types: HashMap<String, usize>,
+    type_decls: HashMap<String, Vec<(String, IrType)>>,
+    /// 目标平台（指针宽度和类型大小）
+    target: TargetInfo,
+}
+
+impl DebugInfoBuilder {
+    /// 以主源文件创建编译单元
+    pub fn new(primary_file: &str) -> Self {
+        let mut builder = Self {
+            nodes: Vec::new(),
+            compile_unit: 0,
+            files: HashMap::new(),
+            subprogram_files: HashMap::new(),
+            file_scopes: HashMap::new(),
+            locations: HashMap::new(),
+            types: HashMap::new(),
+            type_decls: HashMap::new(),
+            target: TargetInfo::default(),
+        };
+        builder.compile_unit = builder.reserve();
+        let file = builder.file(primary_file);
+        builder.nodes[builder.compile_unit] = format!(
+            "distinct !DICompileUnit(language: DW_LANG_C_plus_plus, file: !{}, producer: {}, isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)",
+            file,
+            metadata_string(&format!("cavvy {}", env!("CARGO_PKG_VERSION")))
+        );
+        builder
+    }
+
+    /// 设置目标平台，类型大小和成员偏移按它计算
+    pub fn set_target(&mut self, target: TargetInfo) {
+        self.target = target;
+    }
+
+    /// 类实例布局（`class.X` 类型声明），用于生成 `DICompositeType`
+    pub fn set_type_declarations(&mut self, decls: &[IrTypeDecl]) {
+        self.type_decls = decls.iter().map(|d| (d.name.clone(), d.fields.clone())).collect();
+    }
+
+    /// 源文件的 `DIFile`，相对路径以当前目录为基准
+    pub fn file(&mut self, path: &str) -> usize {
+        if let Some(&id) = self.files.get(path) {
+            return id;
+        }
+        let path_ref = Path::new(path);
+        let name = path_ref.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| path.to_string());
+        let parent = path_ref.parent().unwrap_or(Path::new(""));
+        let directory = if parent.is_absolute() {
+            parent.to_path_buf()
+        } else {
+            std::env::current_dir().map(|d| d.join(parent)).unwrap_or_else(|_| parent.to_path_buf())
+        };
+        let directory = directory.to_string_lossy().trim_end_matches('/').to_string();
+        let id = self.add(format!(
+            "!DIFile(filename: {}, directory: {})",
+            metadata_string(&name), metadata_string(&directory)
+        ));
+        self.files.insert(path.to_string(), id);
+        id
+    }
+
+    /// 函数定义的 `DISubprogram`；`signature[0]` 是返回类型，其余为参数类型
+    pub fn subprogram(&mut self, name: &str, file: &str, line: u32, signature: &[IrType]) -> usize {
+        let file_id = self.file(file);
+        let types: Vec<String> = signature.iter()
+            .map(|ty| self.ty(ty).map_or("null".to_string(), |id| format!("!{}", id)))
+            .collect();
+        let types = self.add(format!("!{{{}}}", types.join(", ")));
+        let subroutine = self.add(format!("!DISubroutineType(types: !{})", types));
+        let id = self.add(format!(
+            "distinct !DISubprogram(name: {}, scope: !{}, file: !{}, line: {}, type: !{}, scopeLine: {}, spFlags: DISPFlagDefinition, unit: !{})",
+            metadata_string(name), file_id, file_id, line, subroutine, line, self.compile_unit
+        ));
+        self.subprogram_files.insert(id, file_id);
+        id
+    }
+
+    /// 子程序中某个源位置的 `DILocation`
+    pub fn location(&mut self, subprogram: usize, file: &str, line: u32, column: u32) -> usize {
+        let scope = self.scope(subprogram, file);
+        if let Some(&id) = self.locations.get(&(line, column, scope)) {
+            return id;
+        }
+        let id = self.add(format!("!DILocation(line: {}, column: {}, scope: !{})", line, column, scope));
+        self.locations.insert((line, column, scope), id);
+        id
+    }
+
+    /// 局部变量或参数（`arg` 从 1 开始）的 `DILocalVariable`；类型无法描述时返回 None
+    pub fn local_variable(
+        &mut self,
+        subprogram: usize,
+        name: &str,
+        arg: Option<usize>,
+        file: &str,
+        line: u32,
+        ty: &IrType,
+    ) -> Option<usize> {
+        let ty = self.ty(ty)?;
+        let file_id = self.file(file);
+        let arg = arg.map_or(String::new(), |n| format!("arg: {}, ", n));
+        Some(self.add(format!(
+            "!DILocalVariable(name: {}, {}scope: !{}, file: !{}, line: {}, type: !{})",
+            metadata_string(name), arg, subprogram, file_id, line, ty
+        )))
+    }
+
+    /// 模块级命名元数据和所有节点
+    pub fn finish(mut self) -> String {
+        let dwarf_version = self.add("!{i32 7, !\"Dwarf Version\", i32 4}".to_string());
+        let debug_info_version = self.add("!{i32 2, !\"Debug Info Version\", i32 3}".to_string());
+        let mut out = String::new();
+        out.push_str(&format!("!llvm.dbg.cu = !{{!{}}}\n", self.compile_unit));
+        out.push_str(&format!("!llvm.module.flags = !{{!{}, !{}}}\n", dwarf_version, debug_info_version));
+        out.push('\n');
+        for (id, node) in self.nodes.iter().enumerate() {
+            out.push_str(&format!("!{} = {}\n", id, node));
+        }
+        out
+    }
+
+    /// 位置所在的作用域：子程序本身，或其他文件时的 `DILexicalBlockFile`
+    fn scope(&mut self, subprogram: usize, file: &str) -> usize {
+        let file_id = self.file(file);
+        if self.subprogram_files.get(&subprogram) == Some(&file_id) {
+            return subprogram;
+        }
+        if let Some(&id) = self.file_scopes.get(&(subprogram, file_id)) {
+            return id;
+        }
+        let id = self.add(format!(
+            "!DILexicalBlockFile(scope: !{}, file: !{}, discriminator: 0)",
+            subprogram, file_id
+        ));
+        self.file_scopes.insert((subprogram, file_id), id);
+        id
+    }
+
+    /// IR 类型的调试类型；void 和无法描述的类型返回 None
+    fn ty(&mut self, ty: &IrType) -> Option<usize> {
+        let key = ty.to_llvm_str();
+        if let Some(&id) = self.types.get(&key) {
+            return Some(id);
+        }
+        let id = match ty {
+            IrType::I1 => self.basic_type("boolean", 8, "DW_ATE_boolean"),
+            IrType::I8 => self.basic_type("char", 8, "DW_ATE_signed_char"),
+            IrType::I16 => self.basic_type("short", 16, "DW_ATE_signed"),
+            IrType::I32 => self.basic_type("int", 32, "DW_ATE_signed"),
+            IrType::I64 => self.basic_type("long", 64, "DW_ATE_signed"),
+            IrType::F32 => self.basic_type("float", 32, "DW_ATE_float"),
+            IrType::F64 => self.basic_type("double", 64, "DW_ATE_float"),
+            IrType::Pointer(inner) => {
+                let base = match inner.as_ref() {
+                    IrType::Function { .. } => self.function_type(inner),
+                    inner => self.ty(inner),
+                };
+                let base = base.map_or("null".to_string(), |id| format!("!{}", id));
+                self.add(format!("!DIDerivedType(tag: DW_TAG_pointer_type, baseType: {}, size: {})", base, self.target.pointer_size * 8))
+            }
+            IrType::Function { .. } => {
+                let base = self.function_type(ty).map_or("null".to_string(), |id| format!("!{}", id));
+                self.add(format!("!DIDerivedType(tag: DW_TAG_pointer_type, baseType: {}, size: {})", base, self.target.pointer_size * 8))
+            }
+            IrType::Array(elem, count) => {
+                let base = self.ty(elem)?;
+                let subrange = self.add(format!("!DISubrange(count: {})", count));
+                let elements = self.add(format!("!{{!{}}}", subrange));
+                self.add(format!(
+                    "!DICompositeType(tag: DW_TAG_array_type, baseType: !{}, size: {}, elements: !{})",
+                    base, ty.size_bytes(&self.target) * 8, elements
+                ))
+            }
+            IrType::Struct { name, .. } => return Some(self.composite_type(name)),
+            IrType::Void | IrType::Label | IrType::Raw(_) => return None,
+        };
+        self.types.insert(key, id);
+        Some(id)
+    }
+
+    fn basic_type(&mut self, name: &str, bits: usize, encoding: &str) -> usize {
+        self.add(format!("!DIBasicType(name: \"{}\", size: {}, encoding: {})", name, bits, encoding))
+    }
+
+    fn function_type(&mut self, ty: &IrType) -> Option<usize> {
+        let IrType::Function { params, return_type } = ty else { return None };
+        let types: Vec<String> = std::iter::once(return_type.as_ref()).chain(params)
+            .map(|t| self.ty(t).map_or("null".to_string(), |id| format!("!{}", id)))
+            .collect();
+        let types = self.add(format!("!{{{}}}", types.join(", ")));
+        Some(self.add(format!("!DISubroutineType(types: !{})", types)))
+    }
+
+    /// 类的 `DICompositeType`，成员偏移按类型声明中字段的自然对齐计算；名称为空的字段是对象头或填充
+    fn composite_type(&mut self, name: &str) -> usize {
+        let key = format!("%{}", name);
+        if let Some(&id) = self.types.get(&key) {
+            return id;
+        }
+        let display = name.strip_prefix("class.").unwrap_or(name);
+        let id = self.reserve();
+        self.types.insert(key, id);
+
+        let Some(fields) = self.type_decls.get(name).cloned() else {
+            self.nodes[id] = format!(
+                "!DICompositeType(tag: DW_TAG_class_type, name: {}, flags: DIFlagFwdDecl)",
+                metadata_string(display)
+            );
+            return id;
+        };
+        let mut members = Vec::new();
+        let mut offset = 0;
+        for (field, field_ty) in &fields {
+            let align = field_ty.alignment(&self.target);
+            offset = (offset + align - 1) & !(align - 1);
+            // 空名字段是布局填充，不生成成员
+            let base = if field.is_empty() { None } else { self.ty(field_ty) };
+            if let Some(base) = base {
+                members.push(self.add(format!(
+                    "!DIDerivedType(tag: DW_TAG_member, name: {}, scope: !{}, baseType: !{}, size: {}, offset: {})",
+                    metadata_string(field), id, base, field_ty.size_bytes(&self.target) * 8, offset * 8
+                )));
+            }
+            offset += field_ty.size_bytes(&self.target);
+        }
+        let members: Vec<String> = members.iter().map(|m| format!("!{}", m)).collect();
+        let elements = self.add(format!("!{{{}}}", members.join(", ")));
+        self.nodes[id] = format!(
+            "!DICompositeType(tag: DW_TAG_class_type, name: {}, size: {}, elements: !{})",
+            metadata_string(display), ((offset + 7) & !7) * 8, elements
+        );
+        id
+    }
+
+    fn add(&mut self, node: String) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// 预留节点编号（用于自引用的节点）
+    fn reserve(&mut self) -> usize {
+        self.add(String::new())
+    }
+}
+
+/// 元数据字符串 `"..."`：引号、反斜杠和控制字符写成 `\XX`
+fn metadata_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for ch in value.chars() {
+        if ch == '"' || ch == '\\' || (ch as u32) < 0x20 || ch == '\u{7f}' {
+            out.push_str(&format!("\\{:02X}", ch as u32));
+        } else {
+            out.push(ch);
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// 按旧代码生成器的 `; !source file:line:col` 注释给 LLVM IR 文本补上行号调试信息
+///
+/// 含源映射注释的函数得到 `DISubprogram`，其中每条指令附上最近一条注释的位置
+/// （第一条注释之前的指令使用函数的起始位置）；没有注释的函数（运行时支持代码）保持不变。
+pub fn add_line_info(ir: &str) -> String {
+    let lines: Vec<&str> = ir.lines().collect();
+    let Some(primary) = lines.iter().find_map(|l| parse_source_comment(l)).map(|(file, _, _)| file) else {
+        return ir.to_string();
+    };
+    let mut debug = DebugInfoBuilder::new(primary);
+    let mut out = String::with_capacity(ir.len() * 2);
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+        let end = if trimmed.starts_with("define ") && line.trim_end().ends_with('{') {
+            (i + 1..lines.len()).find(|&j| lines[j].trim() == "}")
+        } else {
+            None
+        };
+        let first_location = end.and_then(|end| lines[i + 1..end].iter().find_map(|l| parse_source_comment(l)));
+        let (Some(end), Some((file, line_no, _))) = (end, first_location) else {
+            out.push_str(line);
+            out.push('\n');
+            i += 1;
+            continue;
+        };
+
+        let name = function_name(trimmed).unwrap_or("");
+        let subprogram = debug.subprogram(name, file, line_no, &[]);
+        let header = line.trim_end().trim_end_matches('{').trim_end();
+        out.push_str(&format!("{} !dbg !{} {{\n", header, subprogram));
+
+        let mut location = debug.location(subprogram, file, line_no, 0);
+        // 多行指令（括号未闭合或以 `,` 续行，如 switch 的 case 列表）只在最后一行附加位置
+        let mut depth = 0i32;
+        for body_line in &lines[i + 1..end] {
+            if let Some((file, line_no, column)) = parse_source_comment(body_line) {
+                location = debug.location(subprogram, file, line_no, column);
+                out.push_str(body_line);
+            } else if !is_instruction_line(body_line) {
+                out.push_str(body_line);
+            } else {
+                let code = split_comment(body_line).0.trim_end();
+                depth += bracket_balance(code);
+                if depth <= 0 && !code.ends_with(',') {
+                    depth = 0;
+                    out.push_str(&with_location(body_line, location));
+                } else {
+                    out.push_str(body_line);
+                }
+            }
+            out.push('\n');
+        }
+        out.push_str(lines[end]);
+        out.push('\n');
+        i = end + 1;
+    }
+
+    out.push('\n');
+    out.push_str(&debug.finish());
+    out
+}
+
+/// 解析 `; !source file:line:col` 注释
+fn parse_source_comment(line: &str) -> Option<(&str, u32, u32)> {
+    let rest = line.trim_start().strip_prefix("; !source ")?.trim_end();
+    let (rest, column) = rest.rsplit_once(':')?;
+    let (file, line_no) = rest.rsplit_once(':')?;
+    Some((file, line_no.parse().ok()?, column.parse().ok()?))
+}
+
+/// `define ... @name(...)` 中的函数名
+fn function_name(define: &str) -> Option<&str> {
+    let start = define.find('@')? + 1;
+    let rest = &define[start..];
+    if let Some(quoted) = rest.strip_prefix('"') {
+        return quoted.split('"').next();
+    }
+    rest.split('(').next()
+}
+
+/// 是否是函数体内的指令行（排除空行、注释、标签和已有调试位置的行）
+fn is_instruction_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty()
+        && !trimmed.starts_with(';')
+        && !trimmed.ends_with(':')
+        && !trimmed.contains("!dbg")
+}
+
+/// 拆分行尾注释（字符串中的 `;` 除外）
+fn split_comment(line: &str) -> (&str, &str) {
+    let mut in_string = false;
+    for (index, ch) in line.char_indices() {
+        match ch {
+            '"' => in_string = !in_string,
+            ';' if !in_string => return line.split_at(index),
+            _ => {}
+        }
+    }
+    (line, "")
+}
+
+/// 一行中开括号与闭括号数量之差（字符串中的除外）
+fn bracket_balance(code: &str) -> i32 {
+    let mut in_string = false;
+    let mut balance = 0;
+    for ch in code.chars() {
+        match ch {
+            '"' => in_string = !in_string,
+            '(' | '[' | '{' if !in_string => balance += 1,
+            ')' | ']' | '}' if !in_string => balance -= 1,
+            _ => {}
+        }
+    }
+    balance
+}
+
+/// 在指令末尾（行尾注释之前）附加 `, !dbg !N`
+fn with_location(line: &str, location: usize) -> String {
+    let (code, comment) = split_comment(line);
+    let code = code.trim_end();
+    if comment.is_empty() {
+        format!("{}, !dbg !{}", code, location)
+    } else {
+        format!("{}, !dbg !{} {}", code, location, comment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_line_info_attaches_locations() {
+        let ir = "declare i32 @printf(i8*, ...)\n\
+                  define void @Main.main() {\n\
+                  entry:\n\
+                  ; !source main.cay:3:5\n  \
+                  %t0 = add i32 1, 2 ; sum\n  \
+                  %t1 = select i1 true,\n    \
+                  i32 1,\n    \
+                  i32 2\n  \
+                  switch i32 %t1, label %done [\n    \
+                  i32 1, label %done\n  \
+                  ]\n\
+                  ; !source inc/util.cay:7:1\n  \
+                  ret void\n\
+                  }\n\
+                  define i32 @helper() {\n\
+                  entry:\n  \
+                  ret i32 0\n\
+                  }\n";
+        let out = add_line_info(ir);
+        assert!(out.contains("define void @Main.main() !dbg !"), "{}", out);
+        assert!(out.contains("%t0 = add i32 1, 2, !dbg !"), "{}", out);
+        assert!(out.contains(" ; sum"), "{}", out);
+        assert!(out.contains("select i1 true,\n"), "{}", out);
+        assert!(out.contains("i32 2, !dbg !"), "{}", out);
+        assert!(out.contains("i32 1, label %done\n"), "{}", out);
+        assert!(out.contains("], !dbg !"), "{}", out);
+        assert!(out.contains("define i32 @helper() {"), "{}", out);
+        assert!(out.contains("DISubprogram(name: \"Main.main\""), "{}", out);
+        assert!(out.contains("DILexicalBlockFile"), "{}", out);
+        assert!(out.contains("!DILocation(line: 7, column: 1"), "{}", out);
+        assert!(out.contains("!llvm.dbg.cu"), "{}", out);
+    }
+
+    #[test]
+    fn test_composite_member_offsets() {
+        let mut debug = DebugInfoBuilder::new("main.cay");
+        debug.set_type_declarations(&[IrTypeDecl {
+            name: "class.Point".to_string(),
+            fields: vec![
+                (String::new(), IrType::Array(Box::new(IrType::I8), 8)),
+                ("x".to_string(), IrType::I32),
+                ("next".to_string(), IrType::Pointer(Box::new(IrType::Struct { name: "class.Point".to_string(), fields: vec![] }))),
+            ],
+        }]);
+        let sp = debug.subprogram("f", "main.cay", 1, &[IrType::Void]);
+        let point = IrType::Pointer(Box::new(IrType::Struct { name: "class.Point".to_string(), fields: vec![] }));
+        assert!(debug.local_variable(sp, "p", Some(1), "main.cay", 1, &point).is_some());
+        let out = debug.finish();
+        assert!(out.contains("name: \"x\""), "{}", out);
+        assert!(out.contains("size: 32, offset: 64)"), "{}", out);
+        assert!(out.contains("size: 64, offset: 128)"), "{}", out);
+        assert!(out.contains("DW_TAG_class_type, name: \"Point\", size: 192"), "{}", out);
+    }
+}
@@ -20,7 +20,7 @@ pub enum IrLinkage {
 }
 
 /// 函数参数
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct IrParam {
     pub name: String,
     pub ty: IrType,
@@ -34,7 +34,7 @@ pub struct IrParam {
 /// - 参数列表
 /// - 基本块列表（CFG）
 /// - 元数据
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct IrFunction {
     /// 函数名（包含完整的类和命名空间信息）
     pub name: String,
@@ -87,6 +87,13 @@ impl IrFunction {
         }
     }
 
+    /// 只保留签名的声明（函数定义在其他编译单元中）
+    pub fn into_declaration(self) -> Self {
+        let mut decl = Self::declare(self.name, self.return_type, self.params);
+        decl.is_static = self.is_static;
+        decl
+    }
+
     /// 获取入口基本块
     pub fn entry_block(&self) -> Option<&IrBasicBlock> {
         self.blocks.first()
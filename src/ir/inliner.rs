@@ -2,12 +2,22 @@
 //!
 //! 在 IR 级别执行函数内联展开（inline expansion）。
 //! 将小函数的调用替换为函数体，减少调用开销。
+//!
+//! 内联一个调用点的步骤：
+//! 1. 在调用指令处拆分调用者基本块，调用之后的指令移入续接块（`inline.cont.iN`）
+//! 2. 复制被调函数的基本块，参数替换为实参，寄存器和标签加上唯一后缀 `.iN`
+//! 3. 被调函数的 alloca 提升到调用者入口块，避免在循环中重复分配栈空间
+//! 4. `ret` 改写为跳转到续接块；多个返回点时在续接块开头用 phi 合并返回值
+//! 5. 续接块后继中引用原基本块的 phi 改为引用续接块
+//!
+//! 内联后的代码会继续被扫描，直到达到 `max_depth`。
 
 use super::module::IrModule;
 use super::function::{IrFunction, IrLinkage};
-use super::value::IrInstruction;
+use super::block::IrBasicBlock;
+use super::value::{IrInstruction, IrTerminator, IrValue};
 use crate::error::cayResult;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// 内联器配置
 #[derive(Debug, Clone)]
@@ -43,13 +53,27 @@ pub struct Inliner {
 /// 内联统计
 #[derive(Debug, Default, Clone)]
 pub struct InlinerStats {
+    /// 检查过的调用点数（被调函数有函数体）
     pub candidates_considered: usize,
+    /// 至少被内联过一次的不同函数数
     pub functions_inlined: usize,
+    /// 被替换为函数体的调用点数
     pub calls_eliminated: usize,
+    /// 内联复制进调用者的指令数（含合并返回值的 phi）
     pub instructions_added: usize,
+    /// 被删除的调用指令数
     pub instructions_removed: usize,
 }
 
+/// 等待扫描调用点的基本块
+struct PendingBlock {
+    label: String,
+    /// 块中代码的内联深度（调用者原有代码为 0）
+    depth: usize,
+    /// 产生此块的内联链（调用者及依次内联的被调函数）
+    chain: Vec<String>,
+}
+
 impl Inliner {
     /// 创建新的内联器
     pub fn new() -> Self {
@@ -70,46 +94,21 @@ impl Inliner {
     /// 运行内联优化
     pub fn run(&mut self, module: IrModule) -> cayResult<IrModule> {
         let mut module = module;
-        let mut work_list: Vec<(usize, String)> = Vec::new(); // (函数索引, 被调函数名)
 
-        // 第一遍：收集所有可内联的调用点
-        let function_map: HashMap<String, &IrFunction> = module.functions.iter()
-            .map(|f| (f.name.clone(), f))
+        // 以内联前的函数体作为内联来源，结果不依赖函数的处理顺序
+        let callees: HashMap<String, IrFunction> = module.functions.iter()
+            .filter(|f| f.linkage != IrLinkage::Declare)
+            .map(|f| (f.name.clone(), f.clone()))
             .collect();
 
-        for (func_idx, func) in module.functions.iter().enumerate() {
+        let mut inlined_functions = HashSet::new();
+        for func in &mut module.functions {
             if func.linkage == IrLinkage::Declare {
                 continue;
             }
-            for block in &func.blocks {
-                for inst in &block.instructions {
-                    if let IrInstruction::Call { func_name, .. } = inst {
-                        if let Some(callee) = function_map.get(func_name) {
-                            if self.should_inline(callee) {
-                                work_list.push((func_idx, func_name.clone()));
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        self.stats.candidates_considered = work_list.len();
-
-        // 第二遍：执行内联
-        let mut inlined_calls = HashSet::new();
-        for (caller_idx, callee_name) in work_list {
-            let key = format!("{}->{}", caller_idx, callee_name);
-            if inlined_calls.contains(&key) {
-                continue;
-            }
-            inlined_calls.insert(key);
-
-            if let Err(e) = self.inline_call(&mut module, caller_idx, &callee_name) {
-                // 内联失败不影响编译，只是跳过此优化
-                eprintln!("Inliner: failed to inline {}: {}", callee_name, e);
-            }
+            self.inline_into(func, &callees, &mut inlined_functions);
         }
+        self.stats.functions_inlined += inlined_functions.len();
 
         Ok(module)
     }
@@ -148,32 +147,208 @@ impl Inliner {
         true
     }
 
+    /// 判断某个调用点是否可以内联
+    fn can_inline_at(&self, callee: &IrFunction, args: &[IrValue], site: &PendingBlock) -> bool {
+        if site.depth >= self.config.max_depth || !self.should_inline(callee) {
+            return false;
+        }
+        // 间接递归（内联链中已出现）同样受 inline_recursive 控制
+        if !self.config.inline_recursive && site.chain.contains(&callee.name) {
+            return false;
+        }
+        // 内联 IR 是原始文本，无法重命名其中的寄存器和标签
+        let has_inline_ir = callee.blocks.iter()
+            .flat_map(|b| &b.instructions)
+            .any(|inst| matches!(inst, IrInstruction::InlineIr { .. }));
+        !callee.blocks.is_empty() && callee.params.len() == args.len() && !has_inline_ir
+    }
+
+    /// 内联调用者中所有符合条件的调用点
+    fn inline_into(
+        &mut self,
+        caller: &mut IrFunction,
+        callees: &HashMap<String, IrFunction>,
+        inlined_functions: &mut HashSet<String>,
+    ) {
+        let mut pending: VecDeque<PendingBlock> = caller.blocks.iter()
+            .map(|b| PendingBlock {
+                label: b.label.clone(),
+                depth: 0,
+                chain: vec![caller.name.clone()],
+            })
+            .collect();
+        let mut suffix_counter = 0;
+
+        while let Some(site) = pending.pop_front() {
+            let Some(block_idx) = caller.blocks.iter().position(|b| b.label == site.label) else {
+                continue;
+            };
+
+            // 找到块中第一个可内联的调用点
+            let mut found = None;
+            for (inst_idx, inst) in caller.blocks[block_idx].instructions.iter().enumerate() {
+                let IrInstruction::Call { func_name, args, .. } = inst else { continue };
+                let Some(callee) = callees.get(func_name) else { continue };
+                self.stats.candidates_considered += 1;
+                if self.can_inline_at(callee, args, &site) {
+                    found = Some((inst_idx, callee));
+                    break;
+                }
+            }
+            let Some((inst_idx, callee)) = found else { continue };
+
+            let suffix = fresh_suffix(caller, &mut suffix_counter);
+            let (body_labels, cont_label) = self.inline_call(caller, block_idx, inst_idx, callee, &suffix);
+            inlined_functions.insert(callee.name.clone());
+
+            // 续接块中剩余的调用与原块同深度；复制进来的函数体深度加一
+            let mut chain = site.chain.clone();
+            chain.push(callee.name.clone());
+            for label in body_labels {
+                pending.push_back(PendingBlock { label, depth: site.depth + 1, chain: chain.clone() });
+            }
+            pending.push_back(PendingBlock { label: cont_label, depth: site.depth, chain: site.chain });
+        }
+    }
+
     /// 执行单个调用点的内联
+    ///
+    /// 返回复制进来的基本块标签和续接块标签。
     fn inline_call(
         &mut self,
-        module: &mut IrModule,
-        _caller_idx: usize,
-        callee_name: &str,
-    ) -> cayResult<()> {
-        // 克隆被调函数（因为需要借用 module）
-        let callee = match module.find_function(callee_name) {
-            Some(f) => f.clone(),
-            None => return Err(crate::error::codegen_error("Callee not found".to_string())),
+        caller: &mut IrFunction,
+        block_idx: usize,
+        inst_idx: usize,
+        callee: &IrFunction,
+        suffix: &str,
+    ) -> (Vec<String>, String) {
+        let IrInstruction::Call { result, args, return_ty, .. } = caller.blocks[block_idx].instructions[inst_idx].clone() else {
+            unreachable!("inline site must be a call");
+        };
+
+        // 参数绑定：形参（`name` 或 `%name`）映射到实参
+        let mut bindings: HashMap<String, IrValue> = HashMap::new();
+        for (param, arg) in callee.params.iter().zip(&args) {
+            bindings.insert(param.name.clone(), arg.clone());
+            bindings.insert(format!("%{}", param.name), arg.clone());
+        }
+
+        // 重命名表：被调函数定义的寄存器和基本块标签
+        let mut registers: HashMap<String, String> = HashMap::new();
+        for inst in callee.blocks.iter().flat_map(|b| &b.instructions) {
+            if let Some(IrValue::Register(name, _)) = inst.result() {
+                registers.insert(name.clone(), format!("{}{}", name, suffix));
+            }
+        }
+        let labels: HashMap<String, String> = callee.blocks.iter()
+            .map(|b| (b.label.clone(), format!("{}{}", b.label, suffix)))
+            .collect();
+        let cont_label = format!("inline.cont{}", suffix);
+
+        let rename = |value: &mut IrValue| match value {
+            IrValue::Param(name, _) | IrValue::Register(name, _) if bindings.contains_key(name.as_str()) => {
+                *value = bindings[name.as_str()].clone();
+            }
+            IrValue::Register(name, _) => {
+                if let Some(new_name) = registers.get(name.as_str()) {
+                    *name = new_name.clone();
+                }
+            }
+            _ => {}
         };
 
-        if callee.linkage == IrLinkage::Declare || callee.blocks.is_empty() {
-            return Ok(());
+        let mut body = Vec::with_capacity(callee.blocks.len());
+        let mut allocas = Vec::new();
+        let mut returns: Vec<(Option<IrValue>, String)> = Vec::new();
+        for block in &callee.blocks {
+            let mut new_block = IrBasicBlock::new(labels[&block.label].clone());
+            for inst in &block.instructions {
+                let mut inst = inst.clone();
+                inst.values_mut().into_iter().for_each(rename);
+                if let IrInstruction::Phi { incoming, .. } = &mut inst {
+                    for (_, label) in incoming.iter_mut() {
+                        if let Some(new_label) = labels.get(label.as_str()) {
+                            *label = new_label.clone();
+                        }
+                    }
+                }
+                if matches!(inst, IrInstruction::Alloca { .. }) {
+                    allocas.push(inst);
+                } else {
+                    new_block.push(inst);
+                }
+            }
+
+            let mut term = block.terminator.clone().unwrap_or(IrTerminator::Unreachable);
+            term.values_mut().into_iter().for_each(rename);
+            for target in term.targets_mut() {
+                if let Some(new_label) = labels.get(target.as_str()) {
+                    *target = new_label.clone();
+                }
+            }
+            if let IrTerminator::Return { value } = term {
+                returns.push((value, new_block.label.clone()));
+                term = IrTerminator::Branch { target: cont_label.clone() };
+            }
+            new_block.set_terminator(term);
+            body.push(new_block);
+        }
+
+        // 拆分调用者基本块：调用之前的指令留在原块，之后的移入续接块
+        let block = &mut caller.blocks[block_idx];
+        let tail = block.instructions.split_off(inst_idx + 1);
+        block.instructions.pop();
+        let original_label = block.label.clone();
+        let original_term = block.terminator.replace(IrTerminator::Branch { target: body[0].label.clone() });
+
+        let mut cont = IrBasicBlock::new(cont_label.clone());
+        cont.instructions = tail;
+        cont.terminator = original_term;
+
+        // 返回值：单个返回点直接替换调用结果，多个返回点用 phi 合并
+        let mut added = body.iter().map(|b| b.instructions.len()).sum::<usize>() + allocas.len();
+        let mut replacement = None;
+        if let Some(result) = result {
+            let values: Vec<(IrValue, String)> = returns.into_iter()
+                .map(|(value, label)| (value.unwrap_or_else(|| IrValue::Undef(return_ty.clone())), label))
+                .collect();
+            match values.len() {
+                0 => replacement = Some((result, IrValue::Undef(return_ty.clone()))),
+                1 => replacement = Some((result, values[0].0.clone())),
+                _ => {
+                    cont.instructions.insert(0, IrInstruction::Phi { result, ty: return_ty, incoming: values });
+                    added += 1;
+                }
+            }
+        }
+
+        // 续接块的后继原本从原块跳入，phi 的来源改为续接块
+        let successors: Vec<String> = cont.successor_labels().iter().map(|s| s.to_string()).collect();
+        for successor in caller.blocks.iter_mut().filter(|b| successors.contains(&b.label)) {
+            for inst in &mut successor.instructions {
+                if let IrInstruction::Phi { incoming, .. } = inst {
+                    for (_, label) in incoming.iter_mut().filter(|(_, l)| *l == original_label) {
+                        *label = cont_label.clone();
+                    }
+                }
+            }
+        }
+
+        let body_labels: Vec<String> = body.iter().map(|b| b.label.clone()).collect();
+        let insert_at = block_idx + 1;
+        caller.blocks.splice(insert_at..insert_at, body.into_iter().chain(std::iter::once(cont)));
+
+        if let Some((IrValue::Register(name, _), value)) = replacement {
+            replace_register(caller, &name, &value);
         }
 
-        // TODO: 完整的内联实现需要：
-        // 1. 在调用点前插入参数绑定
-        // 2. 将 callee 的入口块合并到 caller
-        // 3. 重命名 callee 的所有寄存器和标签
-        // 4. 将 return 替换为跳转到调用点之后
-        // 5. 更新 phi 节点
+        // alloca 提升到入口块开头
+        caller.blocks[0].instructions.splice(0..0, allocas);
 
-        self.stats.functions_inlined += 1;
-        Ok(())
+        self.stats.calls_eliminated += 1;
+        self.stats.instructions_added += added;
+        self.stats.instructions_removed += 1;
+        (body_labels, cont_label)
     }
 
     /// 获取统计信息
@@ -188,10 +363,39 @@ impl Default for Inliner {
     }
 }
 
+/// 生成调用者中尚未使用的内联后缀 `.iN`
+fn fresh_suffix(caller: &IrFunction, counter: &mut usize) -> String {
+    loop {
+        let suffix = format!(".i{}", counter);
+        *counter += 1;
+        if !caller.blocks.iter().any(|b| b.label.ends_with(&suffix)) {
+            return suffix;
+        }
+    }
+}
+
+/// 将函数中对寄存器 `name` 的所有使用替换为 `value`
+fn replace_register(func: &mut IrFunction, name: &str, value: &IrValue) {
+    let replace = |v: &mut IrValue| {
+        if matches!(v, IrValue::Register(n, _) if n == name) {
+            *v = value.clone();
+        }
+    };
+    for block in &mut func.blocks {
+        for inst in &mut block.instructions {
+            inst.values_mut().into_iter().for_each(replace);
+        }
+        if let Some(term) = &mut block.terminator {
+            term.values_mut().into_iter().for_each(replace);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::ir::*;
+    use crate::ir::test_support::*;
 
     #[test]
     fn test_should_inline_small_function() {
@@ -220,4 +424,250 @@ mod tests {
 
         assert!(!inliner.should_inline(&func));
     }
+
+    /// 内联前后执行 `main`，输出必须一致；返回内联后的模块
+    fn inline_and_compare(module: IrModule, config: InlinerConfig) -> (IrModule, Inliner, Execution) {
+        let before = Interpreter::run(&module, "main");
+        let mut inliner = Inliner::with_config(config);
+        let inlined = inliner.run(module).unwrap();
+
+        let result = IrVerifier::new().verify(&inlined);
+        assert!(result.is_valid, "IR invalid after inlining: {:?}", result.errors);
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+        LlvmBackend::emit_module(&inlined).unwrap();
+
+        let after = Interpreter::run(&inlined, "main");
+        assert_eq!(before.output, after.output);
+        (inlined, inliner, after)
+    }
+
+    /// square(x) = x * x，通过 alloca 保存参数（与 IrBuilder 的形式一致）
+    fn square() -> IrFunction {
+        function("square", &["x"], IrType::I32, vec![block("entry", vec![
+            IrInstruction::Alloca { result: reg("t0"), ty: IrType::I32, align: 4 },
+            IrInstruction::Store { value: param("x"), ptr: reg("t0"), ty: IrType::I32 },
+            IrInstruction::Load { result: reg("t1"), ptr: reg("t0"), ty: IrType::I32 },
+            binary("t2", IrBinaryOp::Mul, reg("t1"), reg("t1")),
+        ], ret(reg("t2")))])
+    }
+
+    /// abs(x)：两个返回点
+    fn abs() -> IrFunction {
+        function("abs", &["x"], IrType::I32, vec![
+            block("entry", vec![compare("t0", IrCmpOp::Slt, param("x"), int(0))], cond_br(flag("t0"), "neg", "pos")),
+            block("neg", vec![binary("t1", IrBinaryOp::Sub, int(0), param("x"))], ret(reg("t1"))),
+            block("pos", vec![], ret(param("x"))),
+        ])
+    }
+
+    #[test]
+    fn test_inline_single_return() {
+        let main = function("main", &[], IrType::Void, vec![block("entry", vec![
+            call(Some("t0"), "square", vec![int(7)]),
+            call(Some("t1"), "square", vec![reg("t0")]),
+            print(reg("t1")),
+        ], IrTerminator::Return { value: None })]);
+        let (module, inliner, execution) = inline_and_compare(module(vec![square(), main]), InlinerConfig::default());
+
+        assert_eq!(execution.output, vec![2401]);
+        assert_eq!(execution.calls, 1, "only main itself should be called");
+        let main = module.find_function("main").unwrap();
+        assert_eq!(calls_to(main, "square"), 0);
+        // 两次内联的 alloca 都提升到了入口块开头
+        assert!(main.blocks[0].instructions[..2].iter().all(|i| matches!(i, IrInstruction::Alloca { .. })));
+        assert!(main.find_block("entry.i0").is_some() && main.find_block("inline.cont.i1").is_some());
+
+        let stats = inliner.stats();
+        assert_eq!(stats.candidates_considered, 2);
+        assert_eq!(stats.functions_inlined, 1);
+        assert_eq!(stats.calls_eliminated, 2);
+        assert_eq!(stats.instructions_added, 8);
+        assert_eq!(stats.instructions_removed, 2);
+    }
+
+    #[test]
+    fn test_inline_multi_return_uses_join_phi() {
+        // main 在 abs 调用之后还有分支，汇合块的 phi 引用调用所在的块
+        let main = function("main", &[], IrType::Void, vec![
+            block("entry", vec![
+                call(Some("a"), "abs", vec![int(-5)]),
+                call(Some("b"), "abs", vec![int(3)]),
+                compare("c", IrCmpOp::Sgt, reg("a"), reg("b")),
+            ], cond_br(flag("c"), "bigger", "merge")),
+            block("bigger", vec![print(int(1))], br("merge")),
+            block("merge", vec![
+                IrInstruction::Phi { result: reg("m"), ty: IrType::I32, incoming: vec![
+                    (reg("a"), "entry".to_string()),
+                    (int(0), "bigger".to_string()),
+                ] },
+                binary("s", IrBinaryOp::Add, reg("a"), reg("b")),
+                print(reg("s")),
+                print(reg("m")),
+            ], IrTerminator::Return { value: None }),
+        ]);
+        let (module, _, execution) = inline_and_compare(module(vec![abs(), main]), InlinerConfig::default());
+
+        assert_eq!(execution.output, vec![1, 8, 0]);
+        let main = module.find_function("main").unwrap();
+        assert_eq!(calls_to(main, "abs"), 0);
+
+        let join = main.find_block("inline.cont.i0").unwrap();
+        let IrInstruction::Phi { result, incoming, .. } = &join.instructions[0] else {
+            panic!("join block should start with a phi: {}", join);
+        };
+        assert_eq!(result, &reg("a"));
+        let sources: Vec<&str> = incoming.iter().map(|(_, l)| l.as_str()).collect();
+        assert_eq!(sources, vec!["neg.i0", "pos.i0"]);
+
+        // 原来从 entry 跳入 merge 的 phi 现在来自最后一个续接块
+        let merge = main.find_block("merge").unwrap();
+        let IrInstruction::Phi { incoming, .. } = &merge.instructions[0] else { panic!() };
+        assert_eq!(incoming[0].1, "inline.cont.i1");
+    }
+
+    #[test]
+    fn test_inline_respects_max_instructions() {
+        let main = function("main", &[], IrType::Void, vec![block("entry", vec![
+            call(Some("t0"), "square", vec![int(4)]),
+            print(reg("t0")),
+        ], IrTerminator::Return { value: None })]);
+        let config = InlinerConfig { max_instructions: 3, ..InlinerConfig::default() };
+        let (module, inliner, execution) = inline_and_compare(module(vec![square(), main]), config);
+
+        assert_eq!(execution.output, vec![16]);
+        assert_eq!(calls_to(module.find_function("main").unwrap(), "square"), 1);
+        assert_eq!(inliner.stats().candidates_considered, 1);
+        assert_eq!(inliner.stats().functions_inlined, 0);
+        assert_eq!(inliner.stats().calls_eliminated, 0);
+    }
+
+    /// main -> outer -> middle -> square
+    fn call_chain() -> IrModule {
+        let middle = function("middle", &["x"], IrType::I32, vec![block("entry", vec![
+            call(Some("t0"), "square", vec![param("x")]),
+            binary("t1", IrBinaryOp::Add, reg("t0"), int(1)),
+        ], ret(reg("t1")))]);
+        let outer = function("outer", &["x"], IrType::I32, vec![block("entry", vec![
+            call(Some("t0"), "middle", vec![param("x")]),
+            binary("t1", IrBinaryOp::Mul, reg("t0"), int(2)),
+        ], ret(reg("t1")))]);
+        let main = function("main", &[], IrType::Void, vec![block("entry", vec![
+            call(Some("t0"), "outer", vec![int(3)]),
+            print(reg("t0")),
+        ], IrTerminator::Return { value: None })]);
+        module(vec![square(), middle, outer, main])
+    }
+
+    #[test]
+    fn test_inline_respects_max_depth() {
+        let config = InlinerConfig { max_depth: 2, ..InlinerConfig::default() };
+        let (module, _, execution) = inline_and_compare(call_chain(), config);
+        assert_eq!(execution.output, vec![20]);
+        let main = module.find_function("main").unwrap();
+        assert_eq!(calls_to(main, "outer"), 0);
+        assert_eq!(calls_to(main, "middle"), 0);
+        assert_eq!(calls_to(main, "square"), 1);
+
+        let (module, _, _) = inline_and_compare(call_chain(), InlinerConfig::default());
+        assert_eq!(calls_to(module.find_function("main").unwrap(), "square"), 0);
+
+        let config = InlinerConfig { max_depth: 0, ..InlinerConfig::default() };
+        let (module, inliner, _) = inline_and_compare(call_chain(), config);
+        assert_eq!(calls_to(module.find_function("main").unwrap(), "outer"), 1);
+        assert_eq!(inliner.stats().calls_eliminated, 0);
+    }
+
+    /// fact(n) = n <= 1 ? 1 : n * fact(n - 1)
+    fn factorial_module() -> IrModule {
+        let fact = function("fact", &["n"], IrType::I32, vec![
+            block("entry", vec![compare("t0", IrCmpOp::Sle, param("n"), int(1))], cond_br(flag("t0"), "base", "rec")),
+            block("base", vec![], ret(int(1))),
+            block("rec", vec![
+                binary("t1", IrBinaryOp::Sub, param("n"), int(1)),
+                call(Some("t2"), "fact", vec![reg("t1")]),
+                binary("t3", IrBinaryOp::Mul, param("n"), reg("t2")),
+            ], ret(reg("t3"))),
+        ]);
+        let main = function("main", &[], IrType::Void, vec![block("entry", vec![
+            call(Some("t0"), "fact", vec![int(5)]),
+            print(reg("t0")),
+        ], IrTerminator::Return { value: None })]);
+        module(vec![fact, main])
+    }
+
+    #[test]
+    fn test_inline_recursive_functions() {
+        let (module, inliner, execution) = inline_and_compare(factorial_module(), InlinerConfig::default());
+        assert_eq!(execution.output, vec![120]);
+        assert_eq!(calls_to(module.find_function("main").unwrap(), "fact"), 1);
+        assert_eq!(inliner.stats().calls_eliminated, 0);
+
+        let config = InlinerConfig { inline_recursive: true, max_depth: 2, ..InlinerConfig::default() };
+        let (module, inliner, execution) = inline_and_compare(factorial_module(), config);
+        assert_eq!(execution.output, vec![120]);
+        // main 和 fact 中各展开两层，最内层仍然是递归调用
+        assert_eq!(inliner.stats().calls_eliminated, 4);
+        assert_eq!(calls_to(module.find_function("main").unwrap(), "fact"), 1);
+        assert_eq!(calls_to(module.find_function("fact").unwrap(), "fact"), 1);
+        // main 和一次 fact(3) 调用，fact(2)、fact(1) 已内联
+        assert_eq!(execution.calls, 2);
+    }
+
+    #[test]
+    fn test_inline_mutual_recursion_without_inline_recursive() {
+        // even(n) 调用 odd(n-1)，odd(n) 调用 even(n-1)
+        let parity = |name: &str, other: &str, base: i64| function(name, &["n"], IrType::I32, vec![
+            block("entry", vec![compare("t0", IrCmpOp::Eq, param("n"), int(0))], cond_br(flag("t0"), "base", "rec")),
+            block("base", vec![], ret(int(base))),
+            block("rec", vec![
+                binary("t1", IrBinaryOp::Sub, param("n"), int(1)),
+                call(Some("t2"), other, vec![reg("t1")]),
+            ], ret(reg("t2"))),
+        ]);
+        let main = function("main", &[], IrType::Void, vec![block("entry", vec![
+            call(Some("t0"), "even", vec![int(7)]),
+            print(reg("t0")),
+        ], IrTerminator::Return { value: None })]);
+        let config = InlinerConfig { max_depth: 10, ..InlinerConfig::default() };
+        let (module, _, execution) = inline_and_compare(module(vec![parity("even", "odd", 1), parity("odd", "even", 0), main]), config);
+
+        assert_eq!(execution.output, vec![0]);
+        // even 内联进 main 后，odd 中对 even 的调用不再展开
+        let main = module.find_function("main").unwrap();
+        assert_eq!(calls_to(main, "even"), 1);
+        assert_eq!(calls_to(main, "odd"), 0);
+    }
+
+    #[test]
+    fn test_inline_void_callee_in_loop() {
+        // report(x) 打印 x 和 x*x；main 在循环中调用
+        let report = function("report", &["x"], IrType::Void, vec![block("entry", vec![
+            print(param("x")),
+            call(Some("t0"), "square", vec![param("x")]),
+            print(reg("t0")),
+        ], IrTerminator::Return { value: None })]);
+        let main = function("main", &[], IrType::Void, vec![
+            block("entry", vec![], br("loop")),
+            block("loop", vec![
+                IrInstruction::Phi { result: reg("i"), ty: IrType::I32, incoming: vec![
+                    (int(0), "entry".to_string()),
+                    (reg("next"), "loop".to_string()),
+                ] },
+                call(None, "report", vec![reg("i")]),
+                binary("next", IrBinaryOp::Add, reg("i"), int(1)),
+                compare("done", IrCmpOp::Sge, reg("next"), int(3)),
+            ], cond_br(flag("done"), "exit", "loop")),
+            block("exit", vec![], IrTerminator::Return { value: None }),
+        ]);
+        let (inlined, _, after) = inline_and_compare(module(vec![square(), report, main]), InlinerConfig::default());
+        assert_eq!(after.output, vec![0, 0, 1, 1, 2, 4]);
+        let main = inlined.find_function("main").unwrap();
+        // 循环回边现在来自续接块
+        let IrInstruction::Phi { incoming, .. } = &main.find_block("loop").unwrap().instructions[0] else { panic!() };
+        assert_eq!(incoming[1].1, "inline.cont.i0");
+        assert!(main.blocks[0].instructions.iter().any(|i| matches!(i, IrInstruction::Alloca { .. })));
+        assert!(main.blocks.iter().skip(1).flat_map(|b| &b.instructions).all(|i| !matches!(i, IrInstruction::Alloca { .. })));
+        assert_eq!(calls_to(main, "report") + calls_to(main, "square"), 0);
+        assert_eq!(after.calls, 1);
+    }
 }
@@ -375,6 +375,32 @@ public class Dog extends Animal {
         assert!(ir.contains("Dog.__ctor"));
     }
 
+    #[test]
+    fn test_interface_dispatch_panics_on_unknown_type() {
+        let source = r#"
+public interface Shape {
+    int area();
+}
+public class Square implements Shape {
+    public int area() { return 4; }
+}
+public class Main {
+    public static void main() {
+        Shape s = new Square();
+        println(s.area());
+    }
+}
+"#;
+        let module = build_ir(source);
+        let ir = verify_and_emit(&module);
+
+        // 未知类型 ID 报告接口方法 area 的声明位置（第 3 行）
+        let unknown = ir.split("  unknown:\n").nth(1).unwrap();
+        let unknown = &unknown[..unknown.find("\n}").unwrap()];
+        assert!(unknown.contains("@__cay_panic(i32 6,") && unknown.contains("i32 3,"), "{}", unknown);
+        assert!(!unknown.contains("@exit("), "{}", unknown);
+    }
+
     #[test]
     fn test_static_fields() {
         let source = r#"
@@ -2,13 +2,17 @@
 //!
 //! 将 IrModule 发射为 LLVM IR 文本格式（.ll 文件）。
 //! 这是 IR 到最终可编译 LLVM IR 的主要输出通道。
+//! 启用调试信息时由 `SourceLocation`/`VarDecl` 指令生成 DWARF 元数据（见 [`super::debug_info`]）。
 
 use super::module::IrModule;
 use super::function::{IrFunction, IrLinkage};
 use super::block::IrBasicBlock;
-use super::value::{IrInstruction, IrTerminator};
+use super::value::{IrInstruction, IrTerminator, IrValue};
 use super::types::IrType;
+use super::debug_info::DebugInfoBuilder;
 use crate::error::cayResult;
+use crate::target::TargetInfo;
+use std::collections::{HashMap, HashSet};
 
 /// LLVM IR 文本后端
 pub struct LlvmBackend {
@@ -18,6 +22,29 @@ pub struct LlvmBackend {
     indent: usize,
     /// 字符串常量注册表
     string_decls: Vec<String>,
+    /// 是否生成 DWARF 调试信息
+    debug_info: bool,
+    /// 当前模块的调试元数据（模块没有源位置时为 None）
+    debug: Option<DebugInfoBuilder>,
+    /// 当前函数的调试状态
+    debug_function: Option<DebugFunction>,
+    /// 是否引用了 `llvm.dbg.declare`
+    uses_dbg_declare: bool,
+    /// 当前模块的目标平台（对齐按它计算）
+    target: TargetInfo,
+}
+
+/// 正在发射的函数的调试状态
+struct DebugFunction {
+    subprogram: usize,
+    /// 当前指令的 `DILocation`
+    location: usize,
+    file: String,
+    line: u32,
+    /// 函数中定义的 alloca（mem2reg/dce 之后变量声明可能指向已删除的 alloca）
+    allocas: HashSet<String>,
+    /// 保存参数的 alloca -> 参数序号（从 1 开始）
+    param_slots: HashMap<String, usize>,
 }
 
 impl LlvmBackend {
@@ -27,21 +54,52 @@ impl LlvmBackend {
             output: String::new(),
             indent: 0,
             string_decls: Vec::new(),
+            debug_info: false,
+            debug: None,
+            debug_function: None,
+            uses_dbg_declare: false,
+            target: TargetInfo::default(),
         }
     }
 
+    /// 设置是否生成 DWARF 调试信息（`-g`）
+    pub fn set_debug_info(&mut self, enabled: bool) {
+        self.debug_info = enabled;
+    }
+
     /// 将 IR 模块发射为 LLVM IR 文本
     pub fn emit(&mut self, module: &IrModule) -> cayResult<String> {
         self.output.clear();
         self.string_decls.clear();
+        self.uses_dbg_declare = false;
+        // 无法识别的三元组（如手写的 .cayir）不输出数据布局，对齐按默认目标计算
+        let target = TargetInfo::from_triple(&module.target_triple).ok();
+        self.target = target.clone().unwrap_or_default();
+        self.debug = if self.debug_info { compile_unit_file(module).map(DebugInfoBuilder::new) } else { None };
+        if let Some(debug) = &mut self.debug {
+            debug.set_target(self.target.clone());
+            debug.set_type_declarations(&module.type_declarations);
+        }
 
         // 模块头部
         self.emit_module_header(module);
 
-        // 目标三元组
+        // 数据布局和目标三元组
+        if let Some(target) = &target {
+            self.emit_line(&format!("target datalayout = \"{}\"", target.data_layout));
+        }
         self.emit_line(&format!("target triple = \"{}\"", module.target_triple));
         self.emit_line("");
 
+        // 运行时支持代码
+        if !module.runtime_prelude.is_empty() {
+            self.output.push_str(&module.runtime_prelude);
+            if !module.runtime_prelude.ends_with('\n') {
+                self.output.push('\n');
+            }
+            self.emit_line("");
+        }
+
         // 字符串常量声明
         self.emit_string_constants(module);
 
@@ -59,6 +117,23 @@ impl LlvmBackend {
             self.emit_function(module, func)?;
         }
 
+        // 外部声明引用的调用约定属性组
+        if module.extern_declarations.iter().any(|d| d.calling_convention.is_some()) {
+            self.emit_line("; Calling convention attributes");
+            for (i, cc) in ["cdecl", "stdcall", "fastcall", "sysv64", "win64"].iter().enumerate() {
+                self.emit_line(&format!("attributes #{} = {{ \"{}\" }}", i, cc));
+            }
+        }
+
+        // 调试元数据
+        if let Some(debug) = self.debug.take() {
+            if self.uses_dbg_declare {
+                self.emit_line("declare void @llvm.dbg.declare(metadata, metadata, metadata)");
+                self.emit_line("");
+            }
+            self.output.push_str(&debug.finish());
+        }
+
         Ok(self.output.clone())
     }
 
@@ -89,18 +164,17 @@ impl LlvmBackend {
         }
 
         self.emit_line("; String constants");
-        for (name, value) in &module.string_constants {
-            let escaped = value
-                .replace("\\", "\\5C")
-                .replace("\"", "\\22")
-                .replace("\n", "\\0A")
-                .replace("\r", "\\0D")
-                .replace("\t", "\\09")
-                .replace("\0", "\\00");
+        // 按编号排序，保证输出稳定
+        let mut constants: Vec<(&String, &String)> = module.string_constants.iter().collect();
+        constants.sort_by_key(|(name, _)| {
+            let index = name.rsplit('.').next().and_then(|n| n.parse::<u64>().ok());
+            (index.is_none(), index, name.to_string())
+        });
+        for (name, value) in constants {
             let len = value.as_bytes().len() + 1;
             self.emit_line(&format!(
                 "{} = private unnamed_addr constant [{} x i8] c\"{}\\00\", align 1",
-                name, len, escaped
+                name, len, escape_c_string(value)
             ));
         }
         self.emit_line("");
@@ -178,25 +252,30 @@ impl LlvmBackend {
         self.emit_line("; Global variables");
         for global in &module.globals {
             let linkage = match global.linkage {
-                super::module::IrGlobalLinkage::External => "external",
-                super::module::IrGlobalLinkage::Internal => "internal",
-                super::module::IrGlobalLinkage::Private => "private",
+                super::module::IrGlobalLinkage::External => "",
+                super::module::IrGlobalLinkage::Internal => "internal ",
+                super::module::IrGlobalLinkage::Private => "private ",
+                super::module::IrGlobalLinkage::LinkOnceOdr => "linkonce_odr ",
+                super::module::IrGlobalLinkage::Declare => "external ",
             };
 
+            // 外部声明没有初始值
             let init_str = match &global.initializer {
-                Some(val) => val.to_llvm_str(),
-                None => "zeroinitializer".to_string(),
+                Some(val) => format!(" {}", val.to_raw_str()),
+                None if global.linkage == super::module::IrGlobalLinkage::Declare => String::new(),
+                None => " zeroinitializer".to_string(),
             };
 
             let const_str = if global.is_constant { "constant" } else { "global" };
 
             self.emit_line(&format!(
-                "{} = {} {} {} {}",
+                "{} = {}{} {}{}, align {}",
                 global.name,
                 linkage,
                 const_str,
                 global.ty.to_llvm_str(),
-                init_str
+                init_str,
+                global.ty.alignment(&self.target)
             ));
         }
         self.emit_line("");
@@ -239,9 +318,15 @@ impl LlvmBackend {
             None => String::new(),
         };
 
+        self.debug_function = self.begin_debug_function(func);
+        let dbg_str = match &self.debug_function {
+            Some(debug) => format!(" !dbg !{}", debug.subprogram),
+            None => String::new(),
+        };
+
         self.emit_line(&format!(
-            "define {}{} @{}({}){} {{",
-            linkage, ret_str, func.name, param_strs.join(", "), cc_str
+            "define {}{} @{}({}){}{} {{",
+            linkage, ret_str, func.name, param_strs.join(", "), cc_str, dbg_str
         ));
         self.indent += 1;
 
@@ -254,11 +339,73 @@ impl LlvmBackend {
         }
 
         self.indent -= 1;
+        self.debug_function = None;
         self.emit_line("}");
         self.emit_line("");
         Ok(())
     }
 
+    /// 为有源位置的函数创建 `DISubprogram`，起始位置取函数中的第一条源位置
+    fn begin_debug_function(&mut self, func: &IrFunction) -> Option<DebugFunction> {
+        let debug = self.debug.as_mut()?;
+        let (file, line) = func.blocks.iter()
+            .flat_map(|b| &b.instructions)
+            .find_map(|inst| match inst {
+                IrInstruction::SourceLocation { file, line, .. } => Some((file.clone(), *line)),
+                _ => None,
+            })?;
+        let signature: Vec<IrType> = std::iter::once(func.return_type.clone())
+            .chain(func.params.iter().map(|p| p.ty.clone()))
+            .collect();
+        let subprogram = debug.subprogram(&func.name, &file, line, &signature);
+        let location = debug.location(subprogram, &file, line, 0);
+
+        let mut allocas = HashSet::new();
+        let mut param_slots = HashMap::new();
+        for inst in func.blocks.iter().flat_map(|b| &b.instructions) {
+            match inst {
+                IrInstruction::Alloca { result: IrValue::Register(name, _), .. } => {
+                    allocas.insert(name.clone());
+                }
+                IrInstruction::Store { value: IrValue::Param(param, _), ptr: IrValue::Register(slot, _), .. } => {
+                    if let Some(index) = func.params.iter().position(|p| &p.name == param) {
+                        param_slots.entry(slot.clone()).or_insert(index + 1);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some(DebugFunction { subprogram, location, file, line, allocas, param_slots })
+    }
+
+    /// 给刚发射的指令附上当前调试位置
+    fn attach_debug_location(&mut self) {
+        let Some(debug) = &self.debug_function else { return };
+        if self.output.ends_with('\n') {
+            self.output.pop();
+        }
+        self.output.push_str(&format!(", !dbg !{}\n", debug.location));
+    }
+
+    /// 为局部变量或参数发射 `llvm.dbg.declare`
+    fn emit_dbg_declare(&mut self, name: &str, slot: &IrValue, ty: &IrType) {
+        let (Some(debug), Some(function)) = (self.debug.as_mut(), self.debug_function.as_ref()) else { return };
+        let IrValue::Register(reg, _) = slot else { return };
+        if !function.allocas.contains(reg) {
+            return;
+        }
+        let arg = function.param_slots.get(reg).copied();
+        let Some(variable) = debug.local_variable(function.subprogram, name, arg, &function.file, function.line, ty) else {
+            return;
+        };
+        self.uses_dbg_declare = true;
+        let location = function.location;
+        self.emit_line(&format!(
+            "  call void @llvm.dbg.declare(metadata {} {}, metadata !{}, metadata !DIExpression()), !dbg !{}",
+            slot.ir_type().to_llvm_str(), reg, variable, location
+        ));
+    }
+
     // ============================================================
     // 基本块
     // ============================================================
@@ -268,10 +415,15 @@ impl LlvmBackend {
 
         for inst in &block.instructions {
             self.emit_instruction(inst)?;
+            if !matches!(inst, IrInstruction::InlineIr { .. } | IrInstruction::Comment { .. }
+                | IrInstruction::SourceLocation { .. } | IrInstruction::VarDecl { .. }) {
+                self.attach_debug_location();
+            }
         }
 
         if let Some(term) = &block.terminator {
             self.emit_terminator(term)?;
+            self.attach_debug_location();
         }
 
         Ok(())
@@ -286,21 +438,21 @@ impl LlvmBackend {
             IrInstruction::Alloca { result, ty, align } => {
                 self.emit_line(&format!(
                     "  {} = alloca {}, align {}",
-                    result.to_llvm_str(), ty.to_llvm_str(), align
+                    result.to_raw_str(), ty.to_llvm_str(), align
                 ));
             }
 
             IrInstruction::Load { result, ptr, ty } => {
                 self.emit_line(&format!(
-                    "  {} = load {}, {}* {}",
-                    result.to_llvm_str(), ty.to_llvm_str(), ty.to_llvm_str(), ptr.to_llvm_str()
+                    "  {} = load {}, {}* {}, align {}",
+                    result.to_raw_str(), ty.to_llvm_str(), ty.to_llvm_str(), ptr.to_raw_str(), ty.alignment(&self.target)
                 ));
             }
 
             IrInstruction::Store { value, ptr, ty } => {
                 self.emit_line(&format!(
-                    "  store {} {}, {}* {}",
-                    ty.to_llvm_str(), value.to_llvm_str(), ty.to_llvm_str(), ptr.to_llvm_str()
+                    "  store {} {}, {}* {}, align {}",
+                    ty.to_llvm_str(), value.to_raw_str(), ty.to_llvm_str(), ptr.to_raw_str(), ty.alignment(&self.target)
                 ));
             }
 
@@ -308,8 +460,8 @@ impl LlvmBackend {
                 let ty = result.ir_type();
                 self.emit_line(&format!(
                     "  {} = {} {} {}, {}",
-                    result.to_llvm_str(), op.to_llvm_str(),
-                    ty.to_llvm_str(), left.to_llvm_str(), right.to_llvm_str()
+                    result.to_raw_str(), op.to_llvm_str(),
+                    ty.to_llvm_str(), left.to_raw_str(), right.to_raw_str()
                 ));
             }
 
@@ -324,8 +476,8 @@ impl LlvmBackend {
 
                 self.emit_line(&format!(
                     "  {} = {} {} {}, {}",
-                    result.to_llvm_str(), cmp_str,
-                    left_ty.to_llvm_str(), left.to_llvm_str(), right.to_llvm_str()
+                    result.to_raw_str(), cmp_str,
+                    left_ty.to_llvm_str(), left.to_raw_str(), right.to_raw_str()
                 ));
             }
 
@@ -333,55 +485,60 @@ impl LlvmBackend {
                 let from_ty = value.ir_type();
                 self.emit_line(&format!(
                     "  {} = {} {} {} to {}",
-                    result.to_llvm_str(), kind.to_llvm_str(),
-                    from_ty.to_llvm_str(), value.to_llvm_str(), to_ty.to_llvm_str()
+                    result.to_raw_str(), kind.to_llvm_str(),
+                    from_ty.to_llvm_str(), value.to_raw_str(), to_ty.to_llvm_str()
                 ));
             }
 
             IrInstruction::Call { result, func_name, args, return_ty } => {
-                let arg_strs: Vec<String> = args.iter()
-                    .map(|a| format!("{} {}", a.ir_type().to_llvm_str(), a.to_llvm_str()))
-                    .collect();
+                let call = format!("call {} @{}({})", return_ty.to_llvm_str(), func_name, typed_args(args));
+                match result {
+                    Some(res) if *return_ty != IrType::Void => {
+                        self.emit_line(&format!("  {} = {}", res.to_raw_str(), call));
+                    }
+                    _ => self.emit_line(&format!("  {}", call)),
+                }
+            }
 
-                if let Some(res) = result {
-                    if *return_ty == IrType::Void {
-                        self.emit_line(&format!(
-                            "  call void @{}({})",
-                            func_name, arg_strs.join(", ")
-                        ));
-                    } else {
-                        self.emit_line(&format!(
-                            "  {} = call {} @{}({})",
-                            res.to_llvm_str(), return_ty.to_llvm_str(),
-                            func_name, arg_strs.join(", ")
-                        ));
+            IrInstruction::CallIndirect { result, callee, return_ty, param_tys, is_varargs, args } => {
+                let mut params: Vec<String> = param_tys.iter().map(|t| t.to_llvm_str()).collect();
+                if *is_varargs {
+                    params.push("...".to_string());
+                }
+                let call = format!(
+                    "call {} ({}) {}({})",
+                    return_ty.to_llvm_str(), params.join(", "), callee.to_raw_str(), typed_args(args)
+                );
+                match result {
+                    Some(res) if *return_ty != IrType::Void => {
+                        self.emit_line(&format!("  {} = {}", res.to_raw_str(), call));
                     }
-                } else {
-                    self.emit_line(&format!(
-                        "  call void @{}({})",
-                        func_name, arg_strs.join(", ")
-                    ));
+                    _ => self.emit_line(&format!("  {}", call)),
                 }
             }
 
+            IrInstruction::ExtractValue { result, aggregate, index } => {
+                self.emit_line(&format!(
+                    "  {} = extractvalue {} {}, {}",
+                    result.to_raw_str(), aggregate.ir_type().to_llvm_str(), aggregate.to_raw_str(), index
+                ));
+            }
+
             IrInstruction::GetElementPtr { result, ptr, indices, base_ty } => {
-                let idx_strs: Vec<String> = indices.iter()
-                    .map(|i| format!("{} {}", i.ir_type().to_llvm_str(), i.to_llvm_str()))
-                    .collect();
                 self.emit_line(&format!(
                     "  {} = getelementptr {}, {} {}, {}",
-                    result.to_llvm_str(),
+                    result.to_raw_str(),
                     base_ty.to_llvm_str(),
-                    ptr.ir_type().to_llvm_str(), ptr.to_llvm_str(),
-                    idx_strs.join(", ")
+                    ptr.ir_type().to_llvm_str(), ptr.to_raw_str(),
+                    typed_args(indices)
                 ));
             }
 
             IrInstruction::BitCast { result, value, to_ty } => {
                 self.emit_line(&format!(
                     "  {} = bitcast {} {} to {}",
-                    result.to_llvm_str(),
-                    value.ir_type().to_llvm_str(), value.to_llvm_str(),
+                    result.to_raw_str(),
+                    value.ir_type().to_llvm_str(), value.to_raw_str(),
                     to_ty.to_llvm_str()
                 ));
             }
@@ -389,12 +546,12 @@ impl LlvmBackend {
             IrInstruction::Phi { result, ty, incoming } => {
                 let incoming_strs: Vec<String> = incoming.iter()
                     .map(|(val, label)| {
-                        format!("[ {}, %{} ]", val.to_llvm_str(), label)
+                        format!("[ {}, %{} ]", val.to_raw_str(), label)
                     })
                     .collect();
                 self.emit_line(&format!(
                     "  {} = phi {} {}",
-                    result.to_llvm_str(), ty.to_llvm_str(), incoming_strs.join(", ")
+                    result.to_raw_str(), ty.to_llvm_str(), incoming_strs.join(", ")
                 ));
             }
 
@@ -402,10 +559,10 @@ impl LlvmBackend {
                 let ty = true_val.ir_type();
                 self.emit_line(&format!(
                     "  {} = select i1 {}, {} {}, {} {}",
-                    result.to_llvm_str(),
-                    condition.to_llvm_str(),
-                    ty.to_llvm_str(), true_val.to_llvm_str(),
-                    ty.to_llvm_str(), false_val.to_llvm_str()
+                    result.to_raw_str(),
+                    condition.to_raw_str(),
+                    ty.to_llvm_str(), true_val.to_raw_str(),
+                    ty.to_llvm_str(), false_val.to_raw_str()
                 ));
             }
 
@@ -420,13 +577,20 @@ impl LlvmBackend {
                 self.emit_line(&format!("  ; {}", text));
             }
 
-            IrInstruction::SourceLocation { line, column } => {
-                self.emit_line(&format!("  ; #line {}:{}", line, column));
+            IrInstruction::SourceLocation { file, line, column } => {
+                // 与旧代码生成器相同的源映射注释格式，ir2exe 据此把 llc 错误映射回源码
+                self.emit_line(&format!("; !source {}:{}:{}", file, line, column));
+                if let (Some(debug), Some(function)) = (self.debug.as_mut(), self.debug_function.as_mut()) {
+                    function.location = debug.location(function.subprogram, file, *line, *column);
+                    function.file = file.clone();
+                    function.line = *line;
+                }
             }
 
-            IrInstruction::VarDecl { name, alloca_reg: _, ty: _ } => {
-                // 变量声明是元数据，仅作为注释输出
+            IrInstruction::VarDecl { name, alloca_reg, ty } => {
+                // 变量声明是元数据：注释输出，启用调试信息时生成 llvm.dbg.declare
                 self.emit_line(&format!("  ; var: {}", name));
+                self.emit_dbg_declare(name, alloca_reg, ty);
             }
         }
 
@@ -457,19 +621,19 @@ impl LlvmBackend {
             IrTerminator::ConditionalBranch { condition, true_target, false_target } => {
                 self.emit_line(&format!(
                     "  br i1 {}, label %{}, label %{}",
-                    condition.to_llvm_str(), true_target, false_target
+                    condition.to_raw_str(), true_target, false_target
                 ));
             }
 
             IrTerminator::Switch { value, default_target, cases, ty } => {
                 self.emit_line(&format!(
                     "  switch {} {}, label %{} [",
-                    ty.to_llvm_str(), value.to_llvm_str(), default_target
+                    ty.to_llvm_str(), value.to_raw_str(), default_target
                 ));
                 for (case_val, case_label) in cases {
                     self.emit_line(&format!(
                         "    {} {}, label %{}",
-                        ty.to_llvm_str(), case_val.to_llvm_str(), case_label
+                        ty.to_llvm_str(), case_val.to_raw_str(), case_label
                     ));
                 }
                 self.emit_line("  ]");
@@ -502,6 +666,52 @@ impl LlvmBackend {
         let mut backend = Self::new();
         backend.emit(module)
     }
+
+    /// 便捷方法：带 DWARF 调试信息的 LLVM IR 文本
+    pub fn emit_module_with_debug_info(module: &IrModule) -> cayResult<String> {
+        let mut backend = Self::new();
+        backend.set_debug_info(true);
+        backend.emit(module)
+    }
+}
+
+/// 调试信息的编译单元文件：`main` 函数所在的源文件，没有 `main` 时取模块中第一条源位置所在的文件
+fn compile_unit_file(module: &IrModule) -> Option<&str> {
+    module.functions.iter()
+        .find(|f| f.name == "main")
+        .and_then(first_source_file)
+        .or_else(|| module.functions.iter().find_map(first_source_file))
+}
+
+/// 函数中第一条源位置所在的文件
+fn first_source_file(func: &IrFunction) -> Option<&str> {
+    func.blocks.iter()
+        .flat_map(|b| &b.instructions)
+        .find_map(|inst| match inst {
+            IrInstruction::SourceLocation { file, .. } => Some(file.as_str()),
+            _ => None,
+        })
+}
+
+/// `ty value, ty value` 形式的带类型参数列表
+fn typed_args(values: &[IrValue]) -> String {
+    values.iter()
+        .map(|v| format!("{} {}", v.ir_type().to_llvm_str(), v.to_raw_str()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 转义 LLVM `c"..."` 字符串：引号、反斜杠和控制字符写成 `\XX`，其余字节原样输出
+fn escape_c_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if ch == '"' || ch == '\\' || (ch as u32) < 0x20 || ch == '\u{7f}' {
+            escaped.push_str(&format!("\\{:02X}", ch as u32));
+        } else {
+            escaped.push(ch);
+        }
+    }
+    escaped
 }
 
 impl Default for LlvmBackend {
@@ -544,6 +754,39 @@ mod tests {
         assert!(result.contains("ret i32 0"), "Output was:\n{}", result);
     }
 
+    #[test]
+    fn test_debug_info() {
+        let mut module = IrModule::new("test".to_string(), "x86_64-unknown-linux-gnu".to_string());
+        let slot = IrValue::Register("%n_s1".to_string(), IrType::Pointer(Box::new(IrType::I32)));
+        let mut func = IrFunction::new(
+            "Main.square".to_string(),
+            IrType::I32,
+            vec![IrParam { name: "n".to_string(), ty: IrType::I32 }],
+        );
+        let entry = func.entry_block_mut().unwrap();
+        entry.instructions = vec![
+            IrInstruction::Alloca { result: slot.clone(), ty: IrType::I32, align: 4 },
+            IrInstruction::SourceLocation { file: "main.cay".to_string(), line: 2, column: 5 },
+            IrInstruction::Store { value: IrValue::Param("n".to_string(), IrType::I32), ptr: slot.clone(), ty: IrType::I32 },
+            IrInstruction::VarDecl { name: "n".to_string(), alloca_reg: slot, ty: IrType::I32 },
+            IrInstruction::SourceLocation { file: "util.cay".to_string(), line: 7, column: 1 },
+        ];
+        entry.set_terminator(IrTerminator::Return { value: Some(IrValue::IntConst(0, IrType::I32)) });
+        module.add_function(func);
+
+        let plain = LlvmBackend::emit_module(&module).unwrap();
+        assert!(!plain.contains("!dbg"), "Output was:\n{}", plain);
+
+        let result = LlvmBackend::emit_module_with_debug_info(&module).unwrap();
+        assert!(result.contains("define i32 @Main.square(i32 %n) !dbg !"), "Output was:\n{}", result);
+        assert!(result.contains("store i32 %n, i32* %n_s1, align 4, !dbg !"), "Output was:\n{}", result);
+        assert!(result.contains("call void @llvm.dbg.declare(metadata i32* %n_s1"), "Output was:\n{}", result);
+        assert!(result.contains("!DILocalVariable(name: \"n\", arg: 1"), "Output was:\n{}", result);
+        assert!(result.contains("!DILexicalBlockFile"), "Output was:\n{}", result);
+        assert!(result.contains("ret i32 0, !dbg !"), "Output was:\n{}", result);
+        assert!(result.contains("!llvm.dbg.cu"), "Output was:\n{}", result);
+    }
+
     #[test]
     fn test_string_constant() {
         let mut module = IrModule::new("test".to_string(), "x86_64-unknown-linux-gnu".to_string());
@@ -11,7 +11,7 @@
 //! - **SSA 风格**: 值不可变，通过寄存器引用
 //! - **类型安全**: 每个值和指令都带类型信息
 //! - **可优化**: 支持 IR 级别的优化 pass（内联、死代码消除等）
-//! - **可序列化**: 支持 .cayir 格式（计划中）
+//! - **可序列化**: 支持 .cayir 文本格式（见 [`cayir`]），可写出并解析回 IrModule
 //! - **内联 IR**: 支持嵌入原始 LLVM IR 代码片段
 //!
 //! # 架构
@@ -19,7 +19,7 @@
 //! ```text
 //! AST ──► IR Builder ──► IrModule ──► LLVM Backend ──► .ll 文件
 //!                  │                    │
-//!                  │                    ├──► Inliner Pass
+//!                  │                    ├──► Pass Manager (fold/dce/simplifycfg/cse/mem2reg/inline)
 //!                  │                    ├──► Verification
 //!                  │                    └──► Bytecode Backend (未来)
 //!                  │
@@ -56,10 +56,15 @@ pub mod builder;
 pub mod llvm_backend;
 pub mod inline_ir;
 pub mod inliner;
+pub mod passes;
 pub mod verification;
+pub mod cayir;
+pub mod debug_info;
 
 #[cfg(test)]
 mod integration_tests;
+#[cfg(test)]
+mod test_support;
 
 // 核心类型重导出
 pub use types::IrType;
@@ -77,4 +82,7 @@ pub use builder::IrBuilder;
 pub use llvm_backend::LlvmBackend;
 pub use inline_ir::{InlineIrParser, InlineIrBlock};
 pub use inliner::{Inliner, InlinerConfig};
+pub use passes::{IrPass, PassManager, PassStats};
 pub use verification::IrVerifier;
+pub use cayir::{parse_module as parse_cayir, write_module as write_cayir};
+pub use debug_info::DebugInfoBuilder;
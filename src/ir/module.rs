@@ -8,7 +8,7 @@ use super::value::IrValue;
 use std::collections::HashMap;
 
 /// 全局变量定义
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct IrGlobal {
     pub name: String,
     pub ty: IrType,
@@ -23,17 +23,21 @@ pub enum IrGlobalLinkage {
     External,
     Internal,
     Private,
+    /// 各模块都有同样的定义，链接时只保留一份
+    LinkOnceOdr,
+    /// 只声明，定义在其他模块（没有初始值）
+    Declare,
 }
 
 /// 字符串常量
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct IrStringConstant {
     pub name: String,
     pub value: String,
 }
 
 /// 外部函数声明
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct IrExternDecl {
     pub name: String,
     pub return_type: IrType,
@@ -62,6 +66,8 @@ pub struct IrModule {
     pub string_constants: HashMap<String, String>,
     /// 类型声明（结构体等）
     pub type_declarations: Vec<IrTypeDecl>,
+    /// 运行时支持代码（字符串运算、panic 等运行时函数的 LLVM IR 文本），原样输出到目标三元组之后
+    pub runtime_prelude: String,
     /// 字符串计数器
     string_counter: u64,
     /// 全局计数器（用于生成唯一名称）
@@ -69,7 +75,7 @@ pub struct IrModule {
 }
 
 /// 类型声明（结构体定义）
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct IrTypeDecl {
     pub name: String,
     pub fields: Vec<(String, IrType)>,
@@ -86,6 +92,7 @@ impl IrModule {
             globals: Vec::new(),
             string_constants: HashMap::new(),
             type_declarations: Vec::new(),
+            runtime_prelude: String::new(),
             string_counter: 0,
             global_counter: 0,
         }
@@ -132,6 +139,22 @@ impl IrModule {
         name
     }
 
+    /// 按已有的 `@.str.N` 和 `@prefix.N` 名称恢复计数器（用于从 .cayir 加载的模块）
+    pub(crate) fn sync_name_counters(&mut self) {
+        let suffix = |name: &str| name.rsplit('.').next().and_then(|n| n.parse::<u64>().ok());
+        self.string_counter = self.string_constants.keys()
+            .filter(|name| name.starts_with("@.str."))
+            .filter_map(|name| suffix(name))
+            .map(|n| n + 1)
+            .max()
+            .unwrap_or(0);
+        self.global_counter = self.globals.iter()
+            .filter_map(|g| suffix(&g.name))
+            .map(|n| n + 1)
+            .max()
+            .unwrap_or(0);
+    }
+
     /// 添加类型声明
     pub fn add_type_decl(&mut self, decl: IrTypeDecl) {
         self.type_declarations.push(decl);
@@ -0,0 +1,148 @@
+//! 基本块内的公共子表达式消除
+//!
+//! 同一基本块中操作和操作数都相同的纯指令只保留第一条，之后的使用改为引用它。
+//! load 也参与消除，但遇到 store、函数调用或内联 IR 时失效（它们可能修改内存）。
+//! 可交换运算的操作数按文本排序，`a + b` 与 `b + a` 视为同一表达式。
+
+use super::{register_name, replace_uses, run_on_functions, IrPass};
+use crate::ir::function::IrFunction;
+use crate::ir::module::IrModule;
+use crate::ir::value::{IrBinaryOp, IrInstruction, IrValue};
+use crate::error::cayResult;
+use std::collections::HashMap;
+
+/// 局部 CSE pass（`cse`）
+pub struct LocalCse;
+
+impl IrPass for LocalCse {
+    fn name(&self) -> &'static str {
+        "cse"
+    }
+
+    fn run(&mut self, module: &mut IrModule) -> cayResult<usize> {
+        Ok(run_on_functions(module, eliminate_common_subexpressions))
+    }
+}
+
+/// 消除函数各基本块内的重复计算，返回删除的指令数
+pub fn eliminate_common_subexpressions(func: &mut IrFunction) -> usize {
+    let mut replacements: HashMap<String, IrValue> = HashMap::new();
+    for block in &mut func.blocks {
+        let mut available: HashMap<String, IrValue> = HashMap::new();
+        let mut loads: HashMap<String, IrValue> = HashMap::new();
+        let mut kept = Vec::with_capacity(block.instructions.len());
+
+        for mut inst in block.instructions.drain(..) {
+            // 先应用本块已有的替换，使后续表达式的键一致
+            for value in inst.values_mut() {
+                if let Some(new) = register_name(value).and_then(|n| replacements.get(n)) {
+                    *value = new.clone();
+                }
+            }
+
+            if matches!(inst,
+                IrInstruction::Store { .. } | IrInstruction::Call { .. } | IrInstruction::CallIndirect { .. } | IrInstruction::InlineIr { .. }
+            ) {
+                loads.clear();
+            }
+            let (table, key) = match expression_key(&inst) {
+                Some(key) if matches!(inst, IrInstruction::Load { .. }) => (&mut loads, key),
+                Some(key) => (&mut available, key),
+                None => {
+                    kept.push(inst);
+                    continue;
+                }
+            };
+            let Some(IrValue::Register(name, _)) = inst.result().cloned() else {
+                kept.push(inst);
+                continue;
+            };
+            match table.get(&key) {
+                Some(existing) => {
+                    replacements.insert(name, existing.clone());
+                }
+                None => {
+                    table.insert(key, IrValue::Register(name, inst.result().unwrap().ir_type()));
+                    kept.push(inst);
+                }
+            }
+        }
+        block.instructions = kept;
+    }
+    let removed = replacements.len();
+    replace_uses(func, &replacements);
+    removed
+}
+
+/// 可交换的二元运算
+fn is_commutative(op: IrBinaryOp) -> bool {
+    matches!(op,
+        IrBinaryOp::Add | IrBinaryOp::Mul | IrBinaryOp::And | IrBinaryOp::Or | IrBinaryOp::Xor
+        | IrBinaryOp::FAdd | IrBinaryOp::FMul
+    )
+}
+
+/// 纯指令的表达式键（不含结果寄存器）；不参与 CSE 的指令返回 None
+fn expression_key(inst: &IrInstruction) -> Option<String> {
+    let key = match inst {
+        IrInstruction::BinaryOp { result, op, left, right } => {
+            let (mut a, mut b) = (format!("{:?}", left), format!("{:?}", right));
+            if is_commutative(*op) && a > b {
+                std::mem::swap(&mut a, &mut b);
+            }
+            format!("bin {:?} {:?} {} {}", op, result.ir_type(), a, b)
+        }
+        IrInstruction::Compare { op, left, right, .. } => format!("cmp {:?} {:?} {:?}", op, left, right),
+        IrInstruction::Cast { kind, value, to_ty, .. } => format!("cast {:?} {:?} {:?}", kind, value, to_ty),
+        IrInstruction::GetElementPtr { ptr, indices, base_ty, .. } => {
+            format!("gep {:?} {:?} {:?}", base_ty, ptr, indices)
+        }
+        IrInstruction::BitCast { value, to_ty, .. } => format!("bitcast {:?} {:?}", value, to_ty),
+        IrInstruction::Select { condition, true_val, false_val, .. } => {
+            format!("select {:?} {:?} {:?}", condition, true_val, false_val)
+        }
+        IrInstruction::Load { ptr, ty, .. } => format!("load {:?} {:?}", ty, ptr),
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::*;
+    use crate::ir::test_support::*;
+
+    #[test]
+    fn test_local_cse() {
+        let main = function("main", &[], IrType::Void, vec![block("entry", vec![
+            IrInstruction::Alloca { result: reg("slot"), ty: IrType::I32, align: 4 },
+            IrInstruction::Store { value: int(6), ptr: reg("slot"), ty: IrType::I32 },
+            IrInstruction::Load { result: reg("x"), ptr: reg("slot"), ty: IrType::I32 },
+            IrInstruction::Load { result: reg("y"), ptr: reg("slot"), ty: IrType::I32 },
+            binary("a", IrBinaryOp::Add, reg("x"), int(1)),
+            binary("b", IrBinaryOp::Add, int(1), reg("y")),
+            binary("c", IrBinaryOp::Mul, reg("a"), reg("b")),
+            print(reg("c")),
+            // store 之后的 load 不能复用之前的值
+            IrInstruction::Store { value: int(2), ptr: reg("slot"), ty: IrType::I32 },
+            IrInstruction::Load { result: reg("z"), ptr: reg("slot"), ty: IrType::I32 },
+            binary("d", IrBinaryOp::Sub, reg("z"), int(1)),
+            binary("e", IrBinaryOp::Sub, int(1), reg("z")),
+            print(reg("d")),
+            print(reg("e")),
+        ], IrTerminator::Return { value: None })]);
+        let mut module = module(vec![main]);
+        let before = Interpreter::run(&module, "main");
+
+        // y 与 x 相同，b 与 a 相同（加法可交换）；减法不可交换
+        assert_eq!(LocalCse.run(&mut module).unwrap(), 2);
+        let main = module.find_function("main").unwrap();
+        assert_eq!(main.blocks[0].instructions.len(), 12);
+        let IrInstruction::BinaryOp { left, right, .. } = &main.blocks[0].instructions[4] else { panic!() };
+        assert_eq!((left, right), (&reg("a"), &reg("a")));
+
+        assert_eq!(Interpreter::run(&module, "main").output, before.output);
+        assert_eq!(before.output, vec![49, 1, -1]);
+    }
+}
@@ -0,0 +1,176 @@
+//! 死代码与不可达基本块消除
+//!
+//! - 从入口块不可达的基本块被删除（可达性复用 `IrVerifier::compute_reachable_blocks`），
+//!   后继块 phi 中来自被删块的入边一并删除
+//! - 结果未被使用且没有副作用的指令被删除，直到不动点
+//! - 只被写入、从未被读取的 alloca 连同对它的 store 一起删除
+
+use super::{register_name, remove_phi_incoming, run_on_functions, use_counts, IrPass};
+use crate::ir::function::IrFunction;
+use crate::ir::module::IrModule;
+use crate::ir::value::{IrInstruction, IrValue};
+use crate::ir::verification::IrVerifier;
+use crate::error::cayResult;
+use std::collections::HashSet;
+
+/// 死代码消除 pass（`dce`）
+pub struct DeadCodeElimination;
+
+impl IrPass for DeadCodeElimination {
+    fn name(&self) -> &'static str {
+        "dce"
+    }
+
+    fn run(&mut self, module: &mut IrModule) -> cayResult<usize> {
+        Ok(run_on_functions(module, |func| {
+            remove_unreachable_blocks(func) + remove_write_only_allocas(func) + remove_dead_instructions(func)
+        }))
+    }
+}
+
+/// 删除从入口不可达的基本块，返回删除的块数
+pub fn remove_unreachable_blocks(func: &mut IrFunction) -> usize {
+    let reachable: HashSet<String> = IrVerifier::compute_reachable_blocks(func)
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let removed: Vec<String> = func.blocks.iter()
+        .filter(|b| !b.is_entry && !reachable.contains(&b.label))
+        .map(|b| b.label.clone())
+        .collect();
+    if removed.is_empty() {
+        return 0;
+    }
+
+    func.blocks.retain(|b| b.is_entry || reachable.contains(&b.label));
+    for block in &mut func.blocks {
+        for label in &removed {
+            remove_phi_incoming(block, label);
+        }
+    }
+    removed.len()
+}
+
+/// 指令是否没有副作用（结果无人使用时可删除）
+fn is_removable(inst: &IrInstruction) -> bool {
+    matches!(inst,
+        IrInstruction::Alloca { .. }
+        | IrInstruction::Load { .. }
+        | IrInstruction::BinaryOp { .. }
+        | IrInstruction::Compare { .. }
+        | IrInstruction::Cast { .. }
+        | IrInstruction::GetElementPtr { .. }
+        | IrInstruction::BitCast { .. }
+        | IrInstruction::Phi { .. }
+        | IrInstruction::Select { .. }
+        | IrInstruction::ExtractValue { .. }
+    )
+}
+
+/// 删除结果未被使用的无副作用指令，返回删除的指令数
+pub fn remove_dead_instructions(func: &mut IrFunction) -> usize {
+    let mut removed = 0;
+    loop {
+        let uses = use_counts(func);
+        let before = removed;
+        for block in &mut func.blocks {
+            block.instructions.retain(|inst| {
+                let dead = is_removable(inst)
+                    && inst.result().and_then(register_name).is_some_and(|name| !uses.contains_key(name));
+                if dead {
+                    removed += 1;
+                }
+                !dead
+            });
+        }
+        if removed == before {
+            return removed;
+        }
+    }
+}
+
+/// 删除只作为 store 目标、从未被读取或逃逸的 alloca，返回删除的 alloca 数
+fn remove_write_only_allocas(func: &mut IrFunction) -> usize {
+    let allocas: HashSet<String> = func.blocks.iter()
+        .flat_map(|b| &b.instructions)
+        .filter_map(|inst| match inst {
+            IrInstruction::Alloca { result: IrValue::Register(name, _), .. } => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+
+    // 除了作为 store 地址以外的任何使用都说明 alloca 仍然有用
+    let mut used = HashSet::new();
+    for block in &func.blocks {
+        for inst in &block.instructions {
+            let inputs = match inst {
+                IrInstruction::Store { value, .. } => vec![value],
+                other => other.inputs(),
+            };
+            used.extend(inputs.into_iter().filter_map(register_name).map(str::to_string));
+        }
+        if let Some(term) = &block.terminator {
+            used.extend(term.inputs().into_iter().filter_map(register_name).map(str::to_string));
+        }
+    }
+
+    let dead: HashSet<&String> = allocas.iter().filter(|a| !used.contains(*a)).collect();
+    if dead.is_empty() {
+        return 0;
+    }
+    for block in &mut func.blocks {
+        block.instructions.retain(|inst| match inst {
+            IrInstruction::Alloca { result, .. } | IrInstruction::Store { ptr: result, .. } => {
+                !register_name(result).is_some_and(|n| dead.contains(&n.to_string()))
+            }
+            _ => true,
+        });
+    }
+    dead.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::*;
+    use crate::ir::test_support::*;
+
+    #[test]
+    fn test_remove_dead_instructions_and_blocks() {
+        let main = function("main", &[], IrType::Void, vec![
+            block("entry", vec![
+                binary("a", IrBinaryOp::Add, int(1), int(2)),
+                binary("b", IrBinaryOp::Mul, reg("a"), int(3)),
+                binary("c", IrBinaryOp::Add, int(4), int(5)),
+                IrInstruction::Alloca { result: reg("slot"), ty: IrType::I32, align: 4 },
+                IrInstruction::Store { value: reg("c"), ptr: reg("slot"), ty: IrType::I32 },
+                call(Some("kept"), "side_effect", vec![]),
+                print(int(7)),
+            ], br("exit")),
+            block("orphan", vec![print(int(1))], br("exit")),
+            block("exit", vec![
+                IrInstruction::Phi { result: reg("p"), ty: IrType::I32, incoming: vec![
+                    (int(0), "entry".to_string()),
+                    (int(1), "orphan".to_string()),
+                ] },
+                print(reg("p")),
+            ], IrTerminator::Return { value: None }),
+        ]);
+        let side_effect = function("side_effect", &[], IrType::I32, vec![
+            block("entry", vec![print(int(99))], ret(int(0))),
+        ]);
+        let mut module = module(vec![side_effect, main]);
+        let before = Interpreter::run(&module, "main");
+
+        // 1 个块 + 1 个 alloca + a、b、c
+        assert_eq!(DeadCodeElimination.run(&mut module).unwrap(), 5);
+        let main = module.find_function("main").unwrap();
+        assert_eq!(main.block_labels(), vec!["entry", "exit"]);
+        assert_eq!(main.blocks[0].instructions.len(), 2, "{}", main.blocks[0]);
+        let IrInstruction::Phi { incoming, .. } = &main.blocks[1].instructions[0] else { panic!() };
+        assert_eq!(incoming.len(), 1);
+
+        assert_eq!(Interpreter::run(&module, "main").output, before.output);
+        assert!(IrVerifier::new().verify(&module).warnings.is_empty());
+    }
+}
@@ -0,0 +1,303 @@
+//! 常量折叠与常量传播
+//!
+//! 操作数全部为常量的运算、比较和类型转换在编译期求值，结果替换所有使用处，
+//! 替换后新产生的常量操作数继续折叠，直到不动点。
+//! 除零、有符号除法溢出和超出位宽的移位在 LLVM 中是未定义行为，保持原样不折叠。
+
+use super::{replace_uses, run_on_functions, IrPass};
+use crate::ir::function::IrFunction;
+use crate::ir::module::IrModule;
+use crate::ir::types::IrType;
+use crate::ir::value::{IrBinaryOp, IrCastKind, IrCmpOp, IrInstruction, IrValue};
+use crate::error::cayResult;
+use std::collections::HashMap;
+
+/// 常量折叠 pass（`fold`）
+pub struct ConstantFold;
+
+impl IrPass for ConstantFold {
+    fn name(&self) -> &'static str {
+        "fold"
+    }
+
+    fn run(&mut self, module: &mut IrModule) -> cayResult<usize> {
+        Ok(run_on_functions(module, fold_function))
+    }
+}
+
+/// 折叠函数中的常量表达式，返回被折叠的指令数
+pub fn fold_function(func: &mut IrFunction) -> usize {
+    let mut changes = 0;
+    loop {
+        let mut replacements: HashMap<String, IrValue> = HashMap::new();
+        for block in &mut func.blocks {
+            block.instructions.retain(|inst| {
+                let folded = fold_instruction(inst);
+                match (inst.result(), folded) {
+                    (Some(IrValue::Register(name, _)), Some(value)) => {
+                        replacements.insert(name.clone(), value);
+                        false
+                    }
+                    _ => true,
+                }
+            });
+        }
+        if replacements.is_empty() {
+            return changes;
+        }
+        changes += replacements.len();
+        replace_uses(func, &replacements);
+    }
+}
+
+/// 对单条指令求值；不是编译期常量时返回 None
+fn fold_instruction(inst: &IrInstruction) -> Option<IrValue> {
+    match inst {
+        IrInstruction::BinaryOp { result, op, left, right } => {
+            let ty = result.ir_type();
+            if let (IrValue::FloatConst(l, _), IrValue::FloatConst(r, _)) = (left, right) {
+                return fold_float_binary(*op, *l, *r, ty);
+            }
+            let value = fold_int_binary(*op, int_value(left)?, int_value(right)?, &ty)?;
+            Some(int_const(value, ty))
+        }
+        IrInstruction::Compare { op, left, right, .. } => {
+            if let (IrValue::FloatConst(l, _), IrValue::FloatConst(r, _)) = (left, right) {
+                return fold_float_compare(*op, *l, *r).map(IrValue::BoolConst);
+            }
+            let bits = int_bits(&left.ir_type());
+            fold_int_compare(*op, int_value(left)?, int_value(right)?, bits).map(IrValue::BoolConst)
+        }
+        IrInstruction::Cast { kind, value, to_ty, .. } => fold_cast(*kind, value, to_ty),
+        IrInstruction::Select { condition, true_val, false_val, .. } => {
+            if true_val == false_val {
+                return Some(true_val.clone());
+            }
+            match int_value(condition)? {
+                0 => Some(false_val.clone()),
+                _ => Some(true_val.clone()),
+            }
+        }
+        IrInstruction::Phi { result, incoming, .. } => {
+            // 所有入边（忽略自身）都是同一个值时，phi 等于该值
+            let mut values = incoming.iter().map(|(v, _)| v).filter(|v| *v != result);
+            let first = values.next()?;
+            values.all(|v| v == first).then(|| first.clone())
+        }
+        _ => None,
+    }
+}
+
+/// 常量的整数值（布尔视为 0/1）
+pub(super) fn int_value(value: &IrValue) -> Option<i64> {
+    match value {
+        IrValue::IntConst(v, _) => Some(*v),
+        IrValue::BoolConst(b) => Some(*b as i64),
+        _ => None,
+    }
+}
+
+/// 整数类型的位宽
+fn int_bits(ty: &IrType) -> u32 {
+    match ty {
+        IrType::I1 => 1,
+        IrType::I8 => 8,
+        IrType::I16 => 16,
+        IrType::I32 => 32,
+        _ => 64,
+    }
+}
+
+/// 按位宽进行符号扩展（截断高位）
+fn sign_wrap(value: i64, bits: u32) -> i64 {
+    if bits >= 64 {
+        value
+    } else {
+        let shift = 64 - bits;
+        (value << shift) >> shift
+    }
+}
+
+/// 按位宽取无符号值
+fn unsigned(value: i64, bits: u32) -> u64 {
+    if bits >= 64 {
+        value as u64
+    } else {
+        (value as u64) & ((1u64 << bits) - 1)
+    }
+}
+
+/// 构造指定类型的整数常量（i1 使用布尔常量）
+fn int_const(value: i64, ty: IrType) -> IrValue {
+    match ty {
+        IrType::I1 => IrValue::BoolConst(value & 1 != 0),
+        ty => IrValue::IntConst(sign_wrap(value, int_bits(&ty)), ty),
+    }
+}
+
+fn fold_int_binary(op: IrBinaryOp, l: i64, r: i64, ty: &IrType) -> Option<i64> {
+    let bits = int_bits(ty);
+    let (l, r) = (sign_wrap(l, bits), sign_wrap(r, bits));
+    let min = sign_wrap(1i64 << (bits - 1).min(63), bits);
+    let value = match op {
+        IrBinaryOp::Add => l.wrapping_add(r),
+        IrBinaryOp::Sub => l.wrapping_sub(r),
+        IrBinaryOp::Mul => l.wrapping_mul(r),
+        IrBinaryOp::Div | IrBinaryOp::Mod if r == 0 || (l == min && r == -1) => return None,
+        IrBinaryOp::Div => l / r,
+        IrBinaryOp::Mod => l % r,
+        IrBinaryOp::And => l & r,
+        IrBinaryOp::Or => l | r,
+        IrBinaryOp::Xor => l ^ r,
+        IrBinaryOp::Shl | IrBinaryOp::Shr | IrBinaryOp::LShr if r < 0 || r >= bits as i64 => return None,
+        IrBinaryOp::Shl => l.wrapping_shl(r as u32),
+        IrBinaryOp::Shr => l >> r,
+        IrBinaryOp::LShr => (unsigned(l, bits) >> r) as i64,
+        _ => return None,
+    };
+    Some(sign_wrap(value, bits))
+}
+
+fn fold_float_binary(op: IrBinaryOp, l: f64, r: f64, ty: IrType) -> Option<IrValue> {
+    let value = match op {
+        IrBinaryOp::FAdd => l + r,
+        IrBinaryOp::FSub => l - r,
+        IrBinaryOp::FMul => l * r,
+        IrBinaryOp::FDiv => l / r,
+        IrBinaryOp::FRem => l % r,
+        _ => return None,
+    };
+    let value = if ty == IrType::F32 { value as f32 as f64 } else { value };
+    Some(IrValue::FloatConst(value, ty))
+}
+
+fn fold_int_compare(op: IrCmpOp, l: i64, r: i64, bits: u32) -> Option<bool> {
+    let (sl, sr) = (sign_wrap(l, bits), sign_wrap(r, bits));
+    let (ul, ur) = (unsigned(l, bits), unsigned(r, bits));
+    Some(match op {
+        IrCmpOp::Eq | IrCmpOp::FEq => sl == sr,
+        IrCmpOp::Ne | IrCmpOp::FNe => sl != sr,
+        IrCmpOp::Slt | IrCmpOp::FLt => sl < sr,
+        IrCmpOp::Sle | IrCmpOp::FLe => sl <= sr,
+        IrCmpOp::Sgt | IrCmpOp::FGt => sl > sr,
+        IrCmpOp::Sge | IrCmpOp::FGe => sl >= sr,
+        IrCmpOp::Ult => ul < ur,
+        IrCmpOp::Ule => ul <= ur,
+        IrCmpOp::Ugt => ul > ur,
+        IrCmpOp::Uge => ul >= ur,
+    })
+}
+
+/// 浮点比较使用有序语义：任一操作数为 NaN 时结果为假（与 LLVM 的 o* 谓词一致）
+fn fold_float_compare(op: IrCmpOp, l: f64, r: f64) -> Option<bool> {
+    if l.is_nan() || r.is_nan() {
+        return Some(false);
+    }
+    Some(match op {
+        IrCmpOp::FEq | IrCmpOp::Eq => l == r,
+        IrCmpOp::FNe | IrCmpOp::Ne => l != r,
+        IrCmpOp::FLt | IrCmpOp::Slt => l < r,
+        IrCmpOp::FLe | IrCmpOp::Sle => l <= r,
+        IrCmpOp::FGt | IrCmpOp::Sgt => l > r,
+        IrCmpOp::FGe | IrCmpOp::Sge => l >= r,
+        _ => return None,
+    })
+}
+
+fn fold_cast(kind: IrCastKind, value: &IrValue, to_ty: &IrType) -> Option<IrValue> {
+    match (kind, value) {
+        (IrCastKind::SignExt | IrCastKind::Trunc, _) if to_ty.is_integer() => {
+            let bits = int_bits(&value.ir_type());
+            Some(int_const(sign_wrap(int_value(value)?, bits), to_ty.clone()))
+        }
+        (IrCastKind::ZeroExt, _) if to_ty.is_integer() => {
+            let bits = int_bits(&value.ir_type());
+            Some(int_const(unsigned(int_value(value)?, bits) as i64, to_ty.clone()))
+        }
+        (IrCastKind::IntToFloat, _) if to_ty.is_float() => {
+            let v = int_value(value)? as f64;
+            let v = if *to_ty == IrType::F32 { v as f32 as f64 } else { v };
+            Some(IrValue::FloatConst(v, to_ty.clone()))
+        }
+        (IrCastKind::FloatToInt, IrValue::FloatConst(v, _)) if to_ty.is_integer() => {
+            // 超出目标范围的 fptosi 结果是 poison，不折叠
+            let bits = int_bits(to_ty);
+            let truncated = v.trunc();
+            let limit = 2f64.powi(bits as i32 - 1);
+            (truncated >= -limit && truncated < limit).then(|| int_const(truncated as i64, to_ty.clone()))
+        }
+        (IrCastKind::FloatExt | IrCastKind::FloatTrunc, IrValue::FloatConst(v, _)) if to_ty.is_float() => {
+            let v = if *to_ty == IrType::F32 { *v as f32 as f64 } else { *v };
+            Some(IrValue::FloatConst(v, to_ty.clone()))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::*;
+    use crate::ir::test_support::*;
+
+    #[test]
+    fn test_fold_and_propagate() {
+        let main = function("main", &[], IrType::Void, vec![block("entry", vec![
+            binary("a", IrBinaryOp::Mul, int(6), int(7)),
+            binary("b", IrBinaryOp::Sub, reg("a"), int(2)),
+            compare("c", IrCmpOp::Sgt, reg("b"), int(10)),
+            IrInstruction::Select { result: reg("d"), condition: flag("c"), true_val: reg("b"), false_val: int(0) },
+            print(reg("d")),
+        ], IrTerminator::Return { value: None })]);
+        let mut module = module(vec![main]);
+        let changes = ConstantFold.run(&mut module).unwrap();
+        assert_eq!(changes, 4);
+
+        let main = module.find_function("main").unwrap();
+        assert_eq!(main.blocks[0].instructions.len(), 1);
+        let IrInstruction::Call { args, .. } = &main.blocks[0].instructions[0] else { panic!() };
+        assert_eq!(args[0], int(40));
+        assert_eq!(Interpreter::run(&module, "main").output, vec![40]);
+    }
+
+    #[test]
+    fn test_fold_respects_bit_width_and_undefined_behavior() {
+        let wrap = fold_int_binary(IrBinaryOp::Add, i32::MAX as i64, 1, &IrType::I32);
+        assert_eq!(wrap, Some(i32::MIN as i64));
+        assert_eq!(fold_int_binary(IrBinaryOp::LShr, -1, 28, &IrType::I32), Some(15));
+        assert_eq!(fold_int_binary(IrBinaryOp::Div, 1, 0, &IrType::I32), None);
+        assert_eq!(fold_int_binary(IrBinaryOp::Div, i32::MIN as i64, -1, &IrType::I32), None);
+        assert_eq!(fold_int_binary(IrBinaryOp::Mod, i64::MIN, -1, &IrType::I64), None);
+        assert_eq!(fold_int_binary(IrBinaryOp::Shl, 1, 32, &IrType::I32), None);
+        assert_eq!(fold_int_binary(IrBinaryOp::Shl, 1, 40, &IrType::I64), Some(1 << 40));
+        assert_eq!(fold_int_compare(IrCmpOp::Ult, -1, 1, 32), Some(false));
+        assert_eq!(fold_int_compare(IrCmpOp::Slt, -1, 1, 32), Some(true));
+
+        let trunc = fold_cast(IrCastKind::Trunc, &IrValue::IntConst(300, IrType::I32), &IrType::I8);
+        assert_eq!(trunc, Some(IrValue::IntConst(44, IrType::I8)));
+        let zext = fold_cast(IrCastKind::ZeroExt, &IrValue::IntConst(-1, IrType::I8), &IrType::I32);
+        assert_eq!(zext, Some(IrValue::IntConst(255, IrType::I32)));
+        let fptosi = fold_cast(IrCastKind::FloatToInt, &IrValue::FloatConst(3e10, IrType::F64), &IrType::I32);
+        assert_eq!(fptosi, None);
+    }
+
+    #[test]
+    fn test_fold_phi_with_identical_incoming() {
+        let main = function("main", &[], IrType::Void, vec![
+            block("entry", vec![compare("c", IrCmpOp::Eq, param("x"), int(0))], cond_br(flag("c"), "a", "b")),
+            block("a", vec![], br("join")),
+            block("b", vec![], br("join")),
+            block("join", vec![
+                IrInstruction::Phi { result: reg("p"), ty: IrType::I32, incoming: vec![
+                    (int(5), "a".to_string()),
+                    (int(5), "b".to_string()),
+                ] },
+                print(reg("p")),
+            ], IrTerminator::Return { value: None }),
+        ]);
+        let mut func = main;
+        assert_eq!(fold_function(&mut func), 1);
+        let IrInstruction::Call { args, .. } = &func.blocks[3].instructions[0] else { panic!() };
+        assert_eq!(args[0], int(5));
+    }
+}
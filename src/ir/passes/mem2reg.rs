@@ -0,0 +1,441 @@
+//! alloca 局部变量提升为 SSA 寄存器（mem2reg）
+//!
+//! IrBuilder 为每个局部变量和参数生成 alloca，通过 load/store 访问。
+//! 只被直接 load/store（类型与 alloca 一致）的 alloca 可以提升：
+//! 1. 计算支配树和支配边界
+//! 2. 在存在 store 的块的迭代支配边界上插入 phi
+//! 3. 沿支配树重命名：load 替换为当前值，store 更新当前值，phi 填入各前驱的值
+//!
+//! 提升前先删除不可达块，未初始化的读取得到 `undef`。
+//! 新 phi 的名称为 `<alloca 寄存器>.<块标签>`。
+
+use super::dce::remove_unreachable_blocks;
+use super::{predecessors, register_name, replace_uses, run_on_functions, IrPass};
+use crate::ir::function::IrFunction;
+use crate::ir::module::IrModule;
+use crate::ir::types::IrType;
+use crate::ir::value::{IrInstruction, IrValue};
+use crate::error::cayResult;
+use std::collections::{HashMap, HashSet};
+
+/// mem2reg pass（`mem2reg`）
+pub struct Mem2Reg;
+
+impl IrPass for Mem2Reg {
+    fn name(&self) -> &'static str {
+        "mem2reg"
+    }
+
+    fn run(&mut self, module: &mut IrModule) -> cayResult<usize> {
+        Ok(run_on_functions(module, promote_allocas))
+    }
+}
+
+/// 可提升的 alloca
+struct Variable {
+    name: String,
+    ty: IrType,
+}
+
+/// phi 的入边列表 (值, 前驱块)
+type PhiIncoming = Vec<(IrValue, String)>;
+
+/// 重命名过程中的状态
+struct Renamer<'a> {
+    variables: &'a [Variable],
+    /// alloca 寄存器名 -> 变量下标
+    index: HashMap<&'a str, usize>,
+    /// 每个变量当前的值栈
+    stacks: Vec<Vec<IrValue>>,
+    /// 块下标 -> 该块插入的 (变量下标, phi 入边)
+    phis: HashMap<usize, Vec<(usize, PhiIncoming)>>,
+    /// 被删除的 load 结果 -> 替换值
+    replacements: HashMap<String, IrValue>,
+}
+
+/// 提升函数中所有可提升的 alloca，返回提升的数量
+pub fn promote_allocas(func: &mut IrFunction) -> usize {
+    remove_unreachable_blocks(func);
+    let variables = promotable_allocas(func);
+    if variables.is_empty() {
+        return 0;
+    }
+
+    let labels: Vec<String> = func.blocks.iter().map(|b| b.label.clone()).collect();
+    let block_index: HashMap<&str, usize> = labels.iter().enumerate().map(|(i, l)| (l.as_str(), i)).collect();
+    let preds: Vec<Vec<usize>> = {
+        let by_label = predecessors(func);
+        labels.iter().map(|l| by_label[l].iter().map(|p| block_index[p.as_str()]).collect()).collect()
+    };
+    let succs: Vec<Vec<usize>> = func.blocks.iter()
+        .map(|b| b.successor_labels().iter().map(|s| block_index[s]).collect())
+        .collect();
+    let idom = dominators(&succs, &preds);
+    let frontiers = dominance_frontiers(&preds, &idom);
+
+    let mut renamer = Renamer {
+        variables: &variables,
+        index: variables.iter().enumerate().map(|(i, v)| (v.name.as_str(), i)).collect(),
+        stacks: vec![Vec::new(); variables.len()],
+        phis: HashMap::new(),
+        replacements: HashMap::new(),
+    };
+
+    // 在迭代支配边界上放置 phi
+    for (var, variable) in variables.iter().enumerate() {
+        let mut work: Vec<usize> = func.blocks.iter().enumerate()
+            .filter(|(_, b)| b.instructions.iter().any(|i| matches!(i,
+                IrInstruction::Store { ptr, .. } if register_name(ptr) == Some(&variable.name))))
+            .map(|(i, _)| i)
+            .collect();
+        let mut has_phi = HashSet::new();
+        while let Some(block) = work.pop() {
+            for &frontier in &frontiers[block] {
+                if has_phi.insert(frontier) {
+                    renamer.phis.entry(frontier).or_default().push((var, Vec::new()));
+                    work.push(frontier);
+                }
+            }
+        }
+    }
+
+    let mut children = vec![Vec::new(); labels.len()];
+    for (block, parent) in idom.iter().enumerate().skip(1) {
+        if let Some(parent) = parent {
+            children[*parent].push(block);
+        }
+    }
+    renamer.rename(func, 0, &children, &succs);
+
+    // 把 phi 插入各块开头
+    for (block, phis) in std::mem::take(&mut renamer.phis) {
+        let label = labels[block].clone();
+        let new_phis = phis.into_iter().map(|(var, incoming)| {
+            let variable = &variables[var];
+            IrInstruction::Phi {
+                result: IrValue::Register(phi_name(&variable.name, &label), variable.ty.clone()),
+                ty: variable.ty.clone(),
+                incoming,
+            }
+        });
+        let instructions = &mut func.blocks[block].instructions;
+        let first_non_phi = instructions.iter().position(|i| !matches!(i, IrInstruction::Phi { .. })).unwrap_or(instructions.len());
+        instructions.splice(first_non_phi..first_non_phi, new_phis);
+    }
+    replace_uses(func, &renamer.replacements);
+    variables.len()
+}
+
+fn phi_name(alloca: &str, label: &str) -> String {
+    format!("{}.{}", alloca, label)
+}
+
+impl Renamer<'_> {
+    fn variable_of(&self, ptr: &IrValue) -> Option<usize> {
+        register_name(ptr).and_then(|n| self.index.get(n).copied())
+    }
+
+    fn current(&self, var: usize) -> IrValue {
+        self.stacks[var].last().cloned().unwrap_or_else(|| IrValue::Undef(self.variables[var].ty.clone()))
+    }
+
+    /// 解析被删除 load 的替换链
+    fn resolve(&self, value: &mut IrValue) {
+        while let Some(new) = register_name(value).and_then(|n| self.replacements.get(n)) {
+            *value = new.clone();
+        }
+    }
+
+    fn rename(&mut self, func: &mut IrFunction, block: usize, children: &[Vec<usize>], succs: &[Vec<usize>]) {
+        let mut pushed = Vec::new();
+        let label = func.blocks[block].label.clone();
+        if let Some(phis) = self.phis.get(&block) {
+            for (var, _) in phis.clone() {
+                let name = phi_name(&self.variables[var].name, &label);
+                self.stacks[var].push(IrValue::Register(name, self.variables[var].ty.clone()));
+                pushed.push(var);
+            }
+        }
+
+        let instructions = std::mem::take(&mut func.blocks[block].instructions);
+        let mut kept = Vec::with_capacity(instructions.len());
+        for mut inst in instructions {
+            if !matches!(inst, IrInstruction::Phi { .. }) {
+                inst.values_mut().into_iter().for_each(|v| self.resolve(v));
+            }
+            match &inst {
+                IrInstruction::Alloca { result, .. } if self.variable_of(result).is_some() => {}
+                IrInstruction::Load { result: IrValue::Register(name, _), ptr, .. } if self.variable_of(ptr).is_some() => {
+                    let value = self.current(self.variable_of(ptr).unwrap());
+                    self.replacements.insert(name.clone(), value);
+                }
+                IrInstruction::Store { value, ptr, .. } if self.variable_of(ptr).is_some() => {
+                    let var = self.variable_of(ptr).unwrap();
+                    self.stacks[var].push(value.clone());
+                    pushed.push(var);
+                }
+                _ => kept.push(inst),
+            }
+        }
+        func.blocks[block].instructions = kept;
+        if let Some(term) = &mut func.blocks[block].terminator {
+            term.values_mut().into_iter().for_each(|v| self.resolve(v));
+        }
+
+        // 为后继块的 phi 填入本块末尾的值
+        let mut seen = HashSet::new();
+        for &succ in succs[block].iter().filter(|s| seen.insert(**s)) {
+            let Some(phis) = self.phis.get(&succ) else { continue };
+            let values: Vec<IrValue> = phis.iter().map(|(var, _)| self.current(*var)).collect();
+            let phis = self.phis.get_mut(&succ).unwrap();
+            for ((_, incoming), value) in phis.iter_mut().zip(values) {
+                incoming.push((value, label.clone()));
+            }
+        }
+
+        for &child in &children[block] {
+            self.rename(func, child, children, succs);
+        }
+        for var in pushed {
+            self.stacks[var].pop();
+        }
+    }
+}
+
+/// 找出只被直接 load/store 使用的 alloca
+fn promotable_allocas(func: &IrFunction) -> Vec<Variable> {
+    let mut candidates: Vec<Variable> = func.blocks.iter()
+        .flat_map(|b| &b.instructions)
+        .filter_map(|inst| match inst {
+            IrInstruction::Alloca { result: IrValue::Register(name, _), ty, .. } => {
+                Some(Variable { name: name.clone(), ty: ty.clone() })
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut escaped: HashSet<String> = HashSet::new();
+    let types: HashMap<&str, &IrType> = candidates.iter().map(|v| (v.name.as_str(), &v.ty)).collect();
+    for block in &func.blocks {
+        for inst in &block.instructions {
+            let other_uses = match inst {
+                IrInstruction::Load { ptr, ty, .. } => {
+                    if let Some(name) = register_name(ptr).filter(|n| types.get(n).is_some_and(|t| *t != ty)) {
+                        escaped.insert(name.to_string());
+                    }
+                    vec![]
+                }
+                IrInstruction::Store { value, ptr, ty } => {
+                    if let Some(name) = register_name(ptr).filter(|n| types.get(n).is_some_and(|t| *t != ty)) {
+                        escaped.insert(name.to_string());
+                    }
+                    vec![value]
+                }
+                other => other.inputs(),
+            };
+            escaped.extend(other_uses.into_iter().filter_map(register_name).map(str::to_string));
+        }
+        if let Some(term) = &block.terminator {
+            escaped.extend(term.inputs().into_iter().filter_map(register_name).map(str::to_string));
+        }
+    }
+    candidates.retain(|v| !escaped.contains(&v.name));
+    candidates
+}
+
+/// 计算每个块的直接支配者（入口块为 None，不可达块也为 None）
+///
+/// 使用 Cooper-Harvey-Kennedy 迭代算法，按逆后序处理。
+fn dominators(succs: &[Vec<usize>], preds: &[Vec<usize>]) -> Vec<Option<usize>> {
+    let n = succs.len();
+    let mut order = Vec::with_capacity(n);
+    let mut visited = vec![false; n];
+    let mut stack = vec![(0usize, 0usize)];
+    visited[0] = true;
+    while let Some((block, next)) = stack.pop() {
+        if let Some(&succ) = succs[block].get(next) {
+            stack.push((block, next + 1));
+            if !visited[succ] {
+                visited[succ] = true;
+                stack.push((succ, 0));
+            }
+        } else {
+            order.push(block);
+        }
+    }
+    order.reverse();
+    let mut rpo_index = vec![usize::MAX; n];
+    for (i, &block) in order.iter().enumerate() {
+        rpo_index[block] = i;
+    }
+
+    let mut idom: Vec<Option<usize>> = vec![None; n];
+    idom[0] = Some(0);
+    let intersect = |idom: &[Option<usize>], mut a: usize, mut b: usize| {
+        while a != b {
+            while rpo_index[a] > rpo_index[b] {
+                a = idom[a].unwrap();
+            }
+            while rpo_index[b] > rpo_index[a] {
+                b = idom[b].unwrap();
+            }
+        }
+        a
+    };
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &block in order.iter().skip(1) {
+            let mut new_idom = None;
+            for &pred in &preds[block] {
+                if idom[pred].is_none() {
+                    continue;
+                }
+                new_idom = Some(match new_idom {
+                    None => pred,
+                    Some(current) => intersect(&idom, pred, current),
+                });
+            }
+            if new_idom.is_some() && idom[block] != new_idom {
+                idom[block] = new_idom;
+                changed = true;
+            }
+        }
+    }
+    idom[0] = None;
+    idom
+}
+
+/// 计算支配边界
+fn dominance_frontiers(preds: &[Vec<usize>], idom: &[Option<usize>]) -> Vec<HashSet<usize>> {
+    let mut frontiers = vec![HashSet::new(); preds.len()];
+    for (block, block_preds) in preds.iter().enumerate() {
+        if block_preds.len() < 2 {
+            continue;
+        }
+        for &pred in block_preds {
+            let mut runner = Some(pred);
+            while let Some(r) = runner {
+                if Some(r) == idom[block] {
+                    break;
+                }
+                frontiers[r].insert(block);
+                // 入口块没有直接支配者
+                runner = idom[r];
+            }
+        }
+    }
+    frontiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::*;
+    use crate::ir::test_support::*;
+
+    fn alloca(name: &str) -> IrInstruction {
+        IrInstruction::Alloca { result: reg(name), ty: IrType::I32, align: 4 }
+    }
+
+    fn store(value: IrValue, ptr: &str) -> IrInstruction {
+        IrInstruction::Store { value, ptr: reg(ptr), ty: IrType::I32 }
+    }
+
+    fn load(result: &str, ptr: &str) -> IrInstruction {
+        IrInstruction::Load { result: reg(result), ptr: reg(ptr), ty: IrType::I32 }
+    }
+
+    /// fib(n)：循环中更新两个局部变量
+    fn fib() -> IrFunction {
+        function("fib", &["n"], IrType::I32, vec![
+            block("entry", vec![
+                alloca("a"), alloca("b"), alloca("i"),
+                store(int(0), "a"), store(int(1), "b"), store(int(0), "i"),
+            ], br("cond")),
+            block("cond", vec![
+                load("t0", "i"),
+                compare("t1", IrCmpOp::Slt, reg("t0"), param("n")),
+            ], cond_br(flag("t1"), "body", "exit")),
+            block("body", vec![
+                load("t2", "a"), load("t3", "b"),
+                binary("t4", IrBinaryOp::Add, reg("t2"), reg("t3")),
+                store(reg("t3"), "a"), store(reg("t4"), "b"),
+                load("t5", "i"),
+                binary("t6", IrBinaryOp::Add, reg("t5"), int(1)),
+                store(reg("t6"), "i"),
+            ], br("cond")),
+            block("exit", vec![load("t7", "a")], ret(reg("t7"))),
+        ])
+    }
+
+    #[test]
+    fn test_promote_loop_variables() {
+        let main = function("main", &[], IrType::Void, vec![block("entry", vec![
+            call(Some("r"), "fib", vec![int(10)]),
+            print(reg("r")),
+        ], IrTerminator::Return { value: None })]);
+        let mut module = module(vec![fib(), main]);
+        let before = Interpreter::run(&module, "main");
+        assert_eq!(before.output, vec![55]);
+
+        assert_eq!(Mem2Reg.run(&mut module).unwrap(), 3);
+        let fib = module.find_function("fib").unwrap();
+        let memory_ops = fib.blocks.iter().flat_map(|b| &b.instructions)
+            .filter(|i| matches!(i, IrInstruction::Alloca { .. } | IrInstruction::Load { .. } | IrInstruction::Store { .. }))
+            .count();
+        assert_eq!(memory_ops, 0);
+
+        let cond = fib.find_block("cond").unwrap();
+        let phis: Vec<&IrValue> = cond.instructions.iter()
+            .filter_map(|i| match i { IrInstruction::Phi { result, .. } => Some(result), _ => None })
+            .collect();
+        assert_eq!(phis, vec![&reg("a.cond"), &reg("b.cond"), &reg("i.cond")]);
+        let result = IrVerifier::new().verify(&module);
+        assert!(result.is_valid, "{:?}", result.errors);
+        assert_eq!(Interpreter::run(&module, "main").output, before.output);
+    }
+
+    #[test]
+    fn test_diamond_and_escaping_alloca() {
+        // x 在两个分支中赋值；y 的地址传给了函数，不能提升
+        let main = function("main", &[], IrType::Void, vec![
+            block("entry", vec![
+                alloca("x"), alloca("y"),
+                store(int(1), "y"),
+                call(None, "observe", vec![reg("y")]),
+                compare("c", IrCmpOp::Eq, int(1), int(2)),
+            ], cond_br(flag("c"), "then", "else")),
+            block("then", vec![store(int(10), "x")], br("join")),
+            block("else", vec![store(int(20), "x")], br("join")),
+            block("join", vec![load("v", "x"), print(reg("v")), load("w", "y"), print(reg("w"))],
+                IrTerminator::Return { value: None }),
+        ]);
+        let observe = function("observe", &["p"], IrType::Void, vec![
+            block("entry", vec![], IrTerminator::Return { value: None }),
+        ]);
+        let mut module = module(vec![observe, main]);
+        let before = Interpreter::run(&module, "main");
+
+        assert_eq!(Mem2Reg.run(&mut module).unwrap(), 1);
+        let main = module.find_function("main").unwrap();
+        let IrInstruction::Phi { incoming, .. } = &main.find_block("join").unwrap().instructions[0] else { panic!() };
+        assert_eq!(incoming, &vec![(int(10), "then".to_string()), (int(20), "else".to_string())]);
+        assert!(main.blocks[0].instructions.iter().any(|i| matches!(i, IrInstruction::Alloca { result, .. } if *result == reg("y"))));
+        assert_eq!(Interpreter::run(&module, "main").output, before.output);
+        assert_eq!(before.output, vec![20, 1]);
+    }
+
+    #[test]
+    fn test_dominators() {
+        // 0 -> 1 -> {2, 3} -> 4 -> 1
+        let succs = vec![vec![1], vec![2, 3], vec![4], vec![4], vec![1]];
+        let preds = vec![vec![], vec![0, 4], vec![1], vec![1], vec![2, 3]];
+        let idom = dominators(&succs, &preds);
+        assert_eq!(idom, vec![None, Some(0), Some(1), Some(1), Some(1)]);
+        let frontiers = dominance_frontiers(&preds, &idom);
+        assert_eq!(frontiers[2], HashSet::from([4]));
+        assert_eq!(frontiers[4], HashSet::from([1]));
+        assert_eq!(frontiers[1], HashSet::from([1]));
+    }
+}
@@ -0,0 +1,450 @@
+//! IR 优化 Pass 管理器
+//!
+//! 在 IrModule 上按顺序运行标量优化 pass，支持按名称选择（`cay-ir --passes=fold,dce,mem2reg`）、
+//! 每个 pass 之后打印 IR（`--print-after`）以及统计每个 pass 的改动。
+//!
+//! 可用的 pass：
+//! - `fold`: 常量折叠与常量传播
+//! - `dce`: 死代码与不可达基本块消除
+//! - `simplifycfg`: 控制流图简化（常量分支、块合并、空跳转块消除）
+//! - `cse`: 基本块内的公共子表达式消除
+//! - `mem2reg`: 将 alloca 局部变量提升为 SSA 寄存器
+//! - `inline`: 函数内联（见 [`Inliner`]）
+
+pub mod fold;
+pub mod dce;
+pub mod simplify_cfg;
+pub mod cse;
+pub mod mem2reg;
+
+use super::module::IrModule;
+use super::function::{IrFunction, IrLinkage};
+use super::block::IrBasicBlock;
+use super::value::{IrInstruction, IrValue};
+use super::inliner::Inliner;
+use super::llvm_backend::LlvmBackend;
+use super::verification::IrVerifier;
+use crate::error::{cayResult, codegen_error};
+use std::collections::HashMap;
+use std::fmt;
+
+pub use fold::ConstantFold;
+pub use dce::DeadCodeElimination;
+pub use simplify_cfg::SimplifyCfg;
+pub use cse::LocalCse;
+pub use mem2reg::Mem2Reg;
+
+/// 所有可用 pass 的名称和说明
+pub const AVAILABLE_PASSES: [(&str, &str); 6] = [
+    ("fold", "常量折叠与常量传播"),
+    ("dce", "死代码与不可达基本块消除"),
+    ("simplifycfg", "控制流图简化"),
+    ("cse", "基本块内公共子表达式消除"),
+    ("mem2reg", "alloca 局部变量提升为 SSA 寄存器"),
+    ("inline", "函数内联"),
+];
+
+/// 编译时默认运行的 pass 流水线（cayc/cay-ir 未指定 `--passes` 时）
+pub const DEFAULT_PIPELINE: &str = "mem2reg,fold,simplifycfg,dce,cse";
+
+/// 生成调试信息（`-g`）时的 pass 流水线：不运行 mem2reg，局部变量保留在 alloca 中供调试器查看
+pub const DEBUG_PIPELINE: &str = "fold,simplifycfg,dce,cse";
+
+/// IR 优化 pass
+pub trait IrPass {
+    /// pass 名称（用于 `--passes` 选择和统计输出）
+    fn name(&self) -> &'static str;
+
+    /// 对模块执行变换，返回改动次数
+    fn run(&mut self, module: &mut IrModule) -> cayResult<usize>;
+}
+
+/// 按名称创建 pass
+pub fn create_pass(name: &str) -> Option<Box<dyn IrPass>> {
+    let pass: Box<dyn IrPass> = match name {
+        "fold" => Box::new(ConstantFold),
+        "dce" => Box::new(DeadCodeElimination),
+        "simplifycfg" => Box::new(SimplifyCfg),
+        "cse" => Box::new(LocalCse),
+        "mem2reg" => Box::new(Mem2Reg),
+        "inline" => Box::new(Inliner::new()),
+        _ => return None,
+    };
+    Some(pass)
+}
+
+/// 单个 pass 的运行统计
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassStats {
+    pub name: String,
+    /// pass 报告的改动次数
+    pub changes: usize,
+    pub instructions_before: usize,
+    pub instructions_after: usize,
+    pub blocks_before: usize,
+    pub blocks_after: usize,
+}
+
+impl fmt::Display for PassStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} changes, instructions {} -> {}, blocks {} -> {}",
+            self.name, self.changes,
+            self.instructions_before, self.instructions_after,
+            self.blocks_before, self.blocks_after
+        )
+    }
+}
+
+/// 需要打印 IR 的 pass
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PrintAfter {
+    Never,
+    All,
+    Only(Vec<String>),
+}
+
+/// Pass 管理器
+pub struct PassManager {
+    passes: Vec<Box<dyn IrPass>>,
+    print_after: PrintAfter,
+    stats: Vec<PassStats>,
+    /// (pass 名称, 该 pass 之后的 LLVM IR 文本)
+    dumps: Vec<(String, String)>,
+}
+
+impl PassManager {
+    /// 创建空的 pass 管理器
+    pub fn new() -> Self {
+        Self {
+            passes: Vec::new(),
+            print_after: PrintAfter::Never,
+            stats: Vec::new(),
+            dumps: Vec::new(),
+        }
+    }
+
+    /// 默认流水线（见 [`DEFAULT_PIPELINE`]）
+    pub fn default_pipeline() -> Self {
+        Self::from_names(DEFAULT_PIPELINE).expect("default pipeline only names known passes")
+    }
+
+    /// 从逗号分隔的 pass 列表创建（如 `fold,dce,mem2reg`）
+    pub fn from_names(spec: &str) -> cayResult<Self> {
+        let mut manager = Self::new();
+        for name in spec.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let pass = create_pass(name).ok_or_else(|| unknown_pass_error(name))?;
+            manager.add_pass(pass);
+        }
+        Ok(manager)
+    }
+
+    /// 追加一个 pass
+    pub fn add_pass(&mut self, pass: Box<dyn IrPass>) {
+        self.passes.push(pass);
+    }
+
+    /// 每个 pass 之后都记录 IR
+    pub fn print_after_all(&mut self) {
+        self.print_after = PrintAfter::All;
+    }
+
+    /// 只在指定的 pass 之后记录 IR
+    pub fn print_after(&mut self, names: &[String]) -> cayResult<()> {
+        if let Some(name) = names.iter().find(|n| create_pass(n).is_none()) {
+            return Err(unknown_pass_error(name));
+        }
+        self.print_after = PrintAfter::Only(names.to_vec());
+        Ok(())
+    }
+
+    /// 按顺序运行所有 pass
+    ///
+    /// 输入模块通过验证时，每个 pass 之后都重新验证，pass 破坏 IR 时报错。
+    pub fn run(&mut self, module: &mut IrModule) -> cayResult<()> {
+        let valid = IrVerifier::new().verify(module).is_valid;
+
+        for pass in &mut self.passes {
+            let before = module.stats();
+            let changes = pass.run(module)?;
+            let after = module.stats();
+
+            self.stats.push(PassStats {
+                name: pass.name().to_string(),
+                changes,
+                instructions_before: before.instruction_count,
+                instructions_after: after.instruction_count,
+                blocks_before: before.block_count,
+                blocks_after: after.block_count,
+            });
+
+            let print = match &self.print_after {
+                PrintAfter::Never => false,
+                PrintAfter::All => true,
+                PrintAfter::Only(names) => names.iter().any(|n| n == pass.name()),
+            };
+            if print {
+                self.dumps.push((pass.name().to_string(), LlvmBackend::emit_module(module)?));
+            }
+
+            if valid {
+                let result = IrVerifier::new().verify(module);
+                if !result.is_valid {
+                    return Err(codegen_error(format!(
+                        "IR verification failed after pass '{}': {}",
+                        pass.name(), result.errors.join("; ")
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 每个 pass 的运行统计（按运行顺序）
+    pub fn stats(&self) -> &[PassStats] {
+        &self.stats
+    }
+
+    /// `--print-after` 记录的 IR 文本
+    pub fn dumps(&self) -> &[(String, String)] {
+        &self.dumps
+    }
+}
+
+impl Default for PassManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IrPass for Inliner {
+    fn name(&self) -> &'static str {
+        "inline"
+    }
+
+    fn run(&mut self, module: &mut IrModule) -> cayResult<usize> {
+        let before = self.stats().calls_eliminated;
+        let placeholder = IrModule::new(module.name.clone(), module.target_triple.clone());
+        let taken = std::mem::replace(module, placeholder);
+        *module = Inliner::run(self, taken)?;
+        Ok(self.stats().calls_eliminated - before)
+    }
+}
+
+fn unknown_pass_error(name: &str) -> crate::error::cayError {
+    let names: Vec<&str> = AVAILABLE_PASSES.iter().map(|(n, _)| *n).collect();
+    codegen_error(format!("Unknown IR pass '{}' (available: {})", name, names.join(", ")))
+}
+
+// ============================================================
+// pass 共用的辅助函数
+// ============================================================
+
+/// 对模块中每个有函数体的函数运行变换，返回改动总数
+fn run_on_functions(module: &mut IrModule, mut transform: impl FnMut(&mut IrFunction) -> usize) -> usize {
+    module.functions.iter_mut()
+        .filter(|f| f.linkage != IrLinkage::Declare && !f.blocks.is_empty())
+        .map(&mut transform)
+        .sum()
+}
+
+/// 寄存器名称（非寄存器返回 None）
+fn register_name(value: &IrValue) -> Option<&str> {
+    match value {
+        IrValue::Register(name, _) => Some(name),
+        _ => None,
+    }
+}
+
+/// 将函数中寄存器的使用替换为新值；替换链（a -> b -> 常量）会被完全解析
+fn replace_uses(func: &mut IrFunction, replacements: &HashMap<String, IrValue>) {
+    if replacements.is_empty() {
+        return;
+    }
+    let resolve = |value: &mut IrValue| {
+        // 替换链长度不会超过替换表大小
+        for _ in 0..=replacements.len() {
+            let Some(new) = register_name(value).and_then(|n| replacements.get(n)) else { break };
+            *value = new.clone();
+        }
+    };
+    for block in &mut func.blocks {
+        for inst in &mut block.instructions {
+            inst.values_mut().into_iter().for_each(resolve);
+        }
+        if let Some(term) = &mut block.terminator {
+            term.values_mut().into_iter().for_each(resolve);
+        }
+    }
+}
+
+/// 函数中每个寄存器作为输入被使用的次数
+fn use_counts(func: &IrFunction) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for block in &func.blocks {
+        let inputs = block.instructions.iter()
+            .flat_map(|inst| inst.inputs())
+            .chain(block.terminator.iter().flat_map(|t| t.inputs()));
+        for name in inputs.filter_map(register_name) {
+            *counts.entry(name.to_string()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// 每个基本块的前驱标签（按块顺序，同一前驱只记录一次）
+fn predecessors(func: &IrFunction) -> HashMap<String, Vec<String>> {
+    let mut preds: HashMap<String, Vec<String>> = func.blocks.iter()
+        .map(|b| (b.label.clone(), Vec::new()))
+        .collect();
+    for block in &func.blocks {
+        for succ in block.successor_labels() {
+            let list = preds.entry(succ.to_string()).or_default();
+            if !list.contains(&block.label) {
+                list.push(block.label.clone());
+            }
+        }
+    }
+    preds
+}
+
+/// 删除块中 phi 来自 `from` 的入边
+fn remove_phi_incoming(block: &mut IrBasicBlock, from: &str) {
+    for inst in &mut block.instructions {
+        if let IrInstruction::Phi { incoming, .. } = inst {
+            incoming.retain(|(_, label)| label != from);
+        }
+    }
+}
+
+/// 将块中 phi 来自 `from` 的入边改为来自 `to`
+fn rename_phi_incoming(block: &mut IrBasicBlock, from: &str, to: &str) {
+    for inst in &mut block.instructions {
+        if let IrInstruction::Phi { incoming, .. } = inst {
+            for (_, label) in incoming.iter_mut().filter(|(_, l)| l == from) {
+                *label = to.to_string();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::*;
+    use crate::ir::test_support::*;
+
+    /// sum(n) = 0 + 1 + ... + (n-1)，局部变量通过 alloca 访问（IrBuilder 的形式）
+    fn sum_module() -> IrModule {
+        let sum = function("sum", &["n"], IrType::I32, vec![
+            block("entry", vec![
+                IrInstruction::Alloca { result: reg("s"), ty: IrType::I32, align: 4 },
+                IrInstruction::Alloca { result: reg("i"), ty: IrType::I32, align: 4 },
+                IrInstruction::Store { value: int(0), ptr: reg("s"), ty: IrType::I32 },
+                IrInstruction::Store { value: int(0), ptr: reg("i"), ty: IrType::I32 },
+            ], br("cond")),
+            block("cond", vec![
+                IrInstruction::Load { result: reg("t0"), ptr: reg("i"), ty: IrType::I32 },
+                compare("t1", IrCmpOp::Slt, reg("t0"), param("n")),
+            ], cond_br(flag("t1"), "body", "exit")),
+            block("body", vec![
+                IrInstruction::Load { result: reg("t2"), ptr: reg("s"), ty: IrType::I32 },
+                IrInstruction::Load { result: reg("t3"), ptr: reg("i"), ty: IrType::I32 },
+                binary("t4", IrBinaryOp::Add, reg("t2"), reg("t3")),
+                IrInstruction::Store { value: reg("t4"), ptr: reg("s"), ty: IrType::I32 },
+                binary("t5", IrBinaryOp::Add, reg("t3"), int(1)),
+                IrInstruction::Store { value: reg("t5"), ptr: reg("i"), ty: IrType::I32 },
+            ], br("cond")),
+            block("exit", vec![
+                IrInstruction::Load { result: reg("t6"), ptr: reg("s"), ty: IrType::I32 },
+            ], ret(reg("t6"))),
+        ]);
+        let main = function("main", &[], IrType::Void, vec![block("entry", vec![
+            binary("a", IrBinaryOp::Mul, int(2), int(5)),
+            call(Some("b"), "sum", vec![reg("a")]),
+            print(reg("b")),
+        ], IrTerminator::Return { value: None })]);
+        module(vec![sum, main])
+    }
+
+    #[test]
+    fn test_pass_manager_runs_pipeline() {
+        let mut module = sum_module();
+        let before = Interpreter::run(&module, "main");
+
+        let mut manager = PassManager::from_names("mem2reg, fold,dce").unwrap();
+        manager.run(&mut module).unwrap();
+        assert_eq!(Interpreter::run(&module, "main").output, before.output);
+        assert_eq!(before.output, vec![45]);
+
+        let names: Vec<&str> = manager.stats().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["mem2reg", "fold", "dce"]);
+        assert_eq!(manager.stats()[0].changes, 2);
+        let stats = &manager.stats()[1];
+        assert_eq!(stats.changes, 1);
+        assert_eq!(stats.instructions_after, stats.instructions_before - 1);
+        assert!(manager.stats()[0].to_string().starts_with("mem2reg: 2 changes, instructions "));
+        assert!(manager.dumps().is_empty());
+    }
+
+    #[test]
+    fn test_print_after() {
+        let mut module = sum_module();
+        let mut manager = PassManager::from_names("fold,dce").unwrap();
+        manager.print_after(&["fold".to_string()]).unwrap();
+        manager.run(&mut module).unwrap();
+        assert_eq!(manager.dumps().len(), 1);
+        let (name, ir) = &manager.dumps()[0];
+        assert_eq!(name, "fold");
+        assert!(ir.contains("define void @main()"));
+        assert!(!ir.contains(" = mul "), "{}", ir);
+
+        let mut manager = PassManager::from_names("fold,dce").unwrap();
+        manager.print_after_all();
+        manager.run(&mut sum_module()).unwrap();
+        assert_eq!(manager.dumps().len(), 2);
+    }
+
+    #[test]
+    fn test_unknown_pass_names() {
+        let error = PassManager::from_names("fold,licm").err().unwrap().to_string();
+        assert!(error.contains("Unknown IR pass 'licm'"), "{}", error);
+        assert!(error.contains("fold, dce, simplifycfg, cse, mem2reg, inline"), "{}", error);
+
+        let mut manager = PassManager::new();
+        assert!(manager.print_after(&["gvn".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_broken_pass_is_reported() {
+        /// 删除所有终止指令的错误 pass
+        struct Breaker;
+        impl IrPass for Breaker {
+            fn name(&self) -> &'static str {
+                "breaker"
+            }
+            fn run(&mut self, module: &mut IrModule) -> cayResult<usize> {
+                module.functions.iter_mut().for_each(|f| f.blocks[0].terminator = None);
+                Ok(1)
+            }
+        }
+
+        let mut manager = PassManager::new();
+        manager.add_pass(Box::new(Breaker));
+        let error = manager.run(&mut sum_module()).err().unwrap().to_string();
+        assert!(error.contains("IR verification failed after pass 'breaker'"), "{}", error);
+    }
+
+    #[test]
+    fn test_inline_pass() {
+        let mut module = sum_module();
+        let before = Interpreter::run(&module, "main");
+        let mut manager = PassManager::from_names("inline,mem2reg,fold,simplifycfg,cse,dce").unwrap();
+        manager.run(&mut module).unwrap();
+        let after = Interpreter::run(&module, "main");
+        assert_eq!(before.output, after.output);
+        assert_eq!(after.calls, 1);
+        assert_eq!(manager.stats()[0].changes, 1);
+    }
+}
@@ -0,0 +1,250 @@
+//! 控制流图简化
+//!
+//! 重复以下变换直到不动点：
+//! - 条件为常量（或两个目标相同）的条件跳转、值为常量的 switch 改为无条件跳转
+//! - 删除不可达基本块
+//! - 只有唯一前驱、且前驱无条件跳转过来的块合并进前驱
+//! - 空的转发块（只有一条 `br`）被绕过
+
+use super::dce::remove_unreachable_blocks;
+use super::fold::int_value;
+use super::{predecessors, remove_phi_incoming, rename_phi_incoming, replace_uses, run_on_functions, IrPass};
+use crate::ir::function::IrFunction;
+use crate::ir::module::IrModule;
+use crate::ir::value::{IrInstruction, IrTerminator, IrValue};
+use crate::error::cayResult;
+use std::collections::HashMap;
+
+/// CFG 简化 pass（`simplifycfg`）
+pub struct SimplifyCfg;
+
+impl IrPass for SimplifyCfg {
+    fn name(&self) -> &'static str {
+        "simplifycfg"
+    }
+
+    fn run(&mut self, module: &mut IrModule) -> cayResult<usize> {
+        Ok(run_on_functions(module, simplify_function))
+    }
+}
+
+/// 简化函数的控制流图，返回变换次数
+pub fn simplify_function(func: &mut IrFunction) -> usize {
+    let mut changes = 0;
+    loop {
+        let step = fold_constant_terminators(func)
+            + remove_unreachable_blocks(func)
+            + merge_into_predecessor(func)
+            + bypass_forwarding_block(func);
+        if step == 0 {
+            return changes;
+        }
+        changes += step;
+    }
+}
+
+/// 常量条件跳转和常量 switch 改为无条件跳转
+fn fold_constant_terminators(func: &mut IrFunction) -> usize {
+    let mut changes = 0;
+    for i in 0..func.blocks.len() {
+        let target = match &func.blocks[i].terminator {
+            Some(IrTerminator::ConditionalBranch { condition, true_target, false_target }) => {
+                if true_target == false_target {
+                    Some(true_target.clone())
+                } else {
+                    int_value(condition).map(|c| if c != 0 { true_target.clone() } else { false_target.clone() })
+                }
+            }
+            Some(IrTerminator::Switch { value, default_target, cases, .. }) => int_value(value).map(|v| {
+                cases.iter()
+                    .find(|(case, _)| int_value(case) == Some(v))
+                    .map_or_else(|| default_target.clone(), |(_, t)| t.clone())
+            }),
+            _ => None,
+        };
+        let Some(target) = target else { continue };
+
+        let label = func.blocks[i].label.clone();
+        let old_successors: Vec<String> = func.blocks[i].successor_labels().iter().map(|s| s.to_string()).collect();
+        func.blocks[i].set_terminator(IrTerminator::Branch { target: target.clone() });
+        for successor in old_successors.iter().filter(|s| **s != target) {
+            if let Some(block) = func.find_block_mut(successor) {
+                remove_phi_incoming(block, &label);
+            }
+        }
+        changes += 1;
+    }
+    changes
+}
+
+/// 合并一个只有唯一前驱的块，返回合并的块数（0 或 1）
+fn merge_into_predecessor(func: &mut IrFunction) -> usize {
+    let preds = predecessors(func);
+    let candidate = func.blocks.iter().enumerate().skip(1).find_map(|(idx, block)| {
+        let [pred] = preds.get(&block.label)?.as_slice() else { return None };
+        let pred_idx = func.blocks.iter().position(|b| &b.label == pred)?;
+        let jumps_here = matches!(&func.blocks[pred_idx].terminator,
+            Some(IrTerminator::Branch { target }) if *target == block.label);
+        (pred_idx != idx && jumps_here).then_some((pred_idx, idx))
+    });
+    let Some((pred_idx, idx)) = candidate else { return 0 };
+
+    let block = func.blocks.remove(idx);
+    let pred_idx = if pred_idx > idx { pred_idx - 1 } else { pred_idx };
+    let pred_label = func.blocks[pred_idx].label.clone();
+
+    // 唯一前驱时 phi 只有一个入边，直接用入边的值替换
+    let mut replacements: HashMap<String, IrValue> = HashMap::new();
+    for inst in block.instructions {
+        match inst {
+            IrInstruction::Phi { result: IrValue::Register(name, ty), incoming, .. } => {
+                let value = incoming.into_iter().next().map_or(IrValue::Undef(ty), |(v, _)| v);
+                replacements.insert(name, value);
+            }
+            inst => func.blocks[pred_idx].push(inst),
+        }
+    }
+    func.blocks[pred_idx].terminator = block.terminator;
+
+    let successors: Vec<String> = func.blocks[pred_idx].successor_labels().iter().map(|s| s.to_string()).collect();
+    for successor in successors {
+        if let Some(succ) = func.find_block_mut(&successor) {
+            rename_phi_incoming(succ, &block.label, &pred_label);
+        }
+    }
+    replace_uses(func, &replacements);
+    1
+}
+
+/// 绕过一个空的转发块，返回变换次数（0 或 1）
+///
+/// 目标块有 phi 时只处理唯一前驱、且该前驱原本不直接跳到目标块的情况，
+/// 否则 phi 无法区分来自不同前驱的值。
+fn bypass_forwarding_block(func: &mut IrFunction) -> usize {
+    let preds = predecessors(func);
+    for block in func.blocks.iter().skip(1) {
+        let Some(IrTerminator::Branch { target }) = &block.terminator else { continue };
+        if !block.instructions.is_empty() || *target == block.label {
+            continue;
+        }
+        let Some(block_preds) = preds.get(&block.label).filter(|p| !p.is_empty()) else { continue };
+        if block_preds.contains(&block.label) {
+            continue;
+        }
+        let target_has_phi = func.find_block(target)
+            .is_some_and(|t| t.instructions.iter().any(|i| matches!(i, IrInstruction::Phi { .. })));
+        if target_has_phi {
+            let [pred] = block_preds.as_slice() else { continue };
+            if preds.get(target).is_some_and(|p| p.contains(pred)) {
+                continue;
+            }
+        }
+
+        let (label, target, block_preds) = (block.label.clone(), target.clone(), block_preds.clone());
+        for pred in &block_preds {
+            let Some(term) = func.find_block_mut(pred).and_then(|b| b.terminator.as_mut()) else { continue };
+            for t in term.targets_mut().into_iter().filter(|t| **t == label) {
+                *t = target.clone();
+            }
+        }
+        if let (true, Some(target_block)) = (target_has_phi, func.find_block_mut(&target)) {
+            rename_phi_incoming(target_block, &label, &block_preds[0]);
+        }
+        return 1;
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::*;
+    use crate::ir::test_support::*;
+
+    #[test]
+    fn test_constant_branch_and_block_merging() {
+        // entry 的条件恒为真：else 分支被删除，then/join 合并进 entry
+        let main = function("main", &[], IrType::Void, vec![
+            block("entry", vec![print(int(1))], cond_br(IrValue::BoolConst(true), "then", "else")),
+            block("then", vec![print(int(2))], br("join")),
+            block("else", vec![print(int(3))], br("join")),
+            block("join", vec![
+                IrInstruction::Phi { result: reg("p"), ty: IrType::I32, incoming: vec![
+                    (int(10), "then".to_string()),
+                    (int(20), "else".to_string()),
+                ] },
+                print(reg("p")),
+            ], IrTerminator::Return { value: None }),
+        ]);
+        let mut module = module(vec![main]);
+        let before = Interpreter::run(&module, "main");
+        assert!(SimplifyCfg.run(&mut module).unwrap() > 0);
+
+        let main = module.find_function("main").unwrap();
+        assert_eq!(main.block_labels(), vec!["entry"]);
+        let IrInstruction::Call { args, .. } = &main.blocks[0].instructions[2] else { panic!() };
+        assert_eq!(args[0], int(10));
+        assert_eq!(Interpreter::run(&module, "main").output, before.output);
+        assert_eq!(before.output, vec![1, 2, 10]);
+    }
+
+    #[test]
+    fn test_bypass_forwarding_blocks() {
+        let main = function("main", &["x"], IrType::Void, vec![
+            block("entry", vec![compare("c", IrCmpOp::Sgt, param("x"), int(0))], cond_br(flag("c"), "fwd", "other")),
+            block("fwd", vec![], br("join")),
+            block("other", vec![print(int(0))], br("join")),
+            block("join", vec![
+                IrInstruction::Phi { result: reg("p"), ty: IrType::I32, incoming: vec![
+                    (int(1), "fwd".to_string()),
+                    (int(2), "other".to_string()),
+                ] },
+                print(reg("p")),
+            ], IrTerminator::Return { value: None }),
+        ]);
+        let mut func = main;
+        assert!(simplify_function(&mut func) > 0);
+        assert_eq!(func.block_labels(), vec!["entry", "other", "join"]);
+        let IrInstruction::Phi { incoming, .. } = &func.blocks[2].instructions[0] else { panic!() };
+        assert_eq!(incoming[0], (int(1), "entry".to_string()));
+        assert!(func.verify().is_ok());
+    }
+
+    #[test]
+    fn test_forwarding_block_kept_when_phi_needs_it() {
+        // entry 同时直接和经 fwd 跳到 join，fwd 不能绕过
+        let main = function("main", &["x"], IrType::Void, vec![
+            block("entry", vec![compare("c", IrCmpOp::Sgt, param("x"), int(0))], cond_br(flag("c"), "fwd", "join")),
+            block("fwd", vec![], br("join")),
+            block("join", vec![
+                IrInstruction::Phi { result: reg("p"), ty: IrType::I32, incoming: vec![
+                    (int(1), "fwd".to_string()),
+                    (int(2), "entry".to_string()),
+                ] },
+                print(reg("p")),
+            ], IrTerminator::Return { value: None }),
+        ]);
+        let mut func = main;
+        assert_eq!(simplify_function(&mut func), 0);
+        assert_eq!(func.blocks.len(), 3);
+    }
+
+    #[test]
+    fn test_constant_switch() {
+        let main = function("main", &[], IrType::Void, vec![
+            block("entry", vec![], IrTerminator::Switch {
+                value: int(2),
+                default_target: "default".to_string(),
+                cases: vec![(int(1), "one".to_string()), (int(2), "two".to_string())],
+                ty: IrType::I32,
+            }),
+            block("one", vec![print(int(1))], IrTerminator::Return { value: None }),
+            block("two", vec![print(int(2))], IrTerminator::Return { value: None }),
+            block("default", vec![print(int(0))], IrTerminator::Return { value: None }),
+        ]);
+        let mut module = module(vec![main]);
+        SimplifyCfg.run(&mut module).unwrap();
+        assert_eq!(module.find_function("main").unwrap().block_labels(), vec!["entry"]);
+        assert_eq!(Interpreter::run(&module, "main").output, vec![2]);
+    }
+}
@@ -0,0 +1,235 @@
+//! IR 测试辅助
+//!
+//! 手工构造 IR 的简写函数，以及一个只覆盖整数指令子集的 IR 解释器，
+//! 用于比较优化前后的程序输出。
+
+use super::*;
+use std::collections::HashMap;
+
+pub(crate) fn int(v: i64) -> IrValue {
+    IrValue::IntConst(v, IrType::I32)
+}
+
+pub(crate) fn reg(name: &str) -> IrValue {
+    IrValue::Register(format!("%{}", name), IrType::I32)
+}
+
+pub(crate) fn flag(name: &str) -> IrValue {
+    IrValue::Register(format!("%{}", name), IrType::I1)
+}
+
+pub(crate) fn param(name: &str) -> IrValue {
+    IrValue::Param(name.to_string(), IrType::I32)
+}
+
+pub(crate) fn binary(result: &str, op: IrBinaryOp, left: IrValue, right: IrValue) -> IrInstruction {
+    IrInstruction::BinaryOp { result: reg(result), op, left, right }
+}
+
+pub(crate) fn compare(result: &str, op: IrCmpOp, left: IrValue, right: IrValue) -> IrInstruction {
+    IrInstruction::Compare { result: flag(result), op, left, right }
+}
+
+pub(crate) fn call(result: Option<&str>, func_name: &str, args: Vec<IrValue>) -> IrInstruction {
+    IrInstruction::Call {
+        result: result.map(reg),
+        func_name: func_name.to_string(),
+        args,
+        return_ty: if result.is_some() { IrType::I32 } else { IrType::Void },
+    }
+}
+
+pub(crate) fn print(value: IrValue) -> IrInstruction {
+    call(None, "print", vec![value])
+}
+
+pub(crate) fn block(label: &str, instructions: Vec<IrInstruction>, terminator: IrTerminator) -> IrBasicBlock {
+    let mut block = if label == "entry" { IrBasicBlock::entry() } else { IrBasicBlock::new(label.to_string()) };
+    block.instructions = instructions;
+    block.set_terminator(terminator);
+    block
+}
+
+pub(crate) fn ret(value: IrValue) -> IrTerminator {
+    IrTerminator::Return { value: Some(value) }
+}
+
+pub(crate) fn br(target: &str) -> IrTerminator {
+    IrTerminator::Branch { target: target.to_string() }
+}
+
+pub(crate) fn cond_br(condition: IrValue, true_target: &str, false_target: &str) -> IrTerminator {
+    IrTerminator::ConditionalBranch {
+        condition,
+        true_target: true_target.to_string(),
+        false_target: false_target.to_string(),
+    }
+}
+
+pub(crate) fn function(name: &str, params: &[&str], return_type: IrType, blocks: Vec<IrBasicBlock>) -> IrFunction {
+    let params = params.iter()
+        .map(|p| IrParam { name: p.to_string(), ty: IrType::I32 })
+        .collect();
+    let mut func = IrFunction::new(name.to_string(), return_type, params);
+    func.blocks = blocks;
+    func
+}
+
+pub(crate) fn module(functions: Vec<IrFunction>) -> IrModule {
+    let mut module = IrModule::new("test".to_string(), "x86_64-unknown-linux-gnu".to_string());
+    module.add_extern(IrExternDecl {
+        name: "print".to_string(),
+        return_type: IrType::Void,
+        params: vec![("value".to_string(), IrType::I32)],
+        calling_convention: None,
+        is_varargs: false,
+    });
+    for func in functions {
+        module.add_function(func);
+    }
+    module
+}
+
+/// 解释执行 IR 的结果：print 输出和执行的（非 print）调用次数
+#[derive(Debug, Default)]
+pub(crate) struct Execution {
+    pub output: Vec<i64>,
+    pub calls: usize,
+}
+
+/// 只覆盖测试用到的整数指令子集
+pub(crate) struct Interpreter<'a> {
+    module: &'a IrModule,
+    memory: Vec<i64>,
+    execution: Execution,
+}
+
+impl Interpreter<'_> {
+    pub(crate) fn run(module: &IrModule, entry: &str) -> Execution {
+        let mut interp = Interpreter { module, memory: Vec::new(), execution: Execution::default() };
+        interp.call(entry, Vec::new());
+        interp.execution
+    }
+
+    fn call(&mut self, name: &str, args: Vec<i64>) -> Option<i64> {
+        if name == "print" {
+            self.execution.output.push(args[0]);
+            return None;
+        }
+        self.execution.calls += 1;
+        let func = self.module.find_function(name).unwrap_or_else(|| panic!("unknown function {}", name));
+        let mut env: HashMap<String, i64> = HashMap::new();
+        for (p, a) in func.params.iter().zip(args) {
+            env.insert(p.name.clone(), a);
+        }
+
+        let mut previous = String::new();
+        let mut current = func.blocks[0].label.clone();
+        loop {
+            let block = func.find_block(&current).unwrap_or_else(|| panic!("unknown block {}", current));
+            // phi 按块入口的值同时求值
+            let phis: Vec<(String, i64)> = block.instructions.iter()
+                .filter_map(|inst| match inst {
+                    IrInstruction::Phi { result, incoming, .. } => {
+                        let (value, _) = incoming.iter().find(|(_, l)| *l == previous)
+                            .unwrap_or_else(|| panic!("phi in {} has no incoming for {}", current, previous));
+                        Some((result.to_llvm_str(), Self::value(&env, value)))
+                    }
+                    _ => None,
+                })
+                .collect();
+            env.extend(phis);
+            for inst in &block.instructions {
+                self.execute(&mut env, inst);
+            }
+            let next = match block.terminator.as_ref().unwrap() {
+                IrTerminator::Return { value } => return value.as_ref().map(|v| Self::value(&env, v)),
+                IrTerminator::Branch { target } => target.clone(),
+                IrTerminator::ConditionalBranch { condition, true_target, false_target } => {
+                    if Self::value(&env, condition) != 0 { true_target.clone() } else { false_target.clone() }
+                }
+                IrTerminator::Switch { value, default_target, cases, .. } => {
+                    let v = Self::value(&env, value);
+                    cases.iter().find(|(c, _)| Self::value(&env, c) == v)
+                        .map(|(_, t)| t.clone())
+                        .unwrap_or_else(|| default_target.clone())
+                }
+                IrTerminator::Unreachable => panic!("reached unreachable"),
+            };
+            previous = std::mem::replace(&mut current, next);
+        }
+    }
+
+    fn execute(&mut self, env: &mut HashMap<String, i64>, inst: &IrInstruction) {
+        let value = match inst {
+            IrInstruction::Alloca { .. } => {
+                self.memory.push(0);
+                (self.memory.len() - 1) as i64
+            }
+            IrInstruction::Load { ptr, .. } => self.memory[Self::value(env, ptr) as usize],
+            IrInstruction::Store { value, ptr, .. } => {
+                self.memory[Self::value(env, ptr) as usize] = Self::value(env, value);
+                return;
+            }
+            IrInstruction::BinaryOp { op, left, right, .. } => {
+                let (l, r) = (Self::value(env, left), Self::value(env, right));
+                let v = match op {
+                    IrBinaryOp::Add => l.wrapping_add(r),
+                    IrBinaryOp::Sub => l.wrapping_sub(r),
+                    IrBinaryOp::Mul => l.wrapping_mul(r),
+                    IrBinaryOp::Div => l / r,
+                    IrBinaryOp::Mod => l % r,
+                    other => panic!("unsupported op {:?}", other),
+                };
+                v as i32 as i64
+            }
+            IrInstruction::Compare { op, left, right, .. } => {
+                let (l, r) = (Self::value(env, left), Self::value(env, right));
+                let v = match op {
+                    IrCmpOp::Eq => l == r,
+                    IrCmpOp::Ne => l != r,
+                    IrCmpOp::Slt => l < r,
+                    IrCmpOp::Sle => l <= r,
+                    IrCmpOp::Sgt => l > r,
+                    IrCmpOp::Sge => l >= r,
+                    other => panic!("unsupported compare {:?}", other),
+                };
+                v as i64
+            }
+            IrInstruction::Select { condition, true_val, false_val, .. } => {
+                if Self::value(env, condition) != 0 { Self::value(env, true_val) } else { Self::value(env, false_val) }
+            }
+            IrInstruction::Call { func_name, args, .. } => {
+                let args = args.iter().map(|a| Self::value(env, a)).collect();
+                match self.call(func_name, args) {
+                    Some(v) => v,
+                    None => return,
+                }
+            }
+            IrInstruction::Phi { .. } | IrInstruction::Comment { .. } => return,
+            other => panic!("unsupported instruction {:?}", other),
+        };
+        env.insert(inst.result().unwrap().to_llvm_str(), value);
+    }
+
+    fn value(env: &HashMap<String, i64>, value: &IrValue) -> i64 {
+        match value {
+            IrValue::IntConst(v, _) => *v,
+            IrValue::BoolConst(b) => *b as i64,
+            IrValue::Undef(_) => 0,
+            IrValue::Register(name, _) | IrValue::Param(name, _) => {
+                *env.get(name).unwrap_or_else(|| panic!("undefined value {}", name))
+            }
+            other => panic!("unsupported value {:?}", other),
+        }
+    }
+}
+
+/// 函数中对 `callee` 的调用指令数
+pub(crate) fn calls_to(func: &IrFunction, callee: &str) -> usize {
+    func.blocks.iter()
+        .flat_map(|b| &b.instructions)
+        .filter(|inst| matches!(inst, IrInstruction::Call { func_name, .. } if func_name == callee))
+        .count()
+}
+
@@ -2,6 +2,7 @@
 //!
 //! 定义 IR 层面的类型表示，独立于 AST 类型但完全覆盖所有 Cavvy 类型。
 
+use crate::target::TargetInfo;
 use std::fmt;
 
 /// IR 类型 - 后端无关的类型表示
@@ -73,42 +74,84 @@ impl IrType {
         }
     }
 
-    /// 获取类型大小（字节）
-    pub fn size_bytes(&self) -> usize {
+    /// 获取类型在目标平台上的大小（字节）
+    pub fn size_bytes(&self, target: &TargetInfo) -> usize {
         match self {
             IrType::Void => 0,
             IrType::I1 | IrType::I8 => 1,
             IrType::I16 => 2,
             IrType::I32 | IrType::F32 => 4,
-            IrType::I64 | IrType::F64 | IrType::Pointer(_) => 8,
-            IrType::Array(elem, count) => elem.size_bytes() * count,
+            IrType::I64 | IrType::F64 => 8,
+            IrType::Pointer(_) => target.pointer_size,
+            IrType::Array(elem, count) => elem.size_bytes(target) * count,
             IrType::Struct { fields, .. } => {
                 let mut total = 0;
                 for (_, ty) in fields {
-                    let align = ty.alignment();
+                    let align = ty.alignment(target);
                     total = (total + align - 1) & !(align - 1);
-                    total += ty.size_bytes();
+                    total += ty.size_bytes(target);
                 }
-                // 最终对齐到 8 字节
-                (total + 7) & !7
+                // 最终对齐到结构体的对齐
+                let align = self.alignment(target);
+                (total + align - 1) & !(align - 1)
             }
-            IrType::Function { .. } => 8, // 函数指针
+            IrType::Function { .. } => target.pointer_size, // 函数指针
             IrType::Label => 0,
-            IrType::Raw(_) => 8, // 假设指针大小
+            IrType::Raw(_) => target.pointer_size, // 假设指针大小
         }
     }
 
-    /// 获取类型对齐（字节）
-    pub fn alignment(&self) -> usize {
+    /// 获取类型在目标平台上的对齐（字节）
+    pub fn alignment(&self, target: &TargetInfo) -> usize {
         match self {
             IrType::I1 | IrType::I8 => 1,
             IrType::I16 => 2,
             IrType::I32 | IrType::F32 => 4,
-            IrType::I64 | IrType::F64 | IrType::Pointer(_) | IrType::Function { .. } => 8,
-            IrType::Array(elem, _) => elem.alignment(),
-            IrType::Struct { .. } => 8,
+            IrType::I64 | IrType::F64 => target.i64_align(),
+            IrType::Pointer(_) | IrType::Function { .. } | IrType::Raw(_) => target.pointer_size,
+            IrType::Array(elem, _) => elem.alignment(target),
+            // 对象按指针宽度对齐
+            IrType::Struct { .. } => target.pointer_size,
             IrType::Void | IrType::Label => 1,
-            IrType::Raw(_) => 8,
+        }
+    }
+
+    /// 从 LLVM IR 类型字符串解析类型
+    ///
+    /// 支持基本类型、`T*` 指针和 `[N x T]` 数组；其余（如 `%struct.X`、`{i32, i1}`）保留为 `Raw`。
+    pub fn from_llvm_str(s: &str) -> IrType {
+        let s = s.trim();
+        if let Some(inner) = s.strip_suffix('*') {
+            return IrType::Pointer(Box::new(IrType::from_llvm_str(inner)));
+        }
+        match s {
+            "void" => IrType::Void,
+            "i1" => IrType::I1,
+            "i8" => IrType::I8,
+            "i16" => IrType::I16,
+            "i32" => IrType::I32,
+            "i64" => IrType::I64,
+            "float" => IrType::F32,
+            "double" => IrType::F64,
+            "label" => IrType::Label,
+            _ => {
+                if let Some(body) = s.strip_prefix('[').and_then(|b| b.strip_suffix(']')) {
+                    if let Some((count, elem)) = body.split_once(" x ") {
+                        if let Ok(count) = count.trim().parse::<usize>() {
+                            return IrType::Array(Box::new(IrType::from_llvm_str(elem)), count);
+                        }
+                    }
+                }
+                IrType::Raw(s.to_string())
+            }
+        }
+    }
+
+    /// 指针指向的类型（非指针返回 None）
+    pub fn pointee(&self) -> Option<&IrType> {
+        match self {
+            IrType::Pointer(inner) => Some(inner),
+            _ => None,
         }
     }
 
@@ -178,6 +221,7 @@ impl From<&crate::types::Type> for IrType {
                 name: format!("struct.{}", name),
                 fields: Vec::new(),
             },
+            Type::Nullable(inner) => IrType::from(inner.as_ref()),
         }
     }
 }
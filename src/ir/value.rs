@@ -54,29 +54,7 @@ impl IrValue {
     pub fn to_llvm_str(&self) -> String {
         match self {
             IrValue::IntConst(v, ty) => format!("{} {}", ty.to_llvm_str(), v),
-            IrValue::FloatConst(v, ty) => {
-                if v.is_nan() {
-                    match ty {
-                        IrType::F32 => format!("float 0x{:08X}", f32::NAN.to_bits()),
-                        IrType::F64 => format!("double 0x{:016X}", f64::NAN.to_bits()),
-                        _ => format!("{} {}", ty.to_llvm_str(), v),
-                    }
-                } else if v.is_infinite() {
-                    match ty {
-                        IrType::F32 => {
-                            let bits = if *v > 0.0 { f32::INFINITY.to_bits() } else { f32::NEG_INFINITY.to_bits() };
-                            format!("float 0x{:08X}", bits)
-                        }
-                        IrType::F64 => {
-                            let bits = if *v > 0.0 { f64::INFINITY.to_bits() } else { f64::NEG_INFINITY.to_bits() };
-                            format!("double 0x{:016X}", bits)
-                        }
-                        _ => format!("{} {}", ty.to_llvm_str(), v),
-                    }
-                } else {
-                    format!("{} {:.6e}", ty.to_llvm_str(), v)
-                }
-            }
+            IrValue::FloatConst(_, ty) => format!("{} {}", ty.to_llvm_str(), self.to_raw_str()),
             IrValue::BoolConst(v) => format!("i1 {}", if *v { 1 } else { 0 }),
             IrValue::StringConst(s) => {
                 let escaped = s.replace("\\", "\\\\")
@@ -89,7 +67,7 @@ impl IrValue {
             IrValue::NullConst(ty) => format!("{} null", ty.to_llvm_str()),
             IrValue::Register(name, _) => name.clone(),
             IrValue::GlobalRef(name, _) => name.clone(),
-            IrValue::Param(name, _) => name.clone(),
+            IrValue::Param(name, _) => format!("%{}", name),
             IrValue::Undef(ty) => format!("{} undef", ty.to_llvm_str()),
         }
     }
@@ -99,34 +77,16 @@ impl IrValue {
         match self {
             IrValue::IntConst(v, _) => v.to_string(),
             IrValue::FloatConst(v, ty) => {
-                if v.is_nan() {
-                    match ty {
-                        IrType::F32 => format!("0x{:08X}", f32::NAN.to_bits()),
-                        IrType::F64 => format!("0x{:016X}", f64::NAN.to_bits()),
-                        _ => format!("{:.6e}", v),
-                    }
-                } else if v.is_infinite() {
-                    match ty {
-                        IrType::F32 => {
-                            let bits = if *v > 0.0 { f32::INFINITY.to_bits() } else { f32::NEG_INFINITY.to_bits() };
-                            format!("0x{:08X}", bits)
-                        }
-                        IrType::F64 => {
-                            let bits = if *v > 0.0 { f64::INFINITY.to_bits() } else { f64::NEG_INFINITY.to_bits() };
-                            format!("0x{:016X}", bits)
-                        }
-                        _ => format!("{:.6e}", v),
-                    }
-                } else {
-                    format!("{:.6e}", v)
-                }
+                // LLVM 的十六进制浮点常量总是 double 的位模式；float 必须可被 f32 精确表示
+                let v = if *ty == IrType::F32 { *v as f32 as f64 } else { *v };
+                format!("0x{:016X}", v.to_bits())
             }
             IrValue::BoolConst(v) => if *v { "1".to_string() } else { "0".to_string() },
             IrValue::StringConst(s) => format!("@.str.{}", s.len()), // 返回字符串常量名称
             IrValue::NullConst(_) => "null".to_string(),
             IrValue::Register(name, _) => name.clone(),
             IrValue::GlobalRef(name, _) => name.clone(),
-            IrValue::Param(name, _) => name.clone(),
+            IrValue::Param(name, _) => format!("%{}", name),
             IrValue::Undef(_) => "undef".to_string(),
         }
     }
@@ -262,7 +222,7 @@ impl IrCmpOp {
 }
 
 /// IR 指令 - 基本块内的非终止指令
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum IrInstruction {
     /// alloca: 栈上分配内存
     Alloca {
@@ -317,6 +277,26 @@ pub enum IrInstruction {
         return_ty: IrType,
     },
 
+    /// 通过函数指针或显式函数类型的调用
+    ///
+    /// 用于 lambda/方法引用等函数指针调用，以及 printf 这类可变参数函数
+    /// （LLVM 要求调用可变参数函数时写出完整的函数类型）。
+    CallIndirect {
+        result: Option<IrValue>,
+        callee: IrValue,
+        return_ty: IrType,
+        param_tys: Vec<IrType>,
+        is_varargs: bool,
+        args: Vec<IrValue>,
+    },
+
+    /// extractvalue: 从聚合值（如溢出检测内建函数返回的 {iN, i1}）中取出字段
+    ExtractValue {
+        result: IrValue,
+        aggregate: IrValue,
+        index: u32,
+    },
+
     /// getelementptr: 指针计算
     GetElementPtr {
         result: IrValue,
@@ -363,8 +343,9 @@ pub enum IrInstruction {
         text: String,
     },
 
-    /// 源位置信息（用于调试）
+    /// 源位置信息（用于调试和 llc 错误回溯）
     SourceLocation {
+        file: String,
         line: u32,
         column: u32,
     },
@@ -388,6 +369,8 @@ impl IrInstruction {
             IrInstruction::Compare { result, .. } => Some(result),
             IrInstruction::Cast { result, .. } => Some(result),
             IrInstruction::Call { result, .. } => result.as_ref(),
+            IrInstruction::CallIndirect { result, .. } => result.as_ref(),
+            IrInstruction::ExtractValue { result, .. } => Some(result),
             IrInstruction::GetElementPtr { result, .. } => Some(result),
             IrInstruction::BitCast { result, .. } => Some(result),
             IrInstruction::Phi { result, .. } => Some(result),
@@ -409,6 +392,8 @@ impl IrInstruction {
             IrInstruction::Compare { left, right, .. } => vec![left, right],
             IrInstruction::Cast { value, .. } => vec![value],
             IrInstruction::Call { args, .. } => args.iter().collect(),
+            IrInstruction::CallIndirect { callee, args, .. } => std::iter::once(callee).chain(args.iter()).collect(),
+            IrInstruction::ExtractValue { aggregate, .. } => vec![aggregate],
             IrInstruction::GetElementPtr { ptr, indices, .. } => {
                 let mut v: Vec<&IrValue> = vec![ptr];
                 v.extend(indices.iter());
@@ -423,10 +408,43 @@ impl IrInstruction {
             IrInstruction::VarDecl { .. } => vec![],
         }
     }
+
+    /// 获取指令中所有值（结果和输入）的可变引用，用于寄存器重命名等改写
+    pub fn values_mut(&mut self) -> Vec<&mut IrValue> {
+        match self {
+            IrInstruction::Alloca { result, .. } => vec![result],
+            IrInstruction::Load { result, ptr, .. } => vec![result, ptr],
+            IrInstruction::Store { value, ptr, .. } => vec![value, ptr],
+            IrInstruction::BinaryOp { result, left, right, .. } => vec![result, left, right],
+            IrInstruction::Compare { result, left, right, .. } => vec![result, left, right],
+            IrInstruction::Cast { result, value, .. } => vec![result, value],
+            IrInstruction::Call { result, args, .. } => result.iter_mut().chain(args.iter_mut()).collect(),
+            IrInstruction::CallIndirect { result, callee, args, .. } => {
+                result.iter_mut().chain(std::iter::once(callee)).chain(args.iter_mut()).collect()
+            }
+            IrInstruction::ExtractValue { result, aggregate, .. } => vec![result, aggregate],
+            IrInstruction::GetElementPtr { result, ptr, indices, .. } => {
+                let mut v: Vec<&mut IrValue> = vec![result, ptr];
+                v.extend(indices.iter_mut());
+                v
+            }
+            IrInstruction::BitCast { result, value, .. } => vec![result, value],
+            IrInstruction::Phi { result, incoming, .. } => {
+                let mut v: Vec<&mut IrValue> = vec![result];
+                v.extend(incoming.iter_mut().map(|(val, _)| val));
+                v
+            }
+            IrInstruction::Select { result, condition, true_val, false_val } => vec![result, condition, true_val, false_val],
+            IrInstruction::InlineIr { outputs, inputs, .. } => outputs.iter_mut().chain(inputs.iter_mut()).collect(),
+            IrInstruction::Comment { .. } => vec![],
+            IrInstruction::SourceLocation { .. } => vec![],
+            IrInstruction::VarDecl { alloca_reg, .. } => vec![alloca_reg],
+        }
+    }
 }
 
 /// IR 终止指令 - 基本块的结束指令
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum IrTerminator {
     /// ret void 或 ret <value>
     Return {
@@ -456,3 +474,49 @@ pub enum IrTerminator {
     /// 不可达指令
     Unreachable,
 }
+
+impl IrTerminator {
+    /// 获取终止指令引用的所有输入值
+    pub fn inputs(&self) -> Vec<&IrValue> {
+        match self {
+            IrTerminator::Return { value } => value.iter().collect(),
+            IrTerminator::Branch { .. } => vec![],
+            IrTerminator::ConditionalBranch { condition, .. } => vec![condition],
+            IrTerminator::Switch { value, cases, .. } => {
+                let mut v: Vec<&IrValue> = vec![value];
+                v.extend(cases.iter().map(|(val, _)| val));
+                v
+            }
+            IrTerminator::Unreachable => vec![],
+        }
+    }
+
+    /// 获取终止指令中所有值的可变引用
+    pub fn values_mut(&mut self) -> Vec<&mut IrValue> {
+        match self {
+            IrTerminator::Return { value } => value.iter_mut().collect(),
+            IrTerminator::Branch { .. } => vec![],
+            IrTerminator::ConditionalBranch { condition, .. } => vec![condition],
+            IrTerminator::Switch { value, cases, .. } => {
+                let mut v: Vec<&mut IrValue> = vec![value];
+                v.extend(cases.iter_mut().map(|(val, _)| val));
+                v
+            }
+            IrTerminator::Unreachable => vec![],
+        }
+    }
+
+    /// 获取所有跳转目标标签的可变引用
+    pub fn targets_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            IrTerminator::Return { .. } | IrTerminator::Unreachable => vec![],
+            IrTerminator::Branch { target } => vec![target],
+            IrTerminator::ConditionalBranch { true_target, false_target, .. } => vec![true_target, false_target],
+            IrTerminator::Switch { default_target, cases, .. } => {
+                let mut v: Vec<&mut String> = cases.iter_mut().map(|(_, t)| t).collect();
+                v.push(default_target);
+                v
+            }
+        }
+    }
+}
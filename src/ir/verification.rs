@@ -113,6 +113,13 @@ impl IrVerifier {
             defined_values.insert(format!("%{}", param.name));
         }
 
+        // phi 的入边值来自前驱块末尾（可能是循环回边），只要求在函数中有定义
+        let mut all_values = defined_values.clone();
+        all_values.extend(func.blocks.iter()
+            .flat_map(|b| &b.instructions)
+            .filter_map(|inst| inst.result())
+            .map(|v| v.to_llvm_str()));
+
         // 验证每个基本块
         for block in &func.blocks {
             let block_ctx = format!("{}: block '{}'", ctx, block.label);
@@ -124,7 +131,8 @@ impl IrVerifier {
 
             // 验证指令
             for inst in &block.instructions {
-                self.verify_instruction(inst, &block_ctx, &defined_values, &block_labels);
+                let visible = if matches!(inst, IrInstruction::Phi { .. }) { &all_values } else { &defined_values };
+                self.verify_instruction(inst, &block_ctx, visible, &block_labels);
 
                 // 记录此指令产生的值
                 if let Some(result) = inst.result() {
@@ -148,7 +156,7 @@ impl IrVerifier {
 
         // 检查是否有不可达的块（除了入口块）
         if block_labels.len() > 1 {
-            let reachable = self.compute_reachable_blocks(func);
+            let reachable = Self::compute_reachable_blocks(func);
             let unreachable_blocks: Vec<String> = func.blocks.iter()
                 .filter(|b| !b.is_entry && !reachable.contains(&b.label.as_str()))
                 .map(|b| format!("{}: block '{}' is unreachable", ctx, b.label))
@@ -232,7 +240,7 @@ impl IrVerifier {
     }
 
     /// 计算从入口块可达的基本块集合
-    fn compute_reachable_blocks<'a>(&self, func: &'a IrFunction) -> HashSet<&'a str> {
+    pub fn compute_reachable_blocks(func: &IrFunction) -> HashSet<&str> {
         let mut visited = HashSet::new();
         let mut queue = Vec::new();
 
@@ -314,4 +322,26 @@ mod tests {
         let result = IrVerifier::new().verify(&module);
         assert!(result.is_valid);
     }
+
+    #[test]
+    fn test_phi_with_back_edge_value() {
+        use crate::ir::test_support::*;
+
+        // 循环头的 phi 引用循环体中定义的值（回边），不应报告为使用未定义值
+        let main = function("main", &[], IrType::Void, vec![
+            block("entry", vec![], br("loop")),
+            block("loop", vec![
+                IrInstruction::Phi { result: reg("i"), ty: IrType::I32, incoming: vec![
+                    (int(0), "entry".to_string()),
+                    (reg("next"), "loop".to_string()),
+                ] },
+                binary("next", IrBinaryOp::Add, reg("i"), int(1)),
+                compare("c", IrCmpOp::Slt, reg("next"), int(10)),
+            ], cond_br(flag("c"), "loop", "exit")),
+            block("exit", vec![], IrTerminator::Return { value: None }),
+        ]);
+        let result = IrVerifier::new().verify(&module(vec![main]));
+        assert!(result.is_valid, "{:?}", result.errors);
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    }
 }
@@ -29,11 +29,15 @@ pub enum Token {
     Abstract,
     #[token("native")]
     Native,
-    // 注解 - 注意：@main 和 @Override 是完整的令牌，不是 @ + 标识符
+    #[token("export")]
+    Export,
+    // 注解 - 注意：@main、@Override 和 @Test 是完整的令牌，不是 @ + 标识符
     #[token("@main")]
     AtMain,
     #[token("@Override")]
     AtOverride,
+    #[token("@Test")]
+    AtTest,
     #[token("class")]
     Class,
     #[token("void")]
@@ -96,6 +100,8 @@ pub enum Token {
     Interface,
     #[token("instanceof")]
     InstanceOf,
+    #[token("sizeof")]
+    SizeOf,
     #[token("var")]
     Var,
     #[token("let")]
@@ -165,6 +171,12 @@ pub enum Token {
     #[token("fn")]
     Fn,
 
+    // 包和导入关键字
+    #[token("package")]
+    Package,
+    #[token("import")]
+    Import,
+
     // extern 别名关键字
     #[token("as")]
     As,
@@ -880,8 +892,10 @@ pub fn token_name(token: &Token) -> &'static str {
         Token::Final => "final",
         Token::Abstract => "abstract",
         Token::Native => "native",
+        Token::Export => "export",
         Token::AtMain => "@main",
         Token::AtOverride => "@Override",
+        Token::AtTest => "@Test",
         Token::Class => "class",
         Token::Void => "void",
         Token::Int => "int",
@@ -912,6 +926,7 @@ pub fn token_name(token: &Token) -> &'static str {
         Token::Implements => "implements",
         Token::Interface => "interface",
         Token::InstanceOf => "instanceof",
+        Token::SizeOf => "sizeof",
         Token::Var => "var",
         Token::Let => "let",
         Token::Auto => "auto",
@@ -943,6 +958,8 @@ pub fn token_name(token: &Token) -> &'static str {
         Token::Win64 => "win64",
         Token::Alias => "alias",
         Token::Fn => "fn",
+        Token::Package => "package",
+        Token::Import => "import",
         Token::As => "as",
         Token::Identifier(_) => "identifier",
         Token::IntegerLiteral(_) => "integer literal",
@@ -1001,7 +1018,7 @@ pub fn token_name(token: &Token) -> &'static str {
 pub fn is_keyword(token: &Token) -> bool {
     matches!(token,
         Token::Public | Token::Private | Token::Protected |
-        Token::Static | Token::Final | Token::Abstract | Token::Native |
+        Token::Static | Token::Final | Token::Abstract | Token::Native | Token::Export |
         Token::Class | Token::Void | Token::Int | Token::Long |
         Token::Float | Token::Double | Token::Bool | Token::String |
         Token::Char | Token::True | Token::False | Token::Null |
@@ -1009,9 +1026,10 @@ pub fn is_keyword(token: &Token) -> bool {
         Token::Do | Token::Switch | Token::Case | Token::Default |
         Token::Return | Token::Break | Token::Continue |
         Token::New | Token::This | Token::Super |
-        Token::Extends | Token::Implements | Token::Interface | Token::InstanceOf |
+        Token::Extends | Token::Implements | Token::Interface | Token::InstanceOf | Token::SizeOf |
         Token::Var | Token::Let | Token::Auto | Token::Extern | Token::Scope |
-        Token::InlineIr | Token::Alias | Token::Fn | Token::As
+        Token::InlineIr | Token::Alias | Token::Fn | Token::As |
+        Token::Package | Token::Import
     )
 }
 
@@ -1190,11 +1208,21 @@ mod tests {
 
     #[test]
     fn test_annotations() {
-        let source = r#"@main @Override"#;
+        let source = r#"@main @Override @Test"#;
         let tokens = tokenize(source).unwrap();
-        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens.len(), 3);
         assert!(matches!(tokens[0].token, Token::AtMain));
         assert!(matches!(tokens[1].token, Token::AtOverride));
+        assert!(matches!(tokens[2].token, Token::AtTest));
+    }
+
+    #[test]
+    fn test_package_and_import() {
+        let source = r#"package app.util; import app.model.*;"#;
+        let tokens = tokenize(source).unwrap();
+        assert!(matches!(tokens[0].token, Token::Package));
+        assert!(matches!(tokens[1].token, Token::Identifier(ref name) if name == "app"));
+        assert!(matches!(tokens[5].token, Token::Import));
     }
 
     #[test]
@@ -11,6 +11,9 @@ pub mod codegen;
 pub mod ir;
 pub mod rcpl;
 pub mod bytecode;
+pub mod unit;
+pub mod cabi;
+pub mod target;
 
 // GUI模块（cay-idle使用）
 pub mod idle;
@@ -21,10 +24,18 @@ pub mod cavly;
 use std::path::{Path, PathBuf};
 use error::cayResult;
 
+/// 预处理后的代码及其源映射（输出行 -> (文件, 源行)）
+type PreprocessedSource = (String, std::collections::HashMap<usize, (String, usize)>);
+
+/// 多个源文件合并后的程序及其源映射
+type ParsedProgram = (ast::Program, std::collections::HashMap<usize, (String, usize)>);
+
 /// 编译器配置选项
 #[derive(Debug, Clone)]
 pub struct CompilerOptions {
     pub target_os: String,
+    /// 目标三元组（--target=<triple>），决定指针宽度、C 类型大小和数据布局；为 None 时使用 target_os 的 x86_64 目标
+    pub target_triple: Option<String>,
     pub features: Vec<String>,
     pub no_features: Vec<String>,
     pub defines: Vec<String>,
@@ -32,18 +43,53 @@ pub struct CompilerOptions {
     pub obfuscate: bool,
     /// 额外的包含路径（供 #include 搜索）
     pub include_paths: Vec<String>,
+    /// 是否生成运行时检查（空指针、类型转换、除零），默认开启
+    pub runtime_checks: bool,
+    /// 是否对 int/long 算术生成溢出检查（-fcheck-overflow），默认关闭
+    pub check_overflow: bool,
+    /// 使用旧的字符串代码生成器（--legacy-codegen），默认经 IrBuilder 生成结构化 IR
+    pub legacy_codegen: bool,
+    /// 生成 DWARF 调试信息（-g），默认关闭；`legacy_codegen` 时只有函数和行号，没有变量和类型
+    pub debug_info: bool,
+    /// 在 stderr 列出去虚拟化的调用点（--print-devirt），默认关闭
+    pub print_devirt: bool,
+    /// 在 stderr 输出逃逸分析结果（-Rpass=escape），默认关闭；只用于结构化 IR 管线
+    pub escape_remarks: bool,
+    /// 输出类型（--crate-type），库输出时不生成 main，只以 C ABI 导出函数
+    pub crate_type: cabi::CrateType,
+    /// 库输出时额外导出的函数（`类名.方法名` 或函数名），标记 `export` 的函数总是导出
+    pub exports: Vec<String>,
 }
 
 impl Default for CompilerOptions {
     fn default() -> Self {
         Self {
             target_os: std::env::consts::OS.to_string(),
+            target_triple: None,
             features: Vec::new(),
             no_features: Vec::new(),
             defines: Vec::new(),
             undefines: Vec::new(),
             obfuscate: false,
             include_paths: Vec::new(),
+            runtime_checks: true,
+            check_overflow: false,
+            legacy_codegen: false,
+            debug_info: false,
+            print_devirt: false,
+            escape_remarks: false,
+            crate_type: cabi::CrateType::Bin,
+            exports: Vec::new(),
+        }
+    }
+}
+
+impl CompilerOptions {
+    /// 目标平台信息（见 [`target`]）；指定了不支持的三元组时返回错误
+    pub fn target_info(&self) -> Result<target::TargetInfo, String> {
+        match &self.target_triple {
+            Some(triple) => target::TargetInfo::from_triple(triple),
+            None => Ok(target::TargetInfo::for_os(&self.target_os)),
         }
     }
 }
@@ -52,6 +98,41 @@ pub struct Compiler {
     options: CompilerOptions,
 }
 
+/// 递归收集目录下的 .cay 源文件，按路径排序
+///
+/// 供多文件编译（`cayc --src-dir`、`cavly build`）确定输入文件及其顺序。
+pub fn source_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut entries = std::fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.path());
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(source_files(&path)?);
+        } else if path.extension().is_some_and(|ext| ext == "cay") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// 打印语义分析阶段的警告（不影响编译结果）
+fn report_semantic_warnings(warnings: &[semantic::SemanticErrorInfo]) {
+    for warning in warnings {
+        match &warning.file {
+            Some(file) => error::print_warning_with_location(&warning.message, file, warning.line, warning.column),
+            None => error::print_warning(&format!("{} (行 {}, 列 {})", warning.message, warning.line, warning.column)),
+        }
+    }
+}
+
+/// 打印改为直接调用的接口调用点（--print-devirt）
+fn report_devirtualized_calls(calls: &[codegen::DevirtualizedCall]) {
+    for call in calls {
+        eprintln!("devirt: {}", call);
+    }
+}
+
 impl Compiler {
     pub fn new() -> Self {
         Self {
@@ -89,17 +170,13 @@ impl Compiler {
         let ast = parser::parse_with_source(tokens, source.to_string())?;
 
         // 3. 语义分析
-        let mut analyzer = semantic::SemanticAnalyzer::with_features(self.options.features.clone());
+        let mut analyzer = self.semantic_analyzer()?;
         analyzer.analyze(&ast)?;
+        report_semantic_warnings(analyzer.warnings());
 
-        // 4. 代码生成 - 生成LLVM IR（字符串常量已在生成器内处理）
-        let mut ir_gen = codegen::IRGenerator::new();
-        // 传递多平台配置
-        ir_gen.set_platform_config(&self.options);
-        // 传递类型注册表以支持正确的方法名生成
-        ir_gen.set_type_registry(analyzer.get_type_registry().clone());
+        // 4. 代码生成 - 生成LLVM IR
         // 注意：compile方法没有源文件路径，使用空字符串
-        let mut ir = ir_gen.generate(&ast, "")?;
+        let mut ir = self.generate_llvm_ir(&ast, analyzer.get_type_registry(), None, "", None)?;
 
         // 5. 如果启用了混淆，应用IR混淆
         if self.options.obfuscate {
@@ -162,24 +239,22 @@ impl Compiler {
         // 2. 语法分析（传入源代码以支持内联IR解析）
         let ast = parser::parse_with_source(tokens, source.to_string())?;
 
+        self.compile_program(&ast, source_map_for_analyzer, output_path, main_file)
+    }
+
+    /// 语义分析、代码生成并写出 LLVM IR
+    fn compile_program(&self, ast: &ast::Program, source_map: std::collections::HashMap<usize, (String, usize)>, output_path: &str, main_file: Option<String>) -> cayResult<()> {
         // 3. 语义分析
-        let mut analyzer = semantic::SemanticAnalyzer::with_features(self.options.features.clone());
+        let mut analyzer = self.semantic_analyzer()?;
         analyzer.set_current_file(main_file.clone());
         // 传递源映射表以支持多文件include场景下的正确错误定位
-        analyzer.set_source_map(source_map_for_analyzer.clone());
-        analyzer.analyze(&ast)?;
+        analyzer.set_source_map(source_map.clone());
+        analyzer.analyze(ast)?;
+        report_semantic_warnings(analyzer.warnings());
 
-        // 4. 代码生成 - 生成LLVM IR（字符串常量已在生成器内处理）
-        let mut ir_gen = codegen::IRGenerator::new();
-        // 传递多平台配置
-        ir_gen.set_platform_config(&self.options);
-        // 传递类型注册表以支持正确的方法名生成
-        ir_gen.set_type_registry(analyzer.get_type_registry().clone());
-        // 设置预处理器源映射（用于多文件include场景）
-        ir_gen.set_preprocessor_source_map(source_map_for_analyzer.clone());
-        // 设置源文件路径以启用源映射
+        // 4. 代码生成 - 生成LLVM IR（源映射用于多文件include场景，源文件路径用于启用源映射）
         let source_file = main_file.as_deref().unwrap_or("");
-        let mut ir = ir_gen.generate(&ast, source_file)?;
+        let mut ir = self.generate_llvm_ir(ast, analyzer.get_type_registry(), Some(source_map), source_file, None)?;
 
         // 5. 如果启用了混淆，应用IR混淆
         if self.options.obfuscate {
@@ -204,12 +279,291 @@ impl Compiler {
     /// # Returns
     /// 编译成功返回 Ok(())
     pub fn compile_file(&self, input_path: &str, output_path: &str) -> cayResult<()> {
+        let (code, source_map) = self.preprocess_file(input_path)?;
+
+        // 编译预处理后的代码（带源映射和主文件路径）
+        let main_file = Some(input_path.to_string());
+        self.compile_with_source_map_and_main_file(&code, source_map, output_path, main_file)
+    }
+
+    /// 把多个源文件编译为一个 LLVM IR 文件
+    ///
+    /// 每个文件是一个源文件单元（[`ast::SourceUnit`]），可以用 `package`/`import` 声明控制文件之间的可见性；
+    /// 第一个文件是主文件。只有一个输入时等同于 [`Compiler::compile_file`]。
+    pub fn compile_files(&self, input_paths: &[String], output_path: &str) -> cayResult<()> {
+        match input_paths {
+            [] => Err(error::cayError::Io("没有输入文件".to_string())),
+            [input] => self.compile_file(input, output_path),
+            _ => {
+                let (ast, source_map) = self.parse_files(input_paths)?;
+                self.compile_program(&ast, source_map, output_path, Some(input_paths[0].clone()))
+            }
+        }
+    }
+
+    /// 预处理并解析多个源文件，合并为一个程序
+    ///
+    /// 所有文件共用一个预处理器（宏定义按文件顺序可见，共同包含的文件和已被 `#include` 的输入只展开一次），
+    /// 预处理结果按顺序拼接成一份源代码，行号在各文件之间不重叠，源映射把它映射回各个原始文件。
+    ///
+    /// # 复杂度
+    /// 时间 O(n)，n 为所有文件预处理后的总长度
+    fn parse_files(&self, input_paths: &[String]) -> cayResult<ParsedProgram> {
+        let mut pp = self.create_preprocessor(&input_paths[0]);
+        let mut combined = String::new();
+        let mut source_map = std::collections::HashMap::new();
+        let mut inputs = Vec::with_capacity(input_paths.len());
+        let mut line_ranges = Vec::with_capacity(input_paths.len());
+        let mut offset = 0;
+        for input in input_paths {
+            // 已经被前面的文件 #include 展开的输入属于包含它的单元
+            if !pp.mark_included(input) {
+                continue;
+            }
+            let (code, file_map) = self.run_preprocessor(&mut pp, input)?;
+            let lines: Vec<&str> = code.lines().collect();
+            let line_count = lines.len().max(file_map.len());
+            for index in 0..line_count {
+                combined.push_str(lines.get(index).copied().unwrap_or(""));
+                combined.push('\n');
+            }
+            for (line, position) in file_map {
+                source_map.insert(line + offset, position);
+            }
+            inputs.push(input);
+            line_ranges.push(offset + 1..=offset + line_count);
+            offset += line_count;
+        }
+
+        let tokens = lexer::lex_with_source_map(&combined, source_map.clone())?;
+        let mut segments: Vec<Vec<lexer::TokenWithLocation>> = vec![Vec::new(); inputs.len()];
+        for token in tokens {
+            let index = line_ranges.iter().position(|range| range.contains(&token.loc.line))
+                .unwrap_or(inputs.len() - 1);
+            segments[index].push(token);
+        }
+
+        let mut program = ast::Program::default();
+        for (input, segment) in inputs.into_iter().zip(segments) {
+            let mut unit_program = parser::parse_with_source(segment, combined.clone())?;
+            for unit in &mut unit_program.units {
+                unit.file = input.to_string();
+            }
+            program.append(unit_program);
+        }
+        Ok((program, source_map))
+    }
+
+    /// 从文件构建结构化 IR 模块（预处理 → 词法 → 语法 → 语义 → IrBuilder）
+    ///
+    /// 供 IR 优化 pass（`cay-ir --passes=...`）使用。
+    pub fn build_ir_module_file(&self, input_path: &str) -> cayResult<ir::IrModule> {
+        let (code, source_map) = self.preprocess_file(input_path)?;
+        let analyzer_source_map = source_map.clone();
+        let builder_source_map = source_map.clone();
+
+        let tokens = lexer::lex_with_source_map(&code, source_map)?;
+        let ast = parser::parse_with_source(tokens, code.clone())?;
+
+        let mut analyzer = self.semantic_analyzer()?;
+        analyzer.set_current_file(Some(input_path.to_string()));
+        analyzer.set_source_map(analyzer_source_map);
+        analyzer.analyze(&ast)?;
+        report_semantic_warnings(analyzer.warnings());
+
+        self.build_ir_module(&ast, analyzer.get_type_registry(), Some(builder_source_map), input_path, None)
+    }
+
+    /// 分离编译一个源文件（编译单元，见 [`unit`]）
+    ///
+    /// `#include` 的文件不展开，作为依赖单元从 `output_path` 所在目录读取它们的接口文件（.cai），
+    /// 因此依赖单元要先编译；被包含文件中的宏定义对本单元不可见。本单元的 LLVM IR 写到 `output_path`，
+    /// 接口文件写到同一目录，返回接口文件路径。
+    pub fn compile_unit_file(&self, input_path: &str, output_path: &str) -> cayResult<PathBuf> {
+        if self.options.legacy_codegen || self.options.obfuscate {
+            return Err(error::cayError::Io("分离编译不支持 --legacy-codegen 和 IR 混淆".to_string()));
+        }
+        let source = std::fs::canonicalize(input_path)
+            .map(|p| p.to_string_lossy().to_string())
+            .map_err(|e| error::cayError::Io(format!("无法读取源文件 '{}': {}", input_path, e)))?;
+        let out_dir = Path::new(output_path).parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+
+        let mut pp = self.create_preprocessor(input_path);
+        pp.set_unit_mode();
+        let (code, source_map) = self.run_preprocessor(&mut pp, input_path)?;
+        let imports = pp.unit_dependencies().to_vec();
+        let imported = unit::merge_interfaces(&unit::load_interfaces(&imports, out_dir)?);
+
+        let tokens = lexer::lex_with_source_map(&code, source_map.clone())?;
+        let mut parser = parser::Parser::with_source(tokens, code.clone());
+        for alias in &imported.type_aliases {
+            parser.register_type_alias(alias.name.clone(), alias.target_type.clone());
+        }
+        let ast = parser.parse()?;
+
+        let mut analyzer = self.semantic_analyzer()?;
+        analyzer.set_current_file(Some(input_path.to_string()));
+        analyzer.set_source_map(source_map.clone());
+        analyzer.set_imports(imported.clone());
+        analyzer.analyze(&ast)?;
+        report_semantic_warnings(analyzer.warnings());
+
+        let ir = self.generate_llvm_ir(&ast, analyzer.get_type_registry(), Some(source_map), input_path, Some(imported))?;
+        std::fs::write(output_path, ir)
+            .map_err(|e| error::cayError::Io(format!("无法写入 '{}': {}", output_path, e)))?;
+
+        let interface_path = unit::interface_path(&source, out_dir);
+        std::fs::write(&interface_path, unit::write_interface(&ast, &source, &imports))
+            .map_err(|e| error::cayError::Io(format!("无法写入接口文件 '{}': {}", interface_path.display(), e)))?;
+        Ok(interface_path)
+    }
+
+    /// 按编译选项创建语义分析器（语言特性和目标平台）；不支持的目标三元组在这里报错
+    fn semantic_analyzer(&self) -> cayResult<semantic::SemanticAnalyzer> {
+        let target = self.options.target_info().map_err(error::codegen_error)?;
+        let mut analyzer = semantic::SemanticAnalyzer::with_features(self.options.features.clone());
+        analyzer.set_target(target);
+        Ok(analyzer)
+    }
+
+    /// AST → LLVM IR 文本
+    ///
+    /// 默认经 `IrBuilder` 构建 `IrModule`，验证后运行默认 pass 流水线再由 `LlvmBackend` 输出；
+    /// `legacy_codegen` 时使用旧的字符串代码生成器。`debug_info` 时附带 DWARF 调试元数据。
+    /// `imports` 是分离编译时依赖单元的接口声明。
+    fn generate_llvm_ir(
+        &self,
+        ast: &ast::Program,
+        registry: &types::TypeRegistry,
+        source_map: Option<std::collections::HashMap<usize, (String, usize)>>,
+        source_file: &str,
+        imports: Option<ast::Program>,
+    ) -> cayResult<String> {
+        if self.options.legacy_codegen {
+            if self.options.crate_type.is_library() {
+                return Err(error::codegen_error("库输出（--crate-type=staticlib/cdylib）不支持 --legacy-codegen"));
+            }
+            let mut ir_gen = codegen::IRGenerator::new();
+            ir_gen.set_platform_config(&self.options);
+            ir_gen.set_type_registry(registry.clone());
+            if let Some(source_map) = source_map {
+                ir_gen.set_preprocessor_source_map(source_map);
+            }
+            let ir = ir_gen.generate(ast, source_file)?;
+            if self.options.print_devirt {
+                report_devirtualized_calls(&ir_gen.devirtualized_calls);
+            }
+            // 旧代码生成器只有源映射注释，调试信息仅含函数和行号，
+            // 不生成 DILocalVariable / DICompositeType（局部变量和类类型）
+            return Ok(if self.options.debug_info { ir::debug_info::add_line_info(&ir) } else { ir });
+        }
+
+        let mut module = self.build_ir_module(ast, registry, source_map, source_file, imports)?;
+        let result = ir::IrVerifier::new().verify(&module);
+        if !result.is_valid {
+            return Err(error::codegen_error(format!("IR verification failed: {}", result.errors.join("; "))));
+        }
+        if self.options.debug_info {
+            // 保留局部变量的 alloca，使调试器能查看变量
+            ir::PassManager::from_names(ir::passes::DEBUG_PIPELINE)?.run(&mut module)?;
+        } else {
+            ir::PassManager::default_pipeline().run(&mut module)?;
+        }
+        let mut backend = ir::LlvmBackend::new();
+        backend.set_debug_info(self.options.debug_info);
+        backend.emit(&module)
+    }
+
+    /// AST → `IrModule`
+    fn build_ir_module(
+        &self,
+        ast: &ast::Program,
+        registry: &types::TypeRegistry,
+        source_map: Option<std::collections::HashMap<usize, (String, usize)>>,
+        source_file: &str,
+        imports: Option<ast::Program>,
+    ) -> cayResult<ir::IrModule> {
+        let mut builder = ir::IrBuilder::new();
+        builder.set_platform_config(&self.options);
+        builder.set_debug_info(self.options.debug_info);
+        builder.set_type_registry(registry.clone());
+        if self.options.crate_type.is_library() {
+            // 分离编译时依赖单元中的函数也可以导出
+            let mut all = imports.clone().unwrap_or_default();
+            all.append(ast.clone());
+            builder.set_library_exports(cabi::collect_exports(&all, &self.options.exports)?);
+        }
+        if let Some(imports) = imports {
+            builder.set_imports(imports);
+        }
+        if let Some(source_map) = source_map {
+            builder.set_preprocessor_source_map(source_map);
+        }
+        let module = builder.build_from_ast_with_source(ast, source_file)?;
+        if self.options.print_devirt {
+            report_devirtualized_calls(builder.devirtualized_calls());
+        }
+        if self.options.escape_remarks {
+            for remark in builder.escape_remarks() {
+                eprintln!("{}", remark);
+            }
+        }
+        Ok(module)
+    }
+
+    /// 为库输出生成 C 头文件（见 [`cabi`]），声明 `export` 标记和 `exports` 列出的函数
+    pub fn write_c_header(&self, input_path: &str, header_path: &str) -> cayResult<()> {
+        let (code, source_map) = self.preprocess_file(input_path)?;
+        let tokens = lexer::lex_with_source_map(&code, source_map)?;
+        let ast = parser::parse_with_source(tokens, code)?;
+        self.write_c_header_for(&ast, header_path)
+    }
+
+    /// 为多个源文件组成的库生成 C 头文件（见 [`Compiler::compile_files`]）
+    pub fn write_c_header_files(&self, input_paths: &[String], header_path: &str) -> cayResult<()> {
+        match input_paths {
+            [] => Err(error::cayError::Io("没有输入文件".to_string())),
+            [input] => self.write_c_header(input, header_path),
+            _ => {
+                let (ast, _) = self.parse_files(input_paths)?;
+                self.write_c_header_for(&ast, header_path)
+            }
+        }
+    }
+
+    fn write_c_header_for(&self, ast: &ast::Program, header_path: &str) -> cayResult<()> {
+        let exports = cabi::collect_exports(ast, &self.options.exports)?;
+        let header_name = Path::new(header_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| header_path.to_string());
+        std::fs::write(header_path, cabi::write_header(&exports, &header_name))
+            .map_err(|e| error::cayError::Io(format!("无法写入头文件 '{}': {}", header_path, e)))
+    }
+
+    /// 读取并预处理源文件，返回预处理后的代码和源映射
+    fn preprocess_file(&self, input_path: &str) -> cayResult<PreprocessedSource> {
+        let mut pp = self.create_preprocessor(input_path);
+        self.run_preprocessor(&mut pp, input_path)
+    }
+
+    /// 用 `pp` 预处理源文件
+    fn run_preprocessor(&self, pp: &mut preprocessor::Preprocessor, input_path: &str) -> cayResult<PreprocessedSource> {
         // 读取源文件
         let source = std::fs::read_to_string(input_path)
             .map_err(|e| error::cayError::Io(
                 format!("无法读取源文件 '{}': {}", input_path, e)
             ))?;
+        let result = pp.process_with_source_map(&source, input_path)?;
+
+        let source_map = Self::convert_source_map(&result.source_map);
+        Ok((result.code, source_map))
+    }
 
+    /// 创建预处理器：以源文件所在目录为基础目录，系统包含路径为 caylibs 和 `-I` 指定的路径
+    fn create_preprocessor(&self, input_path: &str) -> preprocessor::Preprocessor {
         // 获取基础目录（用于解析相对路径的 #include）
         let base_dir = Path::new(input_path)
             .parent()
@@ -244,19 +598,23 @@ impl Compiler {
         }
 
         // 使用带系统路径的预处理器（带源映射）
-
         let mut pp = if system_paths.is_empty() {
             preprocessor::Preprocessor::new(base_dir)
         } else {
             preprocessor::Preprocessor::with_include_paths(base_dir, system_paths)
         };
-        let result = pp.process_with_source_map(&source, input_path)?;
-
-        let source_map = Self::convert_source_map(&result.source_map);
 
-        // 编译预处理后的代码（带源映射和主文件路径）
-        let main_file = Some(input_path.to_string());
-        self.compile_with_source_map_and_main_file(&result.code, source_map, output_path, main_file)
+        // 命令行宏定义（-D NAME 或 -D NAME=VALUE），-U 在定义之后生效
+        for define in &self.options.defines {
+            match define.split_once('=') {
+                Some((name, value)) => pp.define(name, value),
+                None => pp.define(define, ""),
+            }
+        }
+        for undefine in &self.options.undefines {
+            pp.undefine(undefine);
+        }
+        pp
     }
 
     /// 将预处理器源映射转换为HashMap格式
@@ -307,6 +665,27 @@ mod tests {
         println!("AST: {:?}", ast);
     }
 
+    #[test]
+    fn test_package_import_parser() {
+        let source = r#"package app.core;
+import util.MathUtil;
+import geometry.*;
+
+public class Main {
+}
+"#;
+        let tokens = lexer::lex(source).unwrap();
+        let ast = parser::parse(tokens).unwrap();
+        let unit = &ast.units[0];
+        assert_eq!(unit.package_name(), Some("app.core"));
+        let imports: Vec<String> = unit.imports.iter().map(|i| i.path()).collect();
+        assert_eq!(imports, ["util.MathUtil", "geometry.*"]);
+        assert_eq!(unit.classes, ["Main"]);
+
+        let misplaced = "public class Main {\n}\nimport util.MathUtil;\n";
+        assert!(parser::parse(lexer::lex(misplaced).unwrap()).is_err());
+    }
+
     #[test]
     fn test_preprocessor_define() {
         let source = r#"
@@ -8,7 +8,8 @@ use crate::error::SourceLocation;
 use super::Parser;
 use super::types::{parse_type, is_type_token};
 use super::expressions::parse_expression;
-use super::statements::{parse_block, parse_statement};
+use super::statements::{parse_block, parse_statement_recovering};
+use super::SyncLevel;
 
 /// 解析类声明
 pub fn parse_class(parser: &mut Parser) -> cayResult<ClassDecl> {
@@ -47,10 +48,20 @@ pub fn parse_class(parser: &mut Parser) -> cayResult<ClassDecl> {
 
     let mut members = Vec::new();
     while !parser.check(&Token::RBrace) && !parser.is_at_end() {
-        members.push(parse_class_member(parser)?);
+        let start_pos = parser.pos;
+        match parse_class_member(parser) {
+            Ok(member) => members.push(member),
+            Err(err) => {
+                parser.record_error(err);
+                parser.synchronize(start_pos, SyncLevel::Member);
+            }
+        }
     }
 
-    parser.consume(&Token::RBrace, "期望 '}'\n提示: 类体应以 '}' 结束")?;
+    // 文件提前结束时仍保留已解析的成员，便于大纲和补全
+    if let Err(err) = parser.consume(&Token::RBrace, "期望 '}'\n提示: 类体应以 '}' 结束") {
+        parser.record_error(err);
+    }
 
     Ok(ClassDecl {
         name,
@@ -73,28 +84,55 @@ pub fn parse_interface(parser: &mut Parser) -> cayResult<InterfaceDecl> {
 
     let name = parser.consume_identifier("期望接口名\n提示: 在 'interface' 后应跟接口名，例如: interface MyInterface { ... }")?;
 
+    // 解析继承的父接口
+    let mut parents = Vec::new();
+    if parser.match_token(&Token::Extends) {
+        loop {
+            let parent_name = parser.consume_identifier("期望父接口名\n提示: 在 'extends' 后应跟接口名，例如: interface Shape extends Drawable, Named { ... }")?;
+            parents.push(parent_name);
+            if !parser.match_token(&Token::Comma) {
+                break;
+            }
+        }
+    }
+
     parser.consume(&Token::LBrace, "期望 '{'\n提示: 接口声明后应跟接口体，使用 '{' 开始，例如: interface MyInterface { ... }")?;
 
-    // 接口只能包含方法声明（没有方法体）
+    // 接口包含抽象方法声明和 default 默认方法
     let mut methods = Vec::new();
     while !parser.check(&Token::RBrace) && !parser.is_at_end() {
-        methods.push(parse_interface_method(parser)?);
+        let start_pos = parser.pos;
+        match parse_interface_method(parser) {
+            Ok(method) => methods.push(method),
+            Err(err) => {
+                parser.record_error(err);
+                parser.synchronize(start_pos, SyncLevel::Member);
+            }
+        }
     }
 
-    parser.consume(&Token::RBrace, "期望 '}'\n提示: 接口体应以 '}' 结束")?;
+    if let Err(err) = parser.consume(&Token::RBrace, "期望 '}'\n提示: 接口体应以 '}' 结束") {
+        parser.record_error(err);
+    }
 
     Ok(InterfaceDecl {
         name,
         modifiers,
+        parents,
         methods,
         loc,
     })
 }
 
-/// 解析接口方法（只有声明，没有实现）
+/// 解析接口方法（抽象声明，或带方法体的 default 方法）
 fn parse_interface_method(parser: &mut Parser) -> cayResult<MethodDecl> {
     let loc = parser.current_loc();
-    let modifiers = parse_modifiers(parser)?;
+    let mut modifiers = parse_modifiers(parser)?;
+    if parser.match_token(&Token::Default) {
+        modifiers.push(Modifier::Default);
+        // 允许 default 后继续出现 public 等修饰符
+        modifiers.extend(parse_modifiers(parser)?);
+    }
 
     let return_type = if parser.check(&Token::Void) {
         parser.advance();
@@ -109,15 +147,29 @@ fn parse_interface_method(parser: &mut Parser) -> cayResult<MethodDecl> {
     let params = parse_parameters(parser)?;
     parser.consume(&Token::RParen, "期望 ')'\n提示: 参数列表应以 ')' 结束")?;
 
-    // 接口方法必须以分号结束，没有方法体
-    parser.consume(&Token::Semicolon, "期望 ';'\n提示: 接口方法声明应以 ';' 结束，例如: int calculate();")?;
+    // default 方法必须有方法体，其余接口方法以分号结束
+    let body = if parser.is_interface_file() && parser.check(&Token::Semicolon) {
+        parser.advance();
+        None
+    } else if modifiers.contains(&Modifier::Default) {
+        if parser.check(&Token::Semicolon) {
+            return Err(parser.error(&format!("default 方法 '{}' 缺少方法体\n提示: default 方法需要提供默认实现，例如: default int calculate() {{ return 0; }}", name)));
+        }
+        Some(parse_block(parser)?)
+    } else {
+        if parser.check(&Token::LBrace) {
+            return Err(parser.error(&format!("接口方法 '{}' 不能有方法体\n提示: 如需提供默认实现，请使用 default 修饰，例如: default int calculate() {{ return 0; }}", name)));
+        }
+        parser.consume(&Token::Semicolon, "期望 ';'\n提示: 接口方法声明应以 ';' 结束，例如: int calculate();")?;
+        None
+    };
 
     Ok(MethodDecl {
         name,
         modifiers,
         return_type,
         params,
-        body: None,  // 接口方法没有方法体
+        body,
         loc,
     })
 }
@@ -171,6 +223,17 @@ pub fn parse_class_member(parser: &mut Parser) -> cayResult<ClassMember> {
             parser.consume(&Token::LParen, "Expected '(' after constructor name")?;
             let ctor_params = parse_parameters(parser)?;
             parser.consume(&Token::RParen, "Expected ')' after constructor parameters")?;
+
+            // 接口文件中只有签名
+            if parser.is_interface_file() && parser.match_token(&Token::Semicolon) {
+                return Ok(ClassMember::Constructor(ConstructorDecl {
+                    modifiers: ctor_modifiers,
+                    params: ctor_params,
+                    body: Block { statements: Vec::new(), loc: loc.clone() },
+                    constructor_call: None,
+                    loc,
+                }));
+            }
             
             // 解析构造链调用 this() 或 super()
             let ctor_call_result = parse_constructor_call(parser)?;
@@ -182,7 +245,7 @@ pub fn parse_class_member(parser: &mut Parser) -> cayResult<ClassMember> {
                 // 已经消耗了 {，直接解析语句直到 }
                 let mut statements = Vec::new();
                 while !parser.check(&Token::RBrace) && !parser.is_at_end() {
-                    statements.push(parse_statement(parser)?);
+                    statements.push(parse_statement_recovering(parser));
                 }
                 parser.consume(&Token::RBrace, "期望 '}'\n提示: 构造函数体应以 '}' 结束")?;
                 Block { statements, loc: parser.current_loc() }
@@ -360,7 +423,10 @@ pub fn parse_method(parser: &mut Parser) -> cayResult<MethodDecl> {
     let is_native = modifiers.contains(&Modifier::Native);
     let is_abstract = modifiers.contains(&Modifier::Abstract);
 
-    let body = if is_native || is_abstract {
+    let body = if parser.is_interface_file() && parser.check(&Token::Semicolon) {
+        parser.advance();
+        None
+    } else if is_native || is_abstract {
         parser.consume(&Token::Semicolon, "期望 ';'\n提示: native/abstract 方法声明应以 ';' 结束，例如: native int foo();")?;
         None
     } else {
@@ -391,6 +457,17 @@ pub fn parse_constructor(parser: &mut Parser) -> cayResult<ConstructorDecl> {
     parser.consume(&Token::LParen, "期望 '('\n提示: 构造函数名后应跟 '(' 开始参数列表，例如: MyClass() { ... }")?;
     let params = parse_parameters(parser)?;
     parser.consume(&Token::RParen, "期望 ')'\n提示: 参数列表应以 ')' 结束")?;
+
+    // 接口文件中只有签名
+    if parser.is_interface_file() && parser.match_token(&Token::Semicolon) {
+        return Ok(ConstructorDecl {
+            modifiers,
+            params,
+            body: Block { statements: Vec::new(), loc: loc.clone() },
+            constructor_call: None,
+            loc,
+        });
+    }
     
     // 解析构造链调用 this() 或 super()
     let ctor_call_result = parse_constructor_call(parser)?;
@@ -402,7 +479,7 @@ pub fn parse_constructor(parser: &mut Parser) -> cayResult<ConstructorDecl> {
         // 已经消耗了 {，直接解析语句直到 }
         let mut statements = Vec::new();
         while !parser.check(&Token::RBrace) && !parser.is_at_end() {
-            statements.push(parse_statement(parser)?);
+            statements.push(parse_statement_recovering(parser));
         }
         parser.consume(&Token::RBrace, "Expected '}' after constructor body")?;
         Block { statements, loc: parser.current_loc() }
@@ -540,8 +617,13 @@ pub fn parse_destructor(parser: &mut Parser) -> cayResult<DestructorDecl> {
     parser.consume(&Token::LParen, "期望 '('\n提示: 析构函数名后应跟 '()'，例如: ~MyClass()")?;
     parser.consume(&Token::RParen, "期望 ')'\n提示: 析构函数不接受参数")?;
     
-    // 解析析构函数体
-    let body = parse_block(parser)?;
+    // 解析析构函数体（接口文件中只有签名）
+    let body = if parser.is_interface_file() && parser.check(&Token::Semicolon) {
+        parser.advance();
+        Block { statements: Vec::new(), loc: loc.clone() }
+    } else {
+        parse_block(parser)?
+    };
     
     Ok(DestructorDecl {
         modifiers,
@@ -597,10 +679,18 @@ pub fn parse_modifiers(parser: &mut Parser) -> cayResult<Vec<Modifier>> {
                 modifiers.push(Modifier::Native);
                 parser.advance();
             }
+            Token::Export => {
+                modifiers.push(Modifier::Export);
+                parser.advance();
+            }
             Token::AtOverride => {
                 modifiers.push(Modifier::Override);
                 parser.advance();
             }
+            Token::AtTest => {
+                modifiers.push(Modifier::Test);
+                parser.advance();
+            }
             Token::AtMain => {
                 modifiers.push(Modifier::Main);
                 parser.advance();
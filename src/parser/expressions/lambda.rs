@@ -6,7 +6,7 @@ use crate::ast::*;
 use crate::error::cayResult;
 use super::super::Parser;
 use super::super::types::{parse_type, is_type_token};
-use super::super::statements::parse_statement;
+use super::super::statements::parse_statement_recovering;
 use super::assignment::parse_expression;
 
 /// 尝试解析 Lambda 表达式
@@ -123,9 +123,8 @@ fn parse_lambda_param(parser: &mut Parser) -> cayResult<LambdaParam> {
 fn parse_lambda_block(parser: &mut Parser) -> cayResult<Block> {
     let mut statements = Vec::new();
 
-    while !parser.check(&crate::lexer::Token::RBrace) {
-        let stmt = parse_statement(parser)?;
-        statements.push(stmt);
+    while !parser.check(&crate::lexer::Token::RBrace) && !parser.is_at_end() {
+        statements.push(parse_statement_recovering(parser));
     }
 
     parser.consume(&crate::lexer::Token::RBrace, "期望 '}'\n提示: Lambda 代码块应以 '}' 结束")?;
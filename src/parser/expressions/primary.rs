@@ -6,7 +6,7 @@ use crate::ast::*;
 use crate::types::Type;
 use crate::error::cayResult;
 use super::super::Parser;
-use super::super::types::is_type_token;
+use super::super::types::{is_type_token, parse_type};
 use super::lambda::try_parse_lambda;
 use super::assignment::parse_expression;
 
@@ -114,10 +114,18 @@ pub fn parse_primary(parser: &mut Parser) -> cayResult<Expr> {
             parser.advance();
             parse_new_expression(parser, loc)
         }
+        crate::lexer::Token::SizeOf => {
+            parser.advance();
+            parser.consume(&crate::lexer::Token::LParen, "期望 '('\n提示: sizeof 的用法为 sizeof(类型)，例如: sizeof(c_long)")?;
+            let target_type = parse_type(parser)?;
+            parser.consume(&crate::lexer::Token::RParen, "期望 ')'\n提示: sizeof 的用法为 sizeof(类型)，例如: sizeof(c_long)")?;
+            Ok(Expr::SizeOf(SizeOfExpr { target_type, loc }))
+        }
         crate::lexer::Token::LParen => {
             // 检查是否是 Lambda 表达式: (params) -> { body }
             // 需要向前看，检查是否有 -> 箭头
             let checkpoint = parser.pos;
+            let error_mark = parser.errors.len();
             parser.advance(); // 跳过 '('
 
             // 尝试解析 Lambda 参数列表
@@ -125,8 +133,9 @@ pub fn parse_primary(parser: &mut Parser) -> cayResult<Expr> {
                 return Ok(lambda_expr);
             }
 
-            // 不是 Lambda，回退并解析普通括号表达式
+            // 不是 Lambda，回退并解析普通括号表达式（丢弃试探解析中记录的错误）
             parser.pos = checkpoint;
+            parser.errors.truncate(error_mark);
             parser.advance(); // 跳过 '('
             let expr = parse_expression(parser)?;
             parser.consume(&crate::lexer::Token::RParen, "期望 ')'\n提示: 括号表达式应以 ')' 结束，例如: (x + y)")?;
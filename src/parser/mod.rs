@@ -11,9 +11,40 @@ mod utils;
 
 use crate::lexer::TokenWithLocation;
 use crate::ast::Program;
-use crate::error::cayResult;
+use crate::error::{cayError, cayResult};
 use crate::diagnostic::DiagnosticCollector;
 
+pub use utils::SyncLevel;
+
+/// 带错误恢复的解析结果
+///
+/// 即使源码中存在语法错误，也会返回尽可能完整的 `Program`：
+/// 出错的语句被替换为 `Stmt::Error` 节点，无法恢复的成员和声明被跳过。
+#[derive(Debug, Clone)]
+pub struct ParseOutput {
+    /// 部分（或完整）的语法树
+    pub program: Program,
+    /// 按出现顺序收集的全部语法错误
+    pub errors: Vec<cayError>,
+    /// 解析过程中产生的诊断信息
+    pub diagnostics: DiagnosticCollector,
+}
+
+impl ParseOutput {
+    /// 是否存在语法错误
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// 转换为单一结果：存在错误时返回第一个错误
+    pub fn into_result(self) -> cayResult<Program> {
+        match self.errors.into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(self.program),
+        }
+    }
+}
+
 /// 语法分析器
 pub struct Parser {
     /// 令牌流
@@ -22,10 +53,14 @@ pub struct Parser {
     pub pos: usize,
     /// 诊断收集器
     pub diagnostics: DiagnosticCollector,
+    /// 错误恢复过程中收集的语法错误
+    pub errors: Vec<cayError>,
     /// 源代码文本（用于内联IR等需要直接访问源码的场景）
     source: Option<String>,
     /// 类型别名映射: 别名名称 -> 目标类型
     type_aliases: std::collections::HashMap<String, crate::types::Type>,
+    /// 是否在解析接口文件（.cai）：函数、构造函数和析构函数可以用 ';' 代替函数体
+    interface_file: bool,
 }
 
 impl Parser {
@@ -35,8 +70,10 @@ impl Parser {
             tokens, 
             pos: 0,
             diagnostics: DiagnosticCollector::new(),
+            errors: Vec::new(),
             source: None,
             type_aliases: std::collections::HashMap::new(),
+            interface_file: false,
         }
     }
 
@@ -46,11 +83,23 @@ impl Parser {
             tokens, 
             pos: 0,
             diagnostics: DiagnosticCollector::new(),
+            errors: Vec::new(),
             source: Some(source),
             type_aliases: std::collections::HashMap::new(),
+            interface_file: false,
         }
     }
 
+    /// 创建解析接口文件（.cai）的语法分析器
+    pub fn for_interface_file(tokens: Vec<TokenWithLocation>) -> Self {
+        Self { interface_file: true, ..Self::new(tokens) }
+    }
+
+    /// 是否在解析接口文件
+    pub fn is_interface_file(&self) -> bool {
+        self.interface_file
+    }
+
     /// 获取源代码
     pub fn source(&self) -> Option<&str> {
         self.source.as_deref()
@@ -61,116 +110,227 @@ impl Parser {
         &self.diagnostics
     }
 
-    /// 解析整个程序
+    /// 获取已收集的语法错误
+    pub fn errors(&self) -> &[cayError] {
+        &self.errors
+    }
+
+    /// 记录一个语法错误（用于错误恢复，解析继续进行）
+    pub fn record_error(&mut self, error: cayError) {
+        self.errors.push(error);
+    }
+
+    /// 同步到指定层级的下一个边界
+    pub fn synchronize(&mut self, start_pos: usize, level: SyncLevel) {
+        utils::synchronize(self, start_pos, level)
+    }
+
+    /// 解析整个程序，遇到第一个语法错误时返回该错误
     pub fn parse(&mut self) -> cayResult<Program> {
-        let mut classes = Vec::new();
-        let mut interfaces = Vec::new();
-        let mut top_level_functions = Vec::new();
-        let mut extern_declarations = Vec::new();
-        let mut type_aliases = Vec::new();
+        let program = self.parse_program();
+        match self.errors.first() {
+            Some(err) => Err(err.clone()),
+            None => Ok(program),
+        }
+    }
 
-        while !self.is_at_end() {
-            if self.check(&crate::lexer::Token::Interface)
-                || (self.check(&crate::lexer::Token::Public) && self.check_next(&crate::lexer::Token::Interface))
-            {
-                interfaces.push(self.parse_interface()?);
-            } else if self.check(&crate::lexer::Token::Class)
-                || self.check(&crate::lexer::Token::Private)
-                || self.check(&crate::lexer::Token::Protected)
-                || self.check(&crate::lexer::Token::AtMain)
-            {
-                classes.push(self.parse_class()?);
-            } else if self.check(&crate::lexer::Token::Public) {
-                // 检查是否是顶层函数: public 返回类型 函数名()
-                if self.check_top_level_function() {
-                    top_level_functions.push(self.parse_top_level_function()?);
-                } else {
-                    // 否则可能是 public class
-                    classes.push(self.parse_class()?);
+    /// 解析整个程序并在语法错误后继续，返回部分语法树和全部错误
+    pub fn parse_with_recovery(mut self) -> ParseOutput {
+        let program = self.parse_program();
+        ParseOutput {
+            program,
+            errors: self.errors,
+            diagnostics: self.diagnostics,
+        }
+    }
+
+    /// 解析整个程序（panic-mode 错误恢复，错误记录到 `self.errors`）
+    fn parse_program(&mut self) -> Program {
+        let mut program = Program::default();
+        let file = self.tokens.first()
+            .and_then(|t| t.source_file.clone())
+            .unwrap_or_default();
+        let mut unit = crate::ast::SourceUnit { file, ..Default::default() };
+
+        // 文件开头的 package 声明和 import 声明
+        if self.check(&crate::lexer::Token::Package) {
+            let start_pos = self.pos;
+            match self.parse_package_decl() {
+                Ok(package) => unit.package = Some(package),
+                Err(err) => {
+                    self.record_error(err);
+                    self.synchronize(start_pos, SyncLevel::TopLevel);
                 }
-            } else if self.check_top_level_function_return_type() {
-                // 没有 public 修饰符的顶层函数
-                top_level_functions.push(self.parse_top_level_function_without_public()?);
-            } else if self.check(&crate::lexer::Token::Extern) {
-                extern_declarations.push(self.parse_extern_declaration()?);
-            } else if self.check(&crate::lexer::Token::Alias) {
-                type_aliases.push(self.parse_type_alias()?);
+            }
+        }
+        while self.check(&crate::lexer::Token::Import) {
+            let start_pos = self.pos;
+            match self.parse_import_decl() {
+                Ok(import) => unit.imports.push(import),
+                Err(err) => {
+                    self.record_error(err);
+                    self.synchronize(start_pos, SyncLevel::TopLevel);
+                }
+            }
+        }
+
+        while !self.is_at_end() {
+            let start_pos = self.pos;
+            if let Err(err) = self.parse_top_level_decl(&mut program) {
+                self.record_error(err);
+                self.synchronize(start_pos, SyncLevel::TopLevel);
+            }
+        }
+
+        unit.classes = program.classes.iter().map(|c| c.name.clone()).collect();
+        unit.interfaces = program.interfaces.iter().map(|i| i.name.clone()).collect();
+        unit.functions = program.top_level_functions.iter().map(|f| f.name.clone()).collect();
+        program.units.push(unit);
+        program
+    }
+
+    /// 解析包声明: package a.b;
+    fn parse_package_decl(&mut self) -> cayResult<crate::ast::PackageDecl> {
+        let loc = self.current_loc();
+        self.consume(&crate::lexer::Token::Package, "期望 'package'")?;
+        let mut name = self.consume_identifier("期望包名\n提示: 包声明格式为 package a.b;")?;
+        while self.match_token(&crate::lexer::Token::Dot) {
+            name.push('.');
+            name.push_str(&self.consume_identifier("期望包名\n提示: 包声明格式为 package a.b;")?);
+        }
+        self.consume(&crate::lexer::Token::Semicolon, "期望 ';'\n提示: 包声明应以 ';' 结束")?;
+        Ok(crate::ast::PackageDecl { name, loc })
+    }
+
+    /// 解析导入声明: import a.b.Name; 或 import a.b.*;
+    fn parse_import_decl(&mut self) -> cayResult<crate::ast::ImportDecl> {
+        let loc = self.current_loc();
+        self.consume(&crate::lexer::Token::Import, "期望 'import'")?;
+        let mut segments = vec![self.consume_identifier("期望包名\n提示: 导入格式为 import a.b.Name; 或 import a.b.*;")?];
+        let mut wildcard = false;
+        while self.match_token(&crate::lexer::Token::Dot) {
+            if self.match_token(&crate::lexer::Token::Star) {
+                wildcard = true;
+                break;
+            }
+            segments.push(self.consume_identifier("期望名称或 '*'\n提示: 导入格式为 import a.b.Name; 或 import a.b.*;")?);
+        }
+        self.consume(&crate::lexer::Token::Semicolon, "期望 ';'\n提示: 导入声明应以 ';' 结束")?;
+        let name = if wildcard { None } else { segments.pop() };
+        if segments.is_empty() {
+            return Err(self.error("导入声明缺少包名\n提示: 导入格式为 import a.b.Name; 或 import a.b.*;"));
+        }
+        Ok(crate::ast::ImportDecl { package: segments.join("."), name, loc })
+    }
+
+    /// 解析单个顶层声明并加入程序
+    fn parse_top_level_decl(&mut self, program: &mut Program) -> cayResult<()> {
+        if self.check(&crate::lexer::Token::Interface)
+            || (self.check(&crate::lexer::Token::Public) && self.check_next(&crate::lexer::Token::Interface))
+        {
+            program.interfaces.push(self.parse_interface()?);
+        } else if self.check(&crate::lexer::Token::Export)
+            || (self.check(&crate::lexer::Token::Public) && self.check_next(&crate::lexer::Token::Export))
+        {
+            program.top_level_functions.push(self.parse_exported_top_level_function()?);
+        } else if self.check(&crate::lexer::Token::Class)
+            || self.check(&crate::lexer::Token::Private)
+            || self.check(&crate::lexer::Token::Protected)
+            || self.check(&crate::lexer::Token::AtMain)
+        {
+            program.classes.push(self.parse_class()?);
+        } else if self.check(&crate::lexer::Token::Public) {
+            // 检查是否是顶层函数: public 返回类型 函数名()
+            if self.check_top_level_function() {
+                program.top_level_functions.push(self.parse_top_level_function()?);
             } else {
-                let current_token = utils::current_token(self);
-                let (token_desc, suggestion) = match current_token {
-                    crate::lexer::Token::Semicolon => (
-                        "分号(;)".to_string(),
-                        "顶层声明不能是空语句。可能的问题:\n    - 多余的逗号或分号\n    - 缺少声明内容".to_string()
-                    ),
-                    crate::lexer::Token::LBrace => (
-                        "左花括号({)".to_string(),
-                        "顶层声明不能以代码块开始。可能的问题:\n    - 缺少类或函数声明\n    - 代码块应在函数或方法体内".to_string()
-                    ),
-                    crate::lexer::Token::RBrace => (
-                        "右花括号(})".to_string(),
-                        "文件提前结束或多余的右花括号。可能的问题:\n    - 前面的声明缺少匹配的左花括号\n    - 多余的右花括号".to_string()
-                    ),
-                    crate::lexer::Token::LParen => (
-                        "左圆括号(()".to_string(),
-                        "顶层声明不能以括号开始。可能的问题:\n    - 缺少函数声明\n    - Lambda 表达式不能作为顶层声明".to_string()
-                    ),
-                    crate::lexer::Token::If | crate::lexer::Token::While |
-                    crate::lexer::Token::For | crate::lexer::Token::Do |
-                    crate::lexer::Token::Switch | crate::lexer::Token::Return |
-                    crate::lexer::Token::Break | crate::lexer::Token::Continue => {
-                        let kw = format!("{:?}", current_token).to_lowercase();
-                        (
-                            format!("关键字({})", kw),
-                            format!("{} 是控制流语句，不能作为顶层声明。可能的问题:\n    - 控制流语句只能在函数或方法体内使用\n    - 缺少函数声明", kw)
-                        )
-                    }
-                    crate::lexer::Token::Int | crate::lexer::Token::Long |
-                    crate::lexer::Token::Float | crate::lexer::Token::Double |
-                    crate::lexer::Token::Bool | crate::lexer::Token::Char |
-                    crate::lexer::Token::String => {
-                        let kw = format!("{:?}", current_token).to_lowercase();
-                        (
-                            format!("关键字({})", kw),
-                            format!("类型 '{}' 不能单独作为顶层声明。可能的问题:\n    - 缺少变量或函数声明，如: {} x; 或 {} main() {{ ... }}\n    - 类型后缺少标识符", kw, kw, kw)
-                        )
-                    }
-                    crate::lexer::Token::Identifier(name) => (
-                        format!("标识符('{}')", name),
-                        format!("'{}' 不能作为顶层声明开始。可能的问题:\n    - 需要先声明类或函数\n    - 语句位置错误，应在函数体内\n    - 如果是方法调用，需要在函数或 main 函数中执行", name)
-                    ),
-                    crate::lexer::Token::IntegerLiteral(Some((val, _))) => (
-                        format!("整数({})", val),
-                        "整数字面量不能作为顶层声明。可能的问题:\n    - 缺少变量声明，如: int x = 10;\n    - 语句位置错误，应在函数体内".to_string()
-                    ),
-                    crate::lexer::Token::StringLiteral(Some(s)) => (
-                        format!("字符串(\"{}\")", s),
-                        "字符串字面量不能作为顶层声明。可能的问题:\n    - 缺少变量声明，如: String s = \"hello\";\n    - 语句位置错误，应在函数体内".to_string()
-                    ),
-                    crate::lexer::Token::Private | crate::lexer::Token::Protected |
-                    crate::lexer::Token::Static | crate::lexer::Token::Final |
-                    crate::lexer::Token::Abstract => {
-                        let kw = format!("{:?}", current_token).to_lowercase();
-                        (
-                            format!("关键字({})", kw),
-                            format!("修饰符 '{}' 不能单独作为顶层声明。可能的问题:\n    - 修饰符后缺少类或函数声明\n    - 顶层声明应以 class、interface 或 public 开始", kw)
-                        )
-                    }
-                    _ => {
-                        let token_name = utils::get_token_name(current_token);
-                        (
-                            token_name.clone(),
-                            format!("{} 不能作为顶层声明。有效的顶层声明包括:\n    - 类: class MyClass {{ ... }}\n    - 接口: interface MyInterface {{ ... }}\n    - 外部函数: extern {{ ... }}\n    - 类型别名: type MyType = int;\n    - 主函数: public int main() {{ ... }}", token_name)
-                        )
-                    }
-                };
-                return Err(self.error(&format!(
-                    "期望类、接口、extern 声明或顶层函数声明，但遇到了 {}\n提示: {}",
-                    token_desc, suggestion
-                )));
+                // 否则可能是 public class
+                program.classes.push(self.parse_class()?);
             }
+        } else if self.check_top_level_function_return_type() {
+            // 没有 public 修饰符的顶层函数
+            program.top_level_functions.push(self.parse_top_level_function_without_public()?);
+        } else if self.check(&crate::lexer::Token::Extern) {
+            program.extern_declarations.push(self.parse_extern_declaration()?);
+        } else if self.check(&crate::lexer::Token::Alias) {
+            program.type_aliases.push(self.parse_type_alias()?);
+        } else if self.check(&crate::lexer::Token::Package) || self.check(&crate::lexer::Token::Import) {
+            return Err(self.error("package 和 import 声明必须位于文件开头\n提示: 先写 package 声明，再写 import 声明，最后是类、接口和函数"));
+        } else {
+            let current_token = utils::current_token(self);
+            let (token_desc, suggestion) = match current_token {
+                crate::lexer::Token::Semicolon => (
+                    "分号(;)".to_string(),
+                    "顶层声明不能是空语句。可能的问题:\n    - 多余的逗号或分号\n    - 缺少声明内容".to_string()
+                ),
+                crate::lexer::Token::LBrace => (
+                    "左花括号({)".to_string(),
+                    "顶层声明不能以代码块开始。可能的问题:\n    - 缺少类或函数声明\n    - 代码块应在函数或方法体内".to_string()
+                ),
+                crate::lexer::Token::RBrace => (
+                    "右花括号(})".to_string(),
+                    "文件提前结束或多余的右花括号。可能的问题:\n    - 前面的声明缺少匹配的左花括号\n    - 多余的右花括号".to_string()
+                ),
+                crate::lexer::Token::LParen => (
+                    "左圆括号(()".to_string(),
+                    "顶层声明不能以括号开始。可能的问题:\n    - 缺少函数声明\n    - Lambda 表达式不能作为顶层声明".to_string()
+                ),
+                crate::lexer::Token::If | crate::lexer::Token::While |
+                crate::lexer::Token::For | crate::lexer::Token::Do |
+                crate::lexer::Token::Switch | crate::lexer::Token::Return |
+                crate::lexer::Token::Break | crate::lexer::Token::Continue => {
+                    let kw = format!("{:?}", current_token).to_lowercase();
+                    (
+                        format!("关键字({})", kw),
+                        format!("{} 是控制流语句，不能作为顶层声明。可能的问题:\n    - 控制流语句只能在函数或方法体内使用\n    - 缺少函数声明", kw)
+                    )
+                }
+                crate::lexer::Token::Int | crate::lexer::Token::Long |
+                crate::lexer::Token::Float | crate::lexer::Token::Double |
+                crate::lexer::Token::Bool | crate::lexer::Token::Char |
+                crate::lexer::Token::String => {
+                    let kw = format!("{:?}", current_token).to_lowercase();
+                    (
+                        format!("关键字({})", kw),
+                        format!("类型 '{}' 不能单独作为顶层声明。可能的问题:\n    - 缺少变量或函数声明，如: {} x; 或 {} main() {{ ... }}\n    - 类型后缺少标识符", kw, kw, kw)
+                    )
+                }
+                crate::lexer::Token::Identifier(name) => (
+                    format!("标识符('{}')", name),
+                    format!("'{}' 不能作为顶层声明开始。可能的问题:\n    - 需要先声明类或函数\n    - 语句位置错误，应在函数体内\n    - 如果是方法调用，需要在函数或 main 函数中执行", name)
+                ),
+                crate::lexer::Token::IntegerLiteral(Some((val, _))) => (
+                    format!("整数({})", val),
+                    "整数字面量不能作为顶层声明。可能的问题:\n    - 缺少变量声明，如: int x = 10;\n    - 语句位置错误，应在函数体内".to_string()
+                ),
+                crate::lexer::Token::StringLiteral(Some(s)) => (
+                    format!("字符串(\"{}\")", s),
+                    "字符串字面量不能作为顶层声明。可能的问题:\n    - 缺少变量声明，如: String s = \"hello\";\n    - 语句位置错误，应在函数体内".to_string()
+                ),
+                crate::lexer::Token::Private | crate::lexer::Token::Protected |
+                crate::lexer::Token::Static | crate::lexer::Token::Final |
+                crate::lexer::Token::Abstract => {
+                    let kw = format!("{:?}", current_token).to_lowercase();
+                    (
+                        format!("关键字({})", kw),
+                        format!("修饰符 '{}' 不能单独作为顶层声明。可能的问题:\n    - 修饰符后缺少类或函数声明\n    - 顶层声明应以 class、interface 或 public 开始", kw)
+                    )
+                }
+                _ => {
+                    let token_name = utils::get_token_name(current_token);
+                    (
+                        token_name.clone(),
+                        format!("{} 不能作为顶层声明。有效的顶层声明包括:\n    - 类: class MyClass {{ ... }}\n    - 接口: interface MyInterface {{ ... }}\n    - 外部函数: extern {{ ... }}\n    - 类型别名: type MyType = int;\n    - 主函数: public int main() {{ ... }}", token_name)
+                    )
+                }
+            };
+            return Err(self.error(&format!(
+                "期望类、接口、extern 声明或顶层函数声明，但遇到了 {}\n提示: {}",
+                token_desc, suggestion
+            )));
         }
 
-        Ok(Program { classes, interfaces, top_level_functions, extern_declarations, type_aliases })
+        Ok(())
     }
 
     // 类解析方法
@@ -462,6 +622,19 @@ impl Parser {
         self.parse_top_level_function_body(loc, vec![])
     }
 
+    /// 解析导出的顶层函数：`[public] export 返回类型 函数名(...)`
+    fn parse_exported_top_level_function(&mut self) -> cayResult<crate::ast::TopLevelFunction> {
+        let loc = self.current_loc();
+        let mut modifiers = Vec::new();
+        if self.check(&crate::lexer::Token::Public) {
+            self.advance();
+            modifiers.push(crate::ast::Modifier::Public);
+        }
+        self.consume(&crate::lexer::Token::Export, "期望 'export'")?;
+        modifiers.push(crate::ast::Modifier::Export);
+        self.parse_top_level_function_body(loc, modifiers)
+    }
+
     /// 解析顶层函数的主体部分
     fn parse_top_level_function_body(&mut self, loc: crate::error::SourceLocation, modifiers: Vec<crate::ast::Modifier>) -> cayResult<crate::ast::TopLevelFunction> {
         // 解析返回类型
@@ -475,8 +648,13 @@ impl Parser {
         let params = self.parse_parameters()?;
         self.consume(&crate::lexer::Token::RParen, "期望 ')'\n提示: 参数列表应以 ')' 结束")?;
 
-        // 解析函数体
-        let body = self.parse_block()?;
+        // 解析函数体（接口文件中只有签名）
+        let body = if self.interface_file && self.check(&crate::lexer::Token::Semicolon) {
+            self.advance();
+            crate::ast::Block { statements: Vec::new(), loc: loc.clone() }
+        } else {
+            self.parse_block()?
+        };
 
         Ok(crate::ast::TopLevelFunction {
             name,
@@ -715,7 +893,7 @@ impl Parser {
     }
 
     /// 注册类型别名
-    fn register_type_alias(&mut self, name: String, target_type: crate::types::Type) {
+    pub fn register_type_alias(&mut self, name: String, target_type: crate::types::Type) {
         self.type_aliases.insert(name, target_type);
     }
 
@@ -808,3 +986,18 @@ pub fn parse_with_source(tokens: Vec<TokenWithLocation>, source: String) -> cayR
     let mut parser = Parser::with_source(tokens, source);
     parser.parse()
 }
+
+/// 解析接口文件（.cai）的令牌流
+pub fn parse_interface_file(tokens: Vec<TokenWithLocation>) -> cayResult<Program> {
+    Parser::for_interface_file(tokens).parse()
+}
+
+/// 解析令牌流生成 AST，语法错误后继续解析并收集全部错误
+pub fn parse_recovering(tokens: Vec<TokenWithLocation>) -> ParseOutput {
+    Parser::new(tokens).parse_with_recovery()
+}
+
+/// 解析令牌流生成 AST（带源代码），语法错误后继续解析并收集全部错误
+pub fn parse_with_source_recovering(tokens: Vec<TokenWithLocation>, source: String) -> ParseOutput {
+    Parser::with_source(tokens, source).parse_with_recovery()
+}
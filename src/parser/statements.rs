@@ -2,7 +2,7 @@
 
 use crate::ast::*;
 use crate::error::cayResult;
-use super::Parser;
+use super::{Parser, SyncLevel};
 use super::types::{parse_type, is_primitive_type_token};
 use super::expressions::parse_expression;
 
@@ -42,14 +42,34 @@ pub fn parse_block(parser: &mut Parser) -> cayResult<Block> {
             break;
         }
         
-        statements.push(parse_statement(parser)?);
+        statements.push(parse_statement_recovering(parser));
     }
 
-    parser.consume(&crate::lexer::Token::RBrace, "期望 '}'\n提示: 代码块以 '}' 结束")?;
+    // 循环只会在 '}' 或文件结束处退出；文件提前结束时保留已解析的语句
+    if let Err(err) = parser.consume(&crate::lexer::Token::RBrace, "期望 '}'\n提示: 代码块以 '}' 结束") {
+        parser.record_error(err);
+    }
     
     Ok(Block { statements, loc })
 }
 
+/// 解析语句（带错误恢复）
+///
+/// 出错时记录错误、同步到下一条语句的开始处，并返回 `Stmt::Error` 占位节点，
+/// 使同一代码块中后续的语句仍能被解析。
+pub fn parse_statement_recovering(parser: &mut Parser) -> Stmt {
+    let start_pos = parser.pos;
+    let loc = parser.current_loc();
+    match parse_statement(parser) {
+        Ok(stmt) => stmt,
+        Err(err) => {
+            parser.record_error(err);
+            parser.synchronize(start_pos, SyncLevel::Statement);
+            Stmt::Error(loc)
+        }
+    }
+}
+
 /// 解析语句
 pub fn parse_statement(parser: &mut Parser) -> cayResult<Stmt> {
     // 检查是否是标签语句: label:
@@ -446,7 +466,7 @@ pub fn parse_switch_statement(parser: &mut Parser) -> cayResult<Stmt> {
             let mut body = Vec::new();
             while !parser.check(&crate::lexer::Token::Case) && !parser.check(&crate::lexer::Token::Default)
                 && !parser.check(&crate::lexer::Token::RBrace) && !parser.is_at_end() {
-                body.push(parse_statement(parser)?);
+                body.push(parse_statement_recovering(parser));
             }
             
             cases.push(Case { value, body });
@@ -457,7 +477,7 @@ pub fn parse_switch_statement(parser: &mut Parser) -> cayResult<Stmt> {
             let mut body = Vec::new();
             while !parser.check(&crate::lexer::Token::Case) && !parser.check(&crate::lexer::Token::Default)
                 && !parser.check(&crate::lexer::Token::RBrace) && !parser.is_at_end() {
-                body.push(parse_statement(parser)?);
+                body.push(parse_statement_recovering(parser));
             }
 
             default = Some(body);
@@ -157,9 +157,59 @@ pub fn parse_type(parser: &mut Parser) -> cayResult<Type> {
         result_type = Type::Array(Box::new(result_type));
     }
 
+    // 检查可空标记 Type?（需与三元运算符 cond ? a : b 区分）
+    if parser.check(&crate::lexer::Token::Question) && is_nullable_marker(parser) {
+        if !result_type.is_reference_type() {
+            return Err(parser.error(&format!(
+                "基本类型 '{}' 不能声明为可空\n提示: 只有 String、类类型和数组类型可以使用 '?' 标记，例如: String? name",
+                result_type
+            )));
+        }
+        parser.advance();
+        result_type = Type::Nullable(Box::new(result_type));
+    }
+
     Ok(result_type)
 }
 
+/// 判断当前的 '?' 是否是可空类型标记，而不是三元运算符
+///
+/// 类型后的 '?' 之后只能是变量名（再接 = ; , ) : 等）或声明结束符号，
+/// 而三元运算符的 '?' 之后总是一个表达式，并最终跟随 ':'。
+fn is_nullable_marker(parser: &Parser) -> bool {
+    use crate::lexer::Token;
+    let token_at = |offset: usize| parser.tokens.get(parser.pos + offset).map(|t| &t.token);
+    match token_at(1) {
+        Some(Token::Identifier(_)) => match token_at(2) {
+            Some(Token::Assign) | Some(Token::Semicolon) | Some(Token::Comma)
+                | Some(Token::RParen) => true,
+            // String? name(...) 是方法声明；cond ? f(...) : g 是三元运算符
+            Some(Token::LParen) => {
+                let mut depth = 0usize;
+                let mut offset = 2;
+                while let Some(token) = token_at(offset) {
+                    match token {
+                        Token::LParen => depth += 1,
+                        Token::RParen => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    offset += 1;
+                }
+                !matches!(token_at(offset + 1), Some(Token::Colon) | None)
+            }
+            _ => false,
+        },
+        Some(Token::Assign) | Some(Token::Semicolon) | Some(Token::Comma)
+            | Some(Token::RParen) | Some(Token::Gt) | Some(Token::DotDotDot) => true,
+        _ => false,
+    }
+}
+
 /// 检查当前token是否是类型token
 pub fn is_type_token(parser: &Parser) -> bool {
     matches!(parser.current_token(),
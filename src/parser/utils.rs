@@ -12,29 +12,50 @@ pub fn is_at_end(parser: &Parser) -> bool {
     parser.pos >= parser.tokens.len()
 }
 
-/// 获取当前令牌
+/// 令牌流末尾的哨兵令牌（词法分析器不生成显式的 EOF 令牌）
+static EOF_TOKEN: Token = Token::Newline;
+
+/// 获取当前令牌，到达末尾时返回哨兵令牌
 pub fn current_token(parser: &Parser) -> &Token {
-    &parser.tokens[parser.pos].token
+    parser.tokens.get(parser.pos).map(|t| &t.token).unwrap_or(&EOF_TOKEN)
+}
+
+/// 获取当前令牌的友好名称，到达末尾时返回"文件结束"
+pub fn current_token_name(parser: &Parser) -> String {
+    if is_at_end(parser) {
+        "文件结束".to_string()
+    } else {
+        get_token_name(current_token(parser))
+    }
+}
+
+/// 获取当前位置对应的令牌，到达末尾时退回到最后一个令牌
+fn token_at_or_last(parser: &Parser) -> Option<&TokenWithLocation> {
+    parser.tokens.get(parser.pos).or_else(|| parser.tokens.last())
 }
 
 /// 获取当前完整位置（包含源文件信息）
 pub fn current_full_loc(parser: &Parser) -> FullSourceLocation {
-    FullSourceLocation::from_token(&parser.tokens[parser.pos])
+    token_at_or_last(parser)
+        .map(FullSourceLocation::from_token)
+        .unwrap_or_else(|| FullSourceLocation::new(None, 1, 1))
 }
 
 /// 获取上一个完整位置（包含源文件信息）
 pub fn previous_full_loc(parser: &Parser) -> FullSourceLocation {
     if parser.pos > 0 {
-        FullSourceLocation::from_token(&parser.tokens[parser.pos - 1])
+        FullSourceLocation::from_token(&parser.tokens[parser.pos.min(parser.tokens.len()) - 1])
     } else {
-        FullSourceLocation::from_token(&parser.tokens[0])
+        current_full_loc(parser)
     }
 }
 
 /// 获取当前位置（向后兼容）
 /// 使用 source_line（原始源文件行号）而不是 loc.line（预处理后的行号）
 pub fn current_loc(parser: &Parser) -> crate::error::SourceLocation {
-    let token = &parser.tokens[parser.pos];
+    let Some(token) = token_at_or_last(parser) else {
+        return crate::error::SourceLocation::default();
+    };
     crate::error::SourceLocation {
         file: token.source_file.clone(),
         line: token.loc.line,  // 使用预处理后的行号，让语义分析器来映射
@@ -100,27 +121,27 @@ pub fn consume<'a>(parser: &'a mut Parser, token: &Token, message: &str) -> cayR
         let (error_code, detailed_message, suggestion) = match token {
             Token::Semicolon => (
                 ErrorCodes::PARSER_EXPECTED_SEMICOLON,
-                format!("期望分号 ';'，但找到 '{}'", get_token_name(current_token(parser))),
+                format!("期望分号 ';'，但找到 '{}'", current_token_name(parser)),
                 "在语句末尾添加分号 ';'".to_string()
             ),
             Token::LBrace => (
                 ErrorCodes::PARSER_EXPECTED_BRACE,
-                format!("期望左大括号 '{{'，但找到 '{}'", get_token_name(current_token(parser))),
+                format!("期望左大括号 '{{'，但找到 '{}'", current_token_name(parser)),
                 "在代码块开始处添加 '{{'".to_string()
             ),
             Token::RBrace => (
                 ErrorCodes::PARSER_EXPECTED_BRACE,
-                format!("期望右大括号 '}}'，但找到 '{}'", get_token_name(current_token(parser))),
+                format!("期望右大括号 '}}'，但找到 '{}'", current_token_name(parser)),
                 "在代码块结束处添加 '}}'".to_string()
             ),
             Token::LParen => (
                 ErrorCodes::PARSER_EXPECTED_PAREN,
-                format!("期望左括号 '('，但找到 '{}'", get_token_name(current_token(parser))),
+                format!("期望左括号 '('，但找到 '{}'", current_token_name(parser)),
                 "在表达式或参数列表开始处添加 '('".to_string()
             ),
             Token::RParen => (
                 ErrorCodes::PARSER_EXPECTED_PAREN,
-                format!("期望右括号 ')'，但找到 '{}'", get_token_name(current_token(parser))),
+                format!("期望右括号 ')'，但找到 '{}'", current_token_name(parser)),
                 "在表达式或参数列表结束处添加 ')'".to_string()
             ),
             _ => (
@@ -153,7 +174,7 @@ pub fn consume_identifier(parser: &mut Parser, message: &str) -> cayResult<Strin
         Ok(name)
     } else {
         let loc = current_full_loc(parser);
-        let actual = get_token_name(current_token(parser));
+        let actual = current_token_name(parser);
         let detailed_message = format!("期望标识符，但找到 '{}'", actual);
         
         let diagnostic = Diagnostic::error(
@@ -243,6 +264,7 @@ pub fn get_token_name(token: &Token) -> String {
         Token::Extends => "extends".to_string(),
         Token::Implements => "implements".to_string(),
         Token::InstanceOf => "instanceof".to_string(),
+        Token::SizeOf => "sizeof".to_string(),
         Token::Var => "var".to_string(),
         Token::Let => "let".to_string(),
         Token::Auto => "auto".to_string(),
@@ -252,6 +274,7 @@ pub fn get_token_name(token: &Token) -> String {
         Token::Null => "null".to_string(),
         Token::AtMain => "@main".to_string(),
         Token::AtOverride => "@Override".to_string(),
+        Token::AtTest => "@Test".to_string(),
         Token::LParen => "(".to_string(),
         Token::RParen => ")".to_string(),
         Token::LBrace => "{".to_string(),
@@ -321,8 +344,11 @@ pub fn get_token_name(token: &Token) -> String {
         Token::Sysv64 => "sysv64".to_string(),
         Token::Win64 => "win64".to_string(),
         Token::Native => "native".to_string(),
+        Token::Export => "export".to_string(),
         Token::InlineIr => "__ir".to_string(),
         Token::Alias => "alias".to_string(),
+        Token::Package => "package".to_string(),
+        Token::Import => "import".to_string(),
         Token::Fn => "fn".to_string(),
         Token::As => "as".to_string(),
     }
@@ -341,25 +367,80 @@ pub fn is_type_token(parser: &Parser) -> bool {
     )
 }
 
-/// 同步到下一个语句边界（用于错误恢复）
-pub fn synchronize(parser: &mut Parser) {
-    advance(parser);
-    
+/// 错误恢复时的同步层级
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncLevel {
+    /// 语句边界：代码块内的下一条语句
+    Statement,
+    /// 成员边界：类体内的下一个字段/方法/构造函数
+    Member,
+    /// 顶层边界：下一个类、接口、extern 或顶层函数声明
+    TopLevel,
+}
+
+/// 判断令牌是否是指定层级上的同步点（可以从此处重新开始解析）
+fn is_sync_point(token: &Token, level: SyncLevel) -> bool {
+    match level {
+        SyncLevel::Statement => matches!(token,
+            Token::If | Token::While | Token::For | Token::Do |
+            Token::Switch | Token::Return | Token::Break | Token::Continue |
+            Token::Scope | Token::Final | Token::Var | Token::Let | Token::Auto |
+            Token::Int | Token::Long | Token::Float | Token::Double |
+            Token::Bool | Token::Char | Token::String | Token::InlineIr
+        ),
+        SyncLevel::Member => matches!(token,
+            Token::Public | Token::Private | Token::Protected | Token::Static |
+            Token::Final | Token::Abstract | Token::Native | Token::Export | Token::AtOverride |
+            Token::AtTest | Token::Void | Token::Tilde
+        ),
+        SyncLevel::TopLevel => matches!(token,
+            Token::Class | Token::Interface | Token::Public | Token::Private |
+            Token::Protected | Token::AtMain | Token::Export | Token::Extern | Token::Alias |
+            Token::Package | Token::Import
+        ),
+    }
+}
+
+/// 同步到下一个边界（panic-mode 错误恢复）
+///
+/// 从出错位置开始跳过令牌，直到遇到同一花括号层级上的分号、
+/// 闭合的 `{ ... }` 块、外层的 `}` 或该层级的同步点为止。
+/// `start_pos` 为出错声明/语句的起始位置：若出错时尚未消耗任何令牌，
+/// 至少跳过一个令牌以保证解析能够前进。
+pub fn synchronize(parser: &mut Parser, start_pos: usize, level: SyncLevel) {
+    let mut depth = 0usize;
+    let mut must_advance = parser.pos <= start_pos;
+
     while !is_at_end(parser) {
-        if matches!(current_token(parser), Token::Semicolon) {
-            advance(parser);
-            return;
-        }
-        
-        match current_token(parser) {
-            Token::Class | Token::Interface | Token::Public | 
-            Token::Private | Token::Protected | Token::If | 
-            Token::While | Token::For | Token::Return => {
+        let token = current_token(parser);
+
+        if depth == 0 && !must_advance {
+            // 外层的 '}'：属于包围当前语句/成员的块，留给调用者处理
+            if matches!(token, Token::RBrace) && level != SyncLevel::TopLevel {
+                return;
+            }
+            if is_sync_point(token, level) {
                 return;
             }
-            _ => {
+        }
+        must_advance = false;
+
+        match token {
+            Token::LBrace => depth += 1,
+            Token::RBrace => {
+                advance(parser);
+                if depth <= 1 {
+                    return;
+                }
+                depth -= 1;
+                continue;
+            }
+            Token::Semicolon if depth == 0 => {
                 advance(parser);
+                return;
             }
+            _ => {}
         }
+        advance(parser);
     }
 }
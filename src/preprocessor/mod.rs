@@ -85,6 +85,8 @@ pub struct Preprocessor {
     include_stack: Vec<String>,
     /// 系统包含路径列表
     system_include_paths: Vec<PathBuf>,
+    /// 分离编译时记录的依赖单元（规范化的完整路径）；为 None 时 #include 展开文件内容
+    unit_dependencies: Option<Vec<String>>,
 }
 
 /// 条件编译状态
@@ -179,9 +181,40 @@ impl Preprocessor {
             skipping: false,
             include_stack: Vec::new(),
             system_include_paths: include_paths,
+            unit_dependencies: None,
         }
     }
 
+    /// 预定义宏，等价于源文件开头的 `#define name value`（命令行 `-D`）
+    pub fn define(&mut self, name: &str, value: &str) {
+        self.defines.insert(name.to_string(), value.to_string());
+    }
+
+    /// 取消预定义的宏（命令行 `-U`），包括自动定义的平台宏
+    pub fn undefine(&mut self, name: &str) {
+        self.defines.remove(name);
+    }
+
+    /// 分离编译模式：#include 不再展开文件内容，只把被包含的文件记录为依赖的编译单元
+    pub fn set_unit_mode(&mut self) {
+        self.unit_dependencies.get_or_insert_with(Vec::new);
+    }
+
+    /// 把文件标记为已包含，之后对它的 `#include` 被跳过；文件已经被包含过时返回 false
+    ///
+    /// 多文件编译时每个输入文件都直接参与编译，已被前面的文件 `#include` 展开的输入不再重复处理。
+    pub fn mark_included(&mut self, path: &str) -> bool {
+        let once_key = std::fs::canonicalize(path)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| path.to_string());
+        self.included_files.insert(once_key)
+    }
+
+    /// 分离编译模式下记录的依赖单元（按首次出现的顺序）
+    pub fn unit_dependencies(&self) -> &[String] {
+        self.unit_dependencies.as_deref().unwrap_or(&[])
+    }
+
         /// 预处理源文件，返回处理后的源代码（带源映射）
     ///
     /// # Arguments
@@ -396,6 +429,18 @@ impl Preprocessor {
                 if self.skipping {
                     return Ok(DirectiveResult::Single(None));
                 }
+
+                if self.unit_dependencies.is_some() {
+                    let full_path = self.resolve_include_path(&path, is_system, file_path)?;
+                    let canonical = std::fs::canonicalize(&full_path)
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or(full_path);
+                    let dependencies = self.unit_dependencies.get_or_insert_with(Vec::new);
+                    if !dependencies.contains(&canonical) {
+                        dependencies.push(canonical);
+                    }
+                    return Ok(DirectiveResult::Single(Some(String::new())));
+                }
                 
                 // 读取包含文件
                 match self.read_include_file(&path, is_system, file_path)? {
@@ -562,8 +607,12 @@ impl Preprocessor {
             });
         }
         
-        // 然后检查是否已经包含过（#pragma once 语义）
-        if self.included_files.contains(&full_path) {
+        // 然后检查是否已经包含过（#pragma once 语义）；按规范化路径判断，
+        // `tests/../src/a.cay` 与 `src/a.cay` 视为同一文件
+        let once_key = std::fs::canonicalize(&full_path)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| full_path.clone());
+        if self.included_files.contains(&once_key) {
             return Ok(None);  // 已经包含过，返回 None 表示跳过
         }
         
@@ -578,7 +627,7 @@ impl Preprocessor {
             })?;
         
         // 添加到已包含集合
-        self.included_files.insert(full_path.clone());
+        self.included_files.insert(once_key);
         
         Ok(Some((content, full_path)))
     }
@@ -933,6 +982,21 @@ mod tests {
         assert!(result.contains("int debug = 1"));
     }
 
+    #[test]
+    fn test_predefined_macros() {
+        let mut pp = Preprocessor::new(".");
+        pp.define("FEATURE_GUI", "");
+        pp.define("LEVEL", "3");
+        pp.define("TRACE", "");
+        pp.undefine("TRACE");
+        let result = pp.process(
+            "#ifdef FEATURE_GUI\nint gui = LEVEL;\n#endif\n#ifdef TRACE\nint trace = 1;\n#endif",
+            "test.c"
+        ).unwrap();
+        assert!(result.contains("int gui = 3"));
+        assert!(!result.contains("int trace"));
+    }
+
     #[test]
     fn test_ifndef() {
         let mut pp = Preprocessor::new(".");
@@ -24,12 +24,18 @@ pub struct SemanticAnalyzer {
     pub(super) current_method_is_static: bool,  // 当前方法是否是静态方法
     pub(super) current_method_is_constructor: bool,  // 当前是否是构造函数
     pub(super) errors: Vec<SemanticErrorInfo>,
+    /// 不阻止编译的警告（如仅警告模式下的空安全问题）
+    pub(super) warnings: Vec<SemanticErrorInfo>,
     pub(super) current_file: Option<String>,  // 当前正在分析的文件路径
     /// 源映射表：输出行号 -> (原始文件, 原始行号)
     /// 用于根据AST中的原始行号反查对应的源文件
     pub(super) source_map: Option<std::collections::HashMap<usize, (String, usize)>>,
     /// 启用的语言特性
     pub(super) features: Vec<String>,
+    /// 分离编译时依赖单元的接口声明
+    pub(super) imports: Option<Program>,
+    /// 目标平台（`sizeof` 按它求值）
+    pub(super) target: crate::target::TargetInfo,
 }
 
 impl SemanticAnalyzer {
@@ -47,9 +53,12 @@ impl SemanticAnalyzer {
             current_method_is_static: false,
             current_method_is_constructor: false,
             errors: Vec::new(),
+            warnings: Vec::new(),
             current_file: None,
             source_map: None,
             features,
+            imports: None,
+            target: crate::target::TargetInfo::default(),
         };
         
         // 注册内置函数
@@ -64,30 +73,61 @@ impl SemanticAnalyzer {
     }
 
     pub fn analyze(&mut self, program: &Program) -> cayResult<()> {
+        // 分离编译时声明相关的各遍作用于依赖单元的接口和本单元的合并程序，
+        // 方法体只检查本单元的代码（接口中的方法体为空）
+        let merged = self.imports.take().map(|mut all| {
+            all.append(program.clone());
+            all
+        });
+
+        // 空安全检查需要可空标记，之后的各遍在擦除后的 AST 上进行
+        self.check_null_safety(merged.as_ref().unwrap_or(program));
+        let erased_all = merged.as_ref().map(Program::erase_nullability);
+        let erased = program.erase_nullability();
+        let program = &erased;
+        let all = erased_all.as_ref().unwrap_or(program);
+
         // 保存 program 引用以供类型推断使用
-        self.program = Some(std::rc::Rc::new(program.clone()));
+        self.program = Some(std::rc::Rc::new(all.clone()));
+
+        // 多文件程序：跨文件重复定义和 package/import 可见性
+        self.check_packages(program);
+        if !self.errors.is_empty() {
+            return self.collected_errors();
+        }
 
         // 第一遍：收集所有类定义
-        self.collect_classes(program)?;
+        self.collect_classes(all)?;
 
         // 注册运行时函数到 NetworkUtils 类
         self.register_runtime_functions();
 
         // 注册顶层函数到符号表
-        self.register_top_level_functions(program)?;
+        self.register_top_level_functions(all)?;
 
         // 检查主类冲突（在收集类之后，类型检查之前）
-        self.check_main_class_conflicts(program)?;
+        self.check_main_class_conflicts(all)?;
 
         // 第二遍：分析方法定义
-        self.analyze_methods(program)?;
+        self.analyze_methods(all)?;
 
         // 第三遍：检查继承关系（包括 @Override 验证）
-        self.check_inheritance(program)?;
+        self.check_inheritance(all)?;
+
+        // 检查接口继承、接口实现和 default 方法冲突
+        self.check_interfaces(all);
+
+        // 检查 static final 整数常量表达式的溢出
+        self.check_constant_overflow(program);
 
         // 第四遍：类型检查
         self.type_check_program(program)?;
 
+        self.collected_errors()
+    }
+
+    /// 把收集到的语义错误合并为一个 cayError
+    fn collected_errors(&self) -> cayResult<()> {
         if !self.errors.is_empty() {
             // 使用第一个错误的行号/列号，后续错误拼接在消息中
             let first = &self.errors[0];
@@ -129,6 +169,7 @@ impl SemanticAnalyzer {
                 is_native: true,
                 is_override: false,
                 is_final: false,
+                is_abstract: false,
             };
 
             class_info.add_method(method);
@@ -185,6 +226,21 @@ impl SemanticAnalyzer {
         Ok(())
     }
 
+    /// 设置依赖单元的接口声明（分离编译，见 `crate::unit`），在 `analyze` 之前调用
+    pub fn set_imports(&mut self, imports: Program) {
+        self.imports = Some(imports);
+    }
+
+    /// 设置目标平台（`sizeof` 和 `static final` 常量求值按它计算），在 `analyze` 之前调用
+    pub fn set_target(&mut self, target: crate::target::TargetInfo) {
+        self.target = target;
+    }
+
+    /// 获取语义分析产生的警告
+    pub fn warnings(&self) -> &[SemanticErrorInfo] {
+        &self.warnings
+    }
+
     /// 获取类型注册表（用于代码生成）
     pub fn get_type_registry(&self) -> &TypeRegistry {
         &self.type_registry
@@ -284,6 +340,7 @@ impl SemanticAnalyzer {
             Expr::Ternary(e) => (e.loc.line, e.loc.column),
             Expr::Lambda(e) => (e.loc.line, e.loc.column),
             Expr::InstanceOf(e) => (e.loc.line, e.loc.column),
+            Expr::SizeOf(e) => (e.loc.line, e.loc.column),
             Expr::ArrayCreation(e) => (e.loc.line, e.loc.column),
             Expr::MethodRef(e) => (e.loc.line, e.loc.column),
             Expr::Alloc(e) => (e.loc.line, e.loc.column),
@@ -92,6 +92,7 @@ impl SemanticAnalyzer {
         // 首先收集接口定义
         for interface in &program.interfaces {
             let mut interface_info = crate::types::InterfaceInfo::new(interface.name.clone());
+            interface_info.parents = interface.parents.clone();
 
             // 收集接口方法
             for method in &interface.methods {
@@ -107,6 +108,7 @@ impl SemanticAnalyzer {
                     is_native: false,
                     is_override: false,
                     is_final: false,  // 接口方法不是final
+                    is_abstract: !method.modifiers.contains(&Modifier::Default),
                 };
                 interface_info.add_method(method_info);
             }
@@ -192,6 +194,7 @@ impl SemanticAnalyzer {
                         is_native: method.modifiers.contains(&Modifier::Native),
                         is_override: method.modifiers.contains(&Modifier::Override),
                         is_final: method.modifiers.contains(&Modifier::Final),
+                        is_abstract: method.modifiers.contains(&Modifier::Abstract),
                     };
 
                     if let Some(class_info) = self.type_registry.classes.get_mut(&class.name) {
@@ -243,10 +246,11 @@ impl SemanticAnalyzer {
             self.check_circular_inheritance(&class.name, &class.name, &mut Vec::new())?;
         }
 
-        // 第四遍：验证 @Override 注解 和 final 方法检查
+        // 第四遍：验证 @Override 注解、final 方法检查和 @Test 方法签名
         for class in &program.classes {
             self.check_override_methods(class)?;
             self.check_final_method_override(class)?;
+            self.check_test_methods(class)?;
         }
 
         Ok(())
@@ -276,6 +280,11 @@ impl SemanticAnalyzer {
         for member in &class.members {
             if let ClassMember::Method(method) = member {
                 if method.modifiers.contains(&Modifier::Override) {
+                    // 实现接口方法同样可以标记 @Override
+                    if self.implements_interface_method(&class.name, method) {
+                        continue;
+                    }
+
                     // 检查父类是否存在
                     let parent_name = match &class.parent {
                         Some(p) => p,
@@ -305,6 +314,37 @@ impl SemanticAnalyzer {
         Ok(())
     }
 
+    /// 检查 @Test 方法：无参数、返回 void、有方法体；实例方法所在的类不能是抽象类
+    fn check_test_methods(&self, class: &crate::ast::ClassDecl) -> cayResult<()> {
+        for member in &class.members {
+            if let ClassMember::Method(method) = member {
+                if !method.modifiers.contains(&Modifier::Test) {
+                    continue;
+                }
+                let problem = if !method.params.is_empty() {
+                    Some("must not take parameters")
+                } else if method.return_type != Type::Void {
+                    Some("must return void")
+                } else if method.body.is_none() {
+                    Some("must have a body")
+                } else if !method.modifiers.contains(&Modifier::Static) && class.modifiers.contains(&Modifier::Abstract) {
+                    Some("must be static when declared in an abstract class")
+                } else {
+                    None
+                };
+                if let Some(problem) = problem {
+                    return Err(semantic_error(
+                        method.loc.line,
+                        method.loc.column,
+                        format!("Test method '{}.{}' {}", class.name, method.name, problem)
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// 检查父类中是否存在匹配的方法
     fn method_exists_in_parent(&self, parent_name: &str, method_name: &str, params: &[ParameterInfo], return_type: &Type) -> bool {
         if let Some(parent_class) = self.type_registry.get_class(parent_name) {
@@ -0,0 +1,742 @@
+//! 逃逸分析
+//!
+//! 找出不会逃出所在块的 `new` 对象，IR Builder 把它们分配在栈上（alloca），
+//! 并在离开块时（正常结束或经 break/continue/return 离开）调用析构函数。
+//!
+//! `C v = new C(...)` 满足以下条件时不逃逸：
+//! - 声明直接位于块中（不在 lambda 体、for 初始化或 switch case 中），`C` 是程序中声明的类；
+//! - 块的剩余语句中 `v` 只用于字段读写、`==`/`!=`/`instanceof` 和方法调用接收者，且没有被重新赋值；
+//! - 构造函数、析构函数和被调用的方法都不会让 `this` 逃逸。
+//!
+//! 存入字段、数组或其他变量，作为返回值或调用实参，被 lambda/方法引用捕获，或块中含内联 IR 时视为逃逸。
+//! 方法按名称汇总：类及父类中同名的所有实例方法和接口 default 方法都必须不让 `this` 逃逸，
+//! 汇总从“都不逃逸”出发迭代到不动点，以处理方法之间的相互调用。
+
+use std::collections::{BTreeMap, HashMap};
+use crate::ast::*;
+use crate::error::SourceLocation;
+
+/// 分配在栈上的 `new` 表达式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackAllocation {
+    /// 在程序中的序号（用于命名栈槽）
+    pub index: usize,
+    pub class_name: String,
+    /// 离开作用域时调用的析构函数（类及其父类中最近的 `~C()`）
+    pub destructor: Option<String>,
+}
+
+/// 一个 `new` 表达式的分析结果（`-Rpass=escape` 输出）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscapeRemark {
+    pub loc: SourceLocation,
+    pub class_name: String,
+    /// 逃逸原因；`None` 表示分配在栈上
+    pub reason: Option<String>,
+}
+
+impl EscapeRemark {
+    pub fn message(&self) -> String {
+        match &self.reason {
+            None => format!("'new {}' allocated on the stack", self.class_name),
+            Some(reason) => format!("'new {}' escapes: {}", self.class_name, reason),
+        }
+    }
+}
+
+/// 逃逸分析结果
+#[derive(Debug, Clone, Default)]
+pub struct EscapeInfo {
+    /// 栈分配的 `new` 表达式，按源位置 (行, 列) 索引
+    stack: HashMap<(usize, usize), StackAllocation>,
+    /// 按源位置排序的所有 `new` 表达式
+    remarks: Vec<EscapeRemark>,
+}
+
+impl EscapeInfo {
+    /// 位于 `loc` 的 `new` 表达式是否分配在栈上
+    pub fn stack_allocation(&self, loc: &SourceLocation) -> Option<&StackAllocation> {
+        self.stack.get(&(loc.line, loc.column))
+    }
+
+    pub fn remarks(&self) -> &[EscapeRemark] {
+        &self.remarks
+    }
+}
+
+/// 值在表达式中的去向
+#[derive(Debug, Clone, PartialEq)]
+enum Sink {
+    /// 表达式语句的结果
+    Discarded,
+    /// `==`、`!=` 或 `instanceof` 的操作数
+    Compared,
+    /// 字段访问或数组下标的对象
+    FieldBase,
+    /// 方法调用的接收者
+    Receiver(String),
+    /// 无接收者调用的函数名（可能是隐式 this 方法调用）
+    Callee,
+    /// 赋值或自增自减的目标
+    AssignTarget,
+    Returned,
+    Field(String),
+    /// 赋给已声明的变量
+    Variable(String),
+    /// 作为变量声明的初始值
+    Declared(String),
+    ArrayElement,
+    Argument(String),
+    ConstructorArgument(String),
+    Cast,
+    /// 方法引用的对象
+    Captured,
+    /// 位于 lambda 体内
+    InLambda,
+    Freed,
+    /// 其他运算的操作数
+    Value,
+}
+
+impl Sink {
+    /// 值流向此处时的逃逸原因；只读使用返回 `None`（方法调用接收者另行判断）
+    fn escape_reason(&self) -> Option<String> {
+        let reason = match self {
+            Sink::Discarded | Sink::Compared | Sink::FieldBase | Sink::Receiver(_) | Sink::Callee => return None,
+            Sink::AssignTarget => "the variable is reassigned".to_string(),
+            Sink::Returned => "returned from the function".to_string(),
+            Sink::Field(name) => format!("stored into field '{}'", name),
+            Sink::Variable(name) | Sink::Declared(name) => format!("stored into variable '{}'", name),
+            Sink::ArrayElement => "stored into an array".to_string(),
+            Sink::Argument(callee) => format!("passed to call '{}'", callee),
+            Sink::ConstructorArgument(class) => format!("passed to constructor of '{}'", class),
+            Sink::Cast => "cast to another type".to_string(),
+            Sink::Captured => "captured by a method reference".to_string(),
+            Sink::InLambda => "captured by a lambda".to_string(),
+            Sink::Freed => "released with __cay_free".to_string(),
+            Sink::Value => "used as an operand".to_string(),
+        };
+        Some(reason)
+    }
+}
+
+/// 一段代码中的值（标识符和 `new` 表达式）及其去向
+#[derive(Default)]
+struct Flows<'a> {
+    values: Vec<(&'a Expr, Sink)>,
+    /// 含有内联 IR
+    inline_ir: bool,
+    /// 含有 lambda 或方法引用
+    closures: bool,
+}
+
+impl<'a> Flows<'a> {
+    fn of_block(block: &'a Block) -> Self {
+        let mut flows = Flows::default();
+        flows.stmts(&block.statements);
+        flows
+    }
+
+    fn stmts(&mut self, stmts: &'a [Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::Expr(expr) => self.expr(expr, Sink::Discarded),
+            Stmt::VarDecl(var) => {
+                if let Some(init) = &var.initializer {
+                    self.expr(init, Sink::Declared(var.name.clone()));
+                }
+            }
+            Stmt::Return(Some(expr)) => self.expr(expr, Sink::Returned),
+            Stmt::If(if_stmt) => {
+                self.expr(&if_stmt.condition, Sink::Value);
+                self.stmt(&if_stmt.then_branch);
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    self.stmt(else_branch);
+                }
+            }
+            Stmt::While(while_stmt) => {
+                self.expr(&while_stmt.condition, Sink::Value);
+                self.stmt(&while_stmt.body);
+            }
+            Stmt::DoWhile(do_while) => {
+                self.stmt(&do_while.body);
+                self.expr(&do_while.condition, Sink::Value);
+            }
+            Stmt::For(for_stmt) => {
+                if let Some(init) = &for_stmt.init {
+                    self.stmt(init);
+                }
+                if let Some(condition) = &for_stmt.condition {
+                    self.expr(condition, Sink::Value);
+                }
+                if let Some(update) = &for_stmt.update {
+                    self.expr(update, Sink::Discarded);
+                }
+                self.stmt(&for_stmt.body);
+            }
+            Stmt::Switch(switch) => {
+                self.expr(&switch.expr, Sink::Value);
+                for case in &switch.cases {
+                    self.stmts(&case.body);
+                }
+                if let Some(default) = &switch.default {
+                    self.stmts(default);
+                }
+            }
+            Stmt::Block(block) => self.stmts(&block.statements),
+            Stmt::Scope(scope) => self.stmts(&scope.body.statements),
+            Stmt::InlineIr(_) => self.inline_ir = true,
+            Stmt::Return(None) | Stmt::Break(_) | Stmt::Continue(_) | Stmt::Error(_) => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &'a Expr, sink: Sink) {
+        match expr {
+            Expr::Literal(_) => {}
+            Expr::Identifier(_) => self.values.push((expr, sink)),
+            Expr::Binary(bin) => {
+                let operand = if matches!(bin.op, BinaryOp::Eq | BinaryOp::Ne) { Sink::Compared } else { Sink::Value };
+                self.expr(&bin.left, operand.clone());
+                self.expr(&bin.right, operand);
+            }
+            Expr::Unary(unary) => {
+                let operand = match unary.op {
+                    UnaryOp::PreInc | UnaryOp::PreDec | UnaryOp::PostInc | UnaryOp::PostDec => Sink::AssignTarget,
+                    _ => Sink::Value,
+                };
+                self.expr(&unary.operand, operand);
+            }
+            Expr::Call(call) => {
+                let callee = match call.callee.as_ref() {
+                    Expr::Identifier(ident) => {
+                        self.values.push((&call.callee, Sink::Callee));
+                        ident.name.clone()
+                    }
+                    Expr::MemberAccess(member) => {
+                        self.expr(&member.object, Sink::Receiver(member.member.clone()));
+                        member.member.clone()
+                    }
+                    other => {
+                        self.expr(other, Sink::Value);
+                        "function pointer".to_string()
+                    }
+                };
+                for arg in &call.args {
+                    self.expr(arg, Sink::Argument(callee.clone()));
+                }
+            }
+            Expr::MemberAccess(member) => self.expr(&member.object, Sink::FieldBase),
+            Expr::New(new_expr) => {
+                self.values.push((expr, sink));
+                for arg in &new_expr.args {
+                    self.expr(arg, Sink::ConstructorArgument(new_expr.class_name.clone()));
+                }
+            }
+            Expr::Assignment(assign) => {
+                let value_sink = match assign.target.as_ref() {
+                    Expr::Identifier(ident) => {
+                        self.values.push((&assign.target, Sink::AssignTarget));
+                        Sink::Variable(ident.name.clone())
+                    }
+                    Expr::MemberAccess(member) => {
+                        self.expr(&member.object, Sink::FieldBase);
+                        Sink::Field(member.member.clone())
+                    }
+                    Expr::ArrayAccess(access) => {
+                        self.expr(&access.array, Sink::FieldBase);
+                        self.expr(&access.index, Sink::Value);
+                        Sink::ArrayElement
+                    }
+                    other => {
+                        self.expr(other, Sink::Value);
+                        Sink::Value
+                    }
+                };
+                let value_sink = if assign.op == AssignOp::Assign { value_sink } else { Sink::Value };
+                self.expr(&assign.value, value_sink);
+            }
+            Expr::Cast(cast) => self.expr(&cast.expr, Sink::Cast),
+            Expr::ArrayCreation(arr) => {
+                for size in &arr.sizes {
+                    self.expr(size, Sink::Value);
+                }
+            }
+            Expr::ArrayAccess(access) => {
+                self.expr(&access.array, Sink::FieldBase);
+                self.expr(&access.index, Sink::Value);
+            }
+            Expr::ArrayInit(init) => {
+                for element in &init.elements {
+                    self.expr(element, Sink::ArrayElement);
+                }
+            }
+            Expr::MethodRef(method_ref) => {
+                self.closures = true;
+                if let Some(object) = &method_ref.object {
+                    self.expr(object, Sink::Captured);
+                }
+            }
+            Expr::Lambda(lambda) => {
+                self.closures = true;
+                let mut body = Flows::default();
+                match &lambda.body {
+                    LambdaBody::Expr(expr) => body.expr(expr, Sink::Value),
+                    LambdaBody::Block(block) => body.stmts(&block.statements),
+                }
+                self.inline_ir |= body.inline_ir;
+                self.values.extend(body.values.into_iter().map(|(e, _)| (e, Sink::InLambda)));
+            }
+            Expr::Ternary(ternary) => {
+                self.expr(&ternary.condition, Sink::Value);
+                self.expr(&ternary.true_branch, sink.clone());
+                self.expr(&ternary.false_branch, sink);
+            }
+            Expr::InstanceOf(instance_of) => self.expr(&instance_of.expr, Sink::Compared),
+            Expr::SizeOf(_) => {}
+            Expr::Alloc(alloc) => {
+                self.expr(&alloc.size, Sink::Value);
+                if let Some(align) = &alloc.align {
+                    self.expr(align, Sink::Value);
+                }
+            }
+            Expr::Dealloc(dealloc) => self.expr(&dealloc.ptr, Sink::Freed),
+        }
+    }
+}
+
+/// 构造函数和析构函数在方法汇总中的名称
+const INIT: &str = "<init>";
+const DTOR: &str = "<dtor>";
+
+/// 逃逸分析器
+pub struct EscapeAnalysis<'a> {
+    program: &'a Program,
+    classes: HashMap<&'a str, &'a ClassDecl>,
+    interfaces: HashMap<&'a str, &'a InterfaceDecl>,
+    /// (类型, 方法名) -> 让 `this` 逃逸的原因
+    summaries: BTreeMap<(String, String), Option<String>>,
+}
+
+impl<'a> EscapeAnalysis<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Self {
+            program,
+            classes: program.classes.iter().map(|c| (c.name.as_str(), c)).collect(),
+            interfaces: program.interfaces.iter().map(|i| (i.name.as_str(), i)).collect(),
+            summaries: BTreeMap::new(),
+        }
+    }
+
+    pub fn analyze(mut self) -> EscapeInfo {
+        self.compute_summaries();
+
+        let mut remarks: HashMap<(usize, usize), EscapeRemark> = HashMap::new();
+        let mut occurrences: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut sites = Vec::new();
+        for body in self.bodies() {
+            for (expr, sink) in &Flows::of_block(body).values {
+                if let Expr::New(new_expr) = expr {
+                    let reason = match sink {
+                        Sink::Declared(name) => format!("'{}' is not declared directly in a block", name),
+                        Sink::InLambda => "allocated inside a lambda body".to_string(),
+                        sink => sink.escape_reason()
+                            .unwrap_or_else(|| "the result is not bound to a local variable".to_string()),
+                    };
+                    let key = (new_expr.loc.line, new_expr.loc.column);
+                    *occurrences.entry(key).or_default() += 1;
+                    remarks.insert(key, EscapeRemark {
+                        loc: new_expr.loc.clone(),
+                        class_name: new_expr.class_name.clone(),
+                        reason: Some(reason),
+                    });
+                }
+            }
+            self.collect_sites(&body.statements, &mut sites);
+        }
+
+        let mut stack = HashMap::new();
+        for (new_expr, reason) in sites {
+            // 栈分配按源位置查找，位置相同的多个 `new` 无法区分，都按堆分配
+            let key = (new_expr.loc.line, new_expr.loc.column);
+            let reason = match occurrences.get(&key) {
+                Some(&count) if count > 1 => Some("shares its source position with another 'new'".to_string()),
+                _ => reason,
+            };
+            if reason.is_none() {
+                stack.insert(key, StackAllocation {
+                    index: stack.len(),
+                    class_name: new_expr.class_name.clone(),
+                    destructor: self.destructor_of(&new_expr.class_name),
+                });
+            }
+            remarks.insert(key, EscapeRemark {
+                loc: new_expr.loc.clone(),
+                class_name: new_expr.class_name.clone(),
+                reason,
+            });
+        }
+
+        let mut remarks: Vec<EscapeRemark> = remarks.into_values().collect();
+        remarks.sort_by_key(|r| (r.loc.line, r.loc.column));
+        EscapeInfo { stack, remarks }
+    }
+
+    /// 会被构建为函数体的所有块
+    fn bodies(&self) -> Vec<&'a Block> {
+        let mut bodies: Vec<&'a Block> = self.program.top_level_functions.iter().map(|f| &f.body).collect();
+        for class in &self.program.classes {
+            for member in &class.members {
+                match member {
+                    ClassMember::Method(method) => bodies.extend(method.body.as_ref()),
+                    ClassMember::Constructor(ctor) => bodies.push(&ctor.body),
+                    ClassMember::Destructor(dtor) => bodies.push(&dtor.body),
+                    ClassMember::StaticInitializer(block) => bodies.push(block),
+                    ClassMember::Field(_) | ClassMember::InstanceInitializer(_) => {}
+                }
+            }
+        }
+        for interface in &self.program.interfaces {
+            bodies.extend(interface.methods.iter().filter_map(|m| m.body.as_ref()));
+        }
+        bodies
+    }
+
+    /// 收集块中直接声明的 `C v = new C(...)` 及其逃逸原因（不进入 lambda 体）
+    fn collect_sites(&self, stmts: &'a [Stmt], sites: &mut Vec<(&'a NewExpr, Option<String>)>) {
+        // `C a = ..., b = ...;` 解析为只含声明的块，变量属于外层块
+        let flat: Vec<&'a Stmt> = stmts.iter()
+            .flat_map(|stmt| match stmt {
+                Stmt::Block(block) if block.statements.iter().all(|s| matches!(s, Stmt::VarDecl(_))) => {
+                    block.statements.iter().collect()
+                }
+                other => vec![other],
+            })
+            .collect();
+
+        for (i, stmt) in flat.iter().enumerate() {
+            let site = match stmt {
+                Stmt::VarDecl(var) => match &var.initializer {
+                    Some(Expr::New(new_expr)) => Some((var, new_expr)),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some((var, new_expr)) = site {
+                let reason = self.site_reason(var, new_expr, &flat[i + 1..]);
+                sites.push((new_expr, reason));
+            }
+            self.collect_nested_sites(stmt, sites);
+        }
+    }
+
+    fn collect_nested_sites(&self, stmt: &'a Stmt, sites: &mut Vec<(&'a NewExpr, Option<String>)>) {
+        match stmt {
+            Stmt::Block(block) => self.collect_sites(&block.statements, sites),
+            Stmt::Scope(scope) => self.collect_sites(&scope.body.statements, sites),
+            Stmt::If(if_stmt) => {
+                self.collect_nested_sites(&if_stmt.then_branch, sites);
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    self.collect_nested_sites(else_branch, sites);
+                }
+            }
+            Stmt::While(while_stmt) => self.collect_nested_sites(&while_stmt.body, sites),
+            Stmt::DoWhile(do_while) => self.collect_nested_sites(&do_while.body, sites),
+            Stmt::For(for_stmt) => self.collect_nested_sites(&for_stmt.body, sites),
+            Stmt::Switch(switch) => {
+                let bodies = switch.cases.iter().map(|c| &c.body).chain(switch.default.as_ref());
+                for body in bodies {
+                    for stmt in body {
+                        self.collect_nested_sites(stmt, sites);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `C v = new C(...)` 的逃逸原因，`rest` 是同一块中之后的语句
+    fn site_reason(&self, var: &VarDecl, new_expr: &NewExpr, rest: &[&'a Stmt]) -> Option<String> {
+        let class_name = new_expr.class_name.as_str();
+        if !self.classes.contains_key(class_name) {
+            return Some(format!("'{}' is not a class declared in this program", class_name));
+        }
+        if let Some(reason) = self.summary(class_name, INIT) {
+            return Some(format!("the constructor of '{}' lets 'this' escape: {}", class_name, reason));
+        }
+        let dtor_class = self.destructor_class(class_name);
+        if let Some((dtor_class, reason)) = dtor_class.and_then(|c| Some((c, self.summary(c, DTOR)?))) {
+            return Some(format!("the destructor of '{}' lets 'this' escape: {}", dtor_class, reason));
+        }
+
+        let mut flows = Flows::default();
+        for stmt in rest {
+            flows.stmt(stmt);
+        }
+        if flows.inline_ir {
+            return Some("the enclosing block contains inline IR".to_string());
+        }
+        flows.values.iter()
+            .filter(|(expr, _)| matches!(expr, Expr::Identifier(ident) if ident.name == var.name))
+            .find_map(|(_, sink)| match sink {
+                Sink::Receiver(method) => self.method_reason(class_name, method),
+                sink => sink.escape_reason(),
+            })
+    }
+
+    // ============================================================
+    // 方法汇总
+    // ============================================================
+
+    fn summary(&self, type_name: &str, method: &str) -> Option<&String> {
+        self.summaries.get(&(type_name.to_string(), method.to_string()))?.as_ref()
+    }
+
+    fn compute_summaries(&mut self) {
+        for class in &self.program.classes {
+            for member in &class.members {
+                if let ClassMember::Method(method) = member {
+                    self.summaries.insert((class.name.clone(), method.name.clone()), None);
+                }
+            }
+            self.summaries.insert((class.name.clone(), INIT.to_string()), None);
+            self.summaries.insert((class.name.clone(), DTOR.to_string()), None);
+        }
+        for interface in &self.program.interfaces {
+            for method in &interface.methods {
+                self.summaries.insert((interface.name.clone(), method.name.clone()), None);
+            }
+        }
+
+        // 从“都不逃逸”出发，逃逸只会增加，直到不再变化
+        loop {
+            let mut changed = false;
+            let keys: Vec<(String, String)> = self.summaries.iter()
+                .filter(|(_, reason)| reason.is_none())
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in keys {
+                if let Some(reason) = self.compute_summary(&key.0, &key.1) {
+                    self.summaries.insert(key, Some(reason));
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    /// 按当前汇总计算类型中名为 `method` 的代码是否让 `this` 逃逸
+    fn compute_summary(&self, type_name: &str, method: &str) -> Option<String> {
+        if let Some(interface) = self.interfaces.get(type_name) {
+            return interface.methods.iter()
+                .filter(|m| m.name == method && !m.modifiers.contains(&Modifier::Static))
+                .filter_map(|m| m.body.as_ref())
+                .find_map(|body| self.this_reason(type_name, &Flows::of_block(body)));
+        }
+
+        let class = self.classes.get(type_name)?;
+        if let Some(parent) = class.parent.as_deref().filter(|_| method == INIT) {
+            // 父类来自其他编译单元时看不到其构造函数，按逃逸处理
+            if !self.classes.contains_key(parent) {
+                return Some(format!("parent class '{}' is not declared in this program", parent));
+            }
+            if let Some(reason) = self.summary(parent, INIT) {
+                return Some(format!("the constructor of '{}' lets 'this' escape: {}", parent, reason));
+            }
+        }
+
+        for member in &class.members {
+            let flows = match member {
+                ClassMember::Method(m) if m.name == method && !m.modifiers.contains(&Modifier::Static) => {
+                    if m.modifiers.contains(&Modifier::Native) {
+                        return Some(format!("'{}.{}' is a native method", type_name, method));
+                    }
+                    match &m.body {
+                        Some(body) => Flows::of_block(body),
+                        None => continue,
+                    }
+                }
+                ClassMember::Constructor(ctor) if method == INIT => {
+                    let mut flows = Flows::of_block(&ctor.body);
+                    match &ctor.constructor_call {
+                        Some(ConstructorCall::This(args)) | Some(ConstructorCall::Super(args)) => {
+                            for arg in args {
+                                flows.expr(arg, Sink::ConstructorArgument(type_name.to_string()));
+                            }
+                        }
+                        None => {}
+                    }
+                    flows
+                }
+                ClassMember::Field(field) if method == INIT => {
+                    let mut flows = Flows::default();
+                    if let Some(init) = &field.initializer {
+                        flows.expr(init, Sink::Field(field.name.clone()));
+                    }
+                    flows
+                }
+                ClassMember::InstanceInitializer(block) if method == INIT => Flows::of_block(block),
+                ClassMember::Destructor(dtor) if method == DTOR => Flows::of_block(&dtor.body),
+                _ => continue,
+            };
+            if let Some(reason) = self.this_reason(type_name, &flows) {
+                return Some(reason);
+            }
+        }
+        None
+    }
+
+    /// 代码中的 `this`（包括隐式 this 方法调用和 `super`）是否逃逸
+    fn this_reason(&self, type_name: &str, flows: &Flows) -> Option<String> {
+        if flows.inline_ir {
+            return Some("contains inline IR".to_string());
+        }
+        if flows.closures {
+            return Some("contains a lambda or method reference".to_string());
+        }
+        let parent = self.classes.get(type_name).and_then(|c| c.parent.as_deref());
+        flows.values.iter().find_map(|(expr, sink)| {
+            let Expr::Identifier(ident) = expr else { return None };
+            match (ident.name.as_str(), sink) {
+                ("this", Sink::Receiver(method)) => self.method_reason(type_name, method),
+                ("super", Sink::Receiver(method)) => self.method_reason(parent.unwrap_or(type_name), method),
+                ("this" | "super", sink) => sink.escape_reason(),
+                (name, Sink::Callee) if self.has_method(type_name, name) => self.method_reason(type_name, name),
+                _ => None,
+            }
+        })
+    }
+
+    /// 以 `class_name` 的实例为接收者调用 `method` 是否让接收者逃逸
+    fn method_reason(&self, class_name: &str, method: &str) -> Option<String> {
+        if !self.has_method(class_name, method) {
+            return Some(format!("calls '{}', which is not a method of '{}'", method, class_name));
+        }
+        self.supertypes(class_name).into_iter().find_map(|type_name| {
+            self.summary(type_name, method)
+                .map(|reason| format!("'{}.{}' lets 'this' escape: {}", type_name, method, reason))
+        })
+    }
+
+    fn has_method(&self, class_name: &str, method: &str) -> bool {
+        self.supertypes(class_name).into_iter()
+            .any(|t| self.summaries.contains_key(&(t.to_string(), method.to_string())))
+    }
+
+    /// 类本身、程序中声明的父类和实现的接口（含父接口）
+    fn supertypes(&self, class_name: &str) -> Vec<&'a str> {
+        let mut types: Vec<&'a str> = Vec::new();
+        let mut current = self.classes.get(class_name).copied();
+        while let Some(class) = current {
+            types.push(&class.name);
+            current = class.parent.as_deref().and_then(|p| self.classes.get(p).copied());
+        }
+        let mut queue: Vec<&'a str> = types.iter()
+            .flat_map(|c| self.classes[c].interfaces.iter().map(String::as_str))
+            .collect();
+        while let Some(name) = queue.pop() {
+            let Some(interface) = self.interfaces.get(name).copied() else { continue };
+            if !types.contains(&interface.name.as_str()) {
+                types.push(&interface.name);
+                queue.extend(interface.parents.iter().map(String::as_str));
+            }
+        }
+        types
+    }
+
+    /// 类及其父类中最近声明析构函数的类
+    fn destructor_class(&self, class_name: &str) -> Option<&'a str> {
+        let mut current = self.classes.get(class_name).copied();
+        while let Some(class) = current {
+            if class.members.iter().any(|m| matches!(m, ClassMember::Destructor(_))) {
+                return Some(&class.name);
+            }
+            current = class.parent.as_deref().and_then(|p| self.classes.get(p).copied());
+        }
+        None
+    }
+
+    fn destructor_of(&self, class_name: &str) -> Option<String> {
+        self.destructor_class(class_name).map(|c| format!("{}.__dtor", c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EscapeAnalysis;
+    use crate::{lexer, parser};
+
+    const SOURCE: &str = r#"public class Point {
+    public int x;
+    public Point next;
+    public Point(int x) {
+        this.x = x;
+    }
+    public int getX() {
+        return x;
+    }
+    public void link(Point other) {
+        other.next = this;
+    }
+    public Point self() {
+        return this;
+    }
+    public int twice() {
+        return getX() * 2;
+    }
+}
+public class Main {
+    static Point saved;
+    public static Point make() {
+        Point p = new Point(1);
+        return p;
+    }
+    public static void main() {
+        Point a = new Point(2);
+        println(a.twice());
+        Point b = new Point(3);
+        Point c = new Point(4);
+        b.link(c);
+        Point d = new Point(5);
+        d.self();
+        Point e = new Point(6);
+        saved = e;
+        println(new Point(7).getX());
+        Point f = new Point(8);
+        if (f != null) {
+            f.x = f.getX() + 1;
+        }
+    }
+}"#;
+
+    #[test]
+    fn test_escape_remarks() {
+        let tokens = lexer::lex(SOURCE).unwrap();
+        let program = parser::parse_with_source(tokens, SOURCE.to_string()).unwrap();
+        let info = EscapeAnalysis::new(&program).analyze();
+
+        let remarks: Vec<(usize, String)> = info.remarks().iter().map(|r| (r.loc.line, r.message())).collect();
+        assert_eq!(remarks, vec![
+            (23, "'new Point' escapes: returned from the function".to_string()),
+            (27, "'new Point' allocated on the stack".to_string()),
+            (29, "'new Point' escapes: 'Point.link' lets 'this' escape: stored into field 'next'".to_string()),
+            (30, "'new Point' escapes: passed to call 'link'".to_string()),
+            (32, "'new Point' escapes: 'Point.self' lets 'this' escape: returned from the function".to_string()),
+            (34, "'new Point' escapes: stored into variable 'saved'".to_string()),
+            (36, "'new Point' escapes: the result is not bound to a local variable".to_string()),
+            (37, "'new Point' allocated on the stack".to_string()),
+        ]);
+
+        let stack = info.remarks().iter().filter(|r| r.reason.is_none())
+            .map(|r| info.stack_allocation(&r.loc).unwrap())
+            .map(|s| (s.index, s.destructor.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(stack, vec![(0, None), (1, None)]);
+    }
+}
@@ -7,7 +7,7 @@ use super::analyzer::SemanticAnalyzer;
 use super::symbol_table::SemanticSymbolInfo;
 
 /// 辅助函数：根据SourceLocation创建语义错误
-fn semantic_error_at_loc(loc: &crate::error::SourceLocation, message: impl Into<String>) -> crate::error::cayError {
+pub(super) fn semantic_error_at_loc(loc: &crate::error::SourceLocation, message: impl Into<String>) -> crate::error::cayError {
     semantic_error_with_file(loc.file.clone(), loc.line, loc.column, message)
 }
 
@@ -131,6 +131,7 @@ impl SemanticAnalyzer {
             Expr::Lambda(lambda) => self.infer_lambda_type(lambda),
             Expr::Ternary(ternary) => self.infer_ternary_type(ternary),
             Expr::InstanceOf(instanceof) => self.infer_instanceof_type(instanceof),
+            Expr::SizeOf(size_of) => self.infer_sizeof_type(size_of),
             Expr::Alloc(_) => Ok(Type::Int64),  // 0.5.0.0: alloc 返回 long (指针)
             Expr::Dealloc(_) => Ok(Type::Void), // 0.5.0.0: dealloc 返回 void
         }
@@ -304,6 +305,8 @@ impl SemanticAnalyzer {
                 "readLine" => return Ok(Type::String),
                 "readChar" => return Ok(Type::Char),
                 "readBool" => return Ok(Type::Bool),
+                // 测试断言
+                "assertEquals" | "assertTrue" | "fail" => return self.infer_assertion_type(name.as_str(), call),
                 // 运行时辅助函数
                 "__cay_read_ptr" => {
                     // 检查参数数量
@@ -443,6 +446,15 @@ impl SemanticAnalyzer {
 
         // 支持成员调用: obj.method(...) 或 ClassName.method()（静态方法）
         if let Expr::MemberAccess(member) = call.callee.as_ref() {
+            // Math.addExact() 等溢出检查内建函数（Math 不是变量时）
+            if let Expr::Identifier(class_name) = member.object.as_ref() {
+                if self.symbol_table.lookup(class_name.as_ref()).is_none() {
+                    if let Some(op) = self.type_registry.math_exact_intrinsic(class_name.as_ref(), &member.member) {
+                        return self.infer_math_exact_call(op, call);
+                    }
+                }
+            }
+
             // 推断对象类型
             let obj_type = self.infer_expr_type(&member.object)?;
 
@@ -1012,8 +1024,15 @@ impl SemanticAnalyzer {
         if self.is_subtype_of_by_name(type_b, type_a) {
             return true;
         }
-        
-        false
+
+        // 接口与非 final 类之间的转换在运行时检查（子类可能实现该接口）
+        let registry = &self.type_registry;
+        let may_implement = |iface: &str, other: &str| {
+            registry.interface_exists(iface)
+                && (registry.interface_exists(other)
+                    || registry.get_class(other).is_some_and(|c| !c.is_final))
+        };
+        may_implement(type_a, type_b) || may_implement(type_b, type_a)
     }
     
     /// 通过类型名称检查子类型关系
@@ -1028,9 +1047,15 @@ impl SemanticAnalyzer {
         // 所有类都是 Object 的子类型
         if supertype == "Object" {
             return self.type_registry.class_exists(subtype)
+                || self.type_registry.interface_exists(subtype)
                 || subtype == "String"
                 || subtype == "Function";
         }
+
+        // 接口：检查类（或子接口）是否实现了该接口
+        if self.type_registry.interface_exists(supertype) {
+            return self.type_registry.implements_interface(subtype, supertype);
+        }
         
         // 迭代遍历继承链
         let mut current = subtype.to_string();
@@ -1343,6 +1368,68 @@ impl SemanticAnalyzer {
         Ok(Type::Bool)
     }
 
+    /// 推断 sizeof 表达式类型：类型在目标平台上必须有确定的大小，结果为 long
+    fn infer_sizeof_type(&mut self, size_of: &SizeOfExpr) -> cayResult<Type> {
+        if let Type::Object(class_name) = &size_of.target_type {
+            if !self.type_registry.class_exists(class_name) && !self.type_registry.interface_exists(class_name) {
+                return Err(semantic_error_at_loc(&size_of.loc, format!("Unknown type in sizeof: {}", class_name)));
+            }
+        }
+        if self.target.size_of(&size_of.target_type).is_none() {
+            return Err(semantic_error_at_loc(&size_of.loc, format!(
+                "sizeof cannot be applied to type {}: its size is not known on target {}",
+                size_of.target_type, self.target.triple)));
+        }
+        Ok(Type::Int64)
+    }
+
+    /// 检查测试断言的参数：`assertEquals(expected, actual[, message])`、
+    /// `assertTrue(condition[, message])`、`fail([message])`
+    fn infer_assertion_type(&mut self, name: &str, call: &CallExpr) -> cayResult<Type> {
+        let (required, value_args) = match name {
+            "assertEquals" => (2, 2),
+            "assertTrue" => (1, 1),
+            _ => (0, 0),
+        };
+        if call.args.len() < required || call.args.len() > value_args + 1 {
+            return Err(semantic_error_at_loc(&call.loc, format!(
+                "Function '{}' requires {} argument(s) and an optional message, but got {}",
+                name, required, call.args.len()
+            )));
+        }
+        let mut types = Vec::new();
+        for arg in &call.args {
+            types.push(self.infer_expr_type(arg)?);
+        }
+        if let Some(message) = types.get(value_args).filter(|message| *message.non_null() != Type::String) {
+            return Err(semantic_error_at_loc(&call.loc, format!(
+                "The message argument of '{}' must be a string, but got '{}'", name, message
+            )));
+        }
+        match name {
+            "assertEquals" => {
+                let (expected, actual) = (types[0].non_null(), types[1].non_null());
+                let comparable = (Self::is_numeric_type_helper(expected) && Self::is_numeric_type_helper(actual))
+                    || self.types_compatible(expected, actual)
+                    || self.types_compatible(actual, expected);
+                if !comparable {
+                    return Err(semantic_error_at_loc(&call.loc, format!(
+                        "assertEquals cannot compare '{}' with '{}'", types[0], types[1]
+                    )));
+                }
+            }
+            "assertTrue" => {
+                if *types[0].non_null() != Type::Bool {
+                    return Err(semantic_error_at_loc(&call.loc, format!(
+                        "assertTrue requires a boolean condition, but got '{}'", types[0]
+                    )));
+                }
+            }
+            _ => {}
+        }
+        Ok(Type::Void)
+    }
+
     /// 辅助方法：检查类型是否为数值类型
     fn is_numeric_type_helper(ty: &Type) -> bool {
         matches!(ty, 
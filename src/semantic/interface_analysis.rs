@@ -0,0 +1,400 @@
+//! 接口分析：接口继承、接口实现检查和 default 方法冲突检测
+
+use std::collections::HashSet;
+use crate::ast::{Program, InterfaceDecl, ClassDecl, MethodDecl};
+use crate::types::{MethodInfo, Type};
+use crate::error::SourceLocation;
+use super::analyzer::SemanticAnalyzer;
+
+/// 方法签名：方法名 + 参数类型（接口方法按签名匹配实现）
+type MethodSig = (String, Vec<Type>);
+
+fn method_sig(method: &MethodInfo) -> MethodSig {
+    (method.name.clone(), method.params.iter().map(|p| p.param_type.clone()).collect())
+}
+
+fn format_sig((name, params): &MethodSig) -> String {
+    let params: Vec<String> = params.iter().map(|t| t.to_string()).collect();
+    format!("{}({})", name, params.join(", "))
+}
+
+impl SemanticAnalyzer {
+    /// 检查接口相关规则
+    /// 1. 父接口和 implements 列表中的名字必须是已定义的接口
+    /// 2. 检测循环的接口继承
+    /// 3. 从无关接口继承到冲突的 default 方法时，子接口或类必须重写
+    /// 4. 非抽象类必须实现所有没有 default 实现的接口方法
+    /// 5. 实现方法的返回类型必须与接口方法兼容
+    pub fn check_interfaces(&mut self, program: &Program) {
+        let error_count = self.errors.len();
+
+        for interface in &program.interfaces {
+            for parent in &interface.parents {
+                if self.type_registry.interface_exists(parent) {
+                    continue;
+                }
+                let message = if self.type_registry.class_exists(parent) {
+                    format!("Interface '{}' cannot extend class '{}'", interface.name, parent)
+                } else {
+                    format!("Interface '{}' extends undefined interface '{}'", interface.name, parent)
+                };
+                self.push_interface_error(&interface.loc, message);
+            }
+            if self.has_circular_interface_inheritance(&interface.name) {
+                self.push_interface_error(&interface.loc, format!(
+                    "Circular interface inheritance detected involving interface '{}'", interface.name));
+            }
+        }
+
+        for class in &program.classes {
+            for name in &class.interfaces {
+                if self.type_registry.interface_exists(name) {
+                    continue;
+                }
+                let message = if self.type_registry.class_exists(name) {
+                    format!("Class '{}' cannot implement class '{}'; use 'extends' instead", class.name, name)
+                } else {
+                    format!("Class '{}' implements undefined interface '{}'", class.name, name)
+                };
+                self.push_interface_error(&class.loc, message);
+            }
+        }
+
+        // 类型层次本身有误时，后续检查只会产生连带错误
+        if self.errors.len() > error_count {
+            return;
+        }
+
+        for interface in &program.interfaces {
+            self.check_interface_inherited_methods(interface);
+        }
+        for class in &program.classes {
+            self.check_class_interface_methods(class);
+        }
+    }
+
+    /// 检查方法是否实现了类所实现接口中的某个方法（用于 @Override 验证）
+    pub(super) fn implements_interface_method(&self, class_name: &str, method: &MethodDecl) -> bool {
+        self.type_registry.implemented_interfaces(class_name).iter()
+            .filter_map(|i| self.type_registry.get_interface(i)?.methods.get(&method.name))
+            .any(|m| {
+                m.params.len() == method.params.len()
+                    && m.params.iter().zip(&method.params).all(|(a, b)| a.param_type == b.param_type)
+            })
+    }
+
+    /// 沿父接口查找是否能回到自身
+    fn has_circular_interface_inheritance(&self, name: &str) -> bool {
+        let mut stack: Vec<String> = self.type_registry.get_interface(name)
+            .map(|i| i.parents.clone())
+            .unwrap_or_default();
+        let mut visited = HashSet::new();
+        while let Some(current) = stack.pop() {
+            if current == name {
+                return true;
+            }
+            if !visited.insert(current.clone()) {
+                continue;
+            }
+            if let Some(info) = self.type_registry.get_interface(&current) {
+                stack.extend(info.parents.iter().cloned());
+            }
+        }
+        false
+    }
+
+    /// 检查子接口从父接口继承的方法
+    fn check_interface_inherited_methods(&mut self, interface: &InterfaceDecl) {
+        let ancestors: Vec<String> = self.type_registry.implemented_interfaces(&interface.name)
+            .into_iter()
+            .filter(|i| *i != interface.name)
+            .collect();
+
+        for (sig, inherited) in self.interface_methods_by_sig(&ancestors) {
+            let own = self.type_registry.get_interface(&interface.name)
+                .and_then(|i| i.methods.get(&sig.0))
+                .filter(|m| method_sig(m) == sig)
+                .cloned();
+            match own {
+                // 重新声明（或重写 default）父接口方法时，返回类型必须兼容
+                Some(own) => {
+                    for method in &inherited {
+                        self.check_return_type(&own, method, &interface.loc);
+                    }
+                }
+                None => self.check_inherited_candidates("Interface", &interface.name, &sig, inherited, &interface.loc, false),
+            }
+        }
+    }
+
+    /// 检查类对接口方法的实现
+    fn check_class_interface_methods(&mut self, class: &ClassDecl) {
+        let class_is_abstract = self.type_registry.get_class(&class.name)
+            .map(|c| c.is_abstract)
+            .unwrap_or(false);
+        let interfaces = self.type_registry.implemented_interfaces(&class.name);
+
+        for (sig, inherited) in self.interface_methods_by_sig(&interfaces) {
+            let Some(implementation) = self.find_class_implementation(&class.name, &sig) else {
+                self.check_inherited_candidates("Class", &class.name, &sig, inherited, &class.loc, !class_is_abstract);
+                continue;
+            };
+
+            if implementation.is_abstract {
+                // 抽象父类中的抽象声明不算实现
+                if !class_is_abstract {
+                    self.report_missing_implementation(&class.name, &sig, &inherited, &class.loc);
+                }
+                continue;
+            }
+            for method in &inherited {
+                self.check_return_type(&implementation, method, &class.loc);
+            }
+            if implementation.is_static {
+                let info = self.create_error_info_with_file(
+                    class.loc.file.clone(), class.loc.line, class.loc.column,
+                    format!("Static method '{}.{}' is used to implement interface method '{}'; it will not receive 'this'",
+                        implementation.class_name, format_sig(&sig), format_sig(&sig)),
+                );
+                self.warnings.push(info);
+            }
+        }
+    }
+
+    /// 检查没有被显式实现的继承方法：冲突的 default 方法，以及缺失的实现
+    fn check_inherited_candidates(
+        &mut self,
+        kind: &str,
+        type_name: &str,
+        sig: &MethodSig,
+        inherited: Vec<MethodInfo>,
+        loc: &SourceLocation,
+        require_implementation: bool,
+    ) {
+        if let Some((first, other)) = inherited.iter()
+            .flat_map(|a| inherited.iter().map(move |b| (a, b)))
+            .find(|(a, b)| a.return_type != b.return_type)
+        {
+            self.push_interface_error(loc, format!(
+                "{} '{}' inherits '{}' with incompatible return types from interfaces '{}' ({}) and '{}' ({})",
+                kind, type_name, format_sig(sig), first.class_name, first.return_type, other.class_name, other.return_type));
+            return;
+        }
+
+        // 被子接口覆盖的父接口方法不参与冲突判断
+        let specific: Vec<MethodInfo> = inherited.iter()
+            .filter(|m| !inherited.iter().any(|o| {
+                o.class_name != m.class_name
+                    && self.type_registry.implements_interface(&o.class_name, &m.class_name)
+            }))
+            .cloned()
+            .collect();
+
+        if specific.len() > 1 && specific.iter().any(|m| !m.is_abstract) {
+            let names: Vec<&str> = specific.iter().map(|m| m.class_name.as_str()).collect();
+            self.push_interface_error(loc, format!(
+                "{} '{}' inherits conflicting default methods for '{}' from interfaces '{}'; it must override '{}'",
+                kind, type_name, format_sig(sig), names.join("', '"), sig.0));
+            return;
+        }
+
+        if require_implementation && specific.iter().all(|m| m.is_abstract) {
+            self.report_missing_implementation(type_name, sig, &specific, loc);
+        }
+    }
+
+    fn report_missing_implementation(&mut self, class_name: &str, sig: &MethodSig, inherited: &[MethodInfo], loc: &SourceLocation) {
+        let interface = inherited.first().map(|m| m.class_name.as_str()).unwrap_or("?");
+        self.push_interface_error(loc, format!(
+            "Class '{}' must implement abstract method '{}' from interface '{}' or be declared abstract",
+            class_name, format_sig(sig), interface));
+    }
+
+    /// 实现方法（或子接口重新声明的方法）的返回类型必须与接口方法一致，引用类型允许协变
+    fn check_return_type(&mut self, implementation: &MethodInfo, declared: &MethodInfo, loc: &SourceLocation) {
+        let compatible = implementation.return_type == declared.return_type
+            || (implementation.return_type.is_reference_type()
+                && self.types_compatible(&implementation.return_type, &declared.return_type));
+        if !compatible {
+            self.push_interface_error(loc, format!(
+                "Method '{}.{}' returns {}, which is incompatible with '{}.{}' returning {}",
+                implementation.class_name, implementation.name, implementation.return_type,
+                declared.class_name, declared.name, declared.return_type));
+        }
+    }
+
+    /// 按签名收集一组接口中声明的方法（按方法名排序，保证错误顺序稳定）
+    fn interface_methods_by_sig(&self, interfaces: &[String]) -> Vec<(MethodSig, Vec<MethodInfo>)> {
+        let mut grouped: Vec<(MethodSig, Vec<MethodInfo>)> = Vec::new();
+        for name in interfaces {
+            let Some(info) = self.type_registry.get_interface(name) else { continue };
+            for method in info.methods.values() {
+                let sig = method_sig(method);
+                match grouped.iter_mut().find(|(s, _)| *s == sig) {
+                    Some((_, methods)) => methods.push(method.clone()),
+                    None => grouped.push((sig, vec![method.clone()])),
+                }
+            }
+        }
+        grouped.sort_by(|a, b| a.0.0.cmp(&b.0.0));
+        grouped
+    }
+
+    /// 沿类继承链查找签名完全一致的方法
+    fn find_class_implementation(&self, class_name: &str, sig: &MethodSig) -> Option<MethodInfo> {
+        let mut visited = HashSet::new();
+        let mut current = self.type_registry.get_class(class_name);
+        while let Some(class_info) = current {
+            if !visited.insert(class_info.name.clone()) {
+                break;
+            }
+            if let Some(method) = class_info.methods.get(&sig.0)
+                .and_then(|methods| methods.iter().find(|m| method_sig(m) == *sig))
+            {
+                return Some(method.clone());
+            }
+            current = class_info.parent.as_ref().and_then(|p| self.type_registry.get_class(p));
+        }
+        None
+    }
+
+    fn push_interface_error(&mut self, loc: &SourceLocation, message: String) {
+        let info = self.create_error_info_with_file(loc.file.clone(), loc.line, loc.column, message);
+        self.errors.push(info);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::lex;
+    use crate::parser::parse;
+    use crate::semantic::SemanticAnalyzer;
+
+    fn analyze(source: &str) -> Result<(), String> {
+        let program = parse(lex(source).unwrap()).unwrap();
+        SemanticAnalyzer::new().analyze(&program).map_err(|e| e.to_string())
+    }
+
+    const MAIN: &str = r#"
+public class Main {
+    public static void main() {
+    }
+}
+"#;
+
+    #[test]
+    fn test_default_methods_and_interface_inheritance() {
+        let source = format!(r#"
+interface Named {{
+    String name();
+    default String greet() {{
+        return "Hello, " + name();
+    }}
+}}
+interface Shape extends Named {{
+    int area();
+}}
+class Square implements Shape {{
+    public String name() {{ return "Square"; }}
+    @Override
+    public int area() {{ return 9; }}
+}}
+{}"#, MAIN);
+        analyze(&source).unwrap();
+    }
+
+    #[test]
+    fn test_missing_interface_method_is_reported() {
+        let source = format!(r#"
+interface Named {{
+    String name();
+}}
+interface Shape extends Named {{
+    int area();
+}}
+class Square implements Shape {{
+    public int area() {{ return 9; }}
+}}
+{}"#, MAIN);
+        let error = analyze(&source).unwrap_err();
+        assert!(error.contains("Class 'Square' must implement abstract method 'name()' from interface 'Named'"), "{}", error);
+    }
+
+    #[test]
+    fn test_abstract_class_may_leave_methods_unimplemented() {
+        let source = format!(r#"
+interface Named {{
+    String name();
+}}
+public abstract class Base implements Named {{
+}}
+{}"#, MAIN);
+        analyze(&source).unwrap();
+    }
+
+    #[test]
+    fn test_conflicting_defaults_must_be_overridden() {
+        let interfaces = r#"
+interface A {
+    default int value() { return 1; }
+}
+interface B {
+    default int value() { return 2; }
+}
+"#;
+        let source = format!("{}class C implements A, B {{\n}}\n{}", interfaces, MAIN);
+        let error = analyze(&source).unwrap_err();
+        assert!(error.contains("Class 'C' inherits conflicting default methods for 'value()' from interfaces"), "{}", error);
+
+        let source = format!("{}interface D extends A, B {{\n}}\n{}", interfaces, MAIN);
+        let error = analyze(&source).unwrap_err();
+        assert!(error.contains("Interface 'D' inherits conflicting default methods"), "{}", error);
+
+        let source = format!("{}class C implements A, B {{\n    public int value() {{ return 3; }}\n}}\n{}", interfaces, MAIN);
+        analyze(&source).unwrap();
+    }
+
+    #[test]
+    fn test_sub_interface_default_wins_over_parent() {
+        let source = format!(r#"
+interface A {{
+    default int value() {{ return 1; }}
+}}
+interface B extends A {{
+    default int value() {{ return 2; }}
+}}
+class C implements A, B {{
+}}
+{}"#, MAIN);
+        analyze(&source).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_interface_hierarchy() {
+        let source = format!("class K {{\n}}\ninterface I extends K {{\n}}\n{}", MAIN);
+        let error = analyze(&source).unwrap_err();
+        assert!(error.contains("Interface 'I' cannot extend class 'K'"), "{}", error);
+
+        let source = format!("interface I extends Missing {{\n}}\n{}", MAIN);
+        let error = analyze(&source).unwrap_err();
+        assert!(error.contains("Interface 'I' extends undefined interface 'Missing'"), "{}", error);
+
+        let source = format!("interface I extends J {{\n}}\ninterface J extends I {{\n}}\n{}", MAIN);
+        let error = analyze(&source).unwrap_err();
+        assert!(error.contains("Circular interface inheritance"), "{}", error);
+    }
+
+    #[test]
+    fn test_incompatible_return_type() {
+        let source = format!(r#"
+interface Named {{
+    String name();
+}}
+class Thing implements Named {{
+    public int name() {{ return 1; }}
+}}
+{}"#, MAIN);
+        let error = analyze(&source).unwrap_err();
+        assert!(error.contains("Method 'Thing.name' returns int"), "{}", error);
+    }
+}
@@ -7,10 +7,17 @@
 mod symbol_table;
 mod analyzer;
 mod class_analysis;
+mod interface_analysis;
 mod type_check;
 mod expr_inference;
 mod type_utils;
+mod null_safety;
+mod overflow;
+mod escape;
+mod packages;
 
 // 公开导出
 pub use symbol_table::{SemanticSymbolTable, SemanticSymbolInfo};
 pub use analyzer::{SemanticAnalyzer, SemanticErrorInfo};
+pub use null_safety::{NullSafetyChecker, NullSafetyIssue, Nullness, NULL_SAFETY_FEATURE};
+pub use escape::{EscapeAnalysis, EscapeInfo, EscapeRemark, StackAllocation};
@@ -0,0 +1,1065 @@
+//! 空安全检查
+//!
+//! 基于可空类型标注（`String?`、`Foo?`、`int[]?`）的流敏感分析：
+//! - 未标注 `?` 的引用类型视为非空，向其赋值/传递可能为 null 的值会被报告；
+//! - 可空局部变量在 `x != null`、`x instanceof T` 条件成立的分支内、
+//!   以及 `if (x == null) return;` 之类的提前返回之后被收窄为非空；
+//! - 对可能为 null 的值进行成员访问、方法调用或数组下标访问会被报告。
+//!
+//! 默认运行在仅警告模式，旧代码照常编译；启用 `-F=null_safety` 后问题作为错误报告。
+
+use std::collections::HashMap;
+use crate::ast::*;
+use crate::error::SourceLocation;
+use crate::types::{Type, ParameterInfo};
+use super::analyzer::SemanticAnalyzer;
+
+/// 启用严格空安全检查的语言特性名
+pub const NULL_SAFETY_FEATURE: &str = "null_safety";
+
+/// 表达式在某一程序点的可空状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nullness {
+    NonNull,
+    Null,
+    MaybeNull,
+}
+
+impl Nullness {
+    fn join(self, other: Nullness) -> Nullness {
+        if self == other { self } else { Nullness::MaybeNull }
+    }
+
+    fn may_be_null(self) -> bool {
+        self != Nullness::NonNull
+    }
+}
+
+/// 空安全检查发现的问题
+#[derive(Debug, Clone, PartialEq)]
+pub struct NullSafetyIssue {
+    pub loc: SourceLocation,
+    pub message: String,
+}
+
+/// 局部变量的声明类型与当前流状态
+#[derive(Debug, Clone, PartialEq)]
+struct Local {
+    ty: Type,
+    /// 是否参与流分析（声明为 T? 或由 auto/var 推断）
+    tracked: bool,
+    state: Nullness,
+}
+
+type State = Vec<HashMap<String, Local>>;
+
+/// 条件表达式成立/不成立时可得出的变量状态
+#[derive(Default)]
+struct Facts {
+    when_true: Vec<(String, Nullness)>,
+    when_false: Vec<(String, Nullness)>,
+}
+
+impl Facts {
+    fn swap(self) -> Facts {
+        Facts { when_true: self.when_false, when_false: self.when_true }
+    }
+}
+
+/// 合并两个分支的状态（作用域深度不同时只保留公共外层）
+fn join_states(a: &State, b: &State) -> State {
+    a.iter().zip(b.iter()).map(|(sa, sb)| {
+        sa.iter().map(|(name, local)| {
+            let mut merged = local.clone();
+            if let Some(other) = sb.get(name) {
+                merged.state = local.state.join(other.state);
+            }
+            (name.clone(), merged)
+        }).collect()
+    }).collect()
+}
+
+/// 空安全检查器
+pub struct NullSafetyChecker<'a> {
+    program: &'a Program,
+    classes: HashMap<&'a str, &'a ClassDecl>,
+    top_level_functions: &'a [TopLevelFunction],
+    current_class: Option<&'a ClassDecl>,
+    current_function: String,
+    /// 当前函数的声明位置，报告没有位置信息的返回值（如 `return null;`）时使用
+    function_loc: SourceLocation,
+    return_type: Option<Type>,
+    scopes: State,
+    break_states: Vec<Vec<State>>,
+    continue_states: Vec<Vec<State>>,
+    reporting: bool,
+    issues: Vec<NullSafetyIssue>,
+}
+
+impl<'a> NullSafetyChecker<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Self {
+            program,
+            classes: program.classes.iter().map(|c| (c.name.as_str(), c)).collect(),
+            top_level_functions: &program.top_level_functions,
+            current_class: None,
+            current_function: String::new(),
+            function_loc: SourceLocation::default(),
+            return_type: None,
+            scopes: Vec::new(),
+            break_states: Vec::new(),
+            continue_states: Vec::new(),
+            reporting: true,
+            issues: Vec::new(),
+        }
+    }
+
+    /// 检查整个程序，返回发现的全部问题
+    pub fn check(mut self) -> Vec<NullSafetyIssue> {
+        let program = self.program;
+        for func in &program.top_level_functions {
+            self.current_class = None;
+            self.enter_function(&func.name, &func.params, Some(&func.return_type), &func.loc);
+            self.check_block(&func.body);
+        }
+
+        for class in &program.classes {
+            self.current_class = Some(class);
+            for member in &class.members {
+                match member {
+                    ClassMember::Field(field) => {
+                        if let Some(init) = &field.initializer {
+                            self.enter_function(&field.name, &[], None, &field.loc);
+                            let value = self.eval(init);
+                            self.check_assignable(&field.field_type, value, &field.loc, || {
+                                format!("非空字段 '{}'", field.name)
+                            });
+                        }
+                    }
+                    ClassMember::Method(method) => {
+                        if let Some(body) = &method.body {
+                            self.enter_function(&method.name, &method.params, Some(&method.return_type), &method.loc);
+                            self.check_block(body);
+                        }
+                    }
+                    ClassMember::Constructor(ctor) => {
+                        self.enter_function(&class.name, &ctor.params, None, &ctor.loc);
+                        match &ctor.constructor_call {
+                            Some(ConstructorCall::This(args)) => {
+                                let params = self.find_constructor(&class.name, args.len());
+                                self.check_args(&class.name, params, args, &ctor.loc);
+                            }
+                            Some(ConstructorCall::Super(args)) => {
+                                let params = class.parent.as_deref()
+                                    .and_then(|parent| self.find_constructor(parent, args.len()));
+                                self.check_args("super", params, args, &ctor.loc);
+                            }
+                            None => {}
+                        }
+                        self.check_block(&ctor.body);
+                    }
+                    ClassMember::Destructor(dtor) => {
+                        self.enter_function(&format!("~{}", class.name), &[], None, &dtor.loc);
+                        self.check_block(&dtor.body);
+                    }
+                    ClassMember::InstanceInitializer(block) | ClassMember::StaticInitializer(block) => {
+                        self.enter_function(&class.name, &[], None, &block.loc);
+                        self.check_block(block);
+                    }
+                }
+            }
+        }
+
+        self.issues
+    }
+
+    fn enter_function(&mut self, name: &str, params: &[ParameterInfo], return_type: Option<&Type>, loc: &SourceLocation) {
+        self.current_function = name.to_string();
+        self.function_loc = loc.clone();
+        self.return_type = return_type.cloned();
+        self.break_states.clear();
+        self.continue_states.clear();
+        let mut scope = HashMap::new();
+        for param in params {
+            scope.insert(param.name.clone(), Local {
+                ty: param.param_type.clone(),
+                tracked: param.param_type.is_nullable(),
+                state: if param.param_type.is_nullable() { Nullness::MaybeNull } else { Nullness::NonNull },
+            });
+        }
+        self.scopes = vec![scope];
+    }
+
+    fn report(&mut self, loc: &SourceLocation, message: String) {
+        if !self.reporting {
+            return;
+        }
+        let issue = NullSafetyIssue { loc: loc.clone(), message };
+        if !self.issues.contains(&issue) {
+            self.issues.push(issue);
+        }
+    }
+
+    // ========== 作用域与流状态 ==========
+
+    fn lookup_local(&self, name: &str) -> Option<&Local> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn declare_local(&mut self, name: &str, local: Local) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), local);
+        }
+    }
+
+    fn set_state(&mut self, name: &str, state: Nullness) {
+        let local = self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(name));
+        if let Some(local) = local.filter(|local| local.tracked) {
+            local.state = state;
+        }
+    }
+
+    fn apply_facts(&mut self, facts: &[(String, Nullness)]) {
+        for (name, state) in facts {
+            self.set_state(name, *state);
+        }
+    }
+
+    // ========== 语句 ==========
+
+    /// 检查语句块，返回控制流是否可能从块尾继续执行
+    fn check_block(&mut self, block: &Block) -> bool {
+        self.check_stmts(&block.statements)
+    }
+
+    fn check_stmts(&mut self, stmts: &[Stmt]) -> bool {
+        self.scopes.push(HashMap::new());
+        let mut falls_through = true;
+        for stmt in stmts {
+            if !self.check_stmt(stmt) {
+                // 之后的语句不可达
+                falls_through = false;
+                break;
+            }
+        }
+        self.scopes.pop();
+        falls_through
+    }
+
+    fn check_stmt(&mut self, stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::Expr(expr) => {
+                self.eval(expr);
+                true
+            }
+            Stmt::VarDecl(var) => {
+                let value = match &var.initializer {
+                    Some(init) => self.eval(init),
+                    None => Nullness::Null,
+                };
+                let tracked = var.var_type.is_nullable() || var.var_type == Type::Auto;
+                if var.initializer.is_some() {
+                    self.check_assignable(&var.var_type, value, &var.loc, || {
+                        format!("非空类型 '{}' 的变量 '{}'", var.var_type, var.name)
+                    });
+                }
+                self.declare_local(&var.name, Local {
+                    ty: var.var_type.clone(),
+                    tracked,
+                    state: if tracked { value } else { Nullness::NonNull },
+                });
+                true
+            }
+            Stmt::Return(value) => {
+                if let Some(expr) = value {
+                    let nullness = self.eval(expr);
+                    let ret = self.return_type.clone().filter(|ret| requires_non_null(ret) && nullness.may_be_null());
+                    if let Some(ret) = ret {
+                        // 字面量（`return null;`）没有位置信息，报告在方法声明处
+                        let loc = match expr {
+                            Expr::Literal(_) => self.function_loc.clone(),
+                            _ => expr_loc(expr),
+                        };
+                        self.report(&loc, format!(
+                            "方法 '{}' 的返回类型 '{}' 不可为空，但返回值可能为 null\n提示: 将返回类型声明为 '{}?'",
+                            self.current_function, ret, ret
+                        ));
+                    }
+                }
+                false
+            }
+            Stmt::If(if_stmt) => {
+                let facts = self.eval_condition(&if_stmt.condition);
+                let entry = self.scopes.clone();
+
+                self.apply_facts(&facts.when_true);
+                let then_falls = self.check_stmt(&if_stmt.then_branch);
+                let then_state = std::mem::replace(&mut self.scopes, entry);
+
+                self.apply_facts(&facts.when_false);
+                let else_falls = match &if_stmt.else_branch {
+                    Some(else_branch) => self.check_stmt(else_branch),
+                    None => true,
+                };
+
+                match (then_falls, else_falls) {
+                    (true, true) => {
+                        self.scopes = join_states(&then_state, &self.scopes);
+                        true
+                    }
+                    (true, false) => {
+                        self.scopes = then_state;
+                        true
+                    }
+                    (false, true) => true,
+                    (false, false) => false,
+                }
+            }
+            Stmt::While(while_stmt) => {
+                self.check_loop(Some(&while_stmt.condition), &while_stmt.body, None, false)
+            }
+            Stmt::DoWhile(do_while) => {
+                self.check_loop(Some(&do_while.condition), &do_while.body, None, true)
+            }
+            Stmt::For(for_stmt) => {
+                self.scopes.push(HashMap::new());
+                if let Some(init) = &for_stmt.init {
+                    self.check_stmt(init);
+                }
+                let falls = self.check_loop(
+                    for_stmt.condition.as_ref(),
+                    &for_stmt.body,
+                    for_stmt.update.as_ref(),
+                    false,
+                );
+                self.scopes.pop();
+                falls
+            }
+            Stmt::Switch(switch) => {
+                self.eval(&switch.expr);
+                let entry = self.scopes.clone();
+                self.break_states.push(Vec::new());
+
+                let mut exits: Vec<State> = Vec::new();
+                let mut previous: Option<State> = None;
+                let bodies = switch.cases.iter().map(|c| &c.body).chain(switch.default.iter());
+                for body in bodies {
+                    // 上一个分支未 break 时会贯穿到当前分支
+                    self.scopes = match previous.take() {
+                        Some(fallthrough) => join_states(&entry, &fallthrough),
+                        None => entry.clone(),
+                    };
+                    if self.check_stmts(body) {
+                        previous = Some(self.scopes.clone());
+                    }
+                }
+                if let Some(last) = previous {
+                    exits.push(last);
+                }
+                if switch.default.is_none() {
+                    exits.push(entry.clone());
+                }
+                exits.extend(self.break_states.pop().unwrap_or_default());
+                self.merge_exits(exits, entry.len())
+            }
+            Stmt::Block(block) => self.check_block(block),
+            Stmt::Scope(scope) => self.check_block(&scope.body),
+            Stmt::Break(_) => {
+                let state = self.scopes.clone();
+                if let Some(states) = self.break_states.last_mut() {
+                    states.push(state);
+                }
+                false
+            }
+            Stmt::Continue(_) => {
+                let state = self.scopes.clone();
+                if let Some(states) = self.continue_states.last_mut() {
+                    states.push(state);
+                }
+                false
+            }
+            Stmt::InlineIr(_) | Stmt::Error(_) => true,
+        }
+    }
+
+    /// 将若干出口状态合并为当前状态，没有出口时返回 false（不可达）
+    fn merge_exits(&mut self, exits: Vec<State>, depth: usize) -> bool {
+        let mut iter = exits.into_iter();
+        match iter.next() {
+            Some(first) => {
+                let mut merged = first;
+                for state in iter {
+                    merged = join_states(&merged, &state);
+                }
+                merged.truncate(depth);
+                self.scopes = merged;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 检查循环：先在不报告的情况下迭代到不动点，再以不动点状态正式检查一遍
+    fn check_loop(&mut self, condition: Option<&Expr>, body: &Stmt, update: Option<&Expr>, body_first: bool) -> bool {
+        let entry = self.scopes.clone();
+        let depth = entry.len();
+        let saved_reporting = self.reporting;
+
+        self.reporting = false;
+        let mut head = entry.clone();
+        loop {
+            self.scopes = head.clone();
+            let (back_edge, _) = self.loop_iteration(condition, body, update, body_first);
+            let next = match back_edge {
+                Some(state) => {
+                    let mut joined = join_states(&entry, &state);
+                    joined.truncate(depth);
+                    joined
+                }
+                None => entry.clone(),
+            };
+            if next == head {
+                break;
+            }
+            head = next;
+        }
+        self.reporting = saved_reporting;
+
+        self.scopes = head;
+        let (_, exits) = self.loop_iteration(condition, body, update, body_first);
+        self.merge_exits(exits, depth)
+    }
+
+    /// 执行一次循环迭代，返回（回到循环头的状态，离开循环的状态列表）
+    fn loop_iteration(&mut self, condition: Option<&Expr>, body: &Stmt, update: Option<&Expr>, body_first: bool) -> (Option<State>, Vec<State>) {
+        let mut exits = Vec::new();
+        self.break_states.push(Vec::new());
+        self.continue_states.push(Vec::new());
+
+        let evaluate_condition = |this: &mut Self, exits: &mut Vec<State>| -> bool {
+            let Some(cond) = condition else { return true };
+            let facts = this.eval_condition(cond);
+            if !matches!(cond, Expr::Literal(LiteralValue::Bool(true))) {
+                let mut exit = this.scopes.clone();
+                std::mem::swap(&mut this.scopes, &mut exit);
+                this.apply_facts(&facts.when_false);
+                std::mem::swap(&mut this.scopes, &mut exit);
+                exits.push(exit);
+            }
+            if matches!(cond, Expr::Literal(LiteralValue::Bool(false))) {
+                return false;
+            }
+            this.apply_facts(&facts.when_true);
+            true
+        };
+
+        let mut back_edge = None;
+        if body_first {
+            let body_falls = self.check_stmt(body);
+            let continues = self.continue_states.last().cloned().unwrap_or_default();
+            let mut reached: Vec<State> = continues;
+            if body_falls {
+                reached.push(self.scopes.clone());
+            }
+            if let Some(first) = reached.first().cloned() {
+                self.scopes = reached.iter().skip(1).fold(first, |acc, s| join_states(&acc, s));
+                if evaluate_condition(self, &mut exits) {
+                    back_edge = Some(self.scopes.clone());
+                }
+            }
+        } else if evaluate_condition(self, &mut exits) {
+            let body_falls = self.check_stmt(body);
+            let continues = self.continue_states.last().cloned().unwrap_or_default();
+            let mut reached: Vec<State> = continues;
+            if body_falls {
+                reached.push(self.scopes.clone());
+            }
+            if let Some(first) = reached.first().cloned() {
+                self.scopes = reached.iter().skip(1).fold(first, |acc, s| join_states(&acc, s));
+                if let Some(update) = update {
+                    self.eval(update);
+                }
+                back_edge = Some(self.scopes.clone());
+            }
+        }
+
+        self.continue_states.pop();
+        exits.extend(self.break_states.pop().unwrap_or_default());
+        (back_edge, exits)
+    }
+
+    // ========== 表达式 ==========
+
+    /// 求值条件表达式，返回可用于收窄的事实
+    fn eval_condition(&mut self, expr: &Expr) -> Facts {
+        match expr {
+            Expr::Binary(bin) if matches!(bin.op, BinaryOp::Eq | BinaryOp::Ne) => {
+                let compared = match (bin.left.as_ref(), bin.right.as_ref()) {
+                    (Expr::Identifier(id), Expr::Literal(LiteralValue::Null))
+                    | (Expr::Literal(LiteralValue::Null), Expr::Identifier(id)) => Some(id.name.clone()),
+                    _ => None,
+                };
+                self.eval(&bin.left);
+                self.eval(&bin.right);
+                match compared {
+                    Some(name) if self.lookup_local(&name).is_some_and(|l| l.tracked) => {
+                        let facts = Facts {
+                            when_true: vec![(name.clone(), Nullness::NonNull)],
+                            when_false: vec![(name, Nullness::Null)],
+                        };
+                        // x != null 成立时非空；x == null 则相反
+                        if bin.op == BinaryOp::Ne { facts } else { facts.swap() }
+                    }
+                    _ => Facts::default(),
+                }
+            }
+            Expr::InstanceOf(instance) => {
+                self.eval(&instance.expr);
+                match instance.expr.as_ref() {
+                    Expr::Identifier(id) if self.lookup_local(&id.name).is_some_and(|l| l.tracked) => Facts {
+                        when_true: vec![(id.name.clone(), Nullness::NonNull)],
+                        when_false: Vec::new(),
+                    },
+                    _ => Facts::default(),
+                }
+            }
+            Expr::Binary(bin) if bin.op == BinaryOp::And => {
+                let left = self.eval_condition(&bin.left);
+                let saved = self.scopes.clone();
+                self.apply_facts(&left.when_true);
+                let right = self.eval_condition(&bin.right);
+                self.scopes = join_states(&saved, &self.scopes);
+                let mut when_true = left.when_true;
+                when_true.extend(right.when_true);
+                Facts { when_true, when_false: Vec::new() }
+            }
+            Expr::Binary(bin) if bin.op == BinaryOp::Or => {
+                let left = self.eval_condition(&bin.left);
+                let saved = self.scopes.clone();
+                self.apply_facts(&left.when_false);
+                let right = self.eval_condition(&bin.right);
+                self.scopes = join_states(&saved, &self.scopes);
+                let mut when_false = left.when_false;
+                when_false.extend(right.when_false);
+                Facts { when_true: Vec::new(), when_false }
+            }
+            Expr::Unary(unary) if unary.op == UnaryOp::Not => self.eval_condition(&unary.operand).swap(),
+            _ => {
+                self.eval(expr);
+                Facts::default()
+            }
+        }
+    }
+
+    /// 求值表达式并检查其中的解引用，返回结果的可空状态
+    fn eval(&mut self, expr: &Expr) -> Nullness {
+        match expr {
+            Expr::Literal(LiteralValue::Null) => Nullness::Null,
+            Expr::Literal(_) => Nullness::NonNull,
+            Expr::Identifier(id) => {
+                if let Some(local) = self.lookup_local(&id.name) {
+                    return local.state;
+                }
+                match self.current_class.and_then(|c| self.find_field(&c.name, &id.name)) {
+                    Some(field) if field.field_type.is_nullable() => Nullness::MaybeNull,
+                    _ => Nullness::NonNull,
+                }
+            }
+            Expr::Binary(bin) if matches!(bin.op, BinaryOp::And | BinaryOp::Or | BinaryOp::Eq | BinaryOp::Ne) => {
+                self.eval_condition(expr);
+                Nullness::NonNull
+            }
+            Expr::Binary(bin) => {
+                self.eval(&bin.left);
+                self.eval(&bin.right);
+                Nullness::NonNull
+            }
+            Expr::Unary(unary) => {
+                self.eval(&unary.operand);
+                Nullness::NonNull
+            }
+            Expr::Call(call) => self.eval_call(call),
+            Expr::MemberAccess(member) => {
+                let class_name = self.check_receiver(&member.object, &member.loc);
+                match class_name.and_then(|c| self.find_field(&c, &member.member)) {
+                    Some(field) if field.field_type.is_nullable() => Nullness::MaybeNull,
+                    _ => Nullness::NonNull,
+                }
+            }
+            Expr::New(new) => {
+                let params = self.find_constructor(&new.class_name, new.args.len());
+                self.check_args(&new.class_name, params, &new.args, &new.loc);
+                Nullness::NonNull
+            }
+            Expr::Assignment(assign) => self.eval_assignment(assign),
+            Expr::Cast(cast) => {
+                let value = self.eval(&cast.expr);
+                // 显式转换为非空类型视为断言
+                if cast.target_type.is_nullable() { value } else { Nullness::NonNull }
+            }
+            Expr::ArrayCreation(arr) => {
+                for size in &arr.sizes {
+                    self.eval(size);
+                }
+                Nullness::NonNull
+            }
+            Expr::ArrayAccess(access) => {
+                self.check_receiver(&access.array, &access.loc);
+                self.eval(&access.index);
+                Nullness::NonNull
+            }
+            Expr::ArrayInit(init) => {
+                for element in &init.elements {
+                    self.eval(element);
+                }
+                Nullness::NonNull
+            }
+            Expr::MethodRef(method_ref) => {
+                if let Some(object) = &method_ref.object {
+                    self.check_receiver(object, &method_ref.loc);
+                }
+                Nullness::NonNull
+            }
+            Expr::Lambda(lambda) => {
+                self.check_lambda(lambda);
+                Nullness::NonNull
+            }
+            Expr::Ternary(ternary) => {
+                let facts = self.eval_condition(&ternary.condition);
+                let entry = self.scopes.clone();
+                self.apply_facts(&facts.when_true);
+                let when_true = self.eval(&ternary.true_branch);
+                let true_state = std::mem::replace(&mut self.scopes, entry);
+                self.apply_facts(&facts.when_false);
+                let when_false = self.eval(&ternary.false_branch);
+                self.scopes = join_states(&true_state, &self.scopes);
+                when_true.join(when_false)
+            }
+            Expr::InstanceOf(_) => {
+                self.eval_condition(expr);
+                Nullness::NonNull
+            }
+            Expr::SizeOf(_) => Nullness::NonNull,
+            Expr::Alloc(alloc) => {
+                self.eval(&alloc.size);
+                if let Some(align) = &alloc.align {
+                    self.eval(align);
+                }
+                Nullness::NonNull
+            }
+            Expr::Dealloc(dealloc) => {
+                self.eval(&dealloc.ptr);
+                Nullness::NonNull
+            }
+        }
+    }
+
+    /// 检查被解引用的接收者，返回其静态类名（若可确定）
+    fn check_receiver(&mut self, object: &Expr, loc: &SourceLocation) -> Option<String> {
+        match object {
+            Expr::Identifier(id) if self.is_class_name(&id.name) => return Some(id.name.clone()),
+            _ => {}
+        }
+        let nullness = self.eval(object);
+        if nullness.may_be_null() {
+            let desc = describe(object);
+            let message = if nullness == Nullness::Null {
+                format!("值 '{}' 为 null，不能访问其成员", desc)
+            } else {
+                format!(
+                    "可能为 null 的值 '{}' 被解引用\n提示: 先使用 if ({} != null) 检查，或将其声明为非空类型",
+                    desc, desc
+                )
+            };
+            self.report(loc, message);
+        }
+        self.static_class_of(object)
+    }
+
+    fn eval_call(&mut self, call: &CallExpr) -> Nullness {
+        let (name, method) = match call.callee.as_ref() {
+            Expr::Identifier(id) => {
+                let method = self.current_class
+                    .and_then(|c| self.find_method(&c.name, &id.name, call.args.len()))
+                    .map(|m| (m.params.as_slice(), &m.return_type))
+                    .or_else(|| {
+                        self.top_level_functions.iter()
+                            .find(|f| f.name == id.name && f.params.len() == call.args.len())
+                            .map(|f| (f.params.as_slice(), &f.return_type))
+                    });
+                (id.name.clone(), method)
+            }
+            Expr::MemberAccess(member) => {
+                let class_name = self.check_receiver(&member.object, &member.loc);
+                let method = class_name
+                    .and_then(|c| self.find_method(&c, &member.member, call.args.len()))
+                    .map(|m| (m.params.as_slice(), &m.return_type));
+                (member.member.clone(), method)
+            }
+            other => {
+                self.eval(other);
+                (describe(other), None)
+            }
+        };
+
+        self.check_args(&name, method.map(|(params, _)| params), &call.args, &call.loc);
+        match method {
+            Some((_, ret)) if ret.is_nullable() => Nullness::MaybeNull,
+            _ => Nullness::NonNull,
+        }
+    }
+
+    fn check_args(&mut self, callee: &str, params: Option<&[ParameterInfo]>, args: &[Expr], loc: &SourceLocation) {
+        for (i, arg) in args.iter().enumerate() {
+            let value = self.eval(arg);
+            let Some(param) = params.and_then(|p| p.get(i)) else { continue };
+            if param.is_varargs {
+                continue;
+            }
+            let ty = &param.param_type;
+            if requires_non_null(ty) && value.may_be_null() {
+                self.report(loc, format!(
+                    "'{}' 的参数 '{}' 需要非空的 '{}'，但传入的值 '{}' 可能为 null",
+                    callee, param.name, ty, describe(arg)
+                ));
+            }
+        }
+    }
+
+    fn eval_assignment(&mut self, assign: &AssignmentExpr) -> Nullness {
+        let value = self.eval(&assign.value);
+        if assign.op != AssignOp::Assign {
+            if let Expr::Identifier(id) = assign.target.as_ref() {
+                self.set_state(&id.name, Nullness::NonNull);
+            }
+            return Nullness::NonNull;
+        }
+
+        match assign.target.as_ref() {
+            Expr::Identifier(id) => {
+                if let Some(local) = self.lookup_local(&id.name).cloned() {
+                    if local.tracked {
+                        self.set_state(&id.name, value);
+                    } else {
+                        self.check_assignable(&local.ty, value, &assign.loc, || {
+                            format!("非空类型 '{}' 的变量 '{}'", local.ty, id.name)
+                        });
+                    }
+                } else if let Some(field) = self.current_class.and_then(|c| self.find_field(&c.name, &id.name)) {
+                    self.check_assignable(&field.field_type, value, &assign.loc, || {
+                        format!("非空字段 '{}'", field.name)
+                    });
+                }
+            }
+            Expr::MemberAccess(member) => {
+                let class_name = self.check_receiver(&member.object, &member.loc);
+                if let Some(field) = class_name.and_then(|c| self.find_field(&c, &member.member)) {
+                    self.check_assignable(&field.field_type, value, &assign.loc, || {
+                        format!("非空字段 '{}'", field.name)
+                    });
+                }
+            }
+            other => {
+                self.eval(other);
+            }
+        }
+        value
+    }
+
+    fn check_lambda(&mut self, lambda: &LambdaExpr) {
+        // Lambda 可能在之后任意时刻执行，捕获的可空变量恢复为声明状态
+        let saved_scopes = self.scopes.clone();
+        let saved_return = self.return_type.take();
+        let saved_breaks = std::mem::take(&mut self.break_states);
+        let saved_continues = std::mem::take(&mut self.continue_states);
+        for scope in &mut self.scopes {
+            for local in scope.values_mut() {
+                if local.tracked {
+                    local.state = Nullness::MaybeNull;
+                }
+            }
+        }
+        let mut scope = HashMap::new();
+        for param in &lambda.params {
+            let ty = param.param_type.clone().unwrap_or(Type::Auto);
+            let tracked = ty.is_nullable();
+            scope.insert(param.name.clone(), Local {
+                ty,
+                tracked,
+                state: if tracked { Nullness::MaybeNull } else { Nullness::NonNull },
+            });
+        }
+        self.scopes.push(scope);
+        match &lambda.body {
+            LambdaBody::Expr(body) => {
+                self.eval(body);
+            }
+            LambdaBody::Block(block) => {
+                self.check_block(block);
+            }
+        }
+        self.scopes = saved_scopes;
+        self.return_type = saved_return;
+        self.break_states = saved_breaks;
+        self.continue_states = saved_continues;
+    }
+
+    fn check_assignable(&mut self, target: &Type, value: Nullness, loc: &SourceLocation, describe_target: impl FnOnce() -> String) {
+        if requires_non_null(target) && value.may_be_null() {
+            let message = format!(
+                "不能将可能为 null 的值赋给{}\n提示: 将其声明为 '{}?'，或在赋值前检查 null",
+                describe_target(), target
+            );
+            self.report(loc, message);
+        }
+    }
+
+    // ========== 类型信息查询 ==========
+
+    fn is_class_name(&self, name: &str) -> bool {
+        self.lookup_local(name).is_none()
+            && self.current_class.and_then(|c| self.find_field(&c.name, name)).is_none()
+            && (self.classes.contains_key(name) || name.chars().next().is_some_and(|c| c.is_uppercase()))
+    }
+
+    /// 表达式的静态类名（用于查找字段和方法的声明）
+    fn static_class_of(&self, expr: &Expr) -> Option<String> {
+        let ty = match expr {
+            Expr::Identifier(id) if id.name == "this" => return self.current_class.map(|c| c.name.clone()),
+            Expr::Identifier(id) if id.name == "super" => return self.current_class.and_then(|c| c.parent.clone()),
+            Expr::Identifier(id) => match self.lookup_local(&id.name) {
+                Some(local) => local.ty.clone(),
+                None => self.current_class.and_then(|c| self.find_field(&c.name, &id.name))?.field_type.clone(),
+            },
+            Expr::MemberAccess(member) => {
+                let owner = self.static_class_of(&member.object)?;
+                self.find_field(&owner, &member.member)?.field_type.clone()
+            }
+            Expr::New(new) => return Some(new.class_name.clone()),
+            Expr::Cast(cast) => cast.target_type.clone(),
+            _ => return None,
+        };
+        match ty.non_null() {
+            Type::Object(name) => Some(name.clone()),
+            _ => None,
+        }
+    }
+
+    fn find_field(&self, class_name: &str, field: &str) -> Option<&'a FieldDecl> {
+        let mut current = self.classes.get(class_name).copied();
+        while let Some(class) = current {
+            let found = class.members.iter().find_map(|member| match member {
+                ClassMember::Field(f) if f.name == field => Some(f),
+                _ => None,
+            });
+            if found.is_some() {
+                return found;
+            }
+            current = class.parent.as_deref().and_then(|p| self.classes.get(p).copied());
+        }
+        None
+    }
+
+    fn find_method(&self, class_name: &str, method: &str, arity: usize) -> Option<&'a MethodDecl> {
+        let mut current = self.classes.get(class_name).copied();
+        while let Some(class) = current {
+            let found = class.members.iter().find_map(|member| match member {
+                ClassMember::Method(m) if m.name == method && m.params.len() == arity => Some(m),
+                _ => None,
+            });
+            if found.is_some() {
+                return found;
+            }
+            current = class.parent.as_deref().and_then(|p| self.classes.get(p).copied());
+        }
+        None
+    }
+
+    fn find_constructor(&self, class_name: &str, arity: usize) -> Option<&'a [ParameterInfo]> {
+        let class = self.classes.get(class_name)?;
+        class.members.iter().find_map(|member| match member {
+            ClassMember::Constructor(ctor) if ctor.params.len() == arity => Some(ctor.params.as_slice()),
+            _ => None,
+        })
+    }
+}
+
+/// 表达式位置（字面量没有位置信息时返回默认值）
+fn expr_loc(expr: &Expr) -> SourceLocation {
+    use crate::ast::HasLocation;
+    expr.location().clone()
+}
+
+/// 生成表达式的简短描述，用于诊断消息
+fn describe(expr: &Expr) -> String {
+    match expr {
+        Expr::Identifier(id) => id.name.clone(),
+        Expr::Literal(LiteralValue::Null) => "null".to_string(),
+        Expr::MemberAccess(member) => format!("{}.{}", describe(&member.object), member.member),
+        Expr::Call(call) => format!("{}(...)", describe(&call.callee)),
+        Expr::ArrayAccess(access) => format!("{}[...]", describe(&access.array)),
+        _ => "<表达式>".to_string(),
+    }
+}
+
+/// 不能接收 null 的类型：非可空的引用类型（FFI 裸指针除外）
+fn requires_non_null(ty: &Type) -> bool {
+    ty.is_reference_type() && !ty.is_nullable() && !ty.is_raw_pointer()
+}
+
+impl SemanticAnalyzer {
+    /// 空安全检查（必须在擦除可空标记之前运行）
+    ///
+    /// 未启用 `null_safety` 特性时，发现的问题只作为警告记录。
+    pub(super) fn check_null_safety(&mut self, program: &Program) {
+        let strict = self.features.iter().any(|f| f == NULL_SAFETY_FEATURE);
+        let issues = NullSafetyChecker::new(program).check();
+        for issue in issues {
+            let info = self.create_error_info_with_file(
+                issue.loc.file.clone(),
+                issue.loc.line,
+                issue.loc.column,
+                issue.message,
+            );
+            if strict {
+                self.errors.push(info);
+            } else {
+                self.warnings.push(info);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::lex;
+    use crate::parser::parse;
+
+    fn issues(source: &str) -> Vec<String> {
+        let program = parse(lex(source).unwrap()).unwrap();
+        NullSafetyChecker::new(&program).check()
+            .into_iter()
+            .map(|issue| issue.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_deref_of_nullable_is_reported() {
+        let found = issues(r#"
+public class Main {
+    public static void main() {
+        String? s = null;
+        int n = s.length();
+    }
+}
+"#);
+        assert_eq!(found.len(), 1, "{:?}", found);
+        assert!(found[0].contains("'s'"));
+    }
+
+    #[test]
+    fn test_narrowing_after_null_check() {
+        let found = issues(r#"
+public class Main {
+    static int len(String? s) {
+        if (s != null) {
+            return s.length();
+        }
+        return 0;
+    }
+    static int len2(String? s) {
+        if (s == null) {
+            return 0;
+        }
+        return s.length();
+    }
+    static bool both(String? s) {
+        return s != null && s.length() > 0;
+    }
+    public static void main() {}
+}
+"#);
+        assert!(found.is_empty(), "{:?}", found);
+    }
+
+    #[test]
+    fn test_narrowing_after_instanceof() {
+        let found = issues(r#"
+public class Animal {
+    public void speak() {}
+}
+public class Main {
+    static void talk(Animal? a) {
+        if (a instanceof Animal) {
+            a.speak();
+        }
+        a.speak();
+    }
+    public static void main() {}
+}
+"#);
+        assert_eq!(found.len(), 1, "{:?}", found);
+    }
+
+    #[test]
+    fn test_null_into_non_null_slots() {
+        let found = issues(r#"
+public class Main {
+    static String name;
+    static void greet(String who) {}
+    static String find() {
+        return null;
+    }
+    public static void main() {
+        String s = null;
+        String? t = null;
+        greet(t);
+        name = t;
+    }
+}
+"#);
+        assert_eq!(found.len(), 4, "{:?}", found);
+        assert!(found.iter().any(|m| m.contains("返回类型")));
+        assert!(found.iter().any(|m| m.contains("参数 'who'")));
+        assert!(found.iter().any(|m| m.contains("非空字段 'name'")));
+    }
+
+    #[test]
+    fn test_loop_assignment_invalidates_narrowing() {
+        let found = issues(r#"
+public class Main {
+    public static void main() {
+        String? s = "a";
+        int i = 0;
+        while (i < 3) {
+            int n = s.length();
+            s = null;
+            i = i + 1;
+        }
+    }
+}
+"#);
+        assert_eq!(found.len(), 1, "{:?}", found);
+    }
+
+    #[test]
+    fn test_nullable_method_result_must_be_checked() {
+        let found = issues(r#"
+public class Main {
+    static String? lookup(int key) {
+        return null;
+    }
+    public static void main() {
+        String? v = lookup(1);
+        if (v != null) {
+            println(v.length());
+        }
+        println(lookup(2).length());
+    }
+}
+"#);
+        assert_eq!(found.len(), 1, "{:?}", found);
+        assert!(found[0].contains("lookup(...)"));
+    }
+}
@@ -0,0 +1,262 @@
+//! 整数溢出相关的语义检查
+//!
+//! - `Math.addExact` 等溢出检查内建函数的类型推断
+//! - `static final` 整数常量表达式的编译期求值与溢出报错（`sizeof` 按目标平台求值）
+
+use std::collections::HashMap;
+use crate::ast::*;
+use crate::types::{ExactOp, Type};
+use crate::error::{cayResult, SourceLocation};
+use crate::target::TargetInfo;
+use super::analyzer::SemanticAnalyzer;
+use super::expr_inference::semantic_error_at_loc;
+
+/// 编译期整数常量：值和位宽（32 为 int，64 为 long）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IntConst {
+    value: i64,
+    bits: u32,
+}
+
+impl IntConst {
+    fn type_name(bits: u32) -> &'static str {
+        if bits == 64 { "long" } else { "int" }
+    }
+
+    /// 将精确结果收窄到位宽内，超出范围时返回 None
+    fn fit(value: i128, bits: u32) -> Option<IntConst> {
+        let fits = if bits == 64 {
+            i64::try_from(value).is_ok()
+        } else {
+            i32::try_from(value).is_ok()
+        };
+        fits.then_some(IntConst { value: value as i64, bits })
+    }
+
+    /// 按位宽回绕（用于位运算和类型转换）
+    fn wrap(value: i64, bits: u32) -> IntConst {
+        let value = if bits == 64 { value } else { value as i32 as i64 };
+        IntConst { value, bits }
+    }
+}
+
+/// 常量求值中发现的溢出：位置和描述
+type OverflowError = (SourceLocation, String);
+
+/// 可参与常量求值的 static final 字段：`类名.字段名` -> (初始化表达式, 所在类)
+type ConstantFields<'a> = HashMap<String, (&'a Expr, &'a str)>;
+
+impl SemanticAnalyzer {
+    /// 推断 `Math.*Exact` 调用的类型：参数必须是 int/long，有 long 参数时结果为 long
+    pub(super) fn infer_math_exact_call(&mut self, op: ExactOp, call: &CallExpr) -> cayResult<Type> {
+        if call.args.len() != op.arity() {
+            return Err(semantic_error_at_loc(&call.loc, format!(
+                "Method 'Math.{}' requires {} argument(s), but got {}",
+                op.method_name(), op.arity(), call.args.len())));
+        }
+        let mut result = Type::Int32;
+        for (i, arg) in call.args.iter().enumerate() {
+            match self.infer_expr_type(arg)? {
+                Type::Int32 => {}
+                Type::Int64 => result = Type::Int64,
+                other => {
+                    return Err(semantic_error_at_loc(&call.loc, format!(
+                        "Argument {} of 'Math.{}' must be int or long, got {}",
+                        i + 1, op.method_name(), other)));
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// 检查 `static final` int/long 字段的常量初始化表达式是否溢出
+    pub fn check_constant_overflow(&mut self, program: &Program) {
+        let mut constants: ConstantFields = HashMap::new();
+        for class in &program.classes {
+            for member in &class.members {
+                let ClassMember::Field(field) = member else { continue };
+                if let Some(init) = constant_initializer(field) {
+                    constants.insert(format!("{}.{}", class.name, field.name), (init, class.name.as_str()));
+                }
+            }
+        }
+
+        for class in &program.classes {
+            for member in &class.members {
+                let ClassMember::Field(field) = member else { continue };
+                let Some(init) = constant_initializer(field) else { continue };
+                let mut visiting = vec![format!("{}.{}", class.name, field.name)];
+                if let Err((loc, message)) = fold_int_constant(init, &class.name, &constants, &self.target, &mut visiting) {
+                    let info = self.create_error_info_with_file(loc.file.clone(), loc.line, loc.column, format!(
+                        "{} in initializer of static final field '{}.{}'", message, class.name, field.name));
+                    self.errors.push(info);
+                }
+            }
+        }
+    }
+}
+
+/// static final int/long 字段的初始化表达式
+fn constant_initializer(field: &FieldDecl) -> Option<&Expr> {
+    let is_constant = field.modifiers.contains(&Modifier::Static)
+        && field.modifiers.contains(&Modifier::Final)
+        && matches!(field.field_type, Type::Int32 | Type::Int64);
+    if is_constant { field.initializer.as_ref() } else { None }
+}
+
+/// 对整数常量表达式求值
+///
+/// 返回 `Ok(None)` 表示表达式不是编译期常量；运算溢出时返回错误。
+fn fold_int_constant(
+    expr: &Expr,
+    class_name: &str,
+    constants: &ConstantFields,
+    target: &TargetInfo,
+    visiting: &mut Vec<String>,
+) -> Result<Option<IntConst>, OverflowError> {
+    let constant = match expr {
+        Expr::Literal(LiteralValue::Int32(n)) => IntConst { value: *n as i64, bits: 32 },
+        Expr::Literal(LiteralValue::Int64(n)) => IntConst { value: *n, bits: 64 },
+        Expr::SizeOf(size_of) => match target.size_of(&size_of.target_type) {
+            Some(size) => IntConst { value: size as i64, bits: 64 },
+            None => return Ok(None),
+        },
+        Expr::Identifier(id) => {
+            return Ok(fold_constant_field(&format!("{}.{}", class_name, id.name), constants, target, visiting));
+        }
+        Expr::MemberAccess(member) => {
+            let Expr::Identifier(owner) = member.object.as_ref() else { return Ok(None) };
+            return Ok(fold_constant_field(&format!("{}.{}", owner.name, member.member), constants, target, visiting));
+        }
+        Expr::Cast(cast) => {
+            let Some(inner) = fold_int_constant(&cast.expr, class_name, constants, target, visiting)? else { return Ok(None) };
+            match cast.target_type {
+                Type::Int32 => IntConst::wrap(inner.value, 32),
+                Type::Int64 => IntConst::wrap(inner.value, 64),
+                _ => return Ok(None),
+            }
+        }
+        Expr::Unary(unary) => {
+            let Some(operand) = fold_int_constant(&unary.operand, class_name, constants, target, visiting)? else { return Ok(None) };
+            match unary.op {
+                UnaryOp::Neg => IntConst::fit(-(operand.value as i128), operand.bits).ok_or_else(|| (
+                    unary.loc.clone(),
+                    format!("Integer overflow in constant expression: -({}) overflows {}",
+                        operand.value, IntConst::type_name(operand.bits)),
+                ))?,
+                UnaryOp::BitNot => IntConst::wrap(!operand.value, operand.bits),
+                _ => return Ok(None),
+            }
+        }
+        Expr::Binary(bin) => {
+            let Some(left) = fold_int_constant(&bin.left, class_name, constants, target, visiting)? else { return Ok(None) };
+            let Some(right) = fold_int_constant(&bin.right, class_name, constants, target, visiting)? else { return Ok(None) };
+            let bits = left.bits.max(right.bits);
+            let (l, r) = (left.value as i128, right.value as i128);
+            let (exact, symbol) = match bin.op {
+                BinaryOp::Add => (l + r, "+"),
+                BinaryOp::Sub => (l - r, "-"),
+                BinaryOp::Mul => (l * r, "*"),
+                // 除零由类型检查报告，这里不作为常量处理
+                BinaryOp::Div if r != 0 => (l / r, "/"),
+                BinaryOp::Mod if r != 0 => return Ok(Some(IntConst::wrap((l % r) as i64, bits))),
+                BinaryOp::BitAnd => return Ok(Some(IntConst::wrap(left.value & right.value, bits))),
+                BinaryOp::BitOr => return Ok(Some(IntConst::wrap(left.value | right.value, bits))),
+                BinaryOp::BitXor => return Ok(Some(IntConst::wrap(left.value ^ right.value, bits))),
+                BinaryOp::Shl | BinaryOp::Shr | BinaryOp::UnsignedShr => {
+                    return Ok(Some(fold_shift(bin.op, left.value, right.value, left.bits)));
+                }
+                _ => return Ok(None),
+            };
+            IntConst::fit(exact, bits).ok_or_else(|| (
+                bin.loc.clone(),
+                format!("Integer overflow in constant expression: {} {} {} overflows {}",
+                    left.value, symbol, right.value, IntConst::type_name(bits)),
+            ))?
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(constant))
+}
+
+/// 求值被引用的 static final 字段；循环引用或自身溢出（已单独报告）时视为非常量
+fn fold_constant_field(key: &str, constants: &ConstantFields, target: &TargetInfo, visiting: &mut Vec<String>) -> Option<IntConst> {
+    let (init, owner) = constants.get(key)?;
+    if visiting.iter().any(|k| k == key) {
+        return None;
+    }
+    visiting.push(key.to_string());
+    let result = fold_int_constant(init, owner, constants, target, visiting).ok().flatten();
+    visiting.pop();
+    result
+}
+
+/// 移位运算：移位量按位宽取模（与 Java 一致）
+fn fold_shift(op: BinaryOp, value: i64, amount: i64, bits: u32) -> IntConst {
+    let shift = (amount & (bits as i64 - 1)) as u32;
+    let result = match (op, bits) {
+        (BinaryOp::Shl, _) => value.wrapping_shl(shift),
+        (BinaryOp::Shr, 64) => value >> shift,
+        (BinaryOp::Shr, _) => ((value as i32) >> shift) as i64,
+        (_, 64) => ((value as u64) >> shift) as i64,
+        _ => ((value as i32 as u32) >> shift) as i64,
+    };
+    IntConst::wrap(result, bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::lex;
+    use crate::parser::parse;
+    use crate::semantic::SemanticAnalyzer;
+
+    fn analyze(fields: &str, body: &str) -> Result<(), String> {
+        let source = format!(r#"
+public class Main {{
+{}
+    public static void main() {{
+{}
+    }}
+}}
+"#, fields, body);
+        let program = parse(lex(&source).unwrap()).unwrap();
+        SemanticAnalyzer::new().analyze(&program).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_static_final_overflow_is_reported() {
+        let error = analyze("    static final int BIG = 2147483647 + 1;", "").unwrap_err();
+        assert!(error.contains("Integer overflow in constant expression: 2147483647 + 1 overflows int"), "{}", error);
+        assert!(error.contains("static final field 'Main.BIG'"), "{}", error);
+    }
+
+    #[test]
+    fn test_overflow_through_referenced_constants() {
+        let fields = "    static final int MAX = 2147483647;\n    static final int HALF = 1073741824;\n    static final int SUM = HALF * 2;";
+        let error = analyze(fields, "").unwrap_err();
+        assert!(error.contains("1073741824 * 2 overflows int"), "{}", error);
+        assert!(error.contains("'Main.SUM'"), "{}", error);
+
+        let fields = "    static final long WIDE = 1073741824L * 4;\n    static final int NEG = -(-2147483647 - 1);";
+        let error = analyze(fields, "").unwrap_err();
+        assert!(!error.contains("WIDE"), "{}", error);
+        assert!(error.contains("-(-2147483648) overflows int"), "{}", error);
+    }
+
+    #[test]
+    fn test_non_overflowing_constants_are_accepted() {
+        let fields = "    static final int A = 1 << 31;\n    static final int B = (int) 4294967296L;\n    static final long C = 9223372036854775807L - 1;\n    static int D = 2147483647 + 1;";
+        analyze(fields, "").unwrap();
+    }
+
+    #[test]
+    fn test_math_exact_types() {
+        analyze("", "        int a = Math.addExact(1, 2);\n        long b = Math.multiplyExact(3L, 4);\n        int c = Math.negateExact(a);").unwrap();
+        let error = analyze("", "        int a = Math.addExact(1L, 2);").unwrap_err();
+        assert!(error.contains("Cannot assign long to int"), "{}", error);
+        let error = analyze("", "        double d = Math.addExact(1.0, 2.0);").unwrap_err();
+        assert!(error.contains("Argument 1 of 'Math.addExact' must be int or long"), "{}", error);
+        let error = analyze("", "        int a = Math.incrementExact(1, 2);").unwrap_err();
+        assert!(error.contains("Method 'Math.incrementExact' requires 1 argument(s), but got 2"), "{}", error);
+    }
+}
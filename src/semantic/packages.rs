@@ -0,0 +1,418 @@
+//! 多文件程序的包检查：跨文件重复定义和 package/import 可见性
+//!
+//! 规则（只在程序由多个源文件组成时检查）：
+//! 1. 类、接口和顶层函数的名字在所有文件中唯一
+//! 2. 默认包（没有 package 声明的文件）中的声明对所有文件可见
+//! 3. 同一个包中的声明互相可见
+//! 4. 其他包中的声明必须是 public，并且用 `import 包.名字;` 或 `import 包.*;` 导入
+
+use std::collections::{HashMap, HashSet};
+use crate::ast::*;
+use crate::error::SourceLocation;
+use crate::types::Type;
+use super::analyzer::SemanticAnalyzer;
+
+/// 一个顶层声明（类、接口或顶层函数）
+struct Declaration {
+    kind: &'static str,
+    unit: usize,
+    public: bool,
+    loc: SourceLocation,
+}
+
+impl SemanticAnalyzer {
+    /// 检查多文件程序的包规则，问题记录到 `self.errors`
+    pub fn check_packages(&mut self, program: &Program) {
+        if program.units.len() < 2 {
+            return;
+        }
+        let owners = Owners::new(program);
+        let Some(declarations) = self.collect_declarations(program, &owners) else {
+            return;
+        };
+        self.check_imports(program, &declarations);
+
+        let mut reported = HashSet::new();
+        for (class, unit) in program.classes.iter().zip(&owners.classes) {
+            let mut collector = ReferenceCollector::default();
+            collector.visit_class(class);
+            self.check_references(program, &declarations, *unit, collector.refs, &mut reported);
+        }
+        for (interface, unit) in program.interfaces.iter().zip(&owners.interfaces) {
+            let mut collector = ReferenceCollector::default();
+            collector.visit_interface(interface);
+            self.check_references(program, &declarations, *unit, collector.refs, &mut reported);
+        }
+        for (func, unit) in program.top_level_functions.iter().zip(&owners.functions) {
+            let mut collector = ReferenceCollector::default();
+            collector.visit_function(func);
+            self.check_references(program, &declarations, *unit, collector.refs, &mut reported);
+        }
+    }
+
+    /// 收集所有顶层声明；同名声明出现在多个文件中时报错并返回 None
+    fn collect_declarations(&mut self, program: &Program, owners: &Owners) -> Option<HashMap<String, Declaration>> {
+        let mut all = Vec::new();
+        for (class, unit) in program.classes.iter().zip(&owners.classes) {
+            all.push((&class.name, "Class", *unit, class.modifiers.contains(&Modifier::Public), &class.loc));
+        }
+        for (interface, unit) in program.interfaces.iter().zip(&owners.interfaces) {
+            all.push((&interface.name, "Interface", *unit, interface.modifiers.contains(&Modifier::Public), &interface.loc));
+        }
+        for (func, unit) in program.top_level_functions.iter().zip(&owners.functions) {
+            all.push((&func.name, "Function", *unit, func.modifiers.contains(&Modifier::Public), &func.loc));
+        }
+
+        let error_count = self.errors.len();
+        let mut declarations: HashMap<String, Declaration> = HashMap::new();
+        for (name, kind, unit, public, loc) in all {
+            // 同一文件中的重复定义由后续各遍报告
+            if let Some(first) = declarations.get(name) {
+                if first.unit != unit {
+                    let message = format!("{} '{}' is defined in both {} and {}",
+                        kind, name, self.describe_location(&first.loc), self.describe_location(loc));
+                    let info = self.create_error_info_with_file(loc.file.clone(), loc.line, loc.column, message);
+                    self.errors.push(info);
+                }
+                continue;
+            }
+            declarations.insert(name.clone(), Declaration { kind, unit, public, loc: loc.clone() });
+        }
+        (self.errors.len() == error_count).then_some(declarations)
+    }
+
+    /// 检查 import 声明指向存在的包和 public 声明
+    fn check_imports(&mut self, program: &Program, declarations: &HashMap<String, Declaration>) {
+        let packages: HashSet<&str> = program.units.iter().filter_map(|u| u.package_name()).collect();
+        for unit in &program.units {
+            for import in &unit.imports {
+                let message = match &import.name {
+                    None if !packages.contains(import.package.as_str()) => Some(format!(
+                        "Package '{}' in import '{}' does not exist", import.package, import.path()
+                    )),
+                    None => None,
+                    Some(name) => match declarations.get(name) {
+                        Some(decl) if program.units[decl.unit].package_name() == Some(import.package.as_str()) => {
+                            (!decl.public).then(|| format!(
+                                "{} '{}' is not public in package '{}' and cannot be imported",
+                                decl.kind, name, import.package
+                            ))
+                        }
+                        _ => Some(format!(
+                            "Import '{}' does not match any class, interface or function in package '{}'",
+                            import.path(), import.package
+                        )),
+                    },
+                };
+                if let Some(message) = message {
+                    let info = self.create_error_info_with_file(import.loc.file.clone(), import.loc.line, import.loc.column, message);
+                    self.errors.push(info);
+                }
+            }
+        }
+    }
+
+    /// 检查一个声明内部对其他声明的引用是否可见（每个文件中的每个名字只报告一次）
+    fn check_references(
+        &mut self,
+        program: &Program,
+        declarations: &HashMap<String, Declaration>,
+        from_unit: usize,
+        refs: Vec<(String, SourceLocation)>,
+        reported: &mut HashSet<(usize, String)>,
+    ) {
+        let unit = &program.units[from_unit];
+        for (name, loc) in refs {
+            let Some(target) = declarations.get(&name) else {
+                continue;
+            };
+            let Some(target_package) = program.units[target.unit].package_name() else {
+                continue;
+            };
+            if target.unit == from_unit || unit.package_name() == Some(target_package) {
+                continue;
+            }
+            let from_package = match unit.package_name() {
+                Some(package) => format!("package '{}'", package),
+                None => "the default package".to_string(),
+            };
+            let message = if !target.public {
+                format!("{} '{}' is not public in package '{}' and cannot be accessed from {}",
+                    target.kind, name, target_package, from_package)
+            } else if !unit.imports.iter().any(|import| {
+                import.package == target_package && import.name.as_ref().is_none_or(|n| *n == name)
+            }) {
+                format!("{} '{}' in package '{}' is not imported; add 'import {}.{};'",
+                    target.kind, name, target_package, target_package, name)
+            } else {
+                continue;
+            };
+            if reported.insert((from_unit, name)) {
+                let info = self.create_error_info_with_file(loc.file.clone(), loc.line, loc.column, message);
+                self.errors.push(info);
+            }
+        }
+    }
+
+    /// `文件:行` 形式的位置描述
+    fn describe_location(&self, loc: &SourceLocation) -> String {
+        let info = self.create_error_info_with_file(loc.file.clone(), loc.line, loc.column, String::new());
+        match info.file {
+            Some(file) => format!("{}:{}", file, info.line),
+            None => format!("line {}", info.line),
+        }
+    }
+}
+
+/// 各声明所在的单元
+///
+/// 合并后的程序按单元顺序排列各单元的声明，第 i 个类属于类数量前缀和覆盖 i 的单元，接口和顶层函数同理
+struct Owners {
+    classes: Vec<usize>,
+    interfaces: Vec<usize>,
+    functions: Vec<usize>,
+}
+
+impl Owners {
+    fn new(program: &Program) -> Self {
+        let owners = |names: fn(&SourceUnit) -> &Vec<String>| -> Vec<usize> {
+            program.units.iter().enumerate()
+                .flat_map(|(index, unit)| std::iter::repeat_n(index, names(unit).len()))
+                .collect()
+        };
+        Self {
+            classes: owners(|unit| &unit.classes),
+            interfaces: owners(|unit| &unit.interfaces),
+            functions: owners(|unit| &unit.functions),
+        }
+    }
+}
+
+/// 收集一个声明中引用的类名、接口名和函数名
+///
+/// 局部变量、参数和当前类的成员遮蔽同名的顶层声明
+#[derive(Default)]
+struct ReferenceCollector {
+    locals: HashSet<String>,
+    members: HashSet<String>,
+    refs: Vec<(String, SourceLocation)>,
+}
+
+impl ReferenceCollector {
+    fn visit_class(&mut self, class: &ClassDecl) {
+        for name in class.parent.iter().chain(&class.interfaces) {
+            self.refs.push((name.clone(), class.loc.clone()));
+        }
+        for member in &class.members {
+            match member {
+                ClassMember::Method(method) => { self.members.insert(method.name.clone()); }
+                ClassMember::Field(field) => { self.members.insert(field.name.clone()); }
+                _ => {}
+            }
+        }
+        for member in &class.members {
+            self.locals.clear();
+            match member {
+                ClassMember::Method(method) => self.visit_method(method),
+                ClassMember::Field(field) => {
+                    self.visit_type(&field.field_type, &field.loc);
+                    if let Some(init) = &field.initializer {
+                        self.visit_expr(init);
+                    }
+                }
+                ClassMember::Constructor(ctor) => {
+                    self.visit_params(&ctor.params, &ctor.loc);
+                    if let Some(ConstructorCall::This(args) | ConstructorCall::Super(args)) = &ctor.constructor_call {
+                        args.iter().for_each(|arg| self.visit_expr(arg));
+                    }
+                    self.visit_block(&ctor.body);
+                }
+                ClassMember::Destructor(dtor) => self.visit_block(&dtor.body),
+                ClassMember::InstanceInitializer(block) | ClassMember::StaticInitializer(block) => self.visit_block(block),
+            }
+        }
+    }
+
+    fn visit_interface(&mut self, interface: &InterfaceDecl) {
+        for parent in &interface.parents {
+            self.refs.push((parent.clone(), interface.loc.clone()));
+        }
+        for method in &interface.methods {
+            self.locals.clear();
+            self.visit_method(method);
+        }
+    }
+
+    fn visit_function(&mut self, func: &TopLevelFunction) {
+        self.visit_type(&func.return_type, &func.loc);
+        self.visit_params(&func.params, &func.loc);
+        self.visit_block(&func.body);
+    }
+
+    fn visit_method(&mut self, method: &MethodDecl) {
+        self.visit_type(&method.return_type, &method.loc);
+        self.visit_params(&method.params, &method.loc);
+        if let Some(body) = &method.body {
+            self.visit_block(body);
+        }
+    }
+
+    fn visit_params(&mut self, params: &[crate::types::ParameterInfo], loc: &SourceLocation) {
+        for param in params {
+            self.visit_type(&param.param_type, loc);
+            self.locals.insert(param.name.clone());
+        }
+    }
+
+    fn visit_type(&mut self, ty: &Type, loc: &SourceLocation) {
+        match ty {
+            Type::Object(name) => self.refs.push((name.clone(), loc.clone())),
+            Type::Array(inner) | Type::Pointer(inner) | Type::Nullable(inner) => self.visit_type(inner, loc),
+            Type::Function(func) => {
+                func.params.iter().for_each(|param| self.visit_type(param, loc));
+                self.visit_type(&func.return_type, loc);
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        block.statements.iter().for_each(|stmt| self.visit_stmt(stmt));
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Expr(expr) => self.visit_expr(expr),
+            Stmt::VarDecl(var) => {
+                self.visit_type(&var.var_type, &var.loc);
+                self.locals.insert(var.name.clone());
+                if let Some(init) = &var.initializer {
+                    self.visit_expr(init);
+                }
+            }
+            Stmt::Return(value) => {
+                if let Some(value) = value {
+                    self.visit_expr(value);
+                }
+            }
+            Stmt::If(if_stmt) => {
+                self.visit_expr(&if_stmt.condition);
+                self.visit_stmt(&if_stmt.then_branch);
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    self.visit_stmt(else_branch);
+                }
+            }
+            Stmt::While(while_stmt) => {
+                self.visit_expr(&while_stmt.condition);
+                self.visit_stmt(&while_stmt.body);
+            }
+            Stmt::For(for_stmt) => {
+                if let Some(init) = &for_stmt.init {
+                    self.visit_stmt(init);
+                }
+                if let Some(cond) = &for_stmt.condition {
+                    self.visit_expr(cond);
+                }
+                if let Some(update) = &for_stmt.update {
+                    self.visit_expr(update);
+                }
+                self.visit_stmt(&for_stmt.body);
+            }
+            Stmt::DoWhile(do_while) => {
+                self.visit_stmt(&do_while.body);
+                self.visit_expr(&do_while.condition);
+            }
+            Stmt::Switch(switch) => {
+                self.visit_expr(&switch.expr);
+                for case in &switch.cases {
+                    case.body.iter().for_each(|stmt| self.visit_stmt(stmt));
+                }
+                if let Some(default) = &switch.default {
+                    default.iter().for_each(|stmt| self.visit_stmt(stmt));
+                }
+            }
+            Stmt::Block(block) => self.visit_block(block),
+            Stmt::Scope(scope) => self.visit_block(&scope.body),
+            Stmt::Break(_) | Stmt::Continue(_) | Stmt::InlineIr(_) | Stmt::Error(_) => {}
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(_) => {}
+            Expr::Identifier(ident) => {
+                if !self.locals.contains(&ident.name) && !self.members.contains(&ident.name) {
+                    self.refs.push((ident.name.clone(), ident.loc.clone()));
+                }
+            }
+            Expr::Binary(bin) => {
+                self.visit_expr(&bin.left);
+                self.visit_expr(&bin.right);
+            }
+            Expr::Unary(unary) => self.visit_expr(&unary.operand),
+            Expr::Call(call) => {
+                self.visit_expr(&call.callee);
+                call.args.iter().for_each(|arg| self.visit_expr(arg));
+            }
+            Expr::MemberAccess(member) => self.visit_expr(&member.object),
+            Expr::New(new) => {
+                self.refs.push((new.class_name.clone(), new.loc.clone()));
+                new.args.iter().for_each(|arg| self.visit_expr(arg));
+            }
+            Expr::Assignment(assign) => {
+                self.visit_expr(&assign.target);
+                self.visit_expr(&assign.value);
+            }
+            Expr::Cast(cast) => {
+                self.visit_type(&cast.target_type, &cast.loc);
+                self.visit_expr(&cast.expr);
+            }
+            Expr::ArrayCreation(arr) => {
+                self.visit_type(&arr.element_type, &arr.loc);
+                arr.sizes.iter().for_each(|size| self.visit_expr(size));
+            }
+            Expr::ArrayAccess(arr) => {
+                self.visit_expr(&arr.array);
+                self.visit_expr(&arr.index);
+            }
+            Expr::ArrayInit(arr) => arr.elements.iter().for_each(|element| self.visit_expr(element)),
+            Expr::MethodRef(method_ref) => {
+                if let Some(class_name) = &method_ref.class_name {
+                    self.refs.push((class_name.clone(), method_ref.loc.clone()));
+                }
+                if let Some(object) = &method_ref.object {
+                    self.visit_expr(object);
+                }
+            }
+            Expr::Lambda(lambda) => {
+                for param in &lambda.params {
+                    if let Some(ty) = &param.param_type {
+                        self.visit_type(ty, &lambda.loc);
+                    }
+                    self.locals.insert(param.name.clone());
+                }
+                match &lambda.body {
+                    LambdaBody::Expr(body) => self.visit_expr(body),
+                    LambdaBody::Block(block) => self.visit_block(block),
+                }
+            }
+            Expr::Ternary(ternary) => {
+                self.visit_expr(&ternary.condition);
+                self.visit_expr(&ternary.true_branch);
+                self.visit_expr(&ternary.false_branch);
+            }
+            Expr::InstanceOf(instance) => {
+                self.visit_type(&instance.target_type, &instance.loc);
+                self.visit_expr(&instance.expr);
+            }
+            Expr::SizeOf(size_of) => self.visit_type(&size_of.target_type, &size_of.loc),
+            Expr::Alloc(alloc) => {
+                self.visit_expr(&alloc.size);
+                if let Some(align) = &alloc.align {
+                    self.visit_expr(align);
+                }
+            }
+            Expr::Dealloc(dealloc) => self.visit_expr(&dealloc.ptr),
+        }
+    }
+}
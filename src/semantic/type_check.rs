@@ -15,47 +15,7 @@ impl SemanticAnalyzer {
             for member in &class.members {
                 match member {
                     ClassMember::Method(method) => {
-                        self.current_method = Some(method.name.clone());
-                        self.current_method_is_static = method.modifiers.contains(&Modifier::Static);
-                        self.current_method_is_constructor = false;
-                        self.symbol_table.enter_scope();
-                        
-                        // 非静态方法需要添加 this
-                        if !self.current_method_is_static {
-                            if let Some(current_class) = &self.current_class {
-                                self.symbol_table.declare(
-                                    "this".to_string(),
-                                    SemanticSymbolInfo {
-                                        name: "this".to_string(),
-                                        symbol_type: Type::Object(current_class.clone()),
-                                        is_final: true,
-                                        is_initialized: true,
-                                    }
-                                );
-                            }
-                        }
-                        
-                        // 添加参数到符号表
-                        for param in &method.params {
-                            self.symbol_table.declare(
-                                param.name.clone(),
-                                SemanticSymbolInfo {
-                                    name: param.name.clone(),
-                                    symbol_type: param.param_type.clone(),
-                                    is_final: false,
-                                    is_initialized: true,
-                                }
-                            );
-                        }
-                        
-                        // 类型检查方法体
-                        if let Some(body) = &method.body {
-                            self.type_check_statement(&Stmt::Block(body.clone()), Some(&method.return_type))?;
-                        }
-                        
-                        self.symbol_table.exit_scope();
-                        self.current_method = None;
-                        self.current_method_is_static = false;
+                        self.type_check_method(method)?;
                     }
                     ClassMember::Field(_) => {
                         // 字段类型检查暂不实现
@@ -141,6 +101,17 @@ impl SemanticAnalyzer {
             self.current_class = None;
         }
 
+        // 类型检查接口的 default 方法体（this 的类型为接口本身）
+        for interface in &program.interfaces {
+            self.current_class = Some(interface.name.clone());
+            for method in &interface.methods {
+                if method.body.is_some() {
+                    self.type_check_method(method)?;
+                }
+            }
+            self.current_class = None;
+        }
+
         // 类型检查顶层函数
         for func in &program.top_level_functions {
             self.current_class = None;  // 顶层函数不属于任何类
@@ -173,6 +144,52 @@ impl SemanticAnalyzer {
         Ok(())
     }
 
+    /// 类型检查当前类（或接口）中的方法
+    fn type_check_method(&mut self, method: &MethodDecl) -> cayResult<()> {
+        self.current_method = Some(method.name.clone());
+        self.current_method_is_static = method.modifiers.contains(&Modifier::Static);
+        self.current_method_is_constructor = false;
+        self.symbol_table.enter_scope();
+        
+        // 非静态方法需要添加 this
+        if !self.current_method_is_static {
+            if let Some(current_class) = &self.current_class {
+                self.symbol_table.declare(
+                    "this".to_string(),
+                    SemanticSymbolInfo {
+                        name: "this".to_string(),
+                        symbol_type: Type::Object(current_class.clone()),
+                        is_final: true,
+                        is_initialized: true,
+                    }
+                );
+            }
+        }
+        
+        // 添加参数到符号表
+        for param in &method.params {
+            self.symbol_table.declare(
+                param.name.clone(),
+                SemanticSymbolInfo {
+                    name: param.name.clone(),
+                    symbol_type: param.param_type.clone(),
+                    is_final: false,
+                    is_initialized: true,
+                }
+            );
+        }
+        
+        // 类型检查方法体
+        if let Some(body) = &method.body {
+            self.type_check_statement(&Stmt::Block(body.clone()), Some(&method.return_type))?;
+        }
+        
+        self.symbol_table.exit_scope();
+        self.current_method = None;
+        self.current_method_is_static = false;
+        Ok(())
+    }
+
     /// 类型检查语句
     pub fn type_check_statement(&mut self, stmt: &Stmt, expected_return: Option<&Type>) -> cayResult<()> {
         match stmt {
@@ -185,9 +185,15 @@ impl SemanticAnalyzer {
         if supertype == "Object" {
             // 检查 subtype 是否是一个有效的类名（不是内置类型别名）
             return self.type_registry.class_exists(subtype)
+                || self.type_registry.interface_exists(subtype)
                 || subtype == "String"
                 || subtype == "Function";
         }
+
+        // 接口：检查类（或子接口）是否实现了该接口
+        if self.type_registry.interface_exists(supertype) {
+            return self.type_registry.implements_interface(subtype, supertype);
+        }
         
         // 迭代遍历继承链
         let mut current = subtype.to_string();
@@ -0,0 +1,232 @@
+//! 目标平台信息
+//!
+//! `TargetInfo` 描述一个目标三元组的指针宽度、C 类型的大小、LLVM 数据布局和默认 ABI。
+//! 语义分析（`sizeof` 常量）、旧代码生成器（FFI 类型、类布局）、IrBuilder 和
+//! `IrType::size_bytes`/`alignment` 都按它计算，生成的 IR 带有对应的 `target datalayout`。
+//!
+//! 支持 x86_64（Linux、Windows、macOS）、aarch64（Linux、macOS）和 riscv64（Linux）；
+//! `riscv64gc` 这类带扩展后缀的三元组写入 IR 时规范化为 LLVM 的架构名，扩展作为 CPU 特性传给 llc/clang。
+
+use crate::types::Type;
+
+/// 目标架构
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    X86_64,
+    AArch64,
+    RiscV64,
+}
+
+impl Arch {
+    /// LLVM 三元组中的架构名
+    pub fn llvm_name(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "x86_64",
+            Arch::AArch64 => "aarch64",
+            Arch::RiscV64 => "riscv64",
+        }
+    }
+}
+
+/// 目标平台信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetInfo {
+    /// LLVM 目标三元组（写入 IR 的 `target triple`）
+    pub triple: String,
+    pub arch: Arch,
+    /// 操作系统：linux、windows、macos（与 `CompilerOptions::target_os` 一致）
+    pub os: String,
+    /// 指针宽度（字节），也是 size_t/intptr_t 的大小
+    pub pointer_size: usize,
+    /// C `long` 的大小（字节）：Windows 是 LLP64 为 4，其余为 8
+    pub c_long_size: usize,
+    /// C `char` 是否有符号：aarch64/riscv64 Linux 上为无符号
+    pub c_char_signed: bool,
+    /// LLVM 数据布局
+    pub data_layout: &'static str,
+    /// 默认 ABI（如 riscv64 的 lp64d）；None 时使用 LLVM 的默认值
+    pub abi: Option<&'static str>,
+    /// 默认开启的 CPU 特性（llc 的 `-mattr`）
+    pub features: Option<&'static str>,
+    /// 传给 clang 的 `-march`（riscv64 的扩展集合）
+    pub march: Option<&'static str>,
+}
+
+impl TargetInfo {
+    /// 解析目标三元组
+    pub fn from_triple(triple: &str) -> Result<Self, String> {
+        let mut parts = triple.split('-');
+        let arch_name = parts.next().unwrap_or_default();
+        let rest: Vec<&str> = parts.collect();
+        let rest_str = rest.join("-");
+
+        // riscv64 的扩展后缀：gc = imafdc，Linux 发行版的默认配置
+        let (arch, features, abi, march) = match arch_name {
+            "x86_64" | "amd64" => (Arch::X86_64, None, None, None),
+            "aarch64" | "arm64" => (Arch::AArch64, None, None, None),
+            "riscv64" | "riscv64gc" => (Arch::RiscV64, Some("+m,+a,+f,+d,+c"), Some("lp64d"), Some("rv64gc")),
+            "riscv64imac" => (Arch::RiscV64, Some("+m,+a,+c"), Some("lp64"), Some("rv64imac")),
+            _ => return Err(format!("不支持的目标架构 '{}'（目标 '{}'），支持 x86_64、aarch64、riscv64", arch_name, triple)),
+        };
+
+        let os = if rest_str.contains("windows") || rest_str.contains("mingw") {
+            "windows"
+        } else if rest_str.contains("apple") || rest_str.contains("darwin") || rest_str.contains("macos") {
+            "macos"
+        } else if rest_str.contains("linux") {
+            "linux"
+        } else {
+            return Err(format!("无法从目标 '{}' 确定操作系统，支持 linux、windows、macos", triple));
+        };
+
+        let data_layout = match (arch, os) {
+            (Arch::X86_64, "windows") => "e-m:w-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128",
+            (Arch::X86_64, "macos") => "e-m:o-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128",
+            (Arch::X86_64, _) => "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128",
+            (Arch::AArch64, "macos") => "e-m:o-i64:64-i128:128-n32:64-S128",
+            (Arch::AArch64, "linux") => "e-m:e-i8:8:32-i16:16:32-i64:64-i128:128-n32:64-S128",
+            (Arch::RiscV64, "linux") => "e-m:e-p:64:64-i64:64-i128:128-n64-S128",
+            _ => return Err(format!("不支持的目标 '{}'：{} 只支持 {}", triple, arch.llvm_name(),
+                if arch == Arch::AArch64 { "linux、macos" } else { "linux" })),
+        };
+
+        Ok(Self {
+            triple: format!("{}-{}", arch.llvm_name(), rest_str),
+            arch,
+            os: os.to_string(),
+            pointer_size: 8,
+            c_long_size: if os == "windows" { 4 } else { 8 },
+            c_char_signed: arch == Arch::X86_64 || os == "macos",
+            data_layout,
+            abi,
+            features,
+            march,
+        })
+    }
+
+    /// 操作系统对应的默认 x86_64 目标（`--target <os>` 和未指定三元组时）
+    pub fn for_os(os: &str) -> Self {
+        let triple = match os {
+            "windows" => "x86_64-w64-mingw32",
+            "macos" => "x86_64-apple-darwin",
+            _ => "x86_64-unknown-linux-gnu",
+        };
+        Self::from_triple(triple).expect("built-in x86_64 targets are supported")
+    }
+
+    /// 整数类型的 LLVM 类型名（按字节数）
+    fn int_llvm(size: usize) -> String {
+        format!("i{}", size * 8)
+    }
+
+    /// 指针宽度的整数类型（size_t/intptr_t）
+    pub fn pointer_int_llvm(&self) -> String {
+        Self::int_llvm(self.pointer_size)
+    }
+
+    /// C `long` 的 LLVM 类型
+    pub fn c_long_llvm(&self) -> String {
+        Self::int_llvm(self.c_long_size)
+    }
+
+    /// 64 位整数和 double 的对齐（字节）
+    pub fn i64_align(&self) -> usize {
+        8
+    }
+
+    /// 类型的大小（字节）；void 和 auto 没有大小，按值传递的 C 结构体大小由 C 编译器决定
+    pub fn size_of(&self, ty: &Type) -> Option<usize> {
+        let size = match ty {
+            Type::Void | Type::CVoid | Type::Auto | Type::Struct(_) => return None,
+            Type::Int32 | Type::Float32 | Type::CInt | Type::CUInt | Type::CFloat => 4,
+            Type::Int64 | Type::Float64 | Type::CDouble => 8,
+            Type::Bool | Type::Char | Type::CChar | Type::CUChar | Type::CBool => 1,
+            Type::CShort | Type::CUShort => 2,
+            Type::CLong => self.c_long_size,
+            // 引用类型和指针
+            Type::String | Type::Object(_) | Type::Array(_) | Type::Function(_) | Type::Pointer(_) => self.pointer_size,
+            Type::SizeT | Type::SSizeT | Type::UIntPtr | Type::IntPtr => self.pointer_size,
+            Type::Nullable(inner) => return self.size_of(inner),
+        };
+        Some(size)
+    }
+
+    /// 整数类型是否无符号（扩展时用零扩展）
+    pub fn is_unsigned(&self, ty: &Type) -> bool {
+        match ty {
+            Type::CUChar | Type::CUShort | Type::CUInt | Type::SizeT | Type::UIntPtr => true,
+            Type::CChar => !self.c_char_signed,
+            _ => false,
+        }
+    }
+
+    /// llc 的目标参数：三元组、CPU 特性和 ABI
+    pub fn llc_args(&self) -> Vec<String> {
+        let mut args = vec![format!("-mtriple={}", self.triple)];
+        if let Some(features) = self.features {
+            args.push(format!("-mattr={}", features));
+        }
+        if let Some(abi) = self.abi {
+            args.push(format!("-target-abi={}", abi));
+        }
+        args
+    }
+
+    /// clang 的目标参数
+    pub fn clang_args(&self) -> Vec<String> {
+        let mut args = vec!["-target".to_string(), self.triple.clone()];
+        if let Some(march) = self.march {
+            args.push(format!("-march={}", march));
+        }
+        if let Some(abi) = self.abi {
+            args.push(format!("-mabi={}", abi));
+        }
+        args
+    }
+}
+
+impl Default for TargetInfo {
+    /// 当前操作系统的 x86_64 目标
+    fn default() -> Self {
+        Self::for_os(std::env::consts::OS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cross_targets() {
+        let aarch64 = TargetInfo::from_triple("aarch64-unknown-linux-gnu").unwrap();
+        assert_eq!(aarch64.arch, Arch::AArch64);
+        assert_eq!(aarch64.os, "linux");
+        assert_eq!(aarch64.data_layout, "e-m:e-i8:8:32-i16:16:32-i64:64-i128:128-n32:64-S128");
+        assert!(!aarch64.c_char_signed);
+
+        let riscv = TargetInfo::from_triple("riscv64gc-unknown-linux-gnu").unwrap();
+        assert_eq!(riscv.triple, "riscv64-unknown-linux-gnu");
+        assert_eq!(riscv.llc_args(), vec!["-mtriple=riscv64-unknown-linux-gnu", "-mattr=+m,+a,+f,+d,+c", "-target-abi=lp64d"]);
+
+        let apple = TargetInfo::from_triple("arm64-apple-darwin").unwrap();
+        assert_eq!(apple.triple, "aarch64-apple-darwin");
+        assert!(apple.c_char_signed);
+
+        assert!(TargetInfo::from_triple("riscv64gc-apple-darwin").is_err());
+        assert!(TargetInfo::from_triple("mips-unknown-linux-gnu").is_err());
+    }
+
+    #[test]
+    fn test_c_type_sizes_follow_target() {
+        let windows = TargetInfo::for_os("windows");
+        let linux = TargetInfo::for_os("linux");
+        assert_eq!(windows.triple, "x86_64-w64-mingw32");
+        assert_eq!(windows.size_of(&Type::CLong), Some(4));
+        assert_eq!(linux.size_of(&Type::CLong), Some(8));
+        assert_eq!(windows.c_long_llvm(), "i32");
+        assert_eq!(linux.size_of(&Type::SizeT), Some(8));
+        assert_eq!(linux.size_of(&Type::Object("Foo".into())), Some(8));
+        assert_eq!(linux.size_of(&Type::Struct("Point".into())), None);
+        assert_eq!(linux.size_of(&Type::Void), None);
+    }
+}
@@ -35,6 +35,8 @@ pub enum Type {
     Pointer(Box<Type>),  // 通用指针类型: Pointer(CVoid) = void*
     // FFI 结构体类型
     Struct(String),      // 命名结构体: Struct("SDL_Window")
+    // 可空引用类型: String? / Foo? / int[]?（仅在空安全检查中有意义）
+    Nullable(Box<Type>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -69,7 +71,8 @@ pub struct ConstructorInfo {
 #[derive(Debug, Clone)]
 pub struct InterfaceInfo {
     pub name: String,
-    pub methods: HashMap<String, MethodInfo>,
+    pub parents: Vec<String>,  // 直接继承的父接口
+    pub methods: HashMap<String, MethodInfo>,  // 本接口声明的方法（is_abstract 为 false 的是 default 方法）
 }
 
 impl ClassInfo {
@@ -210,6 +213,8 @@ impl ClassInfo {
 
     /// 检查类型是否匹配（支持基本类型转换）
     fn types_match(param_type: &Type, arg_type: &Type) -> bool {
+        // 可空性由空安全检查负责，这里只比较底层类型
+        let (param_type, arg_type) = (param_type.non_null(), arg_type.non_null());
         if param_type == arg_type {
             return true;
         }
@@ -273,6 +278,7 @@ pub struct MethodInfo {
     pub is_native: bool,
     pub is_override: bool,  // 标记是否是重写方法
     pub is_final: bool,  // 是否是final方法（禁止重写）
+    pub is_abstract: bool,  // 是否没有方法体（抽象方法，或没有 default 实现的接口方法）
 }
 
 #[derive(Debug, Clone)]
@@ -314,39 +320,43 @@ impl ParameterInfo {
 }
 
 impl Type {
-    pub fn size_in_bytes(&self) -> usize {
+    /// 类型在目标平台上的存储大小（字节）
+    ///
+    /// 与 `TargetInfo::size_of` 相同，但 void 为 0，C 结构体按指针传递取指针大小。
+    pub fn size_in_bytes(&self, target: &crate::target::TargetInfo) -> usize {
         match self {
-            Type::Void => 0,
-            Type::Int32 => 4,
-            Type::Int64 => 8,
-            Type::Float32 => 4,
-            Type::Float64 => 8,
-            Type::Bool => 1,
-            Type::Char => 1,
-            Type::String => 8, // 指针大小
-            Type::Object(_) => 8, // 引用类型
-            Type::Array(_) => 8, // 指针大小
-            Type::Function(_) => 8, // 函数指针
+            Type::Void | Type::CVoid => 0,
             Type::Auto => panic!("Cannot get size of auto type - type inference not completed"),
-            // FFI 类型大小 (平台相关，这里使用常见值)
-            Type::CInt => 4,       // C int 通常为 4 字节
-            Type::CUInt => 4,      // C unsigned int 通常为 4 字节
-            Type::CLong => 8,      // C long: Windows 4, Linux/macOS 8，使用 8 作为保守值
-            Type::CShort => 2,     // C short 为 2 字节
-            Type::CUShort => 2,    // C unsigned short 为 2 字节
-            Type::CChar => 1,      // C char 为 1 字节
-            Type::CUChar => 1,     // C unsigned char 为 1 字节
-            Type::CFloat => 4,     // C float 为 4 字节
-            Type::CDouble => 8,    // C double 为 8 字节
-            Type::SizeT => 8,      // size_t 为指针大小 (64位系统)
-            Type::SSizeT => 8,     // ssize_t 为指针大小 (64位系统)
-            Type::UIntPtr => 8,    // uintptr_t 为指针大小
-            Type::IntPtr => 8,     // intptr_t 为指针大小
-            Type::CVoid => 0,      // void 无大小
-            Type::CBool => 1,      // C bool 通常为 1 字节
-            // FFI 指针和结构体
-            Type::Pointer(_) => 8, // 指针大小 (64位系统)
-            Type::Struct(_) => 8,  // 结构体作为指针传递，实际大小由编译器决定
+            Type::Struct(_) => target.pointer_size,
+            _ => target.size_of(self).unwrap_or(target.pointer_size),
+        }
+    }
+
+    /// 是否为可空类型（T?）
+    pub fn is_nullable(&self) -> bool {
+        matches!(self, Type::Nullable(_))
+    }
+
+    /// 去掉最外层的可空标记，返回底层类型
+    pub fn non_null(&self) -> &Type {
+        match self {
+            Type::Nullable(inner) => inner.non_null(),
+            other => other,
+        }
+    }
+
+    /// 递归擦除所有可空标记（语义检查之后的阶段不区分 T 与 T?）
+    pub fn erase_nullability(&self) -> Type {
+        match self {
+            Type::Nullable(inner) => inner.erase_nullability(),
+            Type::Array(inner) => Type::Array(Box::new(inner.erase_nullability())),
+            Type::Pointer(inner) => Type::Pointer(Box::new(inner.erase_nullability())),
+            Type::Function(func) => Type::Function(Box::new(FunctionType {
+                params: func.params.iter().map(|p| p.erase_nullability()).collect(),
+                return_type: Box::new(func.return_type.erase_nullability()),
+                is_static: func.is_static,
+            })),
+            other => other.clone(),
         }
     }
 
@@ -370,7 +380,16 @@ impl Type {
     }
 
     pub fn is_reference_type(&self) -> bool {
-        matches!(self, Type::String | Type::Object(_) | Type::Array(_))
+        matches!(self.non_null(), Type::String | Type::Object(_) | Type::Array(_))
+    }
+
+    /// FFI 裸指针（`ptr`、`c_string`、`T*`）：和 C 一样可以为 null，不参与空安全检查
+    pub fn is_raw_pointer(&self) -> bool {
+        match self.non_null() {
+            Type::Pointer(_) => true,
+            Type::Object(name) => name == "ptr",
+            _ => false,
+        }
     }
 
     pub fn is_integer(&self) -> bool {
@@ -421,6 +440,51 @@ impl fmt::Display for Type {
             // FFI 指针和结构体
             Type::Pointer(inner) => write!(f, "{}*", inner),
             Type::Struct(name) => write!(f, "struct {}", name),
+            Type::Nullable(inner) => write!(f, "{}?", inner),
+        }
+    }
+}
+
+/// `Math.*Exact` 内建函数：溢出时 panic 的 int/long 算术
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExactOp {
+    Add,
+    Subtract,
+    Multiply,
+    Negate,
+    Increment,
+    Decrement,
+}
+
+impl ExactOp {
+    pub fn from_method_name(name: &str) -> Option<Self> {
+        match name {
+            "addExact" => Some(ExactOp::Add),
+            "subtractExact" => Some(ExactOp::Subtract),
+            "multiplyExact" => Some(ExactOp::Multiply),
+            "negateExact" => Some(ExactOp::Negate),
+            "incrementExact" => Some(ExactOp::Increment),
+            "decrementExact" => Some(ExactOp::Decrement),
+            _ => None,
+        }
+    }
+
+    pub fn method_name(self) -> &'static str {
+        match self {
+            ExactOp::Add => "addExact",
+            ExactOp::Subtract => "subtractExact",
+            ExactOp::Multiply => "multiplyExact",
+            ExactOp::Negate => "negateExact",
+            ExactOp::Increment => "incrementExact",
+            ExactOp::Decrement => "decrementExact",
+        }
+    }
+
+    /// 参数个数
+    pub fn arity(self) -> usize {
+        match self {
+            ExactOp::Add | ExactOp::Subtract | ExactOp::Multiply => 2,
+            ExactOp::Negate | ExactOp::Increment | ExactOp::Decrement => 1,
         }
     }
 }
@@ -480,6 +544,7 @@ impl TypeRegistry {
             is_native: false,
             is_final: true,
             is_override: false,
+            is_abstract: false,
         });
 
         // valueOf(long)
@@ -499,6 +564,7 @@ impl TypeRegistry {
             is_native: false,
             is_final: true,
             is_override: false,
+            is_abstract: false,
         });
 
         // valueOf(float)
@@ -518,6 +584,7 @@ impl TypeRegistry {
             is_native: false,
             is_final: true,
             is_override: false,
+            is_abstract: false,
         });
 
         // valueOf(double)
@@ -537,6 +604,7 @@ impl TypeRegistry {
             is_native: false,
             is_final: true,
             is_override: false,
+            is_abstract: false,
         });
 
         // valueOf(boolean)
@@ -556,6 +624,7 @@ impl TypeRegistry {
             is_native: false,
             is_final: true,
             is_override: false,
+            is_abstract: false,
         });
 
         // valueOf(char)
@@ -575,6 +644,7 @@ impl TypeRegistry {
             is_native: false,
             is_final: true,
             is_override: false,
+            is_abstract: false,
         });
 
         // valueOf(String) - 返回自身
@@ -594,6 +664,7 @@ impl TypeRegistry {
             is_native: false,
             is_final: true,
             is_override: false,
+            is_abstract: false,
         });
 
         // 注册 String 类
@@ -632,6 +703,7 @@ impl TypeRegistry {
             is_native: false,
             is_final: true,
             is_override: false,
+            is_abstract: false,
         });
 
         // 注册 Integer 类
@@ -662,6 +734,21 @@ impl TypeRegistry {
         Ok(())
     }
 
+    /// 识别 `Math.<name>(...)` 形式的溢出检查内建函数
+    ///
+    /// 用户（或 caylibs/Math.cay）定义的同名 Math 方法优先于内建函数。
+    pub fn math_exact_intrinsic(&self, class_name: &str, method_name: &str) -> Option<ExactOp> {
+        if class_name != "Math" {
+            return None;
+        }
+        let user_defined = self.get_class("Math")
+            .is_some_and(|c| c.methods.contains_key(method_name));
+        if user_defined {
+            return None;
+        }
+        ExactOp::from_method_name(method_name)
+    }
+
     pub fn get_interface(&self, name: &str) -> Option<&InterfaceInfo> {
         self.interfaces.get(name)
     }
@@ -681,20 +768,35 @@ impl TypeRegistry {
 
     /// 根据类名和方法名获取方法（获取第一个匹配的方法，用于无参数类型信息的情况，支持继承）
     pub fn get_method(&self, class_name: &str, method_name: &str) -> Option<&MethodInfo> {
+        self.get_class_chain_method(class_name, method_name)
+            .or_else(|| {
+                self.implemented_interfaces(class_name).iter()
+                    .find_map(|i| self.interfaces.get(i)?.methods.get(method_name))
+            })
+    }
+
+    fn get_class_chain_method(&self, class_name: &str, method_name: &str) -> Option<&MethodInfo> {
         if let Some(class_info) = self.classes.get(class_name) {
             if let Some(method) = class_info.find_method_by_name(method_name) {
                 return Some(method);
             }
             // 如果在当前类中没找到，递归在父类中查找
             if let Some(ref parent_name) = class_info.parent {
-                return self.get_method(parent_name, method_name);
+                return self.get_class_chain_method(parent_name, method_name);
             }
         }
         None
     }
 
     /// 根据类名、方法名和参数类型查找方法（支持重载和继承）
+    ///
+    /// 类继承链中找不到时，回退到类（或接口本身）实现的接口方法。
     pub fn find_method(&self, class_name: &str, method_name: &str, arg_types: &[Type]) -> Option<&MethodInfo> {
+        self.find_class_chain_method(class_name, method_name, arg_types)
+            .or_else(|| self.find_interface_method(class_name, method_name, arg_types))
+    }
+
+    fn find_class_chain_method(&self, class_name: &str, method_name: &str, arg_types: &[Type]) -> Option<&MethodInfo> {
         // 首先在当前类中查找
         if let Some(class_info) = self.classes.get(class_name) {
             if let Some(method) = class_info.find_method(method_name, arg_types) {
@@ -702,12 +804,103 @@ impl TypeRegistry {
             }
             // 如果在当前类中没找到，递归在父类中查找
             if let Some(ref parent_name) = class_info.parent {
-                return self.find_method(parent_name, method_name, arg_types);
+                return self.find_class_chain_method(parent_name, method_name, arg_types);
             }
         }
         None
     }
 
+    /// 在类型实现的所有接口（含父接口）中查找方法
+    ///
+    /// 多个接口声明了同名方法时，返回最具体的那个：
+    /// 优先选择带 default 实现的方法，且子接口优先于父接口。
+    pub fn find_interface_method(&self, type_name: &str, method_name: &str, arg_types: &[Type]) -> Option<&MethodInfo> {
+        let candidates: Vec<&MethodInfo> = self.implemented_interfaces(type_name).iter()
+            .filter_map(|i| self.interfaces.get(i)?.methods.get(method_name))
+            .filter(|m| ClassInfo::match_method_params(&m.params, arg_types))
+            .collect();
+        candidates.iter()
+            .find(|m| !m.is_abstract)
+            .or_else(|| candidates.first())
+            .copied()
+    }
+
+    /// 获取类型实现的全部接口
+    ///
+    /// 对类：沿继承链收集 implements 的接口及其父接口；
+    /// 对接口：返回接口自身及其所有父接口。
+    /// 结果按广度优先排列（越具体越靠前）且不含重复项。
+    pub fn implemented_interfaces(&self, type_name: &str) -> Vec<String> {
+        let mut queue: std::collections::VecDeque<String> = std::collections::VecDeque::new();
+        if self.interfaces.contains_key(type_name) {
+            queue.push_back(type_name.to_string());
+        } else {
+            let mut visited_classes = Vec::new();
+            let mut current = self.classes.get(type_name);
+            while let Some(class_info) = current {
+                if visited_classes.contains(&class_info.name) {
+                    break;
+                }
+                visited_classes.push(class_info.name.clone());
+                queue.extend(class_info.interfaces.iter().cloned());
+                current = class_info.parent.as_ref().and_then(|p| self.classes.get(p));
+            }
+        }
+
+        let mut result: Vec<String> = Vec::new();
+        while let Some(name) = queue.pop_front() {
+            if result.contains(&name) {
+                continue;
+            }
+            if let Some(info) = self.interfaces.get(&name) {
+                queue.extend(info.parents.iter().cloned());
+                result.push(name);
+            }
+        }
+        result
+    }
+
+    /// 检查类或接口 `type_name` 是否实现（或继承）了接口 `interface_name`
+    pub fn implements_interface(&self, type_name: &str, interface_name: &str) -> bool {
+        self.interfaces.contains_key(interface_name)
+            && self.implemented_interfaces(type_name).iter().any(|i| i == interface_name)
+    }
+
+    /// 检查类 `class_name` 是否是 `ancestor` 自身或其（间接）子类
+    pub fn is_subclass_of(&self, class_name: &str, ancestor: &str) -> bool {
+        let mut visited = Vec::new();
+        let mut current = self.classes.get(class_name);
+        while let Some(class_info) = current {
+            if class_info.name == ancestor {
+                return true;
+            }
+            if visited.contains(&class_info.name) {
+                break;
+            }
+            visited.push(class_info.name.clone());
+            current = class_info.parent.as_ref().and_then(|p| self.classes.get(p));
+        }
+        false
+    }
+
+    /// 类型（类或接口）的全部非抽象子类型，按类名排序
+    ///
+    /// 即运行时可能作为该静态类型出现的对象的实际类，供类层次分析使用。
+    pub fn concrete_subtypes(&self, type_name: &str) -> Vec<String> {
+        let is_interface = self.interfaces.contains_key(type_name);
+        let mut result: Vec<String> = self.classes.values()
+            .filter(|c| !c.is_abstract)
+            .filter(|c| if is_interface {
+                self.implements_interface(&c.name, type_name)
+            } else {
+                self.is_subclass_of(&c.name, type_name)
+            })
+            .map(|c| c.name.clone())
+            .collect();
+        result.sort();
+        result
+    }
+
     /// 根据类名、方法名和参数类型查找方法，只在当前类中查找（不递归父类）
     pub fn find_method_in_class(&self, class_name: &str, method_name: &str, arg_types: &[Type]) -> Option<&MethodInfo> {
         self.classes.get(class_name)
@@ -729,6 +922,7 @@ impl InterfaceInfo {
     pub fn new(name: String) -> Self {
         Self {
             name,
+            parents: Vec::new(),
             methods: HashMap::new(),
         }
     }
@@ -0,0 +1,258 @@
+//! 分离编译：编译单元与接口文件（.cai）
+//!
+//! 分离编译时每个源文件是一个编译单元，`#include` 不再展开被包含文件的内容，而是把它记录为
+//! 依赖的单元。编译单元时读取依赖单元（含间接依赖）的接口文件，语义分析和 IR 构建只处理单元自己的代码：
+//! - 单元输出自己的 LLVM IR（对象）和接口文件，接口文件包含导出的类、接口、顶层函数、extern 和类型别名的签名
+//! - 各单元的 IR 由 `llvm-link` 合并为一个模块，再按原有流程生成可执行文件
+//! - 单元的产物以 `<文件名>-<路径哈希>` 命名，放在同一目录中，依赖单元的接口文件按此名称查找
+
+mod writer;
+
+pub use writer::write_interface;
+pub(crate) use writer::needs_class_init;
+
+use crate::ast::Program;
+use crate::error::{cayError, cayResult};
+use std::path::{Path, PathBuf};
+
+/// 接口文件扩展名
+pub const INTERFACE_EXTENSION: &str = "cai";
+
+/// 编译单元的接口
+#[derive(Debug, Clone)]
+pub struct ModuleInterface {
+    /// 单元源文件的规范化路径
+    pub source: String,
+    /// 直接依赖的单元（规范化路径）
+    pub imports: Vec<String>,
+    /// 接口声明（函数体为空）
+    pub program: Program,
+}
+
+impl ModuleInterface {
+    /// 解析接口文件文本，`path` 只用于错误信息
+    pub fn parse(text: &str, path: &str) -> cayResult<Self> {
+        let mut source = String::new();
+        let mut imports = Vec::new();
+        for line in text.lines().take_while(|l| l.starts_with("//")) {
+            if let Some(value) = line.strip_prefix("// source: ") {
+                source = value.to_string();
+            } else if let Some(value) = line.strip_prefix("// import: ") {
+                imports.push(value.to_string());
+            }
+        }
+        let invalid = |e: cayError| cayError::Io(format!("接口文件 '{}' 无效: {}", path, crate::error::get_error_message(&e)));
+        let tokens = crate::lexer::lex(text).map_err(invalid)?;
+        let program = crate::parser::parse_interface_file(tokens).map_err(invalid)?;
+        Ok(Self { source, imports, program })
+    }
+
+    /// 读取接口文件
+    pub fn load(path: &Path) -> cayResult<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| cayError::Io(format!("无法读取接口文件 '{}': {}", path.display(), e)))?;
+        Self::parse(&text, &path.to_string_lossy())
+    }
+}
+
+/// 按依赖顺序（被依赖的单元在前）读取 `imports` 及其间接依赖的接口文件
+pub fn load_interfaces(imports: &[String], dir: &Path) -> cayResult<Vec<ModuleInterface>> {
+    let mut loaded = Vec::new();
+    let mut visiting = Vec::new();
+    for import in imports {
+        load_interface_recursive(import, dir, &mut loaded, &mut visiting)?;
+    }
+    Ok(loaded)
+}
+
+fn load_interface_recursive(
+    source: &str,
+    dir: &Path,
+    loaded: &mut Vec<ModuleInterface>,
+    visiting: &mut Vec<String>,
+) -> cayResult<()> {
+    if loaded.iter().any(|i| i.source == source) {
+        return Ok(());
+    }
+    if visiting.iter().any(|s| s == source) {
+        return Err(cayError::Io(format!("编译单元之间存在循环依赖: {}", source)));
+    }
+    let path = interface_path(source, dir);
+    if !path.exists() {
+        return Err(cayError::Io(format!(
+            "找不到 '{}' 的接口文件 '{}'，请先编译该单元",
+            source,
+            path.display()
+        )));
+    }
+    let interface = ModuleInterface::load(&path)?;
+    visiting.push(source.to_string());
+    for import in &interface.imports {
+        load_interface_recursive(import, dir, loaded, visiting)?;
+    }
+    visiting.pop();
+    loaded.push(interface);
+    Ok(())
+}
+
+/// 把依赖单元的接口声明合并为一个程序（类型别名、extern、接口、类、顶层函数）
+pub fn merge_interfaces(interfaces: &[ModuleInterface]) -> Program {
+    let mut program = Program::default();
+    for interface in interfaces {
+        program.append(interface.program.clone());
+    }
+    program
+}
+
+/// 单元产物的文件名（不含扩展名）：`<文件名>-<路径哈希>`，不同目录下的同名文件不会冲突
+pub fn artifact_stem(source: &str) -> String {
+    let stem = Path::new(source)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "unit".to_string());
+    format!("{}-{}", stem, &content_hash(source.as_bytes())[..8])
+}
+
+/// 单元的接口文件路径
+pub fn interface_path(source: &str, dir: &Path) -> PathBuf {
+    dir.join(format!("{}.{}", artifact_stem(source), INTERFACE_EXTENSION))
+}
+
+/// 内容哈希（64 位 FNV-1a 的十六进制表示），跨平台和编译器版本稳定
+pub fn content_hash(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// 分离编译时类型的 ID：由类型名哈希得到，各单元独立计算也一致
+pub fn stable_type_id(name: &str) -> i32 {
+    let hash = u64::from_str_radix(&content_hash(name.as_bytes()), 16).unwrap_or(0);
+    (hash & 0x3fff_ffff) as i32
+}
+
+/// 查找 llvm-link：先查 PATH，再查编译器所在目录的 llvm-minimal
+pub fn find_llvm_link() -> Option<PathBuf> {
+    let found = std::process::Command::new("llvm-link")
+        .arg("--version")
+        .output()
+        .is_ok_and(|o| o.status.success());
+    if found {
+        return Some(PathBuf::from("llvm-link"));
+    }
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let sub_path = if cfg!(target_os = "windows") {
+        "llvm-minimal/bin/llvm-link.exe"
+    } else {
+        "llvm-minimal/bin-linux/llvm-link"
+    };
+    [exe_dir.join(sub_path), exe_dir.join("..").join(sub_path)]
+        .into_iter()
+        .find(|p| p.exists())
+}
+
+/// 用 llvm-link 把各单元的 IR 合并为一个 `.ll` 文件
+pub fn link_units(units: &[PathBuf], output: &Path) -> cayResult<()> {
+    let llvm_link = find_llvm_link()
+        .ok_or_else(|| cayError::Io("找不到 llvm-link，请确保 LLVM 已安装并在 PATH 中".to_string()))?;
+    let result = std::process::Command::new(&llvm_link)
+        .args(units)
+        .arg("-S")
+        .arg("-o")
+        .arg(output)
+        .output()
+        .map_err(|e| cayError::Io(format!("执行 llvm-link 失败: {}", e)))?;
+    if !result.status.success() {
+        return Err(cayError::Io(format!(
+            "链接编译单元失败: {}",
+            String::from_utf8_lossy(&result.stderr)
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHAPES: &str = r#"alias Mapper = fn(int) -> int;
+extern {
+    c_int abs(c_int value);
+}
+public interface Shape {
+    int area();
+    default int twice() {
+        return area() * 2;
+    }
+}
+public abstract class Base implements Shape {
+    protected int id;
+    public static int count = 0;
+    public static String label = "base";
+    public Base(int id) {
+        this.id = id;
+    }
+    ~Base() {
+        count = count - 1;
+    }
+    public abstract int area();
+    public int apply(Mapper f, int... values) {
+        return f(values[0]);
+    }
+}
+public int helper(int a, String? name) {
+    return a;
+}
+"#;
+
+    fn interface_text() -> String {
+        let tokens = crate::lexer::lex(SHAPES).unwrap();
+        let program = crate::parser::parse(tokens).unwrap();
+        write_interface(&program, "/src/shapes.cay", &["/src/base.cay".to_string()])
+    }
+
+    #[test]
+    fn test_interface_keeps_only_signatures() {
+        let text = interface_text();
+        assert!(text.contains("// source: /src/shapes.cay\n// import: /src/base.cay\n"));
+        assert!(text.contains("alias __cai_fn0 = fn(int) -> int;\n"));
+        assert!(text.contains("    default int twice();\n"));
+        assert!(text.contains("public abstract class Base implements Shape {\n"));
+        assert!(text.contains("    public static int count;\n"));
+        assert!(text.contains("    public Base(int id);\n"));
+        assert!(text.contains("    ~Base();\n"));
+        assert!(text.contains("    public int apply(__cai_fn0 f, int... values);\n"));
+        // 字符串初始化器在运行时求值，需要静态初始化
+        assert!(text.contains("    static {}\n"));
+        assert!(text.contains("public int helper(int a, string? name);\n"));
+        assert!(!text.contains("return"));
+    }
+
+    #[test]
+    fn test_interface_round_trip() {
+        let interface = ModuleInterface::parse(&interface_text(), "shapes.cai").unwrap();
+        assert_eq!(interface.source, "/src/shapes.cay");
+        assert_eq!(interface.imports, vec!["/src/base.cay".to_string()]);
+        let program = &interface.program;
+        assert_eq!(program.interfaces[0].methods.len(), 2);
+        assert!(program.interfaces[0].methods.iter().all(|m| m.body.is_none()));
+        assert_eq!(program.extern_declarations[0].functions[0].name, "abs");
+        assert_eq!(program.top_level_functions[0].params.len(), 2);
+        let base = &program.classes[0];
+        assert_eq!(base.parent, None);
+        assert!(needs_class_init(base));
+        assert_eq!(write_interface(program, &interface.source, &interface.imports), interface_text());
+    }
+
+    #[test]
+    fn test_artifact_names_are_stable() {
+        assert_eq!(content_hash(b""), "cbf29ce484222325");
+        assert_eq!(artifact_stem("/a/util.cay"), artifact_stem("/a/util.cay"));
+        assert_ne!(artifact_stem("/a/util.cay"), artifact_stem("/b/util.cay"));
+        assert!(artifact_stem("/a/util.cay").starts_with("util-"));
+        assert_eq!(stable_type_id("Base"), stable_type_id("Base"));
+    }
+}
@@ -0,0 +1,225 @@
+//! 接口文件（.cai）的生成
+//!
+//! 接口文件使用 Cavvy 声明语法，只保留签名：方法、构造函数、析构函数和顶层函数以 `;` 结束，
+//! 字段不带初始化器，`static {}` 表示类需要静态初始化（静态初始化块或非常量的静态字段初始化器）。
+
+use crate::ast::*;
+use crate::types::{ParameterInfo, Type};
+use std::fmt::Write;
+
+/// 生成编译单元的接口文件文本
+///
+/// `source` 和 `imports` 是单元及其直接依赖单元的规范化路径，写在文件头的注释中。
+pub fn write_interface(program: &Program, source: &str, imports: &[String]) -> String {
+    let mut writer = InterfaceWriter::default();
+    // 从接口文件读回的程序中含有上次生成的函数类型别名，重新生成即可
+    for alias in program.type_aliases.iter().filter(|a| !a.name.starts_with(FUNCTION_ALIAS_PREFIX)) {
+        let target = writer.alias_target(&alias.target_type);
+        let _ = writeln!(writer.body, "alias {} = {};", alias.name, target);
+    }
+    for extern_decl in &program.extern_declarations {
+        writer.extern_decl(extern_decl);
+    }
+    for interface in &program.interfaces {
+        writer.interface(interface);
+    }
+    for class in &program.classes {
+        writer.class(class);
+    }
+    for func in &program.top_level_functions {
+        let signature = writer.signature(&func.modifiers, &func.return_type, &func.name, &func.params);
+        let _ = writeln!(writer.body, "{};", signature);
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "// Cavvy interface file, generated by cayc {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(out, "// source: {}", source);
+    for import in imports {
+        let _ = writeln!(out, "// import: {}", import);
+    }
+    // 函数类型只能通过别名书写，先输出为其生成的别名
+    for (name, target) in &writer.function_aliases {
+        let _ = writeln!(out, "alias {} = {};", name, target);
+    }
+    out.push_str(&writer.body);
+    out
+}
+
+/// 为函数类型生成的别名的名称前缀
+const FUNCTION_ALIAS_PREFIX: &str = "__cai_fn";
+
+/// 类是否需要静态初始化：有静态初始化块，或有不能作为全局初始值的静态字段初始化器
+pub(crate) fn needs_class_init(class: &ClassDecl) -> bool {
+    class.members.iter().any(|m| match m {
+        ClassMember::StaticInitializer(_) => true,
+        ClassMember::Field(field) => field.modifiers.contains(&Modifier::Static)
+            && field.initializer.as_ref().is_some_and(|init| !is_scalar_literal(init)),
+        _ => false,
+    })
+}
+
+/// 数值、布尔和字符字面量总能作为静态字段的全局初始值
+fn is_scalar_literal(expr: &Expr) -> bool {
+    matches!(expr, Expr::Literal(literal) if !matches!(literal, LiteralValue::String(_) | LiteralValue::Null))
+}
+
+#[derive(Default)]
+struct InterfaceWriter {
+    body: String,
+    /// 为函数类型生成的别名 (名称, 目标类型)
+    function_aliases: Vec<(String, String)>,
+}
+
+impl InterfaceWriter {
+    fn interface(&mut self, interface: &InterfaceDecl) {
+        let mut header = modifiers(&interface.modifiers);
+        let _ = write!(header, "interface {}", interface.name);
+        if !interface.parents.is_empty() {
+            let _ = write!(header, " extends {}", interface.parents.join(", "));
+        }
+        let _ = writeln!(self.body, "{} {{", header);
+        for method in &interface.methods {
+            let signature = self.signature(&method.modifiers, &method.return_type, &method.name, &method.params);
+            let _ = writeln!(self.body, "    {};", signature);
+        }
+        self.body.push_str("}\n");
+    }
+
+    fn class(&mut self, class: &ClassDecl) {
+        let mut header = modifiers(&class.modifiers);
+        let _ = write!(header, "class {}", class.name);
+        if let Some(parent) = &class.parent {
+            let _ = write!(header, " extends {}", parent);
+        }
+        if !class.interfaces.is_empty() {
+            let _ = write!(header, " implements {}", class.interfaces.join(", "));
+        }
+        let _ = writeln!(self.body, "{} {{", header);
+        for member in &class.members {
+            match member {
+                ClassMember::Field(field) => {
+                    let ty = self.ty(&field.field_type);
+                    let _ = writeln!(self.body, "    {}{} {};", modifiers(&field.modifiers), ty, field.name);
+                }
+                ClassMember::Method(method) => {
+                    let signature = self.signature(&method.modifiers, &method.return_type, &method.name, &method.params);
+                    let _ = writeln!(self.body, "    {};", signature);
+                }
+                ClassMember::Constructor(ctor) => {
+                    let params = self.params(&ctor.params);
+                    let _ = writeln!(self.body, "    {}{}({});", modifiers(&ctor.modifiers), class.name, params);
+                }
+                ClassMember::Destructor(dtor) => {
+                    let _ = writeln!(self.body, "    {}~{}();", modifiers(&dtor.modifiers), class.name);
+                }
+                ClassMember::StaticInitializer(_) | ClassMember::InstanceInitializer(_) => {}
+            }
+        }
+        if needs_class_init(class) {
+            self.body.push_str("    static {}\n");
+        }
+        self.body.push_str("}\n");
+    }
+
+    fn extern_decl(&mut self, extern_decl: &ExternDecl) {
+        let convention = match extern_decl.calling_convention {
+            CallingConvention::Cdecl => "",
+            CallingConvention::Stdcall => "stdcall ",
+            CallingConvention::Fastcall => "fastcall ",
+            CallingConvention::Sysv64 => "sysv64 ",
+            CallingConvention::Win64 => "win64 ",
+        };
+        let _ = writeln!(self.body, "extern {}{{", convention);
+        for func in &extern_decl.functions {
+            let ret = self.ty(&func.return_type);
+            let params = self.params(&func.params);
+            let _ = write!(self.body, "    {} {}({})", ret, func.name, params);
+            if let Some(alias) = &func.alias {
+                let _ = write!(self.body, " as {}", alias);
+            }
+            self.body.push_str(";\n");
+        }
+        self.body.push_str("}\n");
+    }
+
+    fn signature(&mut self, mods: &[Modifier], return_type: &Type, name: &str, params: &[ParameterInfo]) -> String {
+        let ret = self.ty(return_type);
+        let params = self.params(params);
+        format!("{}{} {}({})", modifiers(mods), ret, name, params)
+    }
+
+    fn params(&mut self, params: &[ParameterInfo]) -> String {
+        params.iter()
+            .map(|p| match (p.is_varargs, p.name.as_str()) {
+                (true, "...") => "...".to_string(),
+                // 可变参数在内部表示为数组类型，写回元素类型
+                (true, name) => match &p.param_type {
+                    Type::Array(elem) => format!("{}... {}", self.ty(elem), name),
+                    other => format!("{}... {}", self.ty(other), name),
+                },
+                (false, name) => format!("{} {}", self.ty(&p.param_type), name),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// 类型别名的目标可以直接写函数类型
+    fn alias_target(&mut self, ty: &Type) -> String {
+        match ty {
+            Type::Function(func) => {
+                let params: Vec<String> = func.params.iter().map(|p| self.ty(p)).collect();
+                format!("fn({}) -> {}", params.join(", "), self.ty(&func.return_type))
+            }
+            other => self.ty(other),
+        }
+    }
+
+    fn ty(&mut self, ty: &Type) -> String {
+        match ty {
+            Type::Function(_) => {
+                let target = self.alias_target(ty);
+                if let Some((name, _)) = self.function_aliases.iter().find(|(_, t)| *t == target) {
+                    return name.clone();
+                }
+                let name = format!("{}{}", FUNCTION_ALIAS_PREFIX, self.function_aliases.len());
+                self.function_aliases.push((name.clone(), target));
+                name
+            }
+            Type::Array(inner) => format!("{}[]", self.ty(inner)),
+            Type::Pointer(inner) => format!("{}*", self.ty(inner)),
+            Type::Nullable(inner) => format!("{}?", self.ty(inner)),
+            Type::Struct(name) => name.clone(),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// 修饰符前缀（含结尾空格）；`default` 放在最后
+fn modifiers(mods: &[Modifier]) -> String {
+    let mut out = String::new();
+    for modifier in mods.iter().filter(|m| **m != Modifier::Default) {
+        out.push_str(modifier_keyword(modifier));
+        out.push(' ');
+    }
+    if mods.contains(&Modifier::Default) {
+        out.push_str("default ");
+    }
+    out
+}
+
+fn modifier_keyword(modifier: &Modifier) -> &'static str {
+    match modifier {
+        Modifier::Public => "public",
+        Modifier::Private => "private",
+        Modifier::Protected => "protected",
+        Modifier::Static => "static",
+        Modifier::Final => "final",
+        Modifier::Abstract => "abstract",
+        Modifier::Native => "native",
+        Modifier::Main => "@main",
+        Modifier::Override => "@Override",
+        Modifier::Test => "@Test",
+        Modifier::Default => "default",
+        Modifier::Export => "export",
+    }
+}
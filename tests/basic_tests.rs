@@ -156,6 +156,17 @@ fn test_arithmetic_edge_cases() {
     assert!(output.contains("All arithmetic edge case tests completed!"), "Test should complete, got: {}", output);
 }
 
+#[test]
+fn test_checked_arithmetic() {
+    let output = compile_and_run_eol("examples/test_checked_arithmetic.cay").expect("checked arithmetic example should compile and run");
+    assert!(output.contains("addExact(40, 2) = 42"), "addExact should work, got: {}", output);
+    assert!(output.contains("decrementExact(-2147483647) = -2147483648"), "decrementExact should reach MIN_VALUE, got: {}", output);
+    assert!(output.contains("long multiplyExact ok"), "long multiplyExact should work, got: {}", output);
+    assert!(output.contains("int + long addExact widens to long"), "Mixed addExact should use long, got: {}", output);
+    assert!(output.contains("LIMIT + 1 = -2147483648"), "Plain arithmetic should wrap by default, got: {}", output);
+    assert!(output.contains("All checked arithmetic tests completed!"), "Test should complete, got: {}", output);
+}
+
 #[test]
 fn test_loop_patterns() {
     let output = compile_and_run_eol("examples/test_loop_patterns.cay").expect("loop patterns example should compile and run");
@@ -0,0 +1,160 @@
+//! 库输出与 C ABI 导出测试
+//!
+//! 用 `cayc --crate-type=staticlib/cdylib --emit-header` 把 Cavvy 库编译为静态库/动态库和 C 头文件，
+//! 再用系统 C 编译器（cc）编译调用它的 C 程序，检查运行输出。
+//! 环境中没有 cc 或 LLVM 工具链时跳过；动态库还需要 clang 或 ld.lld。
+
+mod common;
+
+use common::{tool_available, write_sources};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+const LIBRARY_SOURCE: &str = r#"public class MathLib {
+    public static int counter = 40;
+    public static String prefix = "Hello, ";
+
+    public static export int add(int a, int b) {
+        return a + b;
+    }
+
+    public static export long factorial(int n) {
+        long result = 1;
+        for (int i = 2; i <= n; i++) {
+            result = result * i;
+        }
+        return result;
+    }
+
+    public static export double average(double a, double b) {
+        return (a + b) / 2.0;
+    }
+
+    public static export bool isEven(long n) {
+        return n % 2 == 0;
+    }
+
+    public static export String greet(String name) {
+        return prefix + name;
+    }
+
+    public static int next() {
+        counter = counter + 1;
+        return counter;
+    }
+
+    public static int hidden() {
+        return 0;
+    }
+}
+"#;
+
+const C_MAIN: &str = r#"#include <stdio.h>
+#include "mathlib.h"
+
+int main(void) {
+    printf("%d\n", add(2, 3));
+    printf("%lld\n", (long long)factorial(10));
+    printf("%.2f\n", average(1.0, 2.0));
+    printf("%d %d\n", isEven(4), isEven(7));
+    printf("%s\n", greet("C"));
+    printf("%d\n", next());
+    return 0;
+}
+"#;
+
+const EXPECTED_OUTPUT: &str = "5\n3628800\n1.50\n1 0\nHello, C\n41\n";
+
+/// 在临时目录写入库源文件和 C 程序
+fn write_program() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    write_sources(dir.path(), &[("mathlib.cay", LIBRARY_SOURCE), ("main.c", C_MAIN)]);
+    dir
+}
+
+/// 编译 Cavvy 库，返回库文件路径
+fn build_library(dir: &Path, crate_type: &str) -> PathBuf {
+    let output = Command::new(env!("CARGO_BIN_EXE_cayc"))
+        .arg(format!("--crate-type={}", crate_type))
+        .arg("--export").arg("MathLib.next")
+        .arg("--emit-header=mathlib.h")
+        .arg("mathlib.cay")
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "cayc 失败:\n{}\n{}",
+        String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    let lib = if crate_type == "staticlib" { "libmathlib.a" } else { "libmathlib.so" };
+    dir.join(lib)
+}
+
+/// 用 cc 编译并运行 C 程序，返回标准输出
+fn run_c_program(dir: &Path, extra_args: &[&str]) -> String {
+    let exe = dir.join("main");
+    let output = Command::new("cc")
+        .arg("main.c")
+        .args(["-L.", "-lmathlib", "-lm", "-o"])
+        .arg(&exe)
+        .args(extra_args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "cc 失败:\n{}", String::from_utf8_lossy(&output.stderr));
+    let run = Command::new(&exe).current_dir(dir).output().unwrap();
+    assert!(run.status.success());
+    String::from_utf8_lossy(&run.stdout).to_string()
+}
+
+#[test]
+fn test_generated_header_declares_exports() {
+    if !(tool_available("cc") && (tool_available("clang") || tool_available("llc"))) {
+        eprintln!("跳过：没有 cc 或 LLVM 工具链");
+        return;
+    }
+    let dir = write_program();
+    build_library(dir.path(), "staticlib");
+    let header = std::fs::read_to_string(dir.path().join("mathlib.h")).unwrap();
+    assert!(header.contains("#ifndef MATHLIB_H"));
+    assert!(header.contains("#include <stdint.h>"));
+    assert!(header.contains("int32_t add(int32_t a, int32_t b);"));
+    assert!(header.contains("int64_t factorial(int32_t n);"));
+    assert!(header.contains("bool isEven(int64_t n);"));
+    assert!(header.contains("const char* greet(const char* name);"));
+    assert!(header.contains("int32_t next(void);"));
+    assert!(!header.contains("hidden"));
+}
+
+#[test]
+fn test_c_program_links_static_library() {
+    if !(tool_available("cc") && (tool_available("clang") || tool_available("llc"))) {
+        eprintln!("跳过：没有 cc 或 LLVM 工具链");
+        return;
+    }
+    let dir = write_program();
+    let lib = build_library(dir.path(), "staticlib");
+    assert!(lib.exists());
+    // 静态初始化（字符串前缀、计数器）在库的初始化函数中完成
+    assert_eq!(run_c_program(dir.path(), &[]), EXPECTED_OUTPUT);
+
+    // 只有导出函数是外部可见的
+    if tool_available("nm") {
+        let nm = Command::new("nm").arg("-g").arg("--defined-only").arg(&lib).output().unwrap();
+        let symbols = String::from_utf8_lossy(&nm.stdout);
+        assert!(symbols.contains(" T add\n"));
+        assert!(symbols.contains(" T next\n"));
+        assert!(!symbols.contains("hidden"));
+        assert!(!symbols.contains("__cay_string_concat"));
+    }
+}
+
+#[test]
+fn test_c_program_links_shared_library() {
+    if !(tool_available("cc") && (tool_available("clang") || (tool_available("llc") && tool_available("ld.lld")))) {
+        eprintln!("跳过：没有 cc、clang 或 llc + ld.lld");
+        return;
+    }
+    let dir = write_program();
+    assert!(build_library(dir.path(), "cdylib").exists());
+    assert_eq!(run_c_program(dir.path(), &["-Wl,-rpath,."]), EXPECTED_OUTPUT);
+}
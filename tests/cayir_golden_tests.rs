@@ -0,0 +1,72 @@
+//! `.cayir` golden 测试
+//!
+//! `tests/golden/ir/<name>.cay` 经 IrBuilder 生成的模块与 `<name>.cayir` 比较，
+//! 每个优化 pass 单独运行后的模块与 `<name>.<pass>.cayir` 比较。
+//! 设置 `CAY_UPDATE_GOLDEN=1` 重新生成 golden 文件。
+
+use cavvy::ir::passes::AVAILABLE_PASSES;
+use cavvy::ir::{parse_cayir, write_cayir, IrModule, PassManager};
+use cavvy::Compiler;
+use std::path::{Path, PathBuf};
+
+const GOLDEN_DIR: &str = "tests/golden/ir";
+
+fn golden_sources() -> Vec<PathBuf> {
+    let mut sources: Vec<PathBuf> = std::fs::read_dir(GOLDEN_DIR)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "cay"))
+        .collect();
+    sources.sort();
+    sources
+}
+
+/// 生成模块；运行时支持代码与被测代码无关，不写入 golden 文件
+fn build_module(source: &Path) -> IrModule {
+    let mut module = Compiler::new()
+        .build_ir_module_file(source.to_str().unwrap())
+        .unwrap_or_else(|e| panic!("{} 生成 IR 失败: {:?}", source.display(), e));
+    module.runtime_prelude.clear();
+    module
+}
+
+/// 与 golden 文件比较，并检查文本能解析回同一模块
+fn check_golden(path: &Path, module: &IrModule, failures: &mut Vec<String>) {
+    let actual = write_cayir(module);
+    let reparsed = parse_cayir(&actual).unwrap_or_else(|e| panic!("{} 无法解析: {:?}", path.display(), e));
+    assert_eq!(write_cayir(&reparsed), actual, "{} 往返不一致", path.display());
+
+    if std::env::var_os("CAY_UPDATE_GOLDEN").is_some() {
+        std::fs::write(path, &actual).unwrap();
+        return;
+    }
+    match std::fs::read_to_string(path) {
+        Ok(expected) if expected == actual => {}
+        Ok(_) => failures.push(format!("{} 与输出不一致", path.display())),
+        Err(_) => failures.push(format!("{} 不存在", path.display())),
+    }
+}
+
+#[test]
+fn test_ir_builder_golden() {
+    let mut failures = Vec::new();
+    for source in golden_sources() {
+        let module = build_module(&source);
+        check_golden(&source.with_extension("cayir"), &module, &mut failures);
+    }
+    assert!(failures.is_empty(), "golden 不一致（CAY_UPDATE_GOLDEN=1 重新生成）:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn test_pass_golden() {
+    let mut failures = Vec::new();
+    for source in golden_sources() {
+        let module = build_module(&source);
+        for (pass, _) in AVAILABLE_PASSES {
+            let mut optimized = module.clone();
+            PassManager::from_names(pass).unwrap().run(&mut optimized).unwrap();
+            check_golden(&source.with_extension(format!("{}.cayir", pass)), &optimized, &mut failures);
+        }
+    }
+    assert!(failures.is_empty(), "golden 不一致（CAY_UPDATE_GOLDEN=1 重新生成）:\n{}", failures.join("\n"));
+}
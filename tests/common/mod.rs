@@ -1,9 +1,13 @@
 //! Cavvy 语言集成测试公共模块
 //!
 //! 提供测试辅助函数和工具，被多个测试 crate 共享
+//! 各测试 crate 只用到其中一部分函数
 
-use std::process::Command;
+#![allow(dead_code)]
+
+use std::process::{Command, Stdio};
 use std::fs;
+use std::path::Path;
 use std::sync::Mutex;
 use std::time::Duration;
 
@@ -29,6 +33,23 @@ fn get_exe_extension() -> &'static str {
     }
 }
 
+/// 检查外部工具（lli、llc、cc 等）是否可用
+pub fn tool_available(tool: &str) -> bool {
+    Command::new(tool).arg("--version").stdout(Stdio::null()).stderr(Stdio::null()).status().is_ok()
+}
+
+/// 在 `dir` 中写入源文件（文件名可带子目录），返回按给定顺序排列的路径
+///
+/// `dir` 通常是 `tempfile::TempDir` 的路径，测试结束时随之删除。
+pub fn write_sources(dir: &Path, files: &[(&str, &str)]) -> Vec<String> {
+    files.iter().map(|(name, content)| {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }).collect()
+}
+
 /// 全局测试锁，确保测试串行执行避免文件冲突
 static TEST_LOCK: Mutex<()> = Mutex::new(());
 
@@ -0,0 +1,94 @@
+//! 交叉编译目标测试
+//!
+//! 用 `CompilerOptions::target_triple` 为 aarch64/riscv64/Windows 生成 LLVM IR，
+//! 检查 `target datalayout`、`target triple` 和 `sizeof` 常量，
+//! 环境中有 llc 时再用对应的目标参数编译为目标文件。
+
+mod common;
+
+use cavvy::target::TargetInfo;
+use cavvy::{Compiler, CompilerOptions};
+use common::tool_available;
+use std::process::Command;
+use tempfile::TempDir;
+
+const SOURCE: &str = r#"public class Sizes {
+    public static final long LONG_SIZE = sizeof(c_long);
+
+    public static void main() {
+        println(sizeof(int));
+        println(sizeof(size_t));
+        println(sizeof(c_long));
+        println(LONG_SIZE);
+        println(sizeof(Sizes));
+    }
+}
+"#;
+
+/// 按目标三元组编译示例，返回生成的 LLVM IR
+fn compile_for(triple: &str, legacy: bool) -> String {
+    let name = format!("{}_{}", triple, if legacy { "legacy" } else { "ir" });
+    let dir = TempDir::new().unwrap();
+    let ir_path = dir.path().join("out.ll");
+    let compiler = Compiler::with_options(CompilerOptions {
+        target_triple: Some(triple.to_string()),
+        legacy_codegen: legacy,
+        ..CompilerOptions::default()
+    });
+    compiler.compile(SOURCE, ir_path.to_str().unwrap())
+        .unwrap_or_else(|e| panic!("{} 编译失败: {:?}", name, e));
+    std::fs::read_to_string(&ir_path).unwrap()
+}
+
+#[test]
+fn test_target_triple_sets_data_layout() {
+    for triple in ["aarch64-unknown-linux-gnu", "riscv64gc-unknown-linux-gnu", "x86_64-w64-mingw32"] {
+        let target = TargetInfo::from_triple(triple).unwrap();
+        for legacy in [false, true] {
+            let ir = compile_for(triple, legacy);
+            assert!(ir.contains(&format!("target datalayout = \"{}\"", target.data_layout)),
+                "{} 的 IR 缺少数据布局:\n{}", triple, ir);
+            assert!(ir.contains(&format!("target triple = \"{}\"", target.triple)),
+                "{} 的 IR 缺少目标三元组:\n{}", triple, ir);
+        }
+    }
+}
+
+#[test]
+fn test_sizeof_follows_target() {
+    // Windows 是 LLP64，c_long 为 4 字节；静态常量在编译期折叠
+    for (triple, size) in [("x86_64-w64-mingw32", 4), ("aarch64-unknown-linux-gnu", 8), ("riscv64gc-unknown-linux-gnu", 8)] {
+        for legacy in [false, true] {
+            let ir = compile_for(triple, legacy);
+            let expected = format!("@Sizes.LONG_SIZE_s = private global i64 {}", size);
+            assert!(ir.contains(&expected), "{} 上 sizeof(c_long) 应为 {}:\n{}", triple, size, ir);
+        }
+    }
+}
+
+#[test]
+fn test_cross_target_ir_compiles_with_llc() {
+    if !tool_available("llc") {
+        eprintln!("跳过: 未找到 llc");
+        return;
+    }
+    for triple in ["aarch64-unknown-linux-gnu", "riscv64gc-unknown-linux-gnu"] {
+        let target = TargetInfo::from_triple(triple).unwrap();
+        for legacy in [false, true] {
+            let name = format!("{}_{}", triple, if legacy { "legacy" } else { "ir" });
+            let dir = TempDir::new().unwrap();
+            let ir_path = dir.path().join("out.ll");
+            let obj_path = dir.path().join("out.o");
+            std::fs::write(&ir_path, compile_for(triple, legacy)).unwrap();
+            let output = Command::new("llc")
+                .args(target.llc_args())
+                .arg("-filetype=obj")
+                .arg(&ir_path)
+                .arg("-o")
+                .arg(&obj_path)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "llc 无法编译 {}:\n{}", name, String::from_utf8_lossy(&output.stderr));
+        }
+    }
+}
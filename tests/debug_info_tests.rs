@@ -0,0 +1,152 @@
+//! DWARF 调试信息测试
+//!
+//! 以 `-g`（`CompilerOptions::debug_info`）编译带 #include 的程序，用 llc 生成目标文件，
+//! 检查 `llvm-dwarfdump` 输出中的函数、变量、类类型以及它们所在的文件和行号。
+//! 环境中没有 llc 或 llvm-dwarfdump 时跳过。
+
+mod common;
+
+use cavvy::{Compiler, CompilerOptions};
+use common::{tool_available, write_sources};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+const MAIN_SOURCE: &str = r#"#include "shapes.cay"
+
+public class Main {
+    public static void main() {
+        Point p = new Point(3);
+        int area = p.scaled(4);
+        println(area);
+    }
+}
+"#;
+
+const INCLUDED_SOURCE: &str = r#"public class Point {
+    int x;
+    double weight;
+
+    public Point(int x) {
+        this.x = x;
+    }
+
+    public int scaled(int factor) {
+        int result = x * factor;
+        return result;
+    }
+}
+"#;
+
+fn tools_available() -> bool {
+    ["llc", "llvm-dwarfdump"].iter().all(|tool| tool_available(tool))
+}
+
+/// 在临时目录写入源文件，返回主文件路径
+fn write_program(dir: &TempDir) -> PathBuf {
+    let sources = write_sources(dir.path(), &[("shapes.cay", INCLUDED_SOURCE), ("main.cay", MAIN_SOURCE)]);
+    PathBuf::from(&sources[1])
+}
+
+/// 带调试信息编译并生成目标文件，返回 (LLVM IR, dwarfdump --debug-info, dwarfdump --debug-line)
+fn compile_with_debug_info(main: &Path, legacy: bool) -> (String, String, String) {
+    let ir_path = main.with_extension("ll");
+    let obj_path = main.with_extension("o");
+    let compiler = Compiler::with_options(CompilerOptions {
+        debug_info: true,
+        legacy_codegen: legacy,
+        ..CompilerOptions::default()
+    });
+    compiler.compile_file(main.to_str().unwrap(), ir_path.to_str().unwrap()).unwrap();
+    let ir = std::fs::read_to_string(&ir_path).unwrap();
+
+    let llc = Command::new("llc").arg("-filetype=obj").arg(&ir_path).arg("-o").arg(&obj_path).output().unwrap();
+    assert!(llc.status.success(), "llc 失败:\n{}", String::from_utf8_lossy(&llc.stderr));
+    let dump = |section: &str| {
+        let output = Command::new("llvm-dwarfdump").arg(section).arg(&obj_path).output().unwrap();
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    (ir, dump("--debug-info"), dump("--debug-line"))
+}
+
+/// dwarfdump 中名为 `name` 的 DIE 的属性块（到下一个 DIE 为止）
+fn die<'a>(dump: &'a str, name: &str) -> &'a str {
+    let needle = format!("DW_AT_name\t(\"{}\")", name);
+    let start = dump.find(&needle).unwrap_or_else(|| panic!("没有名为 {} 的 DIE:\n{}", name, dump));
+    let rest = &dump[start..];
+    let end = rest.find("\n\n").unwrap_or(rest.len());
+    &rest[..end]
+}
+
+#[test]
+fn test_dwarf_names_source_files_and_lines() {
+    if !tools_available() {
+        eprintln!("跳过: 未找到 llc 或 llvm-dwarfdump");
+        return;
+    }
+    let dir = TempDir::new().unwrap();
+    let main = write_program(&dir);
+    let (_, info, lines) = compile_with_debug_info(&main, false);
+
+    // 编译单元是主文件
+    assert!(info.contains("DW_AT_name\t(\"main.cay\")"), "{}", info);
+
+    // 来自 #include 文件的方法映射回原始文件和行号
+    let scaled = die(&info, "Point.__scaled_i");
+    assert!(scaled.contains("shapes.cay\")"), "{}", scaled);
+    assert!(scaled.contains("DW_AT_decl_line\t(9)"), "{}", scaled);
+    let main_method = die(&info, "Main.main");
+    assert!(main_method.contains("main.cay\")"), "{}", main_method);
+    assert!(main_method.contains("DW_AT_decl_line\t(4)"), "{}", main_method);
+
+    // 参数、局部变量和类的字段
+    assert!(die(&info, "factor").contains("DW_AT_decl_line\t(9)"), "{}", info);
+    assert!(die(&info, "result").contains("DW_AT_decl_line\t(10)"), "{}", info);
+    assert!(die(&info, "area").contains("DW_AT_decl_line\t(6)"), "{}", info);
+    assert!(info.contains("DW_TAG_class_type"), "{}", info);
+    assert!(die(&info, "weight").contains("DW_AT_data_member_location\t(0x10)"), "{}", info);
+
+    // 行号表包含两个文件中的语句行
+    assert!(lines.contains("name: \"shapes.cay\""), "{}", lines);
+    assert!(lines.contains("name: \"main.cay\""), "{}", lines);
+}
+
+/// 旧代码生成器只生成函数和行号，没有局部变量和类类型
+#[test]
+fn test_legacy_codegen_line_info() {
+    if !tools_available() {
+        eprintln!("跳过: 未找到 llc 或 llvm-dwarfdump");
+        return;
+    }
+    let dir = TempDir::new().unwrap();
+    let main = write_program(&dir);
+    let (ir, info, lines) = compile_with_debug_info(&main, true);
+
+    assert!(ir.contains("!llvm.dbg.cu"), "旧代码生成器的输出缺少调试信息");
+    let scaled = die(&info, "Point.__scaled_i");
+    assert!(scaled.contains("shapes.cay\")"), "{}", scaled);
+    assert!(lines.contains("name: \"shapes.cay\""), "{}", lines);
+
+    assert!(!ir.contains("DILocalVariable"), "旧代码生成器不生成局部变量调试信息");
+    assert!(!ir.contains("DICompositeType"), "旧代码生成器不生成类类型调试信息");
+    assert!(!info.contains("DW_TAG_variable") && !info.contains("DW_TAG_class_type"), "{}", info);
+}
+
+#[test]
+fn test_legacy_codegen_debug_info_warns() {
+    let dir = TempDir::new().unwrap();
+    let main = write_program(&dir);
+    let exe = dir.path().join("main");
+    let cayc_stderr = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_cayc"))
+            .args(args).arg(&main).arg(&exe).current_dir(dir.path()).output().unwrap();
+        String::from_utf8_lossy(&output.stderr).to_string()
+    };
+
+    // 警告在编译开始前输出；环境中可能没有链接器，不检查退出码
+    let stderr = cayc_stderr(&["-g", "--legacy-codegen"]);
+    assert!(stderr.contains("--legacy-codegen 的调试信息只有函数和行号"), "{}", stderr);
+
+    let stderr = cayc_stderr(&["-g"]);
+    assert!(!stderr.contains("--legacy-codegen 的调试信息"), "{}", stderr);
+}
@@ -4,7 +4,13 @@
 
 use cavvy::diagnostic::*;
 use cavvy::lexer::{lex, lex_with_diagnostics};
-use cavvy::parser::parse;
+use cavvy::parser::{parse, parse_recovering};
+use cavvy::preprocessor::Preprocessor;
+use cavvy::ast::{ClassMember, Stmt};
+use cavvy::semantic::SemanticAnalyzer;
+use cavvy::types::Type;
+use std::path::PathBuf;
+use std::process::Command;
 
 // ==================== 诊断系统基础测试 ====================
 
@@ -366,6 +372,252 @@ fn test_comprehensive_error_scenario() {
     assert!(phases.contains(&CompilationPhase::Semantic));
 }
 
+// ==================== 语法错误恢复测试 ====================
+
+#[test]
+fn test_parser_recovers_after_missing_semicolon() {
+    let source = r#"public class Test {
+    public static void main() {
+        int x = 1
+        int y = ;
+        println(x);
+    }
+}"#;
+    let tokens = lex(source).unwrap();
+    let output = parse_recovering(tokens);
+
+    // 两处语法错误都应被报告
+    assert_eq!(output.errors.len(), 2, "errors: {:?}", output.errors);
+    assert_eq!(output.program.classes.len(), 1);
+
+    let ClassMember::Method(main) = &output.program.classes[0].members[0] else {
+        panic!("期望 main 方法");
+    };
+    let body = main.body.as_ref().unwrap();
+    assert!(body.statements.iter().any(|s| matches!(s, Stmt::Error(_))));
+    // 出错语句之后的语句仍被解析
+    assert!(matches!(body.statements.last(), Some(Stmt::Expr(_))));
+}
+
+#[test]
+fn test_parse_still_returns_first_error() {
+    let source = r#"public class Test {
+    public static void main() {
+        int x = 1
+        int y = ;
+    }
+}"#;
+    let first = parse(lex(source).unwrap()).unwrap_err();
+    let output = parse_recovering(lex(source).unwrap());
+    assert_eq!(first.to_string(), output.errors[0].to_string());
+}
+
+#[test]
+fn test_parser_recovers_at_member_boundary() {
+    let source = r#"public class Test {
+    public int broken( {
+        return 1;
+    }
+    public int ok() {
+        return 2;
+    }
+}
+class Other {
+    int x;
+}"#;
+    let output = parse_recovering(lex(source).unwrap());
+    assert!(output.has_errors());
+    assert_eq!(output.program.classes.len(), 2);
+
+    let names: Vec<_> = output.program.classes[0].members.iter()
+        .filter_map(|m| match m {
+            ClassMember::Method(method) => Some(method.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(names, vec!["ok"]);
+}
+
+#[test]
+fn test_parser_recovers_at_top_level() {
+    let source = r#"int 42;
+class A {
+}
+; class B {
+}"#;
+    let output = parse_recovering(lex(source).unwrap());
+    assert_eq!(output.errors.len(), 2, "errors: {:?}", output.errors);
+    let names: Vec<_> = output.program.classes.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["A", "B"]);
+}
+
+#[test]
+fn test_parser_truncated_file_keeps_partial_ast() {
+    let source = r#"public class Test {
+    public static void main() {
+        int x = 1;"#;
+    let output = parse_recovering(lex(source).unwrap());
+    assert!(output.has_errors());
+    assert_eq!(output.program.classes.len(), 1);
+    assert_eq!(output.program.classes[0].members.len(), 1);
+}
+
+// ==================== 空安全检查测试 ====================
+
+const NULLABLE_DEREF_SOURCE: &str = r#"public class Test {
+    static String? find(int key) {
+        if (key > 0) {
+            return "found";
+        }
+        return null;
+    }
+
+    public static void main() {
+        String? s = find(1);
+        println(s.length());
+        if (s != null) {
+            println(s.length());
+        }
+    }
+}"#;
+
+#[test]
+fn test_nullable_type_annotation_parsing() {
+    let program = parse(lex(NULLABLE_DEREF_SOURCE).unwrap()).unwrap();
+    let ClassMember::Method(find) = &program.classes[0].members[0] else {
+        panic!("期望 find 方法");
+    };
+    assert_eq!(find.return_type, Type::Nullable(Box::new(Type::String)));
+    assert_eq!(find.return_type.to_string(), "string?");
+}
+
+#[test]
+fn test_ternary_not_mistaken_for_nullable_type() {
+    let source = r#"public class Test {
+    static int pick(bool flag, int a, int b) {
+        return flag ? a : b;
+    }
+    public static void main() {
+        bool flag = true;
+        int x = 1;
+        int y = 2;
+        int z = 0;
+        z = flag ? pick(flag, x, y) : y;
+    }
+}"#;
+    let program = parse(lex(source).unwrap()).unwrap();
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&program).unwrap();
+}
+
+#[test]
+fn test_null_safety_warn_only_by_default() {
+    let program = parse(lex(NULLABLE_DEREF_SOURCE).unwrap()).unwrap();
+    let mut analyzer = SemanticAnalyzer::new();
+
+    // 未启用 null_safety 时只产生警告，编译继续
+    analyzer.analyze(&program).unwrap();
+    assert_eq!(analyzer.warnings().len(), 1, "warnings: {:?}", analyzer.warnings());
+    assert_eq!(analyzer.warnings()[0].line, 11);
+}
+
+#[test]
+fn test_null_safety_errors_when_enabled() {
+    let program = parse(lex(NULLABLE_DEREF_SOURCE).unwrap()).unwrap();
+    let mut analyzer = SemanticAnalyzer::with_features(vec!["null_safety".to_string()]);
+
+    let err = analyzer.analyze(&program).unwrap_err();
+    assert!(err.to_string().contains("可能为 null"), "{}", err);
+}
+
+#[test]
+fn test_null_safety_allows_null_raw_pointers() {
+    let source = r#"extern {
+    ptr malloc(size_t size);
+    c_string getenv(c_string name);
+}
+
+public int main() {
+    ptr p = null;
+    c_string home = null;
+    c_char* raw = null;
+    p = malloc(16);
+    home = getenv("HOME");
+    if (p == null || home == null || raw == null) {
+        return 1;
+    }
+    return 0;
+}"#;
+    let program = parse(lex(source).unwrap()).unwrap();
+    let mut analyzer = SemanticAnalyzer::with_features(vec!["null_safety".to_string()]);
+
+    // ptr、c_string 和 T* 与 C 指针一样可以为 null
+    analyzer.analyze(&program).unwrap();
+    assert!(analyzer.warnings().is_empty(), "warnings: {:?}", analyzer.warnings());
+}
+
+#[test]
+fn test_null_safety_return_null_reports_method_line() {
+    let source = r#"public class Main {
+    static int count;
+
+    static String find(int key) {
+        count = key;
+        return null;
+    }
+
+    public static void main() {
+        println(find(1));
+    }
+}"#;
+    let program = parse(lex(source).unwrap()).unwrap();
+    let mut analyzer = SemanticAnalyzer::new();
+
+    // `null` 字面量没有位置信息，报告在方法声明处而不是 1:1
+    analyzer.analyze(&program).unwrap();
+    assert_eq!(analyzer.warnings().len(), 1, "warnings: {:?}", analyzer.warnings());
+    assert!(analyzer.warnings()[0].message.contains("返回类型"), "{:?}", analyzer.warnings());
+    assert_eq!(analyzer.warnings()[0].line, 4);
+}
+
+#[test]
+fn test_null_safety_stdlib_has_no_warnings() {
+    // File.cay 和 StringBuilder.cay 都定义了 StringBuilder，逐个头文件检查
+    let headers = ["Allocator.cay", "File.cay", "IOPlus.cay", "Math.cay", "StringBuilder.cay", "StringPlus.cay", "std/ffi.cay"];
+    for header in headers {
+        let source = format!("#include \"{}\"\n\npublic class Main {{\n    public static void main() {{\n        println(1);\n    }}\n}}\n", header);
+        let mut pp = Preprocessor::with_include_paths(".", vec![PathBuf::from("caylibs")]);
+        let processed = pp.process(&source, "main.cay").unwrap();
+        let program = parse(lex(&processed).unwrap()).unwrap();
+        let mut analyzer = SemanticAnalyzer::new();
+
+        // 标准库中可能返回 null 的方法和字段已声明为可空类型，默认模式下不产生警告
+        analyzer.analyze(&program).unwrap_or_else(|e| panic!("{}: {}", header, e));
+        assert!(analyzer.warnings().is_empty(), "{}: {:?}", header, analyzer.warnings());
+    }
+}
+
+#[test]
+fn test_cay_check_null_safety_feature() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("main.cay");
+    std::fs::write(&path, NULLABLE_DEREF_SOURCE).unwrap();
+    let check = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_cay-check")).args(args).arg(&path).output().unwrap()
+    };
+
+    // 默认只警告，行号为原始源文件中的行号
+    let output = check(&[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(stderr.contains("main.cay:11:"), "{}", stderr);
+
+    // -F null_safety 时为错误
+    let output = check(&["-F", "null_safety"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("可能为 null"));
+}
+
 // ==================== 边缘情况测试 ====================
 
 #[test]
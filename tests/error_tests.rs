@@ -198,6 +198,55 @@ fn test_error_division_by_zero() {
     );
 }
 
+#[test]
+fn test_error_null_dereference() {
+    let error = compile_and_run_expect_error("examples/errors/error_null_dereference.cay")
+        .expect("null dereference should fail at runtime");
+    assert!(
+        error.contains("null pointer dereference") && error.contains("error_null_dereference.cay:8"),
+        "Should report null dereference with source location, got: {}",
+        error
+    );
+    assert!(
+        error.contains("TestNullDereference.unwrap") && error.contains("TestNullDereference.main"),
+        "Should print a backtrace of Cavvy functions, got: {}",
+        error
+    );
+}
+
+#[test]
+fn test_error_invalid_downcast() {
+    let error = compile_and_run_expect_error("examples/errors/error_invalid_downcast.cay")
+        .expect("invalid downcast should fail at runtime");
+    assert!(
+        error.contains("invalid object cast") && error.contains("error_invalid_downcast.cay:14"),
+        "Should report invalid cast with source location, got: {}",
+        error
+    );
+}
+
+#[test]
+fn test_error_add_exact_overflow() {
+    let error = compile_and_run_expect_error("examples/errors/error_add_exact_overflow.cay")
+        .expect("Math.addExact overflow should fail at runtime");
+    assert!(
+        error.contains("integer overflow") && error.contains("error_add_exact_overflow.cay:4"),
+        "Should report integer overflow with source location, got: {}",
+        error
+    );
+}
+
+#[test]
+fn test_error_static_final_overflow() {
+    let error = compile_eol_expect_error("examples/errors/error_static_final_overflow.cay")
+        .expect("overflowing static final constant should fail to compile");
+    assert!(
+        error.contains("2147483647 + 1 overflows int") && error.contains("TestStaticFinalOverflow.NEXT"),
+        "Should report constant overflow, got: {}",
+        error
+    );
+}
+
 #[test]
 fn test_error_modulo_by_zero() {
     let error = compile_and_run_expect_error("examples/errors/error_modulo_by_zero.cay")
@@ -341,3 +390,37 @@ fn test_error_array_store() {
         error
     );
 }
+
+#[test]
+fn test_error_assert_equals_failed() {
+    let error = compile_and_run_expect_error("examples/errors/error_assert_equals_failed.cay")
+        .expect("failing assertEquals should fail at runtime");
+    assert!(
+        error.contains("assertion failed: greeting: expected \"Hello, World\", actual \"Hello, Cavvy\"")
+            && error.contains("error_assert_equals_failed.cay:9"),
+        "Should report the failing assertion with source location, got: {}",
+        error
+    );
+}
+
+#[test]
+fn test_error_test_method_params() {
+    let error = compile_eol_expect_error("examples/errors/error_test_method_params.cay")
+        .expect("@Test method with parameters should fail to compile");
+    assert!(
+        error.contains("Test method 'CalcTest.adds' must not take parameters"),
+        "Should report invalid test method, got: {}",
+        error
+    );
+}
+
+#[test]
+fn test_error_assert_equals_types() {
+    let error = compile_eol_expect_error("examples/errors/error_assert_equals_types.cay")
+        .expect("assertEquals with incompatible types should fail to compile");
+    assert!(
+        error.contains("assertEquals cannot compare 'int' with 'string'"),
+        "Should report incompatible assertEquals operands, got: {}",
+        error
+    );
+}
@@ -76,6 +76,33 @@ fn test_instanceof() {
             "Should report rect is Drawable (interface), got: {}", output);
 }
 
+#[test]
+fn test_interface_default_methods() {
+    let output = compile_and_run_eol("examples/test_interface_default.cay")
+        .expect("interface default methods should compile and run");
+    assert!(output.contains("Hello, Square"),
+            "Should call inherited default method, got: {}", output);
+    assert!(output.contains("Ding from Bell"),
+            "Should call overriding method through interface, got: {}", output);
+    assert!(output.contains("tiny"),
+            "Subclass override should win over default method, got: {}", output);
+    assert!(output.contains("square is Named"),
+            "instanceof should see super-interfaces, got: {}", output);
+    assert!(output.contains("bell is not Shape"),
+            "instanceof should reject unrelated interfaces, got: {}", output);
+}
+
+#[test]
+fn test_error_interface_missing_method() {
+    let error = compile_eol_expect_error("examples/errors/error_interface_missing_method.cay")
+        .expect("missing interface method should fail to compile");
+    assert!(
+        error.contains("must implement abstract method 'area()'"),
+        "Should report missing interface method, got: {}",
+        error
+    );
+}
+
 // ==================== 0.4.4.x 静态与 Final 语义测试 ====================
 
 #[test]
@@ -0,0 +1,216 @@
+//! 结构化 IR 流水线与旧代码生成器的运行时输出对比
+//!
+//! 同一份示例分别经 IrBuilder 流水线（默认）和 `--legacy-codegen` 编译为 LLVM IR，
+//! 用 lli 运行后比较 stdout 和退出码。环境中没有 lli 时跳过。
+
+mod common;
+
+use cavvy::{Compiler, CompilerOptions};
+use common::{tool_available, write_sources};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tempfile::TempDir;
+
+/// 两条路径输出必须一致的示例
+const CORPUS: &[&str] = &[
+    "hello",
+    "billion",
+    "multiplication_formatted",
+    "test_abstract",
+    "test_access_control",
+    "test_algorithm_sorting",
+    "test_array_2d",
+    "test_array_jagged",
+    "test_array_init_inline",
+    "test_bitwise_operators",
+    "test_break_continue",
+    "test_calloc_integration",
+    "test_cast_int_to_char",
+    "test_checked_arithmetic",
+    "test_constructor",
+    "test_devirtualization",
+    "test_escape_sequences",
+    "test_ffi_math",
+    "test_format_print",
+    "test_game_of_life",
+    "test_include_nested",
+    "test_inline_ir_memory",
+    "test_instanceof",
+    "test_interface_default",
+    "test_labeled_break_continue",
+    "test_memoization",
+    "test_method_overload_types",
+    "test_method_ref_and_lambda",
+    "test_multidim_advanced",
+    "test_overload",
+    "test_preprocessor_ifdef",
+    "test_static_fields",
+    "test_string_methods",
+    "test_super_method",
+    "test_switch_fallthrough",
+    "test_type_casting_comprehensive",
+    "test_varargs_mixed",
+];
+
+/// 旧代码生成器输出错误、IrBuilder 流水线已修正的示例：(示例, IrBuilder 输出应包含的内容)
+const LEGACY_DIVERGENT: &[(&str, &str)] = &[
+    // 复合赋值在旧代码生成器中丢失左操作数
+    ("test_assign_add", "a after += 5: 15"),
+    // `{}` 占位符的整数参数
+    ("test_format_simple_braces", "Name: Alice, Age: 25"),
+    // 整数转字符串截断 i64
+    ("test_shift", "1 << 16 = 65536"),
+    // char 到 String 的强制转换
+    ("test_type_system_rules", "(string)'A' = A"),
+    // 复合赋值
+    ("test_nested_expressions", "a += 5 * 2; a = 20"),
+];
+
+/// 编译示例并用 lli 运行，返回 (退出码, stdout)
+fn compile_and_run(name: &str, legacy: bool) -> Result<(Option<i32>, String), String> {
+    let source = Path::new("examples").join(format!("{}.cay", name));
+    let dir = TempDir::new().map_err(|e| e.to_string())?;
+    let ir_path = dir.path().join(format!("{}.ll", name));
+    let compiler = Compiler::with_options(CompilerOptions {
+        legacy_codegen: legacy,
+        ..CompilerOptions::default()
+    });
+    compiler.compile_file(source.to_str().unwrap(), ir_path.to_str().unwrap())
+        .map_err(|e| format!("{} ({}) 编译失败: {:?}", name, if legacy { "legacy" } else { "ir" }, e))?;
+
+    let output = Command::new("lli")
+        .arg(&ir_path)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("无法运行 lli: {}", e))?;
+    Ok((output.status.code(), String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+#[test]
+fn test_ir_pipeline_matches_legacy_output() {
+    if !tool_available("lli") {
+        eprintln!("跳过: 未找到 lli");
+        return;
+    }
+    let mut mismatches = Vec::new();
+    for name in CORPUS {
+        let legacy = compile_and_run(name, true).unwrap();
+        let new = compile_and_run(name, false).unwrap();
+        if legacy != new {
+            mismatches.push(format!(
+                "{}:\n--- legacy (exit {:?})\n{}\n--- ir (exit {:?})\n{}",
+                name, legacy.0, legacy.1, new.0, new.1
+            ));
+        }
+    }
+    assert!(mismatches.is_empty(), "运行时输出不一致:\n{}", mismatches.join("\n"));
+}
+
+#[test]
+fn test_ir_pipeline_fixes_legacy_divergences() {
+    if !tool_available("lli") {
+        eprintln!("跳过: 未找到 lli");
+        return;
+    }
+    for (name, expected) in LEGACY_DIVERGENT {
+        let (code, stdout) = compile_and_run(name, false).unwrap();
+        assert_eq!(code, Some(0), "{} 应正常退出, 输出:\n{}", name, stdout);
+        assert!(stdout.contains(expected), "{} 的输出应包含 '{}', 实际:\n{}", name, expected, stdout);
+    }
+}
+
+#[test]
+fn test_stack_allocated_objects_run_destructors() {
+    if !tool_available("lli") {
+        eprintln!("跳过: 未找到 lli");
+        return;
+    }
+    // 不逃逸的对象在离开块（包括 continue、break 和 return）时析构，逃逸的对象不析构
+    let (code, stdout) = compile_and_run("test_escape_analysis", false).unwrap();
+    assert_eq!(code, Some(0), "输出:\n{}", stdout);
+    let expected = [
+        "4", "drop inner", "after block",
+        "0", "drop step", "drop step", "2", "drop step",
+        "drop loop",
+        "drop compute", "6", "drop compute", "1",
+        "1",
+    ];
+    assert_eq!(stdout.lines().collect::<Vec<_>>(), expected);
+}
+
+const UNIT_SHAPES: &str = r#"public interface Shape {
+    int area();
+    default int twice() {
+        return area() * 2;
+    }
+}
+public class Rect implements Shape {
+    private int w;
+    private int h;
+    public static int made = 0;
+    public static String label = "rect";
+    public Rect(int w, int h) {
+        this.w = w;
+        this.h = h;
+        made = made + 1;
+    }
+    public int area() {
+        return w * h;
+    }
+}
+"#;
+
+const UNIT_MAIN: &str = r#"#include "shapes.cay"
+public class Square extends Rect {
+    public Square(int s) {
+        super(s, s);
+    }
+    public int area() {
+        return super.area() + 1;
+    }
+}
+public class Main {
+    public static void main() {
+        Shape a = new Rect(2, 3);
+        Shape b = new Square(4);
+        println(a.area());
+        println(b.twice());
+        println(Rect.made);
+        println(Rect.label);
+        if (b instanceof Rect) {
+            println("square is rect");
+        }
+        if (!(a instanceof Square)) {
+            println("rect is not square");
+        }
+    }
+}
+"#;
+
+#[test]
+fn test_separately_compiled_units_link_and_run() {
+    if !tool_available("lli") || cavvy::unit::find_llvm_link().is_none() {
+        eprintln!("跳过: 未找到 lli 或 llvm-link");
+        return;
+    }
+    let dir = TempDir::new().unwrap();
+    let sources = write_sources(dir.path(), &[("shapes.cay", UNIT_SHAPES), ("main.cay", UNIT_MAIN)]);
+
+    // 被依赖的单元先编译，生成接口文件；依赖它的单元只读取接口文件
+    let compiler = Compiler::new();
+    let mut units = Vec::new();
+    for source in &sources {
+        let ir_path = Path::new(source).with_extension("ll");
+        let interface = compiler.compile_unit_file(source, ir_path.to_str().unwrap()).unwrap();
+        assert!(interface.exists());
+        units.push(ir_path);
+    }
+    let linked = dir.path().join("linked.ll");
+    cavvy::unit::link_units(&units, &linked).unwrap();
+
+    let output = Command::new("lli").arg(&linked).stdin(Stdio::null()).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(0), "输出:\n{}", stdout);
+    let expected = ["6", "34", "2", "rect", "square is rect", "rect is not square"];
+    assert_eq!(stdout.lines().collect::<Vec<_>>(), expected);
+}
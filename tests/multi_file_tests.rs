@@ -0,0 +1,153 @@
+//! 多文件编译测试
+//!
+//! 多个源文件合并为一个程序（`Compiler::compile_files`），package/import 控制文件之间的可见性，
+//! 跨文件的重复定义报告两处位置。运行生成的 IR 需要 lli，环境中没有 lli 时只检查编译结果。
+
+mod common;
+
+use cavvy::Compiler;
+use common::{tool_available, write_sources};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tempfile::TempDir;
+
+/// 编译多个源文件，返回错误信息
+fn compile_expect_error(files: &[(&str, &str)]) -> String {
+    let dir = TempDir::new().unwrap();
+    let sources = write_sources(dir.path(), files);
+    let ir_path = dir.path().join("out.ll");
+    match Compiler::new().compile_files(&sources, ir_path.to_str().unwrap()) {
+        Ok(()) => panic!("编译应失败"),
+        Err(e) => e.to_string(),
+    }
+}
+
+const UTIL: &str = "package util;
+
+public class MathUtil {
+    public static int square(int x) {
+        return x * x;
+    }
+}
+";
+
+const HIDDEN: &str = "package util;
+
+class Hidden {
+    public static int value() {
+        return 1;
+    }
+}
+";
+
+#[test]
+fn test_multi_file_example_runs() {
+    let dir = TempDir::new().unwrap();
+    let example = Path::new("examples/multi_file");
+    let sources: Vec<String> = cavvy::source_files(example).unwrap().iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    assert_eq!(sources.len(), 3);
+    let ir_path = dir.path().join("multi_file.ll");
+    Compiler::new().compile_files(&sources, ir_path.to_str().unwrap())
+        .unwrap_or_else(|e| panic!("多文件示例编译失败: {}", e));
+
+    if !tool_available("lli") {
+        eprintln!("跳过运行: 未找到 lli");
+        return;
+    }
+    let output = Command::new("lli").arg(&ir_path).stdin(Stdio::null()).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "输出:\n{}", stdout);
+    for expected in ["distance squared: 25", "square area: 25", "origin: origin"] {
+        assert!(stdout.contains(expected), "输出应包含 '{}', 实际:\n{}", expected, stdout);
+    }
+}
+
+#[test]
+fn test_wildcard_import() {
+    let dir = TempDir::new().unwrap();
+    let sources = write_sources(dir.path(), &[
+        ("main.cay", "import util.*;
+
+public class Main {
+    public static void main() {
+        println(MathUtil.square(6));
+    }
+}
+"),
+        ("util/math.cay", UTIL),
+    ]);
+    let ir_path = dir.path().join("out.ll");
+    Compiler::new().compile_files(&sources, ir_path.to_str().unwrap())
+        .unwrap_or_else(|e| panic!("编译失败: {}", e));
+}
+
+#[test]
+fn test_missing_import_error() {
+    let error = compile_expect_error(&[
+        ("main.cay", "public class Main {
+    public static void main() {
+        println(MathUtil.square(6));
+    }
+}
+"),
+        ("util/math.cay", UTIL),
+    ]);
+    assert!(error.contains("Class 'MathUtil' in package 'util' is not imported; add 'import util.MathUtil;'"), "{}", error);
+    assert!(error.contains("main.cay:3:"), "错误应指向使用处: {}", error);
+}
+
+#[test]
+fn test_non_public_class_error() {
+    let error = compile_expect_error(&[
+        ("main.cay", "package app;
+
+import util.*;
+
+public class Main {
+    public static void main() {
+        println(Hidden.value());
+    }
+}
+"),
+        ("util/hidden.cay", HIDDEN),
+    ]);
+    assert!(error.contains("Class 'Hidden' is not public in package 'util' and cannot be accessed from package 'app'"), "{}", error);
+}
+
+#[test]
+fn test_unknown_import_error() {
+    let error = compile_expect_error(&[
+        ("main.cay", "import util.Missing;
+
+public class Main {
+    public static void main() {
+    }
+}
+"),
+        ("util/math.cay", UTIL),
+    ]);
+    assert!(error.contains("Import 'util.Missing' does not match any class, interface or function in package 'util'"), "{}", error);
+}
+
+#[test]
+fn test_cross_file_duplicate_class_error() {
+    let error = compile_expect_error(&[
+        ("main.cay", "public class Main {
+    public static void main() {
+    }
+}
+
+class Shared {
+}
+"),
+        ("other.cay", "// 另一个文件
+
+class Shared {
+}
+"),
+    ]);
+    assert!(error.contains("Class 'Shared' is defined in both"), "{}", error);
+    assert!(error.contains("main.cay:6") && error.contains("other.cay:3"), "应报告两处定义的位置: {}", error);
+}